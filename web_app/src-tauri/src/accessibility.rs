@@ -0,0 +1,166 @@
+// Queen Mama LITE - Accessibility Preferences
+// Reads the OS reduced motion, increased contrast and reduced transparency settings and reports changes
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{App, AppHandle, Manager, State};
+
+use crate::events::VersionedEmitter;
+use crate::window;
+
+/// The OS doesn't notify us, so the settings are re-read on this interval
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessibilityPrefs {
+    pub reduced_motion: bool,
+    pub increased_contrast: bool,
+    pub reduced_transparency: bool,
+}
+
+#[derive(Default)]
+pub struct AccessibilityState(Mutex<AccessibilityPrefs>);
+
+/// Runs before window setup so the overlay is built with the right effects
+pub fn setup_accessibility(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let prefs = detect();
+    app.manage(AccessibilityState(Mutex::new(prefs)));
+
+    let app_handle = app.app_handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        refresh(&app_handle);
+    });
+
+    tracing::info!(
+        reduced_motion = prefs.reduced_motion,
+        increased_contrast = prefs.increased_contrast,
+        reduced_transparency = prefs.reduced_transparency,
+        "Accessibility preferences loaded"
+    );
+    Ok(())
+}
+
+/// Get the OS accessibility preferences the UI should honor
+#[tauri::command]
+pub fn get_accessibility_prefs(
+    state: State<'_, AccessibilityState>,
+) -> Result<AccessibilityPrefs, String> {
+    let prefs = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(*prefs)
+}
+
+/// Current preferences, or none set if setup hasn't run yet
+pub fn prefs(app: &AppHandle) -> AccessibilityPrefs {
+    app.try_state::<AccessibilityState>()
+        .and_then(|state| state.0.lock().ok().map(|prefs| *prefs))
+        .unwrap_or_default()
+}
+
+/// Re-read the preferences and tell the windows if anything changed
+fn refresh(app: &AppHandle) {
+    let prefs = detect();
+    let state = app.state::<AccessibilityState>();
+    let previous = match state.0.lock() {
+        Ok(mut current) => std::mem::replace(&mut *current, prefs),
+        Err(_) => return,
+    };
+    if previous == prefs {
+        return;
+    }
+
+    if previous.reduced_transparency != prefs.reduced_transparency {
+        window::apply_vibrancy(app);
+    }
+    if let Err(e) = app.emit("accessibility_prefs_changed", prefs) {
+        tracing::error!("Failed to emit preferences: {}", e);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> AccessibilityPrefs {
+    let enabled = |key: &str| {
+        read_command("defaults", &["read", "com.apple.universalaccess", key]).as_deref()
+            == Some("1")
+    };
+    AccessibilityPrefs {
+        reduced_motion: enabled("reduceMotion"),
+        increased_contrast: enabled("increaseContrast"),
+        reduced_transparency: enabled("reduceTransparency"),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn detect() -> AccessibilityPrefs {
+    AccessibilityPrefs {
+        // Turning off "Animation effects" clears MinAnimate
+        reduced_motion: read_registry(r"HKCU\Control Panel\Desktop\WindowMetrics", "MinAnimate")
+            .is_some_and(|value| value == "0"),
+        // HCF_HIGHCONTRASTON is the low bit of the flags
+        increased_contrast: read_registry(
+            r"HKCU\Control Panel\Accessibility\HighContrast",
+            "Flags",
+        )
+        .and_then(|value| value.parse::<u32>().ok())
+        .is_some_and(|flags| flags & 1 != 0),
+        reduced_transparency: read_registry(
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+            "EnableTransparency",
+        )
+        .is_some_and(|value| value == "0x0"),
+    }
+}
+
+/// The value column of `reg query` output for a single value
+#[cfg(target_os = "windows")]
+pub(crate) fn read_registry(key: &str, value: &str) -> Option<String> {
+    let output = read_command("reg", &["query", key, "/v", value])?;
+    output
+        .lines()
+        .find(|line| line.trim_start().starts_with(value))
+        .and_then(|line| line.split_whitespace().last())
+        .map(str::to_string)
+}
+
+/// GNOME settings; other desktops fall back to no preferences set
+#[cfg(target_os = "linux")]
+fn detect() -> AccessibilityPrefs {
+    let gsettings = |schema: &str, key: &str| read_command("gsettings", &["get", schema, key]);
+    AccessibilityPrefs {
+        reduced_motion: gsettings("org.gnome.desktop.interface", "enable-animations").as_deref()
+            == Some("false"),
+        increased_contrast: gsettings("org.gnome.desktop.a11y.interface", "high-contrast")
+            .as_deref()
+            == Some("true"),
+        // GNOME has no separate transparency switch
+        reduced_transparency: false,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn detect() -> AccessibilityPrefs {
+    AccessibilityPrefs::default()
+}
+
+/// Trimmed stdout of a command that succeeded
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+pub(crate) fn read_command(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+
+    // Polling shouldn't flash a console window
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
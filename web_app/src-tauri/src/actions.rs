@@ -0,0 +1,122 @@
+// Queen Mama LITE - Automation Actions
+// Exposes key actions through queenmama://action/<id> URLs so launchers
+// (Apple Shortcuts "Open URL", Raycast, Windows PowerToys) can drive the app
+
+use tauri::{AppHandle, Manager, Url};
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::shortcuts::dispatch_action;
+use crate::{highlights, session};
+
+/// URL scheme registered in tauri.conf.json
+pub const URL_SCHEME: &str = "queenmama";
+
+/// Actions that can be triggered from outside the app, described by their `action.<id>` string
+const ACTIONS: &[&str] = &[
+    "start_session",
+    "stop_session",
+    "toggle_session",
+    "flag_moment",
+    "trigger_assist",
+    "clear_context",
+    "toggle_overlay",
+    "show_overlay",
+    "hide_overlay",
+    "toggle_click_through",
+    "paste_response",
+    "type_response",
+    "open_dashboard",
+];
+
+/// Extract the action id from a queenmama://action/<id> URL
+pub fn action_from_url(url: &Url) -> Option<&str> {
+    if url.scheme() != URL_SCHEME || url.host_str() != Some("action") {
+        return None;
+    }
+
+    url.path_segments()?.next().filter(|id| !id.is_empty())
+}
+
+/// What an action does, for showing to the user
+pub fn description(id: &str) -> Option<&'static str> {
+    ACTIONS
+        .iter()
+        .find(|(action, _)| *action == id)
+        .map(|(_, description)| *description)
+}
+
+/// Run an action by id
+pub fn run(app: &AppHandle, id: &str) -> Result<(), String> {
+    match id {
+        "toggle_overlay"
+        | "trigger_assist"
+        | "toggle_session"
+        | "clear_context"
+        | "toggle_click_through"
+        | "paste_response"
+        | "type_response" => {
+            dispatch_action(app, id);
+        }
+        "start_session" => {
+            session::start(app)?;
+            app.emit("tray_action", id).map_err(|e| e.to_string())?;
+        }
+        "stop_session" => {
+            session::stop(app)?;
+            app.emit("tray_action", id).map_err(|e| e.to_string())?;
+        }
+        "flag_moment" => {
+            highlights::flag(app, None)?;
+        }
+        "show_overlay" => {
+            let overlay = app
+                .get_webview_window("overlay")
+                .ok_or_else(|| t!("error.overlay_missing"))?;
+            overlay.show().map_err(|e| e.to_string())?;
+            overlay.set_focus().map_err(|e| e.to_string())?;
+        }
+        "hide_overlay" => {
+            let overlay = app
+                .get_webview_window("overlay")
+                .ok_or_else(|| t!("error.overlay_missing"))?;
+            overlay.hide().map_err(|e| e.to_string())?;
+        }
+        "open_dashboard" => {
+            let main = app
+                .get_webview_window("main")
+                .ok_or_else(|| t!("error.main_window_missing"))?;
+            main.show().map_err(|e| e.to_string())?;
+            main.set_focus().map_err(|e| e.to_string())?;
+        }
+        _ => return Err(t!("error.unknown_action", action = id)),
+    }
+
+    Ok(())
+}
+
+/// List actions available to automation tools, with their URLs
+#[tauri::command]
+pub fn list_actions() -> Vec<ActionInfo> {
+    ACTIONS
+        .iter()
+        .map(|id| ActionInfo {
+            id: id.to_string(),
+            description: t!(&format!("action.{}", id)),
+            url: format!("{}://action/{}", URL_SCHEME, id),
+        })
+        .collect()
+}
+
+/// Run an action by id
+#[tauri::command]
+pub fn run_action(app: AppHandle, id: String) -> Result<(), String> {
+    run(&app, &id)
+}
+
+#[derive(serde::Serialize)]
+pub struct ActionInfo {
+    id: String,
+    description: String,
+    url: String,
+}
@@ -0,0 +1,586 @@
+// Queen Mama LITE - AI Providers
+// Calls OpenAI, Anthropic or a local Ollama from Rust so API keys never reach the webview
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::{App, AppHandle, Manager, State, Url};
+use tauri_plugin_http::reqwest::header::HeaderMap;
+use tauri_plugin_http::reqwest::{Client, RequestBuilder, StatusCode};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Semaphore;
+use tracing::Instrument;
+
+use crate::chaos::{self, Fault};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::mock::{self, MockFailure};
+use crate::notifications::{self, NoticeAction, NoticeKind};
+use crate::{demo, secrets, trace, SETTINGS_STORE};
+
+pub(crate) const AI_CONFIG_STORE_KEY: &str = "ai_provider";
+
+/// Where API keys were kept before they moved to the keychain
+const LEGACY_KEYS_STORE_KEY: &str = "ai_api_keys";
+
+/// Requests allowed in flight at once, across all windows
+const MAX_CONCURRENT_REQUESTS: usize = 2;
+
+/// Attempts for rate-limited or failed requests, before any token has streamed
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+const MAX_OUTPUT_TOKENS: u32 = 1024;
+
+/// How long a mock reply hangs before it times out
+const MOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Warn when less than this share of the provider's request quota is left
+const QUOTA_WARNING_RATIO: f64 = 0.1;
+
+/// Remaining/limit header pairs sent by providers that report rate limits
+const QUOTA_HEADERS: &[(&str, &str)] = &[
+    ("x-ratelimit-remaining-requests", "x-ratelimit-limit-requests"),
+    ("anthropic-ratelimit-requests-remaining", "anthropic-ratelimit-requests-limit"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderKind {
+    OpenAi,
+    Anthropic,
+    Ollama,
+    /// Canned replies with set latency and failures, for trying the app without a key
+    Mock,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiConfig {
+    provider: ProviderKind,
+    model: String,
+    /// Override the API endpoint, e.g. a remote Ollama or an OpenAI-compatible server
+    base_url: Option<String>,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::OpenAi,
+            model: "gpt-4o-mini".to_string(),
+            base_url: None,
+        }
+    }
+}
+
+impl AiConfig {
+    /// A model is named, and a custom endpoint is an http(s) address
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.model.trim().is_empty() {
+            return Err(t!("error.settings_model_missing"));
+        }
+        if let Some(base_url) = &self.base_url {
+            let valid =
+                Url::parse(base_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !valid {
+                return Err(t!("error.settings_invalid_url", url = base_url));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Prompt {
+    system: Option<String>,
+    prompt: String,
+    /// Use this model instead of the configured one, with the configured provider
+    #[serde(default)]
+    model: Option<String>,
+}
+
+impl Prompt {
+    pub fn new(system: Option<String>, prompt: String) -> Self {
+        Self {
+            system,
+            prompt,
+            model: None,
+        }
+    }
+
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model.filter(|model| !model.trim().is_empty());
+        self
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+}
+
+pub struct AiState {
+    client: Client,
+    permits: Semaphore,
+}
+
+/// A chat API that streams its reply as lines of text
+trait Provider: Send + Sync {
+    fn request(&self, client: &Client, prompt: &Prompt) -> RequestBuilder;
+
+    /// Text carried by one line of the streamed response, if any
+    fn parse_line(&self, line: &str) -> Option<String>;
+}
+
+struct OpenAi {
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl Provider for OpenAi {
+    fn request(&self, client: &Client, prompt: &Prompt) -> RequestBuilder {
+        let mut messages = Vec::new();
+        if let Some(system) = &prompt.system {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": prompt.prompt }));
+
+        client
+            .post(format!("{}/v1/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .header("content-type", "application/json")
+            .body(
+                json!({
+                    "model": self.model,
+                    "stream": true,
+                    "max_tokens": MAX_OUTPUT_TOKENS,
+                    "messages": messages,
+                })
+                .to_string(),
+            )
+    }
+
+    fn parse_line(&self, line: &str) -> Option<String> {
+        let data = line.strip_prefix("data:")?.trim();
+        let event: Value = serde_json::from_str(data).ok()?;
+        event["choices"][0]["delta"]["content"]
+            .as_str()
+            .map(str::to_string)
+    }
+}
+
+struct Anthropic {
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl Provider for Anthropic {
+    fn request(&self, client: &Client, prompt: &Prompt) -> RequestBuilder {
+        let mut body = json!({
+            "model": self.model,
+            "stream": true,
+            "max_tokens": MAX_OUTPUT_TOKENS,
+            "messages": [{ "role": "user", "content": prompt.prompt }],
+        });
+        if let Some(system) = &prompt.system {
+            body["system"] = json!(system);
+        }
+
+        client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .body(body.to_string())
+    }
+
+    fn parse_line(&self, line: &str) -> Option<String> {
+        let data = line.strip_prefix("data:")?.trim();
+        let event: Value = serde_json::from_str(data).ok()?;
+        if event["type"] != "content_block_delta" {
+            return None;
+        }
+        event["delta"]["text"].as_str().map(str::to_string)
+    }
+}
+
+struct Ollama {
+    base_url: String,
+    model: String,
+}
+
+impl Provider for Ollama {
+    fn request(&self, client: &Client, prompt: &Prompt) -> RequestBuilder {
+        let mut messages = Vec::new();
+        if let Some(system) = &prompt.system {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": prompt.prompt }));
+
+        client
+            .post(format!("{}/api/chat", self.base_url))
+            .header("content-type", "application/json")
+            .body(
+                json!({
+                    "model": self.model,
+                    "stream": true,
+                    "messages": messages,
+                })
+                .to_string(),
+            )
+    }
+
+    // Newline-delimited JSON rather than SSE
+    fn parse_line(&self, line: &str) -> Option<String> {
+        let event: Value = serde_json::from_str(line).ok()?;
+        event["message"]["content"].as_str().map(str::to_string)
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AiToken<'a> {
+    request_id: &'a str,
+    token: &'a str,
+}
+
+pub fn setup_ai(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    if let Err(e) = migrate_legacy_keys(app.app_handle()) {
+        tracing::error!(
+            code = "secrets.keychain_unavailable",
+            "Failed to move API keys to the keychain: {}",
+            e
+        );
+    }
+
+    app.manage(AiState {
+        client: Client::new(),
+        permits: Semaphore::new(MAX_CONCURRENT_REQUESTS),
+    });
+    Ok(())
+}
+
+/// Get the selected provider and model
+#[tauri::command]
+pub fn get_ai_config(app: AppHandle) -> AiConfig {
+    load_config(&app)
+}
+
+/// Select the provider and model used for prompts
+#[tauri::command]
+pub fn set_ai_config(app: AppHandle, config: AiConfig) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        AI_CONFIG_STORE_KEY,
+        serde_json::to_value(&config).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Save (or clear with `null`) the API key for a provider; keys are never sent back to the webview
+#[tauri::command]
+pub fn set_ai_api_key(
+    app: AppHandle,
+    provider: ProviderKind,
+    api_key: Option<String>,
+) -> Result<(), String> {
+    match api_key {
+        Some(api_key) => secrets::set(&app, &key_secret(provider), &api_key),
+        None => secrets::delete(&app, &key_secret(provider)),
+    }
+}
+
+/// Send a prompt and wait for the whole reply
+#[tauri::command]
+pub async fn send_prompt(
+    app: AppHandle,
+    state: State<'_, AiState>,
+    prompt: Prompt,
+) -> Result<String, String> {
+    complete(&app, &state, &prompt, |_| {}).await
+}
+
+/// Send a prompt and stream the reply as `ai_token` events, ending with `ai_done` or `ai_error`.
+/// Returns the request id carried by those events.
+#[tauri::command]
+pub fn stream_prompt(app: AppHandle, prompt: Prompt) -> String {
+    // Reuse the command's request id so `get_trace` shows the whole reply
+    let request_id = trace::current_request_id().unwrap_or_else(trace::new_request_id);
+
+    let id = request_id.clone();
+    let reply = async move {
+        let state = app.state::<AiState>();
+        let result = complete(&app, &state, &prompt, |token| {
+            let _ = app.emit(
+                "ai_token",
+                AiToken {
+                    request_id: &id,
+                    token,
+                },
+            );
+        })
+        .await;
+
+        match result {
+            Ok(text) => {
+                tracing::debug!("Reply finished, {} characters", text.len());
+                let _ = app.emit("ai_done", json!({ "requestId": id, "text": text }));
+            }
+            Err(e) => {
+                tracing::warn!("{}", e);
+                let _ = app.emit("ai_error", json!({ "requestId": id, "error": e }));
+            }
+        }
+    };
+    tauri::async_runtime::spawn(reply.instrument(tracing::Span::current()));
+
+    request_id
+}
+
+/// Run a prompt through the configured provider, calling `on_token` as text streams in
+pub async fn complete(
+    app: &AppHandle,
+    state: &AiState,
+    prompt: &Prompt,
+    mut on_token: impl FnMut(&str),
+) -> Result<String, String> {
+    if demo::is_enabled(app) {
+        return Ok(demo::reply(&prompt.prompt, on_token).await);
+    }
+
+    if chaos::take(app, Fault::AiRateLimit) {
+        check_quota(app, StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new());
+        return Err(t!(
+            "error.ai_request_failed",
+            status = StatusCode::TOO_MANY_REQUESTS,
+            body = t!("error.chaos_injected")
+        ));
+    }
+
+    let Some(provider) = provider(app, prompt.model())? else {
+        return complete_mock(app, prompt, on_token).await;
+    };
+    tracing::debug!("Sending prompt");
+    let _permit = state.permits.acquire().await.map_err(|e| e.to_string())?;
+
+    let mut attempt = 1;
+    let mut response = loop {
+        let response = provider
+            .request(&state.client, prompt)
+            .send()
+            .await
+            .map_err(|e| e.to_string());
+
+        let retryable = match &response {
+            Ok(response) => {
+                response.status() == StatusCode::TOO_MANY_REQUESTS
+                    || response.status().is_server_error()
+            }
+            Err(_) => true,
+        };
+        if !retryable || attempt == MAX_ATTEMPTS {
+            break response?;
+        }
+
+        tracing::debug!("Attempt {} failed, retrying", attempt);
+        tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+        attempt += 1;
+    };
+
+    check_quota(app, response.status(), response.headers());
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(t!("error.ai_request_failed", status = status, body = body));
+    }
+
+    let mut text = String::new();
+    let mut pending = String::new();
+    while let Some(bytes) = response.chunk().await.map_err(|e| e.to_string())? {
+        pending.push_str(&String::from_utf8_lossy(&bytes));
+
+        while let Some(newline) = pending.find('\n') {
+            let line: String = pending.drain(..=newline).collect();
+            if let Some(token) = provider.parse_line(line.trim()) {
+                on_token(&token);
+                text.push_str(&token);
+            }
+        }
+    }
+    if let Some(token) = provider.parse_line(pending.trim()) {
+        on_token(&token);
+        text.push_str(&token);
+    }
+
+    Ok(text)
+}
+
+/// Reply from the mock provider, going through its latency and any failure it should inject
+async fn complete_mock(
+    app: &AppHandle,
+    prompt: &Prompt,
+    on_token: impl FnMut(&str),
+) -> Result<String, String> {
+    let config = mock::load_config(app);
+    let reply = config.reply_to(&prompt.prompt);
+    tokio::time::sleep(config.latency()).await;
+
+    match config.roll_failure() {
+        None | Some(MockFailure::None) => {
+            Ok(demo::stream_words(&reply, config.token_delay(), on_token).await)
+        }
+        Some(MockFailure::Error) => Err(t!(
+            "error.ai_request_failed",
+            status = StatusCode::INTERNAL_SERVER_ERROR,
+            body = t!("error.mock_failure")
+        )),
+        Some(MockFailure::RateLimited) => {
+            check_quota(app, StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new());
+            Err(t!(
+                "error.ai_request_failed",
+                status = StatusCode::TOO_MANY_REQUESTS,
+                body = t!("error.mock_failure")
+            ))
+        }
+        Some(MockFailure::Timeout) => {
+            tokio::time::sleep(MOCK_TIMEOUT).await;
+            Err(t!("error.mock_timeout"))
+        }
+        Some(MockFailure::Disconnect) => {
+            let words: Vec<&str> = reply.split_inclusive(' ').collect();
+            let half = words[..words.len() / 2].concat();
+            demo::stream_words(&half, config.token_delay(), on_token).await;
+            Err(t!("error.mock_disconnected"))
+        }
+    }
+}
+
+/// Warn the user when the provider says the request quota is nearly used up
+fn check_quota(app: &AppHandle, status: StatusCode, headers: &HeaderMap) {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<f64>().ok())
+    };
+    let near_limit = status == StatusCode::TOO_MANY_REQUESTS
+        || QUOTA_HEADERS.iter().any(|(remaining, limit)| {
+            match (header(remaining), header(limit)) {
+                (Some(remaining), Some(limit)) if limit > 0.0 => {
+                    remaining / limit < QUOTA_WARNING_RATIO
+                }
+                _ => false,
+            }
+        });
+
+    if near_limit {
+        notifications::notify(
+            app,
+            NoticeKind::QuotaNearLimit,
+            &t!("notice.quota.title"),
+            &t!("notice.quota.body"),
+            vec![(t!("action.open_settings"), NoticeAction::OpenSettings)],
+        );
+    }
+}
+
+/// Whether the selected provider needs an API key that hasn't been saved
+pub fn missing_api_key(app: &AppHandle) -> bool {
+    let provider = load_config(app).provider;
+    !matches!(provider, ProviderKind::Ollama | ProviderKind::Mock)
+        && !matches!(secrets::get(app, &key_secret(provider)), Ok(Some(_)))
+}
+
+/// The HTTP provider to call, or `None` for the mock provider
+/// Name of the model a prompt overriding it with `model` would run on
+pub(crate) fn model_name(app: &AppHandle, model: Option<&str>) -> String {
+    if demo::is_enabled(app) {
+        return "demo".to_string();
+    }
+    let config = load_config(app);
+    match (config.provider, model) {
+        (ProviderKind::Mock, _) => "mock".to_string(),
+        (_, Some(model)) => model.to_string(),
+        (_, None) => config.model,
+    }
+}
+
+fn provider(app: &AppHandle, model: Option<&str>) -> Result<Option<Box<dyn Provider>>, String> {
+    let mut config = load_config(app);
+    if let Some(model) = model {
+        config.model = model.to_string();
+    }
+    let api_key = || {
+        secrets::get(app, &key_secret(config.provider))?
+            .ok_or_else(|| t!("error.ai_no_api_key"))
+    };
+
+    Ok(Some(match config.provider {
+        ProviderKind::OpenAi => Box::new(OpenAi {
+            base_url: base_url(&config, "https://api.openai.com"),
+            api_key: api_key()?,
+            model: config.model.clone(),
+        }),
+        ProviderKind::Anthropic => Box::new(Anthropic {
+            base_url: base_url(&config, "https://api.anthropic.com"),
+            api_key: api_key()?,
+            model: config.model.clone(),
+        }),
+        ProviderKind::Ollama => Box::new(Ollama {
+            base_url: base_url(&config, "http://localhost:11434"),
+            model: config.model.clone(),
+        }),
+        ProviderKind::Mock => return Ok(None),
+    }))
+}
+
+fn base_url(config: &AiConfig, default: &str) -> String {
+    config
+        .base_url
+        .as_deref()
+        .unwrap_or(default)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn load_config(app: &AppHandle) -> AiConfig {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(AI_CONFIG_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Keychain entry holding a provider's API key
+fn key_secret(provider: ProviderKind) -> String {
+    let name = match provider {
+        ProviderKind::OpenAi => "openai",
+        ProviderKind::Anthropic => "anthropic",
+        ProviderKind::Ollama => "ollama",
+        ProviderKind::Mock => "mock",
+    };
+    format!("{}ai_api_key.{}", secrets::INTERNAL_PREFIX, name)
+}
+
+/// Move plaintext keys from the settings store into the keychain, once
+fn migrate_legacy_keys(app: &AppHandle) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    let Some(value) = store.get(LEGACY_KEYS_STORE_KEY) else {
+        return Ok(());
+    };
+
+    let keys: HashMap<ProviderKind, String> =
+        serde_json::from_value(value).map_err(|e| e.to_string())?;
+    for (provider, api_key) in keys {
+        secrets::set(app, &key_secret(provider), &api_key)?;
+    }
+
+    store.delete(LEGACY_KEYS_STORE_KEY);
+    store.save().map_err(|e| e.to_string())?;
+
+    tracing::info!("Moved API keys to the keychain");
+    Ok(())
+}
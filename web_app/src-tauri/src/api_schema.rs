@@ -0,0 +1,190 @@
+// Queen Mama LITE - API Schema
+// OpenAPI spec and WebSocket event catalog for the companion relay, generated from the Rust types
+
+use schemars::generate::SchemaSettings;
+use schemars::SchemaGenerator;
+use serde_json::json;
+
+use crate::events;
+use crate::nudge::HapticNudge;
+use crate::permissions::ClientCredentials;
+use crate::relay::{self, RelayCommand, VersionReply};
+
+/// Served by the relay over plain HTTP, next to the WebSocket
+pub const OPENAPI_PATH: &str = "/openapi.json";
+pub const ASYNCAPI_PATH: &str = "/asyncapi.json";
+
+const TITLE: &str = "Queen Mama LITE companion relay";
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiSchema {
+    openapi: serde_json::Value,
+    asyncapi: serde_json::Value,
+}
+
+/// Get the relay's OpenAPI spec and WebSocket event catalog, e.g. to export for client generation
+#[tauri::command]
+pub fn get_api_schema() -> ApiSchema {
+    ApiSchema {
+        openapi: openapi(),
+        asyncapi: asyncapi(),
+    }
+}
+
+/// The HTTP side: the WebSocket handshake and these documents
+pub fn openapi() -> serde_json::Value {
+    let mut generator = generator();
+    // Referenced from the event catalog, listed here so one set of generated types covers both
+    generator.subschema_for::<RelayCommand>();
+    generator.subschema_for::<HapticNudge>();
+    generator.subschema_for::<VersionReply>();
+    generator.subschema_for::<ClientCredentials>();
+
+    let token = json!({
+        "name": "token",
+        "in": "query",
+        "required": true,
+        "description": "Pairing token from the QR code",
+        "schema": { "type": "string" },
+    });
+    let document = |description: &str| {
+        json!({
+            "summary": description,
+            "parameters": [token],
+            "responses": {
+                "200": {
+                    "description": description,
+                    "content": { "application/json": { "schema": { "type": "object" } } },
+                },
+                "401": { "description": "The pairing token is missing or wrong" },
+            },
+        })
+    };
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": TITLE, "version": env!("CARGO_PKG_VERSION") },
+        "paths": {
+            "/": {
+                "get": {
+                    "summary": "Open the WebSocket",
+                    "description": format!(
+                        "Upgrades to a WebSocket carrying the messages described in {}",
+                        ASYNCAPI_PATH
+                    ),
+                    "parameters": [
+                        token,
+                        {
+                            "name": "client",
+                            "in": "query",
+                            "description": "From `client_credentials`; without it new ones are sent",
+                            "schema": { "type": "string" },
+                        },
+                        {
+                            "name": "secret",
+                            "in": "query",
+                            "description": "From `client_credentials`, proving `client` is yours",
+                            "schema": { "type": "string" },
+                        },
+                        {
+                            "name": "name",
+                            "in": "query",
+                            "description": "Shown when the user is asked to allow an action",
+                            "schema": { "type": "string" },
+                        },
+                    ],
+                    "responses": {
+                        "101": { "description": "Switched to the WebSocket" },
+                        "401": { "description": "The pairing token is missing or wrong" },
+                    },
+                },
+            },
+            OPENAPI_PATH: { "get": document("This document") },
+            ASYNCAPI_PATH: { "get": document("The WebSocket event catalog") },
+        },
+        "components": { "schemas": generator.take_definitions(true) },
+    })
+}
+
+/// The WebSocket side: what the relay sends and what it accepts
+pub fn asyncapi() -> serde_json::Value {
+    let mut generator = generator();
+    // `version` and `emittedAt` are only sent from version 2, after `negotiate_version`
+    let envelope = |kind: serde_json::Value, payload: serde_json::Value| {
+        json!({
+            "type": "object",
+            "required": ["type", "payload"],
+            "properties": {
+                "version": { "type": "integer", "enum": events::EVENT_VERSIONS },
+                "type": kind,
+                "emittedAt": { "type": "integer", "description": "Unix milliseconds" },
+                "payload": payload,
+            },
+        })
+    };
+    let message = |kind: &str, summary: &str, payload: serde_json::Value| {
+        json!({
+            "name": kind,
+            "summary": summary,
+            "payload": envelope(json!({ "type": "string", "enum": [kind] }), payload),
+        })
+    };
+    let outgoing = [
+        message(
+            "haptic",
+            "Buzz the phone or watch for a nudge rule",
+            json!(generator.subschema_for::<HapticNudge>()),
+        ),
+        message(
+            "client_credentials",
+            "Sent on connecting without valid ones; pass them back to be remembered",
+            json!(generator.subschema_for::<ClientCredentials>()),
+        ),
+        message(
+            "version",
+            "The message version in use, answering `negotiate_version`",
+            json!(generator.subschema_for::<VersionReply>()),
+        ),
+        // Published by the frontend through `relay_publish`, so the relay doesn't know the shape
+        json!({
+            "name": "other",
+            "summary": "Anything else the app forwards, e.g. suggestions and transcript segments",
+            "payload": envelope(json!({ "type": "string" }), json!({})),
+        }),
+    ];
+    let incoming = generator.subschema_for::<RelayCommand>();
+
+    json!({
+        "asyncapi": "2.6.0",
+        "info": { "title": TITLE, "version": env!("CARGO_PKG_VERSION") },
+        "servers": {
+            "relay": {
+                "url": "{host}:{port}",
+                "protocol": "ws",
+                "description": "Pair with the QR code to get the address and token",
+                "variables": {
+                    "host": { "default": "localhost" },
+                    "port": { "default": relay::DEFAULT_RELAY_PORT.to_string() },
+                },
+            },
+        },
+        "channels": {
+            "/": {
+                "subscribe": {
+                    "summary": "Messages the relay sends to paired phones",
+                    "message": { "oneOf": outgoing },
+                },
+                "publish": {
+                    "summary": "Commands a paired phone can send",
+                    "message": { "name": "command", "payload": incoming },
+                },
+            },
+        },
+        "components": { "schemas": generator.take_definitions(true) },
+    })
+}
+
+/// Definitions go under `components/schemas`, where both documents expect them
+fn generator() -> SchemaGenerator {
+    SchemaSettings::openapi3().into_generator()
+}
@@ -0,0 +1,170 @@
+// Queen Mama LITE - Overlay Attach Mode
+// Docks the overlay to another app's window (e.g. beside Zoom) and follows it as it moves
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use tauri::{App, AppHandle, Manager, State};
+use xcap::Window;
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+
+/// How often the target window's bounds are polled
+const TRACK_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Gap between the overlay and the window it's attached to
+const ATTACH_GAP: i32 = 8;
+
+#[derive(Default)]
+pub struct AttachState {
+    target: Mutex<Option<Attachment>>,
+    /// Bumped on every attach/detach so the previous tracker stops
+    generation: AtomicU64,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    window_id: u32,
+    edge: AttachEdge,
+}
+
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AttachEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// Window bounds as reported by xcap: points on macOS, physical pixels elsewhere
+#[derive(Clone, Copy, PartialEq)]
+struct Bounds {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+pub fn setup_attach(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(AttachState::default());
+    Ok(())
+}
+
+/// Dock the overlay to a window (ids come from `list_capture_targets`)
+#[tauri::command]
+pub fn attach_overlay(
+    app: AppHandle,
+    state: State<'_, AttachState>,
+    window_id: u32,
+    edge: AttachEdge,
+) -> Result<(), String> {
+    window_bounds(window_id).ok_or_else(|| t!("error.window_not_found", id = window_id))?;
+
+    let attachment = Attachment { window_id, edge };
+    *state.target.lock().map_err(|e| e.to_string())? = Some(attachment.clone());
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    thread::spawn(move || track(app, attachment, generation));
+    Ok(())
+}
+
+/// Stop following the attached window
+#[tauri::command]
+pub fn detach_overlay(state: State<'_, AttachState>) -> Result<(), String> {
+    *state.target.lock().map_err(|e| e.to_string())? = None;
+    state.generation.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Get the window the overlay is attached to, if any
+#[tauri::command]
+pub fn get_overlay_attachment(state: State<'_, AttachState>) -> Result<Option<Attachment>, String> {
+    Ok(state.target.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Whether the overlay is docked to a window
+pub fn is_attached(app: &AppHandle) -> bool {
+    app.try_state::<AttachState>()
+        .and_then(|state| state.target.lock().ok().map(|target| target.is_some()))
+        .unwrap_or(false)
+}
+
+/// Reposition the overlay whenever the target window moves or resizes
+fn track(app: AppHandle, attachment: Attachment, generation: u64) {
+    let mut last_bounds = None;
+
+    loop {
+        let state = app.state::<AttachState>();
+        if state.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+
+        let Some(bounds) = window_bounds(attachment.window_id) else {
+            // The window was closed
+            if let Ok(mut target) = state.target.lock() {
+                *target = None;
+            }
+            state.generation.fetch_add(1, Ordering::SeqCst);
+            let _ = app.emit("overlay_detached", attachment.window_id);
+            tracing::info!("Target window closed, detached overlay");
+            return;
+        };
+
+        if last_bounds != Some(bounds) {
+            last_bounds = Some(bounds);
+            if let Err(e) = dock(&app, &attachment, bounds) {
+                tracing::error!("Failed to move overlay: {}", e);
+            }
+        }
+
+        thread::sleep(TRACK_INTERVAL);
+    }
+}
+
+fn dock(app: &AppHandle, attachment: &Attachment, bounds: Bounds) -> Result<(), String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+
+    let size = overlay.outer_size().map_err(|e| e.to_string())?;
+    // Bring the overlay size into the same units as the window bounds
+    #[cfg(target_os = "macos")]
+    let size = size.to_logical::<f64>(overlay.scale_factor().map_err(|e| e.to_string())?);
+    let (width, height) = (size.width as i32, size.height as i32);
+
+    let (x, y) = match attachment.edge {
+        AttachEdge::Left => (bounds.x - width - ATTACH_GAP, bounds.y),
+        AttachEdge::Right => (bounds.x + bounds.width + ATTACH_GAP, bounds.y),
+        AttachEdge::Top => (bounds.x + bounds.width - width, bounds.y - height - ATTACH_GAP),
+        AttachEdge::Bottom => (
+            bounds.x + bounds.width - width,
+            bounds.y + bounds.height + ATTACH_GAP,
+        ),
+    };
+
+    #[cfg(target_os = "macos")]
+    let position = tauri::LogicalPosition::new(x, y);
+    #[cfg(not(target_os = "macos"))]
+    let position = tauri::PhysicalPosition::new(x, y);
+
+    overlay.set_position(position).map_err(|e| e.to_string())
+}
+
+fn window_bounds(window_id: u32) -> Option<Bounds> {
+    let window = Window::all()
+        .ok()?
+        .into_iter()
+        .find(|window| window.id().ok() == Some(window_id))?;
+
+    Some(Bounds {
+        x: window.x().ok()?,
+        y: window.y().ok()?,
+        width: window.width().ok()? as i32,
+        height: window.height().ok()? as i32,
+    })
+}
@@ -0,0 +1,1109 @@
+// Queen Mama LITE - Audio Capture
+// Records the microphone and system audio natively and streams PCM chunks to the frontend for transcription
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample};
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::broadcast;
+
+use crate::vault::{self, FileReader, FileWriter};
+use crate::{demo, SETTINGS_STORE};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+
+pub(crate) const AUDIO_DEVICES_STORE_KEY: &str = "audio_devices";
+
+/// Length of each PCM chunk emitted to the frontend
+const CHUNK_DURATION_MS: u32 = 100;
+
+/// Chunks a slow consumer can fall behind by before it starts losing audio (10s)
+const CHUNK_BACKLOG: usize = 100;
+
+/// Resolution of the waveform stored alongside each recording
+const PEAKS_PER_SECOND: u32 = 50;
+
+/// `audio_level` is emitted this many times a second per source
+const LEVELS_PER_SECOND: u32 = 20;
+
+/// Peaks this close to full scale are reported as clipping
+const CLIPPING_LEVEL: f32 = 0.99;
+
+/// A pre-armed microphone is only trusted for this long, since the default device can change
+const ARMED_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Virtual devices that route system output back as an input, where native loopback isn't available
+#[cfg(not(target_os = "windows"))]
+const LOOPBACK_DEVICE_HINTS: &[&str] = &["blackhole", "loopback", "soundflower", "monitor of"];
+
+/// Each source is captured on its own channel so the user (mic) and the other party (system) stay separate
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AudioSource {
+    Microphone,
+    System,
+}
+
+impl AudioSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AudioSource::Microphone => "microphone",
+            AudioSource::System => "system",
+        }
+    }
+
+    pub fn parse(source: &str) -> Option<Self> {
+        match source {
+            "microphone" => Some(AudioSource::Microphone),
+            "system" => Some(AudioSource::System),
+            _ => None,
+        }
+    }
+}
+
+/// Devices picked in settings over the system defaults
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AudioDevices {
+    /// Microphone name as listed by `list_audio_devices`; the default is used while it's unplugged
+    pub microphone: Option<String>,
+}
+
+pub struct AudioState {
+    captures: Mutex<HashMap<AudioSource, ActiveCapture>>,
+    /// Every chunk, for in-process consumers such as local transcription
+    chunks: broadcast::Sender<Arc<PcmChunk>>,
+    /// Id of the microphone recording started by push-to-talk, stopped when the key is released
+    push_to_talk: Mutex<Option<String>>,
+    /// Microphone looked up ahead of a likely session, and when
+    armed: Mutex<Option<(Instant, cpal::Device, cpal::SupportedStreamConfig)>>,
+}
+
+impl Default for AudioState {
+    fn default() -> Self {
+        Self {
+            captures: Mutex::default(),
+            chunks: broadcast::channel(CHUNK_BACKLOG).0,
+            push_to_talk: Mutex::default(),
+            armed: Mutex::default(),
+        }
+    }
+}
+
+/// Owned copy of a chunk, shared with in-process consumers
+pub struct PcmChunk {
+    pub recording_id: String,
+    pub source: AudioSource,
+    pub sample_rate: u32,
+    pub samples: Vec<i16>,
+}
+
+struct ActiveCapture {
+    recording: RecordingInfo,
+    controls: Arc<CaptureControls>,
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+/// Flags read by the capture thread for every batch of samples
+#[derive(Default)]
+struct CaptureControls {
+    /// Record silence instead of the input
+    muted: AtomicBool,
+    /// Drop samples entirely, leaving a gap-free recording of the unpaused parts
+    paused: AtomicBool,
+    /// Set when the device is unplugged, or by chaos mode to act as if it was
+    device_lost: AtomicBool,
+}
+
+/// Where a capture's audio comes from
+enum CaptureInput {
+    Device(cpal::Device, cpal::SupportedStreamConfig),
+    /// ScreenCaptureKit, for system audio without a loopback device
+    #[cfg(target_os = "macos")]
+    ScreenCapture,
+    /// Silence from a fake device, for demo mode
+    Demo,
+}
+
+/// Keeps a capture's audio coming until dropped
+enum CaptureStream {
+    Device(cpal::Stream),
+    #[cfg(target_os = "macos")]
+    ScreenCapture(crate::system_audio::SystemAudioStream),
+}
+
+/// A recording in progress (or just finished), saved as mono 16-bit WAV
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingInfo {
+    pub id: String,
+    pub source: AudioSource,
+    pub device: String,
+    pub sample_rate: u32,
+    pub path: PathBuf,
+    /// Unix ms
+    pub started_at: i64,
+}
+
+/// Downsampled waveform for a recording: the loudest absolute sample in each
+/// bucket, with buckets spread evenly over `duration_ms`
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformPeaks {
+    recording_id: String,
+    duration_ms: u64,
+    peaks: Vec<u16>,
+}
+
+/// Loudness of a source over the last 1/`LEVELS_PER_SECOND` of a second, as a fraction of full scale
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioLevel<'a> {
+    recording_id: &'a str,
+    source: AudioSource,
+    rms: f32,
+    peak: f32,
+    clipping: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviceDisconnected<'a> {
+    recording_id: &'a str,
+    source: AudioSource,
+    device: &'a str,
+    /// Device the recording carries on with; None when there was none and the recording stopped
+    fallback: Option<&'a str>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDeviceInfo {
+    name: String,
+    is_default: bool,
+}
+
+/// Mono 16-bit PCM, ready to forward to a transcription service
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioChunk<'a> {
+    recording_id: &'a str,
+    source: AudioSource,
+    sample_rate: u32,
+    samples: &'a [i16],
+}
+
+pub fn setup_audio(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(AudioState::default());
+    Ok(())
+}
+
+/// List available microphones
+#[tauri::command]
+pub fn list_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|device| device.name().ok());
+
+    let devices = host.input_devices().map_err(|e| e.to_string())?;
+    Ok(devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| AudioDeviceInfo {
+            is_default: Some(&name) == default_name.as_ref(),
+            name,
+        })
+        .collect())
+}
+
+/// Start recording from a microphone (the one picked in settings when `device` is omitted)
+#[tauri::command]
+pub fn start_audio_capture(
+    app: AppHandle,
+    state: State<'_, AudioState>,
+    device: Option<String>,
+) -> Result<RecordingInfo, String> {
+    start_capture(&app, &state, AudioSource::Microphone, device.as_deref())
+}
+
+/// Start or stop capturing system audio (the other side of a call) alongside the microphone
+#[tauri::command]
+pub fn capture_system_audio(
+    app: AppHandle,
+    state: State<'_, AudioState>,
+    enabled: bool,
+) -> Result<Option<RecordingInfo>, String> {
+    if enabled {
+        start_capture(&app, &state, AudioSource::System, None).map(Some)
+    } else {
+        stop_source(&state, AudioSource::System)
+    }
+}
+
+/// Mute or unmute a source; muted sources keep recording silence so timelines stay aligned
+#[tauri::command]
+pub fn set_audio_source_muted(
+    state: State<'_, AudioState>,
+    source: AudioSource,
+    muted: bool,
+) -> Result<(), String> {
+    let captures = state.captures.lock().map_err(|e| e.to_string())?;
+    let capture = captures
+        .get(&source)
+        .ok_or_else(|| t!("error.audio_not_captured"))?;
+    capture.controls.muted.store(muted, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Make every capture lose its device, for chaos mode
+pub(crate) fn lose_devices(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AudioState>();
+    let captures = state.captures.lock().map_err(|e| e.to_string())?;
+    if captures.is_empty() {
+        return Err(t!("error.audio_not_captured"));
+    }
+    for capture in captures.values() {
+        capture.controls.device_lost.store(true, Ordering::SeqCst);
+    }
+    report_stream_error(app, &t!("error.chaos_device_lost"));
+    Ok(())
+}
+
+/// Look up the microphone and its format now, so a capture started soon after opens
+/// it without the wait. Nothing is recorded.
+pub(crate) fn prearm(app: &AppHandle) -> Result<(), String> {
+    if demo::is_enabled(app) {
+        return Ok(());
+    }
+    let device = preferred_input_device(app)?;
+    let config = device.default_input_config().map_err(|e| e.to_string())?;
+    *app.state::<AudioState>()
+        .armed
+        .lock()
+        .map_err(|e| e.to_string())? = Some((Instant::now(), device, config));
+    Ok(())
+}
+
+/// The pre-armed microphone, if no particular one is asked for and it's still fresh
+fn take_armed(
+    state: &AudioState,
+    device_name: Option<&str>,
+) -> Option<(cpal::Device, cpal::SupportedStreamConfig)> {
+    if device_name.is_some() {
+        return None;
+    }
+    let (armed_at, device, config) = state.armed.lock().ok()?.take()?;
+    (armed_at.elapsed() < ARMED_TTL).then_some((device, config))
+}
+
+/// Stop recording every source and finalize the WAV files
+#[tauri::command]
+pub fn stop_audio_capture(state: State<'_, AudioState>) -> Result<Vec<RecordingInfo>, String> {
+    stop_capture(&state)
+}
+
+/// Get the recordings in progress
+#[tauri::command]
+pub fn get_audio_capture(state: State<'_, AudioState>) -> Result<Vec<RecordingInfo>, String> {
+    Ok(state
+        .captures
+        .lock()
+        .map_err(|e| e.to_string())?
+        .values()
+        .map(|capture| capture.recording.clone())
+        .collect())
+}
+
+/// Get the waveform of a finished recording, optionally reduced to `buckets` peaks for display
+#[tauri::command]
+pub fn get_waveform_peaks(
+    app: AppHandle,
+    recording_id: String,
+    buckets: Option<usize>,
+) -> Result<WaveformPeaks, String> {
+    // Ids are uuids; reject anything else so it can't escape the recordings dir
+    uuid::Uuid::parse_str(&recording_id).map_err(|e| e.to_string())?;
+
+    let path = recordings_dir(&app)?.join(format!("{}.peaks.json", recording_id));
+    let data = vault::read(&app, &path)?;
+    let mut waveform: WaveformPeaks = serde_json::from_slice(&data).map_err(|e| e.to_string())?;
+
+    if let Some(buckets) = buckets.filter(|&b| b > 0 && b < waveform.peaks.len()) {
+        let len = waveform.peaks.len();
+        waveform.peaks = (0..buckets)
+            .map(|i| {
+                let range = (i * len / buckets)..((i + 1) * len / buckets);
+                waveform.peaks[range].iter().copied().max().unwrap_or(0)
+            })
+            .collect();
+    }
+
+    Ok(waveform)
+}
+
+/// Receive every chunk captured from now on
+pub fn subscribe(state: &AudioState) -> broadcast::Receiver<Arc<PcmChunk>> {
+    state.chunks.subscribe()
+}
+
+pub fn start_capture(
+    app: &AppHandle,
+    state: &AudioState,
+    source: AudioSource,
+    device_name: Option<&str>,
+) -> Result<RecordingInfo, String> {
+    let mut captures = state.captures.lock().map_err(|e| e.to_string())?;
+    if captures.contains_key(&source) {
+        return Err(t!("error.audio_running"));
+    }
+
+    // Demo mode records silence from a fake device, so it works without a microphone
+    let input = if demo::is_enabled(app) {
+        CaptureInput::Demo
+    } else {
+        match source {
+            AudioSource::Microphone => {
+                let (device, config) = match take_armed(state, device_name) {
+                    Some(input) => input,
+                    None => {
+                        let device = match device_name {
+                            Some(_) => find_input_device(device_name)?,
+                            None => preferred_input_device(app)?,
+                        };
+                        let config = device.default_input_config().map_err(|e| e.to_string())?;
+                        (device, config)
+                    }
+                };
+                CaptureInput::Device(device, config)
+            }
+            #[cfg(target_os = "macos")]
+            AudioSource::System => CaptureInput::ScreenCapture,
+            #[cfg(not(target_os = "macos"))]
+            AudioSource::System => {
+                let (device, config) = find_system_device()?;
+                CaptureInput::Device(device, config)
+            }
+        }
+    };
+
+    let capture = match spawn_capture(app, source, input) {
+        // Before macOS 13, or without Screen Recording, a loopback device can still do
+        #[cfg(target_os = "macos")]
+        Err(e) if source == AudioSource::System && !demo::is_enabled(app) => {
+            tracing::warn!("ScreenCaptureKit failed, using a loopback device: {}", e);
+            let (device, config) = find_system_device().map_err(|_| e)?;
+            spawn_capture(app, source, CaptureInput::Device(device, config))?
+        }
+        started => started?,
+    };
+    let recording = capture.recording.clone();
+    captures.insert(source, capture);
+
+    tracing::info!(device = ?recording.device, "Capture started");
+    Ok(recording)
+}
+
+/// Start recording `input` on its own thread, once it's delivering audio
+fn spawn_capture(
+    app: &AppHandle,
+    source: AudioSource,
+    input: CaptureInput,
+) -> Result<ActiveCapture, String> {
+    let (device_name, sample_rate) = match &input {
+        CaptureInput::Device(device, config) => {
+            (device.name().unwrap_or_default(), config.sample_rate().0)
+        }
+        #[cfg(target_os = "macos")]
+        CaptureInput::ScreenCapture => (
+            crate::system_audio::DEVICE_NAME.to_string(),
+            crate::system_audio::SAMPLE_RATE,
+        ),
+        CaptureInput::Demo => (demo::DEVICE_NAME.to_string(), demo::SAMPLE_RATE),
+    };
+
+    let dir = recordings_dir(app)?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let recording = RecordingInfo {
+        path: dir.join(format!("{}.wav", id)),
+        id,
+        source,
+        device: device_name,
+        sample_rate,
+        started_at: crate::db::now_ms(),
+    };
+
+    // cpal streams aren't Send on every platform, so the stream lives on its own thread
+    let controls = Arc::new(CaptureControls::default());
+    let (stop, stop_rx) = mpsc::channel();
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let thread = {
+        let app = app.clone();
+        let recording = recording.clone();
+        let controls = controls.clone();
+        thread::spawn(move || run_capture(app, input, recording, controls, stop_rx, ready_tx))
+    };
+
+    ready_rx
+        .recv()
+        .map_err(|_| t!("error.audio_thread_exited"))??;
+    Ok(ActiveCapture {
+        recording,
+        controls,
+        stop,
+        thread,
+    })
+}
+
+/// Pause or resume every source without finalizing the recordings
+pub fn set_paused(state: &AudioState, paused: bool) -> Result<(), String> {
+    let captures = state.captures.lock().map_err(|e| e.to_string())?;
+    for capture in captures.values() {
+        capture.controls.paused.store(paused, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Stop every source
+pub fn stop_capture(state: &AudioState) -> Result<Vec<RecordingInfo>, String> {
+    let captures: Vec<ActiveCapture> = state
+        .captures
+        .lock()
+        .map_err(|e| e.to_string())?
+        .drain()
+        .map(|(_, capture)| capture)
+        .collect();
+
+    Ok(captures.into_iter().map(finish).collect())
+}
+
+pub fn stop_source(state: &AudioState, source: AudioSource) -> Result<Option<RecordingInfo>, String> {
+    let capture = state
+        .captures
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&source);
+
+    Ok(capture.map(finish))
+}
+
+/// Record the microphone while push-to-talk is held. A microphone that was already
+/// recording when the key went down is left running on release.
+pub fn push_to_talk(
+    app: &AppHandle,
+    state: &AudioState,
+    pressed: bool,
+) -> Result<Option<RecordingInfo>, String> {
+    let mut held = state.push_to_talk.lock().map_err(|e| e.to_string())?;
+
+    if pressed {
+        // Key repeat sends more presses while the key is held
+        if held.is_some()
+            || state
+                .captures
+                .lock()
+                .map_err(|e| e.to_string())?
+                .contains_key(&AudioSource::Microphone)
+        {
+            return Ok(None);
+        }
+        let recording = start_capture(app, state, AudioSource::Microphone, None)?;
+        *held = Some(recording.id.clone());
+        return Ok(Some(recording));
+    }
+
+    let Some(id) = held.take() else {
+        return Ok(None);
+    };
+    let mut captures = state.captures.lock().map_err(|e| e.to_string())?;
+    if !captures
+        .get(&AudioSource::Microphone)
+        .is_some_and(|capture| capture.recording.id == id)
+    {
+        return Ok(None);
+    }
+    let capture = captures.remove(&AudioSource::Microphone);
+    drop(captures);
+    Ok(capture.map(finish))
+}
+
+fn finish(capture: ActiveCapture) -> RecordingInfo {
+    let _ = capture.stop.send(());
+    let _ = capture.thread.join();
+
+    tracing::info!(device = ?capture.recording.device, "Capture stopped");
+    capture.recording
+}
+
+/// Open a finished recording, decrypting it as it's read if the profile has a passphrase
+pub(crate) fn open_recording(
+    app: &AppHandle,
+    path: &Path,
+) -> Result<hound::WavReader<FileReader>, String> {
+    hound::WavReader::new(vault::open(app, path)?).map_err(|e| e.to_string())
+}
+
+/// Read back a finished recording's samples
+pub(crate) fn read_recording(app: &AppHandle, path: &Path) -> Result<Vec<i16>, String> {
+    open_recording(app, path)?
+        .samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Read `count` samples of a recording from sample `first`, fewer if it ends sooner
+pub(crate) fn read_recording_range(
+    app: &AppHandle,
+    path: &Path,
+    first: u32,
+    count: usize,
+) -> Result<Vec<i16>, String> {
+    let mut reader = open_recording(app, path)?;
+    reader
+        .seek(first.min(reader.duration()))
+        .map_err(|e| e.to_string())?;
+    reader
+        .samples::<i16>()
+        .take(count)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Write a whole recording and its waveform at once, e.g. an edited copy, returning its length
+pub(crate) fn write_recording(
+    app: &AppHandle,
+    recording: &RecordingInfo,
+    samples: &[i16],
+) -> Result<u64, String> {
+    let mut writer = RecordingWriter::create(app, recording)?;
+    writer.write(samples)?;
+    writer.finish()
+}
+
+/// Writes a recording made outside a capture, e.g. a converted file, a block at a time
+pub(crate) struct RecordingWriter {
+    app: AppHandle,
+    recording: RecordingInfo,
+    writer: hound::WavWriter<FileWriter>,
+    peaks: PeakBuilder,
+    samples: u64,
+}
+
+impl RecordingWriter {
+    pub(crate) fn create(app: &AppHandle, recording: &RecordingInfo) -> Result<Self, String> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: recording.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let file = vault::create(app, &recording.path)?;
+        Ok(Self {
+            app: app.clone(),
+            recording: recording.clone(),
+            writer: hound::WavWriter::new(file, spec).map_err(|e| e.to_string())?,
+            peaks: PeakBuilder::new((recording.sample_rate / PEAKS_PER_SECOND) as usize),
+            samples: 0,
+        })
+    }
+
+    pub(crate) fn write(&mut self, samples: &[i16]) -> Result<(), String> {
+        for sample in samples {
+            self.writer
+                .write_sample(*sample)
+                .map_err(|e| e.to_string())?;
+        }
+        self.peaks.push(samples);
+        self.samples += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Finish the file and save its waveform, returning its length
+    pub(crate) fn finish(self) -> Result<u64, String> {
+        self.writer.finalize().map_err(|e| e.to_string())?;
+        let duration_ms = self.samples * 1000 / self.recording.sample_rate.max(1) as u64;
+        save_peaks(&self.app, &self.recording, duration_ms, self.peaks.finish())?;
+        Ok(duration_ms)
+    }
+}
+
+/// Length of a finished recording
+pub(crate) fn recording_duration_ms(app: &AppHandle, path: &Path) -> Result<u64, String> {
+    let reader = open_recording(app, path)?;
+    let sample_rate = reader.spec().sample_rate.max(1) as u64;
+    Ok(reader.duration() as u64 * 1000 / sample_rate)
+}
+
+/// Delete a recording with its waveform
+pub(crate) fn remove_recording(path: &Path) -> Result<(), String> {
+    let _ = fs::remove_file(path.with_extension("peaks.json"));
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::profiles::active_data_dir(app)?.join("recordings");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+pub(crate) fn find_input_device(name: Option<&str>) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    match name {
+        Some(name) => host
+            .input_devices()
+            .map_err(|e| e.to_string())?
+            .find(|device| device.name().ok().as_deref() == Some(name))
+            .ok_or_else(|| t!("error.audio_device_not_found", name = name)),
+        None => host
+            .default_input_device()
+            .ok_or_else(|| t!("error.no_microphone")),
+    }
+}
+
+/// The microphone picked in settings, or the default when none is picked or it's unplugged
+fn preferred_input_device(app: &AppHandle) -> Result<cpal::Device, String> {
+    let devices: AudioDevices = app
+        .store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(AUDIO_DEVICES_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    match devices.microphone {
+        Some(name) => find_input_device(Some(&name)).or_else(|_| find_input_device(None)),
+        None => find_input_device(None),
+    }
+}
+
+/// The default device for a source, opened at the sample rate its recording already uses
+fn fallback_device(
+    source: AudioSource,
+    sample_rate: u32,
+) -> Result<(cpal::Device, cpal::SupportedStreamConfig), String> {
+    let (device, config) = match source {
+        AudioSource::Microphone => {
+            let device = find_input_device(None)?;
+            let config = device.default_input_config().map_err(|e| e.to_string())?;
+            (device, config)
+        }
+        AudioSource::System => find_system_device()?,
+    };
+    if config.sample_rate().0 == sample_rate {
+        return Ok((device, config));
+    }
+
+    // A WAV file has one rate throughout, so the new device has to support the one in use
+    let ranges: Vec<cpal::SupportedStreamConfigRange> =
+        if cfg!(target_os = "windows") && source == AudioSource::System {
+            device
+                .supported_output_configs()
+                .map_err(|e| e.to_string())?
+                .collect()
+        } else {
+            device
+                .supported_input_configs()
+                .map_err(|e| e.to_string())?
+                .collect()
+        };
+    let config = ranges
+        .into_iter()
+        .find(|range| {
+            range.min_sample_rate().0 <= sample_rate && sample_rate <= range.max_sample_rate().0
+        })
+        .map(|range| range.with_sample_rate(cpal::SampleRate(sample_rate)))
+        .ok_or_else(|| t!("error.audio_fallback_rate", rate = sample_rate))?;
+    Ok((device, config))
+}
+
+/// System audio device: WASAPI loopback on the default output on Windows,
+/// otherwise a virtual loopback input (BlackHole, PulseAudio monitor, ...). On macOS this is
+/// only the fallback for when ScreenCaptureKit can't be used.
+pub(crate) fn find_system_device() -> Result<(cpal::Device, cpal::SupportedStreamConfig), String> {
+    let host = cpal::default_host();
+
+    #[cfg(target_os = "windows")]
+    {
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| t!("error.no_output_device"))?;
+        let config = device.default_output_config().map_err(|e| e.to_string())?;
+        Ok((device, config))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let device = host
+            .input_devices()
+            .map_err(|e| e.to_string())?
+            .find(|device| {
+                device.name().is_ok_and(|name| {
+                    let name = name.to_lowercase();
+                    LOOPBACK_DEVICE_HINTS.iter().any(|hint| name.contains(hint))
+                })
+            })
+            .ok_or_else(|| t!("error.no_loopback_device"))?;
+        let config = device.default_input_config().map_err(|e| e.to_string())?;
+        Ok((device, config))
+    }
+}
+
+fn run_capture(
+    app: AppHandle,
+    input: CaptureInput,
+    recording: RecordingInfo,
+    controls: Arc<CaptureControls>,
+    stop: Receiver<()>,
+    ready: Sender<Result<(), String>>,
+) {
+    // Kept to hand to a fallback device if this one is unplugged
+    let (samples_tx, samples_rx) = mpsc::channel::<Vec<f32>>();
+
+    let is_demo = matches!(input, CaptureInput::Demo);
+    let stream = match input {
+        CaptureInput::Device(device, config) => {
+            open_stream(&app, &device, &config, &controls, samples_tx.clone()).and_then(|stream| {
+                stream.play().map_err(|e| e.to_string())?;
+                Ok(Some(CaptureStream::Device(stream)))
+            })
+        }
+        #[cfg(target_os = "macos")]
+        CaptureInput::ScreenCapture => crate::system_audio::start(samples_tx.clone())
+            .map(|stream| Some(CaptureStream::ScreenCapture(stream))),
+        CaptureInput::Demo => {
+            demo::feed_silence(samples_tx.clone());
+            Ok(None)
+        }
+    };
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: recording.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let (mut stream, mut writer) = match stream.and_then(|stream| {
+        let file = vault::create(&app, &recording.path)?;
+        let writer = hound::WavWriter::new(file, spec).map_err(|e| e.to_string())?;
+        Ok((stream, writer))
+    }) {
+        Ok(started) => started,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(()));
+
+    let chunk_len = (recording.sample_rate * CHUNK_DURATION_MS / 1000) as usize;
+    let mut buffer: Vec<i16> = Vec::with_capacity(chunk_len * 2);
+    let mut peaks = PeakBuilder::new((recording.sample_rate / PEAKS_PER_SECOND) as usize);
+    let mut levels = LevelMeter::new((recording.sample_rate / LEVELS_PER_SECOND) as usize);
+    let mut device = recording.device.clone();
+
+    loop {
+        match stop.try_recv() {
+            Ok(()) | Err(mpsc::TryRecvError::Disconnected) => break,
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+
+        // The device was unplugged, or chaos mode acts as if it was
+        if controls.device_lost.swap(false, Ordering::SeqCst) {
+            stream = None;
+            let fallback = if is_demo {
+                Ok((None, demo::DEVICE_NAME.to_string()))
+            } else {
+                fallback_device(recording.source, recording.sample_rate).and_then(
+                    |(fallback, config)| {
+                        let stream =
+                            open_stream(&app, &fallback, &config, &controls, samples_tx.clone())?;
+                        stream.play().map_err(|e| e.to_string())?;
+                        Ok((
+                            Some(CaptureStream::Device(stream)),
+                            fallback.name().unwrap_or_default(),
+                        ))
+                    },
+                )
+            };
+            let fallback = match fallback {
+                Ok((fallback_stream, name)) => {
+                    tracing::info!(
+                        lost_device = ?device,
+                        device = ?name,
+                        "Lost the device, recording from another one"
+                    );
+                    stream = fallback_stream;
+                    Some(name)
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        device = ?device,
+                        "Lost the device with nothing to fall back to: {}",
+                        e
+                    );
+                    None
+                }
+            };
+            let _ = app.emit(
+                "device_disconnected",
+                DeviceDisconnected {
+                    recording_id: &recording.id,
+                    source: recording.source,
+                    device: &device,
+                    fallback: fallback.as_deref(),
+                },
+            );
+            match fallback {
+                Some(name) => device = name,
+                None => break,
+            }
+        }
+
+        let appended = buffer.len();
+        match samples_rx.recv_timeout(Duration::from_millis(50)) {
+            Ok(_) if controls.paused.load(Ordering::Relaxed) => continue,
+            Ok(samples) if controls.muted.load(Ordering::Relaxed) => {
+                buffer.resize(buffer.len() + samples.len(), 0)
+            }
+            Ok(samples) => buffer.extend(samples.into_iter().map(i16::from_sample)),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        for (rms, peak) in levels.push(&buffer[appended..]) {
+            let _ = app.emit(
+                "audio_level",
+                AudioLevel {
+                    recording_id: &recording.id,
+                    source: recording.source,
+                    rms,
+                    peak,
+                    clipping: peak >= CLIPPING_LEVEL,
+                },
+            );
+        }
+
+        while buffer.len() >= chunk_len {
+            let chunk: Vec<i16> = buffer.drain(..chunk_len).collect();
+            peaks.push(&chunk);
+            write_chunk(&app, &recording, &mut writer, &chunk);
+        }
+    }
+
+    drop(stream);
+    if !buffer.is_empty() {
+        peaks.push(&buffer);
+        write_chunk(&app, &recording, &mut writer, &buffer);
+    }
+    let duration_ms = writer.duration() as u64 * 1000 / recording.sample_rate as u64;
+    if let Err(e) = writer.finalize() {
+        tracing::error!("Failed to finalize recording: {}", e);
+    }
+    if let Err(e) = save_peaks(&app, &recording, duration_ms, peaks.finish()) {
+        tracing::error!("Failed to save waveform: {}", e);
+    }
+
+    let _ = app.emit("audio_capture_stopped", &recording);
+}
+
+/// Tracks the loudest sample per fixed-size bucket while recording, so the
+/// waveform is ready as soon as the file is finalized
+struct PeakBuilder {
+    bucket_len: usize,
+    current: u16,
+    filled: usize,
+    peaks: Vec<u16>,
+}
+
+impl PeakBuilder {
+    fn new(bucket_len: usize) -> Self {
+        Self {
+            bucket_len: bucket_len.max(1),
+            current: 0,
+            filled: 0,
+            peaks: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, samples: &[i16]) {
+        for sample in samples {
+            self.current = self.current.max(sample.unsigned_abs());
+            self.filled += 1;
+            if self.filled == self.bucket_len {
+                self.peaks.push(self.current);
+                self.current = 0;
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u16> {
+        if self.filled > 0 {
+            self.peaks.push(self.current);
+        }
+        self.peaks
+    }
+}
+
+/// RMS and peak level over fixed-size windows, for the live level meter
+struct LevelMeter {
+    window_len: usize,
+    sum_squares: f64,
+    peak: u16,
+    filled: usize,
+}
+
+impl LevelMeter {
+    fn new(window_len: usize) -> Self {
+        Self {
+            window_len: window_len.max(1),
+            sum_squares: 0.0,
+            peak: 0,
+            filled: 0,
+        }
+    }
+
+    /// Add samples, returning the RMS and peak of each window they complete
+    fn push(&mut self, samples: &[i16]) -> Vec<(f32, f32)> {
+        let full_scale = i16::MAX as f64;
+        let mut levels = Vec::new();
+        for sample in samples {
+            self.sum_squares += (*sample as f64 / full_scale).powi(2);
+            self.peak = self.peak.max(sample.unsigned_abs());
+            self.filled += 1;
+            if self.filled == self.window_len {
+                let rms = (self.sum_squares / self.window_len as f64).sqrt();
+                let peak = (self.peak as f64 / full_scale).min(1.0);
+                levels.push((rms as f32, peak as f32));
+                self.sum_squares = 0.0;
+                self.peak = 0;
+                self.filled = 0;
+            }
+        }
+        levels
+    }
+}
+
+fn save_peaks(
+    app: &AppHandle,
+    recording: &RecordingInfo,
+    duration_ms: u64,
+    peaks: Vec<u16>,
+) -> Result<(), String> {
+    let waveform = WaveformPeaks {
+        recording_id: recording.id.clone(),
+        duration_ms,
+        peaks,
+    };
+    let data = serde_json::to_vec(&waveform).map_err(|e| e.to_string())?;
+    vault::write(app, &recording.path.with_extension("peaks.json"), data)
+}
+
+fn open_stream(
+    app: &AppHandle,
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    controls: &Arc<CaptureControls>,
+    samples: Sender<Vec<f32>>,
+) -> Result<cpal::Stream, String> {
+    let channels = config.channels() as usize;
+    let stream_config = config.config();
+    let controls = controls.clone();
+    match config.sample_format() {
+        SampleFormat::F32 => {
+            build_stream::<f32>(app, device, &stream_config, channels, controls, samples)
+        }
+        SampleFormat::I16 => {
+            build_stream::<i16>(app, device, &stream_config, channels, controls, samples)
+        }
+        SampleFormat::U16 => {
+            build_stream::<u16>(app, device, &stream_config, channels, controls, samples)
+        }
+        SampleFormat::I32 => {
+            build_stream::<i32>(app, device, &stream_config, channels, controls, samples)
+        }
+        format => Err(t!("error.unsupported_sample_format", format = format)),
+    }
+}
+
+fn build_stream<T>(
+    app: &AppHandle,
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    channels: usize,
+    controls: Arc<CaptureControls>,
+    samples: Sender<Vec<f32>>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let error_app = app.clone();
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                // Downmix interleaved frames to mono
+                let mono = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        frame.iter().map(|sample| sample.to_sample::<f32>()).sum::<f32>()
+                            / channels as f32
+                    })
+                    .collect();
+                let _ = samples.send(mono);
+            },
+            move |e| {
+                report_stream_error(&error_app, &e.to_string());
+                // The capture thread falls back to the default device
+                if matches!(e, cpal::StreamError::DeviceNotAvailable) {
+                    controls.device_lost.store(true, Ordering::SeqCst);
+                }
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+fn report_stream_error(app: &AppHandle, error: &str) {
+    tracing::warn!(code = "audio.stream_failed", "Stream error: {}", error);
+    let _ = app.emit("audio_capture_error", error);
+}
+
+fn write_chunk(
+    app: &AppHandle,
+    recording: &RecordingInfo,
+    writer: &mut hound::WavWriter<FileWriter>,
+    chunk: &[i16],
+) {
+    for sample in chunk {
+        let _ = writer.write_sample(*sample);
+    }
+
+    let _ = app.emit(
+        "audio_chunk",
+        AudioChunk {
+            recording_id: &recording.id,
+            source: recording.source,
+            sample_rate: recording.sample_rate,
+            samples: chunk,
+        },
+    );
+
+    // Fails only when nobody is subscribed
+    let _ = app.state::<AudioState>().chunks.send(Arc::new(PcmChunk {
+        recording_id: recording.id.clone(),
+        source: recording.source,
+        sample_rate: recording.sample_rate,
+        samples: chunk.to_vec(),
+    }));
+}
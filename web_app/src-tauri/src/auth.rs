@@ -0,0 +1,387 @@
+// Queen Mama LITE - Account Sign-in
+// OAuth authorization code flow with PKCE: browser sign-in, callback on a loopback port or a
+// queenmama:// link, tokens in the keychain, refreshed before they expire
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use tauri::{App, AppHandle, Manager, State, Url};
+use tauri_plugin_http::reqwest::Client;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use crate::actions::URL_SCHEME;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::{db, secrets};
+
+const AUTHORIZE_URL: &str = "https://queenmama.app/oauth/authorize";
+const TOKEN_URL: &str = "https://queenmama.app/oauth/token";
+const CLIENT_ID: &str = "queen-mama-desktop";
+const SCOPES: &str = "openid profile offline_access";
+
+/// Keychain entry holding the tokens; never readable from the webview
+const TOKENS_SECRET: &str = "internal.auth.tokens";
+
+/// Path the loopback listener answers on
+const LOOPBACK_PATH: &str = "/callback";
+
+/// How long the browser sign-in may take before we give up
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Tokens are refreshed this long before they expire
+const REFRESH_MARGIN_MS: i64 = 60_000;
+
+#[derive(Default)]
+pub struct AuthState {
+    /// Sign-in waiting for its callback
+    pending: Mutex<Option<PendingLogin>>,
+    /// Held while tokens are refreshed so concurrent callers share one refresh
+    refresh: tokio::sync::Mutex<()>,
+}
+
+struct PendingLogin {
+    /// Echoed back by the provider; callbacks with another value are rejected
+    state: String,
+    callback: oneshot::Sender<Callback>,
+}
+
+struct Callback {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tokens {
+    access_token: String,
+    refresh_token: Option<String>,
+    /// Unix ms
+    expires_at: Option<i64>,
+}
+
+#[derive(serde::Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    /// Seconds
+    expires_in: Option<i64>,
+}
+
+/// Emitted as `auth_state_changed`
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum AuthStatus {
+    SignedOut,
+    SigningIn,
+    #[serde(rename_all = "camelCase")]
+    SignedIn {
+        /// Unix ms when the current access token expires
+        expires_at: Option<i64>,
+    },
+}
+
+pub fn setup_auth(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(AuthState::default());
+    Ok(())
+}
+
+/// Sign in through the browser; resolves once the provider has redirected back
+#[tauri::command]
+pub async fn login(app: AppHandle, state: State<'_, AuthState>) -> Result<AuthStatus, String> {
+    let verifier = random_token();
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    let csrf = random_token();
+
+    let (sender, receiver) = oneshot::channel();
+    *state.pending.lock().map_err(|e| e.to_string())? = Some(PendingLogin {
+        state: csrf.clone(),
+        callback: sender,
+    });
+
+    // Prefer a loopback port; fall back to our URL scheme when none can be opened
+    let redirect_uri = match TcpListener::bind("127.0.0.1:0").await {
+        Ok(listener) => {
+            let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+            tauri::async_runtime::spawn(serve_loopback(app.clone(), listener));
+            format!("http://127.0.0.1:{}{}", port, LOOPBACK_PATH)
+        }
+        Err(e) => {
+            tracing::warn!("Loopback unavailable, using the deep link: {}", e);
+            format!("{}://auth/callback", URL_SCHEME)
+        }
+    };
+
+    let authorize_url = Url::parse_with_params(
+        AUTHORIZE_URL,
+        &[
+            ("response_type", "code"),
+            ("client_id", CLIENT_ID),
+            ("redirect_uri", &redirect_uri),
+            ("scope", SCOPES),
+            ("state", &csrf),
+            ("code_challenge", &challenge),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    open::that(authorize_url.as_str()).map_err(|e| e.to_string())?;
+    set_status(&app, AuthStatus::SigningIn);
+
+    let result = async {
+        let callback = tokio::time::timeout(LOGIN_TIMEOUT, receiver)
+            .await
+            .map_err(|_| t!("error.login_timed_out"))?
+            .map_err(|_| t!("error.login_cancelled"))?;
+        if let Some(error) = callback.error {
+            return Err(t!("error.login_failed", error = error));
+        }
+        if callback.state.as_deref() != Some(csrf.as_str()) {
+            return Err(t!("error.login_state_mismatch"));
+        }
+        let code = callback.code.ok_or_else(|| t!("error.login_no_code"))?;
+
+        request_tokens(&[
+            ("grant_type", "authorization_code"),
+            ("client_id", CLIENT_ID),
+            ("code", &code),
+            ("redirect_uri", &redirect_uri),
+            ("code_verifier", &verifier),
+        ])
+        .await
+    }
+    .await;
+
+    // A newer sign-in may have replaced ours while we waited
+    let mut pending = state.pending.lock().map_err(|e| e.to_string())?;
+    if pending
+        .as_ref()
+        .is_some_and(|pending| pending.state == csrf)
+    {
+        pending.take();
+    }
+    drop(pending);
+
+    match result {
+        Ok(tokens) => {
+            save_tokens(&app, &tokens)?;
+            tracing::info!("Signed in");
+            let status = AuthStatus::SignedIn {
+                expires_at: tokens.expires_at,
+            };
+            set_status(&app, status.clone());
+            Ok(status)
+        }
+        Err(e) => {
+            set_status(&app, current_status(&app));
+            Err(e)
+        }
+    }
+}
+
+/// Sign out and forget the saved tokens
+#[tauri::command]
+pub fn logout(app: AppHandle, state: State<'_, AuthState>) -> Result<(), String> {
+    state.pending.lock().map_err(|e| e.to_string())?.take();
+    secrets::delete(&app, TOKENS_SECRET)?;
+    tracing::info!("Signed out");
+    set_status(&app, AuthStatus::SignedOut);
+    Ok(())
+}
+
+/// Whether the user is signed in
+#[tauri::command]
+pub fn get_auth_state(app: AppHandle, state: State<'_, AuthState>) -> Result<AuthStatus, String> {
+    if state.pending.lock().map_err(|e| e.to_string())?.is_some() {
+        return Ok(AuthStatus::SigningIn);
+    }
+    Ok(current_status(&app))
+}
+
+/// A valid access token for our API, refreshed first if it's about to expire
+pub async fn access_token(app: &AppHandle) -> Result<String, String> {
+    let state = app.state::<AuthState>();
+    let _refreshing = state.refresh.lock().await;
+
+    let tokens = load_tokens(app).ok_or_else(|| t!("error.not_signed_in"))?;
+    let expiring = tokens
+        .expires_at
+        .is_some_and(|expires_at| expires_at - REFRESH_MARGIN_MS <= db::now_ms());
+    if !expiring {
+        return Ok(tokens.access_token);
+    }
+
+    let Some(refresh_token) = tokens.refresh_token else {
+        secrets::delete(app, TOKENS_SECRET)?;
+        set_status(app, AuthStatus::SignedOut);
+        return Err(t!("error.not_signed_in"));
+    };
+    let refreshed = request_tokens(&[
+        ("grant_type", "refresh_token"),
+        ("client_id", CLIENT_ID),
+        ("refresh_token", &refresh_token),
+    ])
+    .await
+    .inspect_err(|e| tracing::warn!("Token refresh failed: {}", e))?;
+
+    // Providers may keep the refresh token as it is and leave it out of the response
+    let refreshed = Tokens {
+        refresh_token: refreshed.refresh_token.or(Some(refresh_token)),
+        ..refreshed
+    };
+    save_tokens(app, &refreshed)?;
+    set_status(
+        app,
+        AuthStatus::SignedIn {
+            expires_at: refreshed.expires_at,
+        },
+    );
+    Ok(refreshed.access_token)
+}
+
+/// Finish a sign-in that used the queenmama://auth/callback redirect
+pub fn handle_callback(
+    app: &AppHandle,
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+) -> Result<(), String> {
+    deliver(app, Callback { code, state, error })
+}
+
+/// Hand the redirect to the sign-in in progress. One carrying another state is left
+/// unanswered, so a stray or forged redirect can't cancel the real sign-in.
+fn deliver(app: &AppHandle, callback: Callback) -> Result<(), String> {
+    let state = app.state::<AuthState>();
+    let mut pending = state.pending.lock().map_err(|e| e.to_string())?;
+    let Some(expected) = pending.as_ref().map(|pending| pending.state.as_str()) else {
+        tracing::warn!("Sign-in callback with no sign-in in progress");
+        return Ok(());
+    };
+    if callback.state.as_deref() != Some(expected) {
+        tracing::warn!("Sign-in callback for another sign-in, ignoring it");
+        return Err(t!("error.login_state_mismatch"));
+    }
+
+    if let Some(pending) = pending.take() {
+        let _ = pending.callback.send(callback);
+    }
+    Ok(())
+}
+
+/// Answer the browser's redirect on the loopback port, then stop listening
+async fn serve_loopback(app: AppHandle, listener: TcpListener) {
+    let serve = async {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                continue;
+            };
+
+            let mut request = vec![0; 8192];
+            let Ok(read) = stream.read(&mut request).await else {
+                continue;
+            };
+            let request = String::from_utf8_lossy(&request[..read]);
+            let Some(url) = request
+                .lines()
+                .next()
+                .and_then(|line| line.split_whitespace().nth(1))
+                .and_then(|path| Url::parse(&format!("http://127.0.0.1{}", path)).ok())
+            else {
+                continue;
+            };
+
+            // Browsers also ask for a favicon
+            if url.path() != LOOPBACK_PATH {
+                let _ = stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n")
+                    .await;
+                continue;
+            }
+
+            let query = |name: &str| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+            };
+            let page = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nConnection: close\r\n\r\n\
+                 <!doctype html><title>Queen Mama</title><p>{}</p>",
+                t!("auth.browser_done")
+            );
+            let _ = stream.write_all(page.as_bytes()).await;
+
+            let callback = Callback {
+                code: query("code"),
+                state: query("state"),
+                error: query("error"),
+            };
+            // Keep listening for the real redirect after a stray one
+            if deliver(&app, callback).is_ok() {
+                return;
+            }
+        }
+    };
+    let _ = tokio::time::timeout(LOGIN_TIMEOUT, serve).await;
+}
+
+async fn request_tokens(form: &[(&str, &str)]) -> Result<Tokens, String> {
+    let response = Client::new()
+        .post(TOKEN_URL)
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(t!("error.token_request_failed", status = response.status()));
+    }
+
+    let tokens: TokenResponse = response.json().await.map_err(|e| e.to_string())?;
+    Ok(Tokens {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        expires_at: tokens
+            .expires_in
+            .map(|seconds| db::now_ms() + seconds * 1000),
+    })
+}
+
+fn load_tokens(app: &AppHandle) -> Option<Tokens> {
+    secrets::get(app, TOKENS_SECRET)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn save_tokens(app: &AppHandle, tokens: &Tokens) -> Result<(), String> {
+    let json = serde_json::to_string(tokens).map_err(|e| e.to_string())?;
+    secrets::set(app, TOKENS_SECRET, &json)
+}
+
+fn current_status(app: &AppHandle) -> AuthStatus {
+    match load_tokens(app) {
+        Some(tokens) => AuthStatus::SignedIn {
+            expires_at: tokens.expires_at,
+        },
+        None => AuthStatus::SignedOut,
+    }
+}
+
+fn set_status(app: &AppHandle, status: AuthStatus) {
+    if let Err(e) = app.emit("auth_state_changed", status) {
+        tracing::error!("Failed to emit auth state: {}", e);
+    }
+}
+
+/// 64 URL-safe characters from three random UUIDs, for the PKCE verifier and the state
+fn random_token() -> String {
+    let bytes: Vec<u8> = (0..3)
+        .flat_map(|_| *uuid::Uuid::new_v4().as_bytes())
+        .collect();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
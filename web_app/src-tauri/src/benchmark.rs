@@ -0,0 +1,235 @@
+// Queen Mama LITE - Cohort Benchmark
+// Opt-in comparison of the user's delivery with other users': only a few aggregate numbers leave
+// the machine, and only cohort percentiles come back
+
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::Client;
+use tauri_plugin_store::StoreExt;
+
+use crate::db::{self, Database};
+use crate::i18n::t;
+use crate::SETTINGS_STORE;
+
+pub(crate) const BENCHMARK_STORE_KEY: &str = "cohort_benchmark";
+
+/// Takes the user's aggregate metrics and answers with the cohort's percentiles
+const BENCHMARK_ENDPOINT: &str = "https://queenmama.app/api/benchmarks";
+
+/// Bumped when the uploaded metrics change, so the server compares like with like
+const METRICS_VERSION: u32 = 1;
+
+/// Sessions started this long ago or less are measured
+const WINDOW_DAYS: i64 = 30;
+
+/// Less of the user's own speech than this says little about how they speak
+const MIN_OWN_SPEECH_MS: i64 = 10 * 60 * 1000;
+
+/// Hesitation sounds counted as fillers, in the languages the app speaks. Words like "like" or
+/// "so" are fillers only some of the time, so they're left out.
+const FILLER_WORDS: &[&str] = &[
+    "um", "umm", "uh", "uhm", "erm", "hmm", "hm", "euh", "heu", "eh", "ehm", "äh", "ähm", "öhm",
+];
+
+/// Whether the user agreed to compare their metrics with the cohort's
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BenchmarkConsent {
+    enabled: bool,
+    /// When the user last opted in or out, Unix ms
+    answered_at: Option<i64>,
+}
+
+/// Everything that's uploaded, and nothing else: no ids, account, transcript or timestamps
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkMetrics {
+    version: u32,
+    /// The user's share of the talking, 0.0 to 1.0
+    talk_ratio: f64,
+    /// Fillers per minute of the user's own speech
+    filler_rate: f64,
+    /// Sessions the metrics were taken from
+    sessions: usize,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Percentile {
+    /// 0 to 100
+    percentile: f64,
+    value: f64,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortPercentiles {
+    /// Users the percentiles were computed over
+    cohort_size: u64,
+    talk_ratio: Vec<Percentile>,
+    filler_rate: Vec<Percentile>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkComparison {
+    metrics: BenchmarkMetrics,
+    cohort: CohortPercentiles,
+    /// Where the user falls in the cohort, 0 to 100
+    talk_ratio_percentile: Option<f64>,
+    filler_rate_percentile: Option<f64>,
+}
+
+/// Get whether the user opted in to the cohort benchmark
+#[tauri::command]
+pub fn get_benchmark_consent(app: AppHandle) -> BenchmarkConsent {
+    load_consent(&app)
+}
+
+/// Opt in to or out of the cohort benchmark
+#[tauri::command]
+pub fn set_benchmark_consent(app: AppHandle, enabled: bool) -> Result<BenchmarkConsent, String> {
+    let consent = BenchmarkConsent {
+        enabled,
+        answered_at: Some(db::now_ms()),
+    };
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        BENCHMARK_STORE_KEY,
+        serde_json::to_value(&consent).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+
+    tracing::info!(enabled, "Answered the cohort benchmark opt-in");
+    Ok(consent)
+}
+
+/// Get exactly what `get_cohort_benchmark` would upload, without uploading it
+#[tauri::command]
+pub fn preview_benchmark_metrics(app: AppHandle) -> Result<BenchmarkMetrics, String> {
+    metrics(&app.state::<Database>().connection()?)
+}
+
+/// Upload the user's aggregate metrics from the last 30 days and compare them with the cohort's
+/// percentiles; only once the user has opted in
+#[tauri::command]
+pub async fn get_cohort_benchmark(app: AppHandle) -> Result<BenchmarkComparison, String> {
+    if !load_consent(&app).enabled {
+        return Err(t!("error.benchmark_consent_required"));
+    }
+    let metrics = metrics(&app.state::<Database>().connection()?)?;
+
+    // Sent without the account token, so the numbers can't be tied to the user
+    let response = Client::new()
+        .post(BENCHMARK_ENDPOINT)
+        .json(&metrics)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(t!(
+            "error.benchmark_request_failed",
+            status = response.status()
+        ));
+    }
+    let mut cohort: CohortPercentiles = response.json().await.map_err(|e| e.to_string())?;
+    cohort
+        .talk_ratio
+        .sort_by(|a, b| a.percentile.total_cmp(&b.percentile));
+    cohort
+        .filler_rate
+        .sort_by(|a, b| a.percentile.total_cmp(&b.percentile));
+
+    tracing::info!(
+        cohort = cohort.cohort_size,
+        "Compared metrics with the cohort"
+    );
+    Ok(BenchmarkComparison {
+        talk_ratio_percentile: rank(metrics.talk_ratio, &cohort.talk_ratio),
+        filler_rate_percentile: rank(metrics.filler_rate, &cohort.filler_rate),
+        metrics,
+        cohort,
+    })
+}
+
+/// The user's talk ratio and filler rate over recent finished sessions, from the segments
+/// labelled as the user ("me") and the other side ("them")
+fn metrics(connection: &Connection) -> Result<BenchmarkMetrics, String> {
+    let since = db::now_ms() - WINDOW_DAYS * 24 * 60 * 60 * 1000;
+    let mut statement = connection
+        .prepare(
+            "SELECT t.session_id, t.speaker, t.text, t.end_ms - t.start_ms FROM transcript_segments t
+             JOIN sessions s ON s.id = t.session_id
+             WHERE s.started_at >= ?1 AND s.ended_at IS NOT NULL AND s.deleted_at IS NULL
+               AND t.speaker IN ('me', 'them')",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params![since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut sessions = HashSet::new();
+    let (mut own_ms, mut their_ms, mut fillers) = (0i64, 0i64, 0usize);
+    for row in rows {
+        let (session_id, speaker, text, duration_ms) = row.map_err(|e| e.to_string())?;
+        sessions.insert(session_id);
+        if speaker == "them" {
+            their_ms += duration_ms.max(0);
+            continue;
+        }
+        own_ms += duration_ms.max(0);
+        fillers += text
+            .split(|c: char| !c.is_alphabetic())
+            .filter(|word| FILLER_WORDS.contains(&word.to_lowercase().as_str()))
+            .count();
+    }
+    if own_ms < MIN_OWN_SPEECH_MS {
+        return Err(t!(
+            "error.benchmark_not_enough_speech",
+            minutes = MIN_OWN_SPEECH_MS / 60_000
+        ));
+    }
+
+    Ok(BenchmarkMetrics {
+        version: METRICS_VERSION,
+        talk_ratio: own_ms as f64 / (own_ms + their_ms) as f64,
+        filler_rate: fillers as f64 / (own_ms as f64 / 60_000.0),
+        sessions: sessions.len(),
+    })
+}
+
+/// Where a value falls in the cohort, interpolated between the percentiles around it
+fn rank(value: f64, percentiles: &[Percentile]) -> Option<f64> {
+    let (first, last) = (percentiles.first()?, percentiles.last()?);
+    if value <= first.value {
+        return Some(first.percentile);
+    }
+    if value >= last.value {
+        return Some(last.percentile);
+    }
+    percentiles.windows(2).find_map(|pair| {
+        let (low, high) = (&pair[0], &pair[1]);
+        if value < low.value || value > high.value || high.value <= low.value {
+            return None;
+        }
+        let position = (value - low.value) / (high.value - low.value);
+        Some(low.percentile + position * (high.percentile - low.percentile))
+    })
+}
+
+fn load_consent(app: &AppHandle) -> BenchmarkConsent {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(BENCHMARK_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,192 @@
+// Queen Mama LITE - Bulk Session Operations
+// Trash, tag or export many sessions in one call; changes to the database happen in a single
+// transaction, so a failure part way leaves every session as it was
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rusqlite::params;
+use tauri::{AppHandle, Manager};
+
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::export::{self, ExportFormat};
+use crate::notes::sanitize_file_name;
+use crate::vault;
+
+/// `bulk_progress` is emitted after this many sessions
+const PROGRESS_EVERY: usize = 25;
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+enum BulkOperation {
+    Delete,
+    Tag,
+    Export,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkProgress {
+    operation: BulkOperation,
+    /// Sessions handled so far
+    done: usize,
+    total: usize,
+}
+
+/// Emits `bulk_progress` every so often
+struct Progress<'a> {
+    app: &'a AppHandle,
+    operation: BulkOperation,
+    done: usize,
+    total: usize,
+}
+
+impl Progress<'_> {
+    fn new(app: &AppHandle, operation: BulkOperation, total: usize) -> Progress<'_> {
+        let progress = Progress {
+            app,
+            operation,
+            done: 0,
+            total,
+        };
+        progress.emit();
+        progress
+    }
+
+    fn advance(&mut self) {
+        self.done += 1;
+        if self.done % PROGRESS_EVERY == 0 || self.done == self.total {
+            self.emit();
+        }
+    }
+
+    fn emit(&self) {
+        let progress = BulkProgress {
+            operation: self.operation,
+            done: self.done,
+            total: self.total,
+        };
+        if let Err(e) = self.app.emit("bulk_progress", progress) {
+            tracing::error!("Failed to emit event: {}", e);
+        }
+    }
+}
+
+/// Move sessions to the trash, returning how many weren't there already
+#[tauri::command]
+pub async fn delete_sessions(app: AppHandle, ids: Vec<String>) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut progress = Progress::new(&app, BulkOperation::Delete, ids.len());
+        let db = app.state::<Database>();
+        let mut connection = db.connection()?;
+        let transaction = connection.transaction().map_err(|e| e.to_string())?;
+        let mut deleted = 0;
+        {
+            let mut statement = transaction
+                .prepare("UPDATE sessions SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL")
+                .map_err(|e| e.to_string())?;
+            let now = db::now_ms();
+            for id in &ids {
+                deleted += statement
+                    .execute(params![id, now])
+                    .map_err(|e| e.to_string())?;
+                progress.advance();
+            }
+        }
+        transaction.commit().map_err(|e| e.to_string())?;
+        drop(connection);
+        db::sessions_changed(&app);
+        tracing::info!("Moved {} sessions to the trash", deleted);
+        Ok(deleted)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Add a tag to sessions, returning how many exist; sessions that already have it are counted
+#[tauri::command]
+pub async fn tag_sessions(app: AppHandle, ids: Vec<String>, tag: String) -> Result<usize, String> {
+    let tag = crate::tags::valid_tag(&tag)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut progress = Progress::new(&app, BulkOperation::Tag, ids.len());
+        let db = app.state::<Database>();
+        let mut connection = db.connection()?;
+        let transaction = connection.transaction().map_err(|e| e.to_string())?;
+        let mut tagged = 0;
+        {
+            let mut exists = transaction
+                .prepare("SELECT 1 FROM sessions WHERE id = ?1")
+                .map_err(|e| e.to_string())?;
+            let mut insert = transaction
+                .prepare("INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?1, ?2)")
+                .map_err(|e| e.to_string())?;
+            for id in &ids {
+                if exists.exists(params![id]).map_err(|e| e.to_string())? {
+                    insert
+                        .execute(params![id, tag])
+                        .map_err(|e| e.to_string())?;
+                    tagged += 1;
+                }
+                progress.advance();
+            }
+        }
+        transaction.commit().map_err(|e| e.to_string())?;
+        Ok(tagged)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Render sessions in `format` into a single zip at `path`, one file per session
+#[tauri::command]
+pub async fn export_sessions(
+    app: AppHandle,
+    ids: Vec<String>,
+    format: ExportFormat,
+    path: PathBuf,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let exported = export_zip(&app, &ids, format, &path);
+        if exported.is_err() {
+            let _ = fs::remove_file(&path);
+        }
+        exported
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn export_zip(
+    app: &AppHandle,
+    ids: &[String],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<String, String> {
+    let mut progress = Progress::new(app, BulkOperation::Export, ids.len());
+    let mut zip = zip::ZipWriter::new(vault::create(app, path)?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut names = HashSet::new();
+    for id in ids {
+        let (title, rendered) = export::render(app, id, format)?;
+        let base = sanitize_file_name(&title);
+        let mut name = format!("{}.{}", base, format.extension());
+        let mut copy = 1;
+        while !names.insert(name.clone()) {
+            copy += 1;
+            name = format!("{} ({}).{}", base, copy, format.extension());
+        }
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&rendered).map_err(|e| e.to_string())?;
+        progress.advance();
+    }
+    let mut file = zip.finish().map_err(|e| e.to_string())?;
+    file.flush().map_err(|e| e.to_string())?;
+
+    tracing::info!(path = ?path, "Exported {} sessions", ids.len());
+    Ok(path.to_string_lossy().into_owned())
+}
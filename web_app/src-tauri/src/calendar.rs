@@ -0,0 +1,644 @@
+// Queen Mama LITE - Calendar
+// Reads upcoming meetings from the system calendar (macOS) or an ICS feed, and says when one is about to start
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{Datelike, Days, Local, NaiveDate, NaiveDateTime, TimeZone, Weekday};
+use tauri::{App, AppHandle, Manager};
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::{db, secrets, session};
+
+/// How often upcoming meetings are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// `meeting_starting_soon` fires this long before a meeting
+const LEAD_TIME_MS: i64 = 5 * 60 * 1000;
+
+/// How far ahead `get_upcoming_events` looks by default
+const DEFAULT_HORIZON_HOURS: u32 = 24;
+
+/// Feeds are re-downloaded at most this often
+const FEED_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Links in an event that mean it's a video call
+const MEETING_HOSTS: &[&str] = &[
+    "zoom.us",
+    "meet.google.com",
+    "teams.microsoft.com",
+    "teams.live.com",
+    "webex.com",
+    "whereby.com",
+    "around.co",
+];
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Default)]
+pub struct CalendarState {
+    /// Occurrences already announced, by key, with their start
+    announced: Mutex<HashMap<String, i64>>,
+    feed: Mutex<Option<CachedFeed>>,
+}
+
+struct CachedFeed {
+    url: String,
+    fetched: Instant,
+    events: Vec<FeedEvent>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    pub id: String,
+    pub title: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub all_day: bool,
+    pub location: Option<String>,
+    /// Zoom, Meet, Teams or similar link found in the event
+    pub join_url: Option<String>,
+}
+
+/// Emitted as `meeting_starting_soon`
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MeetingStartingSoon {
+    event: CalendarEvent,
+    starts_in_ms: i64,
+}
+
+/// A VEVENT from a feed, before its recurrences are expanded
+struct FeedEvent {
+    uid: String,
+    title: String,
+    start_ms: i64,
+    duration_ms: i64,
+    all_day: bool,
+    location: Option<String>,
+    join_url: Option<String>,
+    rule: Option<Rule>,
+    /// Occurrences removed or moved to their own VEVENT
+    excluded: HashSet<i64>,
+    /// The occurrence of its series this VEVENT replaces
+    recurrence_id: Option<i64>,
+}
+
+/// The subset of RRULE we expand; other frequencies only get their first occurrence.
+/// Weeks count from the first occurrence rather than WKST.
+struct Rule {
+    weekly: bool,
+    interval: u64,
+    until_ms: Option<i64>,
+    count: Option<usize>,
+    weekdays: Vec<Weekday>,
+}
+
+pub fn setup_calendar(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(CalendarState::default());
+
+    let app_handle = app.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        // The same failure every poll is only worth one warning
+        let mut last_error = None;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            match announce_meetings(&app_handle).await {
+                Ok(()) => last_error = None,
+                Err(e) if last_error.as_ref() != Some(&e) => {
+                    tracing::warn!("Calendar check failed: {}", e);
+                    last_error = Some(e);
+                }
+                Err(_) => {}
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Meetings starting within the next `hours` (24 by default), soonest first.
+/// Asks for calendar access on macOS the first time.
+#[tauri::command]
+pub async fn get_upcoming_events(
+    app: AppHandle,
+    hours: Option<u32>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let now = db::now_ms();
+    let horizon = hours.unwrap_or(DEFAULT_HORIZON_HOURS) as i64 * 60 * 60 * 1000;
+    upcoming(&app, now, now + horizon, true).await
+}
+
+/// Read meetings from an ICS feed (a CalDAV calendar's export or a private iCal link), or stop with `None`
+#[tauri::command]
+pub fn set_calendar_feed(app: AppHandle, url: Option<String>) -> Result<(), String> {
+    let state = app.state::<CalendarState>();
+    *state.feed.lock().map_err(|e| e.to_string())? = None;
+
+    let Some(url) = url else {
+        return secrets::delete(&app, &feed_secret());
+    };
+    let parsed = tauri::Url::parse(&url).map_err(|e| e.to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https" | "webcal") {
+        return Err(t!("error.unsupported_url", url = url));
+    }
+    secrets::set(&app, &feed_secret(), &url)
+}
+
+/// Whether an ICS feed is set; the link itself often carries a private token, so it stays in Rust
+#[tauri::command]
+pub fn has_calendar_feed(app: AppHandle) -> Result<bool, String> {
+    Ok(secrets::get(&app, &feed_secret())?.is_some())
+}
+
+/// Tell the windows about meetings starting within the lead time, once each
+async fn announce_meetings(app: &AppHandle) -> Result<(), String> {
+    let now = db::now_ms();
+    let events = upcoming(app, now, now + LEAD_TIME_MS, false).await?;
+
+    let state = app.state::<CalendarState>();
+    let mut announced = state.announced.lock().map_err(|e| e.to_string())?;
+    announced.retain(|_, start_ms| *start_ms >= now);
+
+    for event in events {
+        if event.all_day || event.start_ms < now {
+            continue;
+        }
+        let key = format!("{}@{}", event.id, event.start_ms);
+        if announced.insert(key, event.start_ms).is_some() {
+            continue;
+        }
+        // Already coaching, nothing to offer
+        if session::active_session_id(app).is_some() {
+            continue;
+        }
+
+        tracing::info!("Meeting starting in {} s", (event.start_ms - now) / 1000);
+        app.emit(
+            "meeting_starting_soon",
+            MeetingStartingSoon {
+                starts_in_ms: event.start_ms - now,
+                event,
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Events from every configured source overlapping `from_ms..to_ms`
+async fn upcoming(
+    app: &AppHandle,
+    from_ms: i64,
+    to_ms: i64,
+    request_access: bool,
+) -> Result<Vec<CalendarEvent>, String> {
+    let mut events = Vec::new();
+
+    #[cfg(target_os = "macos")]
+    events.extend(
+        tauri::async_runtime::spawn_blocking(move || system_events(from_ms, to_ms, request_access))
+            .await
+            .map_err(|e| e.to_string())??,
+    );
+    #[cfg(not(target_os = "macos"))]
+    let _ = request_access;
+
+    if let Some(url) = secrets::get(app, &feed_secret())? {
+        events.extend(feed_events(app, &url, from_ms, to_ms).await?);
+    }
+
+    events.sort_by_key(|event| event.start_ms);
+    Ok(events)
+}
+
+/// Keychain entry holding the feed link
+fn feed_secret() -> String {
+    format!("{}calendar_feed_url", secrets::INTERNAL_PREFIX)
+}
+
+/// Events from EventKit. Without access yet, asks for it when `request_access` is set;
+/// the answer arrives later, so the first call comes back empty.
+#[cfg(target_os = "macos")]
+fn system_events(
+    from_ms: i64,
+    to_ms: i64,
+    request_access: bool,
+) -> Result<Vec<CalendarEvent>, String> {
+    use block2::RcBlock;
+    use objc2::runtime::{Bool, NSObjectProtocol};
+    use objc2::sel;
+    use objc2_event_kit::{EKAuthorizationStatus, EKEntityType, EKEventStore};
+    use objc2_foundation::{NSDate, NSError};
+
+    unsafe {
+        let status = EKEventStore::authorizationStatusForEntityType(EKEntityType::Event);
+        if status == EKAuthorizationStatus::NotDetermined {
+            if request_access {
+                let store = EKEventStore::new();
+                // Holding the store keeps the request alive until it's answered
+                let keep_alive = store.clone();
+                let completion = RcBlock::new(move |granted: Bool, _error: *mut NSError| {
+                    let _ = &keep_alive;
+                    tracing::info!(granted = granted.as_bool(), "Calendar access answered");
+                });
+                // Full access is macOS 14+; older versions only have the entity type request
+                if store.respondsToSelector(sel!(requestFullAccessToEventsWithCompletion:)) {
+                    store.requestFullAccessToEventsWithCompletion(RcBlock::as_ptr(&completion));
+                } else {
+                    #[allow(deprecated)]
+                    store.requestAccessToEntityType_completion(
+                        EKEntityType::Event,
+                        RcBlock::as_ptr(&completion),
+                    );
+                }
+            }
+            return Ok(Vec::new());
+        }
+        // FullAccess shares its value with the pre-14 Authorized.
+        // Background checks skip a calendar the user chose not to share.
+        if status != EKAuthorizationStatus::FullAccess {
+            if !request_access {
+                return Ok(Vec::new());
+            }
+            return Err(t!("error.calendar_access_denied"));
+        }
+
+        let store = EKEventStore::new();
+        let start = NSDate::dateWithTimeIntervalSince1970(from_ms as f64 / 1000.0);
+        let end = NSDate::dateWithTimeIntervalSince1970(to_ms as f64 / 1000.0);
+        let predicate = store.predicateForEventsWithStartDate_endDate_calendars(&start, &end, None);
+
+        Ok(store
+            .eventsMatchingPredicate(&predicate)
+            .iter()
+            .map(|event| {
+                let location = event.location().map(|location| location.to_string());
+                let notes = event.notes().map(|notes| notes.to_string());
+                let url = event
+                    .URL()
+                    .and_then(|url| url.absoluteString())
+                    .map(|url| url.to_string());
+                CalendarEvent {
+                    id: event
+                        .eventIdentifier()
+                        .map(|id| id.to_string())
+                        .unwrap_or_default(),
+                    title: event.title().to_string(),
+                    start_ms: (event.startDate().timeIntervalSince1970() * 1000.0) as i64,
+                    end_ms: (event.endDate().timeIntervalSince1970() * 1000.0) as i64,
+                    all_day: event.isAllDay(),
+                    join_url: find_join_url(&[
+                        url.as_deref(),
+                        location.as_deref(),
+                        notes.as_deref(),
+                    ]),
+                    location,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Occurrences from the ICS feed, downloading it again when the cached copy is stale
+async fn feed_events(
+    app: &AppHandle,
+    url: &str,
+    from_ms: i64,
+    to_ms: i64,
+) -> Result<Vec<CalendarEvent>, String> {
+    let state = app.state::<CalendarState>();
+    let fresh = state
+        .feed
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .is_some_and(|feed| feed.url == url && feed.fetched.elapsed() < FEED_REFRESH_INTERVAL);
+
+    if !fresh {
+        // webcal:// is plain HTTPS with a scheme calendar apps register for
+        let download_url = match url.strip_prefix("webcal://") {
+            Some(rest) => format!("https://{}", rest),
+            None => url.to_string(),
+        };
+        let text = tauri_plugin_http::reqwest::get(download_url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+        *state.feed.lock().map_err(|e| e.to_string())? = Some(CachedFeed {
+            url: url.to_string(),
+            fetched: Instant::now(),
+            events: parse_feed(&text),
+        });
+    }
+
+    let feed = state.feed.lock().map_err(|e| e.to_string())?;
+    let Some(feed) = feed.as_ref() else {
+        return Ok(Vec::new());
+    };
+    Ok(feed
+        .events
+        .iter()
+        .flat_map(|event| {
+            occurrences(event, from_ms, to_ms)
+                .into_iter()
+                .map(|start_ms| CalendarEvent {
+                    id: event.uid.clone(),
+                    title: event.title.clone(),
+                    start_ms,
+                    end_ms: start_ms + event.duration_ms,
+                    all_day: event.all_day,
+                    location: event.location.clone(),
+                    join_url: event.join_url.clone(),
+                })
+        })
+        .collect())
+}
+
+/// The VEVENTs of an iCalendar file
+fn parse_feed(text: &str) -> Vec<FeedEvent> {
+    // Long lines are folded onto continuation lines starting with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    // Property name to its parameters and value, first one wins
+    let mut events = Vec::new();
+    let mut current: Option<HashMap<&str, (&str, &str)>> = None;
+    for line in &lines {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = head.split_once(';').unwrap_or((head, ""));
+        if name == "BEGIN" && value == "VEVENT" {
+            current = Some(HashMap::new());
+        } else if name == "END" && value == "VEVENT" {
+            events.extend(current.take().and_then(|props| feed_event(&props)));
+        } else if let Some(props) = current.as_mut() {
+            props.entry(name).or_insert((params, value));
+        }
+    }
+
+    // A VEVENT with a RECURRENCE-ID replaces that occurrence of its series
+    let moved: Vec<(String, i64)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.clone(), event.recurrence_id?)))
+        .collect();
+    for event in events.iter_mut().filter(|event| event.rule.is_some()) {
+        event.excluded.extend(
+            moved
+                .iter()
+                .filter(|(uid, _)| *uid == event.uid)
+                .map(|(_, start_ms)| *start_ms),
+        );
+    }
+    events
+}
+
+fn feed_event(props: &HashMap<&str, (&str, &str)>) -> Option<FeedEvent> {
+    if props
+        .get("STATUS")
+        .is_some_and(|(_, status)| *status == "CANCELLED")
+    {
+        return None;
+    }
+
+    let (start_params, start) = props.get("DTSTART")?;
+    let start_ms = parse_time(start_params, start)?;
+    let all_day = !start.contains('T');
+    let duration_ms = match (props.get("DTEND"), props.get("DURATION")) {
+        (Some((params, end)), _) => parse_time(params, end)? - start_ms,
+        (None, Some((_, duration))) => parse_duration(duration)?,
+        // Without an end, dates last the day and times are instants
+        (None, None) if all_day => DAY_MS,
+        (None, None) => 0,
+    };
+
+    let text = |name: &str| props.get(name).map(|(_, value)| unescape(value));
+    let location = text("LOCATION");
+    let join_url = find_join_url(&[
+        text("URL").as_deref(),
+        location.as_deref(),
+        text("DESCRIPTION").as_deref(),
+    ]);
+
+    let excluded = props
+        .get("EXDATE")
+        .map(|(params, dates)| {
+            dates
+                .split(',')
+                .filter_map(|date| parse_time(params, date))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(FeedEvent {
+        uid: text("UID").unwrap_or_default(),
+        title: text("SUMMARY").unwrap_or_default(),
+        start_ms,
+        duration_ms,
+        all_day,
+        location,
+        join_url,
+        rule: props
+            .get("RRULE")
+            .and_then(|(_, rule)| parse_rule(rule, start_ms)),
+        excluded,
+        recurrence_id: props
+            .get("RECURRENCE-ID")
+            .and_then(|(params, original)| parse_time(params, original)),
+    })
+}
+
+/// `20250106T090000Z`, a time in a TZID, a floating local time, or a `20250106` date
+fn parse_time(params: &str, value: &str) -> Option<i64> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(time.and_utc().timestamp_millis());
+    }
+
+    let time = match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Ok(time) => time,
+        Err(_) => NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?,
+    };
+    // Outlook names zones its own way; those fall back to local time
+    let zone = params
+        .split(';')
+        .find_map(|param| param.strip_prefix("TZID="))
+        .and_then(|zone| zone.trim_matches('"').parse::<chrono_tz::Tz>().ok());
+    match zone {
+        Some(zone) => zone
+            .from_local_datetime(&time)
+            .earliest()
+            .map(|time| time.timestamp_millis()),
+        None => Local
+            .from_local_datetime(&time)
+            .earliest()
+            .map(|time| time.timestamp_millis()),
+    }
+}
+
+/// `P1DT2H30M`-style durations, with days and weeks as fixed lengths
+fn parse_duration(value: &str) -> Option<i64> {
+    let mut total = 0;
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'T' => continue,
+            'W' => 7 * DAY_MS,
+            'D' => DAY_MS,
+            'H' => 60 * 60 * 1000,
+            'M' => 60 * 1000,
+            'S' => 1000,
+            _ => return None,
+        };
+        total += number.parse::<i64>().ok()? * unit;
+        number.clear();
+    }
+    Some(total)
+}
+
+fn parse_rule(rule: &str, start_ms: i64) -> Option<Rule> {
+    let parts: HashMap<&str, &str> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect();
+    let weekly = match parts.get("FREQ").copied() {
+        Some("DAILY") => false,
+        Some("WEEKLY") => true,
+        _ => return None,
+    };
+
+    let mut weekdays: Vec<Weekday> = parts
+        .get("BYDAY")
+        .map(|days| {
+            days.split(',')
+                // Ordinals like 1MO only mean something for monthly rules
+                .filter_map(|day| match day.get(day.len().saturating_sub(2)..) {
+                    Some("MO") => Some(Weekday::Mon),
+                    Some("TU") => Some(Weekday::Tue),
+                    Some("WE") => Some(Weekday::Wed),
+                    Some("TH") => Some(Weekday::Thu),
+                    Some("FR") => Some(Weekday::Fri),
+                    Some("SA") => Some(Weekday::Sat),
+                    Some("SU") => Some(Weekday::Sun),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if weekly && weekdays.is_empty() {
+        weekdays.push(Local.timestamp_millis_opt(start_ms).single()?.weekday());
+    }
+
+    Some(Rule {
+        weekly,
+        interval: parts
+            .get("INTERVAL")
+            .and_then(|interval| interval.parse().ok())
+            .filter(|interval| *interval > 0)
+            .unwrap_or(1),
+        until_ms: parts.get("UNTIL").and_then(|until| parse_time("", until)),
+        count: parts.get("COUNT").and_then(|count| count.parse().ok()),
+        weekdays,
+    })
+}
+
+/// Starts of an event's occurrences overlapping `from_ms..to_ms`, stepping a local day at a time
+fn occurrences(event: &FeedEvent, from_ms: i64, to_ms: i64) -> Vec<i64> {
+    let overlaps = |start_ms: i64| start_ms <= to_ms && start_ms + event.duration_ms >= from_ms;
+    let Some(rule) = &event.rule else {
+        return [event.start_ms]
+            .into_iter()
+            .filter(|start_ms| overlaps(*start_ms) && !event.excluded.contains(start_ms))
+            .collect();
+    };
+    let Some(first) = Local.timestamp_millis_opt(event.start_ms).single() else {
+        return Vec::new();
+    };
+
+    let mut starts = Vec::new();
+    let mut count = 0;
+    for day in 0.. {
+        let Some(time) = first.naive_local().checked_add_days(Days::new(day)) else {
+            break;
+        };
+        // Skipped by a DST change
+        let Some(start) = Local.from_local_datetime(&time).earliest() else {
+            continue;
+        };
+        let start_ms = start.timestamp_millis();
+        if start_ms > to_ms
+            || rule.until_ms.is_some_and(|until_ms| start_ms > until_ms)
+            || rule.count.is_some_and(|max| count >= max)
+        {
+            break;
+        }
+
+        let matches = if rule.weekly {
+            (day / 7) % rule.interval == 0 && rule.weekdays.contains(&start.weekday())
+        } else {
+            day % rule.interval == 0
+        };
+        if !matches {
+            continue;
+        }
+        count += 1;
+        if overlaps(start_ms) && !event.excluded.contains(&start_ms) {
+            starts.push(start_ms);
+        }
+    }
+    starts
+}
+
+/// The first video call link in any of the fields
+fn find_join_url(fields: &[Option<&str>]) -> Option<String> {
+    fields
+        .iter()
+        .flatten()
+        .flat_map(|field| field.split_whitespace())
+        .map(|word| word.trim_matches(|c: char| matches!(c, '<' | '>' | '(' | ')' | '"' | ',')))
+        .filter_map(|word| tauri::Url::parse(word).ok())
+        .find(|url| {
+            url.scheme() == "https"
+                && url.host_str().is_some_and(|host| {
+                    MEETING_HOSTS
+                        .iter()
+                        .any(|meeting| host == *meeting || host.ends_with(&format!(".{}", meeting)))
+                })
+        })
+        .map(String::from)
+}
+
+/// Undo iCalendar text escaping
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
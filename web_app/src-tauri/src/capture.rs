@@ -0,0 +1,216 @@
+// Queen Mama LITE - Screen Capture
+// Grabs screenshots of a monitor, a window or a region for the AI assist flow
+
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use image::{ImageFormat, RgbaImage};
+use xcap::{Monitor, Window};
+
+use crate::i18n::t;
+
+/// What to capture; `screen` is the primary monitor
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CaptureTarget {
+    Screen,
+    #[serde(rename_all = "camelCase")]
+    Monitor { monitor_id: u32 },
+    #[serde(rename_all = "camelCase")]
+    Window { window_id: u32 },
+    /// Rectangle in the monitor's physical pixels, e.g. drawn by the user over the screen
+    #[serde(rename_all = "camelCase")]
+    Region {
+        monitor_id: Option<u32>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureOutput {
+    /// PNG bytes returned inline
+    #[default]
+    Bytes,
+    /// PNG written to a temp file, for large screenshots
+    File,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Screenshot {
+    width: u32,
+    height: u32,
+    png: Option<Vec<u8>>,
+    path: Option<PathBuf>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureTargets {
+    monitors: Vec<MonitorInfo>,
+    windows: Vec<WindowInfo>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    id: u32,
+    name: String,
+    width: u32,
+    height: u32,
+    is_primary: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    id: u32,
+    title: String,
+    app_name: String,
+}
+
+/// List monitors and visible windows that can be captured
+#[tauri::command]
+pub async fn list_capture_targets() -> Result<CaptureTargets, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let monitors = Monitor::all()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .filter_map(|monitor| {
+                Some(MonitorInfo {
+                    id: monitor.id().ok()?,
+                    name: monitor.name().unwrap_or_default(),
+                    width: monitor.width().ok()?,
+                    height: monitor.height().ok()?,
+                    is_primary: monitor.is_primary().unwrap_or(false),
+                })
+            })
+            .collect();
+
+        let windows = Window::all()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .filter(|window| !window.is_minimized().unwrap_or(true))
+            .filter_map(|window| {
+                Some(WindowInfo {
+                    id: window.id().ok()?,
+                    title: window.title().unwrap_or_default(),
+                    app_name: window.app_name().unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        Ok(CaptureTargets { monitors, windows })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Capture a screenshot as PNG, returned inline or as a temp file path
+#[tauri::command]
+pub async fn capture_screen(
+    target: CaptureTarget,
+    output: Option<CaptureOutput>,
+) -> Result<Screenshot, String> {
+    let output = output.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let image = capture(target)?;
+        let png = encode_png(&image)?;
+
+        let (png, path) = match output {
+            CaptureOutput::Bytes => (Some(png), None),
+            CaptureOutput::File => {
+                let dir = std::env::temp_dir().join("queen-mama-captures");
+                fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                let path = dir.join(format!("{}.png", uuid::Uuid::new_v4()));
+                fs::write(&path, &png).map_err(|e| e.to_string())?;
+                (None, Some(path))
+            }
+        };
+
+        Ok(Screenshot {
+            width: image.width(),
+            height: image.height(),
+            png,
+            path,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub(crate) fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+/// Whether macOS lets us record the screen; other platforms don't ask
+pub fn has_screen_recording_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGPreflightScreenCaptureAccess() -> bool;
+        }
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    true
+}
+
+pub(crate) fn capture(target: CaptureTarget) -> Result<RgbaImage, String> {
+    match target {
+        CaptureTarget::Screen => capture_monitor(None),
+        CaptureTarget::Monitor { monitor_id } => capture_monitor(Some(monitor_id)),
+        CaptureTarget::Window { window_id } => Window::all()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|window| window.id().ok() == Some(window_id))
+            .ok_or_else(|| t!("error.window_not_found", id = window_id))?
+            .capture_image()
+            .map_err(|e| e.to_string()),
+        CaptureTarget::Region {
+            monitor_id,
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let image = capture_monitor(monitor_id)?;
+            if width == 0
+                || height == 0
+                || x.saturating_add(width) > image.width()
+                || y.saturating_add(height) > image.height()
+            {
+                return Err(t!("error.region_outside_monitor"));
+            }
+            Ok(image::imageops::crop_imm(&image, x, y, width, height).to_image())
+        }
+    }
+}
+
+/// Capture a monitor by id, or the primary monitor
+fn capture_monitor(id: Option<u32>) -> Result<RgbaImage, String> {
+    let monitors = Monitor::all().map_err(|e| e.to_string())?;
+    let monitor = match id {
+        Some(id) => monitors
+            .into_iter()
+            .find(|monitor| monitor.id().ok() == Some(id))
+            .ok_or_else(|| t!("error.monitor_not_found", id = id))?,
+        None => monitors
+            .into_iter()
+            .find(|monitor| monitor.is_primary().unwrap_or(false))
+            .ok_or_else(|| t!("error.no_primary_monitor"))?,
+    };
+
+    monitor.capture_image().map_err(|e| e.to_string())
+}
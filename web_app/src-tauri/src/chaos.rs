@@ -0,0 +1,119 @@
+// Queen Mama LITE - Chaos Mode
+// Injects faults on demand so recovery paths can be exercised without waiting for the real failure
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::db::Database;
+use crate::i18n::t;
+use crate::{audio, SETTINGS_STORE};
+
+/// Developer setting that allows `inject_fault`
+pub(crate) const CHAOS_MODE_STORE_KEY: &str = "chaos_mode";
+
+/// How long an injected database lock holds
+const DATABASE_LOCK_DURATION: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Fault {
+    /// Every capture loses its device, as if it had been unplugged
+    AudioDeviceLost,
+    /// Local transcription stops mid-stream
+    TranscriptionCrash,
+    /// The next AI request is rate limited
+    AiRateLimit,
+    /// Database calls fail as busy for a while
+    DatabaseLocked,
+}
+
+#[derive(Default)]
+pub struct ChaosState {
+    /// Faults waiting for the next call they apply to
+    pending: Mutex<HashSet<Fault>>,
+}
+
+pub fn setup_chaos(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(ChaosState::default());
+    Ok(())
+}
+
+/// Whether faults can be injected
+#[tauri::command]
+pub fn get_chaos_mode(app: AppHandle) -> bool {
+    is_enabled(&app)
+}
+
+/// Allow or forbid injecting faults
+#[tauri::command]
+pub fn set_chaos_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(CHAOS_MODE_STORE_KEY, enabled);
+    store.save().map_err(|e| e.to_string())?;
+
+    if !enabled {
+        if let Ok(mut pending) = app.state::<ChaosState>().pending.lock() {
+            pending.clear();
+        }
+    }
+    tracing::info!("Chaos mode {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/// Break something on purpose; only works with chaos mode on
+#[tauri::command]
+pub fn inject_fault(
+    app: AppHandle,
+    state: State<'_, ChaosState>,
+    kind: Fault,
+) -> Result<(), String> {
+    if !is_enabled(&app) {
+        return Err(t!("error.chaos_mode_off"));
+    }
+
+    tracing::info!("Injecting fault {:?}", kind);
+    match kind {
+        Fault::AudioDeviceLost => audio::lose_devices(&app),
+        #[cfg(desktop)]
+        Fault::TranscriptionCrash => crate::transcription::crash(&app),
+        #[cfg(mobile)]
+        Fault::TranscriptionCrash => Err(t!("feature.desktop_only")),
+        Fault::AiRateLimit => {
+            state
+                .pending
+                .lock()
+                .map_err(|e| e.to_string())?
+                .insert(kind);
+            Ok(())
+        }
+        Fault::DatabaseLocked => {
+            app.state::<Database>().lock_for(DATABASE_LOCK_DURATION);
+            Ok(())
+        }
+    }
+}
+
+/// Whether a pending fault should hit this call, clearing it
+pub fn take(app: &AppHandle, fault: Fault) -> bool {
+    app.try_state::<ChaosState>()
+        .and_then(|state| {
+            state
+                .pending
+                .lock()
+                .ok()
+                .map(|mut pending| pending.remove(&fault))
+        })
+        .unwrap_or(false)
+}
+
+fn is_enabled(app: &AppHandle) -> bool {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(CHAOS_MODE_STORE_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
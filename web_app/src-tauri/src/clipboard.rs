@@ -0,0 +1,211 @@
+// Queen Mama LITE - Clipboard
+// Keeps a history of copied text for use as context, and pastes suggested responses into the focused app
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use rdev::{EventType, Key};
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::db;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+
+/// How often the clipboard is checked while watching
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Copies kept in the history, oldest dropped first
+const HISTORY_LIMIT: usize = 50;
+
+/// Gives the user time to let go of the shortcut's modifiers, which would otherwise join the paste
+const PASTE_DELAY: Duration = Duration::from_millis(200);
+
+/// Some apps drop synthetic keys sent back to back
+const KEY_DELAY: Duration = Duration::from_millis(20);
+
+/// The focused app reads the clipboard after the keystroke lands, so it's restored only after this
+const RESTORE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+pub struct ClipboardState {
+    /// Bumped whenever watching is started or stopped so the old watcher exits
+    generation: AtomicU64,
+    /// Copies seen while watching, newest first
+    history: Mutex<VecDeque<ClipboardEntry>>,
+    /// Clipboard text as last seen, so our own pastes don't count as copies
+    last: Mutex<Option<String>>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardEntry {
+    text: String,
+    copied_at: i64,
+}
+
+pub fn setup_clipboard(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(ClipboardState::default());
+    Ok(())
+}
+
+/// Get the text on the clipboard, if it holds any
+#[tauri::command]
+pub fn get_clipboard_text(app: AppHandle) -> Option<String> {
+    read_text(&app)
+}
+
+/// Get the text copied since watching started, newest first
+#[tauri::command]
+pub fn get_clipboard_history(
+    state: State<'_, ClipboardState>,
+) -> Result<Vec<ClipboardEntry>, String> {
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(history.iter().cloned().collect())
+}
+
+/// Emit `clipboard_changed` and keep a history whenever the user copies text
+#[tauri::command]
+pub fn watch_clipboard(
+    app: AppHandle,
+    state: State<'_, ClipboardState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if enabled {
+        *state.last.lock().map_err(|e| e.to_string())? = read_text(&app);
+        thread::spawn(move || watch(app, generation));
+    } else {
+        state.history.lock().map_err(|e| e.to_string())?.clear();
+    }
+    Ok(())
+}
+
+/// Paste text into the focused app through the clipboard, then put back what was there
+#[tauri::command]
+pub async fn paste_text(app: AppHandle, text: String) -> Result<(), String> {
+    if is_own_window_focused(&app) {
+        return Err(t!("error.paste_into_self"));
+    }
+    if !can_send_keys() {
+        return Err(t!("error.paste_needs_accessibility"));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || paste(&app, text))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn paste(app: &AppHandle, text: String) -> Result<(), String> {
+    let state = app.state::<ClipboardState>();
+    let previous = read_text(app);
+
+    *state.last.lock().map_err(|e| e.to_string())? = Some(text.clone());
+    app.clipboard()
+        .write_text(text.clone())
+        .map_err(|e| e.to_string())?;
+
+    thread::sleep(PASTE_DELAY);
+    let result = send_paste();
+    thread::sleep(RESTORE_DELAY);
+
+    // Leave the clipboard alone if something else was copied in the meantime. Only text
+    // can be put back; anything else that was there is lost.
+    if let Some(previous) = previous {
+        if read_text(app).as_deref() == Some(text.as_str()) {
+            *state.last.lock().map_err(|e| e.to_string())? = Some(previous.clone());
+            if let Err(e) = app.clipboard().write_text(previous) {
+                tracing::warn!("Failed to restore the clipboard: {}", e);
+            }
+        }
+    }
+    result
+}
+
+/// Press Cmd+V on macOS, Ctrl+V elsewhere
+fn send_paste() -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let modifier = Key::MetaLeft;
+    #[cfg(not(target_os = "macos"))]
+    let modifier = Key::ControlLeft;
+
+    for event in [
+        EventType::KeyPress(modifier),
+        EventType::KeyPress(Key::KeyV),
+        EventType::KeyRelease(Key::KeyV),
+        EventType::KeyRelease(modifier),
+    ] {
+        rdev::simulate(&event).map_err(|_| t!("error.paste_failed"))?;
+        thread::sleep(KEY_DELAY);
+    }
+    Ok(())
+}
+
+fn watch(app: AppHandle, generation: u64) {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let state = app.state::<ClipboardState>();
+        if state.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+
+        let Some(text) = read_text(&app) else {
+            continue;
+        };
+        {
+            let Ok(mut last) = state.last.lock() else {
+                return;
+            };
+            if last.as_deref() == Some(text.as_str()) {
+                continue;
+            }
+            *last = Some(text.clone());
+        }
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let entry = ClipboardEntry {
+            text,
+            copied_at: db::now_ms(),
+        };
+        if let Ok(mut history) = state.history.lock() {
+            history.push_front(entry.clone());
+            history.truncate(HISTORY_LIMIT);
+        }
+        if let Err(e) = app.emit("clipboard_changed", entry) {
+            tracing::error!("Failed to emit event: {}", e);
+        }
+    }
+}
+
+/// One of our windows has focus, so keys sent now would land in the app itself
+pub(crate) fn is_own_window_focused(app: &AppHandle) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false))
+}
+
+/// Reading fails when the clipboard is empty or holds something other than text
+fn read_text(app: &AppHandle) -> Option<String> {
+    app.clipboard().read_text().ok()
+}
+
+/// Synthetic keys are dropped without a word unless the app is trusted for Accessibility
+#[cfg(target_os = "macos")]
+pub(crate) fn can_send_keys() -> bool {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+    unsafe { AXIsProcessTrusted() }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn can_send_keys() -> bool {
+    true
+}
@@ -0,0 +1,260 @@
+// Queen Mama LITE - Audio Clips
+// Cuts a stretch of a session into a WAV of its recordings mixed together, with the transcript
+// of that stretch alongside, for sharing a key moment with a mentee
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::{AppHandle, Manager};
+
+use crate::audio;
+use crate::db::{self, Database, SessionRecording};
+use crate::export::format_offset;
+use crate::i18n::t;
+use crate::vault;
+
+/// Clips of a session without recordings are written at this rate
+const DEFAULT_SAMPLE_RATE: u32 = 16_000;
+
+/// Longer clips are refused; a whole session can be exported instead
+const MAX_CLIP_MS: i64 = 30 * 60 * 1000;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Clip {
+    path: PathBuf,
+    /// The transcript of the clip, as Markdown
+    transcript_path: PathBuf,
+    duration_ms: i64,
+    segments: usize,
+}
+
+/// A transcript segment, timed from the start of a clip
+pub(crate) struct ClipSegment {
+    pub speaker: Option<String>,
+    pub text: String,
+    pub start_ms: i64,
+}
+
+/// Save `start_ms` to `end_ms` of a session's timeline as a WAV at `path`, its recordings mixed
+/// to one channel, and the transcript of that stretch next to it as Markdown
+#[tauri::command]
+pub async fn extract_clip(
+    app: AppHandle,
+    session_id: String,
+    start_ms: i64,
+    end_ms: i64,
+    path: PathBuf,
+) -> Result<Clip, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let clip = extract(&app, &session_id, start_ms, end_ms, &path);
+        if clip.is_err() {
+            let _ = fs::remove_file(&path);
+        }
+        clip
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn extract(
+    app: &AppHandle,
+    session_id: &str,
+    start_ms: i64,
+    end_ms: i64,
+    path: &Path,
+) -> Result<Clip, String> {
+    if start_ms < 0 || end_ms <= start_ms || end_ms - start_ms > MAX_CLIP_MS {
+        return Err(t!("error.clip_range_invalid", max = MAX_CLIP_MS / 60_000));
+    }
+
+    let db = app.state::<Database>();
+    let (title, recordings, segments) = {
+        let connection = db.connection()?;
+        let title: String = connection
+            .query_row(
+                "SELECT title FROM sessions WHERE id = ?1",
+                params![session_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| t!("error.session_not_found", id = session_id))?;
+        let recordings =
+            db::session_recordings(&connection, session_id).map_err(|e| e.to_string())?;
+        let segments = transcript_slice(&connection, session_id, start_ms, end_ms)
+            .map_err(|e| e.to_string())?;
+        (title, recordings, segments)
+    };
+
+    let sample_rate = output_rate(&recordings);
+    let samples = mix(app, &recordings, start_ms, end_ms, sample_rate)?;
+    write_wav(app, path, sample_rate, &samples)?;
+
+    let transcript_path = path.with_extension("md");
+    let mut markdown = format!(
+        "# {}\n\n{}\n\n",
+        title,
+        t!(
+            "clip.heading",
+            start = format_offset(start_ms),
+            end = format_offset(end_ms)
+        )
+    );
+    markdown.push_str(&transcript_markdown(&segments));
+    vault::write(app, &transcript_path, markdown)?;
+
+    tracing::info!(
+        path = ?path,
+        "Extracted {} ms of session {}",
+        end_ms - start_ms,
+        session_id
+    );
+    Ok(Clip {
+        path: path.to_path_buf(),
+        transcript_path,
+        duration_ms: end_ms - start_ms,
+        segments: segments.len(),
+    })
+}
+
+/// The highest rate among the recordings, so none of them loses quality in the mix
+pub(crate) fn output_rate(recordings: &[SessionRecording]) -> u32 {
+    recordings
+        .iter()
+        .map(|recording| recording.sample_rate)
+        .max()
+        .unwrap_or(DEFAULT_SAMPLE_RATE)
+}
+
+/// Mix the recordings under `start_ms` to `end_ms` of the session's timeline into one channel
+/// at `sample_rate`; stretches no recording covers are silent
+pub(crate) fn mix(
+    app: &AppHandle,
+    recordings: &[SessionRecording],
+    start_ms: i64,
+    end_ms: i64,
+    sample_rate: u32,
+) -> Result<Vec<i16>, String> {
+    let mut mixed = vec![0i32; ms_to_samples(end_ms - start_ms, sample_rate)];
+    for recording in recordings {
+        let from_ms = start_ms.max(recording.offset_ms);
+        let to_ms = end_ms.min(recording.offset_ms + recording.duration_ms);
+        if from_ms >= to_ms {
+            continue;
+        }
+
+        let samples = audio::read_recording_range(
+            app,
+            &recording.path,
+            ms_to_samples(from_ms - recording.offset_ms, recording.sample_rate) as u32,
+            ms_to_samples(to_ms - from_ms, recording.sample_rate),
+        )?;
+
+        let at = ms_to_samples(from_ms - start_ms, sample_rate);
+        for (mixed, sample) in mixed[at.min(mixed.len())..].iter_mut().zip(resample(
+            &samples,
+            recording.sample_rate,
+            sample_rate,
+        )) {
+            *mixed += sample as i32;
+        }
+    }
+    Ok(mixed
+        .into_iter()
+        .map(|sample| sample.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+        .collect())
+}
+
+/// Linear resample of 16-bit PCM between any two rates
+pub(crate) fn resample(samples: &[i16], from_rate: u32, to_rate: u32) -> Vec<i16> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = (position as usize).min(samples.len() - 1);
+            let current = samples[index] as f64;
+            let next = samples.get(index + 1).map_or(current, |next| *next as f64);
+            (current + (next - current) * (position - index as f64)) as i16
+        })
+        .collect()
+}
+
+/// Write a clip or reel, encrypted like the profile's recordings if it has a passphrase
+pub(crate) fn write_wav(
+    app: &AppHandle,
+    path: &Path,
+    sample_rate: u32,
+    samples: &[i16],
+) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer =
+        hound::WavWriter::new(vault::create(app, path)?, spec).map_err(|e| e.to_string())?;
+    for sample in samples {
+        writer.write_sample(*sample).map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+/// Segments overlapping `start_ms` to `end_ms`, timed from `start_ms`
+pub(crate) fn transcript_slice(
+    connection: &Connection,
+    session_id: &str,
+    start_ms: i64,
+    end_ms: i64,
+) -> rusqlite::Result<Vec<ClipSegment>> {
+    connection
+        .prepare(
+            "SELECT speaker, text, start_ms FROM transcript_segments
+             WHERE session_id = ?1 AND start_ms < ?3 AND end_ms > ?2 ORDER BY start_ms, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id, start_ms, end_ms], |row| {
+                    Ok(ClipSegment {
+                        speaker: row.get(0)?,
+                        text: row.get(1)?,
+                        start_ms: (row.get::<_, i64>(2)? - start_ms).max(0),
+                    })
+                })?
+                .collect()
+        })
+}
+
+/// "- [0:05] me: text" per segment
+pub(crate) fn transcript_markdown(segments: &[ClipSegment]) -> String {
+    let mut markdown = String::new();
+    for segment in segments {
+        let _ = match &segment.speaker {
+            Some(speaker) => writeln!(
+                markdown,
+                "- [{}] {}: {}",
+                format_offset(segment.start_ms),
+                speaker,
+                segment.text
+            ),
+            None => writeln!(
+                markdown,
+                "- [{}] {}",
+                format_offset(segment.start_ms),
+                segment.text
+            ),
+        };
+    }
+    markdown
+}
+
+fn ms_to_samples(ms: i64, sample_rate: u32) -> usize {
+    (ms.max(0) as u64 * sample_rate as u64 / 1000) as usize
+}
@@ -0,0 +1,348 @@
+// Queen Mama LITE - Compliance Alerts
+// Flags prohibited phrases (guarantees, unlicensed financial advice, profanity) live and in the post-session report
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use rusqlite::{params, OptionalExtension};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::db::Database;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::nudge;
+use crate::relay::RelayState;
+use crate::vault;
+use crate::SETTINGS_STORE;
+
+pub(crate) const COMPLIANCE_STORE_KEY: &str = "compliance";
+
+/// Nudge rule fired on high-severity hits, so the coach can correct themselves mid-call
+pub const COMPLIANCE_NUDGE_RULE: &str = "compliance";
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    fn label(self) -> String {
+        match self {
+            Severity::Low => t!("compliance.severity_low"),
+            Severity::Medium => t!("compliance.severity_medium"),
+            Severity::High => t!("compliance.severity_high"),
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceRule {
+    id: String,
+    /// Matched case-insensitively on whole words, ignoring punctuation
+    phrase: String,
+    severity: Severity,
+    /// Grouping for the summary, e.g. "guarantee" or "financial advice"
+    category: String,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceConfig {
+    enabled: bool,
+    rules: Vec<ComplianceRule>,
+}
+
+impl Default for ComplianceConfig {
+    fn default() -> Self {
+        // Built-in rules get fixed ids so alerts stay linked to them before the config is first saved
+        let rule = |phrase: &str, severity, category: &str| ComplianceRule {
+            id: format!("default.{}", normalize(phrase).replace([' ', '\''], "_")),
+            phrase: phrase.to_string(),
+            severity,
+            category: category.to_string(),
+        };
+        Self {
+            enabled: false,
+            rules: vec![
+                rule("guaranteed returns", Severity::High, "guarantee"),
+                rule("i guarantee", Severity::High, "guarantee"),
+                rule("risk free", Severity::High, "guarantee"),
+                rule("can't lose", Severity::High, "guarantee"),
+                rule("you should invest in", Severity::High, "financial advice"),
+                rule("you should buy", Severity::Medium, "financial advice"),
+                rule("you should sell", Severity::Medium, "financial advice"),
+                rule("this is financial advice", Severity::Medium, "financial advice"),
+                rule("promise you", Severity::Low, "guarantee"),
+                rule("fuck", Severity::Low, "profanity"),
+                rule("shit", Severity::Low, "profanity"),
+            ],
+        }
+    }
+}
+
+/// Emitted as `compliance_alert` when a live segment matches a rule
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceHit {
+    rule_id: String,
+    phrase: String,
+    severity: Severity,
+    category: String,
+    session_id: String,
+    segment_id: i64,
+    speaker: Option<String>,
+    text: String,
+    start_ms: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceReport {
+    session_id: String,
+    hits: Vec<ComplianceHit>,
+    low: usize,
+    medium: usize,
+    high: usize,
+}
+
+/// Get compliance mode and its phrase rules
+#[tauri::command]
+pub fn get_compliance_config(app: AppHandle) -> ComplianceConfig {
+    load(&app)
+}
+
+/// Turn compliance mode on or off and replace its rules
+#[tauri::command]
+pub fn set_compliance_config(app: AppHandle, mut config: ComplianceConfig) -> Result<(), String> {
+    config.rules.retain(|rule| !normalize(&rule.phrase).is_empty());
+    for rule in &mut config.rules {
+        if rule.id.is_empty() {
+            rule.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        COMPLIANCE_STORE_KEY,
+        serde_json::to_value(&config).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Check a whole session's transcript against the current rules
+#[tauri::command]
+pub fn get_compliance_report(
+    app: AppHandle,
+    db: State<'_, Database>,
+    session_id: String,
+) -> Result<ComplianceReport, String> {
+    report(&app, &db, &session_id)
+}
+
+/// Write a session's compliance report as Markdown
+#[tauri::command]
+pub fn export_compliance_summary(
+    app: AppHandle,
+    db: State<'_, Database>,
+    session_id: String,
+    path: PathBuf,
+) -> Result<String, String> {
+    let title: String = db
+        .connection()?
+        .query_row(
+            "SELECT title FROM sessions WHERE id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.session_not_found", id = session_id))?;
+    let report = report(&app, &db, &session_id)?;
+
+    let mut markdown = format!(
+        "# {}\n\n{}\n",
+        t!("compliance.heading", title = title),
+        t!(
+            "compliance.counts",
+            high = report.high,
+            medium = report.medium,
+            low = report.low
+        )
+    );
+    if report.hits.is_empty() {
+        let _ = write!(markdown, "\n{}\n", t!("compliance.no_hits"));
+    } else {
+        let columns = [
+            "compliance.column_time",
+            "compliance.column_severity",
+            "compliance.column_category",
+            "compliance.column_phrase",
+            "compliance.column_speaker",
+            "compliance.column_said",
+        ]
+        .map(|key| t!(key));
+        let _ = writeln!(markdown, "\n| {} |", columns.join(" | "));
+        markdown.push_str("|---|---|---|---|---|---|\n");
+        for hit in &report.hits {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} | {} | {} | {} | {} |",
+                format_offset(hit.start_ms),
+                hit.severity.label(),
+                hit.category,
+                hit.phrase,
+                hit.speaker.as_deref().unwrap_or("–"),
+                hit.text.replace('|', "\\|").replace('\n', " ")
+            );
+        }
+    }
+
+    vault::write(&app, &path, markdown)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Check a newly saved segment and raise an alert for each rule it breaks
+pub fn scan_segment(
+    app: &AppHandle,
+    session_id: &str,
+    segment_id: i64,
+    speaker: Option<&str>,
+    text: &str,
+    start_ms: i64,
+) {
+    let config = load(app);
+    if !config.enabled {
+        return;
+    }
+
+    let hits = matches(&config.rules, text);
+    for rule in &hits {
+        let hit = ComplianceHit {
+            rule_id: rule.id.clone(),
+            phrase: rule.phrase.clone(),
+            severity: rule.severity,
+            category: rule.category.clone(),
+            session_id: session_id.to_string(),
+            segment_id,
+            speaker: speaker.map(str::to_string),
+            text: text.to_string(),
+            start_ms,
+        };
+        if let Err(e) = app.emit("compliance_alert", &hit) {
+            tracing::error!("Failed to emit alert: {}", e);
+        }
+    }
+
+    if hits.iter().any(|rule| rule.severity == Severity::High) {
+        if let Some(relay) = app.try_state::<RelayState>() {
+            if let Err(e) = nudge::fire(app, &relay, COMPLIANCE_NUDGE_RULE) {
+                tracing::error!("Failed to send nudge: {}", e);
+            }
+        }
+    }
+}
+
+/// Where in a session its compliance hits are, for picking out its highlights
+pub(crate) fn hit_offsets(
+    app: &AppHandle,
+    db: &Database,
+    session_id: &str,
+) -> Result<Vec<i64>, String> {
+    Ok(report(app, db, session_id)?
+        .hits
+        .iter()
+        .map(|hit| hit.start_ms)
+        .collect())
+}
+
+fn report(app: &AppHandle, db: &Database, session_id: &str) -> Result<ComplianceReport, String> {
+    let rules = load(app).rules;
+    let segments = db
+        .connection()?
+        .prepare(
+            "SELECT id, speaker, text, start_ms FROM transcript_segments
+             WHERE session_id = ?1 ORDER BY start_ms, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i64>(3)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut hits = Vec::new();
+    for (segment_id, speaker, text, start_ms) in segments {
+        for rule in matches(&rules, &text) {
+            hits.push(ComplianceHit {
+                rule_id: rule.id.clone(),
+                phrase: rule.phrase.clone(),
+                severity: rule.severity,
+                category: rule.category.clone(),
+                session_id: session_id.to_string(),
+                segment_id,
+                speaker: speaker.clone(),
+                text: text.clone(),
+                start_ms,
+            });
+        }
+    }
+
+    let count = |severity| hits.iter().filter(|hit| hit.severity == severity).count();
+    Ok(ComplianceReport {
+        session_id: session_id.to_string(),
+        low: count(Severity::Low),
+        medium: count(Severity::Medium),
+        high: count(Severity::High),
+        hits,
+    })
+}
+
+/// Rules whose phrase appears in the text, most severe first
+fn matches<'a>(rules: &'a [ComplianceRule], text: &str) -> Vec<&'a ComplianceRule> {
+    let text = format!(" {} ", normalize(text));
+    let mut hits: Vec<&ComplianceRule> = rules
+        .iter()
+        .filter(|rule| {
+            let phrase = normalize(&rule.phrase);
+            !phrase.is_empty() && text.contains(&format!(" {} ", phrase))
+        })
+        .collect();
+    hits.sort_by(|a, b| b.severity.cmp(&a.severity));
+    hits
+}
+
+/// Lowercase words separated by single spaces; apostrophes are kept so "can't" stays one word
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .replace('’', "'")
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Position in the session as m:ss
+fn format_offset(ms: i64) -> String {
+    let seconds = ms.max(0) / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn load(app: &AppHandle) -> ComplianceConfig {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(COMPLIANCE_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
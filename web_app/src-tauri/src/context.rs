@@ -0,0 +1,262 @@
+// Queen Mama LITE - Active Window Context
+// Tells the coaching prompts which app, window and (for browsers) page the user is looking at,
+// and notices when a meeting app comes to the front
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+use xcap::Window;
+
+use crate::events::VersionedEmitter;
+use crate::settings::{MeetingApp, MeetingDetection, MEETING_DETECTION_STORE_KEY};
+use crate::{audio, focus, session, window, SETTINGS_STORE};
+
+/// How often the frontmost window is checked while polling
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Switching away from a meeting and back doesn't announce it again within this long
+const MEETING_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Default)]
+pub struct ContextState {
+    /// The frontend wants `active_window_changed` events
+    polling: AtomicBool,
+    /// When each meeting app was last announced
+    announced: Mutex<HashMap<String, Instant>>,
+}
+
+#[derive(Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveWindowInfo {
+    app_name: String,
+    title: String,
+    /// Current page, for browsers that expose it
+    url: Option<String>,
+}
+
+impl MeetingApp {
+    fn matches(&self, info: &ActiveWindowInfo) -> bool {
+        let contains = |haystack: &str, needle: &String| {
+            haystack.to_lowercase().contains(&needle.to_lowercase())
+        };
+        (self.apps.is_empty() || self.apps.iter().any(|app| contains(&info.app_name, app)))
+            && (self.windows.is_empty()
+                || self.windows.iter().any(|window| {
+                    contains(&info.title, window)
+                        || info.url.as_deref().is_some_and(|url| contains(url, window))
+                }))
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MeetingDetectedEvent {
+    /// The configured name, e.g. `Zoom`
+    app: String,
+    app_name: String,
+    title: String,
+}
+
+pub fn setup_context(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(ContextState::default());
+
+    let app_handle = app.app_handle().clone();
+    thread::spawn(move || watch(app_handle));
+    Ok(())
+}
+
+/// Get the app, window title and browser URL the user is currently in
+#[tauri::command]
+pub async fn get_active_window_info() -> Result<Option<ActiveWindowInfo>, String> {
+    tauri::async_runtime::spawn_blocking(|| active_window_info(true))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Emit `active_window_changed` whenever the user switches app, window or page
+#[tauri::command]
+pub fn set_active_window_polling(
+    state: State<'_, ContextState>,
+    enabled: bool,
+) -> Result<(), String> {
+    state.polling.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Get which apps count as meetings and what happens when one comes to the front
+#[tauri::command]
+pub fn get_meeting_detection(app: AppHandle) -> MeetingDetection {
+    meeting_detection(&app)
+}
+
+/// Set which apps count as meetings and what happens when one comes to the front
+#[tauri::command]
+pub fn set_meeting_detection(app: AppHandle, config: MeetingDetection) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        MEETING_DETECTION_STORE_KEY,
+        serde_json::to_value(&config).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+fn meeting_detection(app: &AppHandle) -> MeetingDetection {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(MEETING_DETECTION_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Checks the frontmost window for as long as the frontend or meeting detection wants it
+fn watch(app: AppHandle) {
+    let mut last = None;
+    let mut was_polling = false;
+    let mut meeting = None;
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let polling = app.state::<ContextState>().polling.load(Ordering::SeqCst);
+        let detection = meeting_detection(&app);
+        if !polling && !detection.enabled {
+            was_polling = false;
+            meeting = None;
+            continue;
+        }
+
+        // Asking browsers for their URL needs the Automation permission, so only the
+        // frontend's polling does it; Meet's tab title is enough to spot a meeting
+        let info = active_window_info(polling);
+        // Start each round of polling with the current window
+        if polling && (info != last || !was_polling) {
+            if let Err(e) = app.emit("active_window_changed", &info) {
+                tracing::error!("Failed to emit event: {}", e);
+            }
+        }
+        if detection.enabled {
+            let current = info.as_ref().and_then(|info| {
+                detection
+                    .apps
+                    .iter()
+                    .find(|meeting| meeting.matches(info))
+                    .map(|meeting| (meeting.name.clone(), info))
+            });
+            let name = current.as_ref().map(|(name, _)| name.clone());
+            if name != meeting {
+                if let Some((name, info)) = current {
+                    on_meeting(&app, &detection, name, info);
+                }
+                meeting = name;
+            }
+        }
+        was_polling = polling;
+        last = info;
+    }
+}
+
+/// A meeting app came to the front
+fn on_meeting(
+    app: &AppHandle,
+    detection: &MeetingDetection,
+    name: String,
+    info: &ActiveWindowInfo,
+) {
+    if session::active_session_id(app).is_some() {
+        return;
+    }
+    {
+        let state = app.state::<ContextState>();
+        let Ok(mut announced) = state.announced.lock() else {
+            return;
+        };
+        if announced
+            .get(&name)
+            .is_some_and(|at| at.elapsed() < MEETING_COOLDOWN)
+        {
+            return;
+        }
+        announced.insert(name.clone(), Instant::now());
+    }
+
+    tracing::info!("Meeting detected in {}", name);
+    let event = MeetingDetectedEvent {
+        app: name,
+        app_name: info.app_name.clone(),
+        title: info.title.clone(),
+    };
+    if let Err(e) = app.emit("meeting_detected", event) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+
+    // Shown without focus so the meeting keeps the keyboard
+    if detection.show_overlay && !focus::hides_overlay(app) {
+        if let Err(e) = window::overlay_window(app)
+            .and_then(|overlay| overlay.show().map_err(|e| e.to_string()))
+        {
+            tracing::error!("Failed to show the overlay: {}", e);
+        }
+    }
+    if detection.prearm_audio {
+        if let Err(e) = audio::prearm(app) {
+            tracing::warn!("Failed to pre-arm the microphone: {}", e);
+        }
+    }
+}
+
+/// The focused window, ignoring our own
+fn active_window_info(with_url: bool) -> Option<ActiveWindowInfo> {
+    let window = Window::all()
+        .ok()?
+        .into_iter()
+        .find(|window| window.is_focused().unwrap_or(false))?;
+    if window.pid().ok() == Some(std::process::id()) {
+        return None;
+    }
+
+    let app_name = window.app_name().unwrap_or_default();
+    Some(ActiveWindowInfo {
+        url: with_url.then(|| browser_url(&app_name)).flatten(),
+        title: window.title().unwrap_or_default(),
+        app_name,
+    })
+}
+
+/// Ask the browser for its front tab's URL over AppleScript
+#[cfg(target_os = "macos")]
+fn browser_url(app_name: &str) -> Option<String> {
+    let script = match app_name {
+        "Safari" => "tell application \"Safari\" to get URL of front document",
+        "Google Chrome" | "Microsoft Edge" | "Brave Browser" | "Arc" | "Vivaldi" => {
+            return run_osascript(&format!(
+                "tell application \"{}\" to get URL of active tab of front window",
+                app_name
+            ));
+        }
+        _ => return None,
+    };
+    run_osascript(script)
+}
+
+#[cfg(target_os = "macos")]
+fn run_osascript(script: &str) -> Option<String> {
+    let output = std::process::Command::new("osascript")
+        .args(["-e", script])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// Other platforms don't expose the URL without a browser extension
+#[cfg(not(target_os = "macos"))]
+fn browser_url(_app_name: &str) -> Option<String> {
+    None
+}
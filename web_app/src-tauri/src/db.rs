@@ -0,0 +1,1210 @@
+// Queen Mama LITE - Session History Store
+// SQLite database under the app data dir holding sessions, transcripts and coaching notes
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use chrono::TimeZone;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension, Row};
+use tauri::{App, AppHandle, Manager, State};
+
+use crate::audio::{AudioSource, RecordingInfo};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+
+pub(crate) const DATABASE_FILE: &str = "queen_mama.db";
+
+/// Launch flag that lists the migrations each profile's database is waiting for instead of
+/// starting the app, without applying them
+pub const CHECK_MIGRATIONS_ARG: &str = "--check-migrations";
+
+/// Segments recognized with less confidence than this are flagged for review
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.6;
+
+/// Schema changes, applied in order; the index + 1 is stored in `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE sessions (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER
+    );
+    CREATE TABLE transcript_segments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        speaker TEXT,
+        text TEXT NOT NULL,
+        start_ms INTEGER NOT NULL,
+        end_ms INTEGER NOT NULL
+    );
+    CREATE INDEX transcript_segments_session ON transcript_segments(session_id, start_ms);
+    CREATE TABLE notes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        content TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX notes_session ON notes(session_id, created_at);
+    CREATE VIRTUAL TABLE transcript_fts USING fts5(
+        text,
+        content='transcript_segments',
+        content_rowid='id'
+    );
+    CREATE TRIGGER transcript_fts_insert AFTER INSERT ON transcript_segments BEGIN
+        INSERT INTO transcript_fts(rowid, text) VALUES (new.id, new.text);
+    END;
+    CREATE TRIGGER transcript_fts_delete AFTER DELETE ON transcript_segments BEGIN
+        INSERT INTO transcript_fts(transcript_fts, rowid, text) VALUES ('delete', old.id, old.text);
+    END;",
+    "CREATE TABLE session_tags (
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL COLLATE NOCASE,
+        PRIMARY KEY (session_id, tag)
+    );
+    CREATE INDEX session_tags_tag ON session_tags(tag);
+    CREATE TABLE custom_fields (
+        name TEXT PRIMARY KEY COLLATE NOCASE,
+        kind TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE session_fields (
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        name TEXT NOT NULL REFERENCES custom_fields(name) ON DELETE CASCADE ON UPDATE CASCADE,
+        value NOT NULL,
+        PRIMARY KEY (session_id, name)
+    );
+    CREATE INDEX session_fields_name ON session_fields(name, value);",
+    "CREATE TABLE saved_filters (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        query TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );",
+    "ALTER TABLE sessions ADD COLUMN deleted_at INTEGER;
+    CREATE INDEX sessions_deleted_at ON sessions(deleted_at) WHERE deleted_at IS NOT NULL;",
+    "CREATE TABLE session_recordings (
+        id TEXT PRIMARY KEY,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        source TEXT NOT NULL,
+        device TEXT NOT NULL,
+        sample_rate INTEGER NOT NULL,
+        path TEXT NOT NULL,
+        offset_ms INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL
+    );
+    CREATE INDEX session_recordings_session ON session_recordings(session_id, offset_ms);",
+    "CREATE TABLE dismissed_duplicates (
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        other_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        PRIMARY KEY (session_id, other_id)
+    );
+    CREATE INDEX sessions_started_at ON sessions(started_at);",
+    "CREATE TABLE session_summaries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        version INTEGER NOT NULL,
+        template TEXT NOT NULL,
+        model TEXT NOT NULL,
+        language TEXT,
+        content TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        is_primary INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE UNIQUE INDEX session_summaries_version ON session_summaries(session_id, version);",
+    "CREATE TABLE redactions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        start_ms INTEGER NOT NULL,
+        end_ms INTEGER NOT NULL,
+        segments INTEGER NOT NULL,
+        recordings INTEGER NOT NULL,
+        reason TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX redactions_session ON redactions(session_id, created_at);",
+    "CREATE TABLE file_transcriptions (
+        id TEXT PRIMARY KEY,
+        fingerprint TEXT NOT NULL,
+        file_name TEXT NOT NULL,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        recording_path TEXT NOT NULL,
+        options TEXT NOT NULL,
+        done_ms INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        speaker INTEGER NOT NULL,
+        created_at INTEGER NOT NULL,
+        finished_at INTEGER
+    );
+    CREATE INDEX file_transcriptions_fingerprint ON file_transcriptions(fingerprint);",
+    "CREATE TABLE reference_documents (
+        session_id TEXT PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE,
+        source_url TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE session_flags (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        at_ms INTEGER NOT NULL,
+        note TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX session_flags_session ON session_flags(session_id, at_ms);",
+    "CREATE TABLE note_drafts (
+        session_id TEXT PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE,
+        content TEXT NOT NULL,
+        version INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        updated_by TEXT
+    );
+    CREATE TABLE note_draft_revisions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        version INTEGER NOT NULL,
+        content TEXT NOT NULL,
+        saved_at INTEGER NOT NULL
+    );
+    CREATE INDEX note_draft_revisions_session ON note_draft_revisions(session_id, version);",
+    "CREATE TABLE note_attachments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        note_id INTEGER NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+        file_name TEXT NOT NULL,
+        mime_type TEXT NOT NULL,
+        path TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX note_attachments_note ON note_attachments(note_id);
+    CREATE VIRTUAL TABLE notes_fts USING fts5(
+        content,
+        content='notes',
+        content_rowid='id'
+    );
+    INSERT INTO notes_fts(notes_fts) VALUES ('rebuild');
+    CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+        INSERT INTO notes_fts(rowid, content) VALUES (new.id, new.content);
+    END;
+    CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+        INSERT INTO notes_fts(notes_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    END;
+    CREATE VIRTUAL TABLE attachment_fts USING fts5(
+        file_name,
+        content='note_attachments',
+        content_rowid='id'
+    );
+    CREATE TRIGGER attachment_fts_insert AFTER INSERT ON note_attachments BEGIN
+        INSERT INTO attachment_fts(rowid, file_name) VALUES (new.id, new.file_name);
+    END;
+    CREATE TRIGGER attachment_fts_delete AFTER DELETE ON note_attachments BEGIN
+        INSERT INTO attachment_fts(attachment_fts, rowid, file_name) VALUES ('delete', old.id, old.file_name);
+    END;",
+    "ALTER TABLE sessions ADD COLUMN journal_date TEXT;
+    CREATE UNIQUE INDEX sessions_journal_date ON sessions(journal_date) WHERE journal_date IS NOT NULL;",
+    "CREATE TABLE session_comments (
+        id TEXT PRIMARY KEY,
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        parent_id TEXT REFERENCES session_comments(id) ON DELETE CASCADE,
+        author TEXT NOT NULL,
+        content TEXT NOT NULL,
+        at_ms INTEGER NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX session_comments_session ON session_comments(session_id, at_ms);",
+    "ALTER TABLE transcript_segments ADD COLUMN confidence REAL;
+    ALTER TABLE transcript_segments ADD COLUMN words TEXT;",
+    // Rows waiting to be re-indexed aren't in the index yet, so deleting them must not touch it
+    "CREATE TABLE reindex_progress (
+        index_name TEXT PRIMARY KEY,
+        last_id INTEGER NOT NULL,
+        max_id INTEGER NOT NULL
+    );
+    DROP TRIGGER transcript_fts_delete;
+    CREATE TRIGGER transcript_fts_delete AFTER DELETE ON transcript_segments
+    WHEN NOT EXISTS (SELECT 1 FROM reindex_progress WHERE index_name = 'transcript_fts'
+        AND old.id > last_id AND old.id <= max_id)
+    BEGIN
+        INSERT INTO transcript_fts(transcript_fts, rowid, text) VALUES ('delete', old.id, old.text);
+    END;
+    DROP TRIGGER notes_fts_delete;
+    CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes
+    WHEN NOT EXISTS (SELECT 1 FROM reindex_progress WHERE index_name = 'notes_fts'
+        AND old.id > last_id AND old.id <= max_id)
+    BEGIN
+        INSERT INTO notes_fts(notes_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    END;
+    DROP TRIGGER attachment_fts_delete;
+    CREATE TRIGGER attachment_fts_delete AFTER DELETE ON note_attachments
+    WHEN NOT EXISTS (SELECT 1 FROM reindex_progress WHERE index_name = 'attachment_fts'
+        AND old.id > last_id AND old.id <= max_id)
+    BEGIN
+        INSERT INTO attachment_fts(attachment_fts, rowid, file_name) VALUES ('delete', old.id, old.file_name);
+    END;",
+    "CREATE TABLE session_keywords (
+        session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        keyword TEXT NOT NULL,
+        count INTEGER NOT NULL,
+        PRIMARY KEY (session_id, keyword)
+    );
+    ALTER TABLE sessions ADD COLUMN keywords_extracted_at INTEGER;",
+];
+
+/// Columns `segment_from_row` reads
+pub(crate) const SEGMENT_COLUMNS: &str = "id, speaker, text, start_ms, end_ms, confidence, words";
+
+/// Columns `session_from_row` reads, from sessions aliased `s`
+pub(crate) const SESSION_COLUMNS: &str = "s.id, s.title, s.started_at, s.ended_at,
+    (SELECT COUNT(*) FROM transcript_segments t WHERE t.session_id = s.id),
+    (SELECT group_concat(tag, char(31)) FROM session_tags st WHERE st.session_id = s.id)";
+
+/// Columns in `SESSION_COLUMNS`, for queries selecting more after them
+pub(crate) const SESSION_COLUMN_COUNT: usize = 6;
+
+/// Separates tags in `SESSION_COLUMNS`; tags can't hold control characters
+const TAG_SEPARATOR: char = '\u{1f}';
+
+pub struct Database {
+    connection: Mutex<Connection>,
+    /// Calls fail as busy until then, when chaos mode locks the database
+    locked_until: Mutex<Option<Instant>>,
+    /// Closed until the user's passphrase unlocks it; calls fail until then
+    sealed: AtomicBool,
+    /// When anything last used the database, for the passphrase lock timeout
+    last_used_ms: AtomicI64,
+}
+
+impl Database {
+    /// Lock the connection for modules that keep their own tables
+    pub(crate) fn connection(&self) -> Result<MutexGuard<'_, Connection>, String> {
+        if self.is_sealed() {
+            return Err(t!("error.database_locked"));
+        }
+        self.last_used_ms.store(now_ms(), Ordering::SeqCst);
+
+        let locked = self
+            .locked_until
+            .lock()
+            .map_err(|e| e.to_string())?
+            .is_some_and(|until| Instant::now() < until);
+        if locked {
+            // The same error another process holding the write lock would cause
+            let busy = rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY);
+            return Err(rusqlite::Error::SqliteFailure(busy, None).to_string());
+        }
+        self.connection.lock().map_err(|e| e.to_string())
+    }
+
+    /// Fail every call as busy for a while, for chaos mode
+    pub(crate) fn lock_for(&self, duration: Duration) {
+        if let Ok(mut locked_until) = self.locked_until.lock() {
+            *locked_until = Some(Instant::now() + duration);
+        }
+    }
+
+    /// Whether it's waiting for the user's passphrase
+    pub(crate) fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::SeqCst)
+    }
+
+    /// How long since anything last used the database
+    pub(crate) fn idle_ms(&self) -> i64 {
+        now_ms() - self.last_used_ms.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    id: String,
+    title: String,
+    started_at: i64,
+    ended_at: Option<i64>,
+    segment_count: i64,
+    tags: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDetail {
+    #[serde(flatten)]
+    session: Session,
+    fields: Vec<crate::tags::SessionField>,
+    segments: Vec<TranscriptSegment>,
+    notes: Vec<Note>,
+    recordings: Vec<SessionRecording>,
+    /// The primary version of its summary
+    summary: Option<crate::summaries::SessionSummary>,
+}
+
+/// A recording kept with a session; a merged session can have several per source
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecording {
+    pub id: String,
+    pub source: AudioSource,
+    pub device: String,
+    pub sample_rate: u32,
+    pub path: PathBuf,
+    /// Where it starts on the session's timeline, the one transcript times are on
+    pub offset_ms: i64,
+    pub duration_ms: i64,
+}
+
+/// Narrows `list_sessions`; every condition given must hold
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SessionFilter {
+    /// Words in the title, transcript or notes
+    text: Option<String>,
+    /// Sessions with all of these tags
+    tags: Vec<String>,
+    fields: Vec<crate::tags::FieldFilter>,
+    /// Started in this period, worked out when the filter runs
+    period: Option<crate::filters::RelativePeriod>,
+    /// Whether anyone left review comments on it
+    reviewed: Option<bool>,
+}
+
+impl SessionFilter {
+    /// SQL conditions on sessions aliased `s`, with the parameters they number
+    pub(crate) fn conditions(&self) -> (Vec<String>, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(text) = self.text.as_deref().filter(|text| !text.trim().is_empty()) {
+            values.push(Value::Text(format!("%{}%", like_escape(text.trim()))));
+            values.push(Value::Text(fts_query(text)));
+            conditions.push(format!(
+                "(s.title LIKE ?{0} ESCAPE '\\'
+                  OR EXISTS (SELECT 1 FROM transcript_fts JOIN transcript_segments t ON t.id = transcript_fts.rowid
+                      WHERE transcript_fts MATCH ?{1} AND t.session_id = s.id)
+                  OR EXISTS (SELECT 1 FROM notes_fts JOIN notes n ON n.id = notes_fts.rowid
+                      WHERE notes_fts MATCH ?{1} AND n.session_id = s.id))",
+                values.len() - 1,
+                values.len()
+            ));
+        }
+        for tag in &self.tags {
+            values.push(Value::Text(tag.trim().to_string()));
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM session_tags st WHERE st.session_id = s.id AND st.tag = ?{})",
+                values.len()
+            ));
+        }
+        for field in &self.fields {
+            conditions.push(field.condition(&mut values));
+        }
+        if let Some(period) = self.period {
+            let now = chrono::Local
+                .timestamp_millis_opt(now_ms())
+                .single()
+                .unwrap_or_else(chrono::Local::now);
+            let (from, to) = period.bounds(now);
+            values.push(Value::Integer(from));
+            values.push(Value::Integer(to));
+            conditions.push(format!(
+                "s.started_at >= ?{} AND s.started_at < ?{}",
+                values.len() - 1,
+                values.len()
+            ));
+        }
+        if let Some(reviewed) = self.reviewed {
+            conditions.push(format!(
+                "{}EXISTS (SELECT 1 FROM session_comments c WHERE c.session_id = s.id)",
+                if reviewed { "" } else { "NOT " }
+            ));
+        }
+        (conditions, values)
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegment {
+    #[serde(default)]
+    pub(crate) id: i64,
+    speaker: Option<String>,
+    text: String,
+    pub(crate) start_ms: i64,
+    end_ms: i64,
+    /// 0.0 to 1.0, when the speech-to-text engine reports it
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    words: Vec<WordConfidence>,
+    /// Below `LOW_CONFIDENCE_THRESHOLD`; set when reading, so exports and summaries can flag or down-weight it
+    #[serde(default)]
+    low_confidence: bool,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordConfidence {
+    pub text: String,
+    pub confidence: f32,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    id: i64,
+    /// "suggestion" for AI output, "note" for the user's own notes
+    kind: String,
+    content: String,
+    created_at: i64,
+    attachments: Vec<crate::notes::Attachment>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    session_id: String,
+    title: String,
+    segment_id: i64,
+    start_ms: i64,
+    snippet: String,
+}
+
+pub fn setup_db(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    // Every test harness starts from an empty database
+    #[cfg(feature = "test-harness")]
+    let (connection, sealed) = (open(Path::new(":memory:"), None)?, false);
+
+    #[cfg(not(feature = "test-harness"))]
+    let (connection, sealed) = {
+        let active = crate::profiles::load(app.app_handle()).active;
+        if crate::profiles::has_passphrase(app.app_handle(), &active) {
+            // Opened by `unlock` once the user enters the passphrase
+            (Connection::open_in_memory()?, true)
+        } else {
+            let (path, key) = crate::profiles::database_location(app.app_handle(), &active)?;
+            (open(&path, Some(&key))?, false)
+        }
+    };
+
+    app.manage(Database {
+        connection: Mutex::new(connection),
+        locked_until: Mutex::default(),
+        sealed: AtomicBool::new(sealed),
+        last_used_ms: AtomicI64::new(now_ms()),
+    });
+
+    tracing::info!("Session history ready");
+    Ok(())
+}
+
+/// Swap in another database file, e.g. when the user switches profile or unlocks one
+pub(crate) fn reopen(db: &Database, path: &Path, key: Option<&str>) -> Result<(), String> {
+    let connection = open(path, key)?;
+    *db.connection.lock().map_err(|e| e.to_string())? = connection;
+    db.sealed.store(false, Ordering::SeqCst);
+    db.last_used_ms.store(now_ms(), Ordering::SeqCst);
+    Ok(())
+}
+
+/// Close the database until `reopen` is given its passphrase
+pub(crate) fn seal(db: &Database) -> Result<(), String> {
+    let placeholder = Connection::open_in_memory().map_err(|e| e.to_string())?;
+    *db.connection.lock().map_err(|e| e.to_string())? = placeholder;
+    db.sealed.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Encrypt the open database at `path` with a new key, keeping `old_key`'s copy if that fails.
+/// It's copied into a new file rather than rekeyed in place, so a failure leaves the old one whole.
+pub(crate) fn rekey(db: &Database, path: &Path, old_key: &str, key: &str) -> Result<(), String> {
+    let encrypted = path.with_file_name(format!("{}.rekey", DATABASE_FILE));
+    let _ = fs::remove_file(&encrypted);
+
+    let mut connection = db.connection()?;
+    export(&connection, &encrypted, key)?;
+
+    // Closing the old connection folds its write-ahead log back into the file being replaced
+    *connection = Connection::open_in_memory().map_err(|e| e.to_string())?;
+    let swapped = fs::rename(&encrypted, path)
+        .map_err(|e| e.to_string())
+        .and_then(|_| open(path, Some(key)));
+    match swapped {
+        Ok(encrypted) => {
+            *connection = encrypted;
+            Ok(())
+        }
+        Err(e) => {
+            *connection = open(path, Some(old_key))?;
+            Err(e)
+        }
+    }
+}
+
+/// Encrypt a database file that isn't open and never was encrypted. SQLCipher can only change the
+/// key of a database that's already encrypted, so it's copied into a new file instead. Backups
+/// taken before migrations are unencrypted too and are removed.
+pub(crate) fn encrypt_file(path: &Path, key: &str) -> Result<(), String> {
+    let encrypted = path.with_file_name(format!("{}.rekey", DATABASE_FILE));
+    let _ = fs::remove_file(&encrypted);
+
+    let connection = open_unmigrated(path, None).map_err(|e| e.to_string())?;
+    export(&connection, &encrypted, key)?;
+    // Closing folds the write-ahead log back in, so none is left to apply to the new file
+    drop(connection);
+    fs::rename(&encrypted, path).map_err(|e| e.to_string())?;
+
+    let backup_prefix = format!("{}.v", DATABASE_FILE);
+    let backups = path
+        .parent()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.starts_with(&backup_prefix) && name.ends_with(".backup")
+        });
+    for backup in backups {
+        if let Err(e) = fs::remove_file(backup.path()) {
+            tracing::warn!("Failed to remove unencrypted backup: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Copy everything in `connection`'s database, schema version included, into a new file
+/// encrypted with `key`
+fn export(connection: &Connection, target: &Path, key: &str) -> Result<(), String> {
+    let version: i64 = connection
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    connection
+        .execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            params![target.to_string_lossy(), key],
+        )
+        .and_then(|_| connection.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(())))
+        .and_then(|_| connection.pragma_update(Some("encrypted"), "user_version", version))
+        .and_then(|_| connection.execute("DETACH DATABASE encrypted", []))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Open a database, unlocking it with `key` when it's encrypted, and bring its schema up to date
+fn open(path: &Path, key: Option<&str>) -> Result<Connection, String> {
+    let mut connection = open_unmigrated(path, key).map_err(|e| e.to_string())?;
+    migrate(&mut connection, path)?;
+    Ok(connection)
+}
+
+/// A migrated database that lives in memory, for tests
+#[cfg(test)]
+pub(crate) fn open_in_memory() -> Connection {
+    open(Path::new(":memory:"), None).unwrap()
+}
+
+fn open_unmigrated(path: &Path, key: Option<&str>) -> rusqlite::Result<Connection> {
+    let connection = Connection::open(path)?;
+    if let Some(key) = key {
+        connection.pragma_update(None, "key", key)?;
+    }
+    connection.pragma_update(None, "journal_mode", "WAL")?;
+    connection.pragma_update(None, "foreign_keys", true)?;
+    Ok(connection)
+}
+
+/// Apply the migrations a database hasn't had yet, copying it aside first when it holds data
+fn migrate(connection: &mut Connection, path: &Path) -> Result<(), String> {
+    let version = schema_version(connection).map_err(|e| e.to_string())?;
+    if version > 0 && version < MIGRATIONS.len() {
+        backup(connection, path, version)?;
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        connection
+            .transaction()
+            .and_then(|transaction| {
+                transaction.execute_batch(migration)?;
+                transaction.pragma_update(None, "user_version", index + 1)?;
+                transaction.commit()
+            })
+            .map_err(|e| {
+                tracing::error!("Migration {} failed: {}", index + 1, e);
+                e.to_string()
+            })?;
+        tracing::info!("Applied migration {}", index + 1);
+    }
+
+    Ok(())
+}
+
+fn schema_version(connection: &Connection) -> rusqlite::Result<usize> {
+    connection.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Copy the database file, as it is at schema `version`, next to it. The write-ahead log is
+/// folded in first so the copy is complete on its own; an encrypted database stays encrypted.
+fn backup(connection: &Connection, path: &Path, version: usize) -> Result<(), String> {
+    connection
+        .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+        .map_err(|e| e.to_string())?;
+    let backup = path.with_file_name(format!("{}.v{}.backup", DATABASE_FILE, version));
+    fs::copy(path, &backup).map_err(|e| {
+        tracing::error!("Failed to back up the database before migrating: {}", e);
+        e.to_string()
+    })?;
+    tracing::info!(path = ?backup, "Backed up version {}", version);
+    Ok(())
+}
+
+/// What a migration check found: a line per profile, and whether any database is behind this
+/// version of the app, ahead of it, or can't be read
+pub struct MigrationCheck {
+    pub report: Vec<String>,
+    pub needs_attention: bool,
+}
+
+/// List the migrations each profile's database still needs. Databases are opened read-only and
+/// nothing is written to them; those locked by a passphrase are skipped.
+pub(crate) fn check_migrations(app: &AppHandle) -> MigrationCheck {
+    let mut check = MigrationCheck {
+        report: Vec::new(),
+        needs_attention: false,
+    };
+    for profile in crate::profiles::load(app).profiles {
+        let label = format!("{} ({})", profile.name, profile.id);
+        let path = match crate::profiles::database_path(app, &profile.id) {
+            Ok(path) if path.exists() => path,
+            Ok(_) => {
+                check
+                    .report
+                    .push(t!("migrations.no_database", profile = label));
+                continue;
+            }
+            Err(e) => {
+                check
+                    .report
+                    .push(t!("migrations.unreadable", profile = label, error = e));
+                check.needs_attention = true;
+                continue;
+            }
+        };
+        if profile.passphrase {
+            check.report.push(t!("migrations.locked", profile = label));
+            continue;
+        }
+
+        // Without a key yet it's the default profile's database from before keys, unencrypted
+        let version = crate::profiles::stored_key(app, &profile.id)
+            .and_then(|key| stored_version(&path, key.as_deref()).map_err(|e| e.to_string()));
+        let version = match version {
+            Ok(version) => version,
+            Err(e) => {
+                check
+                    .report
+                    .push(t!("migrations.unreadable", profile = label, error = e));
+                check.needs_attention = true;
+                continue;
+            }
+        };
+        if version > MIGRATIONS.len() {
+            check.report.push(t!(
+                "migrations.newer",
+                profile = label,
+                version = version,
+                latest = MIGRATIONS.len()
+            ));
+            check.needs_attention = true;
+            continue;
+        }
+        if version == MIGRATIONS.len() {
+            check.report.push(t!(
+                "migrations.up_to_date",
+                profile = label,
+                version = version
+            ));
+            continue;
+        }
+
+        check.needs_attention = true;
+        check.report.push(t!(
+            "migrations.pending",
+            profile = label,
+            version = version,
+            count = MIGRATIONS.len() - version
+        ));
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let first_line = migration.lines().next().unwrap_or_default().trim();
+            check
+                .report
+                .push(format!("  {}: {}", index + 1, first_line));
+        }
+    }
+    check
+}
+
+/// Read a database's schema version without writing to it, the journal mode included
+fn stored_version(path: &Path, key: Option<&str>) -> rusqlite::Result<usize> {
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    if let Some(key) = key {
+        connection.pragma_update(None, "key", key)?;
+    }
+    schema_version(&connection)
+}
+
+/// Create a session; the session manager uses its own id so recordings and history line up
+pub fn insert_session(db: &Database, id: &str, title: &str, started_at: i64) -> Result<(), String> {
+    let connection = db.connection()?;
+    connection
+        .execute(
+            "INSERT INTO sessions (id, title, started_at) VALUES (?1, ?2, ?3)",
+            params![id, title, started_at],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Keep a stopped session's recordings with it, placed on its timeline by when they started
+pub fn insert_recordings(
+    app: &AppHandle,
+    session_id: &str,
+    session_started_at: i64,
+    recordings: &[RecordingInfo],
+) -> Result<(), String> {
+    let db = app.state::<Database>();
+    let connection = db.connection()?;
+    for recording in recordings {
+        let duration_ms = match crate::audio::recording_duration_ms(app, &recording.path) {
+            Ok(duration_ms) => duration_ms as i64,
+            Err(e) => {
+                tracing::warn!("Recording {} unreadable: {}", recording.id, e);
+                continue;
+            }
+        };
+        connection
+            .execute(
+                "INSERT INTO session_recordings
+                    (id, session_id, source, device, sample_rate, path, offset_ms, duration_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    recording.id,
+                    session_id,
+                    recording.source.as_str(),
+                    recording.device,
+                    recording.sample_rate,
+                    recording.path.to_string_lossy(),
+                    (recording.started_at - session_started_at).max(0),
+                    duration_ms
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// A session's recordings, in timeline order
+pub(crate) fn session_recordings(
+    connection: &Connection,
+    session_id: &str,
+) -> rusqlite::Result<Vec<SessionRecording>> {
+    connection
+        .prepare(
+            "SELECT id, source, device, sample_rate, path, offset_ms, duration_ms
+             FROM session_recordings WHERE session_id = ?1 ORDER BY offset_ms, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    let source: String = row.get(1)?;
+                    Ok(SessionRecording {
+                        id: row.get(0)?,
+                        source: AudioSource::parse(&source).unwrap_or(AudioSource::Microphone),
+                        device: row.get(2)?,
+                        sample_rate: row.get(3)?,
+                        path: PathBuf::from(row.get::<_, String>(4)?),
+                        offset_ms: row.get(5)?,
+                        duration_ms: row.get(6)?,
+                    })
+                })?
+                .collect()
+        })
+}
+
+pub fn end_session(db: &Database, id: &str, ended_at: i64) -> Result<(), String> {
+    let connection = db.connection()?;
+    connection
+        .execute(
+            "UPDATE sessions SET ended_at = ?2 WHERE id = ?1",
+            params![id, ended_at],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Create a session, e.g. for an imported recording or transcript
+#[tauri::command]
+pub fn create_session(app: AppHandle, db: State<'_, Database>, title: String) -> Result<Session, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let started_at = now_ms();
+    insert_session(&db, &id, &title, started_at)?;
+    sessions_changed(&app);
+
+    Ok(Session {
+        id,
+        title,
+        started_at,
+        ended_at: None,
+        segment_count: 0,
+        tags: Vec::new(),
+    })
+}
+
+/// List sessions, most recent first, optionally only those matching `filter`
+#[tauri::command]
+pub fn list_sessions(
+    db: State<'_, Database>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    filter: Option<SessionFilter>,
+) -> Result<Vec<Session>, String> {
+    query_sessions(
+        &db.connection()?,
+        &filter.unwrap_or_default(),
+        limit.unwrap_or(50),
+        offset.unwrap_or(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) fn query_sessions(
+    connection: &Connection,
+    filter: &SessionFilter,
+    limit: u32,
+    offset: u32,
+) -> rusqlite::Result<Vec<Session>> {
+    let (mut conditions, mut values) = filter.conditions();
+    conditions.push("s.deleted_at IS NULL".to_string());
+    let where_clause = format!("WHERE {}", conditions.join(" AND "));
+    values.push(Value::Integer(limit.into()));
+    values.push(Value::Integer(offset.into()));
+
+    let sql = format!(
+        "SELECT {} FROM sessions s {}
+         ORDER BY s.started_at DESC
+         LIMIT ?{} OFFSET ?{}",
+        SESSION_COLUMNS,
+        where_clause,
+        values.len() - 1,
+        values.len()
+    );
+    connection.prepare(&sql).and_then(|mut statement| {
+        statement
+            .query_map(params_from_iter(values), session_from_row)?
+            .collect()
+    })
+}
+
+/// Get a session with its transcript and notes
+#[tauri::command]
+pub fn get_session(db: State<'_, Database>, id: String) -> Result<SessionDetail, String> {
+    let connection = db.connection()?;
+
+    let session = connection
+        .query_row(
+            &format!("SELECT {} FROM sessions s WHERE s.id = ?1", SESSION_COLUMNS),
+            params![id],
+            session_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.session_not_found", id = id))?;
+    let fields = crate::tags::session_fields(&connection, &id).map_err(|e| e.to_string())?;
+
+    let segments = connection
+        .prepare(&format!(
+            "SELECT {} FROM transcript_segments WHERE session_id = ?1 ORDER BY start_ms, id",
+            SEGMENT_COLUMNS
+        ))
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![id], segment_from_row)?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut notes = connection
+        .prepare(
+            "SELECT id, kind, content, created_at FROM notes
+             WHERE session_id = ?1 ORDER BY created_at, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![id], |row| {
+                    Ok(Note {
+                        id: row.get(0)?,
+                        kind: row.get(1)?,
+                        content: row.get(2)?,
+                        created_at: row.get(3)?,
+                        attachments: Vec::new(),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+    let mut attachments = crate::notes::session_attachments(&connection, &id)?;
+    for note in &mut notes {
+        note.attachments = attachments.remove(&note.id).unwrap_or_default();
+    }
+
+    let recordings = session_recordings(&connection, &id).map_err(|e| e.to_string())?;
+    let summary = crate::summaries::primary_summary(&connection, &id).map_err(|e| e.to_string())?;
+
+    Ok(SessionDetail {
+        session,
+        fields,
+        segments,
+        notes,
+        recordings,
+        summary,
+    })
+}
+
+/// Full-text search across transcripts
+#[tauri::command]
+pub fn search_sessions(
+    db: State<'_, Database>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<SearchHit>, String> {
+    let match_query = fts_query(&query);
+    if match_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let connection = db.connection()?;
+    let mut statement = connection
+        .prepare(
+            "SELECT s.id, s.title, t.id, t.start_ms,
+                snippet(transcript_fts, 0, '[', ']', '…', 12)
+             FROM transcript_fts
+             JOIN transcript_segments t ON t.id = transcript_fts.rowid
+             JOIN sessions s ON s.id = t.session_id
+             WHERE transcript_fts MATCH ?1 AND s.deleted_at IS NULL
+             ORDER BY rank
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let hits = statement
+        .query_map(params![match_query, limit.unwrap_or(50)], |row| {
+            Ok(SearchHit {
+                session_id: row.get(0)?,
+                title: row.get(1)?,
+                segment_id: row.get(2)?,
+                start_ms: row.get(3)?,
+                snippet: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(hits)
+}
+
+/// Move a session to the trash; it's purged with its transcript, notes and attachments once the
+/// retention window is over
+#[tauri::command]
+pub fn delete_session(app: AppHandle, db: State<'_, Database>, id: String) -> Result<(), String> {
+    let connection = db.connection()?;
+    connection
+        .execute(
+            "UPDATE sessions SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            params![id, now_ms()],
+        )
+        .map_err(|e| e.to_string())?;
+    drop(connection);
+    sessions_changed(&app);
+    Ok(())
+}
+
+/// Remove what a purged session kept outside the database
+pub(crate) fn remove_session_files(
+    app: &AppHandle,
+    id: &str,
+    recordings: &[SessionRecording],
+) -> Result<(), String> {
+    for recording in recordings {
+        crate::audio::remove_recording(&recording.path)?;
+    }
+    let attachments = crate::notes::attachments_dir(app, id)?;
+    if attachments.exists() {
+        fs::remove_dir_all(attachments).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Append a transcript segment to a session
+#[tauri::command]
+pub fn append_transcript_segment(
+    app: AppHandle,
+    db: State<'_, Database>,
+    session_id: String,
+    segment: TranscriptSegment,
+) -> Result<i64, String> {
+    let id = insert_segment(
+        &db,
+        &session_id,
+        segment.speaker.as_deref(),
+        &segment.text,
+        segment.start_ms,
+        segment.end_ms,
+        segment.confidence,
+        &segment.words,
+    )?;
+    crate::compliance::scan_segment(
+        &app,
+        &session_id,
+        id,
+        segment.speaker.as_deref(),
+        &segment.text,
+        segment.start_ms,
+    );
+    crate::transcript::segments_added(&app, &session_id, &[id]);
+    Ok(id)
+}
+
+pub fn insert_segment(
+    db: &Database,
+    session_id: &str,
+    speaker: Option<&str>,
+    text: &str,
+    start_ms: i64,
+    end_ms: i64,
+    confidence: Option<f32>,
+    words: &[WordConfidence],
+) -> Result<i64, String> {
+    let words = if words.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(words).map_err(|e| e.to_string())?)
+    };
+
+    let connection = db.connection()?;
+    connection
+        .execute(
+            "INSERT INTO transcript_segments (session_id, speaker, text, start_ms, end_ms, confidence, words)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![session_id, speaker, text, start_ms, end_ms, confidence, words],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(connection.last_insert_rowid())
+}
+
+pub(crate) fn segment_from_row(row: &Row) -> rusqlite::Result<TranscriptSegment> {
+    let confidence: Option<f32> = row.get(5)?;
+    let words = row
+        .get::<_, Option<String>>(6)?
+        .and_then(|words| serde_json::from_str(&words).ok())
+        .unwrap_or_default();
+    Ok(TranscriptSegment {
+        id: row.get(0)?,
+        speaker: row.get(1)?,
+        text: row.get(2)?,
+        start_ms: row.get(3)?,
+        end_ms: row.get(4)?,
+        low_confidence: is_low_confidence(confidence),
+        confidence,
+        words,
+    })
+}
+
+pub fn is_low_confidence(confidence: Option<f32>) -> bool {
+    confidence.is_some_and(|confidence| confidence < LOW_CONFIDENCE_THRESHOLD)
+}
+
+/// Attach an AI suggestion or a user note to a session
+#[tauri::command]
+pub fn add_session_note(
+    db: State<'_, Database>,
+    session_id: String,
+    kind: String,
+    content: String,
+) -> Result<Note, String> {
+    insert_note(&db, &session_id, kind, content)
+}
+
+pub fn insert_note(
+    db: &Database,
+    session_id: &str,
+    kind: String,
+    content: String,
+) -> Result<Note, String> {
+    let created_at = now_ms();
+    let connection = db.connection()?;
+    connection
+        .execute(
+            "INSERT INTO notes (session_id, kind, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![session_id, kind, content, created_at],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(Note {
+        id: connection.last_insert_rowid(),
+        kind,
+        content,
+        created_at,
+        attachments: Vec::new(),
+    })
+}
+
+pub(crate) fn session_from_row(row: &Row) -> rusqlite::Result<Session> {
+    Ok(Session {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        started_at: row.get(2)?,
+        ended_at: row.get(3)?,
+        segment_count: row.get(4)?,
+        tags: row
+            .get::<_, Option<String>>(5)?
+            .map(|tags| {
+                let mut tags: Vec<String> = tags.split(TAG_SEPARATOR).map(str::to_string).collect();
+                tags.sort();
+                tags
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// Quote each word so user input can't be parsed as FTS syntax
+pub(crate) fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Escape `LIKE` wildcards so they match literally, with `\\` as the escape character
+fn like_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Tell listeners such as the tray's recent sessions menu that sessions were added or removed
+pub fn sessions_changed(app: &AppHandle) {
+    if let Err(e) = app.emit("sessions_changed", ()) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
+
+/// Unix time in milliseconds, the timestamp format used throughout the database
+pub fn now_ms() -> i64 {
+    #[cfg(feature = "test-harness")]
+    if let Some(now) = crate::harness::now_ms() {
+        return now;
+    }
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,143 @@
+// Queen Mama LITE - Deep Links
+// Routes queenmama:// URLs from the web dashboard, OAuth providers and launchers, and tells the frontend about them
+
+use tauri::{App, AppHandle, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::actions::{self, URL_SCHEME};
+#[cfg(desktop)]
+use crate::auth;
+use crate::events::VersionedEmitter;
+use crate::permissions::{self, Caller};
+use crate::window;
+
+/// A queenmama:// URL we understand, emitted as `deep_link`
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DeepLink {
+    /// queenmama://action/<id>
+    Action { id: String },
+    /// queenmama://session/start
+    StartSession,
+    /// queenmama://session/stop
+    StopSession,
+    /// queenmama://session/<id>
+    #[serde(rename_all = "camelCase")]
+    OpenSession { session_id: String },
+    /// queenmama://settings or queenmama://settings/<section>
+    OpenSettings { section: Option<String> },
+    /// queenmama://auth/callback?code=...&state=...
+    AuthCallback {
+        code: Option<String>,
+        state: Option<String>,
+        error: Option<String>,
+    },
+}
+
+/// Runs after sessions and windows are set up so the link the app was launched with can use them
+pub fn setup_deep_links(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let app_handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_url(&app_handle, &url);
+        }
+    });
+
+    if let Some(urls) = app.deep_link().get_current()? {
+        for url in urls {
+            handle_url(app.handle(), &url);
+        }
+    }
+
+    tracing::info!("Deep links registered");
+    Ok(())
+}
+
+/// Act on a queenmama:// URL and pass it on to the frontend; other URLs are ignored
+pub fn handle_url(app: &AppHandle, url: &Url) {
+    let Some(link) = parse(url) else {
+        if url.scheme() == URL_SCHEME {
+            tracing::warn!(url = ?url.as_str(), "Unknown link");
+        }
+        return;
+    };
+
+    if let Err(e) = open(app, &link, Caller::link(app, url)) {
+        tracing::warn!(url = ?url.as_str(), "Failed to open a link: {}", e);
+    }
+    if let Err(e) = app.emit("deep_link", &link) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
+
+pub fn parse(url: &Url) -> Option<DeepLink> {
+    if url.scheme() != URL_SCHEME {
+        return None;
+    }
+
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default();
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    match (url.host_str()?, segments.as_slice()) {
+        ("action", _) => {
+            actions::action_from_url(url).map(|id| DeepLink::Action { id: id.to_string() })
+        }
+        ("session", ["start"]) => Some(DeepLink::StartSession),
+        ("session", ["stop"]) => Some(DeepLink::StopSession),
+        ("session", [session_id]) => Some(DeepLink::OpenSession {
+            session_id: session_id.to_string(),
+        }),
+        ("settings", []) => Some(DeepLink::OpenSettings { section: None }),
+        ("settings", [section]) => Some(DeepLink::OpenSettings {
+            section: Some(section.to_string()),
+        }),
+        ("auth", ["callback"]) => Some(DeepLink::AuthCallback {
+            code: query("code"),
+            state: query("state"),
+            error: query("error"),
+        }),
+        _ => None,
+    }
+}
+
+fn open(app: &AppHandle, link: &DeepLink, caller: Caller) -> Result<(), String> {
+    match link {
+        DeepLink::Action { id } => run_action(app, caller, id),
+        DeepLink::StartSession => run_action(app, caller, "start_session"),
+        DeepLink::StopSession => run_action(app, caller, "stop_session"),
+        DeepLink::OpenSession { session_id } => window::open_session(app, session_id),
+        DeepLink::OpenSettings { section } => {
+            window::show_main(app)?;
+            app.emit("open_settings", section)
+                .map_err(|e| e.to_string())
+        }
+        DeepLink::AuthCallback { code, state, error } => {
+            #[cfg(desktop)]
+            auth::handle_callback(app, code.clone(), state.clone(), error.clone())?;
+            window::show_main(app)
+        }
+    }
+}
+
+/// Any site can open a link, so actions run only once the user has allowed the caller
+fn run_action(app: &AppHandle, caller: Caller, id: &str) -> Result<(), String> {
+    let app = app.clone();
+    let id = id.to_string();
+    tauri::async_runtime::spawn(async move {
+        if !permissions::authorize(&app, &caller, &id).await {
+            tracing::info!(name = ?caller.name, "Caller may not run {}", id);
+            return;
+        }
+        if let Err(e) = actions::run(&app, &id) {
+            tracing::warn!("Failed to run {}: {}", id, e);
+        }
+    });
+    Ok(())
+}
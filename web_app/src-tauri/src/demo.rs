@@ -0,0 +1,203 @@
+// Queen Mama LITE - Demo Mode
+// Scripted call and canned AI replies fed through the real pipeline, for sales demos and UI tests
+
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::audio::AudioSource;
+use crate::events::VersionedEmitter;
+use crate::SETTINGS_STORE;
+
+pub(crate) const DEMO_MODE_STORE_KEY: &str = "demo_mode";
+
+/// Set to 1 to force demo mode on, e.g. for end-to-end test runs
+const DEMO_MODE_ENV: &str = "QUEEN_MAMA_DEMO";
+
+/// Shown as the capture device while in demo mode
+pub const DEVICE_NAME: &str = "Demo";
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// Recording id carried by scripted transcript segments
+pub const RECORDING_ID: &str = "demo";
+
+/// How often the fake device hands over a batch of silence
+const SILENCE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Delay between streamed words of a canned reply
+const TOKEN_DELAY: Duration = Duration::from_millis(40);
+
+/// Confidence reported for every scripted word
+pub const CONFIDENCE: f32 = 0.94;
+
+pub struct ScriptLine {
+    pub source: AudioSource,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: &'static str,
+}
+
+/// A discovery call with a pricing objection, timed from the start of transcription
+pub const TRANSCRIPT: &[ScriptLine] = &[
+    ScriptLine {
+        source: AudioSource::Microphone,
+        start_ms: 1_000,
+        end_ms: 4_500,
+        text: "Thanks for making the time today. How is the quarter going so far?",
+    },
+    ScriptLine {
+        source: AudioSource::System,
+        start_ms: 5_000,
+        end_ms: 9_500,
+        text: "Busy, honestly. Our team is spending hours every week writing up call notes.",
+    },
+    ScriptLine {
+        source: AudioSource::Microphone,
+        start_ms: 10_000,
+        end_ms: 13_500,
+        text: "How many people on the team are taking calls right now?",
+    },
+    ScriptLine {
+        source: AudioSource::System,
+        start_ms: 14_000,
+        end_ms: 18_000,
+        text: "About twelve account executives, and we're hiring four more next month.",
+    },
+    ScriptLine {
+        source: AudioSource::Microphone,
+        start_ms: 18_500,
+        end_ms: 23_000,
+        text: "Got it. What happens to those notes after the call? Who reads them?",
+    },
+    ScriptLine {
+        source: AudioSource::System,
+        start_ms: 23_500,
+        end_ms: 29_000,
+        text: "Managers are supposed to review them, but most of the time nobody has time to.",
+    },
+    ScriptLine {
+        source: AudioSource::System,
+        start_ms: 30_000,
+        end_ms: 35_000,
+        text: "To be upfront, the price you sent over is a lot more than we budgeted for.",
+    },
+    ScriptLine {
+        source: AudioSource::Microphone,
+        start_ms: 35_500,
+        end_ms: 40_000,
+        text: "I appreciate you saying that. Can I ask what you were comparing it against?",
+    },
+    ScriptLine {
+        source: AudioSource::System,
+        start_ms: 40_500,
+        end_ms: 45_000,
+        text: "We looked at a couple of cheaper note takers, but they don't coach during the call.",
+    },
+    ScriptLine {
+        source: AudioSource::Microphone,
+        start_ms: 45_500,
+        end_ms: 50_000,
+        text: "Would it help if we ran a two week pilot with four of your reps first?",
+    },
+    ScriptLine {
+        source: AudioSource::System,
+        start_ms: 50_500,
+        end_ms: 54_000,
+        text: "That could work. Send me something I can share with my director.",
+    },
+];
+
+/// Canned replies, picked by the first keyword found in the prompt
+const REPLIES: &[(&[&str], &str)] = &[
+    (
+        &["summar", "recap"],
+        "They run twelve account executives, hiring four more, and lose hours to call notes that nobody reviews. \
+         They pushed back on price compared with cheaper note takers. Next step: send a two week pilot proposal for four reps \
+         that they can share with their director.",
+    ),
+    (
+        &["price", "budget", "cost", "expensive"],
+        "Acknowledge the budget concern, then tie the price to the hours their reps spend on notes each week. \
+         Ask what a missed follow-up costs them, and offer a small pilot to prove the value before a full rollout.",
+    ),
+    (
+        &["competitor", "cheaper", "alternative"],
+        "Note takers only record the call. Point out that live coaching changes the outcome while the call is still going, \
+         and ask which of their reps would benefit most from that.",
+    ),
+];
+
+const DEFAULT_REPLY: &str =
+    "Ask an open question about how they handle this today, then let them talk. \
+     Their answer will tell you which pain point to focus on next.";
+
+/// Whether demo mode is on, from the settings or the environment.
+/// The test harness always runs on the demo devices and replies.
+pub fn is_enabled(app: &AppHandle) -> bool {
+    if cfg!(feature = "test-harness") {
+        return true;
+    }
+    if std::env::var(DEMO_MODE_ENV).is_ok_and(|value| value == "1") {
+        return true;
+    }
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(DEMO_MODE_STORE_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+/// Whether demo mode is on
+#[tauri::command]
+pub fn get_demo_mode(app: AppHandle) -> bool {
+    is_enabled(&app)
+}
+
+/// Turn demo mode on or off; takes effect for the next capture, transcription or prompt
+#[tauri::command]
+pub fn set_demo_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(DEMO_MODE_STORE_KEY, enabled);
+    store.save().map_err(|e| e.to_string())?;
+
+    tracing::info!("Demo mode {}", if enabled { "on" } else { "off" });
+    app.emit("demo_mode_changed", enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// Stand in for a capture device: send silence until the receiver is dropped
+pub fn feed_silence(samples: Sender<Vec<f32>>) {
+    let batch = (SAMPLE_RATE as u128 * SILENCE_INTERVAL.as_millis() / 1000) as usize;
+    thread::spawn(move || {
+        while samples.send(vec![0.0; batch]).is_ok() {
+            thread::sleep(SILENCE_INTERVAL);
+        }
+    });
+}
+
+/// Stream the canned reply for a prompt word by word, returning the whole reply
+pub async fn reply(prompt: &str, on_token: impl FnMut(&str)) -> String {
+    stream_words(canned_reply(prompt), TOKEN_DELAY, on_token).await
+}
+
+/// The canned reply for a prompt
+pub fn canned_reply(prompt: &str) -> &'static str {
+    let prompt = prompt.to_lowercase();
+    REPLIES
+        .iter()
+        .find(|(keywords, _)| keywords.iter().any(|keyword| prompt.contains(keyword)))
+        .map(|(_, reply)| *reply)
+        .unwrap_or(DEFAULT_REPLY)
+}
+
+/// Hand text to `on_token` a word at a time, like a streaming provider, returning what was sent
+pub async fn stream_words(text: &str, delay: Duration, mut on_token: impl FnMut(&str)) -> String {
+    for token in text.split_inclusive(' ') {
+        tokio::time::sleep(delay).await;
+        on_token(token);
+    }
+    text.to_string()
+}
@@ -0,0 +1,202 @@
+// Queen Mama LITE - Diagnostics
+// Saves a crash report when the backend panics and sends it to us on the next launch if the user agrees
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_http::reqwest::Client;
+
+#[cfg(desktop)]
+use crate::auth;
+use crate::db;
+use crate::i18n::t;
+use crate::logging::LogState;
+use crate::notifications::{self, NoticeAction, NoticeKind};
+
+/// Receives crash reports the user chose to send
+const CRASH_REPORT_ENDPOINT: &str = "https://queenmama.app/api/crashes";
+
+/// Log lines leading up to the panic kept in the report
+const CRASH_LOG_LINES: usize = 200;
+
+pub struct DiagnosticsState {
+    /// One JSON file per crash, deleted once sent or dismissed
+    dir: PathBuf,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    id: String,
+    /// Unix ms
+    created_at: i64,
+    app_version: String,
+    os: String,
+    arch: String,
+    thread: Option<String>,
+    message: String,
+    /// file:line:column of the panic
+    location: Option<String>,
+    backtrace: String,
+    log_lines: Vec<String>,
+}
+
+/// Runs right after logging and strings so panics in later setup steps are caught too
+pub fn setup_diagnostics(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = app.path().app_data_dir()?.join("crashes");
+    fs::create_dir_all(&dir)?;
+
+    let app_handle = app.app_handle().clone();
+    let crash_dir = dir.clone();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info.payload());
+        let location = info.location().map(|location| location.to_string());
+        tracing::error!(
+            code = "panic",
+            critical = true,
+            location = ?location,
+            "Panic: {}",
+            message
+        );
+
+        let report = crash_report(&app_handle, message, location);
+        match save(&crash_dir, &report) {
+            Ok(()) => tracing::error!("Saved crash report {}", report.id),
+            Err(e) => tracing::error!("Failed to save crash report: {}", e),
+        }
+        previous(info);
+    }));
+
+    let pending = pending(&dir).map(|reports| reports.len()).unwrap_or(0);
+    app.manage(DiagnosticsState { dir });
+
+    // The reports are only sent once the user agrees, from settings
+    if pending > 0 {
+        tracing::info!("{} crash reports waiting to be sent", pending);
+        notifications::notify(
+            app.handle(),
+            NoticeKind::CrashReport,
+            &t!("notice.crash_report.title"),
+            &t!("notice.crash_report.body"),
+            vec![(t!("action.open_settings"), NoticeAction::OpenSettings)],
+        );
+    }
+    Ok(())
+}
+
+/// Get crash reports saved since the last launch that haven't been sent or dismissed
+#[tauri::command]
+pub fn get_pending_crash_reports(
+    state: State<'_, DiagnosticsState>,
+) -> Result<Vec<CrashReport>, String> {
+    pending(&state.dir)
+}
+
+/// Send a crash report to us; only called once the user has agreed
+#[tauri::command]
+pub async fn submit_crash_report(
+    app: AppHandle,
+    state: State<'_, DiagnosticsState>,
+    id: String,
+) -> Result<(), String> {
+    let path = report_path(&state.dir, &id)?;
+    let report = load(&path)?;
+
+    let request = Client::new().post(CRASH_REPORT_ENDPOINT).json(&report);
+    // Signed-in users' reports are linked to their account so we can follow up
+    #[cfg(desktop)]
+    let request = match auth::access_token(&app).await {
+        Ok(token) => request.bearer_auth(token),
+        Err(_) => request,
+    };
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(t!(
+            "error.crash_report_upload_failed",
+            status = response.status()
+        ));
+    }
+
+    fs::remove_file(&path).map_err(|e| e.to_string())?;
+    tracing::info!("Sent crash report {}", id);
+    Ok(())
+}
+
+/// Delete a crash report without sending it
+#[tauri::command]
+pub fn dismiss_crash_report(state: State<'_, DiagnosticsState>, id: String) -> Result<(), String> {
+    let path = report_path(&state.dir, &id)?;
+    fs::remove_file(&path).map_err(|e| e.to_string())
+}
+
+fn crash_report(app: &AppHandle, message: String, location: Option<String>) -> CrashReport {
+    // A failure to read the logs shouldn't lose the rest of the report
+    let log_lines = app
+        .try_state::<LogState>()
+        .and_then(|logs| logs.recent_lines(CRASH_LOG_LINES).ok())
+        .unwrap_or_default();
+
+    CrashReport {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: db::now_ms(),
+        app_version: app.package_info().version.to_string(),
+        os: format!(
+            "{} {}",
+            tauri_plugin_os::platform(),
+            tauri_plugin_os::version()
+        ),
+        arch: tauri_plugin_os::arch().to_string(),
+        thread: std::thread::current().name().map(str::to_string),
+        message,
+        location,
+        backtrace: Backtrace::force_capture().to_string(),
+        log_lines,
+    }
+}
+
+/// Panics carry a `&str` or a `String`, depending on whether they were formatted
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
+
+fn save(dir: &Path, report: &CrashReport) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    fs::write(dir.join(format!("{}.json", report.id)), json).map_err(|e| e.to_string())
+}
+
+fn load(path: &Path) -> Result<CrashReport, String> {
+    let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+/// Saved reports, oldest first; unreadable files are skipped
+fn pending(dir: &Path) -> Result<Vec<CrashReport>, String> {
+    let mut reports: Vec<CrashReport> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| load(&path).ok())
+        .collect();
+    reports.sort_by_key(|report| report.created_at);
+    Ok(reports)
+}
+
+/// Ids come from the webview, so only accept ones we could have written
+fn report_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    let path = dir.join(format!("{}.json", id));
+    if uuid::Uuid::parse_str(id).is_err() || !path.is_file() {
+        return Err(t!("error.crash_report_not_found", id = id));
+    }
+    Ok(path)
+}
@@ -0,0 +1,164 @@
+// Queen Mama LITE - Duplicate Sessions
+// Finds sessions that recorded the same meeting twice, e.g. started automatically and again by
+// hand, so they can be merged before they're counted twice in analytics and summaries
+
+use std::collections::HashMap;
+
+use rusqlite::{params, params_from_iter, Connection};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::{self, Database, Session};
+use crate::events::VersionedEmitter;
+
+/// Sessions overlapping at least this share of the shorter one's length are duplicates
+const MIN_OVERLAP_PERCENT: i64 = 50;
+
+/// Pairs of finished sessions overlapping enough, smaller id first, leaving dismissed pairs out
+const DUPLICATE_PAIRS: &str = "SELECT a.id, b.id FROM sessions a
+    JOIN sessions b ON b.started_at < a.ended_at AND a.started_at < b.ended_at AND a.id < b.id
+    WHERE a.deleted_at IS NULL AND b.deleted_at IS NULL
+      AND a.ended_at IS NOT NULL AND b.ended_at IS NOT NULL
+      AND (?1 IS NULL OR a.id = ?1 OR b.id = ?1)
+      AND (MIN(a.ended_at, b.ended_at) - MAX(a.started_at, b.started_at)) * 100
+          >= MIN(a.ended_at - a.started_at, b.ended_at - b.started_at) * ?2
+      AND NOT EXISTS (SELECT 1 FROM dismissed_duplicates d
+                      WHERE d.session_id = a.id AND d.other_id = b.id)";
+
+/// Emitted as `duplicate_sessions_found` when a session that just ended overlaps others
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DuplicatesFound {
+    session_id: String,
+    duplicate_ids: Vec<String>,
+}
+
+/// Groups of sessions that look like the same meeting, each ordered by start; merge a group
+/// with `merge_sessions` or keep it apart with `dismiss_duplicates`
+#[tauri::command]
+pub fn find_duplicate_sessions(db: State<'_, Database>) -> Result<Vec<Vec<Session>>, String> {
+    let connection = db.connection()?;
+    let pairs = duplicate_pairs(&connection, None).map_err(|e| e.to_string())?;
+
+    // Sessions are grouped by following pairs to the first session they lead to
+    let mut group_of: HashMap<String, String> = HashMap::new();
+    for (a, b) in pairs {
+        let root_a = root(&group_of, &a);
+        let root_b = root(&group_of, &b);
+        if root_a != root_b {
+            group_of.insert(root_b, root_a.clone());
+        }
+        group_of.entry(a).or_insert_with(|| root_a.clone());
+        group_of.entry(b).or_insert(root_a);
+    }
+    if group_of.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ids: Vec<&String> = group_of.keys().collect();
+    let sessions: Vec<(String, Session)> = connection
+        .prepare(&format!(
+            "SELECT {} FROM sessions s WHERE s.id IN ({}) ORDER BY s.started_at DESC",
+            db::SESSION_COLUMNS,
+            vec!["?"; ids.len()].join(", ")
+        ))
+        .and_then(|mut statement| {
+            statement
+                .query_map(params_from_iter(&ids), |row| {
+                    Ok((row.get(0)?, db::session_from_row(row)?))
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())?;
+
+    // Groups come most recent meeting first, sessions within one by start
+    let mut duplicates: Vec<Vec<Session>> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (id, session) in sessions {
+        let position = *positions.entry(root(&group_of, &id)).or_insert_with(|| {
+            duplicates.push(Vec::new());
+            duplicates.len() - 1
+        });
+        duplicates[position].insert(0, session);
+    }
+    Ok(duplicates)
+}
+
+/// Mark sessions as separate meetings, so they're no longer offered for merging together
+#[tauri::command]
+pub fn dismiss_duplicates(db: State<'_, Database>, ids: Vec<String>) -> Result<(), String> {
+    let mut connection = db.connection()?;
+    let transaction = connection.transaction().map_err(|e| e.to_string())?;
+    for a in &ids {
+        for b in ids.iter().filter(|b| a < *b) {
+            transaction
+                .execute(
+                    "INSERT OR IGNORE INTO dismissed_duplicates (session_id, other_id)
+                     SELECT ?1, ?2 WHERE EXISTS (SELECT 1 FROM sessions WHERE id = ?1)
+                                     AND EXISTS (SELECT 1 FROM sessions WHERE id = ?2)",
+                    params![a, b],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    transaction.commit().map_err(|e| e.to_string())
+}
+
+/// Offer to merge a session that just ended with the sessions it duplicates, if any
+pub fn check_session(app: &AppHandle, session_id: &str) {
+    let pairs = match app.state::<Database>().connection() {
+        Ok(connection) => duplicate_pairs(&connection, Some(session_id)),
+        Err(e) => {
+            tracing::warn!("Duplicate check skipped: {}", e);
+            return;
+        }
+    };
+    let duplicate_ids: Vec<String> = match pairs {
+        Ok(pairs) => pairs
+            .into_iter()
+            .map(|(a, b)| if a == session_id { b } else { a })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Duplicate check failed: {}", e);
+            return;
+        }
+    };
+    if duplicate_ids.is_empty() {
+        return;
+    }
+
+    tracing::info!(
+        "Session {} overlaps {} other sessions",
+        session_id,
+        duplicate_ids.len()
+    );
+    let found = DuplicatesFound {
+        session_id: session_id.to_string(),
+        duplicate_ids,
+    };
+    if let Err(e) = app.emit("duplicate_sessions_found", found) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
+
+fn duplicate_pairs(
+    connection: &Connection,
+    session_id: Option<&str>,
+) -> rusqlite::Result<Vec<(String, String)>> {
+    connection
+        .prepare(DUPLICATE_PAIRS)
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id, MIN_OVERLAP_PERCENT], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?
+                .collect()
+        })
+}
+
+fn root(group_of: &HashMap<String, String>, id: &str) -> String {
+    let mut id = id;
+    while let Some(parent) = group_of.get(id).filter(|parent| *parent != id) {
+        id = parent;
+    }
+    id.to_string()
+}
@@ -0,0 +1,187 @@
+// Queen Mama LITE - Passphrase Encryption
+// Encrypts a profile's session history, recordings, attachments, imports and exports with a
+// passphrase only the user knows, and locks them again after a while unused
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use tauri::{App, AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::{profiles, reindex, session, vault, SETTINGS_STORE};
+
+/// Minutes unused before the history locks again; 0 keeps it unlocked until quitting
+pub(crate) const LOCK_TIMEOUT_STORE_KEY: &str = "encryption_lock_timeout";
+
+pub(crate) const DEFAULT_LOCK_TIMEOUT_MINUTES: u32 = 15;
+
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// How often the lock timeout is checked
+const LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    /// The active profile's history is encrypted with a passphrase
+    enabled: bool,
+    /// It's waiting for the passphrase; session history calls fail until then
+    locked: bool,
+}
+
+pub fn setup_encryption(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let app_handle = app.app_handle().clone();
+    thread::spawn(move || loop {
+        thread::sleep(LOCK_CHECK_INTERVAL);
+        lock_if_idle(&app_handle);
+    });
+    Ok(())
+}
+
+/// Get whether the active profile's history is encrypted with a passphrase, and whether it's locked
+#[tauri::command]
+pub fn get_encryption_status(app: AppHandle) -> EncryptionStatus {
+    status(&app)
+}
+
+/// Encrypt the active profile's history with a passphrase, and its recordings, attachments and
+/// imports in the background. It can't be recovered without it, and it's asked for on every launch.
+#[tauri::command]
+pub fn enable_encryption(app: AppHandle, passphrase: String) -> Result<EncryptionStatus, String> {
+    let active = profiles::load(&app).active;
+    if profiles::has_passphrase(&app, &active) {
+        return Err(t!("error.encryption_enabled"));
+    }
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(t!("error.passphrase_too_short", min = MIN_PASSPHRASE_LEN));
+    }
+    if session::active_session_id(&app).is_some() {
+        return Err(t!("error.encryption_in_session"));
+    }
+
+    let (path, key) = profiles::database_location(&app, &active)?;
+    db::rekey(&app.state::<Database>(), &path, &key, &passphrase)?;
+    profiles::set_passphrase(&app, &active)?;
+    vault::unlock(&app, &profiles::active_data_dir(&app)?, &passphrase)?;
+    seal_existing_files(&app);
+
+    tracing::info!("Encrypted session history with a passphrase");
+    Ok(state_changed(&app))
+}
+
+/// Unlock the active profile's history with its passphrase
+#[tauri::command]
+pub fn unlock(app: AppHandle, passphrase: String) -> Result<EncryptionStatus, String> {
+    let db = app.state::<Database>();
+    if !db.is_sealed() {
+        return Ok(status(&app));
+    }
+
+    let path = profiles::database_path(&app, &profiles::load(&app).active)?;
+    // A wrong key only shows when the file is first read, as "file is not a database"
+    db::reopen(&db, &path, Some(&passphrase)).map_err(|e| {
+        tracing::warn!("Failed to unlock session history: {}", e);
+        t!("error.wrong_passphrase")
+    })?;
+    vault::unlock(&app, &profiles::active_data_dir(&app)?, &passphrase)?;
+    seal_existing_files(&app);
+
+    tracing::info!("Unlocked session history");
+    db::sessions_changed(&app);
+    reindex::resume(&app);
+    Ok(state_changed(&app))
+}
+
+/// Lock the active profile's history until its passphrase is entered again
+#[tauri::command]
+pub fn lock(app: AppHandle) -> Result<EncryptionStatus, String> {
+    if !profiles::has_passphrase(&app, &profiles::load(&app).active) {
+        return Err(t!("error.encryption_disabled"));
+    }
+    if session::active_session_id(&app).is_some() {
+        return Err(t!("error.encryption_in_session"));
+    }
+    db::seal(&app.state::<Database>())?;
+    vault::lock(&app);
+
+    tracing::info!("Locked session history");
+    Ok(state_changed(&app))
+}
+
+/// Decrypt a file exported from a passphrase-protected profile into `target`, e.g. to open it in
+/// another app. Works from any profile, or none, with the passphrase of the one it came from.
+#[tauri::command]
+pub async fn decrypt_export(
+    path: PathBuf,
+    target: PathBuf,
+    passphrase: String,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        vault::decrypt(&path, &target, &passphrase)?;
+        tracing::info!(path = ?path, "Decrypted a file");
+        Ok(target.to_string_lossy().into_owned())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Seal files kept from before the passphrase was set, or left over by an earlier failure
+fn seal_existing_files(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || match vault::seal_existing(&app) {
+        Ok(0) => {}
+        Ok(sealed) => tracing::info!("Encrypted {} recordings, attachments and imports", sealed),
+        Err(e) => tracing::error!("Failed to encrypt recordings, attachments and imports: {}", e),
+    });
+}
+
+/// Lock once nothing has used the history for the timeout; never mid-session
+fn lock_if_idle(app: &AppHandle) {
+    let db = app.state::<Database>();
+    if db.is_sealed() || !profiles::has_passphrase(app, &profiles::load(app).active) {
+        return;
+    }
+    let minutes = lock_timeout_minutes(app);
+    if minutes == 0 || db.idle_ms() < minutes as i64 * 60_000 {
+        return;
+    }
+    if session::active_session_id(app).is_some() {
+        return;
+    }
+
+    match db::seal(&db) {
+        Ok(()) => {
+            vault::lock(app);
+            tracing::info!("Locked session history after {} minutes unused", minutes);
+            state_changed(app);
+        }
+        Err(e) => tracing::error!("Failed to lock session history: {}", e),
+    }
+}
+
+fn lock_timeout_minutes(app: &AppHandle) -> u32 {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(LOCK_TIMEOUT_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or(DEFAULT_LOCK_TIMEOUT_MINUTES)
+}
+
+fn status(app: &AppHandle) -> EncryptionStatus {
+    EncryptionStatus {
+        enabled: profiles::has_passphrase(app, &profiles::load(app).active),
+        locked: app.state::<Database>().is_sealed(),
+    }
+}
+
+fn state_changed(app: &AppHandle) -> EncryptionStatus {
+    let status = status(app);
+    if let Err(e) = app.emit("encryption_state_changed", &status) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+    status
+}
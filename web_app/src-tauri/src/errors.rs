@@ -0,0 +1,144 @@
+// Queen Mama LITE - Backend Errors
+// Turns every warning and error the backend logs into a `backend_error` event the frontend can toast
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, State};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::db;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::logging;
+
+/// Errors kept for `get_recent_errors`, oldest dropped first
+const MAX_RECENT_ERRORS: usize = 100;
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorSeverity {
+    Warning,
+    Error,
+    /// The app or a feature stopped working, e.g. a panic
+    Critical,
+}
+
+/// Emitted as `backend_error`
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendError {
+    id: String,
+    /// Set with a `code` field on the log line, e.g. "audio.stream_failed"; defaults to the module
+    code: String,
+    severity: ErrorSeverity,
+    module: String,
+    message: String,
+    /// What the user can do about it, from the `remediation.<code>` string when there is one
+    remediation: Option<String>,
+    /// Unix ms
+    timestamp: i64,
+}
+
+#[derive(Clone, Default)]
+pub struct ErrorState(Arc<Mutex<VecDeque<BackendError>>>);
+
+/// Installed by `logging::setup_logging` for warnings and errors, whatever the log level
+pub struct ErrorLayer {
+    app: AppHandle,
+    recent: ErrorState,
+}
+
+impl ErrorLayer {
+    pub fn new(app: AppHandle, recent: &ErrorState) -> Self {
+        Self {
+            app,
+            recent: recent.clone(),
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ErrorLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        // Warnings from dependencies aren't ours to report
+        let Some(module) = logging::module_name(metadata.target()) else {
+            return;
+        };
+
+        let mut fields = ErrorFields::default();
+        event.record(&mut fields);
+
+        let code = fields.code.unwrap_or_else(|| module.to_string());
+        let remediation_key = format!("remediation.{}", code);
+        let remediation = t!(&remediation_key);
+        let severity = if fields.critical {
+            ErrorSeverity::Critical
+        } else if *metadata.level() == Level::ERROR {
+            ErrorSeverity::Error
+        } else {
+            ErrorSeverity::Warning
+        };
+        let error = BackendError {
+            id: uuid::Uuid::new_v4().to_string(),
+            code,
+            severity,
+            module: module.to_string(),
+            message: fields.message,
+            remediation: (remediation != remediation_key).then_some(remediation),
+            timestamp: db::now_ms(),
+        };
+
+        if let Ok(mut recent) = self.recent.0.lock() {
+            if recent.len() >= MAX_RECENT_ERRORS {
+                recent.pop_front();
+            }
+            recent.push_back(error.clone());
+        }
+        // Not logged on failure, which would come straight back here
+        let _ = self.app.emit("backend_error", &error);
+    }
+}
+
+/// Get the errors reported since launch, oldest first
+#[tauri::command]
+pub fn get_recent_errors(state: State<'_, ErrorState>) -> Vec<BackendError> {
+    state
+        .0
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// The fields we read off a log line
+#[derive(Default)]
+struct ErrorFields {
+    message: String,
+    code: Option<String>,
+    critical: bool,
+}
+
+impl Visit for ErrorFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "code" => self.code = Some(value.to_string()),
+            "message" => self.message = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if field.name() == "critical" {
+            self.critical = value;
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Queen Mama LITE - Event Versioning
+// Wraps emitted payloads in the envelope version each consumer asked for, so payloads can evolve
+// without breaking older frontends and companion apps
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::Serialize;
+use tauri::{App, Emitter, Manager, State, Wry};
+
+use crate::db;
+use crate::i18n::t;
+
+/// Envelope versions we can send, oldest first
+pub const EVENT_VERSIONS: &[u32] = &[1, 2];
+
+/// Consumers that never negotiate get what they always got: the bare payload
+pub const DEFAULT_EVENT_VERSION: u32 = 1;
+
+pub struct EventVersionState {
+    /// Shared by every window, since they all load the same frontend
+    frontend: AtomicU32,
+}
+
+impl Default for EventVersionState {
+    fn default() -> Self {
+        Self {
+            frontend: AtomicU32::new(DEFAULT_EVENT_VERSION),
+        }
+    }
+}
+
+/// Version 2: the payload with its name, version and time, so consumers can tell shapes apart
+#[derive(Clone, Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope<T> {
+    pub version: u32,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Unix milliseconds
+    pub emitted_at: i64,
+    pub payload: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(kind: &str, payload: T) -> Self {
+        Self {
+            version: 2,
+            kind: kind.to_string(),
+            emitted_at: db::now_ms(),
+            payload,
+        }
+    }
+}
+
+pub fn setup_events(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(EventVersionState::default());
+    Ok(())
+}
+
+/// Agree on the newest event version both sides support; every window gets events in it from now on
+#[tauri::command]
+pub fn negotiate_event_version(
+    state: State<'_, EventVersionState>,
+    supported: Vec<u32>,
+) -> Result<u32, String> {
+    let version = negotiate(&supported).ok_or_else(|| unsupported(&supported))?;
+    state.frontend.store(version, Ordering::SeqCst);
+    tracing::info!("Frontend events use version {}", version);
+    Ok(version)
+}
+
+/// The newest version in `supported` that we can send
+pub fn negotiate(supported: &[u32]) -> Option<u32> {
+    EVENT_VERSIONS
+        .iter()
+        .rev()
+        .find(|version| supported.contains(version))
+        .copied()
+}
+
+pub fn unsupported(supported: &[u32]) -> String {
+    let list = |versions: &[u32]| {
+        versions
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    t!(
+        "error.event_version_unsupported",
+        requested = list(supported),
+        supported = list(EVENT_VERSIONS)
+    )
+}
+
+/// `emit` in the version the frontend negotiated. Import this instead of `tauri::Emitter`
+/// so every event goes through it.
+pub trait VersionedEmitter {
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()>;
+}
+
+impl<M: Manager<Wry> + Emitter<Wry>> VersionedEmitter for M {
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+        let version = self
+            .try_state::<EventVersionState>()
+            .map(|state| state.frontend.load(Ordering::SeqCst))
+            .unwrap_or(DEFAULT_EVENT_VERSION);
+        match version {
+            1 => Emitter::emit(self, event, payload),
+            _ => Emitter::emit(self, event, Envelope::new(event, payload)),
+        }
+    }
+}
@@ -0,0 +1,486 @@
+// Queen Mama LITE - Session Export
+// Renders a session's transcript and coaching notes to Markdown, JSON or PDF
+
+use std::fmt::Write as _;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+};
+use rusqlite::{params, OptionalExtension};
+use tauri::{AppHandle, Manager};
+
+use crate::db::Database;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::notes::format_time;
+use crate::tags::{session_fields, session_tags, SessionField};
+use crate::vault;
+
+/// Identifies JSON exports
+const EXPORT_FORMAT: &str = "queen-mama-session";
+const EXPORT_VERSION: u32 = 1;
+
+/// `export_progress` is emitted after this many segments and notes
+const PROGRESS_EVERY: usize = 200;
+
+/// A4 with 20mm margins
+const PAGE_WIDTH: Mm = Mm(210.0);
+const PAGE_HEIGHT: Mm = Mm(297.0);
+const PAGE_MARGIN: f32 = 20.0;
+
+const TITLE_SIZE: f32 = 18.0;
+const HEADING_SIZE: f32 = 13.0;
+const BODY_SIZE: f32 = 10.0;
+
+/// Characters per line of body text; Helvetica averages about half its size in width
+const LINE_CHARS: usize = 95;
+
+/// Points to millimetres, for line heights
+const PT_TO_MM: f32 = 0.3528;
+
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionExport {
+    format: &'static str,
+    version: u32,
+    title: String,
+    started_at: i64,
+    ended_at: Option<i64>,
+    tags: Vec<String>,
+    fields: Vec<SessionField>,
+    segments: Vec<ExportSegment>,
+    notes: Vec<ExportNote>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportSegment {
+    speaker: Option<String>,
+    text: String,
+    start_ms: i64,
+    end_ms: i64,
+    confidence: Option<f32>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportNote {
+    /// "suggestion" for AI coaching, "note" for the user's own notes
+    kind: String,
+    content: String,
+    created_at: i64,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportProgress {
+    session_id: String,
+    /// Segments and notes rendered so far
+    done: usize,
+    total: usize,
+}
+
+/// Emits `export_progress` every so often while rendering
+struct Progress<'a> {
+    app: &'a AppHandle,
+    session_id: &'a str,
+    done: usize,
+    total: usize,
+}
+
+impl Progress<'_> {
+    fn advance(&mut self) {
+        self.done += 1;
+        if self.done % PROGRESS_EVERY == 0 || self.done == self.total {
+            self.emit();
+        }
+    }
+
+    fn emit(&self) {
+        let progress = ExportProgress {
+            session_id: self.session_id.to_string(),
+            done: self.done,
+            total: self.total,
+        };
+        if let Err(e) = self.app.emit("export_progress", progress) {
+            tracing::error!("Failed to emit event: {}", e);
+        }
+    }
+}
+
+/// Write a session's transcript and coaching notes to `path` as Markdown, JSON or PDF, encrypted
+/// if the profile has a passphrase. Long sessions report `export_progress` as they go.
+#[tauri::command]
+pub async fn export_session(
+    app: AppHandle,
+    session_id: String,
+    format: ExportFormat,
+    path: PathBuf,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || export(&app, &session_id, format, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Show a file selected in Finder or Explorer, e.g. one just exported
+#[cfg(desktop)]
+#[tauri::command]
+pub fn reveal_in_file_manager(path: PathBuf) -> Result<(), String> {
+    if !path.exists() {
+        return Err(t!("error.file_not_found", path = path.display()));
+    }
+
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open")
+        .arg("-R")
+        .arg(&path)
+        .spawn()
+        .map(|_| ());
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("explorer")
+        .arg("/select,")
+        .arg(&path)
+        .spawn()
+        .map(|_| ());
+    // File managers on Linux don't agree on a way to select a file, so open its folder
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let result = open::that(path.parent().unwrap_or(&path));
+
+    result.map_err(|e| e.to_string())
+}
+
+fn export(
+    app: &AppHandle,
+    session_id: &str,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<String, String> {
+    let (_, rendered) = render(app, session_id, format)?;
+    vault::write(app, path, rendered)?;
+
+    tracing::info!(path = ?path, "Exported session {}", session_id);
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// A session's title and its rendering in `format`, reporting `export_progress` as it goes
+pub(crate) fn render(
+    app: &AppHandle,
+    session_id: &str,
+    format: ExportFormat,
+) -> Result<(String, Vec<u8>), String> {
+    let session = load(&app.state::<Database>(), session_id)?;
+    let mut progress = Progress {
+        app,
+        session_id,
+        done: 0,
+        total: session.segments.len() + session.notes.len(),
+    };
+    progress.emit();
+
+    let rendered = match format {
+        ExportFormat::Markdown => markdown(&session, &mut progress).into_bytes(),
+        ExportFormat::Json => {
+            let json = serde_json::to_vec_pretty(&session).map_err(|e| e.to_string())?;
+            progress.done = progress.total;
+            progress.emit();
+            json
+        }
+        ExportFormat::Pdf => {
+            let mut buffer = BufWriter::new(Vec::new());
+            pdf(&session, &mut progress)?
+                .save(&mut buffer)
+                .map_err(|e| e.to_string())?;
+            buffer.into_inner().map_err(|e| e.to_string())?
+        }
+    };
+    Ok((session.title, rendered))
+}
+
+fn load(db: &Database, session_id: &str) -> Result<SessionExport, String> {
+    let connection = db.connection()?;
+    let (title, started_at, ended_at): (String, i64, Option<i64>) = connection
+        .query_row(
+            "SELECT title, started_at, ended_at FROM sessions WHERE id = ?1",
+            params![session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.session_not_found", id = session_id))?;
+    let tags = session_tags(&connection, session_id).map_err(|e| e.to_string())?;
+    let fields = session_fields(&connection, session_id).map_err(|e| e.to_string())?;
+
+    let segments = connection
+        .prepare(
+            "SELECT speaker, text, start_ms, end_ms, confidence FROM transcript_segments
+             WHERE session_id = ?1 ORDER BY start_ms, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok(ExportSegment {
+                        speaker: row.get(0)?,
+                        text: row.get(1)?,
+                        start_ms: row.get(2)?,
+                        end_ms: row.get(3)?,
+                        confidence: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    let notes = connection
+        .prepare(
+            "SELECT kind, content, created_at FROM notes
+             WHERE session_id = ?1 ORDER BY created_at, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok(ExportNote {
+                        kind: row.get(0)?,
+                        content: row.get(1)?,
+                        created_at: row.get(2)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(SessionExport {
+        format: EXPORT_FORMAT,
+        version: EXPORT_VERSION,
+        title,
+        started_at,
+        ended_at,
+        tags,
+        fields,
+        segments,
+        notes,
+    })
+}
+
+fn markdown(session: &SessionExport, progress: &mut Progress) -> String {
+    let mut markdown = format!("# {}\n\n_{}_\n", session.title, subtitle(session));
+    for line in details(session) {
+        let _ = write!(markdown, "\n{}\n", line);
+    }
+
+    if !session.segments.is_empty() {
+        let _ = write!(markdown, "\n## {}\n\n", t!("export.transcript"));
+        for segment in &session.segments {
+            let _ = writeln!(markdown, "{}\n", segment_line(segment, "**"));
+            progress.advance();
+        }
+    }
+
+    if !session.notes.is_empty() {
+        let _ = writeln!(markdown, "\n## {}", t!("export.coaching_notes"));
+        for note in &session.notes {
+            let _ = write!(
+                markdown,
+                "\n### {}\n\n{}\n",
+                note_heading(session, note),
+                note.content.trim()
+            );
+            progress.advance();
+        }
+    }
+    markdown
+}
+
+fn pdf(session: &SessionExport, progress: &mut Progress) -> Result<PdfDocumentReference, String> {
+    let (document, page, layer) =
+        PdfDocument::new(&session.title, PAGE_WIDTH, PAGE_HEIGHT, "Layer 1");
+    let regular = document
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| e.to_string())?;
+    let bold = document
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| e.to_string())?;
+    let mut writer = PdfWriter {
+        layer: document.get_page(page).get_layer(layer),
+        document,
+        y: PAGE_HEIGHT.0 - PAGE_MARGIN,
+    };
+
+    writer.line(&session.title, TITLE_SIZE, &bold);
+    writer.paragraph(&subtitle(session), BODY_SIZE, &regular);
+    for line in details(session) {
+        writer.paragraph(&line, BODY_SIZE, &regular);
+    }
+
+    if !session.segments.is_empty() {
+        writer.gap();
+        writer.line(&t!("export.transcript"), HEADING_SIZE, &bold);
+        for segment in &session.segments {
+            writer.paragraph(&segment_line(segment, ""), BODY_SIZE, &regular);
+            progress.advance();
+        }
+    }
+
+    if !session.notes.is_empty() {
+        writer.gap();
+        writer.line(&t!("export.coaching_notes"), HEADING_SIZE, &bold);
+        for note in &session.notes {
+            writer.gap();
+            writer.line(&note_heading(session, note), BODY_SIZE, &bold);
+            for paragraph in note.content.trim().lines() {
+                writer.paragraph(paragraph, BODY_SIZE, &regular);
+            }
+            progress.advance();
+        }
+    }
+    Ok(writer.document)
+}
+
+/// Lays out lines top to bottom, starting a new page when one fills up
+struct PdfWriter {
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    /// Baseline of the next line, from the bottom of the page
+    y: f32,
+}
+
+impl PdfWriter {
+    fn line(&mut self, text: &str, size: f32, font: &IndirectFontRef) {
+        let height = size * PT_TO_MM * 1.4;
+        if self.y - height < PAGE_MARGIN {
+            let (page, layer) = self.document.add_page(PAGE_WIDTH, PAGE_HEIGHT, "Layer 1");
+            self.layer = self.document.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT.0 - PAGE_MARGIN;
+        }
+        self.y -= height;
+        self.layer
+            .use_text(pdf_text(text), size, Mm(PAGE_MARGIN), Mm(self.y), font);
+    }
+
+    /// Text wrapped to the page width
+    fn paragraph(&mut self, text: &str, size: f32, font: &IndirectFontRef) {
+        for line in wrap(text, LINE_CHARS) {
+            self.line(&line, size, font);
+        }
+    }
+
+    fn gap(&mut self) {
+        self.y -= BODY_SIZE * PT_TO_MM;
+    }
+}
+
+/// When the session started and how long it ran
+fn subtitle(session: &SessionExport) -> String {
+    let started = t!("document.started", time = format_time(session.started_at));
+    match session.ended_at {
+        Some(ended_at) => format!(
+            "{} · {}",
+            started,
+            format_offset(ended_at - session.started_at)
+        ),
+        None => started,
+    }
+}
+
+/// "Tags: hiring, onsite" and a "Name: value" line per custom field
+fn details(session: &SessionExport) -> Vec<String> {
+    let mut lines = Vec::new();
+    if !session.tags.is_empty() {
+        lines.push(t!("export.tags", tags = session.tags.join(", ")));
+    }
+    for field in &session.fields {
+        lines.push(format!("{}: {}", field.name, field.value.display()));
+    }
+    lines
+}
+
+/// "[1:05] me: text", with the time and speaker between `emphasis`
+fn segment_line(segment: &ExportSegment, emphasis: &str) -> String {
+    let label = match &segment.speaker {
+        Some(speaker) => format!("[{}] {}:", format_offset(segment.start_ms), speaker),
+        None => format!("[{}]", format_offset(segment.start_ms)),
+    };
+    format!("{0}{1}{0} {2}", emphasis, label, segment.text.trim())
+}
+
+/// "Suggestion · 2024-05-01 10:05 (12:30 in)"
+fn note_heading(session: &SessionExport, note: &ExportNote) -> String {
+    let kind = if note.kind == "suggestion" {
+        t!("document.suggestion")
+    } else {
+        t!("document.note")
+    };
+    t!(
+        "export.note_heading",
+        kind = kind,
+        time = format_time(note.created_at),
+        offset = format_offset(note.created_at - session.started_at)
+    )
+}
+
+/// "m:ss", or "h:mm:ss" from an hour
+pub(crate) fn format_offset(ms: i64) -> String {
+    let seconds = ms.max(0) / 1000;
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
+
+/// Break text into lines of at most `width` characters, at spaces where possible
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word;
+        // Words longer than a line are cut
+        while word.chars().count() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let cut = word
+                .char_indices()
+                .nth(width)
+                .map_or(word.len(), |(i, _)| i);
+            lines.push(word[..cut].to_string());
+            word = &word[cut..];
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// The built-in PDF fonts only cover Western European text; anything else prints as "?"
+fn pdf_text(text: &str) -> String {
+    text.chars()
+        .map(|c| if (c as u32) < 0x100 { c } else { '?' })
+        .collect()
+}
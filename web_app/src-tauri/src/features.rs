@@ -0,0 +1,237 @@
+// Queen Mama LITE - Feature Availability
+// Says which features work on this machine right now, and why the others don't
+
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::i18n::t;
+use crate::{ai, audio, demo, notifications, SETTINGS_STORE};
+#[cfg(desktop)]
+use crate::{capture, ocr, shortcut_capture, transcription};
+
+/// Features turned off by an administrator, e.g. in a settings.json deployed with the app
+pub(crate) const DISABLED_FEATURES_STORE_KEY: &str = "disabled_features";
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Feature {
+    AiAssist,
+    Microphone,
+    SystemAudio,
+    LocalTranscription,
+    ScreenCapture,
+    ScreenOcr,
+    ActiveWindow,
+    GlobalShortcuts,
+    ShortcutCapture,
+    Pedals,
+    WatchFolder,
+    Notifications,
+    SignIn,
+    Updates,
+    Relay,
+}
+
+impl Feature {
+    const ALL: [Feature; 15] = [
+        Feature::AiAssist,
+        Feature::Microphone,
+        Feature::SystemAudio,
+        Feature::LocalTranscription,
+        Feature::ScreenCapture,
+        Feature::ScreenOcr,
+        Feature::ActiveWindow,
+        Feature::GlobalShortcuts,
+        Feature::ShortcutCapture,
+        Feature::Pedals,
+        Feature::WatchFolder,
+        Feature::Notifications,
+        Feature::SignIn,
+        Feature::Updates,
+        Feature::Relay,
+    ];
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Permission {
+    ScreenRecording,
+    Accessibility,
+    Notifications,
+}
+
+impl Permission {
+    /// How to grant it, ready to show
+    fn hint(self) -> String {
+        match self {
+            Permission::ScreenRecording => t!("feature.permission.screen_recording"),
+            Permission::Accessibility => t!("feature.permission.accessibility"),
+            Permission::Notifications => t!("feature.permission.notifications"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum UnavailableReason {
+    MissingPermission {
+        permission: Permission,
+    },
+    UnsupportedPlatform,
+    NoApiKey,
+    DisabledByPolicy,
+    /// Something has to be installed or set up first, e.g. a model or a loopback device
+    NotSetUp,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureAvailability {
+    feature: Feature,
+    available: bool,
+    reason: Option<UnavailableReason>,
+    /// Why it's unavailable, ready to show
+    message: Option<String>,
+}
+
+type Unavailable = (UnavailableReason, String);
+
+/// Report every feature and, for those that can't be used right now, why not
+#[tauri::command]
+pub async fn get_feature_availability(app: AppHandle) -> Result<Vec<FeatureAvailability>, String> {
+    // Device, keychain and permission checks can block
+    tauri::async_runtime::spawn_blocking(move || {
+        let disabled = disabled_features(&app);
+        Feature::ALL
+            .into_iter()
+            .map(|feature| {
+                let result = if disabled.contains(&feature) {
+                    Err((
+                        UnavailableReason::DisabledByPolicy,
+                        t!("feature.disabled_by_policy"),
+                    ))
+                } else {
+                    check(&app, feature)
+                };
+                match result {
+                    Ok(()) => FeatureAvailability {
+                        feature,
+                        available: true,
+                        reason: None,
+                        message: None,
+                    },
+                    Err((reason, message)) => FeatureAvailability {
+                        feature,
+                        available: false,
+                        reason: Some(reason),
+                        message: Some(message),
+                    },
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+fn check(app: &AppHandle, feature: Feature) -> Result<(), Unavailable> {
+    // Demo mode fakes the call and the AI, so it needs no device, model or key
+    let demo_ready = matches!(
+        feature,
+        Feature::AiAssist
+            | Feature::Microphone
+            | Feature::SystemAudio
+            | Feature::LocalTranscription
+    );
+    if demo_ready && demo::is_enabled(app) {
+        return Ok(());
+    }
+
+    match feature {
+        Feature::AiAssist => {
+            if ai::missing_api_key(app) {
+                return Err((UnavailableReason::NoApiKey, t!("error.ai_no_api_key")));
+            }
+            Ok(())
+        }
+        Feature::Microphone => audio::find_input_device(None)
+            .map(|_| ())
+            .map_err(not_set_up),
+        Feature::SystemAudio => audio::find_system_device().map(|_| ()).map_err(not_set_up),
+        Feature::Notifications => {
+            if !notifications::permission_granted(app) {
+                return Err(missing(Permission::Notifications));
+            }
+            Ok(())
+        }
+        Feature::Relay => Ok(()),
+        Feature::LocalTranscription
+        | Feature::ScreenCapture
+        | Feature::ScreenOcr
+        | Feature::ActiveWindow
+        | Feature::GlobalShortcuts
+        | Feature::ShortcutCapture
+        | Feature::Pedals
+        | Feature::WatchFolder
+        | Feature::SignIn
+        | Feature::Updates => check_desktop(app, feature),
+    }
+}
+
+#[cfg(desktop)]
+fn check_desktop(app: &AppHandle, feature: Feature) -> Result<(), Unavailable> {
+    match feature {
+        Feature::LocalTranscription if transcription::is_mock_selected(app) => Ok(()),
+        Feature::LocalTranscription => transcription::selected_model_path(app)
+            .map(|_| ())
+            .map_err(not_set_up),
+        // Window titles are hidden on macOS without screen recording too
+        Feature::ScreenCapture | Feature::ActiveWindow => screen_recording(),
+        Feature::ScreenOcr => {
+            screen_recording()?;
+            ocr::check_engine().map_err(not_set_up)
+        }
+        Feature::ShortcutCapture => {
+            if !shortcut_capture::has_input_monitoring_permission() {
+                return Err(missing(Permission::Accessibility));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(mobile)]
+fn check_desktop(_app: &AppHandle, _feature: Feature) -> Result<(), Unavailable> {
+    Err((
+        UnavailableReason::UnsupportedPlatform,
+        t!("feature.desktop_only"),
+    ))
+}
+
+#[cfg(desktop)]
+fn screen_recording() -> Result<(), Unavailable> {
+    if !capture::has_screen_recording_permission() {
+        return Err(missing(Permission::ScreenRecording));
+    }
+    Ok(())
+}
+
+fn missing(permission: Permission) -> Unavailable {
+    (
+        UnavailableReason::MissingPermission { permission },
+        permission.hint(),
+    )
+}
+
+fn not_set_up(message: String) -> Unavailable {
+    (UnavailableReason::NotSetUp, message)
+}
+
+fn disabled_features(app: &AppHandle) -> Vec<Feature> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(DISABLED_FEATURES_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
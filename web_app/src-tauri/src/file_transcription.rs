@@ -0,0 +1,617 @@
+// Queen Mama LITE - File Transcription
+// Transcribes recordings made elsewhere, like calls recorded on a phone, with the local Whisper
+// model. The file becomes the session's recording, then it's transcribed a chunk at a time with
+// progress saved after each, so a long file picks up where it stopped.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use rusqlite::{params, OptionalExtension, Row};
+use sha2::{Digest, Sha256};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use tauri::{App, AppHandle, Manager, State};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+use crate::audio::{self, AudioSource, RecordingInfo, RecordingWriter};
+use crate::compliance;
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::transcript;
+use crate::transcription::{self, WHISPER_SAMPLE_RATE};
+use crate::vault::{self, FileReader};
+
+/// Whisper's own window; progress is saved after each chunk
+const CHUNK_SECONDS: usize = 30;
+
+const JOB_COLUMNS: &str = "id, session_id, file_name, recording_path, options, done_ms,
+    duration_ms, speaker, created_at, finished_at";
+
+/// Files being transcribed, by job id, with the flag that stops them
+#[derive(Default)]
+pub struct FileTranscriptionState {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TranscribeOptions {
+    /// Language spoken, e.g. "en"; detected when not set
+    language: Option<String>,
+    /// Label who's speaking; needs a speaker-turn model such as small.en-tdrz
+    diarize: bool,
+    /// Whisper model to use instead of the selected one
+    model: Option<String>,
+    /// Title of the session; the file name by default
+    title: Option<String>,
+}
+
+impl TranscribeOptions {
+    /// The default options, with the session titled `title`
+    pub(crate) fn titled(title: String) -> Self {
+        Self {
+            title: Some(title),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTranscription {
+    id: String,
+    session_id: String,
+    file_name: String,
+    #[serde(skip)]
+    recording_path: PathBuf,
+    #[serde(skip)]
+    options: TranscribeOptions,
+    /// Transcribed so far, ms into the file
+    done_ms: i64,
+    duration_ms: i64,
+    /// Speaker of the last segment when diarizing, carried across chunks
+    #[serde(skip)]
+    speaker: i64,
+    created_at: i64,
+    finished_at: Option<i64>,
+    /// Being transcribed right now; otherwise an unfinished file waits to be resumed
+    running: bool,
+}
+
+/// Emitted as `file_transcription_progress` after each chunk
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileProgress {
+    id: String,
+    session_id: String,
+    done_ms: i64,
+    duration_ms: i64,
+}
+
+/// Removes a job from the running ones however its run ends
+struct RunningGuard<'a> {
+    app: &'a AppHandle,
+    id: String,
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        let state = self.app.state::<FileTranscriptionState>();
+        match state.running.lock() {
+            Ok(mut running) => {
+                running.remove(&self.id);
+            }
+            Err(e) => tracing::error!("Failed to clear file transcription {}: {}", self.id, e),
+        };
+    }
+}
+
+pub fn setup_file_transcription(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(FileTranscriptionState::default());
+    Ok(())
+}
+
+/// Transcribe an audio file into a new session, returning its id. Transcribing a file again
+/// that didn't finish, after a cancel or a crash, resumes it in the same session.
+#[tauri::command]
+pub async fn transcribe_file(
+    app: AppHandle,
+    path: PathBuf,
+    options: Option<TranscribeOptions>,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || run(&app, &path, options.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Stop transcribing a file after the chunk in progress; it can be resumed later
+#[tauri::command]
+pub fn cancel_file_transcription(
+    state: State<'_, FileTranscriptionState>,
+    id: String,
+) -> Result<(), String> {
+    if let Some(running) = state.running.lock().map_err(|e| e.to_string())?.get(&id) {
+        running.store(false, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Transcribed files, newest first, unfinished ones included
+#[tauri::command]
+pub fn list_file_transcriptions(
+    db: State<'_, Database>,
+    state: State<'_, FileTranscriptionState>,
+) -> Result<Vec<FileTranscription>, String> {
+    let mut jobs: Vec<FileTranscription> = db
+        .connection()?
+        .prepare(&format!(
+            "SELECT {} FROM file_transcriptions ORDER BY created_at DESC",
+            JOB_COLUMNS
+        ))
+        .and_then(|mut statement| statement.query_map([], job_from_row)?.collect())
+        .map_err(|e| e.to_string())?;
+    let running = state.running.lock().map_err(|e| e.to_string())?;
+    for job in &mut jobs {
+        job.running = running.contains_key(&job.id);
+    }
+    Ok(jobs)
+}
+
+/// Transcribe a file like `transcribe_file` does, on the calling thread
+pub(crate) fn run(
+    app: &AppHandle,
+    path: &Path,
+    options: TranscribeOptions,
+) -> Result<String, String> {
+    let fingerprint = fingerprint(app, path)?;
+    let job = match unfinished_job(app, &fingerprint)? {
+        Some(job) => job,
+        None => create_job(app, path, &fingerprint, options)?,
+    };
+
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let state = app.state::<FileTranscriptionState>();
+        let mut jobs = state.running.lock().map_err(|e| e.to_string())?;
+        if jobs.contains_key(&job.id) {
+            return Err(t!("error.file_transcription_running"));
+        }
+        jobs.insert(job.id.clone(), running.clone());
+    }
+    let _guard = RunningGuard {
+        app,
+        id: job.id.clone(),
+    };
+
+    transcribe(app, job, &running)
+}
+
+/// Identifies a file by its contents, so a resumed file is the same file even if it moved
+fn fingerprint(app: &AppHandle, path: &Path) -> Result<String, String> {
+    let mut file = vault::open(app, path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn unfinished_job(app: &AppHandle, fingerprint: &str) -> Result<Option<FileTranscription>, String> {
+    app.state::<Database>()
+        .connection()?
+        .query_row(
+            &format!(
+                "SELECT {} FROM file_transcriptions
+                 WHERE fingerprint = ?1 AND finished_at IS NULL
+                   AND session_id IN (SELECT id FROM sessions WHERE deleted_at IS NULL)
+                 ORDER BY created_at DESC LIMIT 1",
+                JOB_COLUMNS
+            ),
+            params![fingerprint],
+            job_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// Create the session and its recording from the file, and the job that transcribes it
+fn create_job(
+    app: &AppHandle,
+    path: &Path,
+    fingerprint: &str,
+    options: TranscribeOptions,
+) -> Result<FileTranscription, String> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let title = options
+        .title
+        .clone()
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| file_name.clone());
+    // The file was most likely recorded when it was last written
+    let started_at = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or_else(db::now_ms, |since| since.as_millis() as i64);
+
+    let recording_id = uuid::Uuid::new_v4().to_string();
+    let recording = RecordingInfo {
+        path: audio::recordings_dir(app)?.join(format!("{}.wav", recording_id)),
+        id: recording_id,
+        source: AudioSource::Microphone,
+        device: file_name.clone(),
+        sample_rate: WHISPER_SAMPLE_RATE,
+        started_at,
+    };
+    let duration_ms = match convert(app, path, &recording) {
+        Ok(duration_ms) => duration_ms as i64,
+        Err(e) => {
+            let _ = audio::remove_recording(&recording.path);
+            return Err(e);
+        }
+    };
+
+    let db = app.state::<Database>();
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let job = FileTranscription {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id: session_id.clone(),
+        file_name,
+        recording_path: recording.path.clone(),
+        options,
+        done_ms: 0,
+        duration_ms,
+        speaker: 0,
+        created_at: db::now_ms(),
+        finished_at: None,
+        running: false,
+    };
+    let saved = db::insert_session(&db, &session_id, &title, started_at)
+        .and_then(|_| db::end_session(&db, &session_id, started_at + duration_ms))
+        .and_then(|_| {
+            db::insert_recordings(
+                app,
+                &session_id,
+                started_at,
+                std::slice::from_ref(&recording),
+            )
+        })
+        .and_then(|_| {
+            let options = serde_json::to_string(&job.options).map_err(|e| e.to_string())?;
+            db.connection()?
+                .execute(
+                    "INSERT INTO file_transcriptions
+                        (id, fingerprint, file_name, session_id, recording_path, options, done_ms,
+                         duration_ms, speaker, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, 0, ?8)",
+                    params![
+                        job.id,
+                        fingerprint,
+                        job.file_name,
+                        session_id,
+                        recording.path.to_string_lossy(),
+                        options,
+                        duration_ms,
+                        job.created_at
+                    ],
+                )
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = saved {
+        if let Ok(connection) = db.connection() {
+            let _ = connection.execute("DELETE FROM sessions WHERE id = ?1", params![session_id]);
+        }
+        let _ = audio::remove_recording(&recording.path);
+        return Err(e);
+    }
+
+    db::sessions_changed(app);
+    tracing::info!(file = ?job.file_name, "Converted a file for transcription");
+    Ok(job)
+}
+
+/// Imports are sealed like recordings, and containers such as MP4 need to seek while decoding
+impl MediaSource for FileReader {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Decode an audio file into a 16 kHz mono recording, returning its length
+fn convert(app: &AppHandle, path: &Path, recording: &RecordingInfo) -> Result<u64, String> {
+    let unsupported = || t!("error.audio_file_unsupported", path = path.display());
+    let file = vault::open(app, path)?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|_| unsupported())?
+        .format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(unsupported)?;
+    let track_id = track.id;
+    let sample_rate = track.codec_params.sample_rate.ok_or_else(unsupported)?;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|_| unsupported())?;
+
+    // Resampled a block at a time, so the whole file is never in memory
+    let block_len = sample_rate as usize * CHUNK_SECONDS;
+    let mut writer = RecordingWriter::create(app, recording)?;
+    let mut pending: Vec<f32> = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.to_string()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A damaged packet costs a moment of audio, not the file
+            Err(DecodeError::DecodeError(e)) => {
+                tracing::warn!("Skipped a damaged packet: {}", e);
+                continue;
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        let mut samples = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        samples.copy_interleaved_ref(decoded);
+        pending.extend(
+            samples
+                .samples()
+                .chunks(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+        );
+
+        while pending.len() >= block_len {
+            let block: Vec<f32> = pending.drain(..block_len).collect();
+            writer.write(&to_pcm(transcription::resample_float(block, sample_rate)))?;
+        }
+    }
+    writer.write(&to_pcm(transcription::resample_float(pending, sample_rate)))?;
+    writer.finish()
+}
+
+fn to_pcm(samples: Vec<f32>) -> Vec<i16> {
+    samples
+        .into_iter()
+        .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect()
+}
+
+/// Transcribe the job's recording from where it got to, until it's done or cancelled
+fn transcribe(
+    app: &AppHandle,
+    mut job: FileTranscription,
+    running: &AtomicBool,
+) -> Result<String, String> {
+    let model = job
+        .options
+        .model
+        .clone()
+        .unwrap_or_else(|| transcription::selected_model(app));
+    if job.options.diarize && !model.contains("tdrz") {
+        return Err(t!("error.diarization_model", name = model));
+    }
+    let model_path = transcription::model_path(app, &model)?;
+    let context = WhisperContext::new_with_params(
+        &model_path.to_string_lossy(),
+        WhisperContextParameters::default(),
+    )
+    .map_err(|e| t!("error.whisper_model_load", name = model, error = e))?;
+    let mut whisper = context.create_state().map_err(|e| e.to_string())?;
+
+    let mut reader = audio::open_recording(app, &job.recording_path)?;
+    let done_samples = (job.done_ms as u64 * WHISPER_SAMPLE_RATE as u64 / 1000) as u32;
+    reader.seek(done_samples).map_err(|e| e.to_string())?;
+    let mut samples = reader.samples::<i16>();
+    let chunk_len = WHISPER_SAMPLE_RATE as usize * CHUNK_SECONDS;
+    tracing::info!(
+        file = ?job.file_name,
+        "Transcribing from {} ms with {}",
+        job.done_ms,
+        model
+    );
+
+    while running.load(Ordering::SeqCst) {
+        let chunk = samples
+            .by_ref()
+            .take(chunk_len)
+            .map(|sample| sample.map(|sample| sample as f32 / 32768.0))
+            .collect::<Result<Vec<f32>, _>>()
+            .map_err(|e| e.to_string())?;
+        if chunk.is_empty() {
+            finish(app, &job)?;
+            return Ok(job.session_id);
+        }
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_language(Some(job.options.language.as_deref().unwrap_or("auto")));
+        params.set_tdrz_enable(job.options.diarize);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_special(false);
+        params.set_print_timestamps(false);
+        whisper.full(params, &chunk).map_err(|e| e.to_string())?;
+
+        let chunk_ms = (chunk.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as i64;
+        save_chunk(app, &mut job, &whisper, chunk_ms)?;
+
+        let progress = FileProgress {
+            id: job.id.clone(),
+            session_id: job.session_id.clone(),
+            done_ms: job.done_ms,
+            duration_ms: job.duration_ms,
+        };
+        if let Err(e) = app.emit("file_transcription_progress", progress) {
+            tracing::error!("Failed to emit event: {}", e);
+        }
+    }
+
+    tracing::info!(file = ?job.file_name, "Paused at {} ms", job.done_ms);
+    Ok(job.session_id)
+}
+
+/// Save a chunk's segments and the job's progress together, so a resumed job neither repeats
+/// nor skips any
+fn save_chunk(
+    app: &AppHandle,
+    job: &mut FileTranscription,
+    whisper: &whisper_rs::WhisperState,
+    chunk_ms: i64,
+) -> Result<(), String> {
+    let mut speaker = job.speaker;
+    let mut segments = Vec::new();
+    for index in 0..whisper.full_n_segments().unwrap_or(0) {
+        let Ok(text) = whisper.full_get_segment_text(index) else {
+            continue;
+        };
+        let text = text.trim().to_string();
+        let label = job
+            .options
+            .diarize
+            .then(|| format!("speaker{}", speaker + 1));
+        if job.options.diarize && whisper.full_get_segment_speaker_turn_next(index) {
+            speaker = 1 - speaker;
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let words = transcription::segment_words(whisper, index);
+        let confidence = (!words.is_empty())
+            .then(|| words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32);
+        let words = if words.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&words).map_err(|e| e.to_string())?)
+        };
+        // Whisper timestamps are in centiseconds from the start of the chunk
+        let start_ms = job.done_ms + whisper.full_get_segment_t0(index).unwrap_or(0) * 10;
+        let end_ms = job.done_ms + whisper.full_get_segment_t1(index).unwrap_or(0) * 10;
+        segments.push((label, text, start_ms, end_ms, confidence, words));
+    }
+
+    let db = app.state::<Database>();
+    let mut connection = db.connection()?;
+    let transaction = connection.transaction().map_err(|e| e.to_string())?;
+    let mut saved = Vec::new();
+    for (label, text, start_ms, end_ms, confidence, words) in segments {
+        transaction
+            .execute(
+                "INSERT INTO transcript_segments
+                    (session_id, speaker, text, start_ms, end_ms, confidence, words)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    job.session_id,
+                    label,
+                    text,
+                    start_ms,
+                    end_ms,
+                    confidence,
+                    words
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        saved.push((transaction.last_insert_rowid(), label, text, start_ms));
+    }
+    transaction
+        .execute(
+            "UPDATE file_transcriptions SET done_ms = ?2, speaker = ?3 WHERE id = ?1",
+            params![job.id, job.done_ms + chunk_ms, speaker],
+        )
+        .map_err(|e| e.to_string())?;
+    transaction.commit().map_err(|e| e.to_string())?;
+    drop(connection);
+
+    job.done_ms += chunk_ms;
+    job.speaker = speaker;
+    let ids: Vec<i64> = saved.iter().map(|(id, ..)| *id).collect();
+    transcript::segments_added(app, &job.session_id, &ids);
+    for (segment_id, label, text, start_ms) in saved {
+        compliance::scan_segment(
+            app,
+            &job.session_id,
+            segment_id,
+            label.as_deref(),
+            &text,
+            start_ms,
+        );
+    }
+    Ok(())
+}
+
+fn finish(app: &AppHandle, job: &FileTranscription) -> Result<(), String> {
+    app.state::<Database>()
+        .connection()?
+        .execute(
+            "UPDATE file_transcriptions SET finished_at = ?2 WHERE id = ?1",
+            params![job.id, db::now_ms()],
+        )
+        .map_err(|e| e.to_string())?;
+    db::sessions_changed(app);
+    tracing::info!(file = ?job.file_name, "Transcribed a file");
+    Ok(())
+}
+
+fn job_from_row(row: &Row) -> rusqlite::Result<FileTranscription> {
+    let recording_path: String = row.get(3)?;
+    let options: String = row.get(4)?;
+    Ok(FileTranscription {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        file_name: row.get(2)?,
+        recording_path: PathBuf::from(recording_path),
+        options: serde_json::from_str(&options).unwrap_or_default(),
+        done_ms: row.get(5)?,
+        duration_ms: row.get(6)?,
+        speaker: row.get(7)?,
+        created_at: row.get(8)?,
+        finished_at: row.get(9)?,
+        running: false,
+    })
+}
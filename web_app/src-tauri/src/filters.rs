@@ -0,0 +1,200 @@
+// Queen Mama LITE - Saved Filters
+// Named session queries ("Unreviewed interviews this month") the dashboard lists as smart folders;
+// they're evaluated in SQL each time they're opened, so relative periods stay current
+
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, NaiveTime, TimeZone};
+use rusqlite::{params, OptionalExtension, Row};
+use tauri::State;
+
+use crate::db::{self, Database, Session, SessionFilter};
+use crate::i18n::t;
+
+/// Longer names don't fit the sidebar
+const MAX_NAME_CHARS: usize = 50;
+
+/// A period counted back from today, in local time
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RelativePeriod {
+    Today,
+    Yesterday,
+    /// Weeks start on Monday
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    LastMonth,
+    /// Today and the 6 days before
+    Last7Days,
+    /// Today and the 29 days before
+    Last30Days,
+    ThisYear,
+}
+
+impl RelativePeriod {
+    /// Start and exclusive end of the period as of `now`, in Unix ms
+    pub(crate) fn bounds(self, now: DateTime<Local>) -> (i64, i64) {
+        let today = now.date_naive();
+        let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+        let month = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap_or(today);
+        let year = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today);
+
+        let (start, end) = match self {
+            RelativePeriod::Today => (Some(today), today.checked_add_days(Days::new(1))),
+            RelativePeriod::Yesterday => (today.checked_sub_days(Days::new(1)), Some(today)),
+            RelativePeriod::ThisWeek => (Some(monday), monday.checked_add_days(Days::new(7))),
+            RelativePeriod::LastWeek => (monday.checked_sub_days(Days::new(7)), Some(monday)),
+            RelativePeriod::ThisMonth => (Some(month), month.checked_add_months(Months::new(1))),
+            RelativePeriod::LastMonth => (month.checked_sub_months(Months::new(1)), Some(month)),
+            RelativePeriod::Last7Days => (
+                today.checked_sub_days(Days::new(6)),
+                today.checked_add_days(Days::new(1)),
+            ),
+            RelativePeriod::Last30Days => (
+                today.checked_sub_days(Days::new(29)),
+                today.checked_add_days(Days::new(1)),
+            ),
+            RelativePeriod::ThisYear => (Some(year), year.checked_add_months(Months::new(12))),
+        };
+        (
+            start.map_or(i64::MIN, start_of_day),
+            end.map_or(i64::MAX, start_of_day),
+        )
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedFilter {
+    id: String,
+    name: String,
+    query: SessionFilter,
+    created_at: i64,
+}
+
+/// Save a session query under a name, to be opened with `list_sessions_by_filter`
+#[tauri::command]
+pub fn save_filter(
+    db: State<'_, Database>,
+    name: String,
+    query: SessionFilter,
+) -> Result<SavedFilter, String> {
+    let filter = SavedFilter {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: valid_name(&name)?,
+        query,
+        created_at: db::now_ms(),
+    };
+    let query = serde_json::to_string(&filter.query).map_err(|e| e.to_string())?;
+    db.connection()?
+        .execute(
+            "INSERT INTO saved_filters (id, name, query, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![filter.id, filter.name, query, filter.created_at],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(filter)
+}
+
+/// Rename a saved filter or change its query
+#[tauri::command]
+pub fn update_filter(
+    db: State<'_, Database>,
+    id: String,
+    name: String,
+    query: SessionFilter,
+) -> Result<(), String> {
+    let name = valid_name(&name)?;
+    let query = serde_json::to_string(&query).map_err(|e| e.to_string())?;
+    let updated = db
+        .connection()?
+        .execute(
+            "UPDATE saved_filters SET name = ?2, query = ?3 WHERE id = ?1",
+            params![id, name, query],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(t!("error.filter_not_found", id = id));
+    }
+    Ok(())
+}
+
+/// Saved filters, in the order they were saved
+#[tauri::command]
+pub fn list_filters(db: State<'_, Database>) -> Result<Vec<SavedFilter>, String> {
+    db.connection()?
+        .prepare("SELECT id, name, query, created_at FROM saved_filters ORDER BY created_at, id")
+        .and_then(|mut statement| {
+            statement
+                .query_map([], filter_from_row)?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_filter(db: State<'_, Database>, id: String) -> Result<(), String> {
+    db.connection()?
+        .execute("DELETE FROM saved_filters WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Sessions matching a saved filter, most recent first
+#[tauri::command]
+pub fn list_sessions_by_filter(
+    db: State<'_, Database>,
+    id: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<Session>, String> {
+    let connection = db.connection()?;
+    let filter = connection
+        .query_row(
+            "SELECT id, name, query, created_at FROM saved_filters WHERE id = ?1",
+            params![id],
+            filter_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.filter_not_found", id = id))?;
+
+    db::query_sessions(
+        &connection,
+        &filter.query,
+        limit.unwrap_or(50),
+        offset.unwrap_or(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn filter_from_row(row: &Row) -> rusqlite::Result<SavedFilter> {
+    let query: String = row.get(2)?;
+    Ok(SavedFilter {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        // A query that no longer parses shows every session rather than failing the whole list
+        query: serde_json::from_str(&query).unwrap_or_default(),
+        created_at: row.get(3)?,
+    })
+}
+
+/// Midnight at the start of `date`, or the first moment after it when a clock change skips it
+fn start_of_day(date: NaiveDate) -> i64 {
+    let midnight = date.and_time(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(midnight + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map_or(0, |time| time.timestamp_millis())
+}
+
+fn valid_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(t!("error.filter_name_invalid", max = MAX_NAME_CHARS));
+    }
+    Ok(name.to_string())
+}
@@ -0,0 +1,179 @@
+// Queen Mama LITE - Focus Mode
+// Tracks macOS Focus, Windows Do Not Disturb and GNOME's notification switch, and what to hold back while one is on
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::events::VersionedEmitter;
+use crate::SETTINGS_STORE;
+
+pub(crate) const FOCUS_SUPPRESSION_STORE_KEY: &str = "focus_suppression";
+
+/// The OS doesn't notify us, so the state is re-read on this interval
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusState {
+    pub active: bool,
+    /// The Focus in use on macOS, e.g. `com.apple.focus.work`
+    pub mode: Option<String>,
+}
+
+/// What to hold back while a focus mode is on
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FocusSuppression {
+    /// Behave as usual
+    Off,
+    /// Keep native notifications and their sounds quiet; they still reach the app
+    #[default]
+    Notifications,
+    /// Also hide the overlay until the focus mode ends
+    NotificationsAndOverlay,
+}
+
+#[derive(Default)]
+pub struct FocusModeState(Mutex<FocusState>);
+
+pub fn setup_focus(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let state = detect(app.app_handle());
+    tracing::info!(active = state.active, mode = ?state.mode, "Focus state loaded");
+    app.manage(FocusModeState(Mutex::new(state)));
+
+    let app_handle = app.app_handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        refresh(&app_handle);
+    });
+    Ok(())
+}
+
+/// Whether a focus or do not disturb mode is on
+#[tauri::command]
+pub fn get_focus_state(state: State<'_, FocusModeState>) -> Result<FocusState, String> {
+    let focus = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(focus.clone())
+}
+
+/// Get what's held back while a focus mode is on
+#[tauri::command]
+pub fn get_focus_suppression(app: AppHandle) -> FocusSuppression {
+    suppression(&app)
+}
+
+/// Set what's held back while a focus mode is on
+#[tauri::command]
+pub fn set_focus_suppression(app: AppHandle, suppression: FocusSuppression) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        FOCUS_SUPPRESSION_STORE_KEY,
+        serde_json::to_value(suppression).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Native notifications should stay quiet right now
+pub fn silences_notifications(app: &AppHandle) -> bool {
+    is_active(app) && suppression(app) != FocusSuppression::Off
+}
+
+/// The overlay should stay hidden right now
+pub fn hides_overlay(app: &AppHandle) -> bool {
+    is_active(app) && suppression(app) == FocusSuppression::NotificationsAndOverlay
+}
+
+fn is_active(app: &AppHandle) -> bool {
+    app.try_state::<FocusModeState>()
+        .is_some_and(|state| state.0.lock().is_ok_and(|focus| focus.active))
+}
+
+fn suppression(app: &AppHandle) -> FocusSuppression {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(FOCUS_SUPPRESSION_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Re-read the state and tell the windows if it changed
+fn refresh(app: &AppHandle) {
+    let focus = detect(app);
+    let state = app.state::<FocusModeState>();
+    let previous = match state.0.lock() {
+        Ok(mut current) => std::mem::replace(&mut *current, focus.clone()),
+        Err(_) => return,
+    };
+    if previous == focus {
+        return;
+    }
+
+    tracing::info!(active = focus.active, mode = ?focus.mode, "Focus state changed");
+    if let Err(e) = app.emit("focus_state_changed", focus) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
+
+/// Focus writes an assertion for each mode that's on. Reading the file needs Full Disk Access
+/// on recent macOS versions; without it Focus always looks off.
+#[cfg(target_os = "macos")]
+fn detect(app: &AppHandle) -> FocusState {
+    let assertions = app
+        .path()
+        .home_dir()
+        .ok()
+        .map(|home| home.join("Library/DoNotDisturb/DB/Assertions.json"))
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+    let record = assertions.as_ref().and_then(|assertions| {
+        assertions["data"]
+            .as_array()?
+            .iter()
+            .filter_map(|data| data["storeAssertionRecords"].as_array())
+            .flatten()
+            .next()
+    });
+
+    FocusState {
+        active: record.is_some(),
+        mode: record.and_then(|record| {
+            record["assertionDetails"]["assertionDetailsModeIdentifier"]
+                .as_str()
+                .map(str::to_string)
+        }),
+    }
+}
+
+/// Windows 11 Do Not Disturb turns toasts off globally
+#[cfg(target_os = "windows")]
+fn detect(_app: &AppHandle) -> FocusState {
+    let toasts = crate::accessibility::read_registry(
+        r"HKCU\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings",
+        "NOC_GLOBAL_SETTING_TOASTS_ENABLED",
+    );
+    FocusState {
+        active: toasts.is_some_and(|value| value == "0x0"),
+        mode: None,
+    }
+}
+
+/// GNOME's Do Not Disturb switch; other desktops always look off
+#[cfg(target_os = "linux")]
+fn detect(_app: &AppHandle) -> FocusState {
+    let banners = crate::accessibility::read_command(
+        "gsettings",
+        &["get", "org.gnome.desktop.notifications", "show-banners"],
+    );
+    FocusState {
+        active: banners.as_deref() == Some("false"),
+        mode: None,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn detect(_app: &AppHandle) -> FocusState {
+    FocusState::default()
+}
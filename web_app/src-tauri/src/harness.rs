@@ -0,0 +1,174 @@
+// Queen Mama LITE - Test Harness
+// Drives the real app from integration tests: hidden windows, in-memory database, virtual clock, demo providers
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tauri::{App, AppHandle, Listener, Manager};
+
+#[cfg(desktop)]
+use crate::audio::AudioSource;
+use crate::{compliance, journal, session, shortcuts};
+
+/// Where the virtual clock starts: Monday 2025-01-06, 09:00 UTC
+const CLOCK_START_MS: i64 = 1_736_154_000_000;
+
+/// Virtual time in Unix ms; `i64::MIN` until a harness starts
+static CLOCK_MS: AtomicI64 = AtomicI64::new(i64::MIN);
+
+/// Who said a scripted line
+#[derive(Clone, Copy)]
+pub enum Speaker {
+    Me,
+    Them,
+}
+
+/// The app, built the way `run` builds it but never shown.
+/// Tests using it should run one at a time, since the virtual clock is shared.
+pub struct Harness {
+    app: App,
+    events: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl Harness {
+    /// Build the app with its own empty settings and database, minus single instance
+    pub fn new() -> tauri::Result<Self> {
+        CLOCK_MS.store(CLOCK_START_MS, Ordering::SeqCst);
+
+        let mut context = crate::context();
+        let config = context.config_mut();
+        // A fresh identifier gives every harness its own app data dir, away from the user's
+        config.identifier = format!(
+            "{}.test-{}",
+            config.identifier,
+            uuid::Uuid::new_v4().simple()
+        );
+        for window in &mut config.app.windows {
+            window.visible = false;
+        }
+
+        // Test threads aren't the main thread; macOS has no way around that
+        let builder = tauri::Builder::default();
+        #[cfg(any(windows, target_os = "linux"))]
+        let builder = builder.any_thread();
+
+        Ok(Self {
+            app: crate::build(builder, context)?,
+            events: Arc::default(),
+        })
+    }
+
+    pub fn handle(&self) -> &AppHandle {
+        self.app.handle()
+    }
+
+    /// Move the virtual clock forward
+    pub fn advance(&self, ms: i64) {
+        CLOCK_MS.fetch_add(ms, Ordering::SeqCst);
+    }
+
+    /// Start keeping the payloads of `event` for `events`
+    pub fn record(&self, event: &str) {
+        let events = self.events.clone();
+        let name = event.to_string();
+        self.app.listen_any(event, move |event| {
+            let payload = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+            if let Ok(mut events) = events.lock() {
+                events.push((name.clone(), payload));
+            }
+        });
+    }
+
+    /// Payloads of `event` emitted since `record`, oldest first
+    pub fn events(&self, event: &str) -> Vec<Value> {
+        self.events
+            .lock()
+            .map(|events| {
+                events
+                    .iter()
+                    .filter(|(name, _)| name == event)
+                    .map(|(_, payload)| payload.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn start_session(&self) -> Result<Value, String> {
+        snapshot(session::start(self.handle())?)
+    }
+
+    pub fn pause_session(&self, paused: bool) -> Result<Value, String> {
+        snapshot(session::set_paused(self.handle(), paused)?)
+    }
+
+    pub fn stop_session(&self) -> Result<Value, String> {
+        snapshot(session::stop(self.handle())?)
+    }
+
+    pub fn active_session_id(&self) -> Option<String> {
+        session::active_session_id(self.handle())
+    }
+
+    /// Run a shortcut or tray action by id, as if its key had been pressed
+    pub fn dispatch_action(&self, action: &str) {
+        shortcuts::dispatch_action(self.handle(), action);
+    }
+
+    /// Add a final transcript segment to the active session, as if whisper had heard it
+    #[cfg(desktop)]
+    pub fn say(&self, speaker: Speaker, text: &str, start_ms: i64, end_ms: i64) {
+        let source = match speaker {
+            Speaker::Me => AudioSource::Microphone,
+            Speaker::Them => AudioSource::System,
+        };
+        crate::transcription::publish_scripted(self.handle(), source, text, start_ms, end_ms);
+    }
+
+    /// A session with its transcript and notes
+    pub fn session(&self, session_id: &str) -> Result<Value, String> {
+        let detail = crate::db::get_session(self.app.state(), session_id.to_string())?;
+        serde_json::to_value(detail).map_err(|e| e.to_string())
+    }
+
+    /// The compliance hits found in a session
+    pub fn compliance_report(&self, session_id: &str) -> Result<Value, String> {
+        let report = compliance::get_compliance_report(
+            self.handle().clone(),
+            self.app.state(),
+            session_id.to_string(),
+        )?;
+        serde_json::to_value(report).map_err(|e| e.to_string())
+    }
+
+    /// Summarize a day's journal (YYYY-MM-DD, today by default) with the demo replies
+    pub async fn journal_reflection(&self, date: Option<&str>) -> Result<Value, String> {
+        let note = journal::generate_journal_reflection(
+            self.handle().clone(),
+            self.app.state(),
+            date.map(str::to_string),
+            None,
+        )
+        .await?;
+        serde_json::to_value(note).map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        CLOCK_MS.store(i64::MIN, Ordering::SeqCst);
+        if let Ok(dir) = self.app.path().app_data_dir() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Virtual time while a harness is running, for `db::now_ms`
+pub(crate) fn now_ms() -> Option<i64> {
+    let now = CLOCK_MS.load(Ordering::SeqCst);
+    (now != i64::MIN).then_some(now)
+}
+
+fn snapshot(snapshot: session::SessionSnapshot) -> Result<Value, String> {
+    serde_json::to_value(snapshot).map_err(|e| e.to_string())
+}
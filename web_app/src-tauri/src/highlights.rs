@@ -0,0 +1,462 @@
+// Queen Mama LITE - Highlights
+// Flagged moments of a session, and a short highlight reel of its best moments: their audio
+// back to back with a spoken introduction each, plus a Markdown digest
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, OptionalExtension};
+use tauri::{AppHandle, Manager, State};
+
+use crate::clips;
+use crate::compliance;
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::export::format_offset;
+use crate::i18n::t;
+use crate::session;
+use crate::vault;
+
+/// Moments in a reel unless asked otherwise
+const DEFAULT_MOMENTS: usize = 5;
+const MAX_MOMENTS: usize = 20;
+
+/// Audio kept either side of a scored point; overlapping windows become one moment
+const WINDOW_MS: i64 = 15_000;
+
+/// Scores of what marks a moment; a moment scores the sum of everything inside it
+const FLAG_SCORE: i64 = 3;
+const COMMENT_SCORE: i64 = 2;
+const COMPLIANCE_SCORE: i64 = 1;
+
+/// Silence after each interstitial, and in its place when speech can't be synthesized
+const PAUSE_MS: i64 = 700;
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Flag {
+    id: i64,
+    session_id: String,
+    /// On the session's timeline
+    at_ms: i64,
+    note: Option<String>,
+    created_at: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Moment {
+    start_ms: i64,
+    end_ms: i64,
+    score: i64,
+    flags: usize,
+    comments: usize,
+    compliance_hits: usize,
+    /// Notes of the flags inside it
+    notes: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Highlights {
+    path: PathBuf,
+    digest_path: PathBuf,
+    duration_ms: i64,
+    /// In the order they play
+    moments: Vec<Moment>,
+}
+
+/// Emitted as `highlights_progress` after each moment of a reel is mixed
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HighlightsProgress {
+    session_id: String,
+    done: usize,
+    total: usize,
+}
+
+/// Something worth coming back to at one point of a session
+struct Mark {
+    at_ms: i64,
+    kind: MarkKind,
+    note: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MarkKind {
+    Flag,
+    Comment,
+    Compliance,
+}
+
+impl MarkKind {
+    fn score(self) -> i64 {
+        match self {
+            MarkKind::Flag => FLAG_SCORE,
+            MarkKind::Comment => COMMENT_SCORE,
+            MarkKind::Compliance => COMPLIANCE_SCORE,
+        }
+    }
+}
+
+/// Flag the current moment of the session being recorded
+#[tauri::command]
+pub fn flag_moment(app: AppHandle, note: Option<String>) -> Result<Flag, String> {
+    flag(&app, note)
+}
+
+/// A session's flags, in timeline order
+#[tauri::command]
+pub fn list_flags(db: State<'_, Database>, session_id: String) -> Result<Vec<Flag>, String> {
+    db.connection()?
+        .prepare(
+            "SELECT id, session_id, at_ms, note, created_at FROM session_flags
+             WHERE session_id = ?1 ORDER BY at_ms, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok(Flag {
+                        id: row.get(0)?,
+                        session_id: row.get(1)?,
+                        at_ms: row.get(2)?,
+                        note: row.get(3)?,
+                        created_at: row.get(4)?,
+                    })
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Remove a flag set by mistake
+#[tauri::command]
+pub fn delete_flag(db: State<'_, Database>, id: i64) -> Result<(), String> {
+    db.connection()?
+        .execute("DELETE FROM session_flags WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Write a reel of a session's `count` best moments to `path` as a WAV, each introduced by its
+/// time and note, with a Markdown digest of them next to it. Flags score highest, then review
+/// comments, then compliance hits.
+#[tauri::command]
+pub async fn generate_highlights(
+    app: AppHandle,
+    session_id: String,
+    path: PathBuf,
+    count: Option<usize>,
+) -> Result<Highlights, String> {
+    let count = count.unwrap_or(DEFAULT_MOMENTS).clamp(1, MAX_MOMENTS);
+    tauri::async_runtime::spawn_blocking(move || {
+        let highlights = generate(&app, &session_id, &path, count);
+        if highlights.is_err() {
+            let _ = fs::remove_file(&path);
+        }
+        highlights
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Save a flag at the current point of the session being recorded, for the UI and the relay
+pub(crate) fn flag(app: &AppHandle, note: Option<String>) -> Result<Flag, String> {
+    let session_id = session::active_session_id(app).ok_or_else(|| t!("error.no_session"))?;
+    let note = note
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+
+    let db = app.state::<Database>();
+    let connection = db.connection()?;
+    let created_at = db::now_ms();
+    let started_at: i64 = connection
+        .query_row(
+            "SELECT started_at FROM sessions WHERE id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let at_ms = (created_at - started_at).max(0);
+    connection
+        .execute(
+            "INSERT INTO session_flags (session_id, at_ms, note, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![session_id, at_ms, note, created_at],
+        )
+        .map_err(|e| e.to_string())?;
+    let flag = Flag {
+        id: connection.last_insert_rowid(),
+        session_id,
+        at_ms,
+        note,
+        created_at,
+    };
+    drop(connection);
+
+    if let Err(e) = app.emit("flag_moment", &flag) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+    Ok(flag)
+}
+
+fn generate(
+    app: &AppHandle,
+    session_id: &str,
+    path: &Path,
+    count: usize,
+) -> Result<Highlights, String> {
+    let db = app.state::<Database>();
+    let (title, length_ms, recordings, mut marks) = {
+        let connection = db.connection()?;
+        let (title, length_ms): (String, i64) = connection
+            .query_row(
+                "SELECT title, COALESCE(ended_at, ?2) - started_at FROM sessions WHERE id = ?1",
+                params![session_id, db::now_ms()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| t!("error.session_not_found", id = session_id))?;
+        let recordings =
+            db::session_recordings(&connection, session_id).map_err(|e| e.to_string())?;
+        let marks = connection
+            .prepare(
+                "SELECT at_ms, note, 0 FROM session_flags WHERE session_id = ?1
+                 UNION ALL
+                 SELECT at_ms, NULL, 1 FROM session_comments
+                 WHERE session_id = ?1 AND parent_id IS NULL",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![session_id], |row| {
+                        Ok(Mark {
+                            at_ms: row.get(0)?,
+                            note: row.get(1)?,
+                            kind: if row.get::<_, i64>(2)? == 0 {
+                                MarkKind::Flag
+                            } else {
+                                MarkKind::Comment
+                            },
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| e.to_string())?;
+        (title, length_ms, recordings, marks)
+    };
+    marks.extend(
+        compliance::hit_offsets(app, &db, session_id)?
+            .into_iter()
+            .map(|at_ms| Mark {
+                at_ms,
+                kind: MarkKind::Compliance,
+                note: None,
+            }),
+    );
+
+    let moments = best_moments(marks, length_ms, count);
+    if moments.is_empty() {
+        return Err(t!("error.highlights_nothing", id = session_id));
+    }
+
+    let sample_rate = clips::output_rate(&recordings);
+    let pause = vec![0i16; (PAUSE_MS * sample_rate as i64 / 1000) as usize];
+    let mut reel = Vec::new();
+    for (done, moment) in moments.iter().enumerate() {
+        let mut intro = t!(
+            "highlights.intro",
+            number = done + 1,
+            time = format_offset(moment.start_ms)
+        );
+        if let Some(note) = moment.notes.first() {
+            intro = format!("{} {}", intro, note);
+        }
+        reel.extend(speak(&intro, sample_rate));
+        reel.extend_from_slice(&pause);
+        reel.extend(clips::mix(
+            app,
+            &recordings,
+            moment.start_ms,
+            moment.end_ms,
+            sample_rate,
+        )?);
+        reel.extend_from_slice(&pause);
+
+        let progress = HighlightsProgress {
+            session_id: session_id.to_string(),
+            done: done + 1,
+            total: moments.len(),
+        };
+        if let Err(e) = app.emit("highlights_progress", progress) {
+            tracing::error!("Failed to emit event: {}", e);
+        }
+    }
+    clips::write_wav(app, path, sample_rate, &reel)?;
+
+    let digest_path = path.with_extension("md");
+    let connection = db.connection()?;
+    let mut digest = format!("# {}\n\n", t!("highlights.heading", title = title));
+    for (number, moment) in moments.iter().enumerate() {
+        let _ = writeln!(
+            digest,
+            "## {}. {} – {}\n",
+            number + 1,
+            format_offset(moment.start_ms),
+            format_offset(moment.end_ms)
+        );
+        for note in &moment.notes {
+            let _ = writeln!(digest, "> {}\n", note);
+        }
+        let segments =
+            clips::transcript_slice(&connection, session_id, moment.start_ms, moment.end_ms)
+                .map_err(|e| e.to_string())?;
+        digest.push_str(&clips::transcript_markdown(&segments));
+        digest.push('\n');
+    }
+    drop(connection);
+    vault::write(app, &digest_path, digest)?;
+
+    let duration_ms = reel.len() as i64 * 1000 / sample_rate as i64;
+    tracing::info!(
+        "Wrote a {} ms highlight reel of session {} with {} moments",
+        duration_ms,
+        session_id,
+        moments.len()
+    );
+    Ok(Highlights {
+        path: path.to_path_buf(),
+        digest_path,
+        duration_ms,
+        moments,
+    })
+}
+
+/// Windows around the marks, overlapping ones merged, keeping the `count` highest scoring in
+/// timeline order
+fn best_moments(mut marks: Vec<Mark>, length_ms: i64, count: usize) -> Vec<Moment> {
+    marks.retain(|mark| mark.at_ms >= 0 && mark.at_ms <= length_ms);
+    marks.sort_by_key(|mark| mark.at_ms);
+
+    let mut moments: Vec<Moment> = Vec::new();
+    for mark in marks {
+        let start_ms = (mark.at_ms - WINDOW_MS).max(0);
+        let end_ms = (mark.at_ms + WINDOW_MS).min(length_ms);
+        if !moments
+            .last()
+            .is_some_and(|moment| start_ms <= moment.end_ms)
+        {
+            moments.push(Moment {
+                start_ms,
+                end_ms,
+                score: 0,
+                flags: 0,
+                comments: 0,
+                compliance_hits: 0,
+                notes: Vec::new(),
+            });
+        }
+        let Some(moment) = moments.last_mut() else {
+            continue;
+        };
+        moment.end_ms = moment.end_ms.max(end_ms);
+        moment.score += mark.kind.score();
+        match mark.kind {
+            MarkKind::Flag => moment.flags += 1,
+            MarkKind::Comment => moment.comments += 1,
+            MarkKind::Compliance => moment.compliance_hits += 1,
+        }
+        moment.notes.extend(mark.note);
+    }
+
+    // Ties go to the earlier moment
+    moments.sort_by(|a, b| b.score.cmp(&a.score).then(a.start_ms.cmp(&b.start_ms)));
+    moments.truncate(count);
+    moments.sort_by_key(|moment| moment.start_ms);
+    moments
+}
+
+/// `text` spoken by the system voice at `sample_rate`, or a pause when there's no voice
+fn speak(text: &str, sample_rate: u32) -> Vec<i16> {
+    let path = std::env::temp_dir().join(format!("queen-mama-tts-{}.wav", uuid::Uuid::new_v4()));
+    let speech = synthesize(text, &path).and_then(|()| read_speech(&path, sample_rate));
+    let _ = fs::remove_file(&path);
+    speech.unwrap_or_else(|e| {
+        tracing::warn!("Speech synthesis unavailable, using a pause: {}", e);
+        vec![0; (PAUSE_MS * sample_rate as i64 / 1000) as usize]
+    })
+}
+
+/// Mono 16-bit speech from a WAV the system voice wrote, resampled to `sample_rate`
+fn read_speech(path: &Path, sample_rate: u32) -> Result<Vec<i16>, String> {
+    let reader = hound::WavReader::open(path).map_err(|e| e.to_string())?;
+    let spec = reader.spec();
+    if spec.bits_per_sample != 16 || spec.sample_format != hound::SampleFormat::Int {
+        return Err(t!("error.speech_format", bits = spec.bits_per_sample));
+    }
+    let channels = spec.channels.max(1) as usize;
+    let samples = reader
+        .into_samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let mono: Vec<i16> = samples
+        .chunks(channels)
+        .map(|frame| (frame.iter().map(|s| *s as i32).sum::<i32>() / frame.len() as i32) as i16)
+        .collect();
+    Ok(clips::resample(&mono, spec.sample_rate, sample_rate))
+}
+
+#[cfg(target_os = "macos")]
+fn synthesize(text: &str, path: &Path) -> Result<(), String> {
+    run_voice(
+        std::process::Command::new("say")
+            .arg("-o")
+            .arg(path)
+            .args(["--file-format=WAVE", "--data-format=LEI16@22050"])
+            .arg(text),
+    )
+}
+
+/// The text and path go in through the environment so nothing in them is read as script
+#[cfg(target_os = "windows")]
+fn synthesize(text: &str, path: &Path) -> Result<(), String> {
+    run_voice(
+        std::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "Add-Type -AssemblyName System.Speech; \
+                 $voice = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+                 $voice.SetOutputToWaveFile($env:QUEEN_MAMA_TTS_PATH); \
+                 $voice.Speak($env:QUEEN_MAMA_TTS_TEXT); $voice.Dispose()",
+            ])
+            .env("QUEEN_MAMA_TTS_PATH", path)
+            .env("QUEEN_MAMA_TTS_TEXT", text),
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn synthesize(text: &str, path: &Path) -> Result<(), String> {
+    run_voice(
+        std::process::Command::new("espeak-ng")
+            .arg("-w")
+            .arg(path)
+            .arg(text),
+    )
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn synthesize(_text: &str, _path: &Path) -> Result<(), String> {
+    Err(t!("error.no_system_voice"))
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn run_voice(command: &mut std::process::Command) -> Result<(), String> {
+    let output = command.output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
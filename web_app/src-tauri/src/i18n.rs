@@ -0,0 +1,1354 @@
+// Queen Mama LITE - Localized Strings
+// Translations for text produced on the Rust side: errors and their remediations, shortcut descriptions, notifications and the tray
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use tauri::{App, AppHandle};
+use tauri_plugin_store::StoreExt;
+
+use crate::events::VersionedEmitter;
+use crate::SETTINGS_STORE;
+
+pub(crate) const LOCALE_STORE_KEY: &str = "locale";
+
+/// Locale used for Rust-side text. Kept outside managed state because errors are
+/// built in places that have no `AppHandle` at hand.
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::En);
+
+/// Look up a string in the current locale, filling `{name}` placeholders:
+/// `t!("error.session_not_found", id = session_id)`
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::text($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::text($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+pub(crate) use t;
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Locale {
+    En,
+    Fr,
+    Es,
+    De,
+}
+
+impl Locale {
+    /// Match a BCP 47 tag such as "fr-CA" or "de_DE" on its language
+    fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_']).next()?.to_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "fr" => Some(Locale::Fr),
+            "es" => Some(Locale::Es),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    fn strings(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Fr => FR,
+            Locale::Es => ES,
+            Locale::De => DE,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleStrings {
+    locale: Locale,
+    /// Every key, with English filling in for missing translations
+    strings: HashMap<&'static str, &'static str>,
+}
+
+/// Runs before the tray and shortcuts so their labels come up in the right language
+pub fn setup_i18n(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    set_current(resolve(app.handle()));
+    Ok(())
+}
+
+/// Get the Rust-side strings for a locale (the current one when omitted)
+#[tauri::command]
+pub fn get_strings(locale: Option<String>) -> LocaleStrings {
+    let locale = locale
+        .as_deref()
+        .and_then(Locale::from_tag)
+        .unwrap_or_else(current);
+
+    let mut strings: HashMap<_, _> = EN.iter().copied().collect();
+    strings.extend(locale.strings().iter().copied());
+    LocaleStrings { locale, strings }
+}
+
+/// Get the locale Rust-side text is shown in
+#[tauri::command]
+pub fn get_locale() -> Locale {
+    current()
+}
+
+/// Pick the language for Rust-side text, or follow the system with `null`.
+/// Tray menu labels change on the next launch.
+#[tauri::command]
+pub fn set_locale(app: AppHandle, locale: Option<String>) -> Result<Locale, String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    match locale {
+        Some(tag) => {
+            let locale = Locale::from_tag(&tag)
+                .ok_or_else(|| t!("error.unsupported_locale", locale = tag))?;
+            store.set(
+                LOCALE_STORE_KEY,
+                serde_json::to_value(locale).map_err(|e| e.to_string())?,
+            );
+        }
+        None => {
+            store.delete(LOCALE_STORE_KEY);
+        }
+    }
+    store.save().map_err(|e| e.to_string())?;
+
+    reload(&app)?;
+    Ok(current())
+}
+
+/// Re-read the locale setting, e.g. after settings.json was edited
+pub fn reload(app: &AppHandle) -> Result<(), String> {
+    let locale = resolve(app);
+    set_current(locale);
+    app.emit("locale_changed", locale).map_err(|e| e.to_string())
+}
+
+pub fn current() -> Locale {
+    LOCALE.read().map(|locale| *locale).unwrap_or(Locale::En)
+}
+
+/// Used by `t!`; falls back to English, then to the key itself
+pub fn text(key: &str, args: &[(&str, String)]) -> String {
+    let lookup = |locale: Locale| {
+        locale
+            .strings()
+            .iter()
+            .find(|(candidate, _)| *candidate == key)
+            .map(|(_, text)| *text)
+    };
+    let mut text = lookup(current())
+        .or_else(|| lookup(Locale::En))
+        .unwrap_or(key)
+        .to_string();
+
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+fn set_current(locale: Locale) {
+    if let Ok(mut current) = LOCALE.write() {
+        *current = locale;
+    }
+}
+
+/// The saved choice, else the system language when we have it, else English
+fn resolve(app: &AppHandle) -> Locale {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(LOCALE_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .or_else(|| tauri_plugin_os::locale().as_deref().and_then(Locale::from_tag))
+        .unwrap_or(Locale::En)
+}
+
+const EN: &[(&str, &str)] = &[
+    // Shortcuts
+    ("shortcut.toggle_overlay", "Toggle overlay visibility"),
+    ("shortcut.trigger_assist", "Trigger AI assist"),
+    ("shortcut.toggle_session", "Start/Stop session"),
+    ("shortcut.clear_context", "Clear context"),
+    ("shortcut.toggle_click_through", "Toggle overlay click-through"),
+    ("shortcut.push_to_talk", "Hold to talk"),
+    ("shortcut.paste_response", "Paste the suggested response"),
+    ("shortcut.type_response", "Type the suggested response"),
+    ("action.start_session", "Start a coaching session"),
+    ("action.stop_session", "Stop the current session"),
+    ("action.toggle_session", "Start/Stop session"),
+    ("action.flag_moment", "Flag this moment of the session"),
+    ("action.trigger_assist", "Trigger AI assist"),
+    ("action.clear_context", "Clear context"),
+    ("action.toggle_overlay", "Toggle overlay visibility"),
+    ("action.show_overlay", "Show overlay"),
+    ("action.hide_overlay", "Hide overlay"),
+    ("action.toggle_click_through", "Toggle overlay click-through"),
+    ("action.paste_response", "Paste the suggested response"),
+    ("action.type_response", "Type the suggested response"),
+    ("action.open_dashboard", "Open dashboard"),
+    // Notifications
+    ("notice.session_saved.title", "Session saved"),
+    ("notice.session_saved.body", "Your transcript and recordings are in the dashboard."),
+    ("notice.model_ready.title", "Transcription model ready"),
+    ("notice.model_ready.body", "Whisper {name} is downloaded and ready for local transcription."),
+    ("notice.quota.title", "AI quota nearly used up"),
+    ("notice.quota.body", "Your AI provider is close to its rate limit, so assists may slow down or fail."),
+    ("notice.update_available.title", "Update available"),
+    ("notice.update_available.body", "Queen Mama {version} is ready to download."),
+    ("notice.up_to_date.title", "You're up to date"),
+    ("notice.up_to_date.body", "Queen Mama {version} is the latest version."),
+    ("notice.update_failed.title", "Couldn't check for updates"),
+    ("notice.crash_report.title", "Queen Mama quit unexpectedly"),
+    ("notice.crash_report.body", "A crash report was saved. You can send it to us from Settings."),
+    ("notice.preview.title", "Notification preview"),
+    ("notice.preview.body", "This is how Queen Mama notifications will sound."),
+    ("action.open_session", "Open Session"),
+    ("action.open_settings", "Open Settings"),
+    // Tray
+    ("tray.show_overlay", "Show Overlay"),
+    ("tray.hide_overlay", "Hide Overlay"),
+    ("tray.click_through", "Click-Through Overlay"),
+    ("tray.start_session", "Start Session"),
+    ("tray.pause_session", "Pause Session"),
+    ("tray.resume_session", "Resume Session"),
+    ("tray.stop_session", "Stop Session"),
+    ("tray.not_recording", "Not recording"),
+    ("tray.recording", "Recording · {elapsed}"),
+    ("tray.paused", "Paused · {elapsed}"),
+    ("tray.saving", "Saving recordings…"),
+    ("tray.now", "Now"),
+    ("tray.no_active_session", "No active session"),
+    ("tray.elapsed", "Elapsed: {elapsed}"),
+    ("tray.talk_ratio", "Talk ratio: {ratio}"),
+    ("tray.open_goals", "Open goals: {count}"),
+    ("tray.recent_sessions", "Recent Sessions"),
+    ("tray.no_sessions", "No sessions yet"),
+    ("tray.profile", "Profile"),
+    ("tray.manage_profiles", "Manage Profiles…"),
+    ("tray.open_dashboard", "Open Dashboard"),
+    ("tray.feedback", "Give Feedback"),
+    ("tray.check_updates", "Check for Updates…"),
+    ("tray.quit", "Quit Queen Mama"),
+    ("overlay_menu.move_to", "Move To"),
+    ("overlay_menu.top_left", "Top Left"),
+    ("overlay_menu.top_center", "Top Center"),
+    ("overlay_menu.top_right", "Top Right"),
+    ("overlay_menu.bottom_left", "Bottom Left"),
+    ("overlay_menu.bottom_center", "Bottom Center"),
+    ("overlay_menu.bottom_right", "Bottom Right"),
+    ("overlay_menu.opacity", "Opacity"),
+    ("overlay_menu.click_through", "Click-Through"),
+    ("overlay_menu.end_session", "End Session"),
+    // Defaults
+    ("profile.default_name", "Default"),
+    ("import.shared_text", "Shared text"),
+    ("session.split_title", "{title} (part 2)"),
+    ("session.default_title", "Coaching session"),
+    ("auth.browser_done", "You're signed in to Queen Mama. You can close this tab."),
+    ("feature.disabled_by_policy", "Turned off by your organization"),
+    ("feature.desktop_only", "Only available in the desktop app"),
+    ("feature.permission.screen_recording", "Allow Screen Recording for Queen Mama in System Settings > Privacy & Security"),
+    ("feature.permission.accessibility", "Allow Accessibility access for Queen Mama in System Settings > Privacy & Security"),
+    ("feature.permission.notifications", "Allow notifications for Queen Mama in your system settings"),
+    ("migrations.no_database", "{profile}: no database yet"),
+    ("migrations.locked", "{profile}: locked by a passphrase, skipped"),
+    ("migrations.unreadable", "{profile}: can't be read: {error}"),
+    ("migrations.up_to_date", "{profile}: up to date at version {version}"),
+    ("migrations.pending", "{profile}: at version {version}, {count} migrations pending"),
+    ("migrations.newer", "{profile}: at version {version}, newer than this app's {latest}; update the app before opening it"),
+    // Errors
+    ("error.unsupported_locale", "Unsupported language: {locale}"),
+    ("error.overlay_missing", "Overlay window not found"),
+    ("error.main_window_missing", "Main window not found"),
+    ("error.window_not_found", "Window not found: {id}"),
+    ("error.monitor_not_found", "Monitor not found: {id}"),
+    ("error.no_monitor", "No monitor found"),
+    ("error.no_primary_monitor", "No primary monitor"),
+    ("error.region_outside_monitor", "Region is outside the monitor"),
+    ("error.unknown_action", "Unknown action: {action}"),
+    ("error.ai_request_failed", "AI request failed ({status}): {body}"),
+    ("error.ai_no_api_key", "No API key set for the selected AI provider"),
+    ("error.audio_not_captured", "Audio source is not being captured"),
+    ("error.audio_running", "Audio capture already running"),
+    ("error.audio_thread_exited", "Audio capture thread exited"),
+    ("error.audio_device_not_found", "Audio device not found: {name}"),
+    ("error.audio_fallback_rate", "The default device can't record at {rate} Hz"),
+    ("error.no_microphone", "No microphone available"),
+    ("error.no_output_device", "No output device available for loopback capture"),
+    ("error.no_loopback_device", "No loopback device found; install a virtual audio device such as BlackHole to capture system audio"),
+    ("error.no_display", "No display found to capture system audio from"),
+    ("error.system_audio_timeout", "System audio capture didn't start in time"),
+    ("error.unsupported_sample_format", "Unsupported sample format: {format}"),
+    ("error.session_not_found", "Session not found: {id}"),
+    ("error.session_not_in_trash", "Session not in the trash: {id}"),
+    ("error.merge_needs_sessions", "Pick at least two sessions to merge"),
+    ("error.session_recording", "Stop the session before editing it"),
+    ("error.split_out_of_range", "The split point must fall inside the session"),
+    ("error.audio_file_unsupported", "Can't read the audio in {path}; try M4A, MP3, WAV, FLAC or OGG"),
+    ("error.file_transcription_running", "This file is already being transcribed"),
+    ("error.diarization_model", "Telling speakers apart needs a speaker-turn model such as small.en-tdrz, not {name}"),
+    ("error.redaction_range_invalid", "Pick at least one span to redact, each ending after it starts"),
+    ("error.clip_range_invalid", "A clip must end after it starts and last at most {max} minutes"),
+    ("clip.heading", "Clip from {start} to {end}"),
+    ("error.highlights_nothing", "Session {id} has no flags, review comments or compliance hits to build highlights from"),
+    ("highlights.heading", "Highlights of {title}"),
+    ("highlights.intro", "Moment {number}, at {time}."),
+    ("document.started", "Started {time}"),
+    ("document.suggestion", "Suggestion"),
+    ("document.note", "Note"),
+    ("compliance.heading", "Compliance summary: {title}"),
+    ("compliance.counts", "{high} high, {medium} medium, {low} low"),
+    ("compliance.no_hits", "No prohibited phrases found."),
+    ("compliance.column_time", "Time"),
+    ("compliance.column_severity", "Severity"),
+    ("compliance.column_category", "Category"),
+    ("compliance.column_phrase", "Phrase"),
+    ("compliance.column_speaker", "Speaker"),
+    ("compliance.column_said", "Said"),
+    ("compliance.severity_low", "Low"),
+    ("compliance.severity_medium", "Medium"),
+    ("compliance.severity_high", "High"),
+    ("error.no_system_voice", "No system voice on this platform"),
+    ("error.speech_format", "Unexpected speech format: {bits} bits"),
+    ("export.transcript", "Transcript"),
+    ("export.coaching_notes", "Coaching notes"),
+    ("export.tags", "Tags: {tags}"),
+    ("export.note_heading", "{kind} · {time} ({offset} in)"),
+    ("error.tag_invalid", "Tags need 1 to {max} characters"),
+    ("error.field_name_invalid", "Field names need 1 to {max} characters"),
+    ("error.field_exists", "A field named {name} already exists"),
+    ("error.field_not_found", "Field not found: {name}"),
+    ("error.field_value_invalid", "This value doesn't fit the {name} field's type"),
+    ("error.filter_name_invalid", "Filter names need 1 to {max} characters"),
+    ("error.filter_not_found", "Saved filter not found: {id}"),
+    ("error.unsupported_url", "Unsupported URL: {url}"),
+    ("error.media_download_failed", "yt-dlp failed to download {url}"),
+    ("error.yt_dlp_missing", "yt-dlp is not installed; install it to import from video and podcast sites"),
+    ("error.unsupported_file_type", "Unsupported file type: {path}"),
+    ("error.midi_device_not_found", "MIDI device not found: {name}"),
+    ("error.no_journal_entries", "No journal entries on {date}"),
+    ("error.invalid_date", "Invalid date {date}: {error}"),
+    ("error.not_a_file", "Not a file: {path}"),
+    ("error.not_a_directory", "Not a directory: {path}"),
+    ("error.note_not_found", "Note not found: {id}"),
+    ("error.text_recognition_failed", "Text recognition failed: {error}"),
+    ("error.unknown_overlay_position", "Unknown overlay position: {position}"),
+    ("error.profile_name_empty", "Profile name can't be empty"),
+    ("error.profile_exists", "A profile named {name} already exists"),
+    ("error.summary_language_invalid", "Summary language should be a language name, like English"),
+    ("error.summary_nothing", "Session {id} has no transcript or notes to summarize"),
+    ("error.summary_not_found", "Session {id} has no summary version {version}"),
+    ("error.default_profile_delete", "The default profile can't be deleted"),
+    ("error.profile_active", "Switch to another profile before deleting this one"),
+    ("error.profile_not_found", "Profile not found: {id}"),
+    ("error.profile_switch_in_session", "Stop the current session before switching profiles"),
+    ("error.profile_key_missing", "This profile's encryption key is missing from the keychain"),
+    ("error.database_locked", "Session history is locked; enter your passphrase to unlock it"),
+    ("error.wrong_passphrase", "Wrong passphrase"),
+    ("error.encrypted_file_damaged", "An encrypted file is damaged or was changed"),
+    ("error.encrypted_file_other_key", "This file was encrypted with another profile's passphrase"),
+    ("error.file_not_encrypted", "This file isn't encrypted"),
+    ("error.passphrase_required", "This profile is encrypted; enter its passphrase to switch to it"),
+    ("error.passphrase_too_short", "The passphrase needs at least {min} characters"),
+    ("error.encryption_enabled", "This profile's history is already encrypted with a passphrase"),
+    ("error.encryption_disabled", "This profile's history isn't encrypted with a passphrase"),
+    ("error.encryption_in_session", "Stop the current session first"),
+    ("error.profiles_admin_only", "Only admin profiles can manage profiles"),
+    ("error.admin_profile_unprotected", "{name} is an admin profile without a passphrase, so only an admin can switch to it"),
+    ("error.admin_passphrase_first", "Set a passphrase on an admin profile first, so there's a way back to it"),
+    ("error.invalid_pairing_token", "Invalid pairing token"),
+    ("error.comment_not_found", "Comment not found: {id}"),
+    ("error.comment_needs_position", "Comments need a timeline position"),
+    ("error.not_review_file", "Not a review file"),
+    ("error.review_file_invalid", "Not a review file: {error}"),
+    ("error.review_file_too_new", "Review file version {version} needs a newer version of the app"),
+    ("error.secret_not_readable", "Secret {name} can't be read from the webview"),
+    ("error.session_in_progress", "A session is already in progress"),
+    ("error.session_cannot_pause", "Cannot pause the session now"),
+    ("error.session_cannot_resume", "Cannot resume the session now"),
+    ("error.no_session", "No session in progress"),
+    ("error.no_shortcut_pressed", "No shortcut was pressed"),
+    ("error.shortcut_capture_cancelled", "Shortcut capture cancelled"),
+    ("error.keyboard_unavailable", "Can't listen to the keyboard: {error}"),
+    ("error.key_not_allowed", "{key} can't be used in a shortcut"),
+    ("error.shortcut_needs_modifier", "Shortcuts need at least one modifier key"),
+    ("error.unknown_shortcut", "Unknown shortcut: {id}"),
+    ("error.invalid_shortcut", "Invalid shortcut {shortcut}: {error}"),
+    ("error.shortcut_in_use", "{shortcut} is already used by {action}"),
+    ("error.shortcut_taken", "Already used by {action}"),
+    ("error.shortcut_reserved", "Reserved by the operating system"),
+    ("error.custom_model_needs_name", "Custom models need a name"),
+    ("error.whisper_model_exists", "A Whisper model named {name} already exists"),
+    ("error.transcription_running", "Local transcription already running"),
+    ("error.transcription_not_running", "Local transcription isn't running"),
+    ("error.custom_model_missing", "Custom Whisper model {name} is missing from {path}"),
+    ("error.whisper_model_missing", "Whisper model {name} is not downloaded"),
+    ("error.whisper_model_load", "Can't load Whisper model {name}: {error}"),
+    ("error.unknown_whisper_model", "Unknown Whisper model: {name}"),
+    ("error.not_ggml_model", "{path} is not a whisper.cpp ggml model. Add a LoRA adapter as its folder or safetensors file, or convert full weights with whisper.cpp's convert-h5-to-ggml.py first."),
+    ("error.ggml_model_load", "whisper.cpp couldn't load {path}: {error}"),
+    ("error.lora_needs_base", "Choose the stock Whisper model this LoRA adapter was trained on"),
+    ("error.lora_config_missing", "The LoRA adapter's config {path} is missing"),
+    ("error.lora_invalid", "Not a usable LoRA adapter: {error}"),
+    ("error.lora_wrong_base", "The LoRA adapter doesn't fit this base model ({tensor}); choose the model it was trained on"),
+    ("error.lora_quantized_base", "LoRA adapters can only be merged into unquantized models"),
+    ("error.update_nothing_to_download", "No update to download; check for updates first"),
+    ("error.update_nothing_to_install", "No update to install"),
+    ("error.update_not_downloaded", "The update hasn't been downloaded yet"),
+    ("error.update_in_session", "Stop the current session before installing the update"),
+    ("error.updates_disabled", "Updates aren't enabled in this build"),
+    ("error.crash_report_not_found", "Crash report not found: {id}"),
+    ("error.file_not_found", "File not found: {path}"),
+    ("error.crash_report_upload_failed", "Couldn't send the crash report ({status})"),
+    ("error.benchmark_consent_required", "Turn on the cohort benchmark in settings first"),
+    ("error.benchmark_not_enough_speech", "Record at least {minutes} minutes of your own speech in the last 30 days to compare"),
+    ("error.benchmark_request_failed", "Couldn't get the cohort benchmark ({status})"),
+    ("error.sound_not_wav", "Notification sounds must be WAV files: {path}"),
+    ("error.sound_unreadable", "Can't read sound {path}: {error}"),
+    ("error.sound_too_long", "Notification sounds can be at most {seconds} seconds long"),
+    ("error.no_sound_output", "No audio output device available"),
+    ("error.login_timed_out", "Sign-in timed out"),
+    ("error.login_cancelled", "Sign-in was cancelled"),
+    ("error.login_failed", "Sign-in failed: {error}"),
+    ("error.login_state_mismatch", "Sign-in response didn't match this request"),
+    ("error.login_no_code", "Sign-in response had no authorization code"),
+    ("error.token_request_failed", "Token request failed ({status})"),
+    ("error.not_signed_in", "Not signed in"),
+    ("error.tesseract_missing", "Reading screen text needs tesseract installed and on your PATH"),
+    ("error.tesseract_unavailable", "tesseract is not installed or not on PATH: {error}"),
+    ("error.tesseract_failed", "tesseract failed: {error}"),
+    ("error.realtime_not_connected", "Not connected to the coaching service"),
+    ("error.realtime_heartbeat_timeout", "The coaching service stopped responding"),
+    ("error.realtime_closed", "The coaching service closed the connection"),
+    ("error.calendar_access_denied", "Calendar access is off. Allow it for Queen Mama in System Settings > Privacy & Security > Calendars"),
+    ("error.mock_failure", "Simulated failure from the mock provider"),
+    ("error.mock_timeout", "The mock provider timed out (simulated)"),
+    ("error.mock_disconnected", "The mock provider dropped the connection mid-reply (simulated)"),
+    ("error.chaos_mode_off", "Chaos mode is off. Turn it on in the developer settings to inject faults"),
+    ("error.chaos_injected", "Injected by chaos mode"),
+    ("error.chaos_device_lost", "Audio device disconnected (injected by chaos mode)"),
+    ("error.paste_into_self", "Switch to the app to paste into first"),
+    ("error.paste_needs_accessibility", "Allow Queen Mama LITE under Accessibility in System Settings to paste into other apps"),
+    ("error.paste_failed", "Couldn't paste into the focused app"),
+    ("error.type_into_self", "Switch to the app to type into first"),
+    ("error.typing_needs_accessibility", "Allow Queen Mama LITE under Accessibility in System Settings to type into other apps"),
+    ("error.typing_in_progress", "Already typing a response"),
+    ("error.typing_failed", "Couldn't type into the focused app"),
+    ("error.event_version_unsupported", "No common event version: asked for {requested}, supported {supported}"),
+    ("permission.title", "Allow access?"),
+    ("permission.prompt", "{client} ({source}) wants to: {action}. Your answer is remembered for this client; you can change it in Settings."),
+    ("permission.allow", "Allow"),
+    ("permission.deny", "Don't Allow"),
+    ("permission.source_relay", "paired through the companion relay"),
+    ("permission.source_link", "through a link"),
+    ("permission.anonymous_link", "An app or website"),
+    ("error.settings_invalid", "Invalid settings: {error}"),
+    ("error.settings_invalid_size", "The overlay can't be smaller than {width}×{height}"),
+    ("error.settings_model_missing", "Choose an AI model"),
+    ("error.settings_invalid_url", "Not an http(s) address: {url}"),
+    // Remediations
+    ("remediation.panic", "Restart Queen Mama. A crash report was saved, and you can send it to us from Settings."),
+    ("remediation.audio.stream_failed", "Check that the audio device is still connected, then restart the session."),
+    ("remediation.transcription.inference_failed", "Choose a smaller Whisper model in Settings."),
+    ("remediation.secrets.keychain_unavailable", "Unlock the system keychain, then restart Queen Mama."),
+    ("remediation.shortcuts.register_failed", "Another app may be using this shortcut. Pick a different one in Settings."),
+    ("remediation.watch_folder.unavailable", "Check that the folder still exists, or pick another one in Settings."),
+    ("remediation.input.pedal_unavailable", "Reconnect the pedal, then select it again in Settings."),
+];
+
+const FR: &[(&str, &str)] = &[
+    // Shortcuts
+    ("shortcut.toggle_overlay", "Afficher/masquer l'overlay"),
+    ("shortcut.trigger_assist", "Lancer l'assistance IA"),
+    ("shortcut.toggle_session", "Démarrer/arrêter la session"),
+    ("shortcut.clear_context", "Effacer le contexte"),
+    ("shortcut.toggle_click_through", "Activer/désactiver le clic à travers l'overlay"),
+    ("shortcut.push_to_talk", "Maintenir pour parler"),
+    ("shortcut.paste_response", "Coller la réponse suggérée"),
+    ("shortcut.type_response", "Taper la réponse suggérée"),
+    ("action.start_session", "Démarrer une session de coaching"),
+    ("action.stop_session", "Arrêter la session en cours"),
+    ("action.toggle_session", "Démarrer/arrêter la session"),
+    ("action.flag_moment", "Marquer ce moment de la session"),
+    ("action.trigger_assist", "Lancer l'assistance IA"),
+    ("action.clear_context", "Effacer le contexte"),
+    ("action.toggle_overlay", "Afficher/masquer l'overlay"),
+    ("action.show_overlay", "Afficher l'overlay"),
+    ("action.hide_overlay", "Masquer l'overlay"),
+    ("action.toggle_click_through", "Activer/désactiver le clic à travers l'overlay"),
+    ("action.paste_response", "Coller la réponse suggérée"),
+    ("action.type_response", "Taper la réponse suggérée"),
+    ("action.open_dashboard", "Ouvrir le tableau de bord"),
+    // Notifications
+    ("notice.session_saved.title", "Session enregistrée"),
+    ("notice.session_saved.body", "Votre transcription et vos enregistrements sont dans le tableau de bord."),
+    ("notice.model_ready.title", "Modèle de transcription prêt"),
+    ("notice.model_ready.body", "Whisper {name} est téléchargé et prêt pour la transcription locale."),
+    ("notice.quota.title", "Quota IA presque épuisé"),
+    ("notice.quota.body", "Votre fournisseur IA approche de sa limite, les assistances peuvent ralentir ou échouer."),
+    ("notice.update_available.title", "Mise à jour disponible"),
+    ("notice.update_available.body", "Queen Mama {version} est prêt à être téléchargé."),
+    ("notice.up_to_date.title", "Vous êtes à jour"),
+    ("notice.up_to_date.body", "Queen Mama {version} est la dernière version."),
+    ("notice.update_failed.title", "Impossible de vérifier les mises à jour"),
+    ("notice.crash_report.title", "Queen Mama s'est arrêté de façon inattendue"),
+    ("notice.crash_report.body", "Un rapport de plantage a été enregistré. Vous pouvez nous l'envoyer depuis les Réglages."),
+    ("notice.preview.title", "Aperçu de la notification"),
+    ("notice.preview.body", "Voici le son des notifications de Queen Mama."),
+    ("action.open_session", "Ouvrir la session"),
+    ("action.open_settings", "Ouvrir les réglages"),
+    // Tray
+    ("tray.show_overlay", "Afficher l'overlay"),
+    ("tray.hide_overlay", "Masquer l'overlay"),
+    ("tray.click_through", "Clic à travers l'overlay"),
+    ("tray.start_session", "Démarrer la session"),
+    ("tray.pause_session", "Mettre en pause"),
+    ("tray.resume_session", "Reprendre la session"),
+    ("tray.stop_session", "Arrêter la session"),
+    ("tray.not_recording", "Pas d'enregistrement"),
+    ("tray.recording", "Enregistrement · {elapsed}"),
+    ("tray.paused", "En pause · {elapsed}"),
+    ("tray.saving", "Enregistrement des fichiers…"),
+    ("tray.now", "En cours"),
+    ("tray.no_active_session", "Aucune session active"),
+    ("tray.elapsed", "Durée : {elapsed}"),
+    ("tray.talk_ratio", "Temps de parole : {ratio}"),
+    ("tray.open_goals", "Objectifs ouverts : {count}"),
+    ("tray.recent_sessions", "Sessions récentes"),
+    ("tray.no_sessions", "Aucune session pour l'instant"),
+    ("tray.profile", "Profil"),
+    ("tray.manage_profiles", "Gérer les profils…"),
+    ("tray.open_dashboard", "Ouvrir le tableau de bord"),
+    ("tray.feedback", "Donner votre avis"),
+    ("tray.check_updates", "Rechercher des mises à jour…"),
+    ("tray.quit", "Quitter Queen Mama"),
+    ("overlay_menu.move_to", "Déplacer vers"),
+    ("overlay_menu.top_left", "En haut à gauche"),
+    ("overlay_menu.top_center", "En haut au centre"),
+    ("overlay_menu.top_right", "En haut à droite"),
+    ("overlay_menu.bottom_left", "En bas à gauche"),
+    ("overlay_menu.bottom_center", "En bas au centre"),
+    ("overlay_menu.bottom_right", "En bas à droite"),
+    ("overlay_menu.opacity", "Opacité"),
+    ("overlay_menu.click_through", "Clic à travers"),
+    ("overlay_menu.end_session", "Terminer la session"),
+    // Defaults
+    ("profile.default_name", "Par défaut"),
+    ("import.shared_text", "Texte partagé"),
+    ("session.split_title", "{title} (partie 2)"),
+    ("session.default_title", "Session de coaching"),
+    ("auth.browser_done", "Vous êtes connecté à Queen Mama. Vous pouvez fermer cet onglet."),
+    ("feature.disabled_by_policy", "Désactivé par votre organisation"),
+    ("feature.desktop_only", "Disponible uniquement dans l'application de bureau"),
+    ("feature.permission.screen_recording", "Autorisez l'enregistrement de l'écran pour Queen Mama dans Réglages Système > Confidentialité et sécurité"),
+    ("feature.permission.accessibility", "Autorisez l'accès Accessibilité pour Queen Mama dans Réglages Système > Confidentialité et sécurité"),
+    ("feature.permission.notifications", "Autorisez les notifications pour Queen Mama dans les réglages du système"),
+    ("migrations.no_database", "{profile} : pas encore de base de données"),
+    ("migrations.locked", "{profile} : verrouillé par une phrase secrète, ignoré"),
+    ("migrations.unreadable", "{profile} : illisible : {error}"),
+    ("migrations.up_to_date", "{profile} : à jour en version {version}"),
+    ("migrations.pending", "{profile} : en version {version}, {count} migrations en attente"),
+    ("migrations.newer", "{profile} : en version {version}, plus récente que la {latest} de cette application ; mettez l'application à jour avant de l'ouvrir"),
+    // Errors
+    ("error.unsupported_locale", "Langue non prise en charge : {locale}"),
+    ("error.overlay_missing", "Fenêtre d'overlay introuvable"),
+    ("error.main_window_missing", "Fenêtre principale introuvable"),
+    ("error.window_not_found", "Fenêtre introuvable : {id}"),
+    ("error.monitor_not_found", "Écran introuvable : {id}"),
+    ("error.no_monitor", "Aucun écran trouvé"),
+    ("error.no_primary_monitor", "Aucun écran principal"),
+    ("error.region_outside_monitor", "La zone dépasse de l'écran"),
+    ("error.unknown_action", "Action inconnue : {action}"),
+    ("error.ai_request_failed", "La requête IA a échoué ({status}) : {body}"),
+    ("error.ai_no_api_key", "Aucune clé API pour le fournisseur IA sélectionné"),
+    ("error.audio_not_captured", "Cette source audio n'est pas capturée"),
+    ("error.audio_running", "La capture audio est déjà en cours"),
+    ("error.audio_thread_exited", "Le thread de capture audio s'est arrêté"),
+    ("error.audio_device_not_found", "Périphérique audio introuvable : {name}"),
+    ("error.audio_fallback_rate", "Le périphérique par défaut ne peut pas enregistrer à {rate} Hz"),
+    ("error.no_microphone", "Aucun micro disponible"),
+    ("error.no_output_device", "Aucune sortie audio disponible pour la capture en boucle"),
+    ("error.no_loopback_device", "Aucun périphérique de bouclage trouvé ; installez un périphérique audio virtuel comme BlackHole pour capturer le son du système"),
+    ("error.no_display", "Aucun écran trouvé pour capturer le son du système"),
+    ("error.system_audio_timeout", "La capture du son du système n'a pas démarré à temps"),
+    ("error.unsupported_sample_format", "Format d'échantillon non pris en charge : {format}"),
+    ("error.session_not_found", "Session introuvable : {id}"),
+    ("error.session_not_in_trash", "Session absente de la corbeille : {id}"),
+    ("error.merge_needs_sessions", "Sélectionnez au moins deux sessions à fusionner"),
+    ("error.session_recording", "Arrêtez la session avant de la modifier"),
+    ("error.split_out_of_range", "Le point de découpe doit se situer dans la session"),
+    ("error.audio_file_unsupported", "Impossible de lire l'audio de {path} ; essayez M4A, MP3, WAV, FLAC ou OGG"),
+    ("error.file_transcription_running", "Ce fichier est déjà en cours de transcription"),
+    ("error.diarization_model", "Distinguer les interlocuteurs nécessite un modèle de tours de parole comme small.en-tdrz, pas {name}"),
+    ("error.redaction_range_invalid", "Sélectionnez au moins un passage à caviarder, chacun se terminant après son début"),
+    ("error.clip_range_invalid", "Un extrait doit se terminer après son début et durer au plus {max} minutes"),
+    ("clip.heading", "Extrait de {start} à {end}"),
+    ("error.highlights_nothing", "La session {id} n'a ni signet, ni commentaire de revue, ni alerte de conformité pour en tirer des moments forts"),
+    ("highlights.heading", "Moments forts de {title}"),
+    ("highlights.intro", "Moment {number}, à {time}."),
+    ("document.started", "Commencée le {time}"),
+    ("document.suggestion", "Suggestion"),
+    ("document.note", "Note"),
+    ("compliance.heading", "Synthèse de conformité : {title}"),
+    ("compliance.counts", "{high} élevée(s), {medium} moyenne(s), {low} faible(s)"),
+    ("compliance.no_hits", "Aucune expression interdite trouvée."),
+    ("compliance.column_time", "Moment"),
+    ("compliance.column_severity", "Gravité"),
+    ("compliance.column_category", "Catégorie"),
+    ("compliance.column_phrase", "Expression"),
+    ("compliance.column_speaker", "Intervenant"),
+    ("compliance.column_said", "Propos"),
+    ("compliance.severity_low", "Faible"),
+    ("compliance.severity_medium", "Moyenne"),
+    ("compliance.severity_high", "Élevée"),
+    ("error.no_system_voice", "Aucune voix système sur cette plateforme"),
+    ("error.speech_format", "Format de voix inattendu : {bits} bits"),
+    ("export.transcript", "Transcription"),
+    ("export.coaching_notes", "Notes de coaching"),
+    ("export.tags", "Étiquettes : {tags}"),
+    ("export.note_heading", "{kind} · {time} (à {offset})"),
+    ("error.tag_invalid", "Les tags doivent contenir de 1 à {max} caractères"),
+    ("error.field_name_invalid", "Les noms de champ doivent contenir de 1 à {max} caractères"),
+    ("error.field_exists", "Un champ nommé {name} existe déjà"),
+    ("error.field_not_found", "Champ introuvable : {name}"),
+    ("error.field_value_invalid", "Cette valeur ne correspond pas au type du champ {name}"),
+    ("error.filter_name_invalid", "Les noms de filtre doivent contenir de 1 à {max} caractères"),
+    ("error.filter_not_found", "Filtre enregistré introuvable : {id}"),
+    ("error.unsupported_url", "URL non prise en charge : {url}"),
+    ("error.media_download_failed", "yt-dlp n'a pas pu télécharger {url}"),
+    ("error.yt_dlp_missing", "yt-dlp n'est pas installé ; installez-le pour importer depuis des sites de vidéos et de podcasts"),
+    ("error.unsupported_file_type", "Type de fichier non pris en charge : {path}"),
+    ("error.midi_device_not_found", "Périphérique MIDI introuvable : {name}"),
+    ("error.no_journal_entries", "Aucune entrée de journal le {date}"),
+    ("error.invalid_date", "Date invalide {date} : {error}"),
+    ("error.not_a_file", "Ce n'est pas un fichier : {path}"),
+    ("error.not_a_directory", "Ce n'est pas un dossier : {path}"),
+    ("error.note_not_found", "Note introuvable : {id}"),
+    ("error.text_recognition_failed", "La reconnaissance de texte a échoué : {error}"),
+    ("error.unknown_overlay_position", "Position d'overlay inconnue : {position}"),
+    ("error.profile_name_empty", "Le nom du profil ne peut pas être vide"),
+    ("error.profile_exists", "Un profil nommé {name} existe déjà"),
+    ("error.summary_language_invalid", "La langue des résumés doit être un nom de langue, comme l'anglais"),
+    ("error.summary_nothing", "La session {id} n'a ni transcription ni notes à résumer"),
+    ("error.summary_not_found", "La session {id} n'a pas de version {version} de résumé"),
+    ("error.default_profile_delete", "Le profil par défaut ne peut pas être supprimé"),
+    ("error.profile_active", "Passez à un autre profil avant de supprimer celui-ci"),
+    ("error.profile_not_found", "Profil introuvable : {id}"),
+    ("error.profile_switch_in_session", "Arrêtez la session en cours avant de changer de profil"),
+    ("error.profile_key_missing", "La clé de chiffrement de ce profil est absente du trousseau"),
+    ("error.database_locked", "L'historique des sessions est verrouillé ; saisissez votre phrase secrète pour le déverrouiller"),
+    ("error.wrong_passphrase", "Phrase secrète incorrecte"),
+    ("error.encrypted_file_damaged", "Un fichier chiffré est endommagé ou a été modifié"),
+    ("error.encrypted_file_other_key", "Ce fichier a été chiffré avec la phrase secrète d'un autre profil"),
+    ("error.file_not_encrypted", "Ce fichier n'est pas chiffré"),
+    ("error.passphrase_required", "Ce profil est chiffré ; saisissez sa phrase secrète pour y basculer"),
+    ("error.passphrase_too_short", "La phrase secrète doit comporter au moins {min} caractères"),
+    ("error.encryption_enabled", "L'historique de ce profil est déjà chiffré avec une phrase secrète"),
+    ("error.encryption_disabled", "L'historique de ce profil n'est pas chiffré avec une phrase secrète"),
+    ("error.encryption_in_session", "Arrêtez d'abord la session en cours"),
+    ("error.profiles_admin_only", "Seuls les profils administrateurs peuvent gérer les profils"),
+    ("error.admin_profile_unprotected", "{name} est un profil administrateur sans phrase secrète : seul un administrateur peut y basculer"),
+    ("error.admin_passphrase_first", "Définissez d'abord une phrase secrète sur un profil administrateur pour pouvoir y revenir"),
+    ("error.invalid_pairing_token", "Jeton d'appairage invalide"),
+    ("error.comment_not_found", "Commentaire introuvable : {id}"),
+    ("error.comment_needs_position", "Les commentaires doivent avoir une position sur la chronologie"),
+    ("error.not_review_file", "Ce n'est pas un fichier de relecture"),
+    ("error.review_file_invalid", "Ce n'est pas un fichier de relecture : {error}"),
+    ("error.review_file_too_new", "Le fichier de relecture en version {version} nécessite une version plus récente de l'application"),
+    ("error.secret_not_readable", "Le secret {name} ne peut pas être lu depuis la webview"),
+    ("error.session_in_progress", "Une session est déjà en cours"),
+    ("error.session_cannot_pause", "Impossible de mettre la session en pause maintenant"),
+    ("error.session_cannot_resume", "Impossible de reprendre la session maintenant"),
+    ("error.no_session", "Aucune session en cours"),
+    ("error.no_shortcut_pressed", "Aucun raccourci n'a été pressé"),
+    ("error.shortcut_capture_cancelled", "Saisie du raccourci annulée"),
+    ("error.keyboard_unavailable", "Impossible d'écouter le clavier : {error}"),
+    ("error.key_not_allowed", "{key} ne peut pas être utilisé dans un raccourci"),
+    ("error.shortcut_needs_modifier", "Les raccourcis nécessitent au moins une touche de modification"),
+    ("error.unknown_shortcut", "Raccourci inconnu : {id}"),
+    ("error.invalid_shortcut", "Raccourci invalide {shortcut} : {error}"),
+    ("error.shortcut_in_use", "{shortcut} est déjà utilisé par {action}"),
+    ("error.shortcut_taken", "Déjà utilisé par {action}"),
+    ("error.shortcut_reserved", "Réservé par le système d'exploitation"),
+    ("error.custom_model_needs_name", "Les modèles personnalisés doivent avoir un nom"),
+    ("error.whisper_model_exists", "Un modèle Whisper nommé {name} existe déjà"),
+    ("error.transcription_running", "La transcription locale est déjà en cours"),
+    ("error.transcription_not_running", "La transcription locale n'est pas en cours"),
+    ("error.custom_model_missing", "Le modèle Whisper personnalisé {name} est absent de {path}"),
+    ("error.whisper_model_missing", "Le modèle Whisper {name} n'est pas téléchargé"),
+    ("error.whisper_model_load", "Impossible de charger le modèle Whisper {name} : {error}"),
+    ("error.unknown_whisper_model", "Modèle Whisper inconnu : {name}"),
+    ("error.not_ggml_model", "{path} n'est pas un modèle ggml de whisper.cpp. Ajoutez un adaptateur LoRA par son dossier ou son fichier safetensors, ou convertissez d'abord les poids complets avec convert-h5-to-ggml.py de whisper.cpp."),
+    ("error.ggml_model_load", "whisper.cpp n'a pas pu charger {path} : {error}"),
+    ("error.lora_needs_base", "Choisissez le modèle Whisper standard sur lequel cet adaptateur LoRA a été entraîné"),
+    ("error.lora_config_missing", "La configuration {path} de l'adaptateur LoRA est introuvable"),
+    ("error.lora_invalid", "Adaptateur LoRA inutilisable : {error}"),
+    ("error.lora_wrong_base", "L'adaptateur LoRA ne correspond pas à ce modèle de base ({tensor}) ; choisissez celui sur lequel il a été entraîné"),
+    ("error.lora_quantized_base", "Les adaptateurs LoRA ne peuvent être fusionnés qu'avec des modèles non quantifiés"),
+    ("error.update_nothing_to_download", "Aucune mise à jour à télécharger ; recherchez d'abord les mises à jour"),
+    ("error.update_nothing_to_install", "Aucune mise à jour à installer"),
+    ("error.update_not_downloaded", "La mise à jour n'a pas encore été téléchargée"),
+    ("error.update_in_session", "Arrêtez la session en cours avant d'installer la mise à jour"),
+    ("error.updates_disabled", "Les mises à jour ne sont pas activées dans cette version"),
+    ("error.crash_report_not_found", "Rapport de plantage introuvable : {id}"),
+    ("error.file_not_found", "Fichier introuvable : {path}"),
+    ("error.crash_report_upload_failed", "Impossible d'envoyer le rapport de plantage ({status})"),
+    ("error.benchmark_consent_required", "Activez d'abord la comparaison avec les autres utilisateurs dans les réglages"),
+    ("error.benchmark_not_enough_speech", "Enregistrez au moins {minutes} minutes de votre propre voix sur les 30 derniers jours pour comparer"),
+    ("error.benchmark_request_failed", "Impossible d'obtenir la comparaison ({status})"),
+    ("error.sound_not_wav", "Les sons de notification doivent être des fichiers WAV : {path}"),
+    ("error.sound_unreadable", "Impossible de lire le son {path} : {error}"),
+    ("error.sound_too_long", "Les sons de notification ne doivent pas dépasser {seconds} secondes"),
+    ("error.no_sound_output", "Aucune sortie audio disponible"),
+    ("error.login_timed_out", "La connexion a expiré"),
+    ("error.login_cancelled", "La connexion a été annulée"),
+    ("error.login_failed", "Échec de la connexion : {error}"),
+    ("error.login_state_mismatch", "La réponse de connexion ne correspond pas à cette demande"),
+    ("error.login_no_code", "La réponse de connexion ne contient pas de code d'autorisation"),
+    ("error.token_request_failed", "La demande de jeton a échoué ({status})"),
+    ("error.not_signed_in", "Non connecté"),
+    ("error.tesseract_missing", "La lecture du texte à l'écran nécessite tesseract installé et dans votre PATH"),
+    ("error.tesseract_unavailable", "tesseract n'est pas installé ou n'est pas dans le PATH : {error}"),
+    ("error.tesseract_failed", "tesseract a échoué : {error}"),
+    ("error.realtime_not_connected", "Non connecté au service de coaching"),
+    ("error.realtime_heartbeat_timeout", "Le service de coaching ne répond plus"),
+    ("error.realtime_closed", "Le service de coaching a fermé la connexion"),
+    ("error.calendar_access_denied", "L'accès au calendrier est désactivé. Autorisez-le pour Queen Mama dans Réglages Système > Confidentialité et sécurité > Calendriers"),
+    ("error.mock_failure", "Échec simulé du fournisseur fictif"),
+    ("error.mock_timeout", "Le fournisseur fictif n'a pas répondu à temps (simulé)"),
+    ("error.mock_disconnected", "Le fournisseur fictif a coupé la connexion en pleine réponse (simulé)"),
+    ("error.chaos_mode_off", "Le mode chaos est désactivé. Activez-le dans les réglages développeur pour injecter des pannes"),
+    ("error.chaos_injected", "Injecté par le mode chaos"),
+    ("error.chaos_device_lost", "Périphérique audio déconnecté (injecté par le mode chaos)"),
+    ("error.paste_into_self", "Passez d'abord à l'application où coller"),
+    ("error.paste_needs_accessibility", "Autorisez Queen Mama LITE dans Accessibilité des Réglages Système pour coller dans d'autres applications"),
+    ("error.paste_failed", "Impossible de coller dans l'application active"),
+    ("error.type_into_self", "Passez d'abord à l'application où taper"),
+    ("error.typing_needs_accessibility", "Autorisez Queen Mama LITE dans Accessibilité des Réglages Système pour taper dans d'autres applications"),
+    ("error.typing_in_progress", "Une réponse est déjà en cours de saisie"),
+    ("error.typing_failed", "Impossible de taper dans l'application active"),
+    ("error.event_version_unsupported", "Aucune version d'événements commune : demandé {requested}, pris en charge {supported}"),
+    ("permission.title", "Autoriser l'accès ?"),
+    ("permission.prompt", "{client} ({source}) veut : {action}. Votre réponse est mémorisée pour ce client ; vous pouvez la modifier dans les Réglages."),
+    ("permission.allow", "Autoriser"),
+    ("permission.deny", "Refuser"),
+    ("permission.source_relay", "appairé via le relais compagnon"),
+    ("permission.source_link", "via un lien"),
+    ("permission.anonymous_link", "Une application ou un site web"),
+    ("error.settings_invalid", "Réglages invalides : {error}"),
+    ("error.settings_invalid_size", "L'overlay ne peut pas être plus petit que {width}×{height}"),
+    ("error.settings_model_missing", "Choisissez un modèle d'IA"),
+    ("error.settings_invalid_url", "Adresse http(s) invalide : {url}"),
+    // Remediations
+    ("remediation.panic", "Redémarrez Queen Mama. Un rapport de plantage a été enregistré ; vous pouvez nous l'envoyer depuis les Réglages."),
+    ("remediation.audio.stream_failed", "Vérifiez que le périphérique audio est toujours connecté, puis relancez la session."),
+    ("remediation.transcription.inference_failed", "Choisissez un modèle Whisper plus petit dans les Réglages."),
+    ("remediation.secrets.keychain_unavailable", "Déverrouillez le trousseau du système, puis redémarrez Queen Mama."),
+    ("remediation.shortcuts.register_failed", "Une autre application utilise peut-être ce raccourci. Choisissez-en un autre dans les Réglages."),
+    ("remediation.watch_folder.unavailable", "Vérifiez que le dossier existe toujours, ou choisissez-en un autre dans les Réglages."),
+    ("remediation.input.pedal_unavailable", "Rebranchez la pédale, puis sélectionnez-la à nouveau dans les Réglages."),
+];
+
+const ES: &[(&str, &str)] = &[
+    // Shortcuts
+    ("shortcut.toggle_overlay", "Mostrar/ocultar la superposición"),
+    ("shortcut.trigger_assist", "Lanzar la asistencia de IA"),
+    ("shortcut.toggle_session", "Iniciar/detener la sesión"),
+    ("shortcut.clear_context", "Borrar el contexto"),
+    ("shortcut.toggle_click_through", "Activar/desactivar clic a través de la superposición"),
+    ("shortcut.push_to_talk", "Mantener para hablar"),
+    ("shortcut.paste_response", "Pegar la respuesta sugerida"),
+    ("shortcut.type_response", "Escribir la respuesta sugerida"),
+    ("action.start_session", "Iniciar una sesión de coaching"),
+    ("action.stop_session", "Detener la sesión actual"),
+    ("action.toggle_session", "Iniciar/detener la sesión"),
+    ("action.flag_moment", "Marcar este momento de la sesión"),
+    ("action.trigger_assist", "Lanzar la asistencia de IA"),
+    ("action.clear_context", "Borrar el contexto"),
+    ("action.toggle_overlay", "Mostrar/ocultar la superposición"),
+    ("action.show_overlay", "Mostrar la superposición"),
+    ("action.hide_overlay", "Ocultar la superposición"),
+    ("action.toggle_click_through", "Activar/desactivar clic a través de la superposición"),
+    ("action.paste_response", "Pegar la respuesta sugerida"),
+    ("action.type_response", "Escribir la respuesta sugerida"),
+    ("action.open_dashboard", "Abrir el panel"),
+    // Notifications
+    ("notice.session_saved.title", "Sesión guardada"),
+    ("notice.session_saved.body", "Tu transcripción y tus grabaciones están en el panel."),
+    ("notice.model_ready.title", "Modelo de transcripción listo"),
+    ("notice.model_ready.body", "Whisper {name} está descargado y listo para la transcripción local."),
+    ("notice.quota.title", "Cuota de IA casi agotada"),
+    ("notice.quota.body", "Tu proveedor de IA está cerca de su límite, así que las asistencias pueden ir lentas o fallar."),
+    ("notice.update_available.title", "Actualización disponible"),
+    ("notice.update_available.body", "Queen Mama {version} está lista para descargar."),
+    ("notice.up_to_date.title", "Estás al día"),
+    ("notice.up_to_date.body", "Queen Mama {version} es la última versión."),
+    ("notice.update_failed.title", "No se pudieron buscar actualizaciones"),
+    ("notice.crash_report.title", "Queen Mama se cerró de forma inesperada"),
+    ("notice.crash_report.body", "Se guardó un informe de error. Puedes enviárnoslo desde Ajustes."),
+    ("notice.preview.title", "Vista previa de la notificación"),
+    ("notice.preview.body", "Así sonarán las notificaciones de Queen Mama."),
+    ("action.open_session", "Abrir sesión"),
+    ("action.open_settings", "Abrir ajustes"),
+    // Tray
+    ("tray.show_overlay", "Mostrar superposición"),
+    ("tray.hide_overlay", "Ocultar superposición"),
+    ("tray.click_through", "Clic a través de la superposición"),
+    ("tray.start_session", "Iniciar sesión"),
+    ("tray.pause_session", "Pausar sesión"),
+    ("tray.resume_session", "Reanudar sesión"),
+    ("tray.stop_session", "Detener sesión"),
+    ("tray.not_recording", "Sin grabar"),
+    ("tray.recording", "Grabando · {elapsed}"),
+    ("tray.paused", "En pausa · {elapsed}"),
+    ("tray.saving", "Guardando grabaciones…"),
+    ("tray.now", "Ahora"),
+    ("tray.no_active_session", "Ninguna sesión activa"),
+    ("tray.elapsed", "Duración: {elapsed}"),
+    ("tray.talk_ratio", "Tiempo de habla: {ratio}"),
+    ("tray.open_goals", "Objetivos abiertos: {count}"),
+    ("tray.recent_sessions", "Sesiones recientes"),
+    ("tray.no_sessions", "Aún no hay sesiones"),
+    ("tray.profile", "Perfil"),
+    ("tray.manage_profiles", "Gestionar perfiles…"),
+    ("tray.open_dashboard", "Abrir el panel"),
+    ("tray.feedback", "Enviar comentarios"),
+    ("tray.check_updates", "Buscar actualizaciones…"),
+    ("tray.quit", "Salir de Queen Mama"),
+    ("overlay_menu.move_to", "Mover a"),
+    ("overlay_menu.top_left", "Arriba a la izquierda"),
+    ("overlay_menu.top_center", "Arriba en el centro"),
+    ("overlay_menu.top_right", "Arriba a la derecha"),
+    ("overlay_menu.bottom_left", "Abajo a la izquierda"),
+    ("overlay_menu.bottom_center", "Abajo en el centro"),
+    ("overlay_menu.bottom_right", "Abajo a la derecha"),
+    ("overlay_menu.opacity", "Opacidad"),
+    ("overlay_menu.click_through", "Clic a través"),
+    ("overlay_menu.end_session", "Finalizar sesión"),
+    // Defaults
+    ("profile.default_name", "Predeterminado"),
+    ("import.shared_text", "Texto compartido"),
+    ("session.split_title", "{title} (parte 2)"),
+    ("session.default_title", "Sesión de coaching"),
+    ("auth.browser_done", "Has iniciado sesión en Queen Mama. Puedes cerrar esta pestaña."),
+    ("feature.disabled_by_policy", "Desactivado por tu organización"),
+    ("feature.desktop_only", "Solo disponible en la aplicación de escritorio"),
+    ("feature.permission.screen_recording", "Permite la grabación de pantalla para Queen Mama en Ajustes del Sistema > Privacidad y seguridad"),
+    ("feature.permission.accessibility", "Permite el acceso de Accesibilidad para Queen Mama en Ajustes del Sistema > Privacidad y seguridad"),
+    ("feature.permission.notifications", "Permite las notificaciones de Queen Mama en los ajustes del sistema"),
+    ("migrations.no_database", "{profile}: aún no tiene base de datos"),
+    ("migrations.locked", "{profile}: bloqueado con una frase de contraseña, omitido"),
+    ("migrations.unreadable", "{profile}: no se puede leer: {error}"),
+    ("migrations.up_to_date", "{profile}: al día en la versión {version}"),
+    ("migrations.pending", "{profile}: en la versión {version}, {count} migraciones pendientes"),
+    ("migrations.newer", "{profile}: en la versión {version}, más reciente que la {latest} de esta aplicación; actualiza la aplicación antes de abrirla"),
+    // Errors
+    ("error.unsupported_locale", "Idioma no compatible: {locale}"),
+    ("error.overlay_missing", "No se encontró la ventana de superposición"),
+    ("error.main_window_missing", "No se encontró la ventana principal"),
+    ("error.window_not_found", "Ventana no encontrada: {id}"),
+    ("error.monitor_not_found", "Pantalla no encontrada: {id}"),
+    ("error.no_monitor", "No se encontró ninguna pantalla"),
+    ("error.no_primary_monitor", "No hay pantalla principal"),
+    ("error.region_outside_monitor", "La región está fuera de la pantalla"),
+    ("error.unknown_action", "Acción desconocida: {action}"),
+    ("error.ai_request_failed", "La solicitud de IA falló ({status}): {body}"),
+    ("error.ai_no_api_key", "No hay clave de API para el proveedor de IA seleccionado"),
+    ("error.audio_not_captured", "Esta fuente de audio no se está capturando"),
+    ("error.audio_running", "La captura de audio ya está en marcha"),
+    ("error.audio_thread_exited", "El hilo de captura de audio terminó"),
+    ("error.audio_device_not_found", "Dispositivo de audio no encontrado: {name}"),
+    ("error.audio_fallback_rate", "El dispositivo predeterminado no puede grabar a {rate} Hz"),
+    ("error.no_microphone", "No hay micrófono disponible"),
+    ("error.no_output_device", "No hay salida de audio disponible para la captura en bucle"),
+    ("error.no_loopback_device", "No se encontró un dispositivo de bucle; instala un dispositivo de audio virtual como BlackHole para capturar el audio del sistema"),
+    ("error.no_display", "No se encontró ninguna pantalla para capturar el audio del sistema"),
+    ("error.system_audio_timeout", "La captura del audio del sistema no se inició a tiempo"),
+    ("error.unsupported_sample_format", "Formato de muestra no compatible: {format}"),
+    ("error.session_not_found", "Sesión no encontrada: {id}"),
+    ("error.session_not_in_trash", "La sesión no está en la papelera: {id}"),
+    ("error.merge_needs_sessions", "Selecciona al menos dos sesiones para fusionar"),
+    ("error.session_recording", "Detén la sesión antes de editarla"),
+    ("error.split_out_of_range", "El punto de división debe estar dentro de la sesión"),
+    ("error.audio_file_unsupported", "No se puede leer el audio de {path}; prueba con M4A, MP3, WAV, FLAC u OGG"),
+    ("error.file_transcription_running", "Este archivo ya se está transcribiendo"),
+    ("error.diarization_model", "Distinguir a los hablantes requiere un modelo de turnos como small.en-tdrz, no {name}"),
+    ("error.redaction_range_invalid", "Selecciona al menos un tramo que censurar, cada uno terminando después de empezar"),
+    ("error.clip_range_invalid", "Un fragmento debe terminar después de empezar y durar como máximo {max} minutos"),
+    ("clip.heading", "Fragmento de {start} a {end}"),
+    ("error.highlights_nothing", "La sesión {id} no tiene marcas, comentarios de revisión ni alertas de cumplimiento para crear momentos destacados"),
+    ("highlights.heading", "Momentos destacados de {title}"),
+    ("highlights.intro", "Momento {number}, en {time}."),
+    ("document.started", "Iniciada el {time}"),
+    ("document.suggestion", "Sugerencia"),
+    ("document.note", "Nota"),
+    ("compliance.heading", "Resumen de cumplimiento: {title}"),
+    ("compliance.counts", "{high} alta(s), {medium} media(s), {low} baja(s)"),
+    ("compliance.no_hits", "No se encontraron frases prohibidas."),
+    ("compliance.column_time", "Momento"),
+    ("compliance.column_severity", "Gravedad"),
+    ("compliance.column_category", "Categoría"),
+    ("compliance.column_phrase", "Frase"),
+    ("compliance.column_speaker", "Hablante"),
+    ("compliance.column_said", "Dicho"),
+    ("compliance.severity_low", "Baja"),
+    ("compliance.severity_medium", "Media"),
+    ("compliance.severity_high", "Alta"),
+    ("error.no_system_voice", "No hay voz del sistema en esta plataforma"),
+    ("error.speech_format", "Formato de voz inesperado: {bits} bits"),
+    ("export.transcript", "Transcripción"),
+    ("export.coaching_notes", "Notas de coaching"),
+    ("export.tags", "Etiquetas: {tags}"),
+    ("export.note_heading", "{kind} · {time} (en {offset})"),
+    ("error.tag_invalid", "Las etiquetas deben tener entre 1 y {max} caracteres"),
+    ("error.field_name_invalid", "Los nombres de campo deben tener entre 1 y {max} caracteres"),
+    ("error.field_exists", "Ya existe un campo llamado {name}"),
+    ("error.field_not_found", "Campo no encontrado: {name}"),
+    ("error.field_value_invalid", "Este valor no corresponde al tipo del campo {name}"),
+    ("error.filter_name_invalid", "Los nombres de filtro deben tener entre 1 y {max} caracteres"),
+    ("error.filter_not_found", "Filtro guardado no encontrado: {id}"),
+    ("error.unsupported_url", "URL no compatible: {url}"),
+    ("error.media_download_failed", "yt-dlp no pudo descargar {url}"),
+    ("error.yt_dlp_missing", "yt-dlp no está instalado; instálalo para importar desde sitios de vídeos y pódcasts"),
+    ("error.unsupported_file_type", "Tipo de archivo no compatible: {path}"),
+    ("error.midi_device_not_found", "Dispositivo MIDI no encontrado: {name}"),
+    ("error.no_journal_entries", "No hay entradas de diario el {date}"),
+    ("error.invalid_date", "Fecha no válida {date}: {error}"),
+    ("error.not_a_file", "No es un archivo: {path}"),
+    ("error.not_a_directory", "No es una carpeta: {path}"),
+    ("error.note_not_found", "Nota no encontrada: {id}"),
+    ("error.text_recognition_failed", "El reconocimiento de texto falló: {error}"),
+    ("error.unknown_overlay_position", "Posición de superposición desconocida: {position}"),
+    ("error.profile_name_empty", "El nombre del perfil no puede estar vacío"),
+    ("error.profile_exists", "Ya existe un perfil llamado {name}"),
+    ("error.summary_language_invalid", "El idioma de los resúmenes debe ser el nombre de un idioma, como inglés"),
+    ("error.summary_nothing", "La sesión {id} no tiene transcripción ni notas que resumir"),
+    ("error.summary_not_found", "La sesión {id} no tiene la versión {version} del resumen"),
+    ("error.default_profile_delete", "El perfil predeterminado no se puede eliminar"),
+    ("error.profile_active", "Cambia a otro perfil antes de eliminar este"),
+    ("error.profile_not_found", "Perfil no encontrado: {id}"),
+    ("error.profile_switch_in_session", "Detén la sesión actual antes de cambiar de perfil"),
+    ("error.profile_key_missing", "Falta la clave de cifrado de este perfil en el llavero"),
+    ("error.database_locked", "El historial de sesiones está bloqueado; introduce tu frase de contraseña para desbloquearlo"),
+    ("error.wrong_passphrase", "Frase de contraseña incorrecta"),
+    ("error.encrypted_file_damaged", "Un archivo cifrado está dañado o fue modificado"),
+    ("error.encrypted_file_other_key", "Este archivo se cifró con la frase de contraseña de otro perfil"),
+    ("error.file_not_encrypted", "Este archivo no está cifrado"),
+    ("error.passphrase_required", "Este perfil está cifrado; introduce su frase de contraseña para cambiar a él"),
+    ("error.passphrase_too_short", "La frase de contraseña necesita al menos {min} caracteres"),
+    ("error.encryption_enabled", "El historial de este perfil ya está cifrado con una frase de contraseña"),
+    ("error.encryption_disabled", "El historial de este perfil no está cifrado con una frase de contraseña"),
+    ("error.encryption_in_session", "Detén primero la sesión actual"),
+    ("error.profiles_admin_only", "Solo los perfiles de administrador pueden gestionar perfiles"),
+    ("error.admin_profile_unprotected", "{name} es un perfil de administrador sin frase de contraseña, así que solo un administrador puede cambiar a él"),
+    ("error.admin_passphrase_first", "Establece primero una frase de contraseña en un perfil de administrador para poder volver a él"),
+    ("error.invalid_pairing_token", "Token de emparejamiento no válido"),
+    ("error.comment_not_found", "Comentario no encontrado: {id}"),
+    ("error.comment_needs_position", "Los comentarios necesitan una posición en la línea de tiempo"),
+    ("error.not_review_file", "No es un archivo de revisión"),
+    ("error.review_file_invalid", "No es un archivo de revisión: {error}"),
+    ("error.review_file_too_new", "El archivo de revisión versión {version} necesita una versión más reciente de la app"),
+    ("error.secret_not_readable", "El secreto {name} no se puede leer desde la webview"),
+    ("error.session_in_progress", "Ya hay una sesión en curso"),
+    ("error.session_cannot_pause", "No se puede pausar la sesión ahora"),
+    ("error.session_cannot_resume", "No se puede reanudar la sesión ahora"),
+    ("error.no_session", "No hay ninguna sesión en curso"),
+    ("error.no_shortcut_pressed", "No se pulsó ningún atajo"),
+    ("error.shortcut_capture_cancelled", "Captura de atajo cancelada"),
+    ("error.keyboard_unavailable", "No se puede escuchar el teclado: {error}"),
+    ("error.key_not_allowed", "{key} no se puede usar en un atajo"),
+    ("error.shortcut_needs_modifier", "Los atajos necesitan al menos una tecla modificadora"),
+    ("error.unknown_shortcut", "Atajo desconocido: {id}"),
+    ("error.invalid_shortcut", "Atajo no válido {shortcut}: {error}"),
+    ("error.shortcut_in_use", "{shortcut} ya lo usa {action}"),
+    ("error.shortcut_taken", "Ya lo usa {action}"),
+    ("error.shortcut_reserved", "Reservado por el sistema operativo"),
+    ("error.custom_model_needs_name", "Los modelos personalizados necesitan un nombre"),
+    ("error.whisper_model_exists", "Ya existe un modelo Whisper llamado {name}"),
+    ("error.transcription_running", "La transcripción local ya está en marcha"),
+    ("error.transcription_not_running", "La transcripción local no está en marcha"),
+    ("error.custom_model_missing", "Falta el modelo Whisper personalizado {name} en {path}"),
+    ("error.whisper_model_missing", "El modelo Whisper {name} no está descargado"),
+    ("error.whisper_model_load", "No se puede cargar el modelo Whisper {name}: {error}"),
+    ("error.unknown_whisper_model", "Modelo Whisper desconocido: {name}"),
+    ("error.not_ggml_model", "{path} no es un modelo ggml de whisper.cpp. Añade un adaptador LoRA por su carpeta o su archivo safetensors, o convierte primero los pesos completos con convert-h5-to-ggml.py de whisper.cpp."),
+    ("error.ggml_model_load", "whisper.cpp no pudo cargar {path}: {error}"),
+    ("error.lora_needs_base", "Elige el modelo Whisper estándar con el que se entrenó este adaptador LoRA"),
+    ("error.lora_config_missing", "Falta la configuración {path} del adaptador LoRA"),
+    ("error.lora_invalid", "No es un adaptador LoRA utilizable: {error}"),
+    ("error.lora_wrong_base", "El adaptador LoRA no encaja con este modelo base ({tensor}); elige el modelo con el que se entrenó"),
+    ("error.lora_quantized_base", "Los adaptadores LoRA solo se pueden fusionar con modelos sin cuantizar"),
+    ("error.update_nothing_to_download", "No hay ninguna actualización que descargar; busca actualizaciones primero"),
+    ("error.update_nothing_to_install", "No hay ninguna actualización que instalar"),
+    ("error.update_not_downloaded", "La actualización aún no se ha descargado"),
+    ("error.update_in_session", "Detén la sesión actual antes de instalar la actualización"),
+    ("error.updates_disabled", "Las actualizaciones no están activadas en esta compilación"),
+    ("error.crash_report_not_found", "Informe de error no encontrado: {id}"),
+    ("error.file_not_found", "Archivo no encontrado: {path}"),
+    ("error.crash_report_upload_failed", "No se pudo enviar el informe de error ({status})"),
+    ("error.benchmark_consent_required", "Activa primero la comparación con otros usuarios en los ajustes"),
+    ("error.benchmark_not_enough_speech", "Graba al menos {minutes} minutos de tu propia voz en los últimos 30 días para comparar"),
+    ("error.benchmark_request_failed", "No se pudo obtener la comparación ({status})"),
+    ("error.sound_not_wav", "Los sonidos de notificación deben ser archivos WAV: {path}"),
+    ("error.sound_unreadable", "No se puede leer el sonido {path}: {error}"),
+    ("error.sound_too_long", "Los sonidos de notificación pueden durar como máximo {seconds} segundos"),
+    ("error.no_sound_output", "No hay ninguna salida de audio disponible"),
+    ("error.login_timed_out", "Se agotó el tiempo para iniciar sesión"),
+    ("error.login_cancelled", "Se canceló el inicio de sesión"),
+    ("error.login_failed", "Error al iniciar sesión: {error}"),
+    ("error.login_state_mismatch", "La respuesta de inicio de sesión no coincide con esta solicitud"),
+    ("error.login_no_code", "La respuesta de inicio de sesión no incluye código de autorización"),
+    ("error.token_request_failed", "La solicitud del token falló ({status})"),
+    ("error.not_signed_in", "No has iniciado sesión"),
+    ("error.tesseract_missing", "Leer el texto de la pantalla requiere tesseract instalado y en tu PATH"),
+    ("error.tesseract_unavailable", "tesseract no está instalado o no está en el PATH: {error}"),
+    ("error.tesseract_failed", "tesseract falló: {error}"),
+    ("error.realtime_not_connected", "No hay conexión con el servicio de coaching"),
+    ("error.realtime_heartbeat_timeout", "El servicio de coaching dejó de responder"),
+    ("error.realtime_closed", "El servicio de coaching cerró la conexión"),
+    ("error.calendar_access_denied", "El acceso al calendario está desactivado. Permítelo para Queen Mama en Ajustes del Sistema > Privacidad y seguridad > Calendarios"),
+    ("error.mock_failure", "Fallo simulado del proveedor de prueba"),
+    ("error.mock_timeout", "El proveedor de prueba agotó el tiempo de espera (simulado)"),
+    ("error.mock_disconnected", "El proveedor de prueba cortó la conexión a mitad de la respuesta (simulado)"),
+    ("error.chaos_mode_off", "El modo caos está desactivado. Actívalo en los ajustes de desarrollador para inyectar fallos"),
+    ("error.chaos_injected", "Inyectado por el modo caos"),
+    ("error.chaos_device_lost", "Dispositivo de audio desconectado (inyectado por el modo caos)"),
+    ("error.paste_into_self", "Cambia primero a la aplicación donde quieres pegar"),
+    ("error.paste_needs_accessibility", "Permite Queen Mama LITE en Accesibilidad de Ajustes del Sistema para pegar en otras aplicaciones"),
+    ("error.paste_failed", "No se pudo pegar en la aplicación activa"),
+    ("error.type_into_self", "Cambia primero a la aplicación donde quieres escribir"),
+    ("error.typing_needs_accessibility", "Permite Queen Mama LITE en Accesibilidad de Ajustes del Sistema para escribir en otras aplicaciones"),
+    ("error.typing_in_progress", "Ya se está escribiendo una respuesta"),
+    ("error.typing_failed", "No se pudo escribir en la aplicación activa"),
+    ("error.event_version_unsupported", "Ninguna versión de eventos en común: se pidió {requested}, se admite {supported}"),
+    ("permission.title", "¿Permitir el acceso?"),
+    ("permission.prompt", "{client} ({source}) quiere: {action}. Tu respuesta se recuerda para este cliente; puedes cambiarla en Ajustes."),
+    ("permission.allow", "Permitir"),
+    ("permission.deny", "No permitir"),
+    ("permission.source_relay", "emparejado mediante el relé complementario"),
+    ("permission.source_link", "mediante un enlace"),
+    ("permission.anonymous_link", "Una aplicación o sitio web"),
+    ("error.settings_invalid", "Ajustes no válidos: {error}"),
+    ("error.settings_invalid_size", "El overlay no puede ser menor de {width}×{height}"),
+    ("error.settings_model_missing", "Elige un modelo de IA"),
+    ("error.settings_invalid_url", "No es una dirección http(s): {url}"),
+    // Remediations
+    ("remediation.panic", "Reinicia Queen Mama. Se guardó un informe de error que puedes enviarnos desde Ajustes."),
+    ("remediation.audio.stream_failed", "Comprueba que el dispositivo de audio sigue conectado y reinicia la sesión."),
+    ("remediation.transcription.inference_failed", "Elige un modelo Whisper más pequeño en Ajustes."),
+    ("remediation.secrets.keychain_unavailable", "Desbloquea el llavero del sistema y reinicia Queen Mama."),
+    ("remediation.shortcuts.register_failed", "Puede que otra aplicación use este atajo. Elige otro en Ajustes."),
+    ("remediation.watch_folder.unavailable", "Comprueba que la carpeta sigue existiendo o elige otra en Ajustes."),
+    ("remediation.input.pedal_unavailable", "Vuelve a conectar el pedal y selecciónalo de nuevo en Ajustes."),
+];
+
+const DE: &[(&str, &str)] = &[
+    // Shortcuts
+    ("shortcut.toggle_overlay", "Overlay ein-/ausblenden"),
+    ("shortcut.trigger_assist", "KI-Assistenz auslösen"),
+    ("shortcut.toggle_session", "Sitzung starten/beenden"),
+    ("shortcut.clear_context", "Kontext leeren"),
+    ("shortcut.toggle_click_through", "Durchklicken des Overlays umschalten"),
+    ("shortcut.push_to_talk", "Zum Sprechen halten"),
+    ("shortcut.paste_response", "Vorgeschlagene Antwort einfügen"),
+    ("shortcut.type_response", "Vorgeschlagene Antwort tippen"),
+    ("action.start_session", "Eine Coaching-Sitzung starten"),
+    ("action.stop_session", "Die aktuelle Sitzung beenden"),
+    ("action.toggle_session", "Sitzung starten/beenden"),
+    ("action.flag_moment", "Diesen Moment der Sitzung markieren"),
+    ("action.trigger_assist", "KI-Assistenz auslösen"),
+    ("action.clear_context", "Kontext leeren"),
+    ("action.toggle_overlay", "Overlay ein-/ausblenden"),
+    ("action.show_overlay", "Overlay anzeigen"),
+    ("action.hide_overlay", "Overlay ausblenden"),
+    ("action.toggle_click_through", "Durchklicken des Overlays umschalten"),
+    ("action.paste_response", "Vorgeschlagene Antwort einfügen"),
+    ("action.type_response", "Vorgeschlagene Antwort tippen"),
+    ("action.open_dashboard", "Dashboard öffnen"),
+    // Notifications
+    ("notice.session_saved.title", "Sitzung gespeichert"),
+    ("notice.session_saved.body", "Dein Transkript und deine Aufnahmen sind im Dashboard."),
+    ("notice.model_ready.title", "Transkriptionsmodell bereit"),
+    ("notice.model_ready.body", "Whisper {name} ist heruntergeladen und bereit für die lokale Transkription."),
+    ("notice.quota.title", "KI-Kontingent fast aufgebraucht"),
+    ("notice.quota.body", "Dein KI-Anbieter ist nahe an seinem Limit, Assistenzen können langsamer werden oder fehlschlagen."),
+    ("notice.update_available.title", "Update verfügbar"),
+    ("notice.update_available.body", "Queen Mama {version} kann heruntergeladen werden."),
+    ("notice.up_to_date.title", "Alles aktuell"),
+    ("notice.up_to_date.body", "Queen Mama {version} ist die neueste Version."),
+    ("notice.update_failed.title", "Suche nach Updates fehlgeschlagen"),
+    ("notice.crash_report.title", "Queen Mama wurde unerwartet beendet"),
+    ("notice.crash_report.body", "Ein Absturzbericht wurde gespeichert. Du kannst ihn uns in den Einstellungen senden."),
+    ("notice.preview.title", "Vorschau der Benachrichtigung"),
+    ("notice.preview.body", "So klingen Benachrichtigungen von Queen Mama."),
+    ("action.open_session", "Sitzung öffnen"),
+    ("action.open_settings", "Einstellungen öffnen"),
+    // Tray
+    ("tray.show_overlay", "Overlay anzeigen"),
+    ("tray.hide_overlay", "Overlay ausblenden"),
+    ("tray.click_through", "Overlay durchklickbar"),
+    ("tray.start_session", "Sitzung starten"),
+    ("tray.pause_session", "Sitzung pausieren"),
+    ("tray.resume_session", "Sitzung fortsetzen"),
+    ("tray.stop_session", "Sitzung beenden"),
+    ("tray.not_recording", "Keine Aufnahme"),
+    ("tray.recording", "Aufnahme · {elapsed}"),
+    ("tray.paused", "Pausiert · {elapsed}"),
+    ("tray.saving", "Aufnahmen werden gespeichert…"),
+    ("tray.now", "Jetzt"),
+    ("tray.no_active_session", "Keine aktive Sitzung"),
+    ("tray.elapsed", "Dauer: {elapsed}"),
+    ("tray.talk_ratio", "Redeanteil: {ratio}"),
+    ("tray.open_goals", "Offene Ziele: {count}"),
+    ("tray.recent_sessions", "Letzte Sitzungen"),
+    ("tray.no_sessions", "Noch keine Sitzungen"),
+    ("tray.profile", "Profil"),
+    ("tray.manage_profiles", "Profile verwalten…"),
+    ("tray.open_dashboard", "Dashboard öffnen"),
+    ("tray.feedback", "Feedback geben"),
+    ("tray.check_updates", "Nach Updates suchen…"),
+    ("tray.quit", "Queen Mama beenden"),
+    ("overlay_menu.move_to", "Verschieben nach"),
+    ("overlay_menu.top_left", "Oben links"),
+    ("overlay_menu.top_center", "Oben mittig"),
+    ("overlay_menu.top_right", "Oben rechts"),
+    ("overlay_menu.bottom_left", "Unten links"),
+    ("overlay_menu.bottom_center", "Unten mittig"),
+    ("overlay_menu.bottom_right", "Unten rechts"),
+    ("overlay_menu.opacity", "Deckkraft"),
+    ("overlay_menu.click_through", "Durchklickbar"),
+    ("overlay_menu.end_session", "Sitzung beenden"),
+    // Defaults
+    ("profile.default_name", "Standard"),
+    ("import.shared_text", "Geteilter Text"),
+    ("session.split_title", "{title} (Teil 2)"),
+    ("session.default_title", "Coaching-Sitzung"),
+    ("auth.browser_done", "Du bist bei Queen Mama angemeldet. Du kannst diesen Tab schließen."),
+    ("feature.disabled_by_policy", "Von deiner Organisation deaktiviert"),
+    ("feature.desktop_only", "Nur in der Desktop-App verfügbar"),
+    ("feature.permission.screen_recording", "Erlaube Bildschirmaufnahme für Queen Mama unter Systemeinstellungen > Datenschutz & Sicherheit"),
+    ("feature.permission.accessibility", "Erlaube Bedienungshilfen-Zugriff für Queen Mama unter Systemeinstellungen > Datenschutz & Sicherheit"),
+    ("feature.permission.notifications", "Erlaube Mitteilungen für Queen Mama in den Systemeinstellungen"),
+    ("migrations.no_database", "{profile}: noch keine Datenbank"),
+    ("migrations.locked", "{profile}: durch eine Passphrase gesperrt, übersprungen"),
+    ("migrations.unreadable", "{profile}: kann nicht gelesen werden: {error}"),
+    ("migrations.up_to_date", "{profile}: aktuell mit Version {version}"),
+    ("migrations.pending", "{profile}: Version {version}, {count} Migrationen ausstehend"),
+    ("migrations.newer", "{profile}: Version {version}, neuer als {latest} dieser App; aktualisiere die App, bevor du sie öffnest"),
+    // Errors
+    ("error.unsupported_locale", "Nicht unterstützte Sprache: {locale}"),
+    ("error.overlay_missing", "Overlay-Fenster nicht gefunden"),
+    ("error.main_window_missing", "Hauptfenster nicht gefunden"),
+    ("error.window_not_found", "Fenster nicht gefunden: {id}"),
+    ("error.monitor_not_found", "Bildschirm nicht gefunden: {id}"),
+    ("error.no_monitor", "Kein Bildschirm gefunden"),
+    ("error.no_primary_monitor", "Kein Hauptbildschirm"),
+    ("error.region_outside_monitor", "Der Bereich liegt außerhalb des Bildschirms"),
+    ("error.unknown_action", "Unbekannte Aktion: {action}"),
+    ("error.ai_request_failed", "KI-Anfrage fehlgeschlagen ({status}): {body}"),
+    ("error.ai_no_api_key", "Kein API-Schlüssel für den gewählten KI-Anbieter"),
+    ("error.audio_not_captured", "Diese Audioquelle wird nicht aufgenommen"),
+    ("error.audio_running", "Die Audioaufnahme läuft bereits"),
+    ("error.audio_thread_exited", "Der Audioaufnahme-Thread wurde beendet"),
+    ("error.audio_device_not_found", "Audiogerät nicht gefunden: {name}"),
+    ("error.audio_fallback_rate", "Das Standardgerät kann nicht mit {rate} Hz aufnehmen"),
+    ("error.no_microphone", "Kein Mikrofon verfügbar"),
+    ("error.no_output_device", "Kein Ausgabegerät für die Loopback-Aufnahme verfügbar"),
+    ("error.no_loopback_device", "Kein Loopback-Gerät gefunden; installiere ein virtuelles Audiogerät wie BlackHole, um den Systemton aufzunehmen"),
+    ("error.no_display", "Kein Bildschirm gefunden, über den der Systemton aufgenommen werden kann"),
+    ("error.system_audio_timeout", "Die Aufnahme des Systemtons wurde nicht rechtzeitig gestartet"),
+    ("error.unsupported_sample_format", "Nicht unterstütztes Sample-Format: {format}"),
+    ("error.session_not_found", "Sitzung nicht gefunden: {id}"),
+    ("error.session_not_in_trash", "Sitzung nicht im Papierkorb: {id}"),
+    ("error.merge_needs_sessions", "Wähle mindestens zwei Sitzungen zum Zusammenführen"),
+    ("error.session_recording", "Beende die Sitzung, bevor du sie bearbeitest"),
+    ("error.split_out_of_range", "Der Trennpunkt muss innerhalb der Sitzung liegen"),
+    ("error.audio_file_unsupported", "Audio in {path} kann nicht gelesen werden; versuche M4A, MP3, WAV, FLAC oder OGG"),
+    ("error.file_transcription_running", "Diese Datei wird bereits transkribiert"),
+    ("error.diarization_model", "Sprecher zu unterscheiden braucht ein Sprecherwechsel-Modell wie small.en-tdrz, nicht {name}"),
+    ("error.redaction_range_invalid", "Wähle mindestens einen zu schwärzenden Abschnitt, der jeweils nach seinem Beginn endet"),
+    ("error.clip_range_invalid", "Ein Ausschnitt muss nach seinem Beginn enden und darf höchstens {max} Minuten dauern"),
+    ("clip.heading", "Ausschnitt von {start} bis {end}"),
+    ("error.highlights_nothing", "Sitzung {id} hat keine Markierungen, Review-Kommentare oder Compliance-Treffer für Highlights"),
+    ("highlights.heading", "Highlights von {title}"),
+    ("highlights.intro", "Moment {number}, bei {time}."),
+    ("document.started", "Begonnen am {time}"),
+    ("document.suggestion", "Vorschlag"),
+    ("document.note", "Notiz"),
+    ("compliance.heading", "Compliance-Übersicht: {title}"),
+    ("compliance.counts", "{high} hoch, {medium} mittel, {low} niedrig"),
+    ("compliance.no_hits", "Keine verbotenen Formulierungen gefunden."),
+    ("compliance.column_time", "Zeit"),
+    ("compliance.column_severity", "Schweregrad"),
+    ("compliance.column_category", "Kategorie"),
+    ("compliance.column_phrase", "Formulierung"),
+    ("compliance.column_speaker", "Sprecher"),
+    ("compliance.column_said", "Gesagt"),
+    ("compliance.severity_low", "Niedrig"),
+    ("compliance.severity_medium", "Mittel"),
+    ("compliance.severity_high", "Hoch"),
+    ("error.no_system_voice", "Keine Systemstimme auf dieser Plattform"),
+    ("error.speech_format", "Unerwartetes Sprachformat: {bits} Bit"),
+    ("export.transcript", "Transkript"),
+    ("export.coaching_notes", "Coaching-Notizen"),
+    ("export.tags", "Tags: {tags}"),
+    ("export.note_heading", "{kind} · {time} (bei {offset})"),
+    ("error.tag_invalid", "Tags brauchen 1 bis {max} Zeichen"),
+    ("error.field_name_invalid", "Feldnamen brauchen 1 bis {max} Zeichen"),
+    ("error.field_exists", "Ein Feld namens {name} existiert bereits"),
+    ("error.field_not_found", "Feld nicht gefunden: {name}"),
+    ("error.field_value_invalid", "Dieser Wert passt nicht zum Typ des Felds {name}"),
+    ("error.filter_name_invalid", "Filternamen brauchen 1 bis {max} Zeichen"),
+    ("error.filter_not_found", "Gespeicherter Filter nicht gefunden: {id}"),
+    ("error.unsupported_url", "Nicht unterstützte URL: {url}"),
+    ("error.media_download_failed", "yt-dlp konnte {url} nicht herunterladen"),
+    ("error.yt_dlp_missing", "yt-dlp ist nicht installiert; installiere es, um von Video- und Podcast-Seiten zu importieren"),
+    ("error.unsupported_file_type", "Nicht unterstützter Dateityp: {path}"),
+    ("error.midi_device_not_found", "MIDI-Gerät nicht gefunden: {name}"),
+    ("error.no_journal_entries", "Keine Journaleinträge am {date}"),
+    ("error.invalid_date", "Ungültiges Datum {date}: {error}"),
+    ("error.not_a_file", "Keine Datei: {path}"),
+    ("error.not_a_directory", "Kein Ordner: {path}"),
+    ("error.note_not_found", "Notiz nicht gefunden: {id}"),
+    ("error.text_recognition_failed", "Texterkennung fehlgeschlagen: {error}"),
+    ("error.unknown_overlay_position", "Unbekannte Overlay-Position: {position}"),
+    ("error.profile_name_empty", "Der Profilname darf nicht leer sein"),
+    ("error.profile_exists", "Ein Profil namens {name} existiert bereits"),
+    ("error.summary_language_invalid", "Die Zusammenfassungssprache muss ein Sprachname sein, etwa Englisch"),
+    ("error.summary_nothing", "Sitzung {id} hat weder Transkript noch Notizen zum Zusammenfassen"),
+    ("error.summary_not_found", "Sitzung {id} hat keine Zusammenfassungsversion {version}"),
+    ("error.default_profile_delete", "Das Standardprofil kann nicht gelöscht werden"),
+    ("error.profile_active", "Wechsle zu einem anderen Profil, bevor du dieses löschst"),
+    ("error.profile_not_found", "Profil nicht gefunden: {id}"),
+    ("error.profile_switch_in_session", "Beende die laufende Sitzung, bevor du das Profil wechselst"),
+    ("error.profile_key_missing", "Der Verschlüsselungsschlüssel dieses Profils fehlt im Schlüsselbund"),
+    ("error.database_locked", "Der Sitzungsverlauf ist gesperrt; gib deine Passphrase ein, um ihn zu entsperren"),
+    ("error.wrong_passphrase", "Falsche Passphrase"),
+    ("error.encrypted_file_damaged", "Eine verschlüsselte Datei ist beschädigt oder wurde verändert"),
+    ("error.encrypted_file_other_key", "Diese Datei wurde mit der Passphrase eines anderen Profils verschlüsselt"),
+    ("error.file_not_encrypted", "Diese Datei ist nicht verschlüsselt"),
+    ("error.passphrase_required", "Dieses Profil ist verschlüsselt; gib seine Passphrase ein, um zu ihm zu wechseln"),
+    ("error.passphrase_too_short", "Die Passphrase braucht mindestens {min} Zeichen"),
+    ("error.encryption_enabled", "Der Verlauf dieses Profils ist bereits mit einer Passphrase verschlüsselt"),
+    ("error.encryption_disabled", "Der Verlauf dieses Profils ist nicht mit einer Passphrase verschlüsselt"),
+    ("error.encryption_in_session", "Beende zuerst die laufende Sitzung"),
+    ("error.profiles_admin_only", "Nur Admin-Profile können Profile verwalten"),
+    ("error.admin_profile_unprotected", "{name} ist ein Admin-Profil ohne Passphrase, daher kann nur ein Admin zu ihm wechseln"),
+    ("error.admin_passphrase_first", "Lege zuerst eine Passphrase für ein Admin-Profil fest, damit du zu ihm zurückkehren kannst"),
+    ("error.invalid_pairing_token", "Ungültiges Kopplungstoken"),
+    ("error.comment_not_found", "Kommentar nicht gefunden: {id}"),
+    ("error.comment_needs_position", "Kommentare brauchen eine Position auf der Zeitleiste"),
+    ("error.not_review_file", "Keine Review-Datei"),
+    ("error.review_file_invalid", "Keine Review-Datei: {error}"),
+    ("error.review_file_too_new", "Review-Datei-Version {version} erfordert eine neuere Version der App"),
+    ("error.secret_not_readable", "Das Geheimnis {name} kann nicht aus der Webview gelesen werden"),
+    ("error.session_in_progress", "Es läuft bereits eine Sitzung"),
+    ("error.session_cannot_pause", "Die Sitzung kann jetzt nicht pausiert werden"),
+    ("error.session_cannot_resume", "Die Sitzung kann jetzt nicht fortgesetzt werden"),
+    ("error.no_session", "Keine laufende Sitzung"),
+    ("error.no_shortcut_pressed", "Es wurde kein Tastenkürzel gedrückt"),
+    ("error.shortcut_capture_cancelled", "Tastenkürzel-Aufnahme abgebrochen"),
+    ("error.keyboard_unavailable", "Die Tastatur kann nicht abgehört werden: {error}"),
+    ("error.key_not_allowed", "{key} kann nicht in einem Tastenkürzel verwendet werden"),
+    ("error.shortcut_needs_modifier", "Tastenkürzel brauchen mindestens eine Sondertaste"),
+    ("error.unknown_shortcut", "Unbekanntes Tastenkürzel: {id}"),
+    ("error.invalid_shortcut", "Ungültiges Tastenkürzel {shortcut}: {error}"),
+    ("error.shortcut_in_use", "{shortcut} wird bereits von {action} verwendet"),
+    ("error.shortcut_taken", "Bereits von {action} verwendet"),
+    ("error.shortcut_reserved", "Vom Betriebssystem reserviert"),
+    ("error.custom_model_needs_name", "Eigene Modelle brauchen einen Namen"),
+    ("error.whisper_model_exists", "Ein Whisper-Modell namens {name} existiert bereits"),
+    ("error.transcription_running", "Die lokale Transkription läuft bereits"),
+    ("error.transcription_not_running", "Die lokale Transkription läuft nicht"),
+    ("error.custom_model_missing", "Das eigene Whisper-Modell {name} fehlt in {path}"),
+    ("error.whisper_model_missing", "Das Whisper-Modell {name} ist nicht heruntergeladen"),
+    ("error.whisper_model_load", "Das Whisper-Modell {name} kann nicht geladen werden: {error}"),
+    ("error.unknown_whisper_model", "Unbekanntes Whisper-Modell: {name}"),
+    ("error.not_ggml_model", "{path} ist kein ggml-Modell für whisper.cpp. Füge einen LoRA-Adapter über seinen Ordner oder seine safetensors-Datei hinzu, oder konvertiere vollständige Gewichte zuerst mit convert-h5-to-ggml.py aus whisper.cpp."),
+    ("error.ggml_model_load", "whisper.cpp konnte {path} nicht laden: {error}"),
+    ("error.lora_needs_base", "Wähle das Standard-Whisper-Modell, auf dem dieser LoRA-Adapter trainiert wurde"),
+    ("error.lora_config_missing", "Die Konfiguration {path} des LoRA-Adapters fehlt"),
+    ("error.lora_invalid", "Kein verwendbarer LoRA-Adapter: {error}"),
+    ("error.lora_wrong_base", "Der LoRA-Adapter passt nicht zu diesem Basismodell ({tensor}); wähle das Modell, auf dem er trainiert wurde"),
+    ("error.lora_quantized_base", "LoRA-Adapter können nur mit nicht quantisierten Modellen zusammengeführt werden"),
+    ("error.update_nothing_to_download", "Kein Update zum Herunterladen; suche zuerst nach Updates"),
+    ("error.update_nothing_to_install", "Kein Update zum Installieren"),
+    ("error.update_not_downloaded", "Das Update wurde noch nicht heruntergeladen"),
+    ("error.update_in_session", "Beende die laufende Sitzung, bevor du das Update installierst"),
+    ("error.updates_disabled", "Updates sind in diesem Build nicht aktiviert"),
+    ("error.crash_report_not_found", "Absturzbericht nicht gefunden: {id}"),
+    ("error.file_not_found", "Datei nicht gefunden: {path}"),
+    ("error.crash_report_upload_failed", "Absturzbericht konnte nicht gesendet werden ({status})"),
+    ("error.benchmark_consent_required", "Aktiviere zuerst den Vergleich mit anderen Nutzern in den Einstellungen"),
+    ("error.benchmark_not_enough_speech", "Nimm in den letzten 30 Tagen mindestens {minutes} Minuten deiner eigenen Sprache auf, um zu vergleichen"),
+    ("error.benchmark_request_failed", "Der Vergleich konnte nicht abgerufen werden ({status})"),
+    ("error.sound_not_wav", "Benachrichtigungstöne müssen WAV-Dateien sein: {path}"),
+    ("error.sound_unreadable", "Ton {path} kann nicht gelesen werden: {error}"),
+    ("error.sound_too_long", "Benachrichtigungstöne dürfen höchstens {seconds} Sekunden lang sein"),
+    ("error.no_sound_output", "Kein Audioausgabegerät verfügbar"),
+    ("error.login_timed_out", "Zeitüberschreitung bei der Anmeldung"),
+    ("error.login_cancelled", "Die Anmeldung wurde abgebrochen"),
+    ("error.login_failed", "Anmeldung fehlgeschlagen: {error}"),
+    ("error.login_state_mismatch", "Die Anmeldeantwort passt nicht zu dieser Anfrage"),
+    ("error.login_no_code", "Die Anmeldeantwort enthält keinen Autorisierungscode"),
+    ("error.token_request_failed", "Token-Anfrage fehlgeschlagen ({status})"),
+    ("error.not_signed_in", "Nicht angemeldet"),
+    ("error.tesseract_missing", "Zum Lesen von Bildschirmtext muss tesseract installiert und im PATH sein"),
+    ("error.tesseract_unavailable", "tesseract ist nicht installiert oder nicht im PATH: {error}"),
+    ("error.tesseract_failed", "tesseract ist fehlgeschlagen: {error}"),
+    ("error.realtime_not_connected", "Nicht mit dem Coaching-Dienst verbunden"),
+    ("error.realtime_heartbeat_timeout", "Der Coaching-Dienst antwortet nicht mehr"),
+    ("error.realtime_closed", "Der Coaching-Dienst hat die Verbindung getrennt"),
+    ("error.calendar_access_denied", "Der Kalenderzugriff ist deaktiviert. Erlaube ihn für Queen Mama unter Systemeinstellungen > Datenschutz & Sicherheit > Kalender"),
+    ("error.mock_failure", "Simulierter Fehler des Test-Anbieters"),
+    ("error.mock_timeout", "Zeitüberschreitung beim Test-Anbieter (simuliert)"),
+    ("error.mock_disconnected", "Der Test-Anbieter hat die Verbindung mitten in der Antwort getrennt (simuliert)"),
+    ("error.chaos_mode_off", "Der Chaosmodus ist aus. Aktiviere ihn in den Entwicklereinstellungen, um Fehler einzuschleusen"),
+    ("error.chaos_injected", "Vom Chaosmodus eingeschleust"),
+    ("error.chaos_device_lost", "Audiogerät getrennt (vom Chaosmodus eingeschleust)"),
+    ("error.paste_into_self", "Wechsle zuerst zur App, in die eingefügt werden soll"),
+    ("error.paste_needs_accessibility", "Erlaube Queen Mama LITE unter Bedienungshilfen in den Systemeinstellungen, um in andere Apps einzufügen"),
+    ("error.paste_failed", "Einfügen in die aktive App fehlgeschlagen"),
+    ("error.type_into_self", "Wechsle zuerst zur App, in die getippt werden soll"),
+    ("error.typing_needs_accessibility", "Erlaube Queen Mama LITE unter Bedienungshilfen in den Systemeinstellungen, um in andere Apps zu tippen"),
+    ("error.typing_in_progress", "Es wird bereits eine Antwort getippt"),
+    ("error.typing_failed", "Tippen in die aktive App fehlgeschlagen"),
+    ("error.event_version_unsupported", "Keine gemeinsame Ereignisversion: angefragt {requested}, unterstützt {supported}"),
+    ("permission.title", "Zugriff erlauben?"),
+    ("permission.prompt", "{client} ({source}) möchte: {action}. Deine Antwort wird für diesen Client gespeichert; du kannst sie in den Einstellungen ändern."),
+    ("permission.allow", "Erlauben"),
+    ("permission.deny", "Nicht erlauben"),
+    ("permission.source_relay", "über das Begleit-Relay gekoppelt"),
+    ("permission.source_link", "über einen Link"),
+    ("permission.anonymous_link", "Eine App oder Website"),
+    ("error.settings_invalid", "Ungültige Einstellungen: {error}"),
+    ("error.settings_invalid_size", "Das Overlay darf nicht kleiner als {width}×{height} sein"),
+    ("error.settings_model_missing", "Wählen Sie ein KI-Modell"),
+    ("error.settings_invalid_url", "Keine http(s)-Adresse: {url}"),
+    // Remediations
+    ("remediation.panic", "Starte Queen Mama neu. Ein Absturzbericht wurde gespeichert, den du uns in den Einstellungen senden kannst."),
+    ("remediation.audio.stream_failed", "Prüfe, ob das Audiogerät noch verbunden ist, und starte die Sitzung neu."),
+    ("remediation.transcription.inference_failed", "Wähle in den Einstellungen ein kleineres Whisper-Modell."),
+    ("remediation.secrets.keychain_unavailable", "Entsperre den Systemschlüsselbund und starte Queen Mama neu."),
+    ("remediation.shortcuts.register_failed", "Eine andere App nutzt dieses Tastenkürzel möglicherweise. Wähle in den Einstellungen ein anderes."),
+    ("remediation.watch_folder.unavailable", "Prüfe, ob der Ordner noch existiert, oder wähle in den Einstellungen einen anderen."),
+    ("remediation.input.pedal_unavailable", "Schließe das Pedal erneut an und wähle es in den Einstellungen wieder aus."),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::Locale;
+
+    #[test]
+    fn no_duplicate_keys() {
+        for locale in [Locale::En, Locale::Fr, Locale::Es, Locale::De] {
+            let mut seen = HashSet::new();
+            for (key, _) in locale.strings() {
+                assert!(seen.insert(key), "{} is listed twice", key);
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// Queen Mama LITE - Session Import Pipeline
+// Single entry point for files dropped on the dashboard, opened with the app,
+// or shared from other apps on mobile, and for study material fetched from media URLs
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager, WindowEvent};
+
+use crate::events::VersionedEmitter;
+#[cfg(desktop)]
+use crate::file_transcription::{self, TranscribeOptions};
+use crate::i18n::t;
+#[cfg(desktop)]
+use crate::knowledge::{self, ReferenceDocument};
+use crate::vault;
+
+const TRANSCRIPT_EXTENSIONS: &[&str] = &["txt", "md", "srt", "vtt", "json"];
+const AUDIO_EXTENSIONS: &[&str] = &["m4a", "mp3", "wav", "ogg", "opus", "webm", "aac", "flac"];
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportKind {
+    Transcript,
+    Audio,
+}
+
+/// An imported file copied into the app data dir, waiting for the frontend to turn it into a session
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingImport {
+    id: String,
+    kind: ImportKind,
+    file_name: String,
+    path: PathBuf,
+    /// File contents for transcripts, so the frontend doesn't need fs access
+    text: Option<String>,
+}
+
+/// Route files dropped on the dashboard window into the import pipeline
+pub fn handle_window_event(window: &tauri::Window, event: &WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+
+    if let WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) = event {
+        for path in paths {
+            report(window.app_handle(), import(window.app_handle(), path));
+        }
+    }
+}
+
+/// Route files opened with the app (macOS "Open With", iOS/Android share sheet) into the import pipeline
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "android"))]
+pub fn handle_opened_urls(app: &AppHandle, urls: &[tauri::Url]) {
+    for url in urls.iter().filter(|url| url.scheme() == "file") {
+        match url.to_file_path() {
+            Ok(path) => report(app, import(app, &path)),
+            Err(_) => tracing::warn!(url = ?url.as_str(), "Invalid file URL"),
+        }
+    }
+}
+
+/// Import an audio or transcript file as a session
+#[tauri::command]
+pub fn import_file(app: AppHandle, path: PathBuf) -> Result<PendingImport, String> {
+    import(&app, &path)
+}
+
+/// Import plain text shared from another app as a transcript
+#[tauri::command]
+pub fn import_shared_text(
+    app: AppHandle,
+    text: String,
+    title: Option<String>,
+) -> Result<PendingImport, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let path = imports_dir(&app)?.join(format!("{}.txt", id));
+    vault::write(&app, &path, &text)?;
+
+    let pending = PendingImport {
+        id,
+        kind: ImportKind::Transcript,
+        file_name: title.unwrap_or_else(|| t!("import.shared_text")),
+        path,
+        text: Some(text),
+    };
+    app.emit("session_import", &pending)
+        .map_err(|e| e.to_string())?;
+
+    Ok(pending)
+}
+
+/// Whether the file has an extension the pipeline can import
+pub fn is_supported(path: &Path) -> bool {
+    classify(&extension(path)).is_some()
+}
+
+/// Download audio from a media URL, transcribe it and file it in the knowledge base as study
+/// material: direct links to audio files are fetched as-is, anything else (YouTube, podcast
+/// pages) goes through yt-dlp
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn import_media_url(app: AppHandle, url: String) -> Result<ReferenceDocument, String> {
+    let parsed = tauri::Url::parse(&url).map_err(|e| e.to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(t!("error.unsupported_url", url = url));
+    }
+
+    let download_dir = std::env::temp_dir().join("queen-mama-downloads");
+    fs::create_dir_all(&download_dir).map_err(|e| e.to_string())?;
+    let id = uuid::Uuid::new_v4().to_string();
+
+    let direct_extension = Path::new(parsed.path())
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .filter(|ext| AUDIO_EXTENSIONS.contains(&ext.as_str()));
+
+    let (downloaded, title) = match direct_extension {
+        Some(extension) => {
+            let title = parsed
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|name| !name.is_empty())
+                .map(str::to_string);
+            let bytes = tauri_plugin_http::reqwest::get(parsed)
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| e.to_string())?
+                .bytes()
+                .await
+                .map_err(|e| e.to_string())?;
+            let path = download_dir.join(format!("{}.{}", id, extension));
+            fs::write(&path, &bytes).map_err(|e| e.to_string())?;
+            (path, title)
+        }
+        None => download_with_yt_dlp(&app, &url, &download_dir, &id).await?,
+    };
+
+    let options = TranscribeOptions::titled(title.unwrap_or_else(|| url.clone()));
+    let transcribed = {
+        let app = app.clone();
+        let downloaded = downloaded.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            file_transcription::run(&app, &downloaded, options)
+        })
+        .await
+        .map_err(|e| e.to_string())
+    };
+    let _ = fs::remove_file(&downloaded);
+    let session_id = transcribed??;
+    knowledge::add(&app, &session_id, &url)
+}
+
+/// Download the audio track with yt-dlp, returning the file and the media's title
+#[cfg(desktop)]
+async fn download_with_yt_dlp(
+    app: &AppHandle,
+    url: &str,
+    download_dir: &Path,
+    id: &str,
+) -> Result<(PathBuf, Option<String>), String> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let mut child = tokio::process::Command::new("yt-dlp")
+        .args(["--extract-audio", "--audio-format", "m4a", "--newline", "--no-playlist"])
+        .args(["--print", "after_move:title", "--progress"])
+        .arg("--output")
+        .arg(download_dir.join(format!("{}.%(ext)s", id)))
+        .arg(url)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => t!("error.yt_dlp_missing"),
+            _ => e.to_string(),
+        })?;
+
+    // Progress lines look like "[download]  42.3% of 12.34MiB at ..."; the title is printed last
+    let mut title = None;
+    if let Some(stdout) = child.stdout.take() {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Some(progress) = line.strip_prefix("[download]") else {
+                if !line.starts_with('[') && !line.trim().is_empty() {
+                    title = Some(line.trim().to_string());
+                }
+                continue;
+            };
+            let percent = progress
+                .trim_start()
+                .split('%')
+                .next()
+                .and_then(|value| value.parse::<f32>().ok());
+            if let Some(percent) = percent {
+                let _ = app.emit(
+                    "media_import_progress",
+                    serde_json::json!({ "url": url, "percent": percent }),
+                );
+            }
+        }
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(t!("error.media_download_failed", url = url));
+    }
+
+    Ok((download_dir.join(format!("{}.m4a", id)), title))
+}
+
+pub fn import(app: &AppHandle, source: &Path) -> Result<PendingImport, String> {
+    let extension = extension(source);
+    let kind = classify(&extension)
+        .ok_or_else(|| t!("error.unsupported_file_type", path = source.display()))?;
+
+    let text = if kind == ImportKind::Transcript {
+        Some(fs::read_to_string(source).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+
+    // Copy into our own dir, sealed like recordings: shared files often live in temporary inboxes
+    let id = uuid::Uuid::new_v4().to_string();
+    let path = imports_dir(app)?.join(format!("{}.{}", id, extension));
+    vault::copy(app, source, &path)?;
+
+    let pending = PendingImport {
+        id,
+        kind,
+        file_name: source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path,
+        text,
+    };
+    app.emit("session_import", &pending)
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!(path = ?source, "Imported a file");
+    Ok(pending)
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default()
+}
+
+fn classify(extension: &str) -> Option<ImportKind> {
+    if TRANSCRIPT_EXTENSIONS.contains(&extension) {
+        Some(ImportKind::Transcript)
+    } else if AUDIO_EXTENSIONS.contains(&extension) {
+        Some(ImportKind::Audio)
+    } else {
+        None
+    }
+}
+
+fn imports_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::profiles::active_data_dir(app)?.join("imports");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn report(app: &AppHandle, result: Result<PendingImport, String>) {
+    if let Err(e) = result {
+        tracing::warn!("{}", e);
+        let _ = app.emit("session_import_failed", e);
+    }
+}
@@ -0,0 +1,305 @@
+// Queen Mama LITE - External Input Devices
+// Maps foot pedals (MIDI and HID) onto the same actions as the global shortcuts
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use midir::{MidiInput, MidiInputConnection};
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::shortcuts::{self, dispatch_action};
+use crate::SETTINGS_STORE;
+
+pub(crate) const PEDAL_STORE_KEY: &str = "pedal";
+const HID_READ_TIMEOUT_MS: i32 = 100;
+
+/// Pedal configuration persisted in the settings store
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PedalConfig {
+    pub device: PedalDevice,
+    pub bindings: Vec<PedalBinding>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PedalDevice {
+    Midi { port_name: String },
+    Hid { vendor_id: u16, product_id: u16 },
+}
+
+/// Maps a pedal control to a shortcut action id.
+/// MIDI controls are note or CC numbers, HID controls are bit indexes in the input report.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PedalBinding {
+    pub control: u16,
+    pub action: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PedalDeviceInfo {
+    name: String,
+    device: PedalDevice,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PedalEvent {
+    action: String,
+    pressed: bool,
+}
+
+enum PedalConnection {
+    Midi(MidiInputConnection<()>),
+    Hid(Arc<AtomicBool>),
+}
+
+impl PedalConnection {
+    fn close(self) {
+        match self {
+            PedalConnection::Midi(connection) => {
+                connection.close();
+            }
+            PedalConnection::Hid(running) => running.store(false, Ordering::SeqCst),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PedalState {
+    connection: Mutex<Option<PedalConnection>>,
+}
+
+pub fn setup_input(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(PedalState::default());
+
+    let app_handle = app.app_handle().clone();
+    if let Some(config) = load_config(&app_handle) {
+        let state = app.state::<PedalState>();
+        match connect(&app_handle, &config) {
+            Ok(connection) => {
+                *state.connection.lock().map_err(|e| e.to_string())? = Some(connection);
+                tracing::info!("Pedal connected");
+            }
+            Err(e) => tracing::error!(
+                code = "input.pedal_unavailable",
+                "Failed to connect saved pedal: {}",
+                e
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// List MIDI ports and HID devices that can be used as pedals
+#[tauri::command]
+pub fn list_pedal_devices() -> Result<Vec<PedalDeviceInfo>, String> {
+    let mut devices = Vec::new();
+
+    let midi = MidiInput::new("Queen Mama").map_err(|e| e.to_string())?;
+    for port in midi.ports() {
+        if let Ok(name) = midi.port_name(&port) {
+            devices.push(PedalDeviceInfo {
+                name: name.clone(),
+                device: PedalDevice::Midi { port_name: name },
+            });
+        }
+    }
+
+    let hid = hidapi::HidApi::new().map_err(|e| e.to_string())?;
+    for info in hid.device_list() {
+        let name = info
+            .product_string()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{:04x}:{:04x}", info.vendor_id(), info.product_id()));
+        devices.push(PedalDeviceInfo {
+            name,
+            device: PedalDevice::Hid {
+                vendor_id: info.vendor_id(),
+                product_id: info.product_id(),
+            },
+        });
+    }
+
+    Ok(devices)
+}
+
+/// Get the saved pedal configuration
+#[tauri::command]
+pub fn get_pedal_config(app: AppHandle) -> Option<PedalConfig> {
+    load_config(&app)
+}
+
+/// Connect a pedal and persist its configuration
+#[tauri::command]
+pub fn connect_pedal(
+    app: AppHandle,
+    state: State<'_, PedalState>,
+    config: PedalConfig,
+) -> Result<(), String> {
+    let mut current = state.connection.lock().map_err(|e| e.to_string())?;
+    if let Some(connection) = current.take() {
+        connection.close();
+    }
+
+    *current = Some(connect(&app, &config)?);
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        PEDAL_STORE_KEY,
+        serde_json::to_value(&config).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Disconnect the pedal and forget its configuration
+#[tauri::command]
+pub fn disconnect_pedal(app: AppHandle, state: State<'_, PedalState>) -> Result<(), String> {
+    if let Some(connection) = state.connection.lock().map_err(|e| e.to_string())?.take() {
+        connection.close();
+    }
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.delete(PEDAL_STORE_KEY);
+    store.save().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Reconnect using the saved configuration
+pub fn reload(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<PedalState>();
+    let mut current = state.connection.lock().map_err(|e| e.to_string())?;
+    if let Some(connection) = current.take() {
+        connection.close();
+    }
+
+    if let Some(config) = load_config(app) {
+        *current = Some(connect(app, &config)?);
+    }
+    Ok(())
+}
+
+fn load_config(app: &AppHandle) -> Option<PedalConfig> {
+    let store = app.store(SETTINGS_STORE).ok()?;
+    serde_json::from_value(store.get(PEDAL_STORE_KEY)?).ok()
+}
+
+fn connect(app: &AppHandle, config: &PedalConfig) -> Result<PedalConnection, String> {
+    match &config.device {
+        PedalDevice::Midi { port_name } => connect_midi(app, port_name, config.bindings.clone()),
+        PedalDevice::Hid {
+            vendor_id,
+            product_id,
+        } => connect_hid(app, *vendor_id, *product_id, config.bindings.clone()),
+    }
+}
+
+fn connect_midi(
+    app: &AppHandle,
+    port_name: &str,
+    bindings: Vec<PedalBinding>,
+) -> Result<PedalConnection, String> {
+    let midi = MidiInput::new("Queen Mama").map_err(|e| e.to_string())?;
+    let port = midi
+        .ports()
+        .into_iter()
+        .find(|port| midi.port_name(port).ok().as_deref() == Some(port_name))
+        .ok_or_else(|| t!("error.midi_device_not_found", name = port_name))?;
+
+    let app_handle = app.clone();
+    let connection = midi
+        .connect(
+            &port,
+            "queen-mama-pedal",
+            move |_stamp, message, _| {
+                // Note on/off and control change (sustain-style pedals send CC 127/0)
+                if let &[status, control, value, ..] = message {
+                    let pressed = match status & 0xF0 {
+                        0x90 => value > 0,
+                        0x80 => false,
+                        0xB0 => value >= 64,
+                        _ => return,
+                    };
+                    handle_control(&app_handle, &bindings, control as u16, pressed);
+                }
+            },
+            (),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(PedalConnection::Midi(connection))
+}
+
+fn connect_hid(
+    app: &AppHandle,
+    vendor_id: u16,
+    product_id: u16,
+    bindings: Vec<PedalBinding>,
+) -> Result<PedalConnection, String> {
+    let hid = hidapi::HidApi::new().map_err(|e| e.to_string())?;
+    let device = hid.open(vendor_id, product_id).map_err(|e| e.to_string())?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let thread_running = running.clone();
+    let app_handle = app.clone();
+
+    thread::spawn(move || {
+        let mut previous = [0u8; 64];
+        let mut report = [0u8; 64];
+
+        while thread_running.load(Ordering::SeqCst) {
+            let len = match device.read_timeout(&mut report, HID_READ_TIMEOUT_MS) {
+                Ok(0) => continue,
+                Ok(len) => len,
+                Err(e) => {
+                    tracing::error!("HID pedal read failed: {}", e);
+                    break;
+                }
+            };
+
+            // Each button is a bit in the report; fire on every bit that changed
+            for (byte, (new, old)) in report[..len].iter().zip(previous.iter()).enumerate() {
+                let changed = new ^ old;
+                for bit in 0..8 {
+                    if changed & (1 << bit) != 0 {
+                        let control = (byte * 8 + bit) as u16;
+                        handle_control(&app_handle, &bindings, control, new & (1 << bit) != 0);
+                    }
+                }
+            }
+            previous[..len].copy_from_slice(&report[..len]);
+        }
+    });
+
+    Ok(PedalConnection::Hid(running))
+}
+
+fn handle_control(app: &AppHandle, bindings: &[PedalBinding], control: u16, pressed: bool) {
+    let Some(binding) = bindings.iter().find(|b| b.control == control) else {
+        return;
+    };
+
+    let _ = app.emit(
+        "pedal",
+        PedalEvent {
+            action: binding.action.clone(),
+            pressed,
+        },
+    );
+
+    if shortcuts::is_hold_action(&binding.action) {
+        shortcuts::dispatch_hold_action(app, &binding.action, pressed);
+    } else if pressed {
+        dispatch_action(app, &binding.action);
+    }
+}
@@ -0,0 +1,89 @@
+// Queen Mama LITE - Single Instance
+// Keeps one copy of the app running; later launches hand their arguments to it and exit
+
+use tauri::{App, AppHandle, Manager, Url};
+use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_store::StoreExt;
+
+use crate::actions;
+use crate::deep_link;
+use crate::session;
+use crate::settings::AUTOSTART_STORE_KEY;
+use crate::window;
+use crate::SETTINGS_STORE;
+
+/// Passed by autostart so the app starts in the tray
+pub const HIDDEN_ARG: &str = "--hidden";
+
+/// Hide the dashboard when launched at login
+pub fn setup_instance(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::args().any(|arg| arg == HIDDEN_ARG) {
+        if let Some(main) = app.get_webview_window("main") {
+            main.hide()?;
+        }
+        tracing::info!("Started hidden");
+    }
+    Ok(())
+}
+
+/// Runs in the first instance when the app is launched again; the new process exits afterwards
+pub fn handle_second_launch(app: &AppHandle, args: Vec<String>, _cwd: String) {
+    tracing::info!(?args, "App launched again");
+
+    // Windows and Linux deliver queenmama:// links as arguments to a new process
+    let urls: Vec<Url> = args
+        .iter()
+        .skip(1)
+        .filter_map(|arg| Url::parse(arg).ok())
+        .filter(|url| url.scheme() == actions::URL_SCHEME)
+        .collect();
+    for url in &urls {
+        deep_link::handle_url(app, url);
+    }
+
+    // Links pick their own window, and a hidden launch shouldn't steal focus
+    if !urls.is_empty() || args.iter().any(|arg| arg == HIDDEN_ARG) {
+        return;
+    }
+
+    // Mid-session the overlay is what the user is working with
+    let result = if session::active_session_id(app).is_some() {
+        window::overlay_window(app).and_then(|overlay| {
+            overlay.show().map_err(|e| e.to_string())?;
+            overlay.set_focus().map_err(|e| e.to_string())
+        })
+    } else {
+        window::show_main(app)
+    };
+    if let Err(e) = result {
+        tracing::error!("Failed to focus the running instance: {}", e);
+    }
+}
+
+/// Whether the app is registered to start at login, as the system has it
+pub(crate) fn autostart_enabled(app: &AppHandle) -> bool {
+    app.autolaunch().is_enabled().unwrap_or(false)
+}
+
+/// Register or unregister the app to start at login, as the autostart setting says
+pub(crate) fn apply_autostart(app: &AppHandle) -> Result<(), String> {
+    let wanted = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| e.to_string())?
+        .get(AUTOSTART_STORE_KEY)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    if wanted == autostart_enabled(app) {
+        return Ok(());
+    }
+
+    let autolaunch = app.autolaunch();
+    if wanted {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    }
+    .map_err(|e| e.to_string())?;
+    tracing::info!(enabled = wanted, "Start at login changed");
+    Ok(())
+}
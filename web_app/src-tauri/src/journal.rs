@@ -0,0 +1,188 @@
+// Queen Mama LITE - Daily Journal
+// One journal session per day that catches notes and assists made outside a coaching session
+
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{params, OptionalExtension};
+use tauri::{AppHandle, Manager, State};
+
+use crate::ai::{self, AiState, Prompt};
+use crate::db::{self, Database, Note};
+use crate::i18n::t;
+use crate::{profiles, session};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+const REFLECTION_PROMPT: &str = "You are a supportive coach. Write a short reflection on the user's \
+journal entries for the day: what went well, one pattern worth noticing, and one concrete focus for \
+tomorrow. Keep it under 150 words.";
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalStreak {
+    /// Consecutive days with entries, up to today (or yesterday if today is still empty)
+    current: u32,
+    longest: u32,
+    journaled_today: bool,
+}
+
+/// Save a note to the running session, or to today's journal when no session is running.
+/// Use kind "suggestion" for assists and "note" for quick captures.
+#[tauri::command]
+pub fn quick_capture_note(
+    app: AppHandle,
+    db: State<'_, Database>,
+    content: String,
+    kind: Option<String>,
+) -> Result<Note, String> {
+    let session_id = match session::active_session_id(&app) {
+        Some(id) => id,
+        None => journal_session(&app, Local::now().date_naive())?,
+    };
+    db::insert_note(&db, &session_id, kind.unwrap_or_else(|| "note".to_string()), content)
+}
+
+/// Get the id of a day's journal session (today by default), creating it if needed
+#[tauri::command]
+pub fn get_journal_session(app: AppHandle, date: Option<String>) -> Result<String, String> {
+    journal_session(&app, parse_date(date.as_deref())?)
+}
+
+/// Summarize a day's journal with the configured AI provider and save it as a "reflection" note.
+/// It's written in `language` if given, else the profile's summary language.
+#[tauri::command]
+pub async fn generate_journal_reflection(
+    app: AppHandle,
+    state: State<'_, AiState>,
+    date: Option<String>,
+    language: Option<String>,
+) -> Result<Note, String> {
+    let date = parse_date(date.as_deref())?;
+    let (session_id, entries) = {
+        let session_id = journal_session(&app, date)?;
+        let db = app.state::<Database>();
+        let connection = db.connection()?;
+        let entries = connection
+            .prepare(
+                "SELECT kind, content FROM notes
+                 WHERE session_id = ?1 AND kind != 'reflection' ORDER BY created_at, id",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![session_id], |row| {
+                        Ok(format!("[{}] {}", row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| e.to_string())?;
+        (session_id, entries)
+    };
+    if entries.is_empty() {
+        return Err(t!("error.no_journal_entries", date = date.format(DATE_FORMAT)));
+    }
+
+    let mut system = REFLECTION_PROMPT.to_string();
+    if let Some(language) = language.or_else(|| profiles::summary_language(&app)) {
+        system.push_str(&format!(
+            " Write it in {}, whatever language the entries are in.",
+            language
+        ));
+    }
+    let prompt = Prompt::new(Some(system), entries.join("\n"));
+    let reflection = ai::complete(&app, &state, &prompt, |_| {}).await?;
+
+    let db = app.state::<Database>();
+    db::insert_note(&db, &session_id, "reflection".to_string(), reflection)
+}
+
+/// Get the current and longest run of days with journal entries
+#[tauri::command]
+pub fn get_journal_streak(db: State<'_, Database>) -> Result<JournalStreak, String> {
+    let connection = db.connection()?;
+    let days = connection
+        .prepare(
+            "SELECT DISTINCT s.journal_date FROM sessions s
+             WHERE s.journal_date IS NOT NULL AND s.deleted_at IS NULL
+               AND EXISTS (SELECT 1 FROM notes n WHERE n.session_id = s.id AND n.kind != 'reflection')
+             ORDER BY s.journal_date",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|day| NaiveDate::parse_from_str(&day, DATE_FORMAT).ok())
+        .collect::<Vec<_>>();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in &days {
+        run = match previous {
+            Some(previous) if day - previous == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let today = Local::now().date_naive();
+    let journaled_today = days.last() == Some(&today);
+    let current = match days.last() {
+        Some(&last) if last == today || last == today - Duration::days(1) => run,
+        _ => 0,
+    };
+
+    Ok(JournalStreak {
+        current,
+        longest,
+        journaled_today,
+    })
+}
+
+/// A day's journal session id, created on first use
+fn journal_session(app: &AppHandle, date: NaiveDate) -> Result<String, String> {
+    let date = date.format(DATE_FORMAT).to_string();
+    let db = app.state::<Database>();
+    let connection = db.connection()?;
+
+    let existing: Option<String> = connection
+        .query_row(
+            "SELECT id FROM sessions WHERE journal_date = ?1",
+            params![date],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(id) = existing {
+        // Writing in a day's journal brings it back out of the trash
+        connection
+            .execute(
+                "UPDATE sessions SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+                params![id],
+            )
+            .map_err(|e| e.to_string())?;
+        return Ok(id);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    connection
+        .execute(
+            "INSERT INTO sessions (id, title, started_at, journal_date) VALUES (?1, ?2, ?3, ?4)",
+            params![id, format!("Journal {}", date), db::now_ms(), date],
+        )
+        .map_err(|e| e.to_string())?;
+    drop(connection);
+
+    db::sessions_changed(app);
+    Ok(id)
+}
+
+fn parse_date(date: Option<&str>) -> Result<NaiveDate, String> {
+    match date {
+        Some(date) => NaiveDate::parse_from_str(date, DATE_FORMAT)
+            .map_err(|e| t!("error.invalid_date", date = date, error = e)),
+        None => Ok(Local::now().date_naive()),
+    }
+}
@@ -0,0 +1,145 @@
+// Queen Mama LITE - Knowledge Base
+// Reference documents coaches study, such as talks and podcasts imported from a URL. Each one is a
+// transcribed session, so it's searched, redacted and trashed like any other session.
+
+use rusqlite::params;
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceDocument {
+    session_id: String,
+    title: String,
+    source_url: String,
+    created_at: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceHit {
+    session_id: String,
+    title: String,
+    segment_id: i64,
+    start_ms: i64,
+    snippet: String,
+}
+
+/// File a transcribed session as a reference document. Importing the same media again resumes its
+/// session, which stays a single document.
+pub(crate) fn add(
+    app: &AppHandle,
+    session_id: &str,
+    source_url: &str,
+) -> Result<ReferenceDocument, String> {
+    let db = app.state::<Database>();
+    let document = {
+        let connection = db.connection()?;
+        connection
+            .execute(
+                "INSERT INTO reference_documents (session_id, source_url, created_at)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(session_id) DO NOTHING",
+                params![session_id, source_url, db::now_ms()],
+            )
+            .map_err(|e| e.to_string())?;
+        connection
+            .query_row(
+                "SELECT d.session_id, s.title, d.source_url, d.created_at
+                 FROM reference_documents d JOIN sessions s ON s.id = d.session_id
+                 WHERE d.session_id = ?1",
+                params![session_id],
+                document_from_row,
+            )
+            .map_err(|e| e.to_string())?
+    };
+
+    if let Err(e) = app.emit("reference_document_added", &document) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+    tracing::info!(url = ?source_url, "Added a reference document");
+    Ok(document)
+}
+
+/// Reference documents, newest first; trashed ones are left out
+#[tauri::command]
+pub fn list_reference_documents(db: State<'_, Database>) -> Result<Vec<ReferenceDocument>, String> {
+    db.connection()?
+        .prepare(
+            "SELECT d.session_id, s.title, d.source_url, d.created_at
+             FROM reference_documents d JOIN sessions s ON s.id = d.session_id
+             WHERE s.deleted_at IS NULL
+             ORDER BY d.created_at DESC",
+        )
+        .and_then(|mut statement| statement.query_map([], document_from_row)?.collect())
+        .map_err(|e| e.to_string())
+}
+
+/// Full-text search over reference documents only, best matches first
+#[tauri::command]
+pub fn search_reference_documents(
+    db: State<'_, Database>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<ReferenceHit>, String> {
+    let match_query = db::fts_query(&query);
+    if match_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let connection = db.connection()?;
+    let mut statement = connection
+        .prepare(
+            "SELECT s.id, s.title, t.id, t.start_ms,
+                snippet(transcript_fts, 0, '[', ']', '…', 12)
+             FROM transcript_fts
+             JOIN transcript_segments t ON t.id = transcript_fts.rowid
+             JOIN reference_documents d ON d.session_id = t.session_id
+             JOIN sessions s ON s.id = t.session_id
+             WHERE transcript_fts MATCH ?1 AND s.deleted_at IS NULL
+             ORDER BY rank
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let hits = statement
+        .query_map(params![match_query, limit.unwrap_or(50)], |row| {
+            Ok(ReferenceHit {
+                session_id: row.get(0)?,
+                title: row.get(1)?,
+                segment_id: row.get(2)?,
+                start_ms: row.get(3)?,
+                snippet: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(hits)
+}
+
+/// Take a document out of the knowledge base; its session stays in the history
+#[tauri::command]
+pub fn remove_reference_document(
+    db: State<'_, Database>,
+    session_id: String,
+) -> Result<(), String> {
+    db.connection()?
+        .execute(
+            "DELETE FROM reference_documents WHERE session_id = ?1",
+            params![session_id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn document_from_row(row: &rusqlite::Row) -> rusqlite::Result<ReferenceDocument> {
+    Ok(ReferenceDocument {
+        session_id: row.get(0)?,
+        title: row.get(1)?,
+        source_url: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
@@ -1,45 +1,637 @@
 // Queen Mama LITE - Tauri Library
 // Cross-platform AI coaching assistant
 
+#[cfg(desktop)]
+mod accessibility;
+mod actions;
+mod ai;
+mod api_schema;
+#[cfg(desktop)]
+mod attach;
+mod audio;
+#[cfg(desktop)]
+mod auth;
+mod benchmark;
+mod bulk;
+#[cfg(desktop)]
+mod calendar;
+#[cfg(desktop)]
+mod capture;
+mod chaos;
+#[cfg(desktop)]
+mod clipboard;
+mod clips;
+mod compliance;
+#[cfg(desktop)]
+mod context;
+mod db;
+mod deep_link;
+mod demo;
+mod diagnostics;
+mod duplicates;
+mod encryption;
+mod errors;
+mod events;
+mod export;
+mod features;
+#[cfg(desktop)]
+mod file_transcription;
+mod filters;
+#[cfg(desktop)]
+mod focus;
+#[cfg(feature = "test-harness")]
+pub mod harness;
+mod highlights;
+mod i18n;
+mod import;
+#[cfg(desktop)]
+mod input;
+#[cfg(desktop)]
+mod instance;
+mod journal;
+#[cfg(desktop)]
+mod knowledge;
+mod logging;
+#[cfg(desktop)]
+mod lora;
+mod merge;
+mod mock;
+mod notes;
+mod notifications;
+mod nudge;
+#[cfg(desktop)]
+mod ocr;
+#[cfg(desktop)]
+mod overlay_menu;
+mod permissions;
+mod profiles;
+#[cfg(desktop)]
+mod realtime;
+mod redaction;
+mod reindex;
+mod relay;
+mod review;
+mod secrets;
+mod session;
+mod settings;
+#[cfg(desktop)]
+mod shortcut_capture;
 mod shortcuts;
+mod summaries;
+mod support;
+#[cfg(target_os = "macos")]
+mod system_audio;
+mod system_permissions;
+mod tags;
+mod topics;
+mod trace;
+mod transcript;
+#[cfg(desktop)]
+mod transcription;
+mod trash;
+#[cfg(desktop)]
 mod tray;
+#[cfg(desktop)]
+mod typing;
+#[cfg(desktop)]
+mod updates;
+#[cfg(desktop)]
+mod vad;
+mod vault;
+#[cfg(desktop)]
+mod watch_folder;
 mod window;
 
 use tauri::Manager;
 
+/// Store file holding settings owned by the Rust side
+pub(crate) const SETTINGS_STORE: &str = "settings.json";
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+
+    // Single instance goes first so a second launch exits before any other plugin starts.
+    // The test harness leaves it out so test processes don't hand off to each other.
+    #[cfg(desktop)]
+    let builder =
+        builder.plugin(tauri_plugin_single_instance::init(instance::handle_second_launch));
+
+    build(builder, context())
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // Files opened with the app or shared from other apps
+            #[cfg(any(target_os = "macos", target_os = "ios", target_os = "android"))]
+            if let tauri::RunEvent::Opened { urls } = &_event {
+                import::handle_opened_urls(_app, urls);
+            }
+        });
+}
+
+pub(crate) fn context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
+}
+
+/// Add our plugins, setup and commands; shared with the test harness
+pub(crate) fn build(
+    builder: tauri::Builder<tauri::Wry>,
+    context: tauri::Context<tauri::Wry>,
+) -> tauri::Result<tauri::App> {
+    // Tray, global shortcuts, autostart, self-updates and the clipboard only exist on desktop
+    #[cfg(desktop)]
+    let builder = builder
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
-            Some(vec!["--hidden"]),
+            Some(vec![instance::HIDDEN_ARG]),
         ))
+        .plugin(tauri_plugin_updater::Builder::new().build());
+
+    builder
+        .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
+        .on_window_event(|window, event| {
+            import::handle_window_event(window, event);
+            window::handle_window_event(window, event);
+        })
         .setup(|app| {
+            // Setup rotating log files and the backend error channel before anything logs
+            logging::setup_logging(app)?;
+
+            // Setup settings schema migrations before anything reads settings
+            settings::setup_settings_schema(app)?;
+
+            // Setup localized strings before anything builds labels
+            i18n::setup_i18n(app)?;
+
+            // Setup event payload versions before anything emits
+            events::setup_events(app)?;
+
+            // Setup crash reports for backend panics
+            diagnostics::setup_diagnostics(app)?;
+
             // Setup system tray
+            #[cfg(desktop)]
             tray::setup_tray(app)?;
 
             // Setup global shortcuts
+            #[cfg(desktop)]
             shortcuts::setup_shortcuts(app)?;
 
+            // Setup key chord capture for rebinding shortcuts
+            #[cfg(desktop)]
+            shortcut_capture::setup_shortcut_capture(app)?;
+
+            // Setup foot pedals and other external input devices
+            #[cfg(desktop)]
+            input::setup_input(app)?;
+
+            // Setup macOS privacy permission checks
+            system_permissions::setup_system_permissions(app)?;
+
+            // Setup native audio capture
+            audio::setup_audio(app)?;
+
+            // Setup native notifications for background events
+            notifications::setup_notifications(app)?;
+
+            // Setup update checks on the selected release channel
+            #[cfg(desktop)]
+            updates::setup_updates(app)?;
+
+            // Setup session history database
+            db::setup_db(app)?;
+            #[cfg(desktop)]
+            tray::refresh_recent_sessions(app.app_handle());
+
+            // Setup the file key for passphrase-encrypted files
+            vault::setup_vault(app)?;
+
+            // Setup the lock timeout for passphrase-encrypted history
+            encryption::setup_encryption(app)?;
+
+            // Setup notes autosave
+            notes::setup_notes(app)?;
+
+            // Setup search re-indexing, resuming one cut short by quitting
+            reindex::setup_reindex(app)?;
+
+            // Setup active window context for prompts and meeting detection
+            #[cfg(desktop)]
+            context::setup_context(app)?;
+
+            // Setup clipboard history and pasting into other apps
+            #[cfg(desktop)]
+            clipboard::setup_clipboard(app)?;
+
+            // Setup typing suggestions into other apps
+            #[cfg(desktop)]
+            typing::setup_typing(app)?;
+
+            // Setup AI provider client
+            ai::setup_ai(app)?;
+
+            // Setup account sign-in
+            #[cfg(desktop)]
+            auth::setup_auth(app)?;
+
+            // Setup session lifecycle
+            session::setup_session(app)?;
+
+            // Setup local Whisper transcription
+            #[cfg(desktop)]
+            transcription::setup_transcription(app)?;
+
+            // Setup permission prompts for outside clients, before the relay and links can call in
+            permissions::setup_permissions(app)?;
+
+            // Setup companion relay for paired phones
+            relay::setup_relay(app)?;
+
+            // Setup the trash, purging sessions deleted longer ago than the retention window
+            trash::setup_trash(app)?;
+
+            // Setup transcription of audio files
+            #[cfg(desktop)]
+            file_transcription::setup_file_transcription(app)?;
+            // Setup realtime connection to the coaching backend
+            #[cfg(desktop)]
+            realtime::setup_realtime(app)?;
+
+            // Setup upcoming meeting detection from the calendar
+            #[cfg(desktop)]
+            calendar::setup_calendar(app)?;
+
+            // Setup watch folder auto-import
+            #[cfg(desktop)]
+            watch_folder::setup_watch_folder(app)?;
+
+            // Setup OS accessibility preferences
+            #[cfg(desktop)]
+            accessibility::setup_accessibility(app)?;
+
+            // Setup focus and do not disturb tracking
+            #[cfg(desktop)]
+            focus::setup_focus(app)?;
+
             // Setup window management
             window::setup_windows(app)?;
 
+            // Start in the tray when launched at login
+            #[cfg(desktop)]
+            instance::setup_instance(app)?;
+
+            // Setup queenmama:// links, including URL-triggered automation actions
+            deep_link::setup_deep_links(app)?;
+
+            // Setup the overlay's native context menu
+            #[cfg(desktop)]
+            overlay_menu::setup_overlay_menu(app)?;
+
+            // Setup overlay docking to other apps' windows
+            #[cfg(desktop)]
+            attach::setup_attach(app)?;
+
+            // Setup fault injection for resilience testing
+            chaos::setup_chaos(app)?;
+
+            // Apply settings edited outside the app
+            #[cfg(desktop)]
+            settings::setup_settings(app)?;
+
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(trace::instrument(tauri::generate_handler![
             window::toggle_overlay,
             window::set_overlay_expanded,
+            window::get_overlay_geometry,
+            window::set_overlay_geometry,
+            #[cfg(desktop)]
+            window::set_visible_on_all_workspaces,
+            window::set_content_protection,
+            window::get_content_protection,
+            #[cfg(desktop)]
+            window::set_overlay_click_through,
+            #[cfg(desktop)]
+            window::toggle_caption_window,
+            #[cfg(desktop)]
+            overlay_menu::show_overlay_context_menu,
+            #[cfg(desktop)]
+            attach::attach_overlay,
+            #[cfg(desktop)]
+            attach::detach_overlay,
+            #[cfg(desktop)]
+            attach::get_overlay_attachment,
             window::move_overlay,
+            window::begin_overlay_drag,
+            window::list_monitors,
+            window::move_overlay_to_monitor,
+            #[cfg(desktop)]
+            window::set_overlay_follow_monitor,
+            #[cfg(desktop)]
+            window::get_autohide_rules,
+            #[cfg(desktop)]
+            window::set_autohide_rules,
             window::show_main_window,
+            window::show_window,
+            window::hide_window,
+            window::get_window_states,
+            settings::get_settings,
+            settings::update_settings,
             shortcuts::get_shortcuts,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            #[cfg(desktop)]
+            shortcuts::set_shortcut,
+            #[cfg(desktop)]
+            shortcuts::reset_shortcuts,
+            #[cfg(desktop)]
+            shortcuts::get_shortcut_registration_report,
+            #[cfg(desktop)]
+            shortcut_capture::begin_shortcut_capture,
+            #[cfg(desktop)]
+            input::list_pedal_devices,
+            #[cfg(desktop)]
+            input::get_pedal_config,
+            #[cfg(desktop)]
+            input::connect_pedal,
+            #[cfg(desktop)]
+            input::disconnect_pedal,
+            actions::list_actions,
+            actions::run_action,
+            relay::start_relay,
+            relay::stop_relay,
+            relay::get_relay_pairing,
+            relay::relay_publish,
+            api_schema::get_api_schema,
+            permissions::create_api_client,
+            permissions::list_api_clients,
+            permissions::revoke_api_client,
+            events::negotiate_event_version,
+            #[cfg(desktop)]
+            realtime::realtime_connect,
+            #[cfg(desktop)]
+            realtime::realtime_disconnect,
+            #[cfg(desktop)]
+            realtime::realtime_send,
+            #[cfg(desktop)]
+            realtime::get_realtime_status,
+            nudge::get_nudge_rules,
+            nudge::set_nudge_rule,
+            nudge::trigger_nudge,
+            audio::list_audio_devices,
+            audio::start_audio_capture,
+            audio::capture_system_audio,
+            audio::set_audio_source_muted,
+            audio::stop_audio_capture,
+            audio::get_audio_capture,
+            audio::get_waveform_peaks,
+            #[cfg(desktop)]
+            capture::list_capture_targets,
+            #[cfg(desktop)]
+            capture::capture_screen,
+            system_permissions::check_permission,
+            system_permissions::list_permissions,
+            system_permissions::request_permission,
+            #[cfg(desktop)]
+            ocr::extract_text_from_screen,
+            #[cfg(desktop)]
+            calendar::get_upcoming_events,
+            #[cfg(desktop)]
+            calendar::set_calendar_feed,
+            #[cfg(desktop)]
+            calendar::has_calendar_feed,
+            #[cfg(desktop)]
+            clipboard::get_clipboard_text,
+            #[cfg(desktop)]
+            clipboard::get_clipboard_history,
+            #[cfg(desktop)]
+            clipboard::watch_clipboard,
+            #[cfg(desktop)]
+            clipboard::paste_text,
+            #[cfg(desktop)]
+            typing::type_text,
+            #[cfg(desktop)]
+            typing::stop_typing,
+            #[cfg(desktop)]
+            context::get_active_window_info,
+            #[cfg(desktop)]
+            context::set_active_window_polling,
+            #[cfg(desktop)]
+            context::get_meeting_detection,
+            #[cfg(desktop)]
+            context::set_meeting_detection,
+            session::start_session,
+            session::pause_session,
+            session::stop_session,
+            session::get_session_state,
+            db::create_session,
+            db::list_sessions,
+            db::get_session,
+            db::search_sessions,
+            reindex::reindex_all,
+            reindex::get_reindex_status,
+            topics::get_topic_trends,
+            db::delete_session,
+            db::append_transcript_segment,
+            db::add_session_note,
+            notes::save_note_draft,
+            notes::get_note_draft,
+            notes::list_note_revisions,
+            notes::add_note_attachment,
+            notes::delete_note_attachment,
+            notes::search_notes,
+            notes::export_session_notes,
+            export::export_session,
+            #[cfg(desktop)]
+            export::reveal_in_file_manager,
+            notifications::run_notification_action,
+            notifications::get_notification_sounds,
+            notifications::set_notification_sound,
+            notifications::preview_notification,
+            i18n::get_strings,
+            i18n::get_locale,
+            i18n::set_locale,
+            logging::get_recent_logs,
+            logging::export_logs,
+            logging::get_log_level,
+            logging::set_log_level,
+            errors::get_recent_errors,
+            features::get_feature_availability,
+            demo::get_demo_mode,
+            demo::set_demo_mode,
+            mock::get_mock_config,
+            mock::set_mock_config,
+            chaos::get_chaos_mode,
+            chaos::set_chaos_mode,
+            chaos::inject_fault,
+            trace::get_trace,
+            diagnostics::get_pending_crash_reports,
+            diagnostics::submit_crash_report,
+            diagnostics::dismiss_crash_report,
+            benchmark::get_benchmark_consent,
+            benchmark::set_benchmark_consent,
+            benchmark::preview_benchmark_metrics,
+            benchmark::get_cohort_benchmark,
+            support::create_support_bundle,
+            compliance::get_compliance_config,
+            compliance::set_compliance_config,
+            compliance::get_compliance_report,
+            compliance::export_compliance_summary,
+            review::add_session_comment,
+            review::delete_session_comment,
+            review::list_session_comments,
+            review::export_session_for_review,
+            review::import_review_comments,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::delete_profile,
+            profiles::switch_profile,
+            profiles::set_summary_language,
+            encryption::get_encryption_status,
+            encryption::enable_encryption,
+            encryption::unlock,
+            encryption::lock,
+            encryption::decrypt_export,
+            journal::quick_capture_note,
+            journal::get_journal_session,
+            journal::generate_journal_reflection,
+            journal::get_journal_streak,
+            ai::get_ai_config,
+            ai::set_ai_config,
+            ai::set_ai_api_key,
+            ai::send_prompt,
+            ai::stream_prompt,
+            secrets::set_secret,
+            secrets::get_secret,
+            secrets::delete_secret,
+            #[cfg(desktop)]
+            auth::login,
+            #[cfg(desktop)]
+            auth::logout,
+            #[cfg(desktop)]
+            auth::get_auth_state,
+            #[cfg(desktop)]
+            transcription::list_whisper_models,
+            #[cfg(desktop)]
+            transcription::set_whisper_model,
+            #[cfg(desktop)]
+            transcription::download_whisper_model,
+            #[cfg(desktop)]
+            transcription::delete_whisper_model,
+            #[cfg(desktop)]
+            transcription::add_custom_whisper_model,
+            #[cfg(desktop)]
+            transcription::remove_custom_whisper_model,
+            #[cfg(desktop)]
+            transcription::start_local_transcription,
+            #[cfg(desktop)]
+            transcription::stop_local_transcription,
+            import::import_file,
+            import::import_shared_text,
+            #[cfg(desktop)]
+            import::import_media_url,
+            #[cfg(desktop)]
+            watch_folder::get_watch_folder,
+            #[cfg(desktop)]
+            watch_folder::set_watch_folder,
+            #[cfg(desktop)]
+            tray::update_open_goals,
+            tags::list_tags,
+            tags::add_session_tag,
+            tags::remove_session_tag,
+            tags::rename_tag,
+            tags::delete_tag,
+            tags::list_custom_fields,
+            tags::create_custom_field,
+            tags::rename_custom_field,
+            tags::delete_custom_field,
+            tags::set_session_field,
+            tags::get_session_breakdown,
+            filters::save_filter,
+            filters::update_filter,
+            filters::list_filters,
+            filters::delete_filter,
+            filters::list_sessions_by_filter,
+            bulk::delete_sessions,
+            bulk::tag_sessions,
+            bulk::export_sessions,
+            trash::list_trash,
+            trash::restore_session,
+            trash::purge_session,
+            trash::empty_trash,
+            merge::merge_sessions,
+            merge::split_session,
+            duplicates::find_duplicate_sessions,
+            duplicates::dismiss_duplicates,
+            summaries::generate_summary,
+            summaries::list_summary_versions,
+            summaries::set_primary_summary,
+            redaction::redact_segments,
+            redaction::list_redactions,
+            #[cfg(desktop)]
+            file_transcription::transcribe_file,
+            #[cfg(desktop)]
+            file_transcription::cancel_file_transcription,
+            #[cfg(desktop)]
+            file_transcription::list_file_transcriptions,
+            #[cfg(desktop)]
+            knowledge::list_reference_documents,
+            #[cfg(desktop)]
+            knowledge::search_reference_documents,
+            #[cfg(desktop)]
+            knowledge::remove_reference_document,
+            clips::extract_clip,
+            highlights::flag_moment,
+            highlights::list_flags,
+            highlights::delete_flag,
+            highlights::generate_highlights,
+            transcript::get_transcript_page,
+            #[cfg(desktop)]
+            tray::update_tray_state,
+            #[cfg(desktop)]
+            updates::get_update_channel,
+            #[cfg(desktop)]
+            updates::set_update_channel,
+            #[cfg(desktop)]
+            updates::check_for_updates,
+            #[cfg(desktop)]
+            updates::download_update,
+            #[cfg(desktop)]
+            updates::install_and_restart,
+            #[cfg(desktop)]
+            accessibility::get_accessibility_prefs,
+            #[cfg(desktop)]
+            focus::get_focus_state,
+            #[cfg(desktop)]
+            focus::get_focus_suppression,
+            #[cfg(desktop)]
+            focus::set_focus_suppression,
+        ]))
+        .build(context)
+}
+
+/// Whether the app was launched with `db::CHECK_MIGRATIONS_ARG`, to run `check_migrations`
+/// instead of `run`
+pub fn check_migrations_requested() -> bool {
+    std::env::args().any(|arg| arg == db::CHECK_MIGRATIONS_ARG)
+}
+
+/// Report the migrations the database is waiting for. Only the store plugin is loaded: no setup
+/// step runs, no window opens and nothing is written.
+pub fn check_migrations() -> Result<db::MigrationCheck, String> {
+    let mut context = context();
+    context.config_mut().app.windows.clear();
+    context.config_mut().app.tray_icon = None;
+    let app = tauri::Builder::default()
+        .plugin(tauri_plugin_store::Builder::new().build())
+        .build(context)
+        .map_err(|e| e.to_string())?;
+    i18n::setup_i18n(&app).map_err(|e| e.to_string())?;
+    Ok(db::check_migrations(app.handle()))
 }
@@ -0,0 +1,263 @@
+// Queen Mama LITE - Logging
+// Structured logs in daily-rotated files under the app data dir, with a level that can change at runtime
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::DateTime;
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{self, LevelFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+use crate::errors::{ErrorLayer, ErrorState};
+use crate::trace::{TraceLayer, TraceState};
+use crate::SETTINGS_STORE;
+
+pub(crate) const LOG_LEVEL_STORE_KEY: &str = "log_level";
+
+/// Files are named queen-mama.<date>.log
+const LOG_FILE_PREFIX: &str = "queen-mama";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Days of logs kept before the oldest file is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Lines returned by `get_recent_logs` when no count is given
+const DEFAULT_RECENT_LINES: usize = 200;
+
+/// Log target prefix of our own modules
+const CRATE_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+/// Fields that can hold what the user typed or picked. Log titles, names, file paths, links and
+/// devices only as these fields (or ones ending in `_<field>`), Debug-formatted so they're quoted,
+/// e.g. `tracing::info!(path = ?path, "Imported a file")`; support bundles blank them out.
+const PRIVATE_FIELDS: &[&str] = &["title", "name", "path", "file", "url", "device"];
+
+pub struct LogState {
+    dir: PathBuf,
+    level: reload::Handle<LevelFilter, Registry>,
+    /// Flushes buffered lines to disk when the app exits
+    _guard: WorkerGuard,
+}
+
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Runs first so every other setup step is logged
+pub fn setup_logging(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = app.path().app_data_dir()?.join("logs");
+    fs::create_dir_all(&dir)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    // The saved level only applies to the log output; errors and traces have their own
+    let (level, handle) = reload::Layer::new(LevelFilter::from(load_level(app.handle())));
+    let recent_errors = ErrorState::default();
+    let traces = TraceState::default();
+    tracing_subscriber::registry()
+        .with(
+            fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .and_then(fmt::layer().with_writer(io::stderr))
+                .with_filter(level),
+        )
+        .with(ErrorLayer::new(app.handle().clone(), &recent_errors).with_filter(LevelFilter::WARN))
+        .with(
+            TraceLayer::new(&traces).with_filter(filter::filter_fn(|metadata| {
+                module_name(metadata.target()).is_some()
+                    && *metadata.level() <= tracing::Level::DEBUG
+            })),
+        )
+        .try_init()?;
+    app.manage(recent_errors);
+    app.manage(traces);
+
+    tracing::info!(
+        version = %app.package_info().version,
+        path = ?dir,
+        "Logging started"
+    );
+    app.manage(LogState {
+        dir,
+        level: handle,
+        _guard: guard,
+    });
+    Ok(())
+}
+
+/// Whether a log field holds something private, per `PRIVATE_FIELDS`
+pub(crate) fn is_private_field(name: &str) -> bool {
+    PRIVATE_FIELDS.iter().any(|field| {
+        name.strip_suffix(field)
+            .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('_'))
+    })
+}
+
+/// Get the last lines logged, oldest first
+#[tauri::command]
+pub fn get_recent_logs(
+    state: State<'_, LogState>,
+    lines: Option<usize>,
+) -> Result<Vec<String>, String> {
+    state.recent_lines(lines.unwrap_or(DEFAULT_RECENT_LINES))
+}
+
+/// Zip every log file so it can be attached to a bug report
+#[tauri::command]
+pub fn export_logs(state: State<'_, LogState>, zip_path: PathBuf) -> Result<String, String> {
+    let file = File::create(&zip_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for path in log_files(&state.dir)? {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        let mut log = File::open(&path).map_err(|e| e.to_string())?;
+        io::copy(&mut log, &mut zip).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+
+    tracing::info!(path = ?zip_path, "Exported logs");
+    Ok(zip_path.to_string_lossy().into_owned())
+}
+
+/// Get the minimum level written to the logs
+#[tauri::command]
+pub fn get_log_level(app: AppHandle) -> LogLevel {
+    load_level(&app)
+}
+
+/// Change the minimum level written to the logs; takes effect immediately
+#[tauri::command]
+pub fn set_log_level(app: AppHandle, level: LogLevel) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        LOG_LEVEL_STORE_KEY,
+        serde_json::to_value(level).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+    reload(&app)
+}
+
+/// Re-apply the saved log level, e.g. after settings.json was edited
+pub fn reload(app: &AppHandle) -> Result<(), String> {
+    let level = LevelFilter::from(load_level(app));
+    app.state::<LogState>()
+        .level
+        .reload(level)
+        .map_err(|e| e.to_string())?;
+    tracing::info!("Log level set to {}", level);
+    Ok(())
+}
+
+impl LogState {
+    /// The last `wanted` lines across the log files, oldest first
+    pub fn recent_lines(&self, wanted: usize) -> Result<Vec<String>, String> {
+        // Walk back from the newest file until we have enough lines
+        let mut recent = Vec::new();
+        for path in log_files(&self.dir)?.iter().rev() {
+            if recent.len() >= wanted {
+                break;
+            }
+            let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let mut file_lines: Vec<String> = contents.lines().map(str::to_string).collect();
+            let keep = file_lines.len().min(wanted - recent.len());
+            file_lines.drain(..file_lines.len() - keep);
+            file_lines.append(&mut recent);
+            recent = file_lines;
+        }
+        Ok(recent)
+    }
+
+    /// Lines logged between two Unix ms times, oldest first. Lines without a timestamp, such as
+    /// the rest of a multi-line message, go with the line before them.
+    pub fn lines_between(&self, from_ms: i64, to_ms: i64) -> Result<Vec<String>, String> {
+        let mut lines = Vec::new();
+        for path in log_files(&self.dir)? {
+            let contents = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            let mut inside = false;
+            for line in contents.lines() {
+                if let Some(logged_at) = line
+                    .split_whitespace()
+                    .next()
+                    .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+                {
+                    inside = (from_ms..=to_ms).contains(&logged_at.timestamp_millis());
+                }
+                if inside {
+                    lines.push(line.to_string());
+                }
+            }
+        }
+        Ok(lines)
+    }
+}
+
+/// Our module a log target belongs to, e.g. "audio"; `None` for dependencies
+pub(crate) fn module_name(target: &str) -> Option<&str> {
+    target
+        .strip_prefix(CRATE_TARGET)?
+        .strip_prefix("::")?
+        .split("::")
+        .next()
+}
+
+/// Log files, oldest first; the date in their names sorts chronologically
+fn log_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn load_level(app: &AppHandle) -> LogLevel {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(LOG_LEVEL_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,374 @@
+// Queen Mama LITE - LoRA Adapters
+// Applies a fine-tuned LoRA adapter to a stock Whisper model. whisper.cpp can only load merged
+// weights, so the adapter is folded into a copy of the base model's ggml file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use half::{bf16, f16};
+
+use crate::i18n::t;
+
+/// Files PEFT saves an adapter as
+const ADAPTER_CONFIG_FILE: &str = "adapter_config.json";
+const ADAPTER_WEIGHTS_FILE: &str = "adapter_model.safetensors";
+
+/// ggml tensor types a merge can write back; quantized models would need requantizing
+const GGML_TYPE_F32: i32 = 0;
+const GGML_TYPE_F16: i32 = 1;
+
+/// Where an adapter's config and weights were found
+pub(crate) struct AdapterFiles {
+    config: PathBuf,
+    weights: PathBuf,
+}
+
+#[derive(serde::Deserialize)]
+struct AdapterConfig {
+    r: f32,
+    lora_alpha: f32,
+    #[serde(default)]
+    use_rslora: bool,
+    /// e.g. "openai/whisper-small"
+    #[serde(default)]
+    base_model_name_or_path: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct SafetensorsEntry {
+    dtype: String,
+    shape: Vec<usize>,
+    data_offsets: [usize; 2],
+}
+
+/// One adapted weight: `delta = scale * B·A`, with A `rank × inputs` and B `outputs × rank`
+#[derive(Default)]
+struct LoraPair {
+    a: Option<(Vec<usize>, Vec<f32>)>,
+    b: Option<(Vec<usize>, Vec<f32>)>,
+}
+
+/// The adapter at `path` if it is one: a PEFT adapter folder, or its safetensors file with the
+/// config next to it
+pub(crate) fn find_adapter(path: &Path) -> Option<AdapterFiles> {
+    let (dir, weights) = if path.is_dir() {
+        (path.to_path_buf(), path.join(ADAPTER_WEIGHTS_FILE))
+    } else if path
+        .extension()
+        .is_some_and(|extension| extension == "safetensors")
+    {
+        (path.parent()?.to_path_buf(), path.to_path_buf())
+    } else {
+        return None;
+    };
+    Some(AdapterFiles {
+        config: dir.join(ADAPTER_CONFIG_FILE),
+        weights,
+    })
+}
+
+/// Stock model the adapter was trained on, when its config names one, e.g. "small" for
+/// "openai/whisper-small"
+pub(crate) fn base_model(adapter: &AdapterFiles) -> Option<String> {
+    read_config(adapter)
+        .ok()?
+        .base_model_name_or_path?
+        .rsplit('/')
+        .next()?
+        .strip_prefix("whisper-")
+        .map(str::to_string)
+}
+
+/// Write `base` with the adapter folded into its weights to `output`, returning how many weights
+/// were adapted. Nothing is left at `output` if it fails.
+pub(crate) fn merge(adapter: &AdapterFiles, base: &Path, output: &Path) -> Result<usize, String> {
+    let config = read_config(adapter)?;
+    if config.r <= 0.0 {
+        return Err(t!("error.lora_invalid", error = "r"));
+    }
+    let scale = if config.use_rslora {
+        config.lora_alpha / config.r.sqrt()
+    } else {
+        config.lora_alpha / config.r
+    };
+    let mut pairs = read_pairs(&adapter.weights)?;
+
+    let merged = fs::File::create(output)
+        .map_err(|e| e.to_string())
+        .and_then(|file| copy_merged(base, file, &mut pairs, scale));
+    let result = merged.and_then(|adapted| {
+        // Anything left over names a weight the base model doesn't have
+        if let Some(name) = pairs.keys().next() {
+            return Err(t!("error.lora_wrong_base", tensor = name));
+        }
+        if adapted == 0 {
+            return Err(t!("error.lora_invalid", error = ADAPTER_WEIGHTS_FILE));
+        }
+        Ok(adapted)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+fn read_config(adapter: &AdapterFiles) -> Result<AdapterConfig, String> {
+    let config = fs::read_to_string(&adapter.config)
+        .map_err(|_| t!("error.lora_config_missing", path = adapter.config.display()))?;
+    serde_json::from_str(&config).map_err(|e| t!("error.lora_invalid", error = e))
+}
+
+/// The adapter's A and B matrices as f32, keyed by the ggml name of the weight they adapt
+fn read_pairs(path: &Path) -> Result<HashMap<String, LoraPair>, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let invalid = |error: &str| t!("error.lora_invalid", error = error);
+
+    // An 8-byte header length, the JSON header, then the data it points into
+    let data_start = bytes
+        .get(..8)
+        .and_then(|len| len.try_into().ok())
+        .and_then(|len| usize::try_from(u64::from_le_bytes(len)).ok())
+        .and_then(|len| len.checked_add(8))
+        .filter(|&start| start <= bytes.len())
+        .ok_or_else(|| invalid("header"))?;
+    let data = &bytes[data_start..];
+    let mut header: HashMap<String, serde_json::Value> =
+        serde_json::from_slice(&bytes[8..data_start]).map_err(|e| invalid(&e.to_string()))?;
+    header.remove("__metadata__");
+
+    let mut pairs: HashMap<String, LoraPair> = HashMap::new();
+    for (name, entry) in header {
+        let entry: SafetensorsEntry =
+            serde_json::from_value(entry).map_err(|e| invalid(&e.to_string()))?;
+        let Some((module, matrix)) = split_lora_name(&name) else {
+            continue;
+        };
+        let target = ggml_tensor_name(module).ok_or_else(|| invalid(&name))?;
+        let raw = data
+            .get(entry.data_offsets[0]..entry.data_offsets[1])
+            .ok_or_else(|| invalid(&name))?;
+        let values = decode(&entry.dtype, raw).ok_or_else(|| invalid(&entry.dtype))?;
+        if entry.shape.len() != 2 || values.len() != entry.shape[0] * entry.shape[1] {
+            return Err(invalid(&name));
+        }
+
+        let pair = pairs.entry(target).or_default();
+        match matrix {
+            'A' => pair.a = Some((entry.shape, values)),
+            _ => pair.b = Some((entry.shape, values)),
+        }
+    }
+    Ok(pairs)
+}
+
+/// Split e.g. "base_model.model.model.encoder.layers.0.self_attn.q_proj.lora_A.weight" into
+/// the module it adapts and which matrix it is
+fn split_lora_name(name: &str) -> Option<(&str, char)> {
+    let (module, matrix, rest) = if let Some((module, rest)) = name.split_once(".lora_A.") {
+        (module, 'A', rest)
+    } else {
+        let (module, rest) = name.split_once(".lora_B.")?;
+        (module, 'B', rest)
+    };
+    // Older PEFT versions keep the adapter's name, e.g. "lora_A.default.weight"
+    rest.ends_with("weight").then_some((module, matrix))
+}
+
+/// whisper.cpp's name for a Hugging Face Whisper module, as convert-h5-to-ggml.py maps it, e.g.
+/// "encoder.blocks.0.attn.query.weight" for "model.encoder.layers.0.self_attn.q_proj"
+fn ggml_tensor_name(module: &str) -> Option<String> {
+    let module = module.trim_start_matches("base_model.model.");
+    let module = module.strip_prefix("model.").unwrap_or(module);
+    let mut parts = module.splitn(4, '.');
+    let (stack, layers, index, layer) =
+        (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    if !matches!(stack, "encoder" | "decoder") || layers != "layers" {
+        return None;
+    }
+    index.parse::<usize>().ok()?;
+
+    let projection = |name: &str| match name {
+        "q_proj" => Some("query"),
+        "k_proj" => Some("key"),
+        "v_proj" => Some("value"),
+        "out_proj" => Some("out"),
+        _ => None,
+    };
+    let mapped = match layer.split_once('.') {
+        Some(("self_attn", name)) => format!("attn.{}", projection(name)?),
+        Some(("encoder_attn", name)) => format!("cross_attn.{}", projection(name)?),
+        None if layer == "fc1" => "mlp.0".to_string(),
+        None if layer == "fc2" => "mlp.2".to_string(),
+        _ => return None,
+    };
+    Some(format!("{}.blocks.{}.{}.weight", stack, index, mapped))
+}
+
+fn decode(dtype: &str, raw: &[u8]) -> Option<Vec<f32>> {
+    let values = match dtype {
+        "F32" => raw
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect(),
+        "F16" => raw
+            .chunks_exact(2)
+            .map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]).to_f32())
+            .collect(),
+        "BF16" => raw
+            .chunks_exact(2)
+            .map(|bytes| bf16::from_le_bytes([bytes[0], bytes[1]]).to_f32())
+            .collect(),
+        _ => return None,
+    };
+    Some(values)
+}
+
+/// Copy a ggml model, adding each adapted weight's delta on the way. Pairs are removed as
+/// they're applied.
+fn copy_merged(
+    base: &Path,
+    output: fs::File,
+    pairs: &mut HashMap<String, LoraPair>,
+    scale: f32,
+) -> Result<usize, String> {
+    let mut reader = BufReader::new(fs::File::open(base).map_err(|e| e.to_string())?);
+    let mut writer = BufWriter::new(output);
+    let invalid = || t!("error.not_ggml_model", path = base.display());
+
+    // Magic and hyperparameters: n_vocab, audio ctx/state/head/layer, text ctx/state/head/layer,
+    // n_mels, ftype
+    let mut header = [0u8; 4 + 11 * 4];
+    reader.read_exact(&mut header).map_err(|_| invalid())?;
+    writer.write_all(&header).map_err(|e| e.to_string())?;
+
+    // Mel filters
+    let n_mel = copy_i32(&mut reader, &mut writer).map_err(|_| invalid())?;
+    let n_fft = copy_i32(&mut reader, &mut writer).map_err(|_| invalid())?;
+    copy_bytes(&mut reader, &mut writer, n_mel as u64 * n_fft as u64 * 4).map_err(|_| invalid())?;
+
+    // Vocabulary, each token as its length then its bytes
+    let n_vocab = copy_i32(&mut reader, &mut writer).map_err(|_| invalid())?;
+    for _ in 0..n_vocab {
+        let len = copy_i32(&mut reader, &mut writer).map_err(|_| invalid())?;
+        copy_bytes(&mut reader, &mut writer, len as u64).map_err(|_| invalid())?;
+    }
+
+    // Tensors until the end of the file
+    let mut adapted = 0;
+    loop {
+        let mut n_dims = [0u8; 4];
+        match reader.read_exact(&mut n_dims) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.to_string()),
+        }
+        writer.write_all(&n_dims).map_err(|e| e.to_string())?;
+        let n_dims = i32::from_le_bytes(n_dims);
+        let name_len = copy_i32(&mut reader, &mut writer).map_err(|_| invalid())?;
+        let ttype = copy_i32(&mut reader, &mut writer).map_err(|_| invalid())?;
+        let mut shape = Vec::new();
+        for _ in 0..n_dims {
+            shape.push(copy_i32(&mut reader, &mut writer).map_err(|_| invalid())? as usize);
+        }
+        let mut name = vec![0u8; name_len as usize];
+        reader.read_exact(&mut name).map_err(|_| invalid())?;
+        writer.write_all(&name).map_err(|e| e.to_string())?;
+        let name = String::from_utf8_lossy(&name).into_owned();
+
+        let elements: usize = shape.iter().product();
+        let Some(pair) = pairs.remove(&name) else {
+            let size = match ttype {
+                GGML_TYPE_F32 => elements as u64 * 4,
+                GGML_TYPE_F16 => elements as u64 * 2,
+                // Quantized blocks aren't needed unless adapted; their size comes from the rest
+                _ => quantized_size(ttype, elements).ok_or_else(invalid)?,
+            };
+            copy_bytes(&mut reader, &mut writer, size).map_err(|_| invalid())?;
+            continue;
+        };
+
+        if ttype != GGML_TYPE_F32 && ttype != GGML_TYPE_F16 {
+            return Err(t!("error.lora_quantized_base"));
+        }
+        let (Some((a_shape, a)), Some((b_shape, b))) = (pair.a, pair.b) else {
+            return Err(t!("error.lora_invalid", error = name));
+        };
+        // ggml lists the fastest-changing dimension first: [inputs, outputs]
+        let [inputs, outputs] = shape[..] else {
+            return Err(t!("error.lora_wrong_base", tensor = name));
+        };
+        let rank = a_shape[0];
+        if a_shape[1] != inputs || b_shape[0] != outputs || b_shape[1] != rank {
+            return Err(t!("error.lora_wrong_base", tensor = name));
+        }
+
+        let width = if ttype == GGML_TYPE_F32 { 4 } else { 2 };
+        let mut raw = vec![0u8; elements * width];
+        reader.read_exact(&mut raw).map_err(|_| invalid())?;
+        let mut weights: Vec<f32> = if ttype == GGML_TYPE_F32 {
+            raw.chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect()
+        } else {
+            raw.chunks_exact(2)
+                .map(|bytes| f16::from_le_bytes([bytes[0], bytes[1]]).to_f32())
+                .collect()
+        };
+
+        for (row_index, row) in weights.chunks_exact_mut(inputs).enumerate() {
+            for k in 0..rank {
+                let factor = scale * b[row_index * rank + k];
+                if factor == 0.0 {
+                    continue;
+                }
+                for (weight, a) in row.iter_mut().zip(&a[k * inputs..(k + 1) * inputs]) {
+                    *weight += factor * a;
+                }
+            }
+        }
+
+        for weight in weights {
+            let written = if ttype == GGML_TYPE_F32 {
+                writer.write_all(&weight.to_le_bytes())
+            } else {
+                writer.write_all(&f16::from_f32(weight).to_le_bytes())
+            };
+            written.map_err(|e| e.to_string())?;
+        }
+        adapted += 1;
+    }
+
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(adapted)
+}
+
+/// Bytes taken by `elements` values of a quantized ggml type whisper.cpp writes
+fn quantized_size(ttype: i32, elements: usize) -> Option<u64> {
+    // (values per block, bytes per block) for q4_0, q4_1, q5_0, q5_1 and q8_0
+    let (block, bytes) = match ttype {
+        2 => (32, 18),
+        3 => (32, 20),
+        6 => (32, 22),
+        7 => (32, 24),
+        8 => (32, 34),
+        _ => return None,
+    };
+    Some((elements / block * bytes) as u64)
+}
+
+fn copy_i32(reader: &mut impl Read, writer: &mut impl Write) -> std::io::Result<i32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    writer.write_all(&bytes)?;
+    Ok(i32::from_le_bytes(bytes))
+}
+
+fn copy_bytes(reader: &mut impl Read, writer: &mut impl Write, len: u64) -> std::io::Result<()> {
+    let copied = std::io::copy(&mut reader.take(len), writer)?;
+    if copied < len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
@@ -2,6 +2,25 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-fn main() {
-    queen_mama_lite_lib::run()
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // A migration check only reports what each database needs, then exits
+    if queen_mama_lite_lib::check_migrations_requested() {
+        return match queen_mama_lite_lib::check_migrations() {
+            Ok(check) => {
+                for line in &check.report {
+                    println!("{}", line);
+                }
+                ExitCode::from(u8::from(check.needs_attention))
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    queen_mama_lite_lib::run();
+    ExitCode::SUCCESS
 }
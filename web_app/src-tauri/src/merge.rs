@@ -0,0 +1,641 @@
+// Queen Mama LITE - Session Merge & Split
+// Joins sessions that were stopped and restarted mid-call into one, and cuts a session in two,
+// carrying transcript, notes, comments, tags, fields, recordings and redactions along on each
+// timeline
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use tauri::{AppHandle, Manager};
+
+use crate::audio::{self, RecordingInfo};
+use crate::db::{self, Database, SessionRecording};
+use crate::i18n::t;
+use crate::notes;
+use crate::session;
+use crate::transcript;
+
+/// The part of a session that places it in time
+struct SessionSpan {
+    id: String,
+    title: String,
+    started_at: i64,
+    ended_at: Option<i64>,
+    journal_date: Option<String>,
+}
+
+/// An attachment file moved to another session's folder, so it can be moved back
+struct MovedFile {
+    attachment_id: i64,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+/// A recording cut in two by a split, written next to the original until the split is saved
+struct CutRecording {
+    original: SessionRecording,
+    head: SessionRecording,
+    tail: SessionRecording,
+}
+
+/// Merge sessions into the earliest one, returning its id. The others' content moves onto its
+/// timeline, shifted by how much later they started, and they're deleted.
+#[tauri::command]
+pub async fn merge_sessions(app: AppHandle, ids: Vec<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || merge(&app, &ids))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Cut a session in two at `at_ms` on its timeline, returning the id of the new second half
+#[tauri::command]
+pub async fn split_session(app: AppHandle, id: String, at_ms: i64) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || split(&app, &id, at_ms))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+pub(crate) fn merge(app: &AppHandle, ids: &[String]) -> Result<String, String> {
+    let mut unique: Vec<&String> = Vec::new();
+    for id in ids {
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+    if unique.len() < 2 {
+        return Err(t!("error.merge_needs_sessions"));
+    }
+    if let Some(active) = session::active_session_id(app) {
+        if unique.contains(&&active) {
+            return Err(t!("error.session_recording"));
+        }
+    }
+
+    let db = app.state::<Database>();
+    let mut connection = db.connection()?;
+    let mut sessions = unique
+        .iter()
+        .map(|id| load_span(&connection, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    sessions.sort_by_key(|session| session.started_at);
+    let (target, sources) = sessions
+        .split_first()
+        .ok_or_else(|| t!("error.merge_needs_sessions"))?;
+
+    let mut moved = Vec::new();
+    for source in sources {
+        match move_attachments(app, &connection, &source.id, &target.id, i64::MIN) {
+            Ok(files) => moved.extend(files),
+            Err(e) => {
+                restore_files(&moved);
+                return Err(e);
+            }
+        }
+    }
+
+    let merged = connection
+        .transaction()
+        .and_then(|transaction| {
+            for source in sources {
+                merge_rows(&transaction, target, source)?;
+            }
+            let ended_at = sessions
+                .iter()
+                .map(|session| session.ended_at)
+                .max()
+                .flatten();
+            transaction.execute(
+                "UPDATE sessions SET ended_at = ?2, keywords_extracted_at = NULL WHERE id = ?1",
+                params![target.id, ended_at],
+            )?;
+            // No summary covers the whole merged session until one is generated
+            transaction.execute(
+                "UPDATE session_summaries SET is_primary = 0 WHERE session_id = ?1",
+                params![target.id],
+            )?;
+            transaction.execute(
+                "DELETE FROM session_keywords WHERE session_id = ?1",
+                params![target.id],
+            )?;
+            update_attachment_paths(&transaction, &moved)?;
+            transaction.commit()
+        })
+        .map_err(|e| e.to_string());
+    if let Err(e) = merged {
+        restore_files(&moved);
+        return Err(e);
+    }
+    drop(connection);
+
+    for source in sources {
+        remove_empty_dir(app, &source.id);
+        transcript::transcript_reset(app, &source.id);
+    }
+    transcript::transcript_reset(app, &target.id);
+    db::sessions_changed(app);
+    tracing::info!("Merged {} sessions into {}", sources.len(), target.id);
+    Ok(target.id.clone())
+}
+
+pub(crate) fn split(app: &AppHandle, id: &str, at_ms: i64) -> Result<String, String> {
+    if session::active_session_id(app).as_deref() == Some(id) {
+        return Err(t!("error.session_recording"));
+    }
+
+    let db = app.state::<Database>();
+    let mut connection = db.connection()?;
+    let original = load_span(&connection, id)?;
+    let length = original
+        .ended_at
+        .map(|ended_at| ended_at - original.started_at);
+    if at_ms <= 0 || length.is_some_and(|length| at_ms >= length) {
+        return Err(t!("error.split_out_of_range"));
+    }
+
+    let second = SessionSpan {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: t!("session.split_title", title = original.title),
+        started_at: original.started_at + at_ms,
+        ended_at: original.ended_at,
+        journal_date: None,
+    };
+
+    let recordings = db::session_recordings(&connection, id).map_err(|e| e.to_string())?;
+    let mut cuts = Vec::new();
+    for recording in recordings
+        .iter()
+        .filter(|recording| recording.offset_ms < at_ms)
+        .filter(|recording| recording.offset_ms + recording.duration_ms > at_ms)
+    {
+        match cut_recording(app, recording, at_ms - recording.offset_ms) {
+            Ok(cut) => cuts.push(cut),
+            Err(e) => {
+                discard_cuts(&cuts);
+                return Err(e);
+            }
+        }
+    }
+
+    let moved = match move_attachments(app, &connection, id, &second.id, second.started_at) {
+        Ok(moved) => moved,
+        Err(e) => {
+            discard_cuts(&cuts);
+            return Err(e);
+        }
+    };
+
+    let saved = connection
+        .transaction()
+        .and_then(|transaction| {
+            split_rows(&transaction, &original, &second, at_ms)?;
+            for cut in &cuts {
+                transaction.execute(
+                    "DELETE FROM session_recordings WHERE id = ?1",
+                    params![cut.original.id],
+                )?;
+                insert_recording(&transaction, id, &cut.head)?;
+                insert_recording(&transaction, &second.id, &cut.tail)?;
+            }
+            update_attachment_paths(&transaction, &moved)?;
+            transaction.commit()
+        })
+        .map_err(|e| e.to_string());
+    if let Err(e) = saved {
+        restore_files(&moved);
+        discard_cuts(&cuts);
+        return Err(e);
+    }
+    drop(connection);
+
+    for cut in &cuts {
+        if let Err(e) = audio::remove_recording(&cut.original.path) {
+            tracing::warn!(
+                "Failed to remove split recording {}: {}",
+                cut.original.id,
+                e
+            );
+        }
+    }
+    remove_empty_dir(app, id);
+    transcript::transcript_reset(app, id);
+    transcript::transcript_reset(app, &second.id);
+    db::sessions_changed(app);
+    tracing::info!("Split session {} at {} ms into {}", id, at_ms, second.id);
+    Ok(second.id)
+}
+
+fn load_span(connection: &Connection, id: &str) -> Result<SessionSpan, String> {
+    connection
+        .query_row(
+            "SELECT id, title, started_at, ended_at, journal_date FROM sessions
+             WHERE id = ?1 AND deleted_at IS NULL",
+            params![id],
+            |row| {
+                Ok(SessionSpan {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    started_at: row.get(2)?,
+                    ended_at: row.get(3)?,
+                    journal_date: row.get(4)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.session_not_found", id = id))
+}
+
+/// Move everything of `source` onto `target`'s timeline, then delete `source`
+fn merge_rows(
+    transaction: &Transaction,
+    target: &SessionSpan,
+    source: &SessionSpan,
+) -> rusqlite::Result<()> {
+    let offset = source.started_at - target.started_at;
+    transaction.execute(
+        "UPDATE transcript_segments SET session_id = ?1, start_ms = start_ms + ?3, end_ms = end_ms + ?3
+         WHERE session_id = ?2",
+        params![target.id, source.id, offset],
+    )?;
+    transaction.execute(
+        "UPDATE notes SET session_id = ?1 WHERE session_id = ?2",
+        params![target.id, source.id],
+    )?;
+    transaction.execute(
+        "UPDATE session_comments SET session_id = ?1, at_ms = at_ms + ?3 WHERE session_id = ?2",
+        params![target.id, source.id, offset],
+    )?;
+    transaction.execute(
+        "UPDATE session_flags SET session_id = ?1, at_ms = at_ms + ?3 WHERE session_id = ?2",
+        params![target.id, source.id, offset],
+    )?;
+    transaction.execute(
+        "UPDATE redactions SET session_id = ?1, start_ms = start_ms + ?3, end_ms = end_ms + ?3
+         WHERE session_id = ?2",
+        params![target.id, source.id, offset],
+    )?;
+    transaction.execute(
+        "UPDATE session_recordings SET session_id = ?1, offset_ms = offset_ms + ?3
+         WHERE session_id = ?2",
+        params![target.id, source.id, offset],
+    )?;
+    // Summaries of the parts are kept as earlier versions of the merged session's
+    let versions: i64 = transaction.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM session_summaries WHERE session_id = ?1",
+        params![target.id],
+        |row| row.get(0),
+    )?;
+    transaction.execute(
+        "UPDATE session_summaries SET session_id = ?1, version = version + ?3, is_primary = 0
+         WHERE session_id = ?2",
+        params![target.id, source.id, versions],
+    )?;
+    transaction.execute(
+        "INSERT OR IGNORE INTO session_tags (session_id, tag)
+         SELECT ?1, tag FROM session_tags WHERE session_id = ?2",
+        params![target.id, source.id],
+    )?;
+    // Where both have a value, the earlier session's is kept
+    transaction.execute(
+        "INSERT OR IGNORE INTO session_fields (session_id, name, value)
+         SELECT ?1, name, value FROM session_fields WHERE session_id = ?2",
+        params![target.id, source.id],
+    )?;
+    // A draft only moves over, with its history, when the earlier session has none
+    transaction.execute(
+        "UPDATE note_draft_revisions SET session_id = ?1
+         WHERE session_id = ?2 AND NOT EXISTS (SELECT 1 FROM note_drafts WHERE session_id = ?1)",
+        params![target.id, source.id],
+    )?;
+    transaction.execute(
+        "INSERT OR IGNORE INTO note_drafts (session_id, content, version, updated_at, updated_by)
+         SELECT ?1, content, version, updated_at, updated_by FROM note_drafts WHERE session_id = ?2",
+        params![target.id, source.id],
+    )?;
+    transaction.execute("DELETE FROM sessions WHERE id = ?1", params![source.id])?;
+    if source.journal_date.is_some() && target.journal_date.is_none() {
+        transaction.execute(
+            "UPDATE sessions SET journal_date = ?2 WHERE id = ?1",
+            params![target.id, source.journal_date],
+        )?;
+    }
+    Ok(())
+}
+
+/// Create `second` and move what happened from `at_ms` on into it, shifted to its own timeline
+fn split_rows(
+    transaction: &Transaction,
+    first: &SessionSpan,
+    second: &SessionSpan,
+    at_ms: i64,
+) -> rusqlite::Result<()> {
+    transaction.execute(
+        "INSERT INTO sessions (id, title, started_at, ended_at) VALUES (?1, ?2, ?3, ?4)",
+        params![second.id, second.title, second.started_at, second.ended_at],
+    )?;
+    transaction.execute(
+        "UPDATE sessions SET ended_at = ?2, keywords_extracted_at = NULL WHERE id = ?1",
+        params![first.id, second.started_at],
+    )?;
+    transaction.execute(
+        "DELETE FROM session_keywords WHERE session_id = ?1",
+        params![first.id],
+    )?;
+    // Summaries stay with the first part as earlier versions, covering more than it now holds
+    transaction.execute(
+        "UPDATE session_summaries SET is_primary = 0 WHERE session_id = ?1",
+        params![first.id],
+    )?;
+    transaction.execute(
+        "UPDATE transcript_segments SET session_id = ?2, start_ms = start_ms - ?3, end_ms = end_ms - ?3
+         WHERE session_id = ?1 AND start_ms >= ?3",
+        params![first.id, second.id, at_ms],
+    )?;
+    transaction.execute(
+        "UPDATE notes SET session_id = ?2 WHERE session_id = ?1 AND created_at >= ?3",
+        params![first.id, second.id, second.started_at],
+    )?;
+    // Replies sit at their thread's position, so threads stay together
+    transaction.execute(
+        "UPDATE session_comments SET session_id = ?2, at_ms = at_ms - ?3
+         WHERE session_id = ?1 AND at_ms >= ?3",
+        params![first.id, second.id, at_ms],
+    )?;
+    transaction.execute(
+        "UPDATE session_flags SET session_id = ?2, at_ms = at_ms - ?3
+         WHERE session_id = ?1 AND at_ms >= ?3",
+        params![first.id, second.id, at_ms],
+    )?;
+    // A redaction across the cut stays in the first part's audit trail
+    transaction.execute(
+        "UPDATE redactions SET session_id = ?2, start_ms = start_ms - ?3, end_ms = end_ms - ?3
+         WHERE session_id = ?1 AND start_ms >= ?3",
+        params![first.id, second.id, at_ms],
+    )?;
+    transaction.execute(
+        "UPDATE session_recordings SET session_id = ?2, offset_ms = offset_ms - ?3
+         WHERE session_id = ?1 AND offset_ms >= ?3",
+        params![first.id, second.id, at_ms],
+    )?;
+    transaction.execute(
+        "INSERT INTO session_tags (session_id, tag)
+         SELECT ?2, tag FROM session_tags WHERE session_id = ?1",
+        params![first.id, second.id],
+    )?;
+    transaction.execute(
+        "INSERT INTO session_fields (session_id, name, value)
+         SELECT ?2, name, value FROM session_fields WHERE session_id = ?1",
+        params![first.id, second.id],
+    )?;
+    Ok(())
+}
+
+fn insert_recording(
+    transaction: &Transaction,
+    session_id: &str,
+    recording: &SessionRecording,
+) -> rusqlite::Result<usize> {
+    transaction.execute(
+        "INSERT INTO session_recordings
+            (id, session_id, source, device, sample_rate, path, offset_ms, duration_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            recording.id,
+            session_id,
+            recording.source.as_str(),
+            recording.device,
+            recording.sample_rate,
+            recording.path.to_string_lossy(),
+            recording.offset_ms,
+            recording.duration_ms
+        ],
+    )
+}
+
+/// Write the two halves of a recording cut `cut_ms` into it as new files
+fn cut_recording(
+    app: &AppHandle,
+    recording: &SessionRecording,
+    cut_ms: i64,
+) -> Result<CutRecording, String> {
+    let samples = audio::read_recording(app, &recording.path)?;
+    let cut = ((cut_ms as u64 * recording.sample_rate as u64 / 1000) as usize).min(samples.len());
+    let dir = audio::recordings_dir(app)?;
+
+    let head = write_part(app, &dir, recording, &samples[..cut], recording.offset_ms)?;
+    let tail = match write_part(app, &dir, recording, &samples[cut..], 0) {
+        Ok(tail) => tail,
+        Err(e) => {
+            let _ = audio::remove_recording(&head.path);
+            return Err(e);
+        }
+    };
+    Ok(CutRecording {
+        original: recording.clone(),
+        head,
+        tail,
+    })
+}
+
+/// Save part of a recording as a new recording starting at `offset_ms`
+fn write_part(
+    app: &AppHandle,
+    dir: &Path,
+    recording: &SessionRecording,
+    samples: &[i16],
+    offset_ms: i64,
+) -> Result<SessionRecording, String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let info = RecordingInfo {
+        path: dir.join(format!("{}.wav", id)),
+        id,
+        source: recording.source,
+        device: recording.device.clone(),
+        sample_rate: recording.sample_rate,
+        started_at: 0,
+    };
+    let duration_ms = match audio::write_recording(app, &info, samples) {
+        Ok(duration_ms) => duration_ms as i64,
+        Err(e) => {
+            let _ = audio::remove_recording(&info.path);
+            return Err(e);
+        }
+    };
+    Ok(SessionRecording {
+        id: info.id,
+        source: info.source,
+        device: info.device,
+        sample_rate: info.sample_rate,
+        path: info.path,
+        offset_ms,
+        duration_ms,
+    })
+}
+
+fn discard_cuts(cuts: &[CutRecording]) {
+    for cut in cuts {
+        let _ = audio::remove_recording(&cut.head.path);
+        let _ = audio::remove_recording(&cut.tail.path);
+    }
+}
+
+/// Move the attachments of `from`'s notes created at or after `since` into `to`'s folder
+fn move_attachments(
+    app: &AppHandle,
+    connection: &Connection,
+    from: &str,
+    to: &str,
+    since: i64,
+) -> Result<Vec<MovedFile>, String> {
+    let attachments: Vec<(i64, String)> = connection
+        .prepare(
+            "SELECT a.id, a.path FROM note_attachments a JOIN notes n ON n.id = a.note_id
+             WHERE n.session_id = ?1 AND n.created_at >= ?2",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![from, since], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect()
+        })
+        .map_err(|e| e.to_string())?;
+    if attachments.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let dir = notes::attachments_dir(app, to)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let mut moved = Vec::new();
+    for (attachment_id, path) in attachments {
+        let from = PathBuf::from(path);
+        let Some(name) = from.file_name() else {
+            continue;
+        };
+        let to = dir.join(name);
+        if let Err(e) = fs::rename(&from, &to) {
+            restore_files(&moved);
+            return Err(e.to_string());
+        }
+        moved.push(MovedFile {
+            attachment_id,
+            from,
+            to,
+        });
+    }
+    Ok(moved)
+}
+
+fn update_attachment_paths(transaction: &Transaction, moved: &[MovedFile]) -> rusqlite::Result<()> {
+    for file in moved {
+        transaction.execute(
+            "UPDATE note_attachments SET path = ?2 WHERE id = ?1",
+            params![file.attachment_id, file.to.to_string_lossy()],
+        )?;
+    }
+    Ok(())
+}
+
+fn restore_files(moved: &[MovedFile]) {
+    for file in moved {
+        if let Err(e) = fs::rename(&file.to, &file.from) {
+            tracing::error!(path = ?file.to, "Failed to move a file back: {}", e);
+        }
+    }
+}
+
+/// Drop a session's attachments folder once nothing is left in it
+fn remove_empty_dir(app: &AppHandle, session_id: &str) {
+    if let Ok(dir) = notes::attachments_dir(app, session_id) {
+        // Fails, as it should, while the folder still holds files
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::{params, Connection};
+
+    use super::{merge_rows, split_rows, SessionSpan};
+    use crate::db;
+
+    fn session(connection: &Connection, id: &str, started_at: i64) -> SessionSpan {
+        connection
+            .execute(
+                "INSERT INTO sessions (id, title, started_at) VALUES (?1, ?1, ?2)",
+                params![id, started_at],
+            )
+            .unwrap();
+        SessionSpan {
+            id: id.to_string(),
+            title: id.to_string(),
+            started_at,
+            ended_at: None,
+            journal_date: None,
+        }
+    }
+
+    fn redact(connection: &Connection, session_id: &str, start_ms: i64, end_ms: i64) {
+        connection
+            .execute(
+                "INSERT INTO redactions
+                    (session_id, start_ms, end_ms, segments, recordings, created_at)
+                 VALUES (?1, ?2, ?3, 1, 0, 0)",
+                params![session_id, start_ms, end_ms],
+            )
+            .unwrap();
+    }
+
+    fn redactions(connection: &Connection, session_id: &str) -> Vec<(i64, i64)> {
+        connection
+            .prepare("SELECT start_ms, end_ms FROM redactions WHERE session_id = ?1 ORDER BY id")
+            .unwrap()
+            .query_map(params![session_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn merge_moves_redactions_onto_the_earlier_timeline() {
+        let mut connection = db::open_in_memory();
+        let first = session(&connection, "first", 1_000);
+        let second = session(&connection, "second", 61_000);
+        redact(&connection, "first", 2_000, 3_000);
+        redact(&connection, "second", 5_000, 6_000);
+
+        let transaction = connection.transaction().unwrap();
+        merge_rows(&transaction, &first, &second).unwrap();
+        transaction.commit().unwrap();
+
+        assert_eq!(
+            redactions(&connection, "first"),
+            vec![(2_000, 3_000), (65_000, 66_000)]
+        );
+    }
+
+    #[test]
+    fn split_moves_later_redactions_onto_the_new_timeline() {
+        let mut connection = db::open_in_memory();
+        let first = session(&connection, "first", 1_000);
+        redact(&connection, "first", 10_000, 12_000);
+        redact(&connection, "first", 59_000, 61_000);
+        redact(&connection, "first", 70_000, 71_000);
+        let second = SessionSpan {
+            id: "second".to_string(),
+            title: "second".to_string(),
+            started_at: 61_000,
+            ended_at: None,
+            journal_date: None,
+        };
+
+        let transaction = connection.transaction().unwrap();
+        split_rows(&transaction, &first, &second, 60_000).unwrap();
+        transaction.commit().unwrap();
+
+        assert_eq!(
+            redactions(&connection, "first"),
+            vec![(10_000, 12_000), (59_000, 61_000)]
+        );
+        assert_eq!(redactions(&connection, "second"), vec![(10_000, 11_000)]);
+    }
+}
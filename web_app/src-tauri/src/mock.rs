@@ -0,0 +1,157 @@
+// Queen Mama LITE - Mock Providers
+// Stand-in AI and transcription providers with set latency, canned output and injected failures
+
+use std::time::Duration;
+
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::audio::AudioSource;
+use crate::{demo, SETTINGS_STORE};
+
+pub(crate) const MOCK_PROVIDERS_STORE_KEY: &str = "mock_providers";
+
+/// Whisper model name that selects the mock transcriber
+pub const MOCK_MODEL: &str = "mock";
+
+/// Custom transcript lines are timed at this pace
+const WORD_MS: i64 = 350;
+const LINE_GAP_MS: i64 = 500;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MockConfig {
+    /// Wait before a reply starts, or after a line is spoken before its segment arrives
+    pub latency_ms: u64,
+    /// Wait between streamed words of a reply
+    pub token_delay_ms: u64,
+    /// Reply to every prompt; the demo's canned replies when unset
+    pub reply: Option<String>,
+    /// Lines to transcribe, alternating between me and them; the demo call when empty
+    pub transcript: Vec<String>,
+    pub failure: MockFailure,
+    /// Share of replies and transcript lines that fail, 0.0 to 1.0
+    pub failure_rate: f64,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            latency_ms: 0,
+            token_delay_ms: 40,
+            reply: None,
+            transcript: Vec::new(),
+            failure: MockFailure::None,
+            failure_rate: 1.0,
+        }
+    }
+}
+
+/// How a mock provider fails when it does
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MockFailure {
+    #[default]
+    None,
+    /// Replies fail with a server error; the transcriber stops
+    Error,
+    /// Replies fail with 429 and the quota warning; the transcriber stops
+    RateLimited,
+    /// Replies hang until they time out; transcript lines never arrive
+    Timeout,
+    /// Replies stop halfway through; transcript lines stop at their partial
+    Disconnect,
+}
+
+/// A line the mock transcriber will hear, timed from the start of transcription
+pub struct MockLine {
+    pub source: AudioSource,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+impl MockConfig {
+    pub fn latency(&self) -> Duration {
+        Duration::from_millis(self.latency_ms)
+    }
+
+    pub fn token_delay(&self) -> Duration {
+        Duration::from_millis(self.token_delay_ms)
+    }
+
+    /// The failure to inject into this call, if it should fail
+    pub fn roll_failure(&self) -> Option<MockFailure> {
+        (self.failure != MockFailure::None && fastrand::f64() < self.failure_rate)
+            .then_some(self.failure)
+    }
+
+    /// The reply to a prompt
+    pub fn reply_to(&self, prompt: &str) -> String {
+        match &self.reply {
+            Some(reply) => reply.clone(),
+            None => demo::canned_reply(prompt).to_string(),
+        }
+    }
+
+    /// The custom lines, or the demo call when there are none
+    pub fn lines(&self) -> Vec<MockLine> {
+        if self.transcript.is_empty() {
+            return demo::TRANSCRIPT
+                .iter()
+                .map(|line| MockLine {
+                    source: line.source,
+                    start_ms: line.start_ms,
+                    end_ms: line.end_ms,
+                    text: line.text.to_string(),
+                })
+                .collect();
+        }
+
+        let mut start_ms = LINE_GAP_MS;
+        self.transcript
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let end_ms = start_ms + text.split_whitespace().count().max(1) as i64 * WORD_MS;
+                let line = MockLine {
+                    source: if index % 2 == 0 {
+                        AudioSource::Microphone
+                    } else {
+                        AudioSource::System
+                    },
+                    start_ms,
+                    end_ms,
+                    text: text.clone(),
+                };
+                start_ms = end_ms + LINE_GAP_MS;
+                line
+            })
+            .collect()
+    }
+}
+
+/// Get the mock providers' latency, output and failures
+#[tauri::command]
+pub fn get_mock_config(app: AppHandle) -> MockConfig {
+    load_config(&app)
+}
+
+/// Set the mock providers' latency, output and failures; applies from the next reply or transcription
+#[tauri::command]
+pub fn set_mock_config(app: AppHandle, config: MockConfig) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        MOCK_PROVIDERS_STORE_KEY,
+        serde_json::to_value(&config).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+pub fn load_config(app: &AppHandle) -> MockConfig {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(MOCK_PROVIDERS_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
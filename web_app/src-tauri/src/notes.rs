@@ -0,0 +1,595 @@
+// Queen Mama LITE - Session Notes
+// Autosaves note drafts across windows, keeps recent revisions, and stores note attachments
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::{App, AppHandle, Manager, State, WebviewWindow};
+
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::vault;
+
+/// Typing pauses this long before a draft is written to disk
+const DRAFT_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Revisions kept per session, oldest dropped first
+const MAX_REVISIONS: i64 = 20;
+
+#[derive(Default)]
+pub struct NotesState {
+    /// Latest draft per session, ahead of what's on disk until the debounced write lands
+    drafts: Mutex<HashMap<String, PendingDraft>>,
+}
+
+struct PendingDraft {
+    draft: NoteDraft,
+    /// Bumped on every save so only the last one in a burst gets written
+    generation: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteDraft {
+    session_id: String,
+    content: String,
+    version: i64,
+    updated_at: i64,
+    /// Label of the window that last saved the draft
+    updated_by: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum DraftSaveResult {
+    Saved { draft: NoteDraft },
+    /// Another window saved since `base_version`; merge with `current` and save again
+    Conflict { current: NoteDraft },
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRevision {
+    version: i64,
+    content: String,
+    saved_at: i64,
+}
+
+/// A screenshot or file stored with a note, copied under the session's attachments folder
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    id: i64,
+    note_id: i64,
+    file_name: String,
+    mime_type: String,
+    path: String,
+    size: i64,
+    created_at: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSearchHit {
+    session_id: String,
+    title: String,
+    note_id: i64,
+    /// Set when the match is an attachment's file name rather than the note text
+    attachment_id: Option<i64>,
+    snippet: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DraftChanged<'a> {
+    session_id: &'a str,
+    version: i64,
+    updated_by: Option<&'a str>,
+}
+
+pub fn setup_notes(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(NotesState::default());
+    Ok(())
+}
+
+/// Save the notes being typed for a session. Pass the version the edit started from;
+/// if another window saved in the meantime the save is refused with the current draft.
+#[tauri::command]
+pub fn save_note_draft(
+    app: AppHandle,
+    window: WebviewWindow,
+    notes: State<'_, NotesState>,
+    db: State<'_, Database>,
+    session_id: String,
+    content: String,
+    base_version: Option<i64>,
+) -> Result<DraftSaveResult, String> {
+    let mut drafts = notes.drafts.lock().map_err(|e| e.to_string())?;
+
+    let current = match drafts.get(&session_id) {
+        Some(pending) => Some(pending.draft.clone()),
+        None => load_draft(&db, &session_id)?,
+    };
+    let current_version = current.as_ref().map(|draft| draft.version).unwrap_or(0);
+
+    if let (Some(current), Some(base_version)) = (&current, base_version) {
+        if base_version != current_version && current.updated_by.as_deref() != Some(window.label()) {
+            return Ok(DraftSaveResult::Conflict {
+                current: current.clone(),
+            });
+        }
+    }
+
+    let draft = NoteDraft {
+        session_id: session_id.clone(),
+        content,
+        version: current_version + 1,
+        updated_at: db::now_ms(),
+        updated_by: Some(window.label().to_string()),
+    };
+    let generation = drafts
+        .get(&session_id)
+        .map(|pending| pending.generation + 1)
+        .unwrap_or(0);
+    drafts.insert(
+        session_id.clone(),
+        PendingDraft {
+            draft: draft.clone(),
+            generation,
+        },
+    );
+    drop(drafts);
+
+    // Let other windows showing this session pick up the change
+    let _ = app.emit(
+        "note_draft_changed",
+        DraftChanged {
+            session_id: &session_id,
+            version: draft.version,
+            updated_by: draft.updated_by.as_deref(),
+        },
+    );
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(DRAFT_SAVE_DELAY).await;
+        if let Err(e) = flush_draft(&app, &session_id, generation) {
+            tracing::error!("Failed to save draft: {}", e);
+        }
+    });
+
+    Ok(DraftSaveResult::Saved { draft })
+}
+
+/// Get the latest notes draft for a session
+#[tauri::command]
+pub fn get_note_draft(
+    notes: State<'_, NotesState>,
+    db: State<'_, Database>,
+    session_id: String,
+) -> Result<Option<NoteDraft>, String> {
+    if let Some(pending) = notes
+        .drafts
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&session_id)
+    {
+        return Ok(Some(pending.draft.clone()));
+    }
+    load_draft(&db, &session_id)
+}
+
+/// List saved revisions of a session's notes, newest first
+#[tauri::command]
+pub fn list_note_revisions(
+    db: State<'_, Database>,
+    session_id: String,
+) -> Result<Vec<NoteRevision>, String> {
+    let connection = db.connection()?;
+    let mut statement = connection
+        .prepare(
+            "SELECT version, content, saved_at FROM note_draft_revisions
+             WHERE session_id = ?1 ORDER BY version DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let revisions = statement
+        .query_map(params![session_id], |row| {
+            Ok(NoteRevision {
+                version: row.get(0)?,
+                content: row.get(1)?,
+                saved_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(revisions)
+}
+
+/// Write a draft to disk unless a newer save has superseded it
+fn flush_draft(app: &AppHandle, session_id: &str, generation: u64) -> Result<(), String> {
+    let notes = app.state::<NotesState>();
+    let draft = {
+        let drafts = notes.drafts.lock().map_err(|e| e.to_string())?;
+        match drafts.get(session_id) {
+            Some(pending) if pending.generation == generation => pending.draft.clone(),
+            _ => return Ok(()),
+        }
+    };
+
+    let db = app.state::<Database>();
+    let mut connection = db.connection()?;
+    let transaction = connection.transaction().map_err(|e| e.to_string())?;
+    transaction
+        .execute(
+            "INSERT INTO note_drafts (session_id, content, version, updated_at, updated_by)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(session_id) DO UPDATE SET
+                content = excluded.content,
+                version = excluded.version,
+                updated_at = excluded.updated_at,
+                updated_by = excluded.updated_by",
+            params![
+                draft.session_id,
+                draft.content,
+                draft.version,
+                draft.updated_at,
+                draft.updated_by
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    transaction
+        .execute(
+            "INSERT INTO note_draft_revisions (session_id, version, content, saved_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![draft.session_id, draft.version, draft.content, draft.updated_at],
+        )
+        .map_err(|e| e.to_string())?;
+    transaction
+        .execute(
+            "DELETE FROM note_draft_revisions WHERE session_id = ?1 AND id NOT IN (
+                SELECT id FROM note_draft_revisions WHERE session_id = ?1
+                ORDER BY version DESC LIMIT ?2
+             )",
+            params![draft.session_id, MAX_REVISIONS],
+        )
+        .map_err(|e| e.to_string())?;
+    transaction.commit().map_err(|e| e.to_string())?;
+    drop(connection);
+
+    // Once on disk the database is the source of truth again
+    let mut drafts = notes.drafts.lock().map_err(|e| e.to_string())?;
+    if drafts
+        .get(session_id)
+        .is_some_and(|pending| pending.generation == generation)
+    {
+        drafts.remove(session_id);
+    }
+    Ok(())
+}
+
+fn load_draft(db: &Database, session_id: &str) -> Result<Option<NoteDraft>, String> {
+    let connection = db.connection()?;
+    connection
+        .query_row(
+            "SELECT session_id, content, version, updated_at, updated_by FROM note_drafts
+             WHERE session_id = ?1",
+            params![session_id],
+            |row| {
+                Ok(NoteDraft {
+                    session_id: row.get(0)?,
+                    content: row.get(1)?,
+                    version: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    updated_by: row.get(4)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// Attach a file (e.g. a screenshot saved by `capture_screen`) to a note
+#[tauri::command]
+pub fn add_note_attachment(
+    app: AppHandle,
+    db: State<'_, Database>,
+    note_id: i64,
+    path: String,
+) -> Result<Attachment, String> {
+    let source = PathBuf::from(&path);
+    let file_name = source
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| t!("error.not_a_file", path = path))?
+        .to_string();
+
+    let connection = db.connection()?;
+    let session_id: String = connection
+        .query_row(
+            "SELECT session_id FROM notes WHERE id = ?1",
+            params![note_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.note_not_found", id = note_id))?;
+
+    let dir = attachments_dir(&app, &session_id)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let destination = dir.join(format!("{}-{}", uuid::Uuid::new_v4(), file_name));
+    let size = vault::copy(&app, &source, &destination)? as i64;
+
+    let attachment = Attachment {
+        id: 0,
+        note_id,
+        mime_type: mime_type(&file_name).to_string(),
+        file_name,
+        path: destination.to_string_lossy().into_owned(),
+        size,
+        created_at: db::now_ms(),
+    };
+    let inserted = connection.execute(
+        "INSERT INTO note_attachments (note_id, file_name, mime_type, path, size, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            attachment.note_id,
+            attachment.file_name,
+            attachment.mime_type,
+            attachment.path,
+            attachment.size,
+            attachment.created_at
+        ],
+    );
+    if let Err(e) = inserted {
+        let _ = fs::remove_file(&destination);
+        return Err(e.to_string());
+    }
+
+    Ok(Attachment {
+        id: connection.last_insert_rowid(),
+        ..attachment
+    })
+}
+
+/// Remove an attachment and its stored file
+#[tauri::command]
+pub fn delete_note_attachment(db: State<'_, Database>, id: i64) -> Result<(), String> {
+    let connection = db.connection()?;
+    let path: Option<String> = connection
+        .query_row(
+            "SELECT path FROM note_attachments WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    connection
+        .execute("DELETE FROM note_attachments WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    if let Some(path) = path {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Full-text search across notes and attachment names
+#[tauri::command]
+pub fn search_notes(
+    db: State<'_, Database>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<NoteSearchHit>, String> {
+    let match_query = db::fts_query(&query);
+    if match_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let connection = db.connection()?;
+    let mut statement = connection
+        .prepare(
+            "SELECT s.id, s.title, n.id, NULL, snippet(notes_fts, 0, '[', ']', '…', 12), rank
+             FROM notes_fts
+             JOIN notes n ON n.id = notes_fts.rowid
+             JOIN sessions s ON s.id = n.session_id
+             WHERE notes_fts MATCH ?1 AND s.deleted_at IS NULL
+             UNION ALL
+             SELECT s.id, s.title, n.id, a.id, snippet(attachment_fts, 0, '[', ']', '…', 12), rank
+             FROM attachment_fts
+             JOIN note_attachments a ON a.id = attachment_fts.rowid
+             JOIN notes n ON n.id = a.note_id
+             JOIN sessions s ON s.id = n.session_id
+             WHERE attachment_fts MATCH ?1 AND s.deleted_at IS NULL
+             ORDER BY 6
+             LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let hits = statement
+        .query_map(params![match_query, limit.unwrap_or(50)], |row| {
+            Ok(NoteSearchHit {
+                session_id: row.get(0)?,
+                title: row.get(1)?,
+                note_id: row.get(2)?,
+                attachment_id: row.get(3)?,
+                snippet: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(hits)
+}
+
+/// Export a session's notes as Markdown into `directory`, copying attachments next to it, all
+/// encrypted if the profile has a passphrase. Returns the path of the Markdown file.
+#[tauri::command]
+pub fn export_session_notes(
+    app: AppHandle,
+    db: State<'_, Database>,
+    session_id: String,
+    directory: String,
+) -> Result<String, String> {
+    let connection = db.connection()?;
+    let (title, started_at): (String, i64) = connection
+        .query_row(
+            "SELECT title, started_at FROM sessions WHERE id = ?1",
+            params![session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.session_not_found", id = session_id))?;
+
+    let notes = connection
+        .prepare(
+            "SELECT id, kind, content, created_at FROM notes
+             WHERE session_id = ?1 ORDER BY created_at, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, i64>(3)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+    let mut attachments = session_attachments(&connection, &session_id)?;
+    drop(connection);
+
+    let directory = PathBuf::from(directory);
+    let attachments_out = directory.join("attachments");
+    let mut markdown = format!(
+        "# {}\n\n_{}_\n",
+        title,
+        t!("document.started", time = format_time(started_at))
+    );
+
+    for (id, kind, content, created_at) in notes {
+        let heading = if kind == "suggestion" {
+            t!("document.suggestion")
+        } else {
+            t!("document.note")
+        };
+        let _ = write!(
+            markdown,
+            "\n## {} · {}\n\n{}\n",
+            heading,
+            format_time(created_at),
+            content.trim()
+        );
+
+        for attachment in attachments.remove(&id).unwrap_or_default() {
+            fs::create_dir_all(&attachments_out).map_err(|e| e.to_string())?;
+            let name = format!("{}-{}", attachment.id, attachment.file_name);
+            vault::copy(&app, Path::new(&attachment.path), &attachments_out.join(&name))?;
+
+            let link = format!("attachments/{}", name.replace(' ', "%20"));
+            let image = if attachment.mime_type.starts_with("image/") { "!" } else { "" };
+            let _ = write!(markdown, "\n{}[{}]({})\n", image, attachment.file_name, link);
+        }
+    }
+
+    let file = directory.join(format!("{}.md", sanitize_file_name(&title)));
+    vault::write(&app, &file, markdown)?;
+    Ok(file.to_string_lossy().into_owned())
+}
+
+/// Folder holding a session's attachments
+pub(crate) fn attachments_dir(app: &AppHandle, session_id: &str) -> Result<PathBuf, String> {
+    Ok(crate::profiles::active_data_dir(app)?
+        .join("attachments")
+        .join(session_id))
+}
+
+/// Attachments of every note in a session, by note id
+pub(crate) fn session_attachments(
+    connection: &Connection,
+    session_id: &str,
+) -> Result<HashMap<i64, Vec<Attachment>>, String> {
+    let mut statement = connection
+        .prepare(
+            "SELECT a.id, a.note_id, a.file_name, a.mime_type, a.path, a.size, a.created_at
+             FROM note_attachments a JOIN notes n ON n.id = a.note_id
+             WHERE n.session_id = ?1 ORDER BY a.created_at, a.id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut attachments: HashMap<i64, Vec<Attachment>> = HashMap::new();
+    let rows = statement
+        .query_map(params![session_id], |row| {
+            Ok(Attachment {
+                id: row.get(0)?,
+                note_id: row.get(1)?,
+                file_name: row.get(2)?,
+                mime_type: row.get(3)?,
+                path: row.get(4)?,
+                size: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    for attachment in rows {
+        let attachment = attachment.map_err(|e| e.to_string())?;
+        attachments
+            .entry(attachment.note_id)
+            .or_default()
+            .push(attachment);
+    }
+    Ok(attachments)
+}
+
+fn mime_type(file_name: &str) -> &'static str {
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Session titles are free text; keep them usable as a file name
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || " -_".contains(c) { c } else { '_' })
+        .collect();
+    match name.trim() {
+        "" => "notes".to_string(),
+        name => name.to_string(),
+    }
+}
+
+pub(crate) fn format_time(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,446 @@
+// Queen Mama LITE - Notifications
+// Native notifications for background events, with actions routed back into the app
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample};
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_notification::{NotificationExt, PermissionState};
+use tauri_plugin_store::StoreExt;
+
+#[cfg(desktop)]
+use crate::events::VersionedEmitter;
+use crate::focus;
+use crate::i18n::t;
+use crate::window;
+use crate::SETTINGS_STORE;
+
+pub(crate) const NOTIFICATION_SOUNDS_STORE_KEY: &str = "notification_sounds";
+
+/// Quota warnings repeat at most this often
+const QUOTA_WARNING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Longest custom sound accepted; notification sounds should be short
+const MAX_SOUND_LENGTH: Duration = Duration::from_secs(10);
+
+/// Name each OS gives its own notification sound
+#[cfg(not(target_os = "linux"))]
+const DEFAULT_SOUND_NAME: &str = "Default";
+#[cfg(target_os = "linux")]
+const DEFAULT_SOUND_NAME: &str = "message-new-instant";
+
+#[derive(Default)]
+pub struct NotificationState {
+    /// When each kind was last shown, for kinds that shouldn't repeat often
+    last_shown: Mutex<HashMap<NoticeKind, Instant>>,
+    /// Custom sounds already decoded, by file
+    sounds: Mutex<HashMap<PathBuf, Arc<DecodedSound>>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NoticeKind {
+    SessionSaved,
+    ModelDownloaded,
+    QuotaNearLimit,
+    Update,
+    CrashReport,
+}
+
+impl NoticeKind {
+    const ALL: [NoticeKind; 5] = [
+        NoticeKind::SessionSaved,
+        NoticeKind::ModelDownloaded,
+        NoticeKind::QuotaNearLimit,
+        NoticeKind::Update,
+        NoticeKind::CrashReport,
+    ];
+
+    /// Used to name the kind's cached sound file
+    fn id(self) -> &'static str {
+        match self {
+            NoticeKind::SessionSaved => "session_saved",
+            NoticeKind::ModelDownloaded => "model_downloaded",
+            NoticeKind::QuotaNearLimit => "quota_near_limit",
+            NoticeKind::Update => "update",
+            NoticeKind::CrashReport => "crash_report",
+        }
+    }
+}
+
+/// What plays when a kind of notification is shown
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NotificationSound {
+    /// The OS notification sound
+    #[default]
+    Default,
+    Silent,
+    /// A sound the OS ships, by name
+    #[serde(rename_all = "camelCase")]
+    System { name: String },
+    /// A WAV file, copied into the app data dir when chosen
+    #[serde(rename_all = "camelCase")]
+    Custom { path: PathBuf },
+}
+
+/// A custom sound, downmixed to mono
+struct DecodedSound {
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+/// What a notification button does, run by `run_notification_action`
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NoticeAction {
+    #[serde(rename_all = "camelCase")]
+    OpenSession { session_id: String },
+    OpenDashboard,
+    OpenSettings,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NoticeButton {
+    label: String,
+    action: NoticeAction,
+}
+
+/// Emitted as `notification` so the app can show it as a toast with its buttons
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Notice {
+    id: String,
+    kind: NoticeKind,
+    title: String,
+    body: String,
+    actions: Vec<NoticeButton>,
+    sound: NotificationSound,
+}
+
+pub fn setup_notifications(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(NotificationState::default());
+    Ok(())
+}
+
+/// Run a notification button's action
+#[tauri::command]
+pub fn run_notification_action(app: AppHandle, action: NoticeAction) -> Result<(), String> {
+    match action {
+        NoticeAction::OpenSession { session_id } => window::open_session(&app, &session_id),
+        NoticeAction::OpenDashboard => window::show_main(&app),
+        NoticeAction::OpenSettings => {
+            window::show_main(&app)?;
+            app.emit("open_settings", ()).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Get the sound played for each kind of notification
+#[tauri::command]
+pub fn get_notification_sounds(app: AppHandle) -> HashMap<NoticeKind, NotificationSound> {
+    let mut sounds = load_sounds(&app);
+    for kind in NoticeKind::ALL {
+        sounds.entry(kind).or_default();
+    }
+    sounds
+}
+
+/// Choose the sound for a kind of notification; custom files are checked and copied into the app
+#[tauri::command]
+pub fn set_notification_sound(
+    app: AppHandle,
+    state: State<'_, NotificationState>,
+    kind: NoticeKind,
+    sound: NotificationSound,
+) -> Result<NotificationSound, String> {
+    let sound = match sound {
+        NotificationSound::Custom { path } => {
+            let decoded = decode_sound(&path)?;
+            let dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| e.to_string())?
+                .join("sounds");
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            let cached = dir.join(format!("{}.wav", kind.id()));
+            fs::copy(&path, &cached).map_err(|e| e.to_string())?;
+            state
+                .sounds
+                .lock()
+                .unwrap()
+                .insert(cached.clone(), Arc::new(decoded));
+            NotificationSound::Custom { path: cached }
+        }
+        sound => sound,
+    };
+
+    let mut sounds = load_sounds(&app);
+    sounds.insert(kind, sound.clone());
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        NOTIFICATION_SOUNDS_STORE_KEY,
+        serde_json::to_value(&sounds).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+    Ok(sound)
+}
+
+/// Show a sample notification of a kind with its sound, whether or not the app has focus
+#[tauri::command]
+pub fn preview_notification(app: AppHandle, event_type: NoticeKind) -> Result<(), String> {
+    let sound = sound_for(&app, event_type);
+    show_native(
+        &app,
+        &t!("notice.preview.title"),
+        &t!("notice.preview.body"),
+        &sound,
+    )
+}
+
+/// Tell the user about a background event. The app always gets a `notification` event;
+/// a native notification is shown too when none of our windows has focus.
+pub fn notify(
+    app: &AppHandle,
+    kind: NoticeKind,
+    title: &str,
+    body: &str,
+    actions: Vec<(String, NoticeAction)>,
+) {
+    if !should_show(app, kind) {
+        return;
+    }
+
+    let sound = sound_for(app, kind);
+    let notice = Notice {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        title: title.to_string(),
+        body: body.to_string(),
+        actions: actions
+            .into_iter()
+            .map(|(label, action)| NoticeButton { label, action })
+            .collect(),
+        sound: sound.clone(),
+    };
+    if let Err(e) = app.emit("notification", &notice) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+
+    // During a presentation the app still gets the notice, but nothing pops up or plays
+    #[cfg(desktop)]
+    if focus::silences_notifications(app) {
+        return;
+    }
+
+    let focused = app
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false));
+    if focused {
+        // The toast has no sound of its own, so custom sounds still play
+        if let NotificationSound::Custom { .. } = sound {
+            play_custom(app, &sound);
+        }
+        return;
+    }
+    if let Err(e) = show_native(app, title, body, &sound) {
+        tracing::error!("Failed to show notification: {}", e);
+    }
+}
+
+/// Whether the OS lets us show native notifications
+pub fn permission_granted(app: &AppHandle) -> bool {
+    matches!(
+        app.notification().permission_state(),
+        Ok(PermissionState::Granted)
+    )
+}
+
+/// Native notifications only know the OS's own sounds, so custom ones are played here
+fn show_native(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    sound: &NotificationSound,
+) -> Result<(), String> {
+    let builder = app.notification().builder().title(title).body(body);
+    let builder = match sound {
+        NotificationSound::Default => builder.sound(DEFAULT_SOUND_NAME),
+        NotificationSound::System { name } => builder.sound(name),
+        NotificationSound::Silent | NotificationSound::Custom { .. } => builder,
+    };
+    builder.show().map_err(|e| e.to_string())?;
+    play_custom(app, sound);
+    Ok(())
+}
+
+fn sound_for(app: &AppHandle, kind: NoticeKind) -> NotificationSound {
+    load_sounds(app).remove(&kind).unwrap_or_default()
+}
+
+fn load_sounds(app: &AppHandle) -> HashMap<NoticeKind, NotificationSound> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(NOTIFICATION_SOUNDS_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Play a custom sound on the default output device, in the background
+fn play_custom(app: &AppHandle, sound: &NotificationSound) {
+    let NotificationSound::Custom { path } = sound else {
+        return;
+    };
+    let Some(state) = app.try_state::<NotificationState>() else {
+        return;
+    };
+
+    let Ok(cached) = state.sounds.lock().map(|sounds| sounds.get(path).cloned()) else {
+        return;
+    };
+    let decoded = match cached {
+        Some(decoded) => decoded,
+        None => match decode_sound(path) {
+            Ok(decoded) => {
+                let decoded = Arc::new(decoded);
+                if let Ok(mut sounds) = state.sounds.lock() {
+                    sounds.insert(path.clone(), decoded.clone());
+                }
+                decoded
+            }
+            Err(e) => {
+                tracing::error!(path = ?path, "Failed to load a sound: {}", e);
+                return;
+            }
+        },
+    };
+
+    std::thread::spawn(move || {
+        if let Err(e) = play(&decoded) {
+            tracing::error!("Failed to play sound: {}", e);
+        }
+    });
+}
+
+/// Check a custom sound is a short, readable WAV file and decode it
+fn decode_sound(path: &Path) -> Result<DecodedSound, String> {
+    let is_wav = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("wav"));
+    if !is_wav {
+        return Err(t!("error.sound_not_wav", path = path.display()));
+    }
+
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| t!("error.sound_unreadable", path = path.display(), error = e))?;
+    let spec = reader.spec();
+    let frames = reader.duration() as u64;
+    if frames * 1000 / spec.sample_rate.max(1) as u64 > MAX_SOUND_LENGTH.as_millis() as u64 {
+        return Err(t!(
+            "error.sound_too_long",
+            seconds = MAX_SOUND_LENGTH.as_secs()
+        ));
+    }
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect::<Result<_, _>>()
+        }
+    }
+    .map_err(|e| t!("error.sound_unreadable", path = path.display(), error = e))?;
+
+    let channels = spec.channels.max(1) as usize;
+    let samples = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    Ok(DecodedSound {
+        sample_rate: spec.sample_rate,
+        samples,
+    })
+}
+
+/// Blocks until the sound has finished
+fn play(sound: &Arc<DecodedSound>) -> Result<(), String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| t!("error.no_sound_output"))?;
+    let config = device.default_output_config().map_err(|e| e.to_string())?;
+    let stream_config = config.config();
+
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => build_output_stream::<f32>(&device, &stream_config, sound.clone()),
+        SampleFormat::I16 => build_output_stream::<i16>(&device, &stream_config, sound.clone()),
+        SampleFormat::U16 => build_output_stream::<u16>(&device, &stream_config, sound.clone()),
+        SampleFormat::I32 => build_output_stream::<i32>(&device, &stream_config, sound.clone()),
+        format => Err(t!("error.unsupported_sample_format", format = format)),
+    }?;
+    stream.play().map_err(|e| e.to_string())?;
+
+    let length = sound.samples.len() as u64 * 1000 / sound.sample_rate.max(1) as u64;
+    std::thread::sleep(Duration::from_millis(length) + Duration::from_millis(100));
+    Ok(())
+}
+
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sound: Arc<DecodedSound>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    // Nearest-sample resampling is plenty for a short chime
+    let step = sound.sample_rate as f64 / config.sample_rate.0 as f64;
+    let mut position = 0.0;
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                for frame in data.chunks_mut(channels) {
+                    let sample = sound.samples.get(position as usize).copied().unwrap_or(0.0);
+                    frame.fill(sample.to_sample::<T>());
+                    position += step;
+                }
+            },
+            |e| tracing::warn!("Stream error: {}", e),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+fn should_show(app: &AppHandle, kind: NoticeKind) -> bool {
+    let min_interval = match kind {
+        NoticeKind::QuotaNearLimit => QUOTA_WARNING_INTERVAL,
+        _ => return true,
+    };
+
+    let Some(state) = app.try_state::<NotificationState>() else {
+        return true;
+    };
+    let Ok(mut last_shown) = state.last_shown.lock() else {
+        return true;
+    };
+    if last_shown
+        .get(&kind)
+        .is_some_and(|shown| shown.elapsed() < min_interval)
+    {
+        return false;
+    }
+    last_shown.insert(kind, Instant::now());
+    true
+}
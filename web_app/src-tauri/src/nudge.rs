@@ -0,0 +1,95 @@
+// Queen Mama LITE - Haptic Nudges
+// Discreet per-rule nudges forwarded through the companion relay to a paired phone/watch
+
+use std::collections::HashMap;
+
+use tauri::{AppHandle, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::relay::{self, RelayState};
+use crate::SETTINGS_STORE;
+
+pub(crate) const NUDGE_STORE_KEY: &str = "haptic_nudges";
+
+/// Rule fired automatically when an AI suggestion is published to the relay
+pub const SUGGESTION_RULE: &str = "suggestion";
+
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum HapticPattern {
+    Tap,
+    DoubleTap,
+    Long,
+}
+
+/// Payload of the relay's `haptic` message
+#[derive(serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HapticNudge {
+    pub rule_id: String,
+    pub pattern: HapticPattern,
+}
+
+/// Get the haptic pattern configured for each rule
+#[tauri::command]
+pub fn get_nudge_rules(app: AppHandle) -> HashMap<String, HapticPattern> {
+    load_rules(&app)
+}
+
+/// Set (or clear with `null`) the haptic pattern for a rule
+#[tauri::command]
+pub fn set_nudge_rule(
+    app: AppHandle,
+    rule_id: String,
+    pattern: Option<HapticPattern>,
+) -> Result<(), String> {
+    let mut rules = load_rules(&app);
+    match pattern {
+        Some(pattern) => rules.insert(rule_id, pattern),
+        None => rules.remove(&rule_id),
+    };
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        NUDGE_STORE_KEY,
+        serde_json::to_value(&rules).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Fire a rule's nudge; returns false when the rule has no pattern configured
+#[tauri::command]
+pub fn trigger_nudge(
+    app: AppHandle,
+    state: State<'_, RelayState>,
+    rule_id: String,
+) -> Result<bool, String> {
+    fire(&app, &state, &rule_id)
+}
+
+pub fn fire(app: &AppHandle, relay: &RelayState, rule_id: &str) -> Result<bool, String> {
+    let Some(pattern) = load_rules(app).get(rule_id).copied() else {
+        return Ok(false);
+    };
+
+    let nudge = HapticNudge {
+        rule_id: rule_id.to_string(),
+        pattern,
+    };
+    relay::publish(
+        relay,
+        "haptic",
+        serde_json::to_value(nudge).map_err(|e| e.to_string())?,
+    )?;
+    Ok(true)
+}
+
+fn load_rules(app: &AppHandle) -> HashMap<String, HapticPattern> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(NUDGE_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,161 @@
+// Queen Mama LITE - Screen OCR
+// Reads on-screen text locally (Apple Vision on macOS, tesseract elsewhere) so assists can quote it without sending a screenshot
+
+use image::RgbaImage;
+
+use crate::capture::{self, CaptureTarget};
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextBlock {
+    text: String,
+    /// Bounding box in the captured image's pixels, from the top-left corner
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// 0.0 to 1.0
+    confidence: f32,
+}
+
+/// Capture the screen (or a monitor, window or region) and return the text on it, top to bottom
+#[tauri::command]
+pub async fn extract_text_from_screen(region: Option<CaptureTarget>) -> Result<Vec<TextBlock>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let image = capture::capture(region.unwrap_or(CaptureTarget::Screen))?;
+        let mut blocks = recognize(&image)?;
+        blocks.sort_by_key(|block| (block.y, block.x));
+        Ok(blocks)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Whether a text recognizer is installed: Apple Vision always is, tesseract has to be on PATH
+pub fn check_engine() -> Result<(), String> {
+    #[cfg(not(target_os = "macos"))]
+    {
+        use crate::i18n::t;
+
+        let installed = std::process::Command::new("tesseract")
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !installed {
+            return Err(t!("error.tesseract_missing"));
+        }
+    }
+    Ok(())
+}
+
+/// Recognize text with Apple Vision's accurate recognizer
+#[cfg(target_os = "macos")]
+fn recognize(image: &RgbaImage) -> Result<Vec<TextBlock>, String> {
+    use objc2::AllocAnyThread;
+    use objc2_foundation::{NSArray, NSData, NSDictionary};
+    use objc2_vision::{
+        VNImageRequestHandler, VNRecognizeTextRequest, VNRequest, VNRequestTextRecognitionLevel,
+    };
+
+    use crate::i18n::t;
+
+    let png = capture::encode_png(image)?;
+    let (width, height) = (image.width() as f64, image.height() as f64);
+
+    unsafe {
+        let handler = VNImageRequestHandler::initWithData_options(
+            VNImageRequestHandler::alloc(),
+            &NSData::with_bytes(&png),
+            &NSDictionary::new(),
+        );
+        let request = VNRecognizeTextRequest::new();
+        request.setRecognitionLevel(VNRequestTextRecognitionLevel::Accurate);
+        request.setUsesLanguageCorrection(true);
+
+        let requests = NSArray::from_slice(&[AsRef::<VNRequest>::as_ref(&*request)]);
+        handler
+            .performRequests_error(&requests)
+            .map_err(|e| t!("error.text_recognition_failed", error = e.localizedDescription()))?;
+
+        let Some(observations) = request.results() else {
+            return Ok(Vec::new());
+        };
+
+        Ok(observations
+            .iter()
+            .filter_map(|observation| {
+                let candidate = observation.topCandidates(1).firstObject()?;
+                // Vision boxes are normalized with the origin at the bottom-left
+                let bounds = observation.boundingBox();
+                Some(TextBlock {
+                    text: candidate.string().to_string(),
+                    x: (bounds.origin.x * width).round() as u32,
+                    y: ((1.0 - bounds.origin.y - bounds.size.height) * height).round() as u32,
+                    width: (bounds.size.width * width).round() as u32,
+                    height: (bounds.size.height * height).round() as u32,
+                    confidence: candidate.confidence(),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Run the tesseract CLI and merge its words back into lines
+#[cfg(not(target_os = "macos"))]
+fn recognize(image: &RgbaImage) -> Result<Vec<TextBlock>, String> {
+    use std::collections::BTreeMap;
+    use std::process::Command;
+
+    let path = std::env::temp_dir().join(format!("queen-mama-ocr-{}.png", uuid::Uuid::new_v4()));
+    std::fs::write(&path, capture::encode_png(image)?).map_err(|e| e.to_string())?;
+
+    let output = Command::new("tesseract").arg(&path).args(["stdout", "tsv"]).output();
+    let _ = std::fs::remove_file(&path);
+    let output = output.map_err(|e| t!("error.tesseract_unavailable", error = e))?;
+    if !output.status.success() {
+        return Err(t!(
+            "error.tesseract_failed",
+            error = String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // level, page, block, par, line, word, left, top, width, height, conf, text
+    let mut lines: BTreeMap<(u32, u32, u32, u32), Vec<TextBlock>> = BTreeMap::new();
+    for row in String::from_utf8_lossy(&output.stdout).lines().skip(1) {
+        let fields: Vec<&str> = row.splitn(12, '\t').collect();
+        if fields.len() < 12 || fields[0] != "5" || fields[11].trim().is_empty() {
+            continue;
+        }
+        let number = |index: usize| fields[index].parse::<u32>().unwrap_or(0);
+        lines
+            .entry((number(1), number(2), number(3), number(4)))
+            .or_default()
+            .push(TextBlock {
+                text: fields[11].trim().to_string(),
+                x: number(6),
+                y: number(7),
+                width: number(8),
+                height: number(9),
+                confidence: fields[10].parse::<f32>().unwrap_or(0.0).max(0.0) / 100.0,
+            });
+    }
+
+    Ok(lines
+        .into_values()
+        .map(|words| {
+            let left = words.iter().map(|word| word.x).min().unwrap_or(0);
+            let top = words.iter().map(|word| word.y).min().unwrap_or(0);
+            let right = words.iter().map(|word| word.x + word.width).max().unwrap_or(0);
+            let bottom = words.iter().map(|word| word.y + word.height).max().unwrap_or(0);
+            let confidence = words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32;
+            TextBlock {
+                text: words.into_iter().map(|word| word.text).collect::<Vec<_>>().join(" "),
+                x: left,
+                y: top,
+                width: right - left,
+                height: bottom - top,
+                confidence,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,132 @@
+// Queen Mama LITE - Overlay Context Menu
+// Native right-click menu for the overlay, built in Rust so it works whatever state the webview is in
+
+use std::sync::atomic::Ordering;
+
+use tauri::{
+    menu::{CheckMenuItemBuilder, MenuBuilder, MenuEvent, MenuItemBuilder, SubmenuBuilder},
+    App, AppHandle, LogicalPosition, Manager,
+};
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::session;
+use crate::window::{self, OverlayPosition, OverlayState};
+
+/// Prefix for menu ids, so the tray's menu handler ignores them
+const MENU_ID_PREFIX: &str = "overlay_menu.";
+
+/// Opacity choices, as percentages
+const OPACITY_LEVELS: [u32; 4] = [100, 85, 70, 50];
+
+pub fn setup_overlay_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.on_menu_event(|app, event| handle_menu_event(app, event));
+    Ok(())
+}
+
+/// Show the overlay's context menu at a point inside it, in logical pixels
+#[tauri::command]
+pub fn show_overlay_context_menu(app: AppHandle, x: f64, y: f64) -> Result<(), String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+
+    let id = |name: &str| format!("{}{}", MENU_ID_PREFIX, name);
+
+    let mut move_to = SubmenuBuilder::new(&app, t!("overlay_menu.move_to"));
+    for name in [
+        "top_left",
+        "top_center",
+        "top_right",
+        "bottom_left",
+        "bottom_center",
+        "bottom_right",
+    ] {
+        let label = t!(&format!("overlay_menu.{}", name));
+        move_to = move_to.item(
+            &MenuItemBuilder::with_id(id(&format!("move.{}", name)), label)
+                .build(&app)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+
+    let mut opacity = SubmenuBuilder::new(&app, t!("overlay_menu.opacity"));
+    for level in OPACITY_LEVELS {
+        opacity = opacity.item(
+            &MenuItemBuilder::with_id(id(&format!("opacity.{}", level)), format!("{}%", level))
+                .build(&app)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+
+    let label = t!("overlay_menu.click_through");
+    let click_through = CheckMenuItemBuilder::with_id(id("click_through"), label)
+        .checked(app.state::<OverlayState>().click_through.load(Ordering::SeqCst))
+        .build(&app)
+        .map_err(|e| e.to_string())?;
+
+    let end_session = MenuItemBuilder::with_id(id("end_session"), t!("overlay_menu.end_session"))
+        .enabled(session::active_session_id(&app).is_some())
+        .build(&app)
+        .map_err(|e| e.to_string())?;
+
+    let menu = MenuBuilder::new(&app)
+        .item(&move_to.build().map_err(|e| e.to_string())?)
+        .item(&opacity.build().map_err(|e| e.to_string())?)
+        .item(&click_through)
+        .separator()
+        .item(&end_session)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    overlay
+        .popup_menu_at(&menu, LogicalPosition::new(x, y))
+        .map_err(|e| e.to_string())
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let Some(id) = event.id().as_ref().strip_prefix(MENU_ID_PREFIX) else {
+        return;
+    };
+
+    let result = match id {
+        "click_through" => window::toggle_click_through(app),
+        "end_session" => session::stop(app).map(|_| ()),
+        _ => {
+            if let Some(position) = id.strip_prefix("move.") {
+                move_overlay(app, position)
+            } else if let Some(level) = id.strip_prefix("opacity.") {
+                // The webview owns its own styling, so it applies the opacity
+                let level: f64 = level.parse().unwrap_or(100.0);
+                app.emit("overlay_opacity_changed", level / 100.0)
+                    .map_err(|e| e.to_string())
+            } else {
+                Ok(())
+            }
+        }
+    };
+
+    if let Err(e) = result {
+        tracing::warn!("{}", e);
+    }
+}
+
+fn move_overlay(app: &AppHandle, position: &str) -> Result<(), String> {
+    let position = match position {
+        "top_left" => OverlayPosition::TopLeft,
+        "top_center" => OverlayPosition::TopCenter,
+        "top_right" => OverlayPosition::TopRight,
+        "bottom_left" => OverlayPosition::BottomLeft,
+        "bottom_center" => OverlayPosition::BottomCenter,
+        "bottom_right" => OverlayPosition::BottomRight,
+        _ => return Err(t!("error.unknown_overlay_position", position = position)),
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = window::move_overlay(app, position).await {
+            tracing::warn!("{}", e);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,355 @@
+// Queen Mama LITE - API Client Permissions
+// Asks the user before an outside client runs a sensitive action for the first time, and remembers
+// the answer for clients that prove who they are with a secret we issued
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+use tauri::{App, AppHandle, Manager, Url};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::oneshot;
+
+use crate::i18n::t;
+use crate::{actions, db, SETTINGS_STORE};
+
+pub(crate) const API_CLIENTS_STORE_KEY: &str = "api_clients";
+
+/// Actions that record, write to the session history, spend AI credits or type into other apps
+const SENSITIVE_ACTIONS: &[&str] = &[
+    "start_session",
+    "stop_session",
+    "toggle_session",
+    "flag_moment",
+    "trigger_assist",
+    "paste_response",
+    "type_response",
+];
+
+/// Links without a valid client secret share this identity, and are asked every time
+const ANONYMOUS_LINK_TOKEN: &str = "link";
+
+#[derive(Default)]
+pub struct PermissionState {
+    /// Client token and action of each prompt on screen, so a burst of calls asks once
+    pending: Mutex<HashSet<(String, String)>>,
+}
+
+/// Where an outside client reaches us from
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClientKind {
+    /// A phone or tool paired with the companion relay
+    Relay,
+    /// A launcher, browser or Stream Deck opening queenmama:// links
+    Link,
+}
+
+/// Who is calling
+#[derive(Clone)]
+pub struct Caller {
+    /// Issued by us, see `ClientCredentials`
+    pub token: String,
+    /// As the client describes itself
+    pub name: String,
+    pub kind: ClientKind,
+    /// Whether answers are remembered; links that can't prove who sent them are asked every time
+    remembered: bool,
+    /// Hash of the secret just issued to a new client, saved with its first answer
+    issued_secret: Option<String>,
+}
+
+/// What a client passes as `client` and `secret` to be recognized. Relay clients are sent theirs
+/// as `client_credentials` when they connect without one; link clients are made in settings.
+#[derive(Clone, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCredentials {
+    pub client: String,
+    pub secret: String,
+}
+
+impl ClientCredentials {
+    fn new() -> Self {
+        Self {
+            client: uuid::Uuid::new_v4().to_string(),
+            secret: format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            ),
+        }
+    }
+}
+
+impl Caller {
+    /// Relay clients pass `client`, `secret` and `name` next to the pairing token. One that
+    /// doesn't, or whose secret is wrong, is issued new credentials, returned to send it.
+    pub fn relay(
+        app: &AppHandle,
+        query: Option<&str>,
+        peer: &str,
+    ) -> (Self, Option<ClientCredentials>) {
+        let url = query.and_then(|query| Url::parse(&format!("ws://relay/?{}", query)).ok());
+        let param = |name: &str| query_param(url.as_ref()?, name);
+        let address = peer.rsplit_once(':').map_or(peer, |(host, _)| host);
+        let name = param("name").unwrap_or_else(|| address.to_string());
+
+        if let Some(token) = verify(app, ClientKind::Relay, param("client"), param("secret")) {
+            let caller = Self::new(token, name, ClientKind::Relay, None);
+            return (caller, None);
+        }
+        // Already paired, so it's trusted with an identity of its own
+        let credentials = ClientCredentials::new();
+        let caller = Self::new(
+            credentials.client.clone(),
+            name,
+            ClientKind::Relay,
+            Some(hash_secret(&credentials.secret)),
+        );
+        (caller, Some(credentials))
+    }
+
+    /// Links may carry `client`, `secret` and `name` query parameters
+    pub fn link(app: &AppHandle, url: &Url) -> Self {
+        let param = |name: &str| query_param(url, name);
+        let name = param("name").unwrap_or_else(|| t!("permission.anonymous_link"));
+        match verify(app, ClientKind::Link, param("client"), param("secret")) {
+            Some(token) => Self::new(token, name, ClientKind::Link, None),
+            // Any site can open a link and claim any client, so nothing is remembered for it
+            None => Self {
+                remembered: false,
+                ..Self::new(
+                    ANONYMOUS_LINK_TOKEN.to_string(),
+                    name,
+                    ClientKind::Link,
+                    None,
+                )
+            },
+        }
+    }
+
+    fn new(token: String, name: String, kind: ClientKind, issued_secret: Option<String>) -> Self {
+        Self {
+            token,
+            name,
+            kind,
+            remembered: true,
+            issued_secret,
+        }
+    }
+}
+
+/// A client we've asked about, and the answers
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiClient {
+    pub token: String,
+    pub name: String,
+    pub kind: ClientKind,
+    /// The user's answer for each action id; true when allowed
+    pub grants: BTreeMap<String, bool>,
+    /// When the user last answered for this client
+    pub answered_at: i64,
+    /// SHA-256 of the client's secret, hex; clients saved before secrets existed have none and
+    /// can't be recognized
+    #[serde(default)]
+    secret_hash: String,
+}
+
+pub fn setup_permissions(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(PermissionState::default());
+    Ok(())
+}
+
+/// List outside clients and what they may do, most recently answered first
+#[tauri::command]
+pub fn list_api_clients(app: AppHandle) -> Vec<ApiClient> {
+    let mut clients: Vec<ApiClient> = load_clients(&app).into_values().collect();
+    clients.sort_by(|a, b| b.answered_at.cmp(&a.answered_at));
+    clients
+}
+
+/// Make credentials for a launcher or Stream Deck to put in its links, so the user's answers to
+/// it are remembered. The secret is only shown now.
+#[tauri::command]
+pub fn create_api_client(app: AppHandle, name: String) -> Result<ClientCredentials, String> {
+    let credentials = ClientCredentials::new();
+    let mut clients = load_clients(&app);
+    clients.insert(
+        credentials.client.clone(),
+        ApiClient {
+            token: credentials.client.clone(),
+            name: name.trim().to_string(),
+            kind: ClientKind::Link,
+            grants: BTreeMap::new(),
+            answered_at: db::now_ms(),
+            secret_hash: hash_secret(&credentials.secret),
+        },
+    );
+    save_clients(&app, &clients)?;
+    Ok(credentials)
+}
+
+/// Forget a client's answer for one action, or the client entirely; it's asked again next time
+#[tauri::command]
+pub fn revoke_api_client(
+    app: AppHandle,
+    token: String,
+    action: Option<String>,
+) -> Result<(), String> {
+    let mut clients = load_clients(&app);
+    match action {
+        Some(action) => {
+            if let Some(client) = clients.get_mut(&token) {
+                client.grants.remove(&action);
+            }
+        }
+        None => {
+            clients.remove(&token);
+        }
+    }
+    save_clients(&app, &clients)
+}
+
+/// Whether the caller may run the action, asking the user the first time
+pub async fn authorize(app: &AppHandle, caller: &Caller, action: &str) -> bool {
+    if !SENSITIVE_ACTIONS.contains(&action) {
+        return true;
+    }
+    if let Some(allowed) = load_clients(app)
+        .get(&caller.token)
+        .filter(|_| caller.remembered)
+        .and_then(|client| client.grants.get(action).copied())
+    {
+        return allowed;
+    }
+
+    // Calls that arrive while the prompt is up are turned away rather than stacking dialogs
+    let key = (caller.token.clone(), action.to_string());
+    let state = app.state::<PermissionState>();
+    match state
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())
+        .map(|mut pending| pending.insert(key.clone()))
+    {
+        Ok(true) => {}
+        Ok(false) => return false,
+        Err(e) => {
+            tracing::error!("Failed to track permission prompts: {}", e);
+            return false;
+        }
+    }
+    let allowed = ask(app, caller, action).await;
+    if let Err(e) = state
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())
+        .map(|mut pending| pending.remove(&key))
+    {
+        tracing::error!("Failed to track permission prompts: {}", e);
+    }
+
+    tracing::info!(
+        client = %caller.name,
+        action,
+        allowed,
+        "Answered an API client's first request"
+    );
+    if caller.remembered {
+        record(app, caller, action, allowed);
+    }
+    allowed
+}
+
+async fn ask(app: &AppHandle, caller: &Caller, action: &str) -> bool {
+    let source = match caller.kind {
+        ClientKind::Relay => t!("permission.source_relay"),
+        ClientKind::Link => t!("permission.source_link"),
+    };
+    let (answer, answered) = oneshot::channel();
+    app.dialog()
+        .message(t!(
+            "permission.prompt",
+            client = caller.name,
+            source = source,
+            action = actions::description(action).unwrap_or_else(|| action.to_string())
+        ))
+        .title(t!("permission.title"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            t!("permission.allow"),
+            t!("permission.deny"),
+        ))
+        .show(move |allowed| {
+            let _ = answer.send(allowed);
+        });
+    answered.await.unwrap_or(false)
+}
+
+fn record(app: &AppHandle, caller: &Caller, action: &str, allowed: bool) {
+    let mut clients = load_clients(app);
+    let client = clients
+        .entry(caller.token.clone())
+        .or_insert_with(|| ApiClient {
+            token: caller.token.clone(),
+            name: caller.name.clone(),
+            kind: caller.kind,
+            grants: BTreeMap::new(),
+            answered_at: 0,
+            secret_hash: caller.issued_secret.clone().unwrap_or_default(),
+        });
+    client.name = caller.name.clone();
+    client.answered_at = db::now_ms();
+    client.grants.insert(action.to_string(), allowed);
+    if let Err(e) = save_clients(app, &clients) {
+        tracing::error!("Failed to save API clients: {}", e);
+    }
+}
+
+/// The client's token if it's one we issued and the secret is its own
+fn verify(
+    app: &AppHandle,
+    kind: ClientKind,
+    client: Option<String>,
+    secret: Option<String>,
+) -> Option<String> {
+    let (client, secret) = (client?, secret?);
+    let hash = hash_secret(&secret);
+    load_clients(app)
+        .get(&client)
+        .filter(|saved| saved.kind == kind && !saved.secret_hash.is_empty())
+        .filter(|saved| saved.secret_hash == hash)
+        .map(|_| client)
+}
+
+fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+fn load_clients(app: &AppHandle) -> HashMap<String, ApiClient> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(API_CLIENTS_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save_clients(app: &AppHandle, clients: &HashMap<String, ApiClient>) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        API_CLIENTS_STORE_KEY,
+        serde_json::to_value(clients).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
@@ -0,0 +1,425 @@
+// Queen Mama LITE - User Profiles
+// Separate, encrypted session histories for coaches sharing one machine
+
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::{secrets, session, vault, SETTINGS_STORE};
+
+pub(crate) const PROFILES_STORE_KEY: &str = "profiles";
+
+/// The profile every install starts with; it keeps its data where it was before profiles existed
+const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Keychain entry holding a profile's database key
+const PROFILE_KEY_SECRET_PREFIX: &str = "internal.profile_key.";
+
+/// Longer than any language name, e.g. "Brazilian Portuguese"
+const MAX_LANGUAGE_LEN: usize = 40;
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProfileRole {
+    /// Can add and remove profiles
+    Admin,
+    Coach,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    role: ProfileRole,
+    created_at: i64,
+    /// Language summaries are written in, whatever language was spoken; None keeps the spoken one
+    #[serde(default)]
+    pub summary_language: Option<String>,
+    /// Its database is encrypted with a passphrase the user types rather than a keychain key,
+    /// and stays locked until they do
+    #[serde(default)]
+    pub passphrase: bool,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileList {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_ID.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: t!("profile.default_name"),
+                role: ProfileRole::Admin,
+                created_at: 0,
+                summary_language: None,
+                passphrase: false,
+            }],
+        }
+    }
+}
+
+/// Get every profile and which one is active
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> ProfileList {
+    load(&app)
+}
+
+/// Add a profile with its own encrypted session history; only admins can add profiles
+#[tauri::command]
+pub fn create_profile(app: AppHandle, name: String, role: Option<ProfileRole>) -> Result<Profile, String> {
+    let mut list = load(&app);
+    require_admin(&list)?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(t!("error.profile_name_empty"));
+    }
+    if list.profiles.iter().any(|profile| profile.name.eq_ignore_ascii_case(&name)) {
+        return Err(t!("error.profile_exists", name = name));
+    }
+
+    let profile = Profile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        role: role.unwrap_or(ProfileRole::Coach),
+        created_at: db::now_ms(),
+        summary_language: None,
+        passphrase: false,
+    };
+    list.profiles.push(profile.clone());
+    save(&app, &list)?;
+
+    tracing::info!(name = ?profile.name, "Created a profile");
+    profiles_changed(&app, &list);
+    Ok(profile)
+}
+
+/// Delete a profile and all of its data; only admins can delete profiles
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, id: String) -> Result<(), String> {
+    let mut list = load(&app);
+    require_admin(&list)?;
+    if id == DEFAULT_PROFILE_ID {
+        return Err(t!("error.default_profile_delete"));
+    }
+    if id == list.active {
+        return Err(t!("error.profile_active"));
+    }
+    let Some(index) = list.profiles.iter().position(|profile| profile.id == id) else {
+        return Err(t!("error.profile_not_found", id = id));
+    };
+
+    let dir = data_dir(&app, &id)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    secrets::delete(&app, &key_secret(id))?;
+
+    let profile = list.profiles.remove(index);
+    save(&app, &list)?;
+
+    tracing::info!(name = ?profile.name, "Deleted a profile");
+    profiles_changed(&app, &list);
+    Ok(())
+}
+
+/// Set the language a profile's summaries are written in, e.g. "English" for notes on a Spanish
+/// call; None writes them in the spoken language. Only admins can change other profiles.
+#[tauri::command]
+pub fn set_summary_language(
+    app: AppHandle,
+    id: String,
+    language: Option<String>,
+) -> Result<Profile, String> {
+    let mut list = load(&app);
+    if id != list.active {
+        require_admin(&list)?;
+    }
+    let language = language
+        .map(|language| language.trim().to_string())
+        .filter(|language| !language.is_empty());
+    if language
+        .as_ref()
+        .is_some_and(|language| language.chars().count() > MAX_LANGUAGE_LEN)
+    {
+        return Err(t!("error.summary_language_invalid"));
+    }
+
+    let profile = list
+        .profiles
+        .iter_mut()
+        .find(|profile| profile.id == id)
+        .ok_or_else(|| t!("error.profile_not_found", id = id))?;
+    profile.summary_language = language;
+    let profile = profile.clone();
+    save(&app, &list)?;
+
+    profiles_changed(&app, &list);
+    Ok(profile)
+}
+
+/// Switch to another profile's session history; a profile encrypted with a passphrase needs it
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    id: String,
+    passphrase: Option<String>,
+) -> Result<Profile, String> {
+    switch(&app, &id, passphrase.as_deref())
+}
+
+/// Close the current profile's database and open another's. The current one stays open if the
+/// other can't be, e.g. when its passphrase is wrong.
+pub fn switch(app: &AppHandle, id: &str, passphrase: Option<&str>) -> Result<Profile, String> {
+    let mut list = load(app);
+    let profile = list
+        .profiles
+        .iter()
+        .find(|profile| profile.id == id)
+        .cloned()
+        .ok_or_else(|| t!("error.profile_not_found", id = id))?;
+    if list.active == id {
+        return Ok(profile);
+    }
+    if session::active_session_id(app).is_some() {
+        return Err(t!("error.profile_switch_in_session"));
+    }
+    check_switch(&list, &profile)?;
+
+    let db = app.state::<Database>();
+    if profile.passphrase {
+        let passphrase = passphrase.ok_or_else(|| t!("error.passphrase_required"))?;
+        let path = database_path(app, id)?;
+        // A wrong key only shows when the file is first read, as "file is not a database"
+        db::reopen(&db, &path, Some(passphrase)).map_err(|e| {
+            tracing::warn!(name = ?profile.name, "Failed to open a profile: {}", e);
+            t!("error.wrong_passphrase")
+        })?;
+        vault::unlock(app, &data_dir(app, id)?, passphrase)?;
+    } else {
+        let (path, key) = database_location(app, id)?;
+        db::reopen(&db, &path, Some(&key))?;
+        vault::lock(app);
+    }
+
+    list.active = id.to_string();
+    save(app, &list)?;
+
+    tracing::info!(name = ?profile.name, "Switched profile");
+    if let Err(e) = app.emit("profile_changed", &profile) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+    profiles_changed(app, &list);
+    Ok(profile)
+}
+
+/// Language the active profile's summaries are written in, if not the spoken one
+pub(crate) fn summary_language(app: &AppHandle) -> Option<String> {
+    let list = load(app);
+    list.profiles
+        .into_iter()
+        .find(|profile| profile.id == list.active)
+        .and_then(|profile| profile.summary_language)
+}
+
+/// Whether a profile's database is encrypted with the user's passphrase
+pub(crate) fn has_passphrase(app: &AppHandle, id: &str) -> bool {
+    load(app)
+        .profiles
+        .iter()
+        .any(|profile| profile.id == id && profile.passphrase)
+}
+
+/// Record that a profile's database is now encrypted with the user's passphrase; its keychain key
+/// is no longer needed
+pub(crate) fn set_passphrase(app: &AppHandle, id: &str) -> Result<(), String> {
+    let mut list = load(app);
+    let profile = list
+        .profiles
+        .iter_mut()
+        .find(|profile| profile.id == id)
+        .ok_or_else(|| t!("error.profile_not_found", id = id))?;
+    profile.passphrase = true;
+    save(app, &list)?;
+    secrets::delete(app, &key_secret(id))?;
+
+    profiles_changed(app, &list);
+    Ok(())
+}
+
+/// Where the active profile keeps its database, recordings and attachments
+pub(crate) fn active_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    data_dir(app, &load(app).active)
+}
+
+/// Database file of a profile, whatever it's encrypted with
+pub(crate) fn database_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    Ok(data_dir(app, id)?.join(db::DATABASE_FILE))
+}
+
+/// Database file of a profile and the keychain key it's encrypted with, made on first use. The
+/// default profile's database predates keys; if it's still unencrypted it's encrypted now.
+pub(crate) fn database_location(app: &AppHandle, id: &str) -> Result<(PathBuf, String), String> {
+    let dir = data_dir(app, id)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(db::DATABASE_FILE);
+
+    let secret = key_secret(id);
+    if let Some(key) = secrets::get(app, &secret)? {
+        return Ok((path, key));
+    }
+    if path.exists() && id != DEFAULT_PROFILE_ID {
+        return Err(t!("error.profile_key_missing"));
+    }
+
+    let key = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    // Saved first: a database encrypted with a key that was then lost could never be opened
+    secrets::set(app, &secret, &key)?;
+    if path.exists() {
+        if let Err(e) = db::encrypt_file(&path, &key) {
+            let _ = secrets::delete(app, &secret);
+            return Err(e);
+        }
+        tracing::info!("Encrypted the default profile's session history");
+    }
+    Ok((path, key))
+}
+
+/// A profile's keychain key if it has one yet, without making one
+pub(crate) fn stored_key(app: &AppHandle, id: &str) -> Result<Option<String>, String> {
+    secrets::get(app, &key_secret(id))
+}
+
+fn key_secret(id: &str) -> String {
+    format!("{}{}", PROFILE_KEY_SECRET_PREFIX, id)
+}
+
+fn data_dir(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    if id == DEFAULT_PROFILE_ID {
+        return Ok(dir);
+    }
+    // Ids are uuids; reject anything else so it can't escape the profiles dir
+    uuid::Uuid::parse_str(id).map_err(|e| e.to_string())?;
+    Ok(dir.join("profiles").join(id))
+}
+
+fn require_admin(list: &ProfileList) -> Result<(), String> {
+    if is_admin(list) {
+        Ok(())
+    } else {
+        Err(t!("error.profiles_admin_only"))
+    }
+}
+
+fn is_admin(list: &ProfileList) -> bool {
+    list.profiles
+        .iter()
+        .any(|profile| profile.id == list.active && profile.role == ProfileRole::Admin)
+}
+
+/// Becoming an admin takes the admin profile's passphrase unless an admin is the one switching,
+/// so an admin only leaves for another role once some admin profile has a passphrase to come
+/// back with
+fn check_switch(list: &ProfileList, target: &Profile) -> Result<(), String> {
+    let admin = is_admin(list);
+    if target.role == ProfileRole::Admin && !admin && !target.passphrase {
+        return Err(t!("error.admin_profile_unprotected", name = target.name));
+    }
+    let protected_admin = list
+        .profiles
+        .iter()
+        .any(|profile| profile.role == ProfileRole::Admin && profile.passphrase);
+    if target.role != ProfileRole::Admin && admin && !protected_admin {
+        return Err(t!("error.admin_passphrase_first"));
+    }
+    Ok(())
+}
+
+fn profiles_changed(app: &AppHandle, list: &ProfileList) {
+    if let Err(e) = app.emit("profiles_changed", list) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+    #[cfg(desktop)]
+    crate::tray::refresh_profile_menu(app);
+}
+
+pub(crate) fn load(app: &AppHandle) -> ProfileList {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(PROFILES_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, list: &ProfileList) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        PROFILES_STORE_KEY,
+        serde_json::to_value(list).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_switch, Profile, ProfileList, ProfileRole};
+
+    fn profile(id: &str, role: ProfileRole, passphrase: bool) -> Profile {
+        Profile {
+            id: id.to_string(),
+            name: id.to_string(),
+            role,
+            created_at: 0,
+            summary_language: None,
+            passphrase,
+        }
+    }
+
+    fn list(active: &str, admin_passphrase: bool) -> ProfileList {
+        ProfileList {
+            active: active.to_string(),
+            profiles: vec![
+                profile("admin", ProfileRole::Admin, admin_passphrase),
+                profile("coach", ProfileRole::Coach, false),
+            ],
+        }
+    }
+
+    #[test]
+    fn coach_cannot_become_admin_without_its_passphrase() {
+        let list = list("coach", false);
+        assert!(check_switch(&list, &list.profiles[0]).is_err());
+    }
+
+    #[test]
+    fn coach_can_switch_to_an_admin_profile_with_a_passphrase() {
+        // The passphrase itself is checked when the profile's database is opened
+        let list = list("coach", true);
+        assert!(check_switch(&list, &list.profiles[0]).is_ok());
+    }
+
+    #[test]
+    fn admin_keeps_a_way_back_before_leaving() {
+        let unprotected = list("admin", false);
+        assert!(check_switch(&unprotected, &unprotected.profiles[1]).is_err());
+        let protected = list("admin", true);
+        assert!(check_switch(&protected, &protected.profiles[1]).is_ok());
+    }
+}
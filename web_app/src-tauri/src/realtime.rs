@@ -0,0 +1,285 @@
+// Queen Mama LITE - Realtime Connection
+// Keeps a signed-in WebSocket to the coaching backend alive and bridges its messages to the frontend
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tauri::{App, AppHandle, Manager, State};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{Instant, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::auth;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+
+const REALTIME_URL: &str = "wss://queenmama.app/api/realtime";
+
+/// Ping this often so proxies keep the connection open and dead ones are noticed
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Reconnect when nothing has been heard from the backend for this long
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Reconnect delays double from the first up to the last
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Messages kept while offline, oldest dropped first
+const MAX_QUEUED_MESSAGES: usize = 500;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[derive(Default)]
+pub struct RealtimeState {
+    connection: Mutex<Option<Connection>>,
+    status: Mutex<RealtimeStatus>,
+}
+
+struct Connection {
+    outgoing: mpsc::UnboundedSender<String>,
+    shutdown: watch::Sender<bool>,
+}
+
+/// Emitted as `realtime_status`
+#[derive(Clone, Default, serde::Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum RealtimeStatus {
+    #[default]
+    Disconnected,
+    Connecting {
+        attempt: u32,
+    },
+    Connected,
+    #[serde(rename_all = "camelCase")]
+    Reconnecting {
+        attempt: u32,
+        retry_in_ms: u64,
+        /// Messages waiting to be sent once we're back
+        queued: usize,
+    },
+}
+
+pub fn setup_realtime(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(RealtimeState::default());
+    Ok(())
+}
+
+/// Connect to the coaching backend; messages arrive as `realtime_message` events
+#[tauri::command]
+pub fn realtime_connect(app: AppHandle, state: State<'_, RealtimeState>) -> Result<(), String> {
+    let mut connection = state.connection.lock().map_err(|e| e.to_string())?;
+    if connection.is_some() {
+        return Ok(());
+    }
+
+    let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    tauri::async_runtime::spawn(run(app, outgoing_rx, shutdown_rx));
+
+    *connection = Some(Connection { outgoing, shutdown });
+    Ok(())
+}
+
+/// Close the connection and drop anything still queued
+#[tauri::command]
+pub fn realtime_disconnect(state: State<'_, RealtimeState>) -> Result<(), String> {
+    if let Some(connection) = state.connection.lock().map_err(|e| e.to_string())?.take() {
+        let _ = connection.shutdown.send(true);
+    }
+    Ok(())
+}
+
+/// Send a message to the backend, queued until the connection is back if it's down
+#[tauri::command]
+pub fn realtime_send(
+    state: State<'_, RealtimeState>,
+    message: serde_json::Value,
+) -> Result<(), String> {
+    let connection = state.connection.lock().map_err(|e| e.to_string())?;
+    let connection = connection
+        .as_ref()
+        .ok_or_else(|| t!("error.realtime_not_connected"))?;
+    connection
+        .outgoing
+        .send(message.to_string())
+        .map_err(|_| t!("error.realtime_not_connected"))
+}
+
+/// Whether we're connected to the backend
+#[tauri::command]
+pub fn get_realtime_status(state: State<'_, RealtimeState>) -> RealtimeStatus {
+    state
+        .status
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_default()
+}
+
+/// Connect, and reconnect with backoff whenever the connection drops, until shut down
+async fn run(
+    app: AppHandle,
+    mut outgoing: mpsc::UnboundedReceiver<String>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut queue = VecDeque::new();
+    let mut attempt = 0;
+
+    'run: loop {
+        set_status(&app, RealtimeStatus::Connecting { attempt });
+        let connected = tokio::select! {
+            connected = connect(&app) => connected,
+            _ = shutdown.changed() => break,
+        };
+        match connected {
+            Ok(socket) => {
+                attempt = 0;
+                tracing::info!("Connected");
+                set_status(&app, RealtimeStatus::Connected);
+                match serve(&app, socket, &mut outgoing, &mut queue, &mut shutdown).await {
+                    Ok(()) => break,
+                    Err(e) => tracing::warn!("Connection lost: {}", e),
+                }
+            }
+            // Only the first failure is worth a warning; the retries say the same
+            Err(e) if attempt == 0 => tracing::warn!("Failed to connect: {}", e),
+            Err(e) => tracing::info!("Reconnect attempt {} failed: {}", attempt, e),
+        }
+
+        let delay = RECONNECT_BASE_DELAY
+            .saturating_mul(1 << attempt.min(6))
+            .min(RECONNECT_MAX_DELAY);
+        attempt += 1;
+        set_status(
+            &app,
+            RealtimeStatus::Reconnecting {
+                attempt,
+                retry_in_ms: delay.as_millis() as u64,
+                queued: queue.len(),
+            },
+        );
+
+        // Keep queuing what the frontend sends while we wait
+        let retry = tokio::time::sleep(delay);
+        tokio::pin!(retry);
+        loop {
+            tokio::select! {
+                _ = &mut retry => break,
+                message = outgoing.recv() => match message {
+                    Some(text) => enqueue(&mut queue, text),
+                    None => break 'run,
+                },
+                _ = shutdown.changed() => break 'run,
+            }
+        }
+    }
+
+    tracing::info!("Disconnected");
+    set_status(&app, RealtimeStatus::Disconnected);
+}
+
+async fn connect(app: &AppHandle) -> Result<Socket, String> {
+    let token = auth::access_token(app).await?;
+    let mut request = REALTIME_URL
+        .into_client_request()
+        .map_err(|e| e.to_string())?;
+    request.headers_mut().insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|e| e.to_string())?,
+    );
+
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(socket)
+}
+
+/// Pass messages both ways until shut down (`Ok`) or the connection fails (`Err`)
+async fn serve(
+    app: &AppHandle,
+    socket: Socket,
+    outgoing: &mut mpsc::UnboundedReceiver<String>,
+    queue: &mut VecDeque<String>,
+    shutdown: &mut watch::Receiver<bool>,
+) -> Result<(), String> {
+    let (mut sink, mut incoming) = socket.split();
+
+    while let Some(text) = queue.pop_front() {
+        if let Err(e) = sink.send(Message::text(text.clone())).await {
+            queue.push_front(text);
+            return Err(e.to_string());
+        }
+    }
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_heard = Instant::now();
+
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                if last_heard.elapsed() > HEARTBEAT_TIMEOUT {
+                    return Err(t!("error.realtime_heartbeat_timeout"));
+                }
+                sink.send(Message::Ping(Default::default()))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            message = outgoing.recv() => match message {
+                Some(text) => {
+                    if let Err(e) = sink.send(Message::text(text.clone())).await {
+                        queue.push_front(text);
+                        return Err(e.to_string());
+                    }
+                }
+                None => break,
+            },
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    last_heard = Instant::now();
+                    deliver(app, &text);
+                }
+                Some(Ok(Message::Close(_))) | None => return Err(t!("error.realtime_closed")),
+                Some(Err(e)) => return Err(e.to_string()),
+                // Pongs and pings count as signs of life
+                Some(Ok(_)) => last_heard = Instant::now(),
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+
+    let _ = sink.close().await;
+    Ok(())
+}
+
+fn deliver(app: &AppHandle, text: &str) {
+    let message =
+        serde_json::from_str(text).unwrap_or_else(|_| serde_json::Value::String(text.to_string()));
+    if let Err(e) = app.emit("realtime_message", message) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
+
+fn enqueue(queue: &mut VecDeque<String>, text: String) {
+    if queue.len() >= MAX_QUEUED_MESSAGES {
+        queue.pop_front();
+        tracing::warn!("Offline queue full, dropped the oldest message");
+    }
+    queue.push_back(text);
+}
+
+fn set_status(app: &AppHandle, status: RealtimeStatus) {
+    if let Ok(mut current) = app.state::<RealtimeState>().status.lock() {
+        *current = status.clone();
+    }
+    if let Err(e) = app.emit("realtime_status", status) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
@@ -0,0 +1,333 @@
+// Queen Mama LITE - Transcript Redaction
+// Scrubs spans of a session for good: the transcript text in them, the audio under them, and
+// anything derived from them, leaving only an audit entry saying when and where
+
+use std::fs;
+use std::path::PathBuf;
+
+use rusqlite::{params, Transaction};
+use tauri::{AppHandle, Manager, State};
+
+use crate::audio::{self, RecordingInfo};
+use crate::db::{self, Database, SessionRecording};
+use crate::i18n::t;
+use crate::session;
+use crate::transcript;
+
+/// Longer reasons are cut
+const MAX_REASON_CHARS: usize = 200;
+
+/// A span of the session's timeline, end exclusive
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRange {
+    start_ms: i64,
+    end_ms: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+    id: i64,
+    start_ms: i64,
+    end_ms: i64,
+    /// Transcript segments removed
+    segments: i64,
+    /// Recordings silenced under the span
+    recordings: i64,
+    reason: Option<String>,
+    created_at: i64,
+}
+
+/// A redacted copy of a recording, swapped in once the database changes are saved
+struct Silenced {
+    recording: SessionRecording,
+    copy: PathBuf,
+}
+
+/// Remove the transcript segments touching `ranges` and silence the recordings under them, for
+/// good. Summaries and keywords are dropped too, since they may repeat what was said. Each range
+/// is logged with `reason` in the session's audit trail.
+#[tauri::command]
+pub async fn redact_segments(
+    app: AppHandle,
+    session_id: String,
+    ranges: Vec<RedactionRange>,
+    reason: Option<String>,
+) -> Result<Vec<Redaction>, String> {
+    tauri::async_runtime::spawn_blocking(move || redact(&app, &session_id, &ranges, reason))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// A session's audit trail of redactions, oldest first
+#[tauri::command]
+pub fn list_redactions(
+    db: State<'_, Database>,
+    session_id: String,
+) -> Result<Vec<Redaction>, String> {
+    db.connection()?
+        .prepare(
+            "SELECT id, start_ms, end_ms, segments, recordings, reason, created_at
+             FROM redactions WHERE session_id = ?1 ORDER BY created_at, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok(Redaction {
+                        id: row.get(0)?,
+                        start_ms: row.get(1)?,
+                        end_ms: row.get(2)?,
+                        segments: row.get(3)?,
+                        recordings: row.get(4)?,
+                        reason: row.get(5)?,
+                        created_at: row.get(6)?,
+                    })
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
+fn redact(
+    app: &AppHandle,
+    session_id: &str,
+    ranges: &[RedactionRange],
+    reason: Option<String>,
+) -> Result<Vec<Redaction>, String> {
+    if ranges.is_empty() || ranges.iter().any(|range| range.start_ms >= range.end_ms) {
+        return Err(t!("error.redaction_range_invalid"));
+    }
+    if session::active_session_id(app).as_deref() == Some(session_id) {
+        return Err(t!("error.session_recording"));
+    }
+    let reason = reason
+        .map(|reason| {
+            reason
+                .trim()
+                .chars()
+                .take(MAX_REASON_CHARS)
+                .collect::<String>()
+        })
+        .filter(|reason| !reason.is_empty());
+
+    let db = app.state::<Database>();
+    let mut connection = db.connection()?;
+    let exists = connection
+        .prepare("SELECT 1 FROM sessions WHERE id = ?1")
+        .and_then(|mut statement| statement.exists(params![session_id]))
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(t!("error.session_not_found", id = session_id));
+    }
+
+    let recordings = db::session_recordings(&connection, session_id).map_err(|e| e.to_string())?;
+    let mut silenced = Vec::new();
+    for recording in &recordings {
+        match silence(app, recording, ranges) {
+            Ok(Some(copy)) => silenced.push(Silenced {
+                recording: recording.clone(),
+                copy,
+            }),
+            Ok(None) => {}
+            Err(e) => {
+                discard(&silenced);
+                return Err(e);
+            }
+        }
+    }
+
+    // Freed pages are zeroed rather than left holding the removed text
+    connection
+        .pragma_update(None, "secure_delete", true)
+        .map_err(|e| e.to_string())?;
+    let mut removed = Vec::new();
+    let redacted = connection
+        .transaction()
+        .and_then(|transaction| {
+            let redactions = redact_rows(
+                &transaction,
+                session_id,
+                ranges,
+                &recordings,
+                reason,
+                &mut removed,
+            )?;
+            transaction.commit()?;
+            Ok(redactions)
+        })
+        .and_then(|redactions| {
+            // Drop the removed text from the search index and the write-ahead log too
+            connection.execute(
+                "INSERT INTO transcript_fts(transcript_fts) VALUES ('optimize')",
+                [],
+            )?;
+            connection.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+            Ok(redactions)
+        });
+    if let Err(e) = connection.pragma_update(None, "secure_delete", false) {
+        tracing::warn!("Failed to turn secure delete off: {}", e);
+    }
+    let redactions = match redacted {
+        Ok(redactions) => redactions,
+        Err(e) => {
+            discard(&silenced);
+            return Err(e.to_string());
+        }
+    };
+    drop(connection);
+
+    for file in &silenced {
+        replace_recording(file)?;
+    }
+    db::sessions_changed(app);
+    transcript::segments_removed(app, session_id, removed);
+    tracing::info!(
+        "Redacted {} spans of session {}",
+        redactions.len(),
+        session_id
+    );
+    Ok(redactions)
+}
+
+fn redact_rows(
+    transaction: &Transaction,
+    session_id: &str,
+    ranges: &[RedactionRange],
+    recordings: &[SessionRecording],
+    reason: Option<String>,
+    removed: &mut Vec<i64>,
+) -> rusqlite::Result<Vec<Redaction>> {
+    let created_at = db::now_ms();
+    let mut redactions = Vec::new();
+    for range in ranges {
+        let ids = transaction
+            .prepare(
+                "DELETE FROM transcript_segments
+                 WHERE session_id = ?1 AND start_ms < ?3 AND end_ms > ?2 RETURNING id",
+            )?
+            .query_map(params![session_id, range.start_ms, range.end_ms], |row| {
+                row.get::<_, i64>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let segments = ids.len() as i64;
+        removed.extend(ids);
+        let silenced = recordings
+            .iter()
+            .filter(|recording| overlap(recording, range).is_some())
+            .count() as i64;
+        transaction.execute(
+            "INSERT INTO redactions
+                (session_id, start_ms, end_ms, segments, recordings, reason, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                session_id,
+                range.start_ms,
+                range.end_ms,
+                segments,
+                silenced,
+                reason,
+                created_at
+            ],
+        )?;
+        redactions.push(Redaction {
+            id: transaction.last_insert_rowid(),
+            start_ms: range.start_ms,
+            end_ms: range.end_ms,
+            segments,
+            recordings: silenced,
+            reason: reason.clone(),
+            created_at,
+        });
+    }
+
+    transaction.execute(
+        "DELETE FROM session_summaries WHERE session_id = ?1",
+        params![session_id],
+    )?;
+    transaction.execute(
+        "DELETE FROM session_keywords WHERE session_id = ?1",
+        params![session_id],
+    )?;
+    transaction.execute(
+        "UPDATE sessions SET keywords_extracted_at = NULL WHERE id = ?1",
+        params![session_id],
+    )?;
+    Ok(redactions)
+}
+
+/// Write a copy of `recording` with the ranges over it silenced, if any are
+fn silence(
+    app: &AppHandle,
+    recording: &SessionRecording,
+    ranges: &[RedactionRange],
+) -> Result<Option<PathBuf>, String> {
+    let spans: Vec<(i64, i64)> = ranges
+        .iter()
+        .filter_map(|range| overlap(recording, range))
+        .collect();
+    if spans.is_empty() {
+        return Ok(None);
+    }
+
+    let mut samples = audio::read_recording(app, &recording.path)?;
+    let rate = recording.sample_rate as i64;
+    for (start_ms, end_ms) in spans {
+        let start = (start_ms * rate / 1000).clamp(0, samples.len() as i64) as usize;
+        let end = (end_ms * rate / 1000).clamp(0, samples.len() as i64) as usize;
+        samples[start..end].fill(0);
+    }
+
+    let copy = recording.path.with_extension("redacted.wav");
+    let info = RecordingInfo {
+        id: recording.id.clone(),
+        source: recording.source,
+        device: recording.device.clone(),
+        sample_rate: recording.sample_rate,
+        path: copy.clone(),
+        started_at: 0,
+    };
+    if let Err(e) = audio::write_recording(app, &info, &samples) {
+        let _ = audio::remove_recording(&copy);
+        return Err(e);
+    }
+    Ok(Some(copy))
+}
+
+/// The part of `range` over `recording`, in ms into the recording
+fn overlap(recording: &SessionRecording, range: &RedactionRange) -> Option<(i64, i64)> {
+    let start = (range.start_ms - recording.offset_ms).max(0);
+    let end = (range.end_ms - recording.offset_ms).min(recording.duration_ms);
+    (start < end).then_some((start, end))
+}
+
+/// Swap a redacted copy, and its waveform, in for the original. The audio goes first: it's what
+/// must not outlive a redaction, while a stale waveform only draws the silenced part wrong.
+fn replace_recording(file: &Silenced) -> Result<(), String> {
+    let original = &file.recording.path;
+    fs::rename(&file.copy, original).map_err(|e| {
+        tracing::error!(
+            "Recording {} still holds redacted audio: {}",
+            file.recording.id,
+            e
+        );
+        e.to_string()
+    })?;
+    if let Err(e) = fs::rename(
+        file.copy.with_extension("peaks.json"),
+        original.with_extension("peaks.json"),
+    ) {
+        tracing::warn!(
+            "Waveform of recording {} is out of date: {}",
+            file.recording.id,
+            e
+        );
+    }
+    Ok(())
+}
+
+fn discard(silenced: &[Silenced]) {
+    for file in silenced {
+        let _ = audio::remove_recording(&file.copy);
+    }
+}
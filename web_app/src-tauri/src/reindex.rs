@@ -0,0 +1,281 @@
+// Queen Mama LITE - Re-indexing
+// Rebuilds the search indexes in the background after upgrades, a batch at a time so the app stays
+// responsive, and picks up where it left off after a restart
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::{App, AppHandle, Manager, State};
+
+use crate::db::Database;
+use crate::events::VersionedEmitter;
+use crate::topics;
+
+/// Rows indexed per transaction; the database is free for everything else in between
+const BATCH_SIZE: i64 = 500;
+
+/// Pause between batches, so a large history doesn't keep a core busy
+const BATCH_PAUSE: Duration = Duration::from_millis(100);
+
+/// A full-text index and the table it indexes, in the order they're rebuilt
+struct FtsIndex {
+    name: &'static str,
+    table: &'static str,
+    column: &'static str,
+}
+
+const INDEXES: &[FtsIndex] = &[
+    FtsIndex {
+        name: "transcript_fts",
+        table: "transcript_segments",
+        column: "text",
+    },
+    FtsIndex {
+        name: "notes_fts",
+        table: "notes",
+        column: "content",
+    },
+    FtsIndex {
+        name: "attachment_fts",
+        table: "note_attachments",
+        column: "file_name",
+    },
+];
+
+#[derive(Default)]
+pub struct ReindexState {
+    running: AtomicBool,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReindexStatus {
+    running: bool,
+    /// Index being rebuilt
+    index: Option<&'static str>,
+    /// Rows indexed so far, across every index
+    done: i64,
+    total: i64,
+}
+
+pub fn setup_reindex(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(ReindexState::default());
+    resume(app.app_handle());
+    Ok(())
+}
+
+/// Resume a re-index that was cut short by quitting; a locked database is resumed once unlocked
+pub(crate) fn resume(app: &AppHandle) {
+    let db = app.state::<Database>();
+    if db.is_sealed() {
+        return;
+    }
+    match db
+        .connection()
+        .and_then(|connection| queued(&connection).map_err(|e| e.to_string()))
+    {
+        Ok(true) => {
+            tracing::info!("Resuming re-indexing");
+            spawn(app.clone());
+        }
+        Ok(false) => {}
+        Err(e) => tracing::error!("Failed to check for an unfinished re-index: {}", e),
+    }
+}
+
+/// Rebuild every search index from scratch in the background, e.g. after an upgrade changed how
+/// text is indexed, and have session topics picked out again. Search only finds what's been
+/// re-indexed until it finishes; `reindex_progress` reports how far along it is.
+#[tauri::command]
+pub fn reindex_all(app: AppHandle, db: State<'_, Database>) -> Result<ReindexStatus, String> {
+    {
+        let mut connection = db.connection()?;
+        let transaction = connection.transaction().map_err(|e| e.to_string())?;
+        // Rows added from here on are indexed as they're inserted, so only existing ones are queued
+        for index in INDEXES {
+            transaction
+                .execute(
+                    &format!("INSERT INTO {0}({0}) VALUES ('delete-all')", index.name),
+                    [],
+                )
+                .map_err(|e| e.to_string())?;
+            transaction
+                .execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO reindex_progress (index_name, last_id, max_id)
+                         SELECT ?1, 0, COALESCE(MAX(id), 0) FROM {}",
+                        index.table
+                    ),
+                    params![index.name],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        // Topics are picked out again the next time trends are asked for
+        topics::reset(&transaction).map_err(|e| e.to_string())?;
+        transaction.commit().map_err(|e| e.to_string())?;
+    }
+
+    tracing::info!("Re-indexing search");
+    spawn(app);
+    load_status(&db.connection()?, true).map_err(|e| e.to_string())
+}
+
+/// Get how far a re-index has come
+#[tauri::command]
+pub fn get_reindex_status(
+    db: State<'_, Database>,
+    state: State<'_, ReindexState>,
+) -> Result<ReindexStatus, String> {
+    let running = state.running.load(Ordering::SeqCst);
+    load_status(&db.connection()?, running).map_err(|e| e.to_string())
+}
+
+/// Start the worker, unless one is already going; it picks up whatever is queued
+fn spawn(app: AppHandle) {
+    if app
+        .state::<ReindexState>()
+        .running
+        .swap(true, Ordering::SeqCst)
+    {
+        return;
+    }
+    thread::spawn(move || {
+        let state = app.state::<ReindexState>();
+        loop {
+            let result = run(&app);
+            state.running.store(false, Ordering::SeqCst);
+            publish(&app);
+            if let Err(e) = result {
+                tracing::error!("Re-indexing stopped: {}", e);
+                return;
+            }
+
+            // A re-index queued just as this one finished would otherwise wait for a restart
+            let requeued = app
+                .state::<Database>()
+                .connection()
+                .is_ok_and(|connection| queued(&connection).unwrap_or(false));
+            if !requeued || state.running.swap(true, Ordering::SeqCst) {
+                return;
+            }
+        }
+    });
+}
+
+fn run(app: &AppHandle) -> Result<(), String> {
+    let db = app.state::<Database>();
+    while index_next_batch(&mut db.connection()?).map_err(|e| e.to_string())? {
+        publish(app);
+        thread::sleep(BATCH_PAUSE);
+    }
+    tracing::info!("Re-indexing finished");
+    Ok(())
+}
+
+/// Index the next batch of rows of the first unfinished index; false once they're all done
+fn index_next_batch(connection: &mut Connection) -> rusqlite::Result<bool> {
+    let transaction = connection.transaction()?;
+    let mut next = None;
+    for index in INDEXES {
+        if let Some((last_id, max_id)) = progress(&transaction, index)? {
+            if last_id < max_id {
+                next = Some((index, last_id, max_id));
+                break;
+            }
+        }
+    }
+    let Some((index, last_id, max_id)) = next else {
+        // Finished indexes were kept until now so the totals don't shrink as it goes
+        transaction.execute("DELETE FROM reindex_progress", [])?;
+        transaction.commit()?;
+        return Ok(false);
+    };
+
+    let batch_end = (last_id + BATCH_SIZE).min(max_id);
+    transaction.execute(
+        &format!(
+            "INSERT INTO {0}(rowid, {1}) SELECT id, {1} FROM {2} WHERE id > ?1 AND id <= ?2",
+            index.name, index.column, index.table
+        ),
+        params![last_id, batch_end],
+    )?;
+    transaction.execute(
+        "UPDATE reindex_progress SET last_id = ?2 WHERE index_name = ?1",
+        params![index.name, batch_end],
+    )?;
+    if batch_end >= max_id {
+        // Merge the segments written batch by batch, so searches don't slow down
+        transaction.execute(
+            &format!("INSERT INTO {0}({0}) VALUES ('optimize')", index.name),
+            [],
+        )?;
+    }
+    transaction.commit()?;
+    Ok(true)
+}
+
+/// Last id indexed and the last id to index
+fn progress(connection: &Connection, index: &FtsIndex) -> rusqlite::Result<Option<(i64, i64)>> {
+    connection
+        .query_row(
+            "SELECT last_id, max_id FROM reindex_progress WHERE index_name = ?1",
+            params![index.name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+}
+
+fn queued(connection: &Connection) -> rusqlite::Result<bool> {
+    connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM reindex_progress WHERE last_id < max_id)",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Rows are counted rather than taken from the ids, since ids have gaps
+fn load_status(connection: &Connection, running: bool) -> rusqlite::Result<ReindexStatus> {
+    let mut status = ReindexStatus {
+        running,
+        index: None,
+        done: 0,
+        total: 0,
+    };
+    for index in INDEXES {
+        let Some((last_id, max_id)) = progress(connection, index)? else {
+            continue;
+        };
+        let (done, total): (i64, i64) = connection.query_row(
+            &format!(
+                "SELECT COUNT(*) FILTER (WHERE id <= ?1), COUNT(*) FROM {} WHERE id <= ?2",
+                index.table
+            ),
+            params![last_id, max_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if last_id < max_id && status.index.is_none() {
+            status.index = Some(index.name);
+        }
+        status.done += done;
+        status.total += total;
+    }
+    Ok(status)
+}
+
+fn publish(app: &AppHandle) {
+    let running = app.state::<ReindexState>().running.load(Ordering::SeqCst);
+    let status = app
+        .state::<Database>()
+        .connection()
+        .and_then(|connection| load_status(&connection, running).map_err(|e| e.to_string()));
+    match status {
+        Ok(status) => {
+            if let Err(e) = app.emit("reindex_progress", status) {
+                tracing::error!("Failed to emit event: {}", e);
+            }
+        }
+        Err(e) => tracing::error!("Failed to read re-indexing progress: {}", e),
+    }
+}
@@ -0,0 +1,426 @@
+// Queen Mama LITE - Companion Relay
+// LAN WebSocket server that lets a paired phone follow the live session and send commands
+
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::sync::{Arc, Mutex};
+
+use futures_util::{SinkExt, StreamExt};
+use qrcode::{render::svg, QrCode};
+use tauri::{App, AppHandle, Manager, State};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response},
+    http::StatusCode,
+    Message,
+};
+
+use crate::api_schema;
+use crate::events::{self, Envelope, VersionedEmitter};
+use crate::highlights;
+use crate::i18n::t;
+use crate::nudge;
+use crate::permissions::{self, Caller};
+use crate::shortcuts::dispatch_action;
+
+/// Default relay port, chosen to stay clear of common dev servers
+pub(crate) const DEFAULT_RELAY_PORT: u16 = 47800;
+const OUTGOING_BUFFER: usize = 256;
+
+#[derive(Default)]
+pub struct RelayState {
+    server: Mutex<Option<RelayServer>>,
+}
+
+struct RelayServer {
+    pairing: PairingInfo,
+    outgoing: broadcast::Sender<Arc<Envelope<serde_json::Value>>>,
+    shutdown: watch::Sender<bool>,
+}
+
+/// Everything the phone needs to connect, also encoded as a QR code
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingInfo {
+    url: String,
+    token: String,
+    qr_svg: String,
+}
+
+/// Version 1 of a message, sent until the phone negotiates a newer one
+#[derive(serde::Serialize)]
+struct RelayMessage<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    payload: &'a serde_json::Value,
+}
+
+/// Payload of the `version` message that answers `negotiate_version`
+#[derive(serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VersionReply {
+    /// The version now in use; unset when there's none in common and nothing changed
+    version: Option<u32>,
+    supported: Vec<u32>,
+}
+
+/// Commands the phone can send
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum RelayCommand {
+    /// Mark the current moment of the session, with an optional note
+    FlagMoment {
+        #[serde(default)]
+        note: Option<String>,
+    },
+    /// Ask the AI for help, as the assist shortcut does
+    TriggerAssist,
+    /// Pick the newest message version both sides support; answered with `version`
+    NegotiateVersion { supported: Vec<u32> },
+}
+
+pub fn setup_relay(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(RelayState::default());
+    Ok(())
+}
+
+/// Start the relay server and return pairing info (returns the existing pairing if running)
+#[tauri::command]
+pub async fn start_relay(
+    app: AppHandle,
+    state: State<'_, RelayState>,
+    port: Option<u16>,
+) -> Result<PairingInfo, String> {
+    if let Some(server) = state.server.lock().map_err(|e| e.to_string())?.as_ref() {
+        return Ok(server.pairing.clone());
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port.unwrap_or(DEFAULT_RELAY_PORT)))
+        .await
+        .map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let url = format!("ws://{}:{}/?token={}", lan_ip(), port, token);
+    let qr_svg = QrCode::new(url.as_bytes())
+        .map_err(|e| e.to_string())?
+        .render::<svg::Color>()
+        .min_dimensions(240, 240)
+        .build();
+
+    let pairing = PairingInfo { url, token: token.clone(), qr_svg };
+    let (outgoing, _) = broadcast::channel(OUTGOING_BUFFER);
+    let (shutdown, shutdown_rx) = watch::channel(false);
+
+    tauri::async_runtime::spawn(accept_loop(
+        app,
+        listener,
+        token,
+        outgoing.clone(),
+        shutdown_rx,
+    ));
+
+    *state.server.lock().map_err(|e| e.to_string())? = Some(RelayServer {
+        pairing: pairing.clone(),
+        outgoing,
+        shutdown,
+    });
+
+    tracing::info!("Listening on port {}", port);
+    Ok(pairing)
+}
+
+/// Stop the relay server and disconnect all phones
+#[tauri::command]
+pub fn stop_relay(state: State<'_, RelayState>) -> Result<(), String> {
+    if let Some(server) = state.server.lock().map_err(|e| e.to_string())?.take() {
+        let _ = server.shutdown.send(true);
+        tracing::info!("Stopped");
+    }
+    Ok(())
+}
+
+/// Get the current pairing info, if the relay is running
+#[tauri::command]
+pub fn get_relay_pairing(state: State<'_, RelayState>) -> Result<Option<PairingInfo>, String> {
+    Ok(state
+        .server
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|server| server.pairing.clone()))
+}
+
+/// Forward a message (transcript segment, suggestion, ...) to connected phones
+#[tauri::command]
+pub fn relay_publish(
+    app: AppHandle,
+    state: State<'_, RelayState>,
+    kind: String,
+    payload: serde_json::Value,
+) -> Result<(), String> {
+    publish(&state, &kind, payload)?;
+
+    // Presenters can't see the overlay, so suggestions also buzz the watch
+    if kind == "suggestion" {
+        nudge::fire(&app, &state, nudge::SUGGESTION_RULE)?;
+    }
+    Ok(())
+}
+
+pub fn publish(state: &RelayState, kind: &str, payload: serde_json::Value) -> Result<(), String> {
+    let server = state.server.lock().map_err(|e| e.to_string())?;
+    if let Some(server) = server.as_ref() {
+        // Each phone encodes it in its own version
+        let message = Arc::new(Envelope::new(kind, payload));
+        // Sending only fails when no phone is connected
+        let _ = server.outgoing.send(message);
+    }
+    Ok(())
+}
+
+async fn accept_loop(
+    app: AppHandle,
+    listener: TcpListener,
+    token: String,
+    outgoing: broadcast::Sender<Arc<Envelope<serde_json::Value>>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(handle_client(
+                        app.clone(),
+                        stream,
+                        token.clone(),
+                        outgoing.subscribe(),
+                        shutdown.clone(),
+                    ));
+                }
+                Err(e) => tracing::error!("Failed to accept connection: {}", e),
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+}
+
+async fn handle_client(
+    app: AppHandle,
+    stream: TcpStream,
+    token: String,
+    mut outgoing: broadcast::Receiver<Arc<Envelope<serde_json::Value>>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let peer = stream
+        .peer_addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    // The schema is fetched over plain HTTP on the same port, with the same token
+    if let Some(request) = schema_request(&stream, &token).await {
+        serve_schema(stream, request).await;
+        return;
+    }
+
+    // Reject connections that don't carry the pairing token
+    let mut caller = None;
+    let authorize = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+        if query_token(request.uri().query()) == Some(token.as_str()) {
+            caller = Some(Caller::relay(&app, request.uri().query(), &peer));
+            Ok(response)
+        } else {
+            let mut error = ErrorResponse::new(Some(t!("error.invalid_pairing_token")));
+            *error.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(error)
+        }
+    };
+
+    let ws = match tokio_tungstenite::accept_hdr_async(stream, authorize).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            tracing::error!("Handshake with {} failed: {}", peer, e);
+            return;
+        }
+    };
+
+    let Some((caller, credentials)) = caller else {
+        return;
+    };
+
+    let _ = app.emit("relay_client_connected", &peer);
+    let (mut sink, mut incoming) = ws.split();
+    let mut version = events::DEFAULT_EVENT_VERSION;
+
+    // A client we don't recognize yet is given the credentials to come back with
+    let credentials = credentials
+        .and_then(|credentials| serde_json::to_value(credentials).ok())
+        .and_then(|payload| encode(&Envelope::new("client_credentials", payload), version));
+    if let Some(text) = credentials {
+        if sink.send(Message::text(text)).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            message = outgoing.recv() => match message {
+                Ok(message) => {
+                    let Some(text) = encode(&message, version) else {
+                        continue;
+                    };
+                    if sink.send(Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = handle_command(&app, &caller, &text, &mut version)
+                        .and_then(|reply| encode(&reply, version));
+                    if let Some(reply) = reply {
+                        if sink.send(Message::text(reply)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = shutdown.changed() => break,
+        }
+    }
+
+    let _ = sink.close().await;
+    let _ = app.emit("relay_client_disconnected", &peer);
+}
+
+/// A plain HTTP request for one of the schema documents
+enum SchemaRequest {
+    Authorized(serde_json::Value),
+    /// The pairing token is missing or wrong
+    Unauthorized,
+}
+
+/// The schema document asked for, if the request isn't a WebSocket handshake
+async fn schema_request(stream: &TcpStream, token: &str) -> Option<SchemaRequest> {
+    let mut buffer = [0; 1024];
+    let read = stream.peek(&mut buffer).await.ok()?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let target = request.lines().next()?.split_whitespace().nth(1)?;
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    };
+    let document = match path {
+        api_schema::OPENAPI_PATH => api_schema::openapi,
+        api_schema::ASYNCAPI_PATH => api_schema::asyncapi,
+        _ => return None,
+    };
+    if query_token(query) == Some(token) {
+        Some(SchemaRequest::Authorized(document()))
+    } else {
+        Some(SchemaRequest::Unauthorized)
+    }
+}
+
+/// Answer a schema request. There's no CORS header, so web pages the user visits can't read it.
+async fn serve_schema(mut stream: TcpStream, request: SchemaRequest) {
+    // Read the request so closing doesn't reset the connection under the response
+    let _ = stream.read(&mut [0; 4096]).await;
+
+    let (status, content_type, body) = match request {
+        SchemaRequest::Authorized(document) => ("200 OK", "application/json", document.to_string()),
+        SchemaRequest::Unauthorized => (
+            "401 Unauthorized",
+            "text/plain; charset=utf-8",
+            t!("error.invalid_pairing_token"),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Run a phone's command, returning the message to answer it with, if any
+fn handle_command(
+    app: &AppHandle,
+    caller: &Caller,
+    text: &str,
+    version: &mut u32,
+) -> Option<Envelope<serde_json::Value>> {
+    match serde_json::from_str::<RelayCommand>(text) {
+        Ok(RelayCommand::FlagMoment { note }) => {
+            let app = app.clone();
+            let caller = caller.clone();
+            tauri::async_runtime::spawn(async move {
+                if permissions::authorize(&app, &caller, "flag_moment").await {
+                    if let Err(e) = highlights::flag(&app, note) {
+                        tracing::warn!("Relay flag not saved: {}", e);
+                    }
+                }
+            });
+        }
+        Ok(RelayCommand::TriggerAssist) => {
+            let app = app.clone();
+            let caller = caller.clone();
+            tauri::async_runtime::spawn(async move {
+                if permissions::authorize(&app, &caller, "trigger_assist").await {
+                    dispatch_action(&app, "trigger_assist");
+                }
+            });
+        }
+        Ok(RelayCommand::NegotiateVersion { supported }) => {
+            let negotiated = events::negotiate(&supported);
+            match negotiated {
+                Some(negotiated) => *version = negotiated,
+                None => tracing::warn!("{}", events::unsupported(&supported)),
+            }
+            let reply = VersionReply {
+                version: negotiated,
+                supported: events::EVENT_VERSIONS.to_vec(),
+            };
+            return Some(Envelope::new("version", serde_json::to_value(reply).ok()?));
+        }
+        Err(e) => tracing::warn!("Ignoring invalid command: {}", e),
+    }
+    None
+}
+
+/// A message in the version the phone negotiated
+fn encode(message: &Envelope<serde_json::Value>, version: u32) -> Option<String> {
+    let text = match version {
+        1 => serde_json::to_string(&RelayMessage {
+            kind: &message.kind,
+            payload: &message.payload,
+        }),
+        _ => serde_json::to_string(message),
+    };
+    text.ok()
+}
+
+fn query_token(query: Option<&str>) -> Option<&str> {
+    query?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+}
+
+/// Best-effort LAN address; connecting a UDP socket sends no packets
+fn lan_ip() -> IpAddr {
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .and_then(|socket| {
+            socket.connect((Ipv4Addr::new(8, 8, 8, 8), 80))?;
+            socket.local_addr()
+        })
+        .map(|addr| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
@@ -0,0 +1,359 @@
+// Queen Mama LITE - Session Review
+// Hands a session to a mentor as a review file and merges their timestamped comments back onto the timeline
+
+use std::path::PathBuf;
+
+use rusqlite::{params, OptionalExtension};
+use tauri::{AppHandle, State};
+
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::vault;
+
+/// Identifies review files, checked on import
+const REVIEW_FORMAT: &str = "queen-mama-review";
+const REVIEW_VERSION: u32 = 1;
+
+/// Everything a mentor needs to review a session; they add to `comments` and send the file back
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewPackage {
+    format: String,
+    version: u32,
+    session: ReviewSession,
+    #[serde(default)]
+    segments: Vec<ReviewSegment>,
+    #[serde(default)]
+    comments: Vec<Comment>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewSession {
+    id: String,
+    title: String,
+    started_at: i64,
+    ended_at: Option<i64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewSegment {
+    speaker: Option<String>,
+    text: String,
+    start_ms: i64,
+    end_ms: i64,
+    /// Lets the mentor see which passages may be misheard
+    #[serde(default)]
+    confidence: Option<f32>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    /// UUID, so comments made on different machines merge without clashing
+    id: String,
+    /// Set on replies; replies always point at the thread's first comment
+    parent_id: Option<String>,
+    author: String,
+    content: String,
+    /// Position on the session timeline
+    at_ms: i64,
+    created_at: i64,
+}
+
+/// A comment and its replies, oldest first
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentThread {
+    #[serde(flatten)]
+    comment: Comment,
+    replies: Vec<Comment>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewImport {
+    session_id: String,
+    /// Comments that weren't already in the session
+    imported: usize,
+}
+
+/// Add a comment at a point on the timeline, or reply to an existing comment
+#[tauri::command]
+pub fn add_session_comment(
+    app: AppHandle,
+    db: State<'_, Database>,
+    session_id: String,
+    author: String,
+    content: String,
+    at_ms: Option<i64>,
+    parent_id: Option<String>,
+) -> Result<Comment, String> {
+    let connection = db.connection()?;
+
+    // Replies join the root thread and sit at its position
+    let (parent_id, at_ms) = match parent_id {
+        Some(parent_id) => {
+            let (root, at_ms) = connection
+                .query_row(
+                    "SELECT COALESCE(parent_id, id), at_ms FROM session_comments
+                     WHERE id = ?1 AND session_id = ?2",
+                    params![parent_id, session_id],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| t!("error.comment_not_found", id = parent_id))?;
+            (Some(root), at_ms)
+        }
+        None => (None, at_ms.ok_or_else(|| t!("error.comment_needs_position"))?),
+    };
+
+    let comment = Comment {
+        id: uuid::Uuid::new_v4().to_string(),
+        parent_id,
+        author,
+        content,
+        at_ms,
+        created_at: db::now_ms(),
+    };
+    connection
+        .execute(
+            "INSERT INTO session_comments (id, session_id, parent_id, author, content, at_ms, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                comment.id,
+                session_id,
+                comment.parent_id,
+                comment.author,
+                comment.content,
+                comment.at_ms,
+                comment.created_at
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    drop(connection);
+
+    emit_changed(&app, &session_id);
+    Ok(comment)
+}
+
+/// Delete a comment; deleting the first comment of a thread deletes its replies
+#[tauri::command]
+pub fn delete_session_comment(app: AppHandle, db: State<'_, Database>, id: String) -> Result<(), String> {
+    let connection = db.connection()?;
+    let session_id: Option<String> = connection
+        .query_row(
+            "SELECT session_id FROM session_comments WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(session_id) = session_id else {
+        return Ok(());
+    };
+
+    connection
+        .execute("DELETE FROM session_comments WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    drop(connection);
+
+    emit_changed(&app, &session_id);
+    Ok(())
+}
+
+/// Get a session's comment threads in timeline order
+#[tauri::command]
+pub fn list_session_comments(
+    db: State<'_, Database>,
+    session_id: String,
+) -> Result<Vec<CommentThread>, String> {
+    let comments = load_comments(&db, &session_id)?;
+
+    let (roots, replies): (Vec<_>, Vec<_>) = comments
+        .into_iter()
+        .partition(|comment| comment.parent_id.is_none());
+    let mut threads: Vec<CommentThread> = roots
+        .into_iter()
+        .map(|comment| CommentThread {
+            comment,
+            replies: Vec::new(),
+        })
+        .collect();
+    for reply in replies {
+        if let Some(thread) = threads
+            .iter_mut()
+            .find(|thread| Some(&thread.comment.id) == reply.parent_id.as_ref())
+        {
+            thread.replies.push(reply);
+        }
+    }
+
+    Ok(threads)
+}
+
+/// Write a session, its transcript and its comments to a review file for a mentor
+#[tauri::command]
+pub fn export_session_for_review(
+    app: AppHandle,
+    db: State<'_, Database>,
+    session_id: String,
+    path: PathBuf,
+) -> Result<String, String> {
+    let (session, segments) = {
+        let connection = db.connection()?;
+        let session = connection
+            .query_row(
+                "SELECT id, title, started_at, ended_at FROM sessions WHERE id = ?1",
+                params![session_id],
+                |row| {
+                    Ok(ReviewSession {
+                        id: row.get(0)?,
+                        title: row.get(1)?,
+                        started_at: row.get(2)?,
+                        ended_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| t!("error.session_not_found", id = session_id))?;
+
+        let segments = connection
+            .prepare(
+                "SELECT speaker, text, start_ms, end_ms, confidence FROM transcript_segments
+                 WHERE session_id = ?1 ORDER BY start_ms, id",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![session_id], |row| {
+                        Ok(ReviewSegment {
+                            speaker: row.get(0)?,
+                            text: row.get(1)?,
+                            start_ms: row.get(2)?,
+                            end_ms: row.get(3)?,
+                            confidence: row.get(4)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| e.to_string())?;
+        (session, segments)
+    };
+
+    let package = ReviewPackage {
+        format: REVIEW_FORMAT.to_string(),
+        version: REVIEW_VERSION,
+        session,
+        segments,
+        comments: load_comments(&db, &session_id)?,
+    };
+    let json = serde_json::to_string_pretty(&package).map_err(|e| e.to_string())?;
+    vault::write(&app, &path, json)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Merge the comments from a review file back into its session; comments already present are skipped
+#[tauri::command]
+pub fn import_review_comments(
+    app: AppHandle,
+    db: State<'_, Database>,
+    path: PathBuf,
+) -> Result<ReviewImport, String> {
+    let json = String::from_utf8(vault::read(&app, &path)?).map_err(|e| e.to_string())?;
+    let package: ReviewPackage =
+        serde_json::from_str(&json).map_err(|e| t!("error.review_file_invalid", error = e))?;
+    if package.format != REVIEW_FORMAT {
+        return Err(t!("error.not_review_file"));
+    }
+    if package.version > REVIEW_VERSION {
+        return Err(t!("error.review_file_too_new", version = package.version));
+    }
+
+    let session_id = package.session.id;
+    let mut connection = db.connection()?;
+    let exists = connection
+        .query_row(
+            "SELECT 1 FROM sessions WHERE id = ?1",
+            params![session_id],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if !exists {
+        return Err(t!("error.session_not_found", id = session_id));
+    }
+
+    // Threads first so replies have a parent to point at
+    let mut comments = package.comments;
+    comments.sort_by_key(|comment| comment.parent_id.is_some());
+
+    let transaction = connection.transaction().map_err(|e| e.to_string())?;
+    let mut imported = 0;
+    for comment in comments {
+        imported += transaction
+            .execute(
+                "INSERT OR IGNORE INTO session_comments
+                    (id, session_id, parent_id, author, content, at_ms, created_at)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7
+                 WHERE ?3 IS NULL OR EXISTS (
+                    SELECT 1 FROM session_comments WHERE id = ?3 AND session_id = ?2
+                 )",
+                params![
+                    comment.id,
+                    session_id,
+                    comment.parent_id,
+                    comment.author,
+                    comment.content,
+                    comment.at_ms,
+                    comment.created_at
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    transaction.commit().map_err(|e| e.to_string())?;
+    drop(connection);
+
+    if imported > 0 {
+        emit_changed(&app, &session_id);
+    }
+    tracing::info!("Imported {} comments into session {}", imported, session_id);
+    Ok(ReviewImport {
+        session_id,
+        imported,
+    })
+}
+
+fn load_comments(db: &Database, session_id: &str) -> Result<Vec<Comment>, String> {
+    let connection = db.connection()?;
+    connection
+        .prepare(
+            "SELECT id, parent_id, author, content, at_ms, created_at FROM session_comments
+             WHERE session_id = ?1 ORDER BY at_ms, created_at",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok(Comment {
+                        id: row.get(0)?,
+                        parent_id: row.get(1)?,
+                        author: row.get(2)?,
+                        content: row.get(3)?,
+                        at_ms: row.get(4)?,
+                        created_at: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())
+}
+
+fn emit_changed(app: &AppHandle, session_id: &str) {
+    if let Err(e) = app.emit("session_comments_changed", session_id) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
@@ -0,0 +1,115 @@
+// Queen Mama LITE - Secret Storage
+// Keeps API keys and tokens in the OS keychain (macOS/iOS Keychain, Windows Credential Manager, libsecret)
+
+use tauri::AppHandle;
+
+use crate::i18n::t;
+
+/// Keychain service name, matching the bundle identifier
+#[cfg(not(target_os = "android"))]
+const SECRETS_SERVICE: &str = "com.queenmama.lite";
+
+/// Secrets under this prefix are only read by Rust; the webview can set them but not read them back
+pub(crate) const INTERNAL_PREFIX: &str = "internal.";
+
+/// Android has no keyring backend, so secrets fall back to the app-private settings store
+#[cfg(target_os = "android")]
+const SECRETS_STORE_KEY: &str = "secrets";
+
+/// Store a secret
+#[tauri::command]
+pub fn set_secret(app: AppHandle, name: String, value: String) -> Result<(), String> {
+    set(&app, &name, &value)
+}
+
+/// Read a secret saved by the frontend
+#[tauri::command]
+pub fn get_secret(app: AppHandle, name: String) -> Result<Option<String>, String> {
+    if name.starts_with(INTERNAL_PREFIX) {
+        return Err(t!("error.secret_not_readable", name = name));
+    }
+    get(&app, &name)
+}
+
+/// Delete a secret
+#[tauri::command]
+pub fn delete_secret(app: AppHandle, name: String) -> Result<(), String> {
+    delete(&app, &name)
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn set(_app: &AppHandle, name: &str, value: &str) -> Result<(), String> {
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn get(_app: &AppHandle, name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn delete(_app: &AppHandle, name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SECRETS_SERVICE, name).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "android")]
+pub fn set(app: &AppHandle, name: &str, value: &str) -> Result<(), String> {
+    update_store(app, |secrets| {
+        secrets.insert(name.to_string(), value.to_string());
+    })
+}
+
+#[cfg(target_os = "android")]
+pub fn get(app: &AppHandle, name: &str) -> Result<Option<String>, String> {
+    Ok(load_store(app)?.remove(name))
+}
+
+#[cfg(target_os = "android")]
+pub fn delete(app: &AppHandle, name: &str) -> Result<(), String> {
+    update_store(app, |secrets| {
+        secrets.remove(name);
+    })
+}
+
+#[cfg(target_os = "android")]
+fn load_store(app: &AppHandle) -> Result<std::collections::HashMap<String, String>, String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app.store(crate::SETTINGS_STORE).map_err(|e| e.to_string())?;
+    Ok(store
+        .get(SECRETS_STORE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+#[cfg(target_os = "android")]
+fn update_store(
+    app: &AppHandle,
+    update: impl FnOnce(&mut std::collections::HashMap<String, String>),
+) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
+
+    let mut secrets = load_store(app)?;
+    update(&mut secrets);
+
+    let store = app.store(crate::SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        SECRETS_STORE_KEY,
+        serde_json::to_value(&secrets).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
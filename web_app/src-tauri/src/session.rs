@@ -0,0 +1,194 @@
+// Queen Mama LITE - Session Manager
+// Single source of truth for the session lifecycle, shared by the tray, shortcuts, automation and UI
+
+use std::sync::Mutex;
+
+use tauri::{App, AppHandle, Manager, State};
+
+use crate::audio::{self, AudioSource, AudioState, RecordingInfo};
+use crate::db::{self, Database};
+use crate::duplicates;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::notifications::{self, NoticeAction, NoticeKind};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionPhase {
+    #[default]
+    Idle,
+    Recording,
+    Paused,
+    /// Recordings are being finalized
+    Processing,
+    Done,
+}
+
+#[derive(Default)]
+pub struct SessionState {
+    inner: Mutex<SessionSnapshot>,
+}
+
+/// Emitted as `session_state_changed` on every transition
+#[derive(Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    phase: SessionPhase,
+    session_id: Option<String>,
+    /// Unix time in milliseconds
+    started_at: Option<i64>,
+    recordings: Vec<RecordingInfo>,
+}
+
+pub fn setup_session(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(SessionState::default());
+    Ok(())
+}
+
+/// Start a new session and begin recording the microphone
+#[tauri::command]
+pub fn start_session(app: AppHandle) -> Result<SessionSnapshot, String> {
+    start(&app)
+}
+
+/// Pause a recording session, or resume a paused one
+#[tauri::command]
+pub fn pause_session(app: AppHandle, paused: bool) -> Result<SessionSnapshot, String> {
+    set_paused(&app, paused)
+}
+
+/// Stop the session and finalize its recordings
+#[tauri::command]
+pub fn stop_session(app: AppHandle) -> Result<SessionSnapshot, String> {
+    stop(&app)
+}
+
+/// Get the current session state
+#[tauri::command]
+pub fn get_session_state(state: State<'_, SessionState>) -> Result<SessionSnapshot, String> {
+    Ok(state.inner.lock().map_err(|e| e.to_string())?.clone())
+}
+
+pub fn start(app: &AppHandle) -> Result<SessionSnapshot, String> {
+    let state = app.state::<SessionState>();
+    let mut session = state.inner.lock().map_err(|e| e.to_string())?;
+    if !matches!(session.phase, SessionPhase::Idle | SessionPhase::Done) {
+        return Err(t!("error.session_in_progress"));
+    }
+
+    let audio = app.state::<AudioState>();
+    audio::start_capture(app, &audio, AudioSource::Microphone, None)?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let started_at = db::now_ms();
+    let title = t!("session.default_title");
+    if let Err(e) = db::insert_session(&app.state::<Database>(), &id, &title, started_at) {
+        let _ = audio::stop_capture(&audio);
+        return Err(e);
+    }
+    db::sessions_changed(app);
+
+    *session = SessionSnapshot {
+        phase: SessionPhase::Recording,
+        session_id: Some(id),
+        started_at: Some(started_at),
+        recordings: Vec::new(),
+    };
+    emit(app, &session);
+
+    tracing::info!("Started");
+    Ok(session.clone())
+}
+
+pub fn set_paused(app: &AppHandle, paused: bool) -> Result<SessionSnapshot, String> {
+    let state = app.state::<SessionState>();
+    let mut session = state.inner.lock().map_err(|e| e.to_string())?;
+    let (from, to, error) = if paused {
+        (SessionPhase::Recording, SessionPhase::Paused, "error.session_cannot_pause")
+    } else {
+        (SessionPhase::Paused, SessionPhase::Recording, "error.session_cannot_resume")
+    };
+    if session.phase != from {
+        return Err(t!(error));
+    }
+
+    audio::set_paused(&app.state::<AudioState>(), paused)?;
+
+    session.phase = to;
+    emit(app, &session);
+    Ok(session.clone())
+}
+
+pub fn stop(app: &AppHandle) -> Result<SessionSnapshot, String> {
+    let state = app.state::<SessionState>();
+    let mut session = state.inner.lock().map_err(|e| e.to_string())?;
+    if !matches!(session.phase, SessionPhase::Recording | SessionPhase::Paused) {
+        return Err(t!("error.no_session"));
+    }
+
+    session.phase = SessionPhase::Processing;
+    emit(app, &session);
+
+    match audio::stop_capture(&app.state::<AudioState>()) {
+        Ok(recordings) => session.recordings = recordings,
+        Err(e) => tracing::error!("Failed to stop audio capture: {}", e),
+    }
+    if let Some(id) = &session.session_id {
+        let db = app.state::<Database>();
+        if let Err(e) = db::end_session(&db, id, db::now_ms()) {
+            tracing::error!("Failed to save session end: {}", e);
+        }
+        let started_at = session.started_at.unwrap_or_else(db::now_ms);
+        if let Err(e) = db::insert_recordings(app, id, started_at, &session.recordings) {
+            tracing::error!("Failed to save session recordings: {}", e);
+        }
+        duplicates::check_session(app, id);
+    }
+
+    session.phase = SessionPhase::Done;
+    emit(app, &session);
+
+    if let Some(id) = session.session_id.clone() {
+        notifications::notify(
+            app,
+            NoticeKind::SessionSaved,
+            &t!("notice.session_saved.title"),
+            &t!("notice.session_saved.body"),
+            vec![(t!("action.open_session"), NoticeAction::OpenSession { session_id: id })],
+        );
+    }
+
+    tracing::info!("Stopped");
+    Ok(session.clone())
+}
+
+/// Start a session when none is running, otherwise stop it
+pub fn toggle(app: &AppHandle) -> Result<SessionSnapshot, String> {
+    let phase = app
+        .state::<SessionState>()
+        .inner
+        .lock()
+        .map_err(|e| e.to_string())?
+        .phase;
+
+    match phase {
+        SessionPhase::Recording | SessionPhase::Paused => stop(app),
+        _ => start(app),
+    }
+}
+
+/// Id of the session being recorded (or paused), if any
+pub fn active_session_id(app: &AppHandle) -> Option<String> {
+    let state = app.state::<SessionState>();
+    let session = state.inner.lock().ok()?;
+    match session.phase {
+        SessionPhase::Recording | SessionPhase::Paused => session.session_id.clone(),
+        _ => None,
+    }
+}
+
+fn emit(app: &AppHandle, session: &SessionSnapshot) {
+    if let Err(e) = app.emit("session_state_changed", session) {
+        tracing::error!("Failed to emit state: {}", e);
+    }
+}
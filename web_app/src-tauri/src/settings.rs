@@ -0,0 +1,495 @@
+// Queen Mama LITE - Settings
+// Typed, validated and versioned view of the settings store, and hot-reload of edits made outside
+// the app. The schema and its migrations build everywhere; only applying changes to desktop
+// subsystems and the hot-reload are desktop-only.
+
+use std::collections::{BTreeMap, HashMap};
+#[cfg(desktop)]
+use std::ffi::OsStr;
+#[cfg(desktop)]
+use std::sync::Mutex;
+#[cfg(desktop)]
+use std::time::Duration;
+
+#[cfg(desktop)]
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+#[cfg(desktop)]
+use tauri::Manager;
+use tauri::{App, AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+#[cfg(desktop)]
+use tokio::sync::mpsc;
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::window::{AutoHideRules, OverlayGeometry};
+use crate::{
+    ai, audio, benchmark, chaos, compliance, demo, encryption, features, i18n, logging, mock,
+    notifications, nudge, permissions, shortcuts, trash, window, SETTINGS_STORE,
+};
+#[cfg(desktop)]
+use crate::{focus, input, instance, transcription, updates, watch_folder};
+
+pub(crate) const SETTINGS_VERSION_STORE_KEY: &str = "settings_version";
+
+/// Kept here rather than in context.rs and instance.rs, which only build on desktop
+pub(crate) const MEETING_DETECTION_STORE_KEY: &str = "meeting_detection";
+/// Whether the app starts, hidden, when the user logs in
+pub(crate) const AUTOSTART_STORE_KEY: &str = "autostart";
+
+/// Editors write files in several steps; wait for them to finish before reloading
+#[cfg(desktop)]
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Entry `i` brings the store from schema version `i` to `i + 1`; append to change the schema
+const MIGRATIONS: &[fn(&Store<Wry>) -> Result<(), String>] = &[drop_unreadable_values];
+
+#[cfg(desktop)]
+#[derive(Default)]
+pub struct SettingsWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsApplied {
+    applied: Vec<String>,
+    /// Changed keys that only take effect after relaunching
+    restart_required: Vec<String>,
+}
+
+/// Every setting in one place, with the store keys behind it kept as they are
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    /// Schema version of the store; changing it has no effect
+    #[serde(default)]
+    version: u32,
+    overlay: OverlaySettings,
+    /// Accelerator for every shortcut action
+    shortcuts: BTreeMap<String, String>,
+    audio: audio::AudioDevices,
+    ai: ai::AiConfig,
+    privacy: PrivacySettings,
+    /// Start in the tray when the user logs in
+    autostart: bool,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlaySettings {
+    geometry: OverlayGeometry,
+    /// Show on every Space / virtual desktop
+    all_workspaces: bool,
+    /// Move to the monitor the mouse is on
+    follow_monitor: bool,
+    autohide: AutoHideRules,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacySettings {
+    /// Hide the overlay from screen sharing and recordings
+    content_protection: bool,
+    /// Read window titles and browser addresses to spot meetings
+    meeting_detection: bool,
+    /// Minutes unused before passphrase-encrypted history locks again; 0 for never
+    lock_timeout_minutes: u32,
+    /// Days deleted sessions stay in the trash; 0 keeps them until it's emptied
+    trash_retention_days: u32,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MeetingDetection {
+    pub enabled: bool,
+    pub apps: Vec<MeetingApp>,
+    /// Show the overlay when a meeting is detected
+    pub show_overlay: bool,
+    /// Look up the microphone when a meeting is detected, so starting a session is instant
+    pub prearm_audio: bool,
+}
+
+impl Default for MeetingDetection {
+    fn default() -> Self {
+        let app = |name: &str, apps: &[&str], windows: &[&str]| MeetingApp {
+            name: name.to_string(),
+            apps: apps.iter().map(|app| app.to_string()).collect(),
+            windows: windows.iter().map(|window| window.to_string()).collect(),
+        };
+        Self {
+            enabled: true,
+            apps: vec![
+                app("Zoom", &["zoom"], &["zoom meeting", "zoom webinar"]),
+                app("Microsoft Teams", &["teams"], &["meeting", "call"]),
+                app("Google Meet", &[], &["meet.google.com", "meet - "]),
+                app("Slack", &["slack"], &["huddle"]),
+            ],
+            show_overlay: false,
+            prearm_audio: false,
+        }
+    }
+}
+
+/// A meeting app, matched case-insensitively against the frontmost window
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeetingApp {
+    /// Shown to the user and sent with `meeting_detected`
+    pub name: String,
+    /// Parts of the app's name to match; any app when empty
+    pub apps: Vec<String>,
+    /// Parts of the window title or page URL that mean a meeting is on; any window when empty
+    pub windows: Vec<String>,
+}
+
+impl AppSettings {
+    fn load(app: &AppHandle, store: &Store<Wry>) -> Self {
+        Self {
+            version: load(store, SETTINGS_VERSION_STORE_KEY).unwrap_or(0),
+            overlay: OverlaySettings {
+                geometry: load(store, window::OVERLAY_GEOMETRY_STORE_KEY).unwrap_or_default(),
+                all_workspaces: load(store, window::OVERLAY_ALL_WORKSPACES_STORE_KEY)
+                    .unwrap_or(false),
+                follow_monitor: load(store, window::OVERLAY_FOLLOW_MONITOR_STORE_KEY)
+                    .unwrap_or(false),
+                autohide: load(store, window::OVERLAY_AUTOHIDE_STORE_KEY).unwrap_or_default(),
+            },
+            shortcuts: shortcuts::load_bindings(app).into_iter().collect(),
+            audio: load(store, audio::AUDIO_DEVICES_STORE_KEY).unwrap_or_default(),
+            ai: load(store, ai::AI_CONFIG_STORE_KEY).unwrap_or_default(),
+            privacy: PrivacySettings {
+                content_protection: load(store, window::OVERLAY_CONTENT_PROTECTION_STORE_KEY)
+                    .unwrap_or(true),
+                meeting_detection: load::<MeetingDetection>(store, MEETING_DETECTION_STORE_KEY)
+                    .unwrap_or_default()
+                    .enabled,
+                lock_timeout_minutes: load(store, encryption::LOCK_TIMEOUT_STORE_KEY)
+                    .unwrap_or(encryption::DEFAULT_LOCK_TIMEOUT_MINUTES),
+                trash_retention_days: load(store, trash::TRASH_RETENTION_STORE_KEY)
+                    .unwrap_or(trash::DEFAULT_TRASH_RETENTION_DAYS),
+            },
+            // Until it's set here, whatever the system has registered
+            autostart: load(store, AUTOSTART_STORE_KEY).unwrap_or_else(|| autostart_enabled(app)),
+        }
+    }
+
+    fn validate(&self, current: &AppSettings) -> Result<(), String> {
+        self.overlay.geometry.validate()?;
+        #[cfg(desktop)]
+        shortcuts::check_bindings(&self.shortcuts)?;
+        // Only checked when picked, so a microphone that's unplugged later stays selected
+        if let Some(microphone) = &self.audio.microphone {
+            if current.audio.microphone.as_ref() != Some(microphone) {
+                audio::find_input_device(Some(microphone))?;
+            }
+        }
+        self.ai.validate()
+    }
+
+    /// The store keys these settings are kept under, with their values
+    fn entries(&self, store: &Store<Wry>) -> Result<Vec<(&'static str, Value)>, String> {
+        let mut meeting_detection: MeetingDetection =
+            load(store, MEETING_DETECTION_STORE_KEY).unwrap_or_default();
+        meeting_detection.enabled = self.privacy.meeting_detection;
+
+        Ok(vec![
+            (
+                window::OVERLAY_GEOMETRY_STORE_KEY,
+                to_value(&self.overlay.geometry)?,
+            ),
+            (
+                window::OVERLAY_ALL_WORKSPACES_STORE_KEY,
+                Value::Bool(self.overlay.all_workspaces),
+            ),
+            (
+                window::OVERLAY_FOLLOW_MONITOR_STORE_KEY,
+                Value::Bool(self.overlay.follow_monitor),
+            ),
+            (
+                window::OVERLAY_AUTOHIDE_STORE_KEY,
+                to_value(&self.overlay.autohide)?,
+            ),
+            (shortcuts::SHORTCUTS_STORE_KEY, to_value(&self.shortcuts)?),
+            (audio::AUDIO_DEVICES_STORE_KEY, to_value(&self.audio)?),
+            (ai::AI_CONFIG_STORE_KEY, to_value(&self.ai)?),
+            (
+                window::OVERLAY_CONTENT_PROTECTION_STORE_KEY,
+                Value::Bool(self.privacy.content_protection),
+            ),
+            (MEETING_DETECTION_STORE_KEY, to_value(&meeting_detection)?),
+            (
+                encryption::LOCK_TIMEOUT_STORE_KEY,
+                to_value(&self.privacy.lock_timeout_minutes)?,
+            ),
+            (
+                trash::TRASH_RETENTION_STORE_KEY,
+                to_value(&self.privacy.trash_retention_days)?,
+            ),
+            (AUTOSTART_STORE_KEY, Value::Bool(self.autostart)),
+        ])
+    }
+}
+
+/// Bring the store up to the current schema, before anything reads it
+pub fn setup_settings_schema(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let store = app.store(SETTINGS_STORE)?;
+    let version: u32 = load(&store, SETTINGS_VERSION_STORE_KEY).unwrap_or(0);
+    if version as usize >= MIGRATIONS.len() {
+        if version as usize > MIGRATIONS.len() {
+            tracing::info!(
+                "Settings are from a newer version ({}), leaving them as they are",
+                version
+            );
+        }
+        return Ok(());
+    }
+
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migrate(&store)?;
+        store.set(SETTINGS_VERSION_STORE_KEY, from as u32 + 1);
+        tracing::info!("Migrated settings to version {}", from + 1);
+    }
+    store.save()?;
+    Ok(())
+}
+
+#[cfg(desktop)]
+pub fn setup_settings(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(SettingsWatcher::default());
+
+    // Watch the directory rather than the file: editors often replace it on save
+    let dir = app.path().app_data_dir()?;
+    std::fs::create_dir_all(&dir)?;
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(reload_loop(app.app_handle().clone(), receiver));
+
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        let is_settings = event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == Some(OsStr::new(SETTINGS_STORE)));
+        if is_settings && (event.kind.is_create() || event.kind.is_modify()) {
+            let _ = sender.send(());
+        }
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+    *app.state::<SettingsWatcher>()
+        .watcher
+        .lock()
+        .map_err(|e| e.to_string())? = Some(watcher);
+
+    tracing::info!("Watching settings for changes");
+    Ok(())
+}
+
+/// Get every setting in one typed object
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    Ok(AppSettings::load(&app, &store))
+}
+
+/// Change some settings, given in the shape `get_settings` returns with only the fields to change.
+/// Nothing is saved unless all of them are valid.
+#[tauri::command]
+pub fn update_settings(app: AppHandle, changes: Value) -> Result<AppSettings, String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    let current = AppSettings::load(&app, &store);
+
+    let mut merged = to_value(&current)?;
+    merge(&mut merged, changes);
+    let updated: AppSettings =
+        serde_json::from_value(merged).map_err(|e| t!("error.settings_invalid", error = e))?;
+    updated.validate(&current)?;
+
+    let previous = current.entries(&store)?;
+    let mut changed = Vec::new();
+    for ((key, value), (_, before)) in updated.entries(&store)?.into_iter().zip(previous) {
+        if value != before {
+            store.set(key, value);
+            changed.push(key.to_string());
+        }
+    }
+    if changed.is_empty() {
+        return Ok(current);
+    }
+    store.save().map_err(|e| e.to_string())?;
+
+    apply(&app, &changed)?;
+    Ok(AppSettings::load(&app, &store))
+}
+
+#[cfg(desktop)]
+async fn reload_loop(app: AppHandle, mut receiver: mpsc::UnboundedReceiver<()>) {
+    while receiver.recv().await.is_some() {
+        tokio::time::sleep(RELOAD_DEBOUNCE).await;
+        while receiver.try_recv().is_ok() {}
+
+        if let Err(e) = reload(&app) {
+            tracing::error!("Failed to reload settings: {}", e);
+        }
+    }
+}
+
+/// Reload the store from disk and apply whatever changed outside the app.
+/// Our own writes are saved immediately, so they match the file and are skipped.
+#[cfg(desktop)]
+fn reload(app: &AppHandle) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    let before: HashMap<String, serde_json::Value> = store.entries().into_iter().collect();
+    store.reload().map_err(|e| e.to_string())?;
+    let after: HashMap<String, serde_json::Value> = store.entries().into_iter().collect();
+
+    let mut changed: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    apply(app, &changed)
+}
+
+/// Hand changed keys to the subsystems that use them, then emit `settings_applied` and
+/// `settings_changed`
+fn apply(app: &AppHandle, changed: &[String]) -> Result<(), String> {
+    let mut applied = Vec::new();
+    let mut restart_required = Vec::new();
+    for key in changed {
+        let result = match key.as_str() {
+            #[cfg(desktop)]
+            shortcuts::SHORTCUTS_STORE_KEY => shortcuts::reload(app),
+            #[cfg(desktop)]
+            watch_folder::WATCH_FOLDER_STORE_KEY => watch_folder::reload(app),
+            #[cfg(desktop)]
+            input::PEDAL_STORE_KEY => input::reload(app),
+            window::OVERLAY_GEOMETRY_STORE_KEY => window::reload(app),
+            #[cfg(desktop)]
+            window::OVERLAY_ALL_WORKSPACES_STORE_KEY => window::apply_all_workspaces(app),
+            window::OVERLAY_CONTENT_PROTECTION_STORE_KEY => window::apply_content_protection(app),
+            i18n::LOCALE_STORE_KEY => i18n::reload(app),
+            logging::LOG_LEVEL_STORE_KEY => logging::reload(app),
+            #[cfg(desktop)]
+            AUTOSTART_STORE_KEY => instance::apply_autostart(app),
+            // Read from the store each time they're used
+            nudge::NUDGE_STORE_KEY
+            | compliance::COMPLIANCE_STORE_KEY
+            | demo::DEMO_MODE_STORE_KEY
+            | mock::MOCK_PROVIDERS_STORE_KEY
+            | chaos::CHAOS_MODE_STORE_KEY
+            | MEETING_DETECTION_STORE_KEY
+            | permissions::API_CLIENTS_STORE_KEY
+            | benchmark::BENCHMARK_STORE_KEY
+            | encryption::LOCK_TIMEOUT_STORE_KEY
+            | trash::TRASH_RETENTION_STORE_KEY
+            | audio::AUDIO_DEVICES_STORE_KEY
+            | features::DISABLED_FEATURES_STORE_KEY
+            | notifications::NOTIFICATION_SOUNDS_STORE_KEY
+            | window::OVERLAY_FOLLOW_MONITOR_STORE_KEY
+            | window::OVERLAY_PLACEMENT_STORE_KEY
+            | window::OVERLAY_AUTOHIDE_STORE_KEY
+            | ai::AI_CONFIG_STORE_KEY => Ok(()),
+            #[cfg(desktop)]
+            focus::FOCUS_SUPPRESSION_STORE_KEY
+            | transcription::WHISPER_MODEL_STORE_KEY
+            | transcription::WHISPER_CUSTOM_MODELS_STORE_KEY
+            | updates::UPDATE_CHANNEL_STORE_KEY => Ok(()),
+            _ => {
+                restart_required.push(key.clone());
+                continue;
+            }
+        };
+
+        match result {
+            Ok(()) => applied.push(key.clone()),
+            Err(e) => {
+                tracing::error!("Failed to apply {}: {}", key, e);
+                restart_required.push(key.clone());
+            }
+        }
+    }
+
+    tracing::info!("Applied {:?}, restart required for {:?}", applied, restart_required);
+    app.emit(
+        "settings_applied",
+        SettingsApplied {
+            applied,
+            restart_required,
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    app.emit("settings_changed", AppSettings::load(app, &store))
+        .map_err(|e| e.to_string())
+}
+
+/// Version 1: unreadable values were quietly replaced by their defaults; remove them so the
+/// store holds what's in effect
+fn drop_unreadable_values(store: &Store<Wry>) -> Result<(), String> {
+    fn check<T: DeserializeOwned>(store: &Store<Wry>, key: &str) {
+        if store
+            .get(key)
+            .is_some_and(|value| serde_json::from_value::<T>(value).is_err())
+        {
+            tracing::info!("Dropping unreadable setting {}", key);
+            store.delete(key);
+        }
+    }
+
+    check::<OverlayGeometry>(store, window::OVERLAY_GEOMETRY_STORE_KEY);
+    check::<bool>(store, window::OVERLAY_ALL_WORKSPACES_STORE_KEY);
+    check::<bool>(store, window::OVERLAY_FOLLOW_MONITOR_STORE_KEY);
+    check::<AutoHideRules>(store, window::OVERLAY_AUTOHIDE_STORE_KEY);
+    check::<bool>(store, window::OVERLAY_CONTENT_PROTECTION_STORE_KEY);
+    check::<HashMap<String, String>>(store, shortcuts::SHORTCUTS_STORE_KEY);
+    check::<ai::AiConfig>(store, ai::AI_CONFIG_STORE_KEY);
+    check::<MeetingDetection>(store, MEETING_DETECTION_STORE_KEY);
+    Ok(())
+}
+
+/// Fields in `changes` replace those in `target`, and objects are merged field by field
+fn merge(target: &mut Value, changes: Value) {
+    match (target, changes) {
+        (Value::Object(target), Value::Object(changes)) => {
+            for (key, value) in changes {
+                merge(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, changes) => *target = changes,
+    }
+}
+
+/// Whether the system starts the app at login; there's no such thing on mobile
+fn autostart_enabled(app: &AppHandle) -> bool {
+    #[cfg(desktop)]
+    return instance::autostart_enabled(app);
+    #[cfg(not(desktop))]
+    {
+        let _ = app;
+        false
+    }
+}
+
+fn load<T: DeserializeOwned>(store: &Store<Wry>, key: &str) -> Option<T> {
+    store
+        .get(key)
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
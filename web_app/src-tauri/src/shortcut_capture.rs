@@ -0,0 +1,279 @@
+// Queen Mama LITE - Shortcut Capture
+// Records the next key chord pressed anywhere so the settings UI can offer "press your new shortcut"
+
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rdev::{EventType, Key};
+use tauri::{App, AppHandle, Manager, State};
+
+use crate::i18n::t;
+use crate::shortcuts;
+
+/// Give up if nothing is pressed within this time
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Default)]
+pub struct CaptureState {
+    /// The keyboard hook is started on first use and then kept for the app's lifetime
+    hooked: AtomicBool,
+    /// Receives the captured accelerator while a capture is in progress
+    pending: Mutex<Option<mpsc::Sender<Result<String, String>>>>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedShortcut {
+    /// Normalized accelerator, ready for `set_shortcut`
+    accelerator: String,
+    /// Human-readable form, e.g. "⌘⇧S"
+    keys: String,
+}
+
+/// Modifiers in the order they're written in an accelerator
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Modifier {
+    CommandOrControl,
+    Control,
+    Super,
+    Alt,
+    Shift,
+}
+
+impl Modifier {
+    fn name(self) -> &'static str {
+        match self {
+            Modifier::CommandOrControl => "CommandOrControl",
+            Modifier::Control => "Control",
+            Modifier::Super => "Super",
+            Modifier::Alt => "Alt",
+            Modifier::Shift => "Shift",
+        }
+    }
+}
+
+pub fn setup_shortcut_capture(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(CaptureState::default());
+    Ok(())
+}
+
+/// Wait for the next key chord and return it as a validated accelerator.
+/// Escape cancels; the registered global shortcuts don't fire while capturing.
+#[tauri::command]
+pub async fn begin_shortcut_capture(
+    app: AppHandle,
+    state: State<'_, CaptureState>,
+) -> Result<CapturedShortcut, String> {
+    if !state.hooked.swap(true, Ordering::SeqCst) {
+        let app = app.clone();
+        thread::spawn(move || listen(app));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    *state.pending.lock().map_err(|e| e.to_string())? = Some(sender);
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        receiver
+            .recv_timeout(CAPTURE_TIMEOUT)
+            .unwrap_or_else(|_| Err(t!("error.no_shortcut_pressed")))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Clear the sender if the capture timed out
+    *state.pending.lock().map_err(|e| e.to_string())? = None;
+
+    let accelerator = result?;
+    shortcuts::check_accelerator(&accelerator)?;
+    Ok(CapturedShortcut {
+        keys: shortcuts::display_keys(&accelerator),
+        accelerator,
+    })
+}
+
+/// Whether a capture is in progress, so shortcut handlers can stand down
+pub fn is_capturing(app: &AppHandle) -> bool {
+    app.try_state::<CaptureState>()
+        .and_then(|state| state.pending.lock().ok().map(|pending| pending.is_some()))
+        .unwrap_or(false)
+}
+
+/// Whether macOS lets us see key presses in other apps; other platforms don't ask
+pub fn has_input_monitoring_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        #[link(name = "ApplicationServices", kind = "framework")]
+        extern "C" {
+            fn AXIsProcessTrusted() -> u8;
+        }
+        unsafe { AXIsProcessTrusted() != 0 }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    true
+}
+
+/// Run the low-level keyboard hook, forwarding chords to a pending capture
+fn listen(app: AppHandle) {
+    let mut held = BTreeSet::new();
+
+    let result = rdev::listen(move |event| {
+        let state = app.state::<CaptureState>();
+        match event.event_type {
+            EventType::KeyPress(key) => {
+                if let Some(modifier) = modifier(key) {
+                    held.insert(modifier);
+                    return;
+                }
+
+                let Ok(mut pending) = state.pending.lock() else {
+                    return;
+                };
+                let Some(sender) = pending.take() else {
+                    return;
+                };
+
+                let result = if key == Key::Escape && held.is_empty() {
+                    Err(t!("error.shortcut_capture_cancelled"))
+                } else {
+                    accelerator(&held, key)
+                };
+                let _ = sender.send(result);
+            }
+            EventType::KeyRelease(key) => {
+                if let Some(modifier) = modifier(key) {
+                    held.remove(&modifier);
+                }
+            }
+            _ => {}
+        }
+    });
+
+    if let Err(e) = result {
+        tracing::error!("Keyboard hook failed: {:?}", e);
+        let state = app.state::<CaptureState>();
+        state.hooked.store(false, Ordering::SeqCst);
+        if let Ok(mut pending) = state.pending.lock() {
+            if let Some(sender) = pending.take() {
+                let error = format!("{:?}", e);
+                let _ = sender.send(Err(t!("error.keyboard_unavailable", error = error)));
+            }
+        }
+    }
+}
+
+fn modifier(key: Key) -> Option<Modifier> {
+    // The platform's primary modifier becomes CommandOrControl so bindings stay portable
+    let primary = |modifier| {
+        if cfg!(target_os = "macos") == (modifier == Modifier::Super) {
+            Modifier::CommandOrControl
+        } else {
+            modifier
+        }
+    };
+
+    match key {
+        Key::ControlLeft | Key::ControlRight => Some(primary(Modifier::Control)),
+        Key::MetaLeft | Key::MetaRight => Some(primary(Modifier::Super)),
+        Key::Alt | Key::AltGr => Some(Modifier::Alt),
+        Key::ShiftLeft | Key::ShiftRight => Some(Modifier::Shift),
+        _ => None,
+    }
+}
+
+fn accelerator(held: &BTreeSet<Modifier>, key: Key) -> Result<String, String> {
+    let code = key_code(key)
+        .ok_or_else(|| t!("error.key_not_allowed", key = format!("{:?}", key)))?;
+    let is_function_key = code.starts_with('F') && code.len() > 1 && code[1..].parse::<u8>().is_ok();
+    if held.is_empty() && !is_function_key {
+        return Err(t!("error.shortcut_needs_modifier"));
+    }
+
+    let mut parts: Vec<&str> = held.iter().map(|modifier| modifier.name()).collect();
+    parts.push(&code);
+    Ok(parts.join("+"))
+}
+
+/// Accelerator name of a key, in the W3C code form the shortcut plugin parses
+fn key_code(key: Key) -> Option<String> {
+    let code = match key {
+        Key::KeyA => "KeyA",
+        Key::KeyB => "KeyB",
+        Key::KeyC => "KeyC",
+        Key::KeyD => "KeyD",
+        Key::KeyE => "KeyE",
+        Key::KeyF => "KeyF",
+        Key::KeyG => "KeyG",
+        Key::KeyH => "KeyH",
+        Key::KeyI => "KeyI",
+        Key::KeyJ => "KeyJ",
+        Key::KeyK => "KeyK",
+        Key::KeyL => "KeyL",
+        Key::KeyM => "KeyM",
+        Key::KeyN => "KeyN",
+        Key::KeyO => "KeyO",
+        Key::KeyP => "KeyP",
+        Key::KeyQ => "KeyQ",
+        Key::KeyR => "KeyR",
+        Key::KeyS => "KeyS",
+        Key::KeyT => "KeyT",
+        Key::KeyU => "KeyU",
+        Key::KeyV => "KeyV",
+        Key::KeyW => "KeyW",
+        Key::KeyX => "KeyX",
+        Key::KeyY => "KeyY",
+        Key::KeyZ => "KeyZ",
+        Key::Num0 => "Digit0",
+        Key::Num1 => "Digit1",
+        Key::Num2 => "Digit2",
+        Key::Num3 => "Digit3",
+        Key::Num4 => "Digit4",
+        Key::Num5 => "Digit5",
+        Key::Num6 => "Digit6",
+        Key::Num7 => "Digit7",
+        Key::Num8 => "Digit8",
+        Key::Num9 => "Digit9",
+        Key::F1 => "F1",
+        Key::F2 => "F2",
+        Key::F3 => "F3",
+        Key::F4 => "F4",
+        Key::F5 => "F5",
+        Key::F6 => "F6",
+        Key::F7 => "F7",
+        Key::F8 => "F8",
+        Key::F9 => "F9",
+        Key::F10 => "F10",
+        Key::F11 => "F11",
+        Key::F12 => "F12",
+        Key::Return => "Enter",
+        Key::Space => "Space",
+        Key::Tab => "Tab",
+        Key::Escape => "Escape",
+        Key::Backspace => "Backspace",
+        Key::Delete => "Delete",
+        Key::BackSlash => "Backslash",
+        Key::Slash => "Slash",
+        Key::Comma => "Comma",
+        Key::Dot => "Period",
+        Key::SemiColon => "Semicolon",
+        Key::Quote => "Quote",
+        Key::BackQuote => "Backquote",
+        Key::Minus => "Minus",
+        Key::Equal => "Equal",
+        Key::LeftBracket => "BracketLeft",
+        Key::RightBracket => "BracketRight",
+        Key::UpArrow => "ArrowUp",
+        Key::DownArrow => "ArrowDown",
+        Key::LeftArrow => "ArrowLeft",
+        Key::RightArrow => "ArrowRight",
+        Key::Home => "Home",
+        Key::End => "End",
+        Key::PageUp => "PageUp",
+        Key::PageDown => "PageDown",
+        _ => return None,
+    };
+    Some(code.to_string())
+}
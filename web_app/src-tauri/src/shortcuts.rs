@@ -1,91 +1,647 @@
 // Queen Mama LITE - Global Keyboard Shortcuts
 // Handles system-wide hotkeys for controlling the application
 
-use tauri::{App, Emitter, Manager};
-use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+use std::collections::HashMap;
 
-/// Shortcut definitions matching macOS app behavior
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+#[cfg(desktop)]
+use std::{collections::BTreeMap, str::FromStr, sync::Mutex};
+#[cfg(desktop)]
+use tauri::{App, State};
+#[cfg(desktop)]
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::audio::{self, AudioState, RecordingInfo};
+use crate::SETTINGS_STORE;
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::trace;
+
+pub(crate) const SHORTCUTS_STORE_KEY: &str = "shortcuts";
+
+/// Shortcut actions with their default bindings, matching macOS app behavior
 /// - Cmd/Ctrl + \: Toggle overlay visibility
 /// - Cmd/Ctrl + Enter: Trigger AI assist
 /// - Cmd/Ctrl + Shift + S: Start/Stop session
 /// - Cmd/Ctrl + R: Clear context
+/// - Cmd/Ctrl + Shift + T: Toggle overlay click-through
+/// - Cmd/Ctrl + Shift + Space (hold): Push-to-talk
+/// - Cmd/Ctrl + Shift + V: Paste the suggested response into the focused app
+/// - Cmd/Ctrl + Shift + Y: Type the suggested response into the focused app
+///
+/// Descriptions live in the i18n table as `shortcut.<action>`
+const SHORTCUT_ACTIONS: &[(&str, &str)] = &[
+    ("toggle_overlay", "CommandOrControl+Backslash"),
+    ("trigger_assist", "CommandOrControl+Enter"),
+    ("toggle_session", "CommandOrControl+Shift+KeyS"),
+    ("clear_context", "CommandOrControl+KeyR"),
+    ("toggle_click_through", "CommandOrControl+Shift+KeyT"),
+    ("push_to_talk", "CommandOrControl+Shift+Space"),
+    ("paste_response", "CommandOrControl+Shift+KeyV"),
+    ("type_response", "CommandOrControl+Shift+KeyY"),
+];
+
+/// Actions that run on key-down and again on key-up instead of once per press
+const HOLD_ACTIONS: &[&str] = &["push_to_talk"];
+
+/// Alternate bindings tried in order when an action's binding can't be registered
+#[cfg(desktop)]
+const SHORTCUT_FALLBACKS: &[(&str, &[&str])] = &[
+    ("toggle_overlay", &["CommandOrControl+Alt+Backslash", "CommandOrControl+Shift+Backslash"]),
+    ("trigger_assist", &["CommandOrControl+Shift+Enter", "CommandOrControl+Alt+Enter"]),
+    ("toggle_session", &["CommandOrControl+Alt+KeyS"]),
+    ("clear_context", &["CommandOrControl+Shift+KeyR", "CommandOrControl+Alt+KeyR"]),
+    ("toggle_click_through", &["CommandOrControl+Alt+KeyT"]),
+    ("push_to_talk", &["CommandOrControl+Alt+Space", "CommandOrControl+Shift+Backquote"]),
+    ("paste_response", &["CommandOrControl+Alt+KeyV"]),
+    ("type_response", &["CommandOrControl+Alt+KeyY"]),
+];
+
+/// Combos the OS keeps for itself; registering them either fails or never fires
+#[cfg(target_os = "macos")]
+const RESERVED_SHORTCUTS: &[&str] = &[
+    "Command+Space",
+    "Control+Space",
+    "Command+Tab",
+    "Command+Alt+Escape",
+    "Command+Shift+Digit3",
+    "Command+Shift+Digit4",
+    "Command+Shift+Digit5",
+    "Control+Command+KeyQ",
+];
+#[cfg(target_os = "windows")]
+const RESERVED_SHORTCUTS: &[&str] = &[
+    "Alt+Tab",
+    "Alt+F4",
+    "Control+Alt+Delete",
+    "Control+Shift+Escape",
+    "Super+KeyD",
+    "Super+KeyE",
+    "Super+KeyL",
+    "Super+KeyR",
+];
+#[cfg(all(desktop, not(any(target_os = "macos", target_os = "windows"))))]
+const RESERVED_SHORTCUTS: &[&str] = &[
+    "Alt+Tab",
+    "Alt+F4",
+    "Control+Alt+Delete",
+    "Control+Alt+KeyT",
+    "Super+KeyL",
+];
+
+/// Shortcuts currently registered with the OS, by action id
+#[cfg(desktop)]
+#[derive(Default)]
+pub struct ShortcutRegistry {
+    bindings: Mutex<HashMap<String, Binding>>,
+    /// Outcome of the last registration attempt, by action id
+    reports: Mutex<HashMap<String, ShortcutReport>>,
+}
+
+#[cfg(desktop)]
+struct Binding {
+    accelerator: String,
+    shortcut: Shortcut,
+}
+
+/// Register the saved bindings (or the defaults) for every shortcut action
+#[cfg(desktop)]
 pub fn setup_shortcuts(app: &App) -> Result<(), Box<dyn std::error::Error>> {
-    let app_handle = app.app_handle().clone();
-
-    // Define shortcuts
-    let toggle_overlay = Shortcut::new(Some(Modifiers::META), Code::Backslash);
-    let trigger_assist = Shortcut::new(Some(Modifiers::META), Code::Enter);
-    let toggle_session = Shortcut::new(Some(Modifiers::META | Modifiers::SHIFT), Code::KeyS);
-    let clear_context = Shortcut::new(Some(Modifiers::META), Code::KeyR);
-
-    // Register all shortcuts
-    app.global_shortcut().on_shortcuts(
-        [toggle_overlay, trigger_assist, toggle_session, clear_context],
-        move |_app, shortcut, event| {
-            if event.state() == ShortcutState::Pressed {
-                let action = match shortcut.id() {
-                    id if id == toggle_overlay.id() => "toggle_overlay",
-                    id if id == trigger_assist.id() => "trigger_assist",
-                    id if id == toggle_session.id() => "toggle_session",
-                    id if id == clear_context.id() => "clear_context",
-                    _ => return,
-                };
-
-                // Emit event to frontend
-                if let Err(e) = app_handle.emit("shortcut", action) {
-                    eprintln!("[Shortcuts] Failed to emit event: {}", e);
-                }
+    app.manage(ShortcutRegistry::default());
 
-                // Handle toggle_overlay directly in Rust
-                if action == "toggle_overlay" {
-                    if let Some(overlay) = app_handle.get_webview_window("overlay") {
-                        let is_visible = overlay.is_visible().unwrap_or(false);
-                        if is_visible {
-                            let _ = overlay.hide();
-                        } else {
-                            let _ = overlay.show();
-                            let _ = overlay.set_focus();
-                        }
-                    }
-                }
+    let registry = app.state::<ShortcutRegistry>();
+    {
+        let mut bindings = registry.bindings.lock().map_err(|e| e.to_string())?;
+        let mut reports = registry.reports.lock().map_err(|e| e.to_string())?;
+        for (action, accelerator) in load_bindings(app.app_handle()) {
+            let (binding, report) =
+                register_with_fallbacks(app.app_handle(), &bindings, &action, &accelerator);
+            if let Some(binding) = binding {
+                bindings.insert(action.clone(), binding);
             }
-        },
-    )?;
+            reports.insert(action, report);
+        }
+    }
 
-    println!("[Shortcuts] Global shortcuts registered successfully");
+    publish_report(app.app_handle(), &registry);
+    notify_changed(app.app_handle());
+    tracing::info!("Global shortcuts registered");
     Ok(())
 }
 
+/// Run a shortcut action: notify the frontend and handle window actions in Rust.
+/// Shared by every input source that maps onto shortcut actions.
+pub fn dispatch_action(app: &AppHandle, action: &str) {
+    // Actions run from a command or a link stay under that request
+    let request_id = trace::current_request_id().unwrap_or_else(trace::new_request_id);
+    let _span = tracing::info_span!("shortcut", request_id = %request_id, action).entered();
+    tracing::debug!("Dispatching {}", action);
+
+    // Emit event to frontend
+    if let Err(e) = app.emit("shortcut", action) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+
+    if action == "toggle_session" {
+        if let Err(e) = crate::session::toggle(app) {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    #[cfg(desktop)]
+    if action == "toggle_click_through" {
+        if let Err(e) = crate::window::toggle_click_through(app) {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    // Handle toggle_overlay directly in Rust
+    if action == "toggle_overlay" {
+        if let Ok(overlay) = crate::window::overlay_window(app) {
+            let is_visible = overlay.is_visible().unwrap_or(false);
+            if is_visible {
+                let _ = overlay.hide();
+            } else {
+                #[cfg(desktop)]
+                if let Err(e) = crate::window::follow_cursor_monitor(app) {
+                    tracing::warn!("{}", e);
+                }
+                let _ = overlay.show();
+                let _ = overlay.set_focus();
+            }
+        }
+    }
+}
+
+/// Whether an action needs both the press and the release
+pub fn is_hold_action(action: &str) -> bool {
+    HOLD_ACTIONS.contains(&action)
+}
+
+/// Run a hold action on key-down and key-up.
+/// Shared by every input source that reports releases as well as presses.
+pub fn dispatch_hold_action(app: &AppHandle, action: &str, pressed: bool) {
+    if action != "push_to_talk" {
+        return;
+    }
+
+    let recording = match audio::push_to_talk(app, &app.state::<AudioState>(), pressed) {
+        Ok(recording) => recording,
+        Err(e) => {
+            tracing::error!("Push-to-talk failed: {}", e);
+            None
+        }
+    };
+
+    if let Err(e) = app.emit(
+        "push_to_talk",
+        PushToTalkEvent {
+            listening: pressed,
+            recording,
+        },
+    ) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
+
 /// Get current shortcut configuration
 #[tauri::command]
-pub fn get_shortcuts() -> Vec<ShortcutInfo> {
-    vec![
-        ShortcutInfo {
-            id: "toggle_overlay".to_string(),
-            keys: if cfg!(target_os = "macos") { "⌘\\" } else { "Ctrl+\\" }.to_string(),
-            description: "Toggle overlay visibility".to_string(),
-        },
-        ShortcutInfo {
-            id: "trigger_assist".to_string(),
-            keys: if cfg!(target_os = "macos") { "⌘↩" } else { "Ctrl+Enter" }.to_string(),
-            description: "Trigger AI assist".to_string(),
-        },
-        ShortcutInfo {
-            id: "toggle_session".to_string(),
-            keys: if cfg!(target_os = "macos") { "⌘⇧S" } else { "Ctrl+Shift+S" }.to_string(),
-            description: "Start/Stop session".to_string(),
-        },
-        ShortcutInfo {
-            id: "clear_context".to_string(),
-            keys: if cfg!(target_os = "macos") { "⌘R" } else { "Ctrl+R" }.to_string(),
-            description: "Clear context".to_string(),
+pub fn get_shortcuts(app: AppHandle) -> Vec<ShortcutInfo> {
+    load_bindings(&app)
+        .into_iter()
+        .map(|(id, accelerator)| shortcut_info(&id, &accelerator))
+        .collect()
+}
+
+/// Get which binding each action ended up with, and why any binding failed
+#[cfg(desktop)]
+#[tauri::command]
+pub fn get_shortcut_registration_report(
+    registry: State<'_, ShortcutRegistry>,
+) -> Result<Vec<ShortcutReport>, String> {
+    let reports = registry.reports.lock().map_err(|e| e.to_string())?;
+    Ok(ordered_reports(&reports))
+}
+
+/// Rebind a shortcut action at runtime and persist the new binding
+#[cfg(desktop)]
+#[tauri::command]
+pub fn set_shortcut(
+    app: AppHandle,
+    registry: State<'_, ShortcutRegistry>,
+    id: String,
+    accelerator: String,
+) -> Result<ShortcutInfo, String> {
+    if !SHORTCUT_ACTIONS.iter().any(|(action, ..)| *action == id) {
+        return Err(t!("error.unknown_shortcut", id = id));
+    }
+
+    let shortcut = Shortcut::from_str(&accelerator)
+        .map_err(|e| t!("error.invalid_shortcut", shortcut = accelerator, error = e))?;
+
+    let mut bindings = registry.bindings.lock().map_err(|e| e.to_string())?;
+    if let Some((other, _)) = bindings
+        .iter()
+        .find(|(action, binding)| **action != id && binding.shortcut.id() == shortcut.id())
+    {
+        return Err(t!(
+            "error.shortcut_in_use",
+            shortcut = accelerator,
+            action = other
+        ));
+    }
+
+    let previous = bindings.remove(&id);
+    if let Some(previous) = &previous {
+        let _ = app.global_shortcut().unregister(previous.shortcut);
+    }
+
+    match register(&app, &id, &accelerator) {
+        Ok(binding) => {
+            bindings.insert(id.clone(), binding);
+        }
+        Err(e) => {
+            // Put the previous binding back so the action keeps working
+            if let Some(previous) = previous {
+                if let Ok(binding) = register(&app, &id, &previous.accelerator) {
+                    bindings.insert(id.clone(), binding);
+                }
+            }
+            return Err(e);
+        }
+    }
+
+    save_binding(&app, &id, &accelerator)?;
+
+    registry.reports.lock().map_err(|e| e.to_string())?.insert(
+        id.clone(),
+        ShortcutReport {
+            id: id.clone(),
+            requested: accelerator.clone(),
+            registered: Some(accelerator.clone()),
+            failures: Vec::new(),
         },
-    ]
+    );
+    drop(bindings);
+    publish_report(&app, &registry);
+    notify_changed(&app);
+
+    Ok(shortcut_info(&id, &accelerator))
+}
+
+/// Restore every shortcut to its default binding
+#[cfg(desktop)]
+#[tauri::command]
+pub fn reset_shortcuts(
+    app: AppHandle,
+    registry: State<'_, ShortcutRegistry>,
+) -> Result<Vec<ShortcutInfo>, String> {
+    {
+        let mut bindings = registry.bindings.lock().map_err(|e| e.to_string())?;
+        for (_, binding) in bindings.drain() {
+            let _ = app.global_shortcut().unregister(binding.shortcut);
+        }
+
+        let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+        store.delete(SHORTCUTS_STORE_KEY);
+        store.save().map_err(|e| e.to_string())?;
+
+        let mut reports = registry.reports.lock().map_err(|e| e.to_string())?;
+        for (action, accelerator) in SHORTCUT_ACTIONS {
+            let (binding, report) = register_with_fallbacks(&app, &bindings, action, accelerator);
+            if let Some(binding) = binding {
+                bindings.insert(action.to_string(), binding);
+            }
+            reports.insert(action.to_string(), report);
+        }
+    }
+
+    publish_report(&app, &registry);
+    notify_changed(&app);
+    Ok(get_shortcuts(app.clone()))
+}
+
+/// Re-register any binding that differs from the saved configuration
+#[cfg(desktop)]
+pub fn reload(app: &AppHandle) -> Result<(), String> {
+    let registry = app.state::<ShortcutRegistry>();
+    {
+        let mut bindings = registry.bindings.lock().map_err(|e| e.to_string())?;
+        let mut reports = registry.reports.lock().map_err(|e| e.to_string())?;
+
+        for (action, accelerator) in load_bindings(app) {
+            if reports
+                .get(&action)
+                .is_some_and(|report| report.requested == accelerator)
+            {
+                continue;
+            }
+            if let Some(previous) = bindings.remove(&action) {
+                let _ = app.global_shortcut().unregister(previous.shortcut);
+            }
+
+            let (binding, report) = register_with_fallbacks(app, &bindings, &action, &accelerator);
+            if let Some(binding) = binding {
+                bindings.insert(action.clone(), binding);
+            }
+            reports.insert(action, report);
+        }
+    }
+
+    publish_report(app, &registry);
+    notify_changed(app);
+    Ok(())
+}
+
+/// Register an action's binding, falling back to its alternates if the OS or another app has it
+#[cfg(desktop)]
+fn register_with_fallbacks(
+    app: &AppHandle,
+    bindings: &HashMap<String, Binding>,
+    action: &str,
+    accelerator: &str,
+) -> (Option<Binding>, ShortcutReport) {
+    let fallbacks = SHORTCUT_FALLBACKS
+        .iter()
+        .find(|(id, _)| *id == action)
+        .map(|(_, fallbacks)| *fallbacks)
+        .unwrap_or_default();
+
+    let mut report = ShortcutReport {
+        id: action.to_string(),
+        requested: accelerator.to_string(),
+        registered: None,
+        failures: Vec::new(),
+    };
+
+    for candidate in std::iter::once(accelerator).chain(fallbacks.iter().copied()) {
+        let taken_by = Shortcut::from_str(candidate).ok().and_then(|shortcut| {
+            bindings
+                .iter()
+                .find(|(other, binding)| *other != action && binding.shortcut.id() == shortcut.id())
+                .map(|(other, _)| other.clone())
+        });
+
+        let result = match taken_by {
+            Some(other) => Err(t!("error.shortcut_taken", action = other)),
+            None => register(app, action, candidate),
+        };
+        match result {
+            Ok(binding) => {
+                report.registered = Some(candidate.to_string());
+                return (Some(binding), report);
+            }
+            Err(reason) => {
+                tracing::error!(
+                    code = "shortcuts.register_failed",
+                    "Failed to register {} ({}): {}",
+                    action, candidate, reason
+                );
+                report.failures.push(ShortcutFailure {
+                    accelerator: candidate.to_string(),
+                    reason,
+                });
+            }
+        }
+    }
+
+    (None, report)
+}
+
+/// Send the registration outcome to the frontend when any binding didn't go as requested
+#[cfg(desktop)]
+fn publish_report(app: &AppHandle, registry: &ShortcutRegistry) {
+    let Ok(reports) = registry.reports.lock() else {
+        return;
+    };
+    let reports = ordered_reports(&reports);
+    if reports.iter().all(|report| report.failures.is_empty()) {
+        return;
+    }
+
+    if let Err(e) = app.emit("shortcut_registration_report", &reports) {
+        tracing::error!("Failed to emit report: {}", e);
+    }
+}
+
+/// Let the tray and other menus pick up the bindings now in use
+#[cfg(desktop)]
+fn notify_changed(app: &AppHandle) {
+    if let Err(e) = app.emit("shortcuts_changed", ()) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
+
+/// Accelerator an action is registered with, which may be a fallback; none if nothing registered
+#[cfg(desktop)]
+pub(crate) fn registered_accelerator(app: &AppHandle, action: &str) -> Option<String> {
+    let registry = app.try_state::<ShortcutRegistry>()?;
+    let bindings = registry.bindings.lock().ok()?;
+    bindings
+        .get(action)
+        .map(|binding| binding.accelerator.clone())
+}
+
+#[cfg(desktop)]
+fn ordered_reports(reports: &HashMap<String, ShortcutReport>) -> Vec<ShortcutReport> {
+    SHORTCUT_ACTIONS
+        .iter()
+        .filter_map(|(action, ..)| reports.get(*action).cloned())
+        .collect()
+}
+
+#[cfg(desktop)]
+fn register(app: &AppHandle, action: &str, accelerator: &str) -> Result<Binding, String> {
+    let shortcut = check_accelerator(accelerator)?;
+
+    let action_id = action.to_string();
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _shortcut, event| {
+            // The shortcut being captured for rebinding shouldn't also trigger its action
+            if crate::shortcut_capture::is_capturing(app) {
+                return;
+            }
+            let pressed = event.state() == ShortcutState::Pressed;
+            if is_hold_action(&action_id) {
+                dispatch_hold_action(app, &action_id, pressed);
+            } else if pressed {
+                dispatch_action(app, &action_id);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(Binding {
+        accelerator: accelerator.to_string(),
+        shortcut,
+    })
+}
+
+/// Parse an accelerator, rejecting combos the OS keeps for itself
+#[cfg(desktop)]
+pub(crate) fn check_accelerator(accelerator: &str) -> Result<Shortcut, String> {
+    let shortcut = Shortcut::from_str(accelerator).map_err(|e| e.to_string())?;
+    if RESERVED_SHORTCUTS
+        .iter()
+        .filter_map(|reserved| Shortcut::from_str(reserved).ok())
+        .any(|reserved| reserved.id() == shortcut.id())
+    {
+        return Err(t!("error.shortcut_reserved"));
+    }
+    Ok(shortcut)
+}
+
+/// Check a full set of bindings: known actions, usable accelerators and no combo bound twice
+#[cfg(desktop)]
+pub(crate) fn check_bindings(bindings: &BTreeMap<String, String>) -> Result<(), String> {
+    let mut used = HashMap::new();
+    for (id, accelerator) in bindings {
+        if !SHORTCUT_ACTIONS
+            .iter()
+            .any(|(action, ..)| *action == id.as_str())
+        {
+            return Err(t!("error.unknown_shortcut", id = id));
+        }
+        let shortcut = check_accelerator(accelerator)
+            .map_err(|e| t!("error.invalid_shortcut", shortcut = accelerator, error = e))?;
+        if let Some(other) = used.insert(shortcut.id(), id) {
+            return Err(t!(
+                "error.shortcut_in_use",
+                shortcut = accelerator,
+                action = other
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Saved bindings layered over the defaults, in declaration order
+pub(crate) fn load_bindings(app: &AppHandle) -> Vec<(String, String)> {
+    let saved = load_saved(app);
+    SHORTCUT_ACTIONS
+        .iter()
+        .map(|(id, default)| {
+            let accelerator = saved.get(*id).map(String::as_str).unwrap_or(default);
+            (id.to_string(), accelerator.to_string())
+        })
+        .collect()
+}
+
+fn load_saved(app: &AppHandle) -> HashMap<String, String> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(SHORTCUTS_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(desktop)]
+fn save_binding(app: &AppHandle, id: &str, accelerator: &str) -> Result<(), String> {
+    let mut saved = load_saved(app);
+    saved.insert(id.to_string(), accelerator.to_string());
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        SHORTCUTS_STORE_KEY,
+        serde_json::to_value(&saved).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+fn shortcut_info(id: &str, accelerator: &str) -> ShortcutInfo {
+    let description = if SHORTCUT_ACTIONS.iter().any(|(action, _)| *action == id) {
+        t!(&format!("shortcut.{}", id))
+    } else {
+        String::new()
+    };
+
+    ShortcutInfo {
+        id: id.to_string(),
+        keys: display_keys(accelerator),
+        accelerator: accelerator.to_string(),
+        description,
+    }
+}
+
+/// Human-readable form of an accelerator, e.g. "⌘⇧S" on macOS or "Ctrl+Shift+S" elsewhere
+pub(crate) fn display_keys(accelerator: &str) -> String {
+    let mac = cfg!(target_os = "macos");
+    let keys: Vec<String> = accelerator
+        .split('+')
+        .map(|token| {
+            let key = match token.to_uppercase().as_str() {
+                "COMMANDORCONTROL" | "COMMANDORCTRL" | "CMDORCTRL" | "CMDORCONTROL" => {
+                    if mac { "⌘" } else { "Ctrl" }
+                }
+                "COMMAND" | "CMD" | "SUPER" | "META" => {
+                    if mac { "⌘" } else { "Win" }
+                }
+                "CONTROL" | "CTRL" => {
+                    if mac { "⌃" } else { "Ctrl" }
+                }
+                "OPTION" | "ALT" => {
+                    if mac { "⌥" } else { "Alt" }
+                }
+                "SHIFT" => {
+                    if mac { "⇧" } else { "Shift" }
+                }
+                "ENTER" => {
+                    if mac { "↩" } else { "Enter" }
+                }
+                "SPACE" => "Space",
+                "BACKSLASH" => "\\",
+                _ => "",
+            };
+
+            if !key.is_empty() {
+                key.to_string()
+            } else {
+                // KeyS -> S, Digit1 -> 1, F5 stays F5
+                token
+                    .strip_prefix("Key")
+                    .or_else(|| token.strip_prefix("Digit"))
+                    .unwrap_or(token)
+                    .to_uppercase()
+            }
+        })
+        .collect();
+
+    keys.join(if mac { "" } else { "+" })
+}
+
+/// How registering one action's shortcut went
+#[cfg(desktop)]
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutReport {
+    id: String,
+    requested: String,
+    /// Binding in use: the requested one, a fallback, or none if all failed
+    registered: Option<String>,
+    failures: Vec<ShortcutFailure>,
+}
+
+#[cfg(desktop)]
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutFailure {
+    accelerator: String,
+    reason: String,
+}
+
+/// Push-to-talk went down or up, for the "listening" indicator
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PushToTalkEvent {
+    listening: bool,
+    /// Recording started on press or finished on release; none when the microphone was already on
+    recording: Option<RecordingInfo>,
 }
 
 #[derive(serde::Serialize)]
 pub struct ShortcutInfo {
     id: String,
     keys: String,
+    accelerator: String,
     description: String,
 }
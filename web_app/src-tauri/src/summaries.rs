@@ -0,0 +1,290 @@
+// Queen Mama LITE - Session Summaries
+// AI summaries of a session's transcript and notes; regenerating one with another template or
+// model adds a version, and the session shows whichever version is primary
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use tauri::{AppHandle, Manager, State};
+
+use crate::ai::{self, AiState, Prompt};
+use crate::db::{self, Database};
+use crate::export::format_offset;
+use crate::i18n::t;
+use crate::profiles;
+
+const SUMMARY_COLUMNS: &str = "version, template, model, language, content, created_at, is_primary";
+
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SummaryTemplate {
+    /// A paragraph on what was discussed and decided
+    #[default]
+    Brief,
+    /// Topic by topic, with the reasoning behind decisions
+    Detailed,
+    /// Just the follow-ups, with owners where they were named
+    ActionItems,
+    /// Feedback on how the user handled the conversation
+    Coaching,
+}
+
+impl SummaryTemplate {
+    fn as_str(self) -> &'static str {
+        match self {
+            SummaryTemplate::Brief => "brief",
+            SummaryTemplate::Detailed => "detailed",
+            SummaryTemplate::ActionItems => "actionItems",
+            SummaryTemplate::Coaching => "coaching",
+        }
+    }
+
+    fn parse(template: &str) -> Self {
+        match template {
+            "detailed" => SummaryTemplate::Detailed,
+            "actionItems" => SummaryTemplate::ActionItems,
+            "coaching" => SummaryTemplate::Coaching,
+            _ => SummaryTemplate::Brief,
+        }
+    }
+
+    fn instructions(self) -> &'static str {
+        match self {
+            SummaryTemplate::Brief => {
+                "Summarize this conversation in one short paragraph: what was discussed and what \
+                 was decided."
+            }
+            SummaryTemplate::Detailed => {
+                "Summarize this conversation topic by topic, under a heading each, with the \
+                 decisions made and the reasons given for them."
+            }
+            SummaryTemplate::ActionItems => {
+                "List the follow-ups agreed in this conversation as Markdown checkboxes, naming \
+                 who owns each one when that was said. Reply with just the list."
+            }
+            SummaryTemplate::Coaching => {
+                "You are a supportive coach. From this conversation and the user's notes, say \
+                 what the user handled well, one habit worth working on, and one concrete thing \
+                 to try next time. Keep it under 200 words."
+            }
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    /// Counts up from 1 per session
+    version: i64,
+    template: SummaryTemplate,
+    model: String,
+    language: Option<String>,
+    content: String,
+    created_at: i64,
+    primary: bool,
+}
+
+/// Summarize a session with `template`, on `model` if given instead of the configured one, in
+/// `language` if given else the profile's summary language. The summary is saved as a new
+/// version and made primary; earlier versions are kept.
+#[tauri::command]
+pub async fn generate_summary(
+    app: AppHandle,
+    state: State<'_, AiState>,
+    session_id: String,
+    template: Option<SummaryTemplate>,
+    model: Option<String>,
+    language: Option<String>,
+) -> Result<SessionSummary, String> {
+    let template = template.unwrap_or_default();
+    let transcript = {
+        let db = app.state::<Database>();
+        let connection = db.connection()?;
+        session_text(&connection, &session_id)?
+    };
+    if transcript.is_empty() {
+        return Err(t!("error.summary_nothing", id = session_id));
+    }
+
+    let language = language
+        .filter(|language| !language.trim().is_empty())
+        .or_else(|| profiles::summary_language(&app));
+    let mut system = template.instructions().to_string();
+    if let Some(language) = &language {
+        system.push_str(&format!(
+            " Write it in {}, whatever language was spoken.",
+            language
+        ));
+    }
+    let prompt = Prompt::new(Some(system), transcript).with_model(model);
+    let model = ai::model_name(&app, prompt.model());
+    let content = ai::complete(&app, &state, &prompt, |_| {}).await?;
+
+    let db = app.state::<Database>();
+    let mut connection = db.connection()?;
+    let transaction = connection.transaction().map_err(|e| e.to_string())?;
+    transaction
+        .execute(
+            "UPDATE session_summaries SET is_primary = 0 WHERE session_id = ?1",
+            params![session_id],
+        )
+        .map_err(|e| e.to_string())?;
+    let created_at = db::now_ms();
+    transaction
+        .execute(
+            "INSERT INTO session_summaries
+                (session_id, version, template, model, language, content, created_at, is_primary)
+             SELECT ?1, COALESCE(MAX(version), 0) + 1, ?2, ?3, ?4, ?5, ?6, 1
+             FROM session_summaries WHERE session_id = ?1",
+            params![
+                session_id,
+                template.as_str(),
+                model,
+                language,
+                content,
+                created_at
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    let summary = transaction
+        .query_row(
+            &format!(
+                "SELECT {} FROM session_summaries WHERE id = ?1",
+                SUMMARY_COLUMNS
+            ),
+            params![transaction.last_insert_rowid()],
+            summary_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    transaction.commit().map_err(|e| e.to_string())?;
+
+    tracing::info!(
+        "Saved summary version {} of session {}",
+        summary.version,
+        session_id
+    );
+    Ok(summary)
+}
+
+/// Every summary of a session, newest first
+#[tauri::command]
+pub fn list_summary_versions(
+    db: State<'_, Database>,
+    session_id: String,
+) -> Result<Vec<SessionSummary>, String> {
+    db.connection()?
+        .prepare(&format!(
+            "SELECT {} FROM session_summaries WHERE session_id = ?1 ORDER BY version DESC",
+            SUMMARY_COLUMNS
+        ))
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], summary_from_row)?
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Show an earlier (or later) version as the session's summary
+#[tauri::command]
+pub fn set_primary_summary(
+    db: State<'_, Database>,
+    session_id: String,
+    version: i64,
+) -> Result<(), String> {
+    let updated = db
+        .connection()?
+        .execute(
+            "UPDATE session_summaries SET is_primary = (version = ?2) WHERE session_id = ?1
+             AND EXISTS (SELECT 1 FROM session_summaries WHERE session_id = ?1 AND version = ?2)",
+            params![session_id, version],
+        )
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(t!(
+            "error.summary_not_found",
+            id = session_id,
+            version = version
+        ));
+    }
+    Ok(())
+}
+
+/// The summary a session shows, if it has one
+pub(crate) fn primary_summary(
+    connection: &Connection,
+    session_id: &str,
+) -> rusqlite::Result<Option<SessionSummary>> {
+    connection
+        .query_row(
+            &format!(
+                "SELECT {} FROM session_summaries WHERE session_id = ?1 AND is_primary = 1",
+                SUMMARY_COLUMNS
+            ),
+            params![session_id],
+            summary_from_row,
+        )
+        .optional()
+}
+
+/// The transcript with speakers and times, followed by the user's notes
+fn session_text(connection: &Connection, session_id: &str) -> Result<String, String> {
+    let mut lines: Vec<String> = connection
+        .prepare(
+            "SELECT speaker, text, start_ms, confidence FROM transcript_segments
+             WHERE session_id = ?1 ORDER BY start_ms, id",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    let speaker: Option<String> = row.get(0)?;
+                    let text: String = row.get(1)?;
+                    let unclear = if db::is_low_confidence(row.get(3)?) {
+                        " (unclear)"
+                    } else {
+                        ""
+                    };
+                    Ok(format!(
+                        "[{}] {}: {}{}",
+                        format_offset(row.get(2)?),
+                        speaker.as_deref().unwrap_or("Speaker"),
+                        text,
+                        unclear
+                    ))
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())?;
+
+    let notes: Vec<String> = connection
+        .prepare("SELECT kind, content FROM notes WHERE session_id = ?1 ORDER BY created_at, id")
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok(format!(
+                        "- [{}] {}",
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?
+                    ))
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())?;
+    if !notes.is_empty() {
+        lines.push(String::new());
+        lines.push("Notes taken during the session:".to_string());
+        lines.extend(notes);
+    }
+    Ok(lines.join("\n"))
+}
+
+fn summary_from_row(row: &Row) -> rusqlite::Result<SessionSummary> {
+    let template: String = row.get(1)?;
+    Ok(SessionSummary {
+        version: row.get(0)?,
+        template: SummaryTemplate::parse(&template),
+        model: row.get(2)?,
+        language: row.get(3)?,
+        content: row.get(4)?,
+        created_at: row.get(5)?,
+        primary: row.get(6)?,
+    })
+}
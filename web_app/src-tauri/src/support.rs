@@ -0,0 +1,399 @@
+// Queen Mama LITE - Support Bundles
+// Packs a session's timings, event sequence and logs into a zip for bug reports, without the
+// conversation itself
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::{self, Database};
+use crate::i18n::t;
+use crate::logging::{self, LogState};
+
+/// Bumped when the bundle layout changes
+const BUNDLE_VERSION: u32 = 1;
+
+/// Logs from this long before and after the session are included, to catch setup and teardown
+const LOG_MARGIN_MS: i64 = 5 * 60 * 1000;
+
+/// Newest log lines kept when more were logged around the session
+const MAX_LOG_LINES: usize = 5000;
+
+/// Stands in for private log fields and addresses taken out of the logs
+const REDACTED: &str = "[redacted]";
+
+/// Speaker labels we set ourselves; they say nothing about who spoke
+const OWN_SPEAKERS: &[&str] = &["me", "them"];
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    version: u32,
+    created_at: i64,
+    app_version: String,
+    os: String,
+    arch: String,
+    session: SessionTimings,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionTimings {
+    /// None while the session is still running
+    duration_ms: Option<i64>,
+    journal: bool,
+    /// From the start to the first transcript segment
+    first_segment_ms: Option<i64>,
+    segments: usize,
+    notes: usize,
+    comments: usize,
+    draft_revisions: usize,
+    attachments: usize,
+}
+
+/// Something that happened in the session, `atMs` after it started
+#[derive(serde::Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum BundleEvent {
+    /// Letters become x and digits 0, so the text keeps its length, spacing and punctuation
+    Segment {
+        at_ms: i64,
+        end_ms: i64,
+        speaker: Option<String>,
+        text: String,
+        confidence: Option<f32>,
+    },
+    Note {
+        at_ms: i64,
+        note_kind: String,
+        length: usize,
+    },
+    Comment {
+        at_ms: i64,
+        reply: bool,
+        length: usize,
+    },
+    DraftRevision {
+        at_ms: i64,
+        version: i64,
+        length: usize,
+    },
+    Attachment {
+        at_ms: i64,
+        mime_type: String,
+        size: i64,
+    },
+}
+
+impl BundleEvent {
+    fn at_ms(&self) -> i64 {
+        match self {
+            BundleEvent::Segment { at_ms, .. }
+            | BundleEvent::Note { at_ms, .. }
+            | BundleEvent::Comment { at_ms, .. }
+            | BundleEvent::DraftRevision { at_ms, .. }
+            | BundleEvent::Attachment { at_ms, .. } => *at_ms,
+        }
+    }
+}
+
+struct SessionRow {
+    started_at: i64,
+    ended_at: Option<i64>,
+    journal: bool,
+}
+
+/// Zip an anonymized copy of a session for a bug report: its timings, the order things happened
+/// in, the transcript's shape without its words, and the logs from around it
+#[tauri::command]
+pub fn create_support_bundle(
+    app: AppHandle,
+    db: State<'_, Database>,
+    logs: State<'_, LogState>,
+    session_id: String,
+    zip_path: PathBuf,
+) -> Result<String, String> {
+    let (session, mut events) = {
+        let connection = db.connection()?;
+        let session = load_session(&connection, &session_id)?;
+        let events =
+            load_events(&connection, &session_id, session.started_at).map_err(|e| e.to_string())?;
+        (session, events)
+    };
+    events.sort_by_key(BundleEvent::at_ms);
+
+    let home = app
+        .path()
+        .home_dir()
+        .ok()
+        .map(|home| home.to_string_lossy().into_owned());
+    let until = session.ended_at.unwrap_or_else(db::now_ms);
+    let mut lines =
+        logs.lines_between(session.started_at - LOG_MARGIN_MS, until + LOG_MARGIN_MS)?;
+    lines.drain(..lines.len().saturating_sub(MAX_LOG_LINES));
+    let lines: Vec<String> = lines
+        .iter()
+        .map(|line| scrub(line, home.as_deref()))
+        .collect();
+
+    let count = |is_kind: fn(&BundleEvent) -> bool| events.iter().filter(|e| is_kind(e)).count();
+    let manifest = Manifest {
+        version: BUNDLE_VERSION,
+        created_at: db::now_ms(),
+        app_version: app.package_info().version.to_string(),
+        os: format!(
+            "{} {}",
+            tauri_plugin_os::platform(),
+            tauri_plugin_os::version()
+        ),
+        arch: tauri_plugin_os::arch().to_string(),
+        session: SessionTimings {
+            duration_ms: session
+                .ended_at
+                .map(|ended_at| ended_at - session.started_at),
+            journal: session.journal,
+            first_segment_ms: events.iter().find_map(|event| match event {
+                BundleEvent::Segment { at_ms, .. } => Some(*at_ms),
+                _ => None,
+            }),
+            segments: count(|e| matches!(e, BundleEvent::Segment { .. })),
+            notes: count(|e| matches!(e, BundleEvent::Note { .. })),
+            comments: count(|e| matches!(e, BundleEvent::Comment { .. })),
+            draft_revisions: count(|e| matches!(e, BundleEvent::DraftRevision { .. })),
+            attachments: count(|e| matches!(e, BundleEvent::Attachment { .. })),
+        },
+    };
+
+    let file = File::create(&zip_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, contents) in [
+        ("manifest.json", to_json(&manifest)?),
+        ("events.json", to_json(&events)?),
+        ("logs.txt", lines.join("\n")),
+    ] {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(contents.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+
+    tracing::info!(
+        events = events.len(),
+        log_lines = lines.len(),
+        path = ?zip_path,
+        "Created a support bundle"
+    );
+    Ok(zip_path.to_string_lossy().into_owned())
+}
+
+fn load_session(connection: &Connection, session_id: &str) -> Result<SessionRow, String> {
+    connection
+        .query_row(
+            "SELECT started_at, ended_at, journal_date IS NOT NULL FROM sessions WHERE id = ?1",
+            params![session_id],
+            |row| {
+                Ok(SessionRow {
+                    started_at: row.get(0)?,
+                    ended_at: row.get(1)?,
+                    journal: row.get(2)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.session_not_found", id = session_id))
+}
+
+/// Everything recorded in the session
+fn load_events(
+    connection: &Connection,
+    session_id: &str,
+    started_at: i64,
+) -> rusqlite::Result<Vec<BundleEvent>> {
+    let mut events = Vec::new();
+
+    // Imported transcripts can name people; they become "speaker 1", "speaker 2"...
+    let mut speakers: HashMap<String, String> = HashMap::new();
+    let mut statement = connection.prepare(
+        "SELECT speaker, text, start_ms, end_ms, confidence FROM transcript_segments
+         WHERE session_id = ?1 ORDER BY start_ms, id",
+    )?;
+    let rows = statement.query_map(params![session_id], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, Option<f32>>(4)?,
+        ))
+    })?;
+    for row in rows {
+        let (speaker, text, at_ms, end_ms, confidence) = row?;
+        let speaker = speaker.map(|speaker| {
+            if OWN_SPEAKERS.contains(&speaker.as_str()) {
+                return speaker;
+            }
+            let next = speakers.len() + 1;
+            speakers
+                .entry(speaker)
+                .or_insert_with(|| format!("speaker {}", next))
+                .clone()
+        });
+        events.push(BundleEvent::Segment {
+            at_ms,
+            end_ms,
+            speaker,
+            text: redact(&text),
+            confidence,
+        });
+    }
+
+    let mut statement =
+        connection.prepare("SELECT kind, content, created_at FROM notes WHERE session_id = ?1")?;
+    let rows = statement.query_map(params![session_id], |row| {
+        Ok(BundleEvent::Note {
+            note_kind: row.get(0)?,
+            length: row.get::<_, String>(1)?.chars().count(),
+            at_ms: row.get::<_, i64>(2)? - started_at,
+        })
+    })?;
+    events.extend(rows.collect::<rusqlite::Result<Vec<_>>>()?);
+
+    let mut statement = connection.prepare(
+        "SELECT content, at_ms, parent_id IS NOT NULL FROM session_comments WHERE session_id = ?1",
+    )?;
+    let rows = statement.query_map(params![session_id], |row| {
+        Ok(BundleEvent::Comment {
+            length: row.get::<_, String>(0)?.chars().count(),
+            at_ms: row.get(1)?,
+            reply: row.get(2)?,
+        })
+    })?;
+    events.extend(rows.collect::<rusqlite::Result<Vec<_>>>()?);
+
+    let mut statement = connection.prepare(
+        "SELECT version, content, saved_at FROM note_draft_revisions WHERE session_id = ?1",
+    )?;
+    let rows = statement.query_map(params![session_id], |row| {
+        Ok(BundleEvent::DraftRevision {
+            version: row.get(0)?,
+            length: row.get::<_, String>(1)?.chars().count(),
+            at_ms: row.get::<_, i64>(2)? - started_at,
+        })
+    })?;
+    events.extend(rows.collect::<rusqlite::Result<Vec<_>>>()?);
+
+    let mut statement = connection.prepare(
+        "SELECT a.mime_type, a.size, a.created_at FROM note_attachments a
+         JOIN notes n ON n.id = a.note_id WHERE n.session_id = ?1",
+    )?;
+    let rows = statement.query_map(params![session_id], |row| {
+        Ok(BundleEvent::Attachment {
+            mime_type: row.get(0)?,
+            size: row.get(1)?,
+            at_ms: row.get::<_, i64>(2)? - started_at,
+        })
+    })?;
+    events.extend(rows.collect::<rusqlite::Result<Vec<_>>>()?);
+
+    Ok(events)
+}
+
+/// Keep a text's shape without its words
+fn redact(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_alphabetic() {
+                'x'
+            } else if c.is_numeric() {
+                '0'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Blank out a log line's private fields (see `logging::PRIVATE_FIELDS`), then the user's home
+/// folder and email addresses in case one slipped into a message
+fn scrub(line: &str, home: Option<&str>) -> String {
+    let mut scrubbed = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(at) = rest.find('=') {
+        let name_start = rest[..at].rfind([' ', '{']).map_or(0, |i| i + 1);
+        let private = logging::is_private_field(&rest[name_start..at]);
+        scrubbed.push_str(&rest[..=at]);
+        rest = &rest[at + 1..];
+        if private {
+            scrubbed.push_str(REDACTED);
+            rest = &rest[value_len(rest)..];
+        }
+    }
+    scrubbed.push_str(rest);
+
+    if let Some(home) = home {
+        scrubbed = scrubbed.replace(home, "~");
+    }
+    scrubbed
+        .split(' ')
+        .map(|word| if word.contains('@') { REDACTED } else { word })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Length of the field value `text` starts with: a quoted string up to its closing quote, or
+/// anything else up to the next space or the end of a span's fields
+fn value_len(text: &str) -> usize {
+    let Some(quoted) = text.strip_prefix('"') else {
+        return text.find([' ', '}']).unwrap_or(text.len());
+    };
+    let mut escaped = false;
+    for (i, c) in quoted.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return i + 2,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scrub;
+
+    #[test]
+    fn scrub_blanks_private_fields_whatever_they_hold() {
+        let line = r#"2026-10-16T12:00:00Z  INFO command{request_id=r1}: queen_mama_lite_lib::import: Imported a file path="/Users/jane/Calls/a \"b\" c.m4a" lost_device="Jane's AirPods" size=3"#;
+        assert_eq!(
+            scrub(line, Some("/Users/jane")),
+            "2026-10-16T12:00:00Z  INFO command{request_id=r1}: queen_mama_lite_lib::import: \
+             Imported a file path=[redacted] lost_device=[redacted] size=3"
+        );
+    }
+
+    #[test]
+    fn scrub_takes_home_and_addresses_out_of_messages() {
+        let line = "WARN queen_mama_lite_lib::auth: Sign-in failed for jane@example.com: \
+                    /Users/jane/Library missing";
+        assert_eq!(
+            scrub(line, Some("/Users/jane")),
+            "WARN queen_mama_lite_lib::auth: Sign-in failed for [redacted] ~/Library missing"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+// Queen Mama LITE - System Audio (macOS)
+// The other side of a call straight from ScreenCaptureKit, so no virtual loopback device has
+// to be installed; audio.rs falls back to one when ScreenCaptureKit can't be used
+
+use std::ptr::NonNull;
+use std::sync::mpsc::{self, Sender};
+use std::time::Duration;
+
+use block2::RcBlock;
+use objc2::rc::Retained;
+use objc2::runtime::ProtocolObject;
+use objc2::{define_class, msg_send, AllocAnyThread, DefinedClass};
+use objc2_core_media::{CMSampleBuffer, CMTime};
+use objc2_foundation::{NSArray, NSError, NSObject, NSObjectProtocol};
+use objc2_screen_capture_kit::{
+    SCContentFilter, SCShareableContent, SCStream, SCStreamConfiguration, SCStreamOutput,
+    SCStreamOutputType,
+};
+
+use crate::capture;
+use crate::i18n::t;
+
+/// Stored as the device of system recordings made this way
+pub(crate) const DEVICE_NAME: &str = "ScreenCaptureKit";
+
+/// ScreenCaptureKit resamples to whatever it's asked for; this is its native rate
+pub(crate) const SAMPLE_RATE: u32 = 48_000;
+
+/// ScreenCaptureKit answers through callbacks; this long without one counts as a failure
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct OutputIvars {
+    samples: Sender<Vec<f32>>,
+}
+
+define_class!(
+    // Receives the stream's audio on ScreenCaptureKit's own queue
+    #[unsafe(super(NSObject))]
+    #[name = "QueenMamaSystemAudioOutput"]
+    #[ivars = OutputIvars]
+    struct AudioOutput;
+
+    unsafe impl NSObjectProtocol for AudioOutput {}
+
+    unsafe impl SCStreamOutput for AudioOutput {
+        #[unsafe(method(stream:didOutputSampleBuffer:ofType:))]
+        fn stream_did_output_sample_buffer(
+            &self,
+            _stream: &SCStream,
+            sample_buffer: &CMSampleBuffer,
+            kind: SCStreamOutputType,
+        ) {
+            if kind != SCStreamOutputType::Audio {
+                return;
+            }
+            if let Some(samples) = read_samples(sample_buffer) {
+                let _ = self.ivars().samples.send(samples);
+            }
+        }
+    }
+);
+
+impl AudioOutput {
+    fn new(samples: Sender<Vec<f32>>) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(OutputIvars { samples });
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+/// System audio being captured; dropping it stops the capture
+pub(crate) struct SystemAudioStream {
+    stream: Retained<SCStream>,
+    // The stream holds its outputs weakly
+    _output: Retained<AudioOutput>,
+}
+
+impl Drop for SystemAudioStream {
+    fn drop(&mut self) {
+        unsafe { self.stream.stopCaptureWithCompletionHandler(None) };
+    }
+}
+
+/// Start sending everything the Mac plays, minus this app, to `samples` as mono
+/// `SAMPLE_RATE` audio. Needs macOS 13 and the Screen Recording permission.
+pub(crate) fn start(samples: Sender<Vec<f32>>) -> Result<SystemAudioStream, String> {
+    if !capture::has_screen_recording_permission() {
+        return Err(t!("feature.permission.screen_recording"));
+    }
+    let content = shareable_content()?;
+
+    unsafe {
+        let display = content
+            .displays()
+            .firstObject()
+            .ok_or_else(|| t!("error.no_display"))?;
+        let filter = SCContentFilter::initWithDisplay_excludingWindows(
+            SCContentFilter::alloc(),
+            &display,
+            &NSArray::new(),
+        );
+
+        let configuration = SCStreamConfiguration::new();
+        configuration.setCapturesAudio(true);
+        configuration.setExcludesCurrentProcessAudio(true);
+        configuration.setSampleRate(SAMPLE_RATE as isize);
+        configuration.setChannelCount(1);
+        // Video can't be turned off, so it's kept tiny and slow
+        configuration.setWidth(2);
+        configuration.setHeight(2);
+        configuration.setMinimumFrameInterval(CMTime::new(1, 1));
+
+        let stream = SCStream::initWithFilter_configuration_delegate(
+            SCStream::alloc(),
+            &filter,
+            &configuration,
+            None,
+        );
+        let output = AudioOutput::new(samples);
+        stream
+            .addStreamOutput_type_sampleHandlerQueue_error(
+                ProtocolObject::from_ref(&*output),
+                SCStreamOutputType::Audio,
+                None,
+            )
+            .map_err(|e| e.localizedDescription().to_string())?;
+
+        let (done_tx, done_rx) = mpsc::channel();
+        let completion = RcBlock::new(move |error: *mut NSError| {
+            let _ = done_tx.send(error.as_ref().map(|e| e.localizedDescription().to_string()));
+        });
+        stream.startCaptureWithCompletionHandler(Some(&completion));
+        match done_rx.recv_timeout(CALLBACK_TIMEOUT) {
+            Ok(None) => {}
+            Ok(Some(e)) => return Err(e),
+            Err(_) => return Err(t!("error.system_audio_timeout")),
+        }
+
+        tracing::info!("Capturing system audio with ScreenCaptureKit");
+        Ok(SystemAudioStream {
+            stream,
+            _output: output,
+        })
+    }
+}
+
+fn shareable_content() -> Result<Retained<SCShareableContent>, String> {
+    let (content_tx, content_rx) = mpsc::channel();
+    let completion = RcBlock::new(
+        move |content: *mut SCShareableContent, error: *mut NSError| {
+            let content = unsafe { Retained::retain(content) }.ok_or_else(|| {
+                unsafe { error.as_ref() }
+                    .map(|e| e.localizedDescription().to_string())
+                    .unwrap_or_default()
+            });
+            let _ = content_tx.send(content);
+        },
+    );
+    unsafe { SCShareableContent::getShareableContentWithCompletionHandler(&completion) };
+    content_rx
+        .recv_timeout(CALLBACK_TIMEOUT)
+        .map_err(|_| t!("error.system_audio_timeout"))?
+}
+
+/// The buffer's audio; with one channel asked for, it's a single run of 32-bit floats
+fn read_samples(sample_buffer: &CMSampleBuffer) -> Option<Vec<f32>> {
+    unsafe {
+        let block = sample_buffer.data_buffer()?;
+        let length = block.data_length();
+        let mut samples = vec![0f32; length / size_of::<f32>()];
+        let destination = NonNull::new(samples.as_mut_ptr())?.cast();
+        (block.copy_data_bytes(0, samples.len() * size_of::<f32>(), destination) == 0)
+            .then_some(samples)
+    }
+}
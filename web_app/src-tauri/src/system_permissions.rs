@@ -0,0 +1,296 @@
+// Queen Mama LITE - System Permissions
+// Checks and asks for the macOS privacy permissions capture depends on; other platforms don't ask,
+// so everything reads as granted there
+
+#[cfg(target_os = "macos")]
+use tauri::Manager;
+use tauri::{App, AppHandle};
+
+#[cfg(target_os = "macos")]
+use crate::events::VersionedEmitter;
+
+/// How often changes made in System Settings are picked up
+#[cfg(target_os = "macos")]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionKind {
+    ScreenRecording,
+    Microphone,
+    Accessibility,
+}
+
+const KINDS: [PermissionKind; 3] = [
+    PermissionKind::ScreenRecording,
+    PermissionKind::Microphone,
+    PermissionKind::Accessibility,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionStatus {
+    Granted,
+    /// Refused; for Screen Recording and Accessibility also not asked yet, since macOS doesn't
+    /// tell the two apart
+    Denied,
+    /// Never asked, so requesting shows the system prompt
+    NotDetermined,
+}
+
+/// Emitted as `permission_changed`, e.g. once the user flips a switch in System Settings
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionState {
+    kind: PermissionKind,
+    status: PermissionStatus,
+}
+
+/// Permissions already prompted for since launch; asking again opens System Settings instead
+#[cfg(target_os = "macos")]
+#[derive(Default)]
+struct Prompted(std::sync::Mutex<std::collections::HashSet<PermissionKind>>);
+
+pub fn setup_system_permissions(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(target_os = "macos")]
+    {
+        app.manage(Prompted::default());
+        let app_handle = app.app_handle().clone();
+        std::thread::spawn(move || watch(app_handle));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+
+    Ok(())
+}
+
+/// Get whether a permission is granted
+#[tauri::command]
+pub fn check_permission(kind: PermissionKind) -> PermissionStatus {
+    status(kind)
+}
+
+/// Get every permission at once, for an onboarding checklist
+#[tauri::command]
+pub fn list_permissions() -> Vec<PermissionState> {
+    KINDS
+        .iter()
+        .map(|kind| PermissionState {
+            kind: *kind,
+            status: status(*kind),
+        })
+        .collect()
+}
+
+/// Ask for a permission: the system prompt the first time, then the right page of System Settings.
+/// The answer arrives as `permission_changed`.
+#[tauri::command]
+pub fn request_permission(app: AppHandle, kind: PermissionKind) -> PermissionStatus {
+    let current = status(kind);
+
+    #[cfg(target_os = "macos")]
+    if current != PermissionStatus::Granted {
+        let first = app
+            .state::<Prompted>()
+            .0
+            .lock()
+            .map(|mut prompted| prompted.insert(kind))
+            .unwrap_or(false);
+        if first || current == PermissionStatus::NotDetermined {
+            macos::prompt(kind);
+        } else if let Err(e) = open::that(macos::settings_pane(kind)) {
+            tracing::error!("Failed to open System Settings: {}", e);
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+
+    current
+}
+
+fn status(kind: PermissionKind) -> PermissionStatus {
+    #[cfg(target_os = "macos")]
+    {
+        let granted = |granted: bool| {
+            if granted {
+                PermissionStatus::Granted
+            } else {
+                PermissionStatus::Denied
+            }
+        };
+        match kind {
+            PermissionKind::ScreenRecording => granted(macos::screen_recording()),
+            PermissionKind::Accessibility => granted(macos::accessibility(false)),
+            PermissionKind::Microphone => match macos::microphone() {
+                macos::AUTHORIZED => PermissionStatus::Granted,
+                macos::NOT_DETERMINED => PermissionStatus::NotDetermined,
+                // Denied, or restricted by a device management profile
+                _ => PermissionStatus::Denied,
+            },
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = kind;
+        PermissionStatus::Granted
+    }
+}
+
+/// Emit `permission_changed` whenever a permission flips, wherever it was changed from
+#[cfg(target_os = "macos")]
+fn watch(app: AppHandle) {
+    let mut last: Vec<PermissionStatus> = KINDS.iter().map(|kind| status(*kind)).collect();
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        for (kind, last) in KINDS.iter().zip(last.iter_mut()) {
+            let current = status(*kind);
+            if current != *last {
+                *last = current;
+                let change = PermissionState {
+                    kind: *kind,
+                    status: current,
+                };
+                let _ = app.emit("permission_changed", change);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::{c_char, c_void};
+
+    use super::PermissionKind;
+
+    type Id = *mut c_void;
+
+    /// `AVAuthorizationStatus` values
+    pub const NOT_DETERMINED: isize = 0;
+    pub const AUTHORIZED: isize = 3;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        static kAXTrustedCheckOptionPrompt: *const c_void;
+        fn AXIsProcessTrustedWithOptions(options: *const c_void) -> bool;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFBooleanTrue: *const c_void;
+        static kCFTypeDictionaryKeyCallBacks: c_void;
+        static kCFTypeDictionaryValueCallBacks: c_void;
+        fn CFDictionaryCreate(
+            allocator: *const c_void,
+            keys: *const *const c_void,
+            values: *const *const c_void,
+            count: isize,
+            key_callbacks: *const c_void,
+            value_callbacks: *const c_void,
+        ) -> *const c_void;
+        fn CFRelease(object: *const c_void);
+    }
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: Id;
+    }
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Id;
+        fn objc_msgSend();
+    }
+
+    pub fn screen_recording() -> bool {
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+
+    /// Whether we may watch and send input; with `prompt`, macOS asks the user if it hasn't yet
+    pub fn accessibility(prompt: bool) -> bool {
+        if !prompt {
+            return unsafe { AXIsProcessTrustedWithOptions(std::ptr::null()) };
+        }
+        unsafe {
+            let options = CFDictionaryCreate(
+                std::ptr::null(),
+                &kAXTrustedCheckOptionPrompt,
+                &kCFBooleanTrue,
+                1,
+                &kCFTypeDictionaryKeyCallBacks as *const c_void,
+                &kCFTypeDictionaryValueCallBacks as *const c_void,
+            );
+            let trusted = AXIsProcessTrustedWithOptions(options);
+            CFRelease(options);
+            trusted
+        }
+    }
+
+    /// `[AVCaptureDevice authorizationStatusForMediaType:AVMediaTypeAudio]`
+    pub fn microphone() -> isize {
+        unsafe {
+            let send: unsafe extern "C" fn(Id, Id, Id) -> isize =
+                std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+            send(
+                objc_getClass(c"AVCaptureDevice".as_ptr()),
+                sel_registerName(c"authorizationStatusForMediaType:".as_ptr()),
+                AVMediaTypeAudio,
+            )
+        }
+    }
+
+    /// Show the system prompt; macOS only shows it while the permission is undecided
+    pub fn prompt(kind: PermissionKind) {
+        match kind {
+            PermissionKind::ScreenRecording => {
+                unsafe { CGRequestScreenCaptureAccess() };
+            }
+            PermissionKind::Accessibility => {
+                accessibility(true);
+            }
+            // Opening the input device is what makes macOS ask
+            PermissionKind::Microphone => {
+                use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+                let Some(device) = cpal::default_host().default_input_device() else {
+                    return;
+                };
+                let Ok(config) = device.default_input_config() else {
+                    return;
+                };
+                let stream = device.build_input_stream(
+                    &config.into(),
+                    |_: &[f32], _: &cpal::InputCallbackInfo| {},
+                    |_| {},
+                    None,
+                );
+                if let Ok(stream) = stream {
+                    let _ = stream.play();
+                }
+            }
+        }
+    }
+
+    pub fn settings_pane(kind: PermissionKind) -> &'static str {
+        match kind {
+            PermissionKind::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+            PermissionKind::Microphone => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone"
+            }
+            PermissionKind::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+        }
+    }
+}
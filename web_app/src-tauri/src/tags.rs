@@ -0,0 +1,595 @@
+// Queen Mama LITE - Session Tags
+// Free-form tags and typed custom fields (deal size, interview stage...) on sessions, to filter
+// the history by and to break analytics down with
+
+use std::collections::HashMap;
+
+use chrono::{Local, TimeZone};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::{self, Database, SessionFilter};
+use crate::i18n::t;
+use crate::topics::TrendRange;
+
+/// Longer tags and field names don't fit the history's chips and columns
+const MAX_NAME_CHARS: usize = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FieldKind {
+    Text,
+    Number,
+    /// Unix ms
+    Date,
+    Boolean,
+}
+
+impl FieldKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            FieldKind::Text => "text",
+            FieldKind::Number => "number",
+            FieldKind::Date => "date",
+            FieldKind::Boolean => "boolean",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "text" => Some(FieldKind::Text),
+            "number" => Some(FieldKind::Number),
+            "date" => Some(FieldKind::Date),
+            "boolean" => Some(FieldKind::Boolean),
+            _ => None,
+        }
+    }
+}
+
+/// A custom field's value on a session, e.g. `{ "kind": "number", "value": 25000 }`
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "camelCase")]
+pub enum FieldValue {
+    Text(String),
+    Number(f64),
+    Date(i64),
+    Boolean(bool),
+}
+
+impl FieldValue {
+    pub fn kind(&self) -> FieldKind {
+        match self {
+            FieldValue::Text(_) => FieldKind::Text,
+            FieldValue::Number(_) => FieldKind::Number,
+            FieldValue::Date(_) => FieldKind::Date,
+            FieldValue::Boolean(_) => FieldKind::Boolean,
+        }
+    }
+
+    /// Stored with SQLite's own types, so values compare and sort as numbers where they are
+    fn to_sql(&self) -> Value {
+        match self {
+            FieldValue::Text(text) => Value::Text(text.clone()),
+            FieldValue::Number(number) => Value::Real(*number),
+            FieldValue::Date(ms) => Value::Integer(*ms),
+            FieldValue::Boolean(flag) => Value::Integer(*flag as i64),
+        }
+    }
+
+    fn from_sql(kind: FieldKind, value: Value) -> Option<Self> {
+        match (kind, value) {
+            (FieldKind::Text, Value::Text(text)) => Some(FieldValue::Text(text)),
+            (FieldKind::Number, Value::Real(number)) => Some(FieldValue::Number(number)),
+            (FieldKind::Number, Value::Integer(number)) => Some(FieldValue::Number(number as f64)),
+            (FieldKind::Date, Value::Integer(ms)) => Some(FieldValue::Date(ms)),
+            (FieldKind::Boolean, Value::Integer(flag)) => Some(FieldValue::Boolean(flag != 0)),
+            _ => None,
+        }
+    }
+
+    /// How exports print it
+    pub fn display(&self) -> String {
+        match self {
+            FieldValue::Text(text) => text.clone(),
+            FieldValue::Number(number) => number.to_string(),
+            FieldValue::Date(ms) => Local
+                .timestamp_millis_opt(*ms)
+                .single()
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            FieldValue::Boolean(true) => "Yes".to_string(),
+            FieldValue::Boolean(false) => "No".to_string(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomField {
+    name: String,
+    kind: FieldKind,
+    /// Sessions with a value for it
+    sessions: i64,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionField {
+    pub name: String,
+    pub value: FieldValue,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    tag: String,
+    sessions: i64,
+}
+
+/// Sessions whose field `name` is set, optionally to `equals` or within `min`..=`max`; bounds
+/// apply to number and date fields
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldFilter {
+    name: String,
+    #[serde(default)]
+    equals: Option<FieldValue>,
+    #[serde(default)]
+    min: Option<f64>,
+    #[serde(default)]
+    max: Option<f64>,
+}
+
+impl FieldFilter {
+    /// SQL condition on sessions aliased `s`, numbering its parameters after those in `values`
+    pub(crate) fn condition(&self, values: &mut Vec<Value>) -> String {
+        values.push(Value::Text(self.name.clone()));
+        let mut condition = format!(
+            "EXISTS (SELECT 1 FROM session_fields sf WHERE sf.session_id = s.id AND sf.name = ?{}",
+            values.len()
+        );
+        if let Some(equals) = &self.equals {
+            values.push(equals.to_sql());
+            condition.push_str(&format!(" AND sf.value = ?{}", values.len()));
+        }
+        if let Some(min) = self.min {
+            values.push(Value::Real(min));
+            condition.push_str(&format!(" AND sf.value >= ?{}", values.len()));
+        }
+        if let Some(max) = self.max {
+            values.push(Value::Real(max));
+            condition.push_str(&format!(" AND sf.value <= ?{}", values.len()));
+        }
+        condition.push(')');
+        condition
+    }
+}
+
+/// What `get_session_breakdown` groups sessions by
+#[derive(Clone, serde::Deserialize)]
+#[serde(tag = "by", content = "field", rename_all = "camelCase")]
+pub enum Breakdown {
+    /// Sessions with several tags count towards each
+    Tag,
+    /// The value of this custom field
+    Field(String),
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakdownGroup {
+    /// The tag or field value; `None` gathers sessions without one
+    key: Option<FieldValue>,
+    sessions: i64,
+    /// Time recorded, over finished sessions
+    duration_ms: i64,
+    segments: i64,
+}
+
+/// Every tag in use, most used first
+#[tauri::command]
+pub fn list_tags(db: State<'_, Database>) -> Result<Vec<TagCount>, String> {
+    db.connection()?
+        .prepare(
+            "SELECT st.tag, COUNT(*) FROM session_tags st
+             JOIN sessions s ON s.id = st.session_id AND s.deleted_at IS NULL
+             GROUP BY st.tag ORDER BY COUNT(*) DESC, st.tag",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| {
+                    Ok(TagCount {
+                        tag: row.get(0)?,
+                        sessions: row.get(1)?,
+                    })
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Tag a session, returning its tags; tags are matched regardless of case
+#[tauri::command]
+pub fn add_session_tag(
+    db: State<'_, Database>,
+    session_id: String,
+    tag: String,
+) -> Result<Vec<String>, String> {
+    let tag = valid_tag(&tag)?;
+    let connection = db.connection()?;
+    ensure_session(&connection, &session_id)?;
+    connection
+        .execute(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?1, ?2)",
+            params![session_id, tag],
+        )
+        .map_err(|e| e.to_string())?;
+    session_tags(&connection, &session_id).map_err(|e| e.to_string())
+}
+
+/// Untag a session, returning its remaining tags
+#[tauri::command]
+pub fn remove_session_tag(
+    db: State<'_, Database>,
+    session_id: String,
+    tag: String,
+) -> Result<Vec<String>, String> {
+    let connection = db.connection()?;
+    connection
+        .execute(
+            "DELETE FROM session_tags WHERE session_id = ?1 AND tag = ?2",
+            params![session_id, tag.trim()],
+        )
+        .map_err(|e| e.to_string())?;
+    session_tags(&connection, &session_id).map_err(|e| e.to_string())
+}
+
+/// Rename a tag on every session; renaming it to a tag already in use merges the two
+#[tauri::command]
+pub fn rename_tag(db: State<'_, Database>, tag: String, new_tag: String) -> Result<(), String> {
+    let tag = tag.trim();
+    let new_tag = valid_tag(&new_tag)?;
+    let mut connection = db.connection()?;
+
+    // Tags are matched regardless of case, so a change of case is the only rename in place
+    if tag.eq_ignore_ascii_case(&new_tag) {
+        connection
+            .execute(
+                "UPDATE session_tags SET tag = ?2 WHERE tag = ?1",
+                params![tag, new_tag],
+            )
+            .map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    let transaction = connection.transaction().map_err(|e| e.to_string())?;
+    transaction
+        .execute(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag)
+             SELECT session_id, ?2 FROM session_tags WHERE tag = ?1",
+            params![tag, new_tag],
+        )
+        .and_then(|_| transaction.execute("DELETE FROM session_tags WHERE tag = ?1", params![tag]))
+        .map_err(|e| e.to_string())?;
+    transaction.commit().map_err(|e| e.to_string())
+}
+
+/// Remove a tag from every session
+#[tauri::command]
+pub fn delete_tag(db: State<'_, Database>, tag: String) -> Result<(), String> {
+    db.connection()?
+        .execute(
+            "DELETE FROM session_tags WHERE tag = ?1",
+            params![tag.trim()],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Custom fields, by name
+#[tauri::command]
+pub fn list_custom_fields(db: State<'_, Database>) -> Result<Vec<CustomField>, String> {
+    db.connection()?
+        .prepare(
+            "SELECT f.name, f.kind, (SELECT COUNT(*) FROM session_fields sf WHERE sf.name = f.name)
+             FROM custom_fields f ORDER BY f.name",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| {
+                    let kind: String = row.get(1)?;
+                    Ok(CustomField {
+                        name: row.get(0)?,
+                        kind: FieldKind::parse(&kind).unwrap_or(FieldKind::Text),
+                        sessions: row.get(2)?,
+                    })
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Add a custom field every session can be given a value for
+#[tauri::command]
+pub fn create_custom_field(
+    db: State<'_, Database>,
+    name: String,
+    kind: FieldKind,
+) -> Result<CustomField, String> {
+    let name = valid_field_name(&name)?;
+    let connection = db.connection()?;
+    if field_kind(&connection, &name)?.is_some() {
+        return Err(t!("error.field_exists", name = name));
+    }
+    connection
+        .execute(
+            "INSERT INTO custom_fields (name, kind, created_at) VALUES (?1, ?2, ?3)",
+            params![name, kind.as_str(), db::now_ms()],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(CustomField {
+        name,
+        kind,
+        sessions: 0,
+    })
+}
+
+/// Rename a custom field, keeping its values
+#[tauri::command]
+pub fn rename_custom_field(
+    db: State<'_, Database>,
+    name: String,
+    new_name: String,
+) -> Result<(), String> {
+    let new_name = valid_field_name(&new_name)?;
+    let connection = db.connection()?;
+    if field_kind(&connection, &name)?.is_none() {
+        return Err(t!("error.field_not_found", name = name));
+    }
+    if !new_name.eq_ignore_ascii_case(&name) && field_kind(&connection, &new_name)?.is_some() {
+        return Err(t!("error.field_exists", name = new_name));
+    }
+    // Values follow through `ON UPDATE CASCADE`
+    connection
+        .execute(
+            "UPDATE custom_fields SET name = ?2 WHERE name = ?1",
+            params![name, new_name],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Delete a custom field and its value on every session
+#[tauri::command]
+pub fn delete_custom_field(db: State<'_, Database>, name: String) -> Result<(), String> {
+    db.connection()?
+        .execute("DELETE FROM custom_fields WHERE name = ?1", params![name])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Set a session's value for a custom field, or clear it with `None`
+#[tauri::command]
+pub fn set_session_field(
+    db: State<'_, Database>,
+    session_id: String,
+    name: String,
+    value: Option<FieldValue>,
+) -> Result<Vec<SessionField>, String> {
+    let connection = db.connection()?;
+    ensure_session(&connection, &session_id)?;
+    let kind =
+        field_kind(&connection, &name)?.ok_or_else(|| t!("error.field_not_found", name = name))?;
+
+    match value {
+        Some(value) => {
+            if value.kind() != kind {
+                return Err(t!("error.field_value_invalid", name = name));
+            }
+            connection.execute(
+                "INSERT INTO session_fields (session_id, name, value) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (session_id, name) DO UPDATE SET value = excluded.value",
+                params![session_id, name, value.to_sql()],
+            )
+        }
+        None => connection.execute(
+            "DELETE FROM session_fields WHERE session_id = ?1 AND name = ?2",
+            params![session_id, name],
+        ),
+    }
+    .map_err(|e| e.to_string())?;
+
+    session_fields(&connection, &session_id).map_err(|e| e.to_string())
+}
+
+/// Count sessions started in `range` and matching `filter` by tag or by a custom field's value,
+/// largest group first
+#[tauri::command]
+pub async fn get_session_breakdown(
+    app: AppHandle,
+    group_by: Breakdown,
+    range: Option<TrendRange>,
+    filter: Option<SessionFilter>,
+) -> Result<Vec<BreakdownGroup>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        let connection = db.connection()?;
+        breakdown(
+            &connection,
+            &group_by,
+            range.unwrap_or_default(),
+            &filter.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// A session's tags, alphabetically
+pub(crate) fn session_tags(
+    connection: &Connection,
+    session_id: &str,
+) -> rusqlite::Result<Vec<String>> {
+    connection
+        .prepare("SELECT tag FROM session_tags WHERE session_id = ?1 ORDER BY tag")
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| row.get(0))?
+                .collect()
+        })
+}
+
+/// A session's custom field values, by field name
+pub(crate) fn session_fields(
+    connection: &Connection,
+    session_id: &str,
+) -> rusqlite::Result<Vec<SessionField>> {
+    let rows: Vec<(String, String, Value)> = connection
+        .prepare(
+            "SELECT sf.name, f.kind, sf.value FROM session_fields sf
+             JOIN custom_fields f ON f.name = sf.name
+             WHERE sf.session_id = ?1 ORDER BY sf.name",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })?
+                .collect()
+        })?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(name, kind, value)| {
+            let value = FieldValue::from_sql(FieldKind::parse(&kind)?, value)?;
+            Some(SessionField { name, value })
+        })
+        .collect())
+}
+
+fn breakdown(
+    connection: &Connection,
+    group_by: &Breakdown,
+    range: TrendRange,
+    filter: &SessionFilter,
+) -> Result<Vec<BreakdownGroup>, String> {
+    let (mut conditions, mut values) = filter.conditions();
+    let (from, to) = range.bounds();
+    values.push(Value::Integer(from));
+    values.push(Value::Integer(to));
+    conditions.push(format!(
+        "s.started_at BETWEEN ?{} AND ?{} AND s.deleted_at IS NULL",
+        values.len() - 1,
+        values.len()
+    ));
+
+    let (join, kind) = match group_by {
+        Breakdown::Tag => (
+            "LEFT JOIN session_tags g ON g.session_id = s.id".to_string(),
+            FieldKind::Text,
+        ),
+        Breakdown::Field(name) => {
+            let kind = field_kind(connection, name)?
+                .ok_or_else(|| t!("error.field_not_found", name = name))?;
+            values.push(Value::Text(name.clone()));
+            (
+                format!(
+                    "LEFT JOIN session_fields g ON g.session_id = s.id AND g.name = ?{}",
+                    values.len()
+                ),
+                kind,
+            )
+        }
+    };
+    let key = match group_by {
+        Breakdown::Tag => "g.tag",
+        Breakdown::Field(_) => "g.value",
+    };
+
+    let sql = format!(
+        "SELECT {key}, COUNT(*), COALESCE(SUM(s.ended_at - s.started_at), 0),
+            SUM((SELECT COUNT(*) FROM transcript_segments t WHERE t.session_id = s.id))
+         FROM sessions s {join}
+         WHERE {}
+         GROUP BY {key}
+         ORDER BY COUNT(*) DESC, {key}",
+        conditions.join(" AND ")
+    );
+    let rows: Vec<(Value, i64, i64, i64)> = connection
+        .prepare(&sql)
+        .and_then(|mut statement| {
+            statement
+                .query_map(params_from_iter(values), |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())?;
+
+    // Number fields can hold 3 and 3.0; they're one group
+    let mut groups: Vec<BreakdownGroup> = Vec::new();
+    let mut index: HashMap<Option<String>, usize> = HashMap::new();
+    for (value, sessions, duration_ms, segments) in rows {
+        let key = FieldValue::from_sql(kind, value);
+        let id = key.as_ref().map(FieldValue::display);
+        match index.get(&id) {
+            Some(&i) => {
+                groups[i].sessions += sessions;
+                groups[i].duration_ms += duration_ms;
+                groups[i].segments += segments;
+            }
+            None => {
+                index.insert(id, groups.len());
+                groups.push(BreakdownGroup {
+                    key,
+                    sessions,
+                    duration_ms,
+                    segments,
+                });
+            }
+        }
+    }
+    groups.sort_by(|a, b| b.sessions.cmp(&a.sessions));
+    Ok(groups)
+}
+
+fn field_kind(connection: &Connection, name: &str) -> Result<Option<FieldKind>, String> {
+    let kind: Option<String> = connection
+        .query_row(
+            "SELECT kind FROM custom_fields WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(kind.and_then(|kind| FieldKind::parse(&kind)))
+}
+
+fn ensure_session(connection: &Connection, session_id: &str) -> Result<(), String> {
+    connection
+        .query_row(
+            "SELECT 1 FROM sessions WHERE id = ?1",
+            params![session_id],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.session_not_found", id = session_id))
+}
+
+pub(crate) fn valid_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() || tag.chars().count() > MAX_NAME_CHARS || tag.chars().any(char::is_control) {
+        return Err(t!("error.tag_invalid", max = MAX_NAME_CHARS));
+    }
+    Ok(tag.to_string())
+}
+
+fn valid_field_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(t!("error.field_name_invalid", max = MAX_NAME_CHARS));
+    }
+    Ok(name.to_string())
+}
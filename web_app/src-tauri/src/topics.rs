@@ -0,0 +1,333 @@
+// Queen Mama LITE - Topic Trends
+// Picks out the key phrases of each finished session once, and counts them by day, week or month
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{Local, TimeZone};
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Manager};
+
+use crate::db::{self, Database};
+
+/// Key phrases kept per session
+const KEYWORDS_PER_SESSION: usize = 20;
+
+/// A phrase said only once isn't a theme of the session
+const MIN_MENTIONS: usize = 2;
+
+/// Longer runs of words are rarely a single topic
+const MAX_PHRASE_WORDS: usize = 3;
+
+/// Shorter words are too ambiguous to stand for a topic
+const MIN_WORD_CHARS: usize = 3;
+
+/// Topics returned when the caller doesn't say how many
+const DEFAULT_TOPIC_LIMIT: usize = 20;
+
+/// Words that split phrases rather than belong to them, in every language the app speaks
+#[rustfmt::skip]
+const STOP_WORDS: &[&str] = &[
+    // English
+    "about", "after", "again", "all", "also", "and", "any", "are", "because", "been", "before",
+    "being", "but", "can", "could", "did", "does", "doing", "don't", "down", "each", "even",
+    "for", "from", "get", "going", "gonna", "got", "had", "has", "have", "having", "her", "here",
+    "him", "his", "how", "i'm", "into", "it's", "its", "just", "know", "like", "let's", "maybe",
+    "mean", "more", "most", "much", "need", "not", "now", "off", "okay", "one", "only", "other",
+    "our", "out", "over", "really", "right", "said", "say", "see", "she", "should", "some",
+    "something", "sure", "than", "that", "that's", "the", "their", "them", "then", "there",
+    "these", "they", "thing", "things", "think", "this", "those", "through", "too", "under",
+    "very", "want", "was", "way", "well", "were", "what", "when", "where", "which", "while",
+    "who", "why", "will", "with", "would", "yeah", "yes", "you", "you're", "your",
+    // French
+    "alors", "après", "aussi", "avec", "avoir", "bien", "c'est", "cela", "ces", "cette", "comme",
+    "dans", "des", "donc", "elle", "elles", "encore", "est", "être", "fait", "faire", "ils",
+    "j'ai", "les", "leur", "mais", "moi", "mon", "même", "nous", "oui", "par", "pas", "peu",
+    "plus", "pour", "quand", "que", "qui", "quoi", "sans", "ses", "son", "sont", "sur", "tout",
+    "très", "une", "vos", "votre", "vous", "voilà",
+    // Spanish
+    "algo", "así", "bien", "como", "con", "cuando", "del", "desde", "donde", "ella", "ellos",
+    "entonces", "era", "esa", "ese", "eso", "esta", "este", "esto", "están", "hay", "las", "los",
+    "más", "muy", "nos", "para", "pero", "por", "porque", "pues", "qué", "ser", "sin", "sobre",
+    "son", "sus", "también", "tiene", "todo", "una", "uno", "usted", "vamos",
+    // German
+    "aber", "alle", "also", "auch", "auf", "aus", "bei", "bin", "bis", "das", "dass", "dem",
+    "den", "der", "des", "die", "dies", "doch", "ein", "eine", "einen", "für", "gibt",
+    "habe", "haben", "hat", "ich", "ihr", "ist", "jetzt", "kann", "mal", "man", "mit", "nach",
+    "nicht", "noch", "nur", "oder", "schon", "sehr", "sich", "sie", "sind", "und", "uns", "von",
+    "was", "wenn", "wie", "wir", "wird", "zum", "zur",
+];
+
+/// How topics are bucketed over time
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrendPeriod {
+    Day,
+    /// ISO weeks, starting on Monday
+    Week,
+    Month,
+}
+
+impl TrendPeriod {
+    /// Label of the period a time falls in; labels sort in time order
+    fn label(self, at_ms: i64) -> Option<String> {
+        let format = match self {
+            TrendPeriod::Day => "%Y-%m-%d",
+            TrendPeriod::Week => "%G-W%V",
+            TrendPeriod::Month => "%Y-%m",
+        };
+        let time = Local.timestamp_millis_opt(at_ms).single()?;
+        Some(time.format(format).to_string())
+    }
+}
+
+/// Sessions started in this range, in ms since the epoch; either end may be left open
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrendRange {
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+impl TrendRange {
+    pub(crate) fn bounds(self) -> (i64, i64) {
+        (self.from.unwrap_or(i64::MIN), self.to.unwrap_or(i64::MAX))
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicTrends {
+    /// Periods with at least one session, oldest first
+    periods: Vec<String>,
+    /// Most mentioned first
+    topics: Vec<TopicTrend>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicTrend {
+    keyword: String,
+    total: i64,
+    /// Mentions in each of `periods`, in the same order
+    counts: Vec<i64>,
+}
+
+/// Count the key phrases of sessions started in `range` by period, for the `limit` most mentioned.
+/// Phrases are picked out of sessions the first time they're asked about, so this can take a while
+/// after a busy month.
+#[tauri::command]
+pub async fn get_topic_trends(
+    app: AppHandle,
+    range: TrendRange,
+    group_by: TrendPeriod,
+    limit: Option<usize>,
+) -> Result<TopicTrends, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<Database>();
+        extract_pending(&db, range)?;
+        trends(
+            &db.connection()?,
+            range,
+            group_by,
+            limit.unwrap_or(DEFAULT_TOPIC_LIMIT),
+        )
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Forget every session's key phrases, so they're picked out again
+pub(crate) fn reset(connection: &Connection) -> rusqlite::Result<()> {
+    connection.execute_batch(
+        "DELETE FROM session_keywords;
+         UPDATE sessions SET keywords_extracted_at = NULL;",
+    )
+}
+
+/// Pick out the key phrases of finished sessions in the range that don't have them yet
+fn extract_pending(db: &Database, range: TrendRange) -> Result<(), String> {
+    let (from, to) = range.bounds();
+    let pending: Vec<String> = db
+        .connection()?
+        .prepare(
+            "SELECT id FROM sessions
+             WHERE keywords_extracted_at IS NULL AND ended_at IS NOT NULL AND deleted_at IS NULL
+               AND started_at BETWEEN ?1 AND ?2",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![from, to], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    // The connection is let go between sessions, so recording carries on meanwhile
+    for session_id in &pending {
+        let texts = load_texts(&db.connection()?, session_id).map_err(|e| e.to_string())?;
+        let keywords = extract_keywords(&texts);
+        save_keywords(&mut db.connection()?, session_id, &keywords).map_err(|e| e.to_string())?;
+    }
+    if !pending.is_empty() {
+        tracing::info!("Extracted topics from {} sessions", pending.len());
+    }
+    Ok(())
+}
+
+/// What was said, and what the user noted; AI suggestions would skew it towards the prompts
+fn load_texts(connection: &Connection, session_id: &str) -> rusqlite::Result<Vec<String>> {
+    connection
+        .prepare(
+            "SELECT text FROM transcript_segments WHERE session_id = ?1
+             UNION ALL
+             SELECT content FROM notes WHERE session_id = ?1 AND kind != 'suggestion'",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![session_id], |row| row.get(0))?
+                .collect()
+        })
+}
+
+fn save_keywords(
+    connection: &mut Connection,
+    session_id: &str,
+    keywords: &[(String, usize)],
+) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "DELETE FROM session_keywords WHERE session_id = ?1",
+        params![session_id],
+    )?;
+    for (keyword, count) in keywords {
+        transaction.execute(
+            "INSERT INTO session_keywords (session_id, keyword, count) VALUES (?1, ?2, ?3)",
+            params![session_id, keyword, *count as i64],
+        )?;
+    }
+    transaction.execute(
+        "UPDATE sessions SET keywords_extracted_at = ?2 WHERE id = ?1",
+        params![session_id, db::now_ms()],
+    )?;
+    transaction.commit()
+}
+
+/// Key phrases and how often each came up, found with RAKE: phrases are runs of words between
+/// stop words and punctuation, and words score higher the longer the phrases they turn up in
+fn extract_keywords(texts: &[String]) -> Vec<(String, usize)> {
+    let mut phrases: Vec<Vec<String>> = Vec::new();
+    for text in texts {
+        let fragments = text.split(|c: char| {
+            !(c.is_alphanumeric() || c.is_whitespace() || c == '\'' || c == '’' || c == '-')
+        });
+        for fragment in fragments {
+            let mut phrase = Vec::new();
+            for word in fragment.split_whitespace() {
+                let word = word
+                    .replace('’', "'")
+                    .trim_matches(|c| c == '\'' || c == '-')
+                    .to_lowercase();
+                let is_content = word.chars().count() >= MIN_WORD_CHARS
+                    && !word.chars().all(|c| c.is_numeric())
+                    && !STOP_WORDS.contains(&word.as_str());
+                if is_content {
+                    phrase.push(word);
+                } else if !phrase.is_empty() {
+                    phrases.push(std::mem::take(&mut phrase));
+                }
+            }
+            if !phrase.is_empty() {
+                phrases.push(phrase);
+            }
+        }
+    }
+    phrases.retain(|phrase| phrase.len() <= MAX_PHRASE_WORDS);
+
+    let mut frequency: HashMap<&str, usize> = HashMap::new();
+    let mut degree: HashMap<&str, usize> = HashMap::new();
+    for phrase in &phrases {
+        for word in phrase {
+            *frequency.entry(word.as_str()).or_default() += 1;
+            *degree.entry(word.as_str()).or_default() += phrase.len();
+        }
+    }
+
+    let mut mentions: HashMap<String, (usize, f64)> = HashMap::new();
+    for phrase in &phrases {
+        let score: f64 = phrase
+            .iter()
+            .map(|word| degree[word.as_str()] as f64 / frequency[word.as_str()] as f64)
+            .sum();
+        mentions.entry(phrase.join(" ")).or_insert((0, score)).0 += 1;
+    }
+
+    // RAKE alone favours long phrases said once; weighting by mentions favours what kept coming up
+    let mut keywords: Vec<(String, usize, f64)> = mentions
+        .into_iter()
+        .filter(|(_, (count, _))| *count >= MIN_MENTIONS)
+        .map(|(keyword, (count, score))| (keyword, count, score * count as f64))
+        .collect();
+    keywords.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    keywords.truncate(KEYWORDS_PER_SESSION);
+    keywords
+        .into_iter()
+        .map(|(keyword, count, _)| (keyword, count))
+        .collect()
+}
+
+fn trends(
+    connection: &Connection,
+    range: TrendRange,
+    group_by: TrendPeriod,
+    limit: usize,
+) -> rusqlite::Result<TopicTrends> {
+    let (from, to) = range.bounds();
+    let mut statement = connection.prepare(
+        "SELECT k.keyword, k.count, s.started_at FROM session_keywords k
+         JOIN sessions s ON s.id = k.session_id
+         WHERE s.started_at BETWEEN ?1 AND ?2 AND s.deleted_at IS NULL",
+    )?;
+    let rows = statement.query_map(params![from, to], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+
+    let mut periods = BTreeSet::new();
+    let mut counts: HashMap<String, HashMap<String, i64>> = HashMap::new();
+    for row in rows {
+        let (keyword, count, started_at) = row?;
+        let Some(period) = group_by.label(started_at) else {
+            continue;
+        };
+        *counts
+            .entry(keyword)
+            .or_default()
+            .entry(period.clone())
+            .or_default() += count;
+        periods.insert(period);
+    }
+    let periods: Vec<String> = periods.into_iter().collect();
+
+    let mut topics: Vec<TopicTrend> = counts
+        .into_iter()
+        .map(|(keyword, by_period)| TopicTrend {
+            keyword,
+            total: by_period.values().sum(),
+            counts: periods
+                .iter()
+                .map(|period| by_period.get(period).copied().unwrap_or(0))
+                .collect(),
+        })
+        .collect();
+    topics.sort_by(|a, b| {
+        b.total
+            .cmp(&a.total)
+            .then_with(|| a.keyword.cmp(&b.keyword))
+    });
+    topics.truncate(limit);
+
+    Ok(TopicTrends { periods, topics })
+}
@@ -0,0 +1,189 @@
+// Queen Mama LITE - Request Tracing
+// Gives every command, shortcut and AI call a request id and keeps what was logged under it
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use tauri::ipc::Invoke;
+use tauri::{Runtime, State};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Registry;
+
+use crate::db;
+use crate::logging;
+
+/// Lets the frontend pick the request id, e.g. to tie several invokes to one assist
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Requests kept for `get_trace`, oldest dropped first
+const MAX_TRACES: usize = 500;
+
+/// Log lines kept per request
+const MAX_TRACE_ENTRIES: usize = 200;
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEntry {
+    /// Unix ms
+    timestamp: i64,
+    level: String,
+    module: String,
+    message: String,
+}
+
+#[derive(Clone, Default)]
+pub struct TraceState(Arc<Mutex<Traces>>);
+
+#[derive(Default)]
+struct Traces {
+    /// Request ids, oldest first
+    order: VecDeque<String>,
+    entries: HashMap<String, Vec<TraceEntry>>,
+}
+
+/// Set on spans with a `request_id` field so events inside them can find it
+struct SpanRequestId(String);
+
+/// Installed by `logging::setup_logging`; records our own debug and higher lines per request
+pub struct TraceLayer {
+    traces: TraceState,
+}
+
+impl TraceLayer {
+    pub fn new(traces: &TraceState) -> Self {
+        Self {
+            traces: traces.clone(),
+        }
+    }
+}
+
+impl<S> Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = TraceFields::default();
+        attrs.record(&mut fields);
+        if let (Some(request_id), Some(span)) = (fields.request_id, ctx.span(id)) {
+            span.extensions_mut().insert(SpanRequestId(request_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(request_id) = ctx.event_scope(event).and_then(|mut scope| {
+            scope.find_map(|span| {
+                span.extensions()
+                    .get::<SpanRequestId>()
+                    .map(|request_id| request_id.0.clone())
+            })
+        }) else {
+            return;
+        };
+
+        let metadata = event.metadata();
+        let mut fields = TraceFields::default();
+        event.record(&mut fields);
+        let entry = TraceEntry {
+            timestamp: db::now_ms(),
+            level: metadata.level().to_string(),
+            module: logging::module_name(metadata.target())
+                .unwrap_or(metadata.target())
+                .to_string(),
+            message: fields.message,
+        };
+
+        let Ok(mut traces) = self.traces.0.lock() else {
+            return;
+        };
+        if !traces.entries.contains_key(&request_id) {
+            if traces.order.len() >= MAX_TRACES {
+                if let Some(oldest) = traces.order.pop_front() {
+                    traces.entries.remove(&oldest);
+                }
+            }
+            traces.order.push_back(request_id.clone());
+        }
+        let entries = traces.entries.entry(request_id).or_default();
+        if entries.len() < MAX_TRACE_ENTRIES {
+            entries.push(entry);
+        }
+    }
+}
+
+/// Get what was logged while handling a request, oldest first
+#[tauri::command]
+pub fn get_trace(state: State<'_, TraceState>, request_id: String) -> Vec<TraceEntry> {
+    state
+        .0
+        .lock()
+        .ok()
+        .and_then(|traces| traces.entries.get(&request_id).cloned())
+        .unwrap_or_default()
+}
+
+/// Wrap the command handler so each invoke runs in a span with its request id.
+/// Async commands finish after the handler returns, so only their start is traced
+/// unless they carry the span over themselves.
+pub fn instrument<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let request_id = invoke
+            .message
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .unwrap_or_else(new_request_id);
+        let command = invoke.message.command().to_string();
+
+        let _span =
+            tracing::info_span!("command", request_id = %request_id, command = %command).entered();
+        tracing::debug!("Invoked {}", command);
+        handler(invoke)
+    }
+}
+
+pub fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Request id of the span we're running in, if any
+pub fn current_request_id() -> Option<String> {
+    tracing::Span::current()
+        .with_subscriber(|(id, dispatch)| {
+            let registry = dispatch.downcast_ref::<Registry>()?;
+            let span = registry.span(id)?;
+            span.scope().find_map(|span| {
+                span.extensions()
+                    .get::<SpanRequestId>()
+                    .map(|request_id| request_id.0.clone())
+            })
+        })
+        .flatten()
+}
+
+#[derive(Default)]
+struct TraceFields {
+    message: String,
+    request_id: Option<String>,
+}
+
+impl Visit for TraceFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            "request_id" => self.request_id = Some(format!("{:?}", value)),
+            _ => {}
+        }
+    }
+}
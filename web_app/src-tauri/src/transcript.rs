@@ -0,0 +1,152 @@
+// Queen Mama LITE - Transcript Paging
+// Reads a session's transcript a page at a time and tells the dashboard what changed, so a
+// multi-hour session never has to be held in memory whole
+
+use rusqlite::{params, params_from_iter};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::{self, Database, TranscriptSegment};
+use crate::events::VersionedEmitter;
+
+const DEFAULT_PAGE_SIZE: usize = 200;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Where a page ends: the position of its last segment in transcript order
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptCursor {
+    start_ms: i64,
+    id: i64,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptPage {
+    segments: Vec<TranscriptSegment>,
+    /// Pass back for the next page; None on the last one
+    next_cursor: Option<TranscriptCursor>,
+    /// Segments in the whole transcript
+    total: i64,
+}
+
+/// Emitted as `transcript_diff` whenever a session's transcript changes. When `reset` is set
+/// the transcript changed too much to describe, and pages loaded so far should be reloaded.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptDiff {
+    session_id: String,
+    added: Vec<TranscriptSegment>,
+    removed: Vec<i64>,
+    reset: bool,
+}
+
+/// Up to `limit` segments of a session's transcript in time order, after `cursor` if given
+/// else from the start. Pages stay consistent while segments are added, since the cursor is a
+/// position rather than an offset.
+#[tauri::command]
+pub fn get_transcript_page(
+    db: State<'_, Database>,
+    session_id: String,
+    cursor: Option<TranscriptCursor>,
+    limit: Option<usize>,
+) -> Result<TranscriptPage, String> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let (after_ms, after_id) =
+        cursor.map_or((i64::MIN, i64::MIN), |cursor| (cursor.start_ms, cursor.id));
+
+    let connection = db.connection()?;
+    let mut segments = connection
+        .prepare(&format!(
+            "SELECT {} FROM transcript_segments
+             WHERE session_id = ?1 AND (start_ms, id) > (?2, ?3)
+             ORDER BY start_ms, id LIMIT ?4",
+            db::SEGMENT_COLUMNS
+        ))
+        .and_then(|mut statement| {
+            statement
+                .query_map(
+                    params![session_id, after_ms, after_id, limit as i64 + 1],
+                    db::segment_from_row,
+                )?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+    let total = connection
+        .query_row(
+            "SELECT COUNT(*) FROM transcript_segments WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    // One extra row was read to tell whether another page follows
+    let next_cursor = if segments.len() > limit {
+        segments.truncate(limit);
+        segments.last().map(|segment| TranscriptCursor {
+            start_ms: segment.start_ms,
+            id: segment.id,
+        })
+    } else {
+        None
+    };
+    Ok(TranscriptPage {
+        segments,
+        next_cursor,
+        total,
+    })
+}
+
+/// Tell the dashboard about segments just saved
+pub(crate) fn segments_added(app: &AppHandle, session_id: &str, ids: &[i64]) {
+    if ids.is_empty() {
+        return;
+    }
+    let added = app.state::<Database>().connection().and_then(|connection| {
+        connection
+            .prepare(&format!(
+                "SELECT {} FROM transcript_segments WHERE id IN ({}) ORDER BY start_ms, id",
+                db::SEGMENT_COLUMNS,
+                vec!["?"; ids.len()].join(", ")
+            ))
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params_from_iter(ids), db::segment_from_row)?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| e.to_string())
+    });
+    match added {
+        Ok(added) => emit(app, session_id, added, Vec::new(), false),
+        Err(e) => tracing::warn!("Transcript diff skipped: {}", e),
+    }
+}
+
+/// Tell the dashboard about segments just deleted
+pub(crate) fn segments_removed(app: &AppHandle, session_id: &str, ids: Vec<i64>) {
+    if !ids.is_empty() {
+        emit(app, session_id, Vec::new(), ids, false);
+    }
+}
+
+/// Tell the dashboard to reload a session's transcript, e.g. after a merge or split
+pub(crate) fn transcript_reset(app: &AppHandle, session_id: &str) {
+    emit(app, session_id, Vec::new(), Vec::new(), true);
+}
+
+fn emit(
+    app: &AppHandle,
+    session_id: &str,
+    added: Vec<TranscriptSegment>,
+    removed: Vec<i64>,
+    reset: bool,
+) {
+    let diff = TranscriptDiff {
+        session_id: session_id.to_string(),
+        added,
+        removed,
+        reset,
+    };
+    if let Err(e) = app.emit("transcript_diff", diff) {
+        tracing::error!("Failed to emit event: {}", e);
+    }
+}
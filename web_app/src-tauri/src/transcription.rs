@@ -0,0 +1,988 @@
+// Queen Mama LITE - Local Transcription
+// Runs whisper.cpp on captured audio so live transcripts work without sending audio to the cloud
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+use crate::audio::{self, AudioSource, AudioState, PcmChunk};
+use crate::db::{self, Database, WordConfidence};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::mock::{self, MockConfig, MockFailure};
+use crate::notifications::{self, NoticeAction, NoticeKind};
+use crate::vad::{Voice, VoiceDetector};
+use crate::{compliance, demo, lora, session, transcript, SETTINGS_STORE};
+
+pub(crate) const WHISPER_MODEL_STORE_KEY: &str = "whisper_model";
+pub(crate) const WHISPER_CUSTOM_MODELS_STORE_KEY: &str = "whisper_custom_models";
+
+/// ggml models published with whisper.cpp, with their download size in MB
+const WHISPER_MODELS: &[(&str, u32)] = &[
+    ("tiny", 75),
+    ("base", 142),
+    ("small", 466),
+    ("medium", 1500),
+    ("large-v3-turbo", 1620),
+    /// Marks speaker turns, for diarizing transcribed files; English only
+    ("small.en-tdrz", 465),
+];
+const DEFAULT_WHISPER_MODEL: &str = "base";
+const MODEL_DOWNLOAD_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// First four bytes of a whisper.cpp ggml model ("ggml" as a little-endian u32)
+const GGML_MAGIC: [u8; 4] = *b"lmgg";
+
+/// Whisper expects 16 kHz mono
+pub(crate) const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+/// Speech is transcribed in windows of at most this length, each producing final segments; a
+/// pause ends the window early
+const WINDOW_SECONDS: usize = 10;
+
+/// How often the unfinished window is re-transcribed for partial results
+const PARTIAL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+pub struct TranscriptionState {
+    worker: Mutex<Option<Worker>>,
+}
+
+struct Worker {
+    model: String,
+    running: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhisperModelInfo {
+    name: String,
+    size_mb: u32,
+    downloaded: bool,
+    selected: bool,
+    /// Fine-tuned weights added from the user's own file
+    custom: bool,
+    /// The mock transcriber, which plays canned lines instead of listening
+    mock: bool,
+}
+
+/// A fine-tuned model used in place, from wherever the user keeps it
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomModel {
+    name: String,
+    path: PathBuf,
+    /// Made by merging a LoRA adapter into a stock model; the file is ours and goes with it
+    #[serde(default)]
+    merged: bool,
+}
+
+/// Emitted as `transcript_segment`; partial segments are replaced until a final one arrives
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptEvent {
+    recording_id: String,
+    source: AudioSource,
+    text: String,
+    start_ms: i64,
+    end_ms: i64,
+    /// Mean of the word confidences, 0.0 to 1.0
+    confidence: Option<f32>,
+    words: Vec<WordConfidence>,
+    low_confidence: bool,
+    is_final: bool,
+    /// When someone was actually talking during the segment
+    speech: Vec<SpeechInterval>,
+}
+
+/// A stretch of speech, in ms from the start of the recording
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpeechInterval {
+    start_ms: i64,
+    end_ms: i64,
+}
+
+/// Emitted as `voice_activity` when someone starts or stops talking on a source
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VoiceActivity {
+    recording_id: String,
+    source: AudioSource,
+    speaking: bool,
+    /// Position in the recording, ms
+    at_ms: i64,
+}
+
+pub fn setup_transcription(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(TranscriptionState::default());
+    Ok(())
+}
+
+/// List Whisper model sizes and which ones are downloaded
+#[tauri::command]
+pub fn list_whisper_models(app: AppHandle) -> Result<Vec<WhisperModelInfo>, String> {
+    let dir = models_dir(&app)?;
+    let selected = selected_model(&app);
+
+    let builtin = WHISPER_MODELS
+        .iter()
+        .map(|(name, size_mb)| WhisperModelInfo {
+            name: name.to_string(),
+            size_mb: *size_mb,
+            downloaded: dir.join(model_file(name)).exists(),
+            selected: *name == selected,
+            custom: false,
+            mock: false,
+        });
+    let custom = custom_models(&app).into_iter().map(|model| {
+        let size = fs::metadata(&model.path).map(|metadata| metadata.len());
+        WhisperModelInfo {
+            size_mb: size.as_ref().map(|size| (size / 1_000_000) as u32).unwrap_or(0),
+            downloaded: size.is_ok(),
+            selected: model.name == selected,
+            name: model.name,
+            custom: true,
+            mock: false,
+        }
+    });
+    let mock = WhisperModelInfo {
+        name: mock::MOCK_MODEL.to_string(),
+        size_mb: 0,
+        downloaded: true,
+        selected: selected == mock::MOCK_MODEL,
+        custom: false,
+        mock: true,
+    };
+    Ok(builtin.chain(custom).chain([mock]).collect())
+}
+
+/// Add fine-tuned Whisper weights from a ggml file, checking that whisper.cpp can load them. A
+/// LoRA adapter, as its PEFT folder or safetensors file, is merged into `base` (or the stock
+/// model its config names), which has to be downloaded.
+#[tauri::command]
+pub async fn add_custom_whisper_model(
+    app: AppHandle,
+    path: PathBuf,
+    name: Option<String>,
+    base: Option<String>,
+) -> Result<WhisperModelInfo, String> {
+    let adapter = lora::find_adapter(&path);
+    // Adapter files always have the same names, so an adapter is named after its folder
+    let default_name = if adapter.is_some() && path.is_file() {
+        path.parent().and_then(Path::file_name)
+    } else {
+        path.file_stem()
+    };
+    let name = name
+        .or_else(|| default_name.map(|stem| stem.to_string_lossy().into_owned()))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| t!("error.custom_model_needs_name"))?;
+    if name == mock::MOCK_MODEL
+        || check_model_name(&name).is_ok()
+        || custom_models(&app).iter().any(|model| model.name == name)
+    {
+        return Err(t!("error.whisper_model_exists", name = name));
+    }
+
+    let (path, size, merged) = match adapter {
+        Some(adapter) => {
+            let base = base
+                .or_else(|| lora::base_model(&adapter))
+                .ok_or_else(|| t!("error.lora_needs_base"))?;
+            check_model_name(&base)?;
+            let dir = models_dir(&app)?;
+            let base_path = dir.join(model_file(&base));
+            if !base_path.exists() {
+                return Err(t!("error.whisper_model_missing", name = base));
+            }
+            let output = dir.join(format!("lora-{}.bin", uuid::Uuid::new_v4().simple()));
+
+            let size = {
+                let output = output.clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    let adapted = lora::merge(&adapter, &base_path, &output)?;
+                    tracing::info!("Merged {} adapted weights into {}", adapted, base);
+                    validate_model(&output).inspect_err(|_| {
+                        let _ = fs::remove_file(&output);
+                    })
+                })
+                .await
+                .map_err(|e| e.to_string())??
+            };
+            (output, size, true)
+        }
+        None => {
+            let size = {
+                let path = path.clone();
+                tauri::async_runtime::spawn_blocking(move || validate_model(&path))
+                    .await
+                    .map_err(|e| e.to_string())??
+            };
+            (path, size, false)
+        }
+    };
+
+    let mut models = custom_models(&app);
+    models.push(CustomModel {
+        name: name.clone(),
+        path,
+        merged,
+    });
+    save_custom_models(&app, &models)?;
+
+    tracing::info!("Added custom model {}", name);
+    Ok(WhisperModelInfo {
+        name: name.clone(),
+        size_mb: (size / 1_000_000) as u32,
+        downloaded: true,
+        selected: selected_model(&app) == name,
+        custom: true,
+        mock: false,
+    })
+}
+
+/// Forget a custom model. The file itself is left where it is, unless it was merged from an
+/// adapter.
+#[tauri::command]
+pub fn remove_custom_whisper_model(app: AppHandle, name: String) -> Result<(), String> {
+    let mut models = custom_models(&app);
+    if let Some(model) = models
+        .iter()
+        .find(|model| model.name == name && model.merged)
+    {
+        if let Err(e) = fs::remove_file(&model.path) {
+            tracing::warn!("Failed to delete merged model {}: {}", name, e);
+        }
+    }
+    models.retain(|model| model.name != name);
+    save_custom_models(&app, &models)
+}
+
+/// Choose the model used for local transcription: a stock size, a custom model or the mock
+#[tauri::command]
+pub fn set_whisper_model(app: AppHandle, name: String) -> Result<(), String> {
+    if name != mock::MOCK_MODEL && !custom_models(&app).iter().any(|model| model.name == name) {
+        check_model_name(&name)?;
+    }
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(WHISPER_MODEL_STORE_KEY, name);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Download a model, emitting `whisper_model_progress` as it goes
+#[tauri::command]
+pub async fn download_whisper_model(app: AppHandle, name: String) -> Result<(), String> {
+    check_model_name(&name)?;
+
+    let path = models_dir(&app)?.join(model_file(&name));
+    let partial = path.with_extension("part");
+
+    let mut response = tauri_plugin_http::reqwest::get(format!(
+        "{}/{}",
+        MODEL_DOWNLOAD_URL,
+        model_file(&name)
+    ))
+    .await
+    .and_then(|response| response.error_for_status())
+    .map_err(|e| e.to_string())?;
+
+    let total = response.content_length();
+    let mut downloaded = 0u64;
+    let mut file = fs::File::create(&partial).map_err(|e| e.to_string())?;
+    let mut last_percent = None;
+
+    while let Some(bytes) = response.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&bytes).map_err(|e| e.to_string())?;
+        downloaded += bytes.len() as u64;
+
+        let percent = total.map(|total| (downloaded * 100 / total.max(1)) as u8);
+        if percent != last_percent {
+            last_percent = percent;
+            let _ = app.emit(
+                "whisper_model_progress",
+                serde_json::json!({ "name": name, "downloaded": downloaded, "total": total }),
+            );
+        }
+    }
+
+    file.flush().map_err(|e| e.to_string())?;
+    fs::rename(&partial, &path).map_err(|e| e.to_string())?;
+
+    tracing::info!("Downloaded model {}", name);
+    notifications::notify(
+        &app,
+        NoticeKind::ModelDownloaded,
+        &t!("notice.model_ready.title"),
+        &t!("notice.model_ready.body", name = name),
+        vec![(t!("action.open_settings"), NoticeAction::OpenSettings)],
+    );
+    Ok(())
+}
+
+/// Delete a downloaded model
+#[tauri::command]
+pub fn delete_whisper_model(app: AppHandle, name: String) -> Result<(), String> {
+    check_model_name(&name)?;
+
+    let path = models_dir(&app)?.join(model_file(&name));
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Start transcribing captured audio with the selected local model
+#[tauri::command]
+pub fn start_local_transcription(
+    app: AppHandle,
+    state: State<'_, TranscriptionState>,
+) -> Result<(), String> {
+    let mut worker = state.worker.lock().map_err(|e| e.to_string())?;
+    if worker.is_some() {
+        return Err(t!("error.transcription_running"));
+    }
+
+    // Demo mode and the mock play canned lines instead of listening, so they need no model
+    let scripted = if demo::is_enabled(&app) {
+        Some((demo::DEVICE_NAME, MockConfig::default()))
+    } else if is_mock_selected(&app) {
+        Some((mock::MOCK_MODEL, mock::load_config(&app)))
+    } else {
+        None
+    };
+    if let Some((model, config)) = scripted {
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let app = app.clone();
+            let running = running.clone();
+            thread::spawn(move || run_scripted_worker(app, config, running))
+        };
+        *worker = Some(Worker {
+            model: model.to_string(),
+            running,
+            thread,
+        });
+
+        tracing::info!("Started the {} transcript", model);
+        return Ok(());
+    }
+
+    let (model, path) = selected_model_path(&app)?;
+
+    let context = WhisperContext::new_with_params(
+        &path.to_string_lossy(),
+        WhisperContextParameters::default(),
+    )
+    .map_err(|e| t!("error.whisper_model_load", name = model, error = e))?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let chunks = audio::subscribe(&app.state::<AudioState>());
+    let thread = {
+        let app = app.clone();
+        let running = running.clone();
+        thread::spawn(move || run_worker(app, context, chunks, running))
+    };
+
+    *worker = Some(Worker {
+        model: model.clone(),
+        running,
+        thread,
+    });
+
+    tracing::info!("Started with model {}", model);
+    Ok(())
+}
+
+/// Stop local transcription, finalizing any buffered audio
+#[tauri::command]
+pub fn stop_local_transcription(state: State<'_, TranscriptionState>) -> Result<(), String> {
+    let Some(worker) = state.worker.lock().map_err(|e| e.to_string())?.take() else {
+        return Ok(());
+    };
+
+    worker.running.store(false, Ordering::SeqCst);
+    let _ = worker.thread.join();
+
+    tracing::info!("Stopped ({})", worker.model);
+    Ok(())
+}
+
+/// Stop the worker without waiting for it, as if it had died mid-stream, for chaos mode
+pub(crate) fn crash(app: &AppHandle) -> Result<(), String> {
+    let worker = app
+        .state::<TranscriptionState>()
+        .worker
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or_else(|| t!("error.transcription_not_running"))?;
+    worker.running.store(false, Ordering::SeqCst);
+
+    tracing::error!(
+        "Stopped unexpectedly ({}): {}",
+        worker.model,
+        t!("error.chaos_injected")
+    );
+    Ok(())
+}
+
+/// Clear a worker that stopped on its own, e.g. when the mock fails, so transcription can be
+/// started again. A worker already taken by `stop_local_transcription`, or replaced by a newer
+/// one, is left alone.
+fn finished(app: &AppHandle, running: &Arc<AtomicBool>) {
+    let state = app.state::<TranscriptionState>();
+    let Ok(mut worker) = state.worker.lock() else {
+        return;
+    };
+    if worker
+        .as_ref()
+        .is_some_and(|worker| Arc::ptr_eq(&worker.running, running))
+    {
+        // Dropping the handle just detaches this thread, which is about to return
+        *worker = None;
+    }
+}
+
+/// Speech of one source waiting to be transcribed, with the silences between it dropped
+struct SourceBuffer {
+    recording_id: String,
+    detector: VoiceDetector,
+    samples: Vec<f32>,
+    /// Where each stretch of speech starts: its index in `samples` and its position in the
+    /// recording, ms
+    runs: Vec<(usize, i64)>,
+}
+
+impl SourceBuffer {
+    fn new(recording_id: String) -> Self {
+        Self {
+            recording_id,
+            detector: VoiceDetector::new(),
+            samples: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Add speech, starting a new stretch if silence was dropped before it
+    fn append(&mut self, at_ms: i64, samples: &[f32]) {
+        let contiguous = self.runs.last().is_some_and(|&(index, start_ms)| {
+            start_ms + samples_to_ms(self.samples.len() - index) == at_ms
+        });
+        if !contiguous {
+            self.runs.push((self.samples.len(), at_ms));
+        }
+        self.samples.extend_from_slice(samples);
+    }
+
+    /// Position in the recording of a sample in the buffer, ms
+    fn position_ms(&self, index: usize) -> i64 {
+        let index = index.min(self.samples.len());
+        let (start, start_ms) = self
+            .runs
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= index)
+            .copied()
+            .unwrap_or((0, 0));
+        start_ms + samples_to_ms(index - start)
+    }
+
+    /// The stretches of speech between two positions in the recording, cut to them
+    fn speech_between(&self, from_ms: i64, to_ms: i64) -> Vec<SpeechInterval> {
+        self.runs
+            .iter()
+            .enumerate()
+            .filter_map(|(run, &(start, start_ms))| {
+                let end = self
+                    .runs
+                    .get(run + 1)
+                    .map_or(self.samples.len(), |(end, _)| *end);
+                let interval = SpeechInterval {
+                    start_ms: start_ms.max(from_ms),
+                    end_ms: (start_ms + samples_to_ms(end - start)).min(to_ms),
+                };
+                (interval.start_ms < interval.end_ms).then_some(interval)
+            })
+            .collect()
+    }
+}
+
+fn samples_to_ms(samples: usize) -> i64 {
+    (samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as i64
+}
+
+fn run_worker(
+    app: AppHandle,
+    context: WhisperContext,
+    mut chunks: broadcast::Receiver<Arc<PcmChunk>>,
+    running: Arc<AtomicBool>,
+) {
+    let mut whisper = match context.create_state() {
+        Ok(whisper) => whisper,
+        Err(e) => {
+            tracing::error!("Failed to create state: {}", e);
+            finished(&app, &running);
+            return;
+        }
+    };
+
+    let window_len = WHISPER_SAMPLE_RATE as usize * WINDOW_SECONDS;
+    let mut buffers: HashMap<AudioSource, SourceBuffer> = HashMap::new();
+    let mut last_partial = Instant::now();
+
+    while running.load(Ordering::SeqCst) {
+        match chunks.try_recv() {
+            Ok(chunk) => {
+                let buffer = buffers
+                    .entry(chunk.source)
+                    .or_insert_with(|| SourceBuffer::new(chunk.recording_id.clone()));
+
+                // A new recording restarts the timeline
+                if buffer.recording_id != chunk.recording_id {
+                    transcribe(&app, &mut whisper, chunk.source, buffer, true);
+                    *buffer = SourceBuffer::new(chunk.recording_id.clone());
+                }
+
+                // Only speech is buffered, so silence costs no inference
+                let samples = resample(&chunk.samples, chunk.sample_rate);
+                for voice in buffer.detector.push(&samples) {
+                    match voice {
+                        Voice::Audio { at_ms, samples } => buffer.append(at_ms, &samples),
+                        Voice::Started(at_ms) => {
+                            voice_activity(&app, &buffer.recording_id, chunk.source, true, at_ms)
+                        }
+                        Voice::Stopped(at_ms) => {
+                            voice_activity(&app, &buffer.recording_id, chunk.source, false, at_ms);
+                            // A pause is where a sentence ends, so it's where the window ends too
+                            transcribe(&app, &mut whisper, chunk.source, buffer, true);
+                        }
+                    }
+                }
+
+                if buffer.samples.len() >= window_len {
+                    transcribe(&app, &mut whisper, chunk.source, buffer, true);
+                }
+            }
+            Err(TryRecvError::Empty) => thread::sleep(Duration::from_millis(50)),
+            Err(TryRecvError::Lagged(missed)) => {
+                tracing::warn!("Fell behind, skipped {} chunks", missed);
+            }
+            Err(TryRecvError::Closed) => break,
+        }
+
+        if last_partial.elapsed() >= PARTIAL_INTERVAL {
+            last_partial = Instant::now();
+            for (source, buffer) in buffers.iter_mut() {
+                transcribe(&app, &mut whisper, *source, buffer, false);
+            }
+        }
+    }
+
+    for (source, buffer) in buffers.iter_mut() {
+        transcribe(&app, &mut whisper, *source, buffer, true);
+    }
+    finished(&app, &running);
+}
+
+/// Transcribe the buffered window; final results are saved to the active session and clear the buffer
+fn transcribe(
+    app: &AppHandle,
+    whisper: &mut whisper_rs::WhisperState,
+    source: AudioSource,
+    buffer: &mut SourceBuffer,
+    is_final: bool,
+) {
+    if buffer.samples.is_empty() {
+        return;
+    }
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some("auto"));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+    params.set_print_timestamps(false);
+
+    if let Err(e) = whisper.full(params, &buffer.samples) {
+        tracing::error!(
+            code = "transcription.inference_failed",
+            "Inference failed: {}",
+            e
+        );
+        return;
+    }
+
+    let session_id = if is_final {
+        session::active_session_id(app)
+    } else {
+        None
+    };
+
+    let segments = whisper.full_n_segments().unwrap_or(0);
+    for index in 0..segments {
+        let Ok(text) = whisper.full_get_segment_text(index) else {
+            continue;
+        };
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+
+        let words = segment_words(whisper, index);
+        let confidence = (!words.is_empty()).then(|| {
+            words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32
+        });
+
+        // Whisper timestamps are in centiseconds, over the speech with the silences dropped
+        let sample_at = |centiseconds: i64| {
+            (centiseconds.max(0) as u64 * WHISPER_SAMPLE_RATE as u64 / 100) as usize
+        };
+        let t0 = whisper.full_get_segment_t0(index).unwrap_or(0);
+        let t1 = whisper.full_get_segment_t1(index).unwrap_or(0);
+        let start_ms = buffer.position_ms(sample_at(t0));
+        let end_ms = buffer.position_ms(sample_at(t1));
+        let event = TranscriptEvent {
+            recording_id: buffer.recording_id.clone(),
+            source,
+            text,
+            start_ms,
+            end_ms,
+            low_confidence: db::is_low_confidence(confidence),
+            confidence,
+            words,
+            is_final,
+            speech: buffer.speech_between(start_ms, end_ms),
+        };
+
+        publish(app, session_id.as_deref(), event);
+    }
+
+    if is_final {
+        buffer.samples.clear();
+        buffer.runs.clear();
+    }
+}
+
+fn voice_activity(
+    app: &AppHandle,
+    recording_id: &str,
+    source: AudioSource,
+    speaking: bool,
+    at_ms: i64,
+) {
+    let activity = VoiceActivity {
+        recording_id: recording_id.to_string(),
+        source,
+        speaking,
+        at_ms,
+    };
+    let _ = app.emit("voice_activity", activity);
+}
+
+/// Save a final segment to the session, if there is one, and pass it to the frontend
+fn publish(app: &AppHandle, session_id: Option<&str>, event: TranscriptEvent) {
+    if let Some(session_id) = session_id {
+        let speaker = match event.source {
+            AudioSource::Microphone => "me",
+            AudioSource::System => "them",
+        };
+        match db::insert_segment(
+            &app.state::<Database>(),
+            session_id,
+            Some(speaker),
+            &event.text,
+            event.start_ms,
+            event.end_ms,
+            event.confidence,
+            &event.words,
+        ) {
+            Ok(segment_id) => {
+                compliance::scan_segment(
+                    app,
+                    session_id,
+                    segment_id,
+                    Some(speaker),
+                    &event.text,
+                    event.start_ms,
+                );
+                transcript::segments_added(app, session_id, &[segment_id]);
+            }
+            Err(e) => tracing::error!("Failed to save segment: {}", e),
+        }
+    }
+
+    let _ = app.emit("transcript_segment", event);
+}
+
+/// Play canned lines as if whisper had heard them, then free the worker's slot however it ended
+fn run_scripted_worker(app: AppHandle, config: MockConfig, running: Arc<AtomicBool>) {
+    play_script(&app, &config, &running);
+    finished(&app, &running);
+}
+
+/// Play the lines with a partial halfway through each. Segments arrive the mock latency after
+/// their line, and fail the way the mock is set to.
+fn play_script(app: &AppHandle, config: &MockConfig, running: &AtomicBool) {
+    let latency_ms = config.latency_ms as i64;
+    let started = Instant::now();
+    let wait_until = |ms: i64| {
+        while started.elapsed() < Duration::from_millis(ms as u64) {
+            if !running.load(Ordering::SeqCst) {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        true
+    };
+
+    for line in config.lines() {
+        let failure = config.roll_failure();
+        if let Some(MockFailure::Error | MockFailure::RateLimited) = failure {
+            tracing::error!("Mock transcriber failed");
+            return;
+        }
+
+        let words: Vec<&str> = line.text.split_whitespace().collect();
+        let partial = words[..words.len() / 2].join(" ");
+        let halfway = (line.start_ms + line.end_ms) / 2;
+        if !wait_until(halfway + latency_ms) {
+            return;
+        }
+        if failure != Some(MockFailure::Timeout) {
+            let _ = app.emit(
+                "transcript_segment",
+                scripted_event(line.source, &partial, line.start_ms, halfway, false),
+            );
+        }
+
+        if !wait_until(line.end_ms + latency_ms) {
+            return;
+        }
+        if failure.is_some() {
+            continue;
+        }
+        let session_id = session::active_session_id(app);
+        let event = scripted_event(line.source, &line.text, line.start_ms, line.end_ms, true);
+        publish(app, session_id.as_deref(), event);
+    }
+}
+
+/// Publish a final segment as if whisper had heard it, for the test harness
+#[cfg(feature = "test-harness")]
+pub(crate) fn publish_scripted(
+    app: &AppHandle,
+    source: AudioSource,
+    text: &str,
+    start_ms: i64,
+    end_ms: i64,
+) {
+    let session_id = session::active_session_id(app);
+    let event = scripted_event(source, text, start_ms, end_ms, true);
+    publish(app, session_id.as_deref(), event);
+}
+
+/// A segment that didn't come from whisper, every word at the demo confidence
+fn scripted_event(
+    source: AudioSource,
+    text: &str,
+    start_ms: i64,
+    end_ms: i64,
+    is_final: bool,
+) -> TranscriptEvent {
+    TranscriptEvent {
+        recording_id: demo::RECORDING_ID.to_string(),
+        source,
+        text: text.to_string(),
+        start_ms,
+        end_ms,
+        confidence: Some(demo::CONFIDENCE),
+        words: text
+            .split_whitespace()
+            .map(|word| WordConfidence {
+                text: word.to_string(),
+                confidence: demo::CONFIDENCE,
+            })
+            .collect(),
+        low_confidence: false,
+        is_final,
+        speech: vec![SpeechInterval { start_ms, end_ms }],
+    }
+}
+
+/// Words of a segment with the probability of their least certain token
+pub(crate) fn segment_words(
+    whisper: &whisper_rs::WhisperState,
+    segment: i32,
+) -> Vec<WordConfidence> {
+    let mut words: Vec<WordConfidence> = Vec::new();
+    for token in 0..whisper.full_n_tokens(segment).unwrap_or(0) {
+        let Ok(text) = whisper.full_get_token_text(segment, token) else {
+            continue;
+        };
+        // Special tokens such as [_BEG_] and <|en|> aren't speech
+        if text.starts_with("[_") || text.starts_with("<|") {
+            continue;
+        }
+        let probability = whisper.full_get_token_prob(segment, token).unwrap_or(0.0);
+
+        // Tokens starting with a space begin a new word
+        match words.last_mut() {
+            Some(word) if !text.starts_with(' ') => {
+                word.text.push_str(&text);
+                word.confidence = word.confidence.min(probability);
+            }
+            _ => words.push(WordConfidence {
+                text: text.trim_start().to_string(),
+                confidence: probability,
+            }),
+        }
+    }
+    words.retain(|word| !word.text.trim().is_empty());
+    words
+}
+
+/// Linear resample of 16-bit PCM to Whisper's 16 kHz float input
+fn resample(samples: &[i16], sample_rate: u32) -> Vec<f32> {
+    let input: Vec<f32> = samples.iter().map(|s| *s as f32 / 32768.0).collect();
+    resample_float(input, sample_rate)
+}
+
+/// Linear resample of float samples to Whisper's 16 kHz
+pub(crate) fn resample_float(input: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+    if sample_rate == WHISPER_SAMPLE_RATE || input.is_empty() {
+        return input;
+    }
+
+    let ratio = sample_rate as f64 / WHISPER_SAMPLE_RATE as f64;
+    let len = (input.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let next = input.get(index + 1).copied().unwrap_or(input[index]);
+            let fraction = (position - index as f64) as f32;
+            input[index] + (next - input[index]) * fraction
+        })
+        .collect()
+}
+
+/// The selected model and its file, or why it can't be loaded
+pub(crate) fn selected_model_path(app: &AppHandle) -> Result<(String, PathBuf), String> {
+    let model = selected_model(app);
+    let path = model_path(app, &model)?;
+    Ok((model, path))
+}
+
+/// The file of a stock or custom model, or why it can't be loaded
+pub(crate) fn model_path(app: &AppHandle, model: &str) -> Result<PathBuf, String> {
+    let custom = custom_models(app)
+        .into_iter()
+        .find(|custom| custom.name == model);
+    match custom {
+        Some(custom) => {
+            if !custom.path.exists() {
+                return Err(t!(
+                    "error.custom_model_missing",
+                    name = model,
+                    path = custom.path.display()
+                ));
+            }
+            Ok(custom.path)
+        }
+        None => {
+            check_model_name(model)?;
+            let path = models_dir(app)?.join(model_file(model));
+            if !path.exists() {
+                return Err(t!("error.whisper_model_missing", name = model));
+            }
+            Ok(path)
+        }
+    }
+}
+
+/// Whether the mock transcriber is selected instead of a whisper model
+pub(crate) fn is_mock_selected(app: &AppHandle) -> bool {
+    selected_model(app) == mock::MOCK_MODEL
+}
+
+pub(crate) fn selected_model(app: &AppHandle) -> String {
+    let custom = custom_models(app);
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(WHISPER_MODEL_STORE_KEY))
+        .and_then(|value| value.as_str().map(str::to_string))
+        .filter(|name| {
+            name == mock::MOCK_MODEL
+                || check_model_name(name).is_ok()
+                || custom.iter().any(|model| model.name == *name)
+        })
+        .unwrap_or_else(|| DEFAULT_WHISPER_MODEL.to_string())
+}
+
+/// Check a custom model file is ggml weights whisper.cpp can load, returning its size in bytes.
+/// Full PyTorch checkpoints have to be converted to ggml first.
+fn validate_model(path: &Path) -> Result<u64, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut magic = [0u8; 4];
+    if file.read_exact(&mut magic).is_err() || magic != GGML_MAGIC {
+        return Err(t!("error.not_ggml_model", path = path.display()));
+    }
+
+    WhisperContext::new_with_params(&path.to_string_lossy(), WhisperContextParameters::default())
+        .map_err(|e| t!("error.ggml_model_load", path = path.display(), error = e))?;
+
+    file.metadata().map(|metadata| metadata.len()).map_err(|e| e.to_string())
+}
+
+fn custom_models(app: &AppHandle) -> Vec<CustomModel> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(WHISPER_CUSTOM_MODELS_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save_custom_models(app: &AppHandle, models: &[CustomModel]) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        WHISPER_CUSTOM_MODELS_STORE_KEY,
+        serde_json::to_value(models).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+fn check_model_name(name: &str) -> Result<(), String> {
+    if WHISPER_MODELS.iter().any(|(model, _)| *model == name) {
+        Ok(())
+    } else {
+        Err(t!("error.unknown_whisper_model", name = name))
+    }
+}
+
+fn model_file(name: &str) -> String {
+    format!("ggml-{}.bin", name)
+}
+
+fn models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("models");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
@@ -0,0 +1,187 @@
+// Queen Mama LITE - Session Trash
+// Deleted sessions wait in the trash for a while before they're purged for good, so a session
+// deleted by mistake can be restored
+
+use std::thread;
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+use crate::db::{self, Database, Session, SessionRecording};
+use crate::i18n::t;
+use crate::SETTINGS_STORE;
+
+/// Days a deleted session stays in the trash; 0 keeps it until the trash is emptied
+pub(crate) const TRASH_RETENTION_STORE_KEY: &str = "trash_retention_days";
+
+pub(crate) const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// How often sessions past the retention window are looked for
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedSession {
+    #[serde(flatten)]
+    session: Session,
+    deleted_at: i64,
+    /// When it will be purged, if the retention window is on
+    purge_at: Option<i64>,
+}
+
+pub fn setup_trash(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let app_handle = app.app_handle().clone();
+    thread::spawn(move || loop {
+        if let Err(e) = purge_expired(&app_handle) {
+            // A locked history is purged once it's unlocked
+            tracing::debug!("Trash not purged: {}", e);
+        }
+        thread::sleep(PURGE_INTERVAL);
+    });
+    Ok(())
+}
+
+/// Sessions in the trash, most recently deleted first
+#[tauri::command]
+pub fn list_trash(
+    app: AppHandle,
+    db: State<'_, Database>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+) -> Result<Vec<TrashedSession>, String> {
+    let retention_ms = retention_days(&app) as i64 * DAY_MS;
+    db.connection()?
+        .prepare(&format!(
+            "SELECT {}, s.deleted_at FROM sessions s WHERE s.deleted_at IS NOT NULL
+             ORDER BY s.deleted_at DESC LIMIT ?1 OFFSET ?2",
+            db::SESSION_COLUMNS
+        ))
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![limit.unwrap_or(50), offset.unwrap_or(0)], |row| {
+                    let deleted_at: i64 = row.get(db::SESSION_COLUMN_COUNT)?;
+                    Ok(TrashedSession {
+                        session: db::session_from_row(row)?,
+                        deleted_at,
+                        purge_at: (retention_ms > 0).then_some(deleted_at + retention_ms),
+                    })
+                })?
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Take a session out of the trash
+#[tauri::command]
+pub fn restore_session(app: AppHandle, db: State<'_, Database>, id: String) -> Result<(), String> {
+    let restored = db
+        .connection()?
+        .execute(
+            "UPDATE sessions SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+    if restored == 0 {
+        return Err(t!("error.session_not_in_trash", id = id));
+    }
+    db::sessions_changed(&app);
+    tracing::info!("Restored session {} from the trash", id);
+    Ok(())
+}
+
+/// Delete a session in the trash for good
+#[tauri::command]
+pub fn purge_session(app: AppHandle, db: State<'_, Database>, id: String) -> Result<(), String> {
+    let purged = purge(&app, &db, Some(&id), i64::MAX)?;
+    if purged == 0 {
+        return Err(t!("error.session_not_in_trash", id = id));
+    }
+    Ok(())
+}
+
+/// Delete every session in the trash for good, returning how many there were
+#[tauri::command]
+pub fn empty_trash(app: AppHandle, db: State<'_, Database>) -> Result<usize, String> {
+    purge(&app, &db, None, i64::MAX)
+}
+
+/// Purge sessions that have been in the trash longer than the retention window
+fn purge_expired(app: &AppHandle) -> Result<(), String> {
+    let days = retention_days(app);
+    if days == 0 {
+        return Ok(());
+    }
+    let purged = purge(
+        app,
+        &app.state::<Database>(),
+        None,
+        db::now_ms() - days as i64 * DAY_MS,
+    )?;
+    if purged > 0 {
+        tracing::info!("Purged {} sessions from the trash", purged);
+    }
+    Ok(())
+}
+
+/// Delete trashed sessions, or just `id`, deleted before `deleted_before`, with their files
+fn purge(
+    app: &AppHandle,
+    db: &Database,
+    id: Option<&str>,
+    deleted_before: i64,
+) -> Result<usize, String> {
+    let purged = {
+        let mut connection = db.connection()?;
+        purge_rows(&mut connection, id, deleted_before).map_err(|e| e.to_string())?
+    };
+
+    if !purged.is_empty() {
+        db::sessions_changed(app);
+    }
+    // The rows are gone either way; a file that can't be removed only costs disk space
+    for (id, recordings) in &purged {
+        if let Err(e) = db::remove_session_files(app, id, recordings) {
+            tracing::warn!("Failed to remove files of session {}: {}", id, e);
+        }
+    }
+    Ok(purged.len())
+}
+
+/// Delete the rows of trashed sessions, returning their ids and recordings
+fn purge_rows(
+    connection: &mut Connection,
+    id: Option<&str>,
+    deleted_before: i64,
+) -> rusqlite::Result<Vec<(String, Vec<SessionRecording>)>> {
+    let transaction = connection.transaction()?;
+    let ids: Vec<String> = transaction
+        .prepare(
+            "SELECT id FROM sessions
+             WHERE deleted_at IS NOT NULL AND deleted_at < ?1 AND (?2 IS NULL OR id = ?2)",
+        )
+        .and_then(|mut statement| {
+            statement
+                .query_map(params![deleted_before, id], |row| row.get(0))?
+                .collect()
+        })?;
+    let mut purged = Vec::new();
+    for id in ids {
+        let recordings = db::session_recordings(&transaction, &id)?;
+        transaction.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        purged.push((id, recordings));
+    }
+    transaction.commit()?;
+    Ok(purged)
+}
+
+fn retention_days(app: &AppHandle) -> u32 {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(TRASH_RETENTION_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+}
@@ -1,13 +1,101 @@
 // Queen Mama LITE - System Tray
 // Provides menu bar access to core functionality
 
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use rusqlite::params;
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
-    tray::TrayIconBuilder,
-    App, Emitter, Manager,
+    menu::{
+        CheckMenuItemBuilder, MenuBuilder, MenuItem, MenuItemBuilder, PredefinedMenuItem, Submenu,
+        SubmenuBuilder,
+    },
+    tray::{TrayIcon, TrayIconBuilder},
+    App, AppHandle, Listener, Manager, State,
 };
 
+use crate::db::{self, Database};
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::session::SessionPhase;
+use crate::{profiles, session, shortcuts, updates, window};
+
+/// How often the "Now" submenu is refreshed during a session
+const NOW_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the recording time next to the session items is refreshed
+const SESSION_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sessions listed under "Recent Sessions"
+const RECENT_SESSIONS_LIMIT: u32 = 8;
+
+/// Dot drawn over the tray icon while recording
+const RECORDING_DOT: [u8; 4] = [0xE5, 0x3E, 0x3E, 0xFF];
+
+/// Session items and icon, kept in line with the session phase
+pub struct TraySession {
+    inner: Mutex<TraySessionState>,
+    tray: TrayIcon,
+    idle_icon: Image<'static>,
+    recording_icon: Image<'static>,
+    status_item: MenuItem<tauri::Wry>,
+    start_item: MenuItem<tauri::Wry>,
+    pause_item: MenuItem<tauri::Wry>,
+    stop_item: MenuItem<tauri::Wry>,
+}
+
+/// The parts of `session_state_changed` the tray cares about
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraySessionState {
+    phase: SessionPhase,
+    /// Unix time in milliseconds
+    started_at: Option<i64>,
+}
+
+/// Live session stats shown in the tray "Now" submenu. Elapsed time and talk ratio come from the
+/// session and its transcript; goals are tracked by the frontend, which reports how many are open.
+pub struct NowStatus {
+    inner: Mutex<NowStatusInner>,
+    elapsed_item: MenuItem<tauri::Wry>,
+    talk_ratio_item: MenuItem<tauri::Wry>,
+    open_goals_item: MenuItem<tauri::Wry>,
+}
+
+#[derive(Default)]
+struct NowStatusInner {
+    session: Option<NowSession>,
+    /// Reported by the frontend for the current session
+    open_goals: Option<u32>,
+}
+
+/// The parts of `session_state_changed` the "Now" submenu cares about
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NowSession {
+    phase: SessionPhase,
+    session_id: Option<String>,
+    /// Unix time in milliseconds
+    started_at: Option<i64>,
+}
+
+/// Items that show the global shortcut doing the same thing, by shortcut action
+pub struct ShortcutMenuItems {
+    items: Vec<(&'static str, MenuItem<tauri::Wry>)>,
+}
+
+/// "Profile" submenu, rebuilt whenever profiles are added, removed or switched
+pub struct ProfileMenu {
+    submenu: Submenu<tauri::Wry>,
+}
+
+/// "Recent Sessions" submenu, rebuilt whenever sessions are added or removed
+pub struct RecentSessionsMenu {
+    submenu: Submenu<tauri::Wry>,
+}
+
 pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
     let app_handle = app.app_handle().clone();
     let app_handle2 = app.app_handle().clone();
@@ -16,27 +104,69 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
     let app_handle5 = app.app_handle().clone();
 
     // Create menu items
-    let show_overlay = MenuItemBuilder::with_id("show_overlay", "Show Overlay")
-        .accelerator("CmdOrCtrl+\\")
+    let show_overlay = MenuItemBuilder::with_id("show_overlay", t!("tray.show_overlay"))
         .build(app)?;
 
-    let hide_overlay = MenuItemBuilder::with_id("hide_overlay", "Hide Overlay")
+    let hide_overlay = MenuItemBuilder::with_id("hide_overlay", t!("tray.hide_overlay"))
         .build(app)?;
 
-    let start_session = MenuItemBuilder::with_id("start_session", "Start Session")
-        .accelerator("CmdOrCtrl+Shift+S")
+    let click_through = CheckMenuItemBuilder::with_id("click_through", t!("tray.click_through"))
+        .checked(false)
         .build(app)?;
 
-    let stop_session = MenuItemBuilder::with_id("stop_session", "Stop Session")
+    let start_session = MenuItemBuilder::with_id("start_session", t!("tray.start_session"))
         .build(app)?;
 
-    let open_dashboard = MenuItemBuilder::with_id("open_dashboard", "Open Dashboard")
+    let pause_session = MenuItemBuilder::with_id("pause_session", t!("tray.pause_session"))
+        .enabled(false)
         .build(app)?;
 
-    let feedback = MenuItemBuilder::with_id("feedback", "Give Feedback")
+    let stop_session = MenuItemBuilder::with_id("stop_session", t!("tray.stop_session"))
+        .enabled(false)
         .build(app)?;
 
-    let quit = MenuItemBuilder::with_id("quit", "Quit Queen Mama")
+    let session_status = MenuItemBuilder::with_id("session_status", t!("tray.not_recording"))
+        .enabled(false)
+        .build(app)?;
+
+    // "Now" submenu, refreshed from Rust state during sessions
+    let now_elapsed = MenuItemBuilder::with_id("now_elapsed", t!("tray.no_active_session"))
+        .enabled(false)
+        .build(app)?;
+
+    let now_talk_ratio =
+        MenuItemBuilder::with_id("now_talk_ratio", t!("tray.talk_ratio", ratio = "–"))
+            .enabled(false)
+            .build(app)?;
+
+    let now_open_goals =
+        MenuItemBuilder::with_id("now_open_goals", t!("tray.open_goals", count = "–"))
+            .enabled(false)
+            .build(app)?;
+
+    let now = SubmenuBuilder::with_id(app, "now", t!("tray.now"))
+        .item(&now_elapsed)
+        .item(&now_talk_ratio)
+        .item(&now_open_goals)
+        .build()?;
+
+    // Latest sessions, filled in by refresh_recent_sessions once the database is open
+    let recent_sessions =
+        SubmenuBuilder::with_id(app, "recent_sessions", t!("tray.recent_sessions")).build()?;
+
+    // Profile switcher, filled in by refresh_profile_menu
+    let profile = SubmenuBuilder::with_id(app, "profile", t!("tray.profile")).build()?;
+
+    let open_dashboard = MenuItemBuilder::with_id("open_dashboard", t!("tray.open_dashboard"))
+        .build(app)?;
+
+    let feedback = MenuItemBuilder::with_id("feedback", t!("tray.feedback"))
+        .build(app)?;
+
+    let check_updates = MenuItemBuilder::with_id("check_updates", t!("tray.check_updates"))
+        .build(app)?;
+
+    let quit = MenuItemBuilder::with_id("quit", t!("tray.quit"))
         .accelerator("CmdOrCtrl+Q")
         .build(app)?;
 
@@ -44,12 +174,19 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
     let menu = MenuBuilder::new(app)
         .item(&show_overlay)
         .item(&hide_overlay)
+        .item(&click_through)
         .separator()
+        .item(&session_status)
         .item(&start_session)
+        .item(&pause_session)
         .item(&stop_session)
+        .item(&now)
         .separator()
+        .item(&recent_sessions)
+        .item(&profile)
         .item(&open_dashboard)
         .item(&feedback)
+        .item(&check_updates)
         .separator()
         .item(&quit)
         .build()?;
@@ -57,16 +194,17 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
     // Load tray icon (you'll need to add an actual icon file)
     let icon = Image::from_bytes(include_bytes!("../icons/icon.png"))
         .unwrap_or_else(|_| Image::from_bytes(&[0u8; 0]).unwrap());
+    let recording_icon = recording_icon(&icon);
 
     // Create tray icon
-    TrayIconBuilder::new()
+    let tray = TrayIconBuilder::new()
         .menu(&menu)
-        .icon(icon)
+        .icon(icon.clone())
         .tooltip("Queen Mama LITE")
         .on_menu_event(move |app, event| {
             match event.id().as_ref() {
                 "show_overlay" => {
-                    if let Some(overlay) = app.get_webview_window("overlay") {
+                    if let Ok(overlay) = window::overlay_window(app) {
                         let _ = overlay.show();
                         let _ = overlay.set_focus();
                     }
@@ -76,10 +214,32 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
                         let _ = overlay.hide();
                     }
                 }
+                "click_through" => {
+                    if let Err(e) = window::toggle_click_through(app) {
+                        tracing::warn!("{}", e);
+                    }
+                }
                 "start_session" => {
+                    if let Err(e) = session::start(app) {
+                        tracing::warn!("{}", e);
+                    }
                     let _ = app_handle.emit("tray_action", "start_session");
                 }
+                "pause_session" => {
+                    let recording = app
+                        .state::<TraySession>()
+                        .inner
+                        .lock()
+                        .map(|state| state.phase == SessionPhase::Recording)
+                        .unwrap_or(false);
+                    if let Err(e) = session::set_paused(app, recording) {
+                        tracing::warn!("{}", e);
+                    }
+                }
                 "stop_session" => {
+                    if let Err(e) = session::stop(app) {
+                        tracing::warn!("{}", e);
+                    }
                     let _ = app_handle2.emit("tray_action", "stop_session");
                 }
                 "open_dashboard" => {
@@ -93,10 +253,39 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
                     // Open feedback URL
                     let _ = open::that("https://queenmama.featurebase.app");
                 }
+                "check_updates" => {
+                    updates::check_from_tray(app);
+                }
                 "quit" => {
                     app_handle4.exit(0);
                 }
-                _ => {}
+                "manage_profiles" => {
+                    let _ = app.emit("tray_action", "manage_profiles");
+                    if let Some(main) = app.get_webview_window("main") {
+                        let _ = main.show();
+                        let _ = main.set_focus();
+                    }
+                }
+                id => {
+                    if let Some(session_id) = id.strip_prefix("recent_session.") {
+                        if let Err(e) = window::open_session(app, session_id) {
+                            tracing::warn!("{}", e);
+                        }
+                    } else if let Some(profile_id) = id.strip_prefix("profile.") {
+                        if profiles::has_passphrase(app, profile_id) {
+                            // The passphrase can't be typed into a menu; ask for it in the dashboard
+                            let _ = app.emit("profile_passphrase_requested", profile_id);
+                            if let Some(main) = app.get_webview_window("main") {
+                                let _ = main.show();
+                                let _ = main.set_focus();
+                            }
+                        } else if let Err(e) = profiles::switch(app, profile_id, None) {
+                            tracing::warn!("{}", e);
+                        }
+                        // Put the check mark back on the active profile if the switch failed
+                        refresh_profile_menu(app);
+                    }
+                }
             }
         })
         .on_tray_icon_event(move |tray, event| {
@@ -105,7 +294,7 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
                 TrayIconEvent::Click { button, .. } => {
                     if button == tauri::tray::MouseButton::Left {
                         // Show overlay on left click
-                        if let Some(overlay) = app_handle5.get_webview_window("overlay") {
+                        if let Ok(overlay) = window::overlay_window(&app_handle5) {
                             let is_visible = overlay.is_visible().unwrap_or(false);
                             if is_visible {
                                 let _ = overlay.hide();
@@ -121,6 +310,363 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         })
         .build(app)?;
 
-    println!("[Tray] System tray initialized");
+    // Shortcuts register after the tray, and can be rebound at any time
+    app.manage(ShortcutMenuItems {
+        items: vec![
+            ("toggle_overlay", show_overlay),
+            ("toggle_session", start_session.clone()),
+        ],
+    });
+    let shortcuts_handle = app.app_handle().clone();
+    app.listen("shortcuts_changed", move |_| {
+        refresh_shortcut_items(&shortcuts_handle)
+    });
+
+    app.manage(TraySession {
+        inner: Mutex::new(TraySessionState::default()),
+        tray,
+        idle_icon: icon,
+        recording_icon,
+        status_item: session_status,
+        start_item: start_session,
+        pause_item: pause_session,
+        stop_item: stop_session,
+    });
+
+    // Follow the session lifecycle, whoever drives it
+    let session_handle = app.app_handle().clone();
+    app.listen("session_state_changed", move |event| {
+        if let Ok(state) = serde_json::from_str::<TraySessionState>(event.payload()) {
+            apply_session_state(&session_handle.state::<TraySession>(), state);
+        }
+    });
+
+    // Keep the recording time current
+    let session_refresh_handle = app.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SESSION_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let tray = session_refresh_handle.state::<TraySession>();
+            let state = tray.inner.lock().map(|state| *state);
+            if let Ok(state) = state {
+                if matches!(state.phase, SessionPhase::Recording | SessionPhase::Paused) {
+                    refresh_session_status(&tray, state);
+                }
+            }
+        }
+    });
+
+    app.manage(RecentSessionsMenu {
+        submenu: recent_sessions,
+    });
+    for event in ["sessions_changed", "session_state_changed", "profile_changed"] {
+        let recent_handle = app.app_handle().clone();
+        app.listen(event, move |_| refresh_recent_sessions(&recent_handle));
+    }
+
+    app.manage(ProfileMenu { submenu: profile });
+    refresh_profile_menu(app.app_handle());
+
+    app.manage(NowStatus {
+        inner: Mutex::new(NowStatusInner::default()),
+        elapsed_item: now_elapsed,
+        talk_ratio_item: now_talk_ratio,
+        open_goals_item: now_open_goals,
+    });
+
+    let now_handle = app.app_handle().clone();
+    app.listen("session_state_changed", move |event| {
+        let Ok(session) = serde_json::from_str::<NowSession>(event.payload()) else {
+            return;
+        };
+        let status = now_handle.state::<NowStatus>();
+        if let Ok(mut inner) = status.inner.lock() {
+            let active = session.phase == SessionPhase::Recording
+                || session.phase == SessionPhase::Paused;
+            let previous_id = inner.session.as_ref().and_then(|s| s.session_id.clone());
+            if !active || previous_id != session.session_id {
+                inner.open_goals = None;
+            }
+            inner.session = active.then_some(session);
+        }
+        refresh_now_menu(&now_handle, &status);
+    });
+
+    // Keep elapsed time and talk ratio current while a session runs
+    let refresh_handle = app.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(NOW_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let status = refresh_handle.state::<NowStatus>();
+            let active = status
+                .inner
+                .lock()
+                .map(|inner| inner.session.is_some())
+                .unwrap_or(false);
+            if active {
+                refresh_now_menu(&refresh_handle, &status);
+            }
+        }
+    });
+
+    // Keep the check mark in sync when click-through is toggled elsewhere
+    app.listen("overlay_click_through_changed", move |event| {
+        if let Ok(enabled) = serde_json::from_str::<bool>(event.payload()) {
+            let _ = click_through.set_checked(enabled);
+        }
+    });
+
+    tracing::info!("System tray initialized");
     Ok(())
 }
+
+/// Report how many of the current session's goals are still open, for the tray "Now" submenu.
+/// Goals only exist in the frontend, so the count stays as reported until the session ends.
+#[tauri::command]
+pub fn update_open_goals(
+    app: AppHandle,
+    status: State<'_, NowStatus>,
+    count: u32,
+) -> Result<(), String> {
+    {
+        let mut inner = status.inner.lock().map_err(|e| e.to_string())?;
+        if inner.session.is_none() {
+            return Ok(());
+        }
+        inner.open_goals = Some(count);
+    }
+
+    refresh_now_menu(&app, &status);
+    Ok(())
+}
+
+/// Update the tray's session items and icon for a session state reported by the frontend
+#[tauri::command]
+pub fn update_tray_state(
+    tray: State<'_, TraySession>,
+    session_state: TraySessionState,
+) -> Result<(), String> {
+    apply_session_state(&tray, session_state);
+    Ok(())
+}
+
+fn apply_session_state(tray: &TraySession, state: TraySessionState) {
+    let previous = match tray.inner.lock() {
+        Ok(mut inner) => std::mem::replace(&mut *inner, state),
+        Err(_) => return,
+    };
+
+    let active = matches!(state.phase, SessionPhase::Recording | SessionPhase::Paused);
+    let _ = tray
+        .start_item
+        .set_enabled(matches!(state.phase, SessionPhase::Idle | SessionPhase::Done));
+    let _ = tray.pause_item.set_enabled(active);
+    let _ = tray.pause_item.set_text(if state.phase == SessionPhase::Paused {
+        t!("tray.resume_session")
+    } else {
+        t!("tray.pause_session")
+    });
+    let _ = tray.stop_item.set_enabled(active);
+
+    let was_recording = previous.phase == SessionPhase::Recording;
+    let recording = state.phase == SessionPhase::Recording;
+    if recording != was_recording {
+        let icon = if recording { &tray.recording_icon } else { &tray.idle_icon };
+        let _ = tray.tray.set_icon(Some(icon.clone()));
+    }
+
+    refresh_session_status(tray, state);
+}
+
+fn refresh_session_status(tray: &TraySession, state: TraySessionState) {
+    let elapsed = || {
+        let seconds = state
+            .started_at
+            .map(|started_at| (db::now_ms() - started_at).max(0) / 1000)
+            .unwrap_or(0);
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    };
+
+    let text = match state.phase {
+        SessionPhase::Idle | SessionPhase::Done => t!("tray.not_recording"),
+        SessionPhase::Recording => t!("tray.recording", elapsed = elapsed()),
+        SessionPhase::Paused => t!("tray.paused", elapsed = elapsed()),
+        SessionPhase::Processing => t!("tray.saving"),
+    };
+    let _ = tray.status_item.set_text(text);
+}
+
+/// The tray icon with a red dot in the corner
+fn recording_icon(icon: &Image<'_>) -> Image<'static> {
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+
+    let radius = width.min(height) as f32 * 0.22;
+    let (center_x, center_y) = (width as f32 - radius - 1.0, height as f32 - radius - 1.0);
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (x as f32 + 0.5 - center_x, y as f32 + 0.5 - center_y);
+            if dx * dx + dy * dy <= radius * radius {
+                let index = ((y * width + x) * 4) as usize;
+                rgba[index..index + 4].copy_from_slice(&RECORDING_DOT);
+            }
+        }
+    }
+
+    Image::new_owned(rgba, width, height)
+}
+
+/// Rebuild the "Recent Sessions" submenu from the session history
+pub fn refresh_recent_sessions(app: &AppHandle) {
+    let Some(menu) = app.try_state::<RecentSessionsMenu>() else {
+        return;
+    };
+    if let Err(e) = build_recent_sessions_menu(app, &menu.submenu) {
+        tracing::error!("Failed to rebuild recent sessions: {}", e);
+    }
+}
+
+fn build_recent_sessions_menu(app: &AppHandle, submenu: &Submenu<tauri::Wry>) -> Result<(), String> {
+    // The tray is set up before the database; setup_db calls back once it's open
+    let Some(db) = app.try_state::<Database>() else {
+        return Ok(());
+    };
+    let sessions = {
+        let connection = db.connection()?;
+        connection
+            .prepare(
+                "SELECT id, title, started_at FROM sessions WHERE deleted_at IS NULL
+                 ORDER BY started_at DESC LIMIT ?1",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![RECENT_SESSIONS_LIMIT], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, i64>(2)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| e.to_string())?
+    };
+
+    while submenu.remove_at(0).map_err(|e| e.to_string())?.is_some() {}
+
+    if sessions.is_empty() {
+        let empty = MenuItemBuilder::with_id("recent_sessions_empty", t!("tray.no_sessions"))
+            .enabled(false)
+            .build(app)
+            .map_err(|e| e.to_string())?;
+        return submenu.append(&empty).map_err(|e| e.to_string());
+    }
+
+    for (id, title, started_at) in sessions {
+        let date = Local
+            .timestamp_millis_opt(started_at)
+            .single()
+            .map(|time| time.format("%b %-d, %H:%M").to_string())
+            .unwrap_or_default();
+        let item = MenuItemBuilder::with_id(
+            format!("recent_session.{}", id),
+            format!("{} — {}", title, date),
+        )
+        .build(app)
+        .map_err(|e| e.to_string())?;
+        submenu.append(&item).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Rebuild the "Profile" submenu from the saved profiles
+pub fn refresh_profile_menu(app: &AppHandle) {
+    let Some(menu) = app.try_state::<ProfileMenu>() else {
+        return;
+    };
+    if let Err(e) = build_profile_menu(app, &menu.submenu) {
+        tracing::error!("Failed to rebuild profile menu: {}", e);
+    }
+}
+
+fn build_profile_menu(app: &AppHandle, submenu: &Submenu<tauri::Wry>) -> tauri::Result<()> {
+    while submenu.remove_at(0)?.is_some() {}
+
+    let list = profiles::load(app);
+    for profile in &list.profiles {
+        let item = CheckMenuItemBuilder::with_id(format!("profile.{}", profile.id), &profile.name)
+            .checked(profile.id == list.active)
+            .build(app)?;
+        submenu.append(&item)?;
+    }
+
+    submenu.append(&PredefinedMenuItem::separator(app)?)?;
+    let manage = MenuItemBuilder::with_id("manage_profiles", t!("tray.manage_profiles")).build(app)?;
+    submenu.append(&manage)?;
+    Ok(())
+}
+
+fn refresh_shortcut_items(app: &AppHandle) {
+    for (action, item) in &app.state::<ShortcutMenuItems>().items {
+        let accelerator = shortcuts::registered_accelerator(app, action);
+        if let Err(e) = item.set_accelerator(accelerator) {
+            tracing::warn!("Failed to show the {} shortcut in the tray: {}", action, e);
+        }
+    }
+}
+
+fn refresh_now_menu(app: &AppHandle, status: &NowStatus) {
+    let (session, open_goals) = match status.inner.lock() {
+        Ok(inner) => (inner.session.clone(), inner.open_goals),
+        Err(_) => return,
+    };
+
+    let (elapsed, talk_ratio, open_goals) = match session {
+        Some(session) => {
+            let minutes = session
+                .started_at
+                .map(|started_at| (db::now_ms() - started_at).max(0) / 60_000)
+                .unwrap_or(0);
+            let elapsed = format!("{}:{:02}", minutes / 60, minutes % 60);
+            let ratio = session
+                .session_id
+                .and_then(|session_id| talk_ratio(app, &session_id))
+                .map_or_else(|| "–".to_string(), |ratio| format!("{:.0}%", ratio * 100.0));
+            let count = open_goals.map_or_else(|| "–".to_string(), |count| count.to_string());
+            (
+                t!("tray.elapsed", elapsed = elapsed),
+                t!("tray.talk_ratio", ratio = ratio),
+                t!("tray.open_goals", count = count),
+            )
+        }
+        None => (
+            t!("tray.no_active_session"),
+            t!("tray.talk_ratio", ratio = "–"),
+            t!("tray.open_goals", count = "–"),
+        ),
+    };
+
+    let _ = status.elapsed_item.set_text(elapsed);
+    let _ = status.talk_ratio_item.set_text(talk_ratio);
+    let _ = status.open_goals_item.set_text(open_goals);
+}
+
+/// The user's share of the talking so far in a session, from the segments labelled as the user
+/// ("me") and the other side ("them")
+fn talk_ratio(app: &AppHandle, session_id: &str) -> Option<f64> {
+    let db = app.state::<Database>();
+    let connection = db.connection().ok()?;
+    let (own_ms, total_ms) = connection
+        .query_row(
+            "SELECT COALESCE(SUM(CASE WHEN speaker = 'me' THEN MAX(end_ms - start_ms, 0) END), 0),
+                COALESCE(SUM(MAX(end_ms - start_ms, 0)), 0)
+             FROM transcript_segments WHERE session_id = ?1 AND speaker IN ('me', 'them')",
+            params![session_id],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .map_err(|e| tracing::warn!("Failed to compute talk ratio: {}", e))
+        .ok()?;
+    (total_ms > 0).then(|| own_ms as f64 / total_ms as f64)
+}
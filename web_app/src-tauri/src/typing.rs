@@ -0,0 +1,142 @@
+// Queen Mama LITE - Auto-Type
+// Types suggested responses into the focused app at a human pace, for apps where pasting stands out
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::clipboard;
+use crate::i18n::t;
+
+/// Pace used when the caller doesn't pick one
+const DEFAULT_WPM: u32 = 80;
+const MIN_WPM: u32 = 10;
+const MAX_WPM: u32 = 400;
+
+/// Words are counted as five characters, as typing tests do
+const CHARS_PER_WORD: f64 = 5.0;
+
+/// People pause a little after spaces and punctuation
+const PAUSE_FACTOR: f64 = 1.6;
+
+#[derive(Default)]
+pub struct TypingState {
+    typing: AtomicBool,
+    /// Set by Escape or `stop_typing`; checked before every character
+    abort: AtomicBool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypingResult {
+    /// Characters typed before finishing or stopping
+    typed: usize,
+    total: usize,
+    aborted: bool,
+}
+
+pub fn setup_typing(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(TypingState::default());
+    Ok(())
+}
+
+/// Type text into the focused app at `wpm` words per minute; Escape stops it
+#[tauri::command]
+pub async fn type_text(
+    app: AppHandle,
+    state: State<'_, TypingState>,
+    text: String,
+    wpm: Option<u32>,
+) -> Result<TypingResult, String> {
+    if clipboard::is_own_window_focused(&app) {
+        return Err(t!("error.type_into_self"));
+    }
+    if !clipboard::can_send_keys() {
+        return Err(t!("error.typing_needs_accessibility"));
+    }
+    if state.typing.swap(true, Ordering::SeqCst) {
+        return Err(t!("error.typing_in_progress"));
+    }
+    state.abort.store(false, Ordering::SeqCst);
+
+    // Escape belongs to us only while typing, so it can't stop the wrong thing
+    let escape = Shortcut::new(None, Code::Escape);
+    let escape_registered = app
+        .global_shortcut()
+        .on_shortcut(escape, |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                app.state::<TypingState>()
+                    .abort
+                    .store(true, Ordering::SeqCst);
+            }
+        })
+        .inspect_err(|e| tracing::warn!("Escape won't stop typing: {}", e))
+        .is_ok();
+
+    let wpm = wpm.unwrap_or(DEFAULT_WPM).clamp(MIN_WPM, MAX_WPM);
+    let total = text.chars().count();
+    let typer = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || type_chars(&typer, &text, wpm))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|typed| typed);
+
+    if escape_registered {
+        let _ = app.global_shortcut().unregister(escape);
+    }
+    state.typing.store(false, Ordering::SeqCst);
+
+    let typed = result?;
+    Ok(TypingResult {
+        typed,
+        total,
+        aborted: typed < total,
+    })
+}
+
+/// Stop typing after the current character
+#[tauri::command]
+pub fn stop_typing(state: State<'_, TypingState>) {
+    state.abort.store(true, Ordering::SeqCst);
+}
+
+/// Type one character at a time, returning how many were typed
+fn type_chars(app: &AppHandle, text: &str, wpm: u32) -> Result<usize, String> {
+    let state = app.state::<TypingState>();
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| e.to_string())?;
+    let char_ms = 60_000.0 / (wpm as f64 * CHARS_PER_WORD);
+
+    let mut typed = 0;
+    for c in text.chars() {
+        if state.abort.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let sent = if c == '\n' {
+            // Return sends the message in most chat apps; Shift+Return is a line break
+            // there and in editors alike
+            enigo
+                .key(Key::Shift, Direction::Press)
+                .and_then(|_| enigo.key(Key::Return, Direction::Click))
+                .and_then(|_| enigo.key(Key::Shift, Direction::Release))
+        } else {
+            enigo.text(c.encode_utf8(&mut [0; 4]))
+        };
+        sent.map_err(|_| t!("error.typing_failed"))?;
+        typed += 1;
+
+        let pause = if c.is_whitespace() || c.is_ascii_punctuation() {
+            PAUSE_FACTOR
+        } else {
+            1.0
+        };
+        // Even pacing looks robotic, so each delay varies by up to 40% either way
+        let jitter = 0.6 + 0.8 * fastrand::f64();
+        thread::sleep(Duration::from_secs_f64(char_ms * pause * jitter / 1000.0));
+    }
+    Ok(typed)
+}
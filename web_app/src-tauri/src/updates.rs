@@ -0,0 +1,238 @@
+// Queen Mama LITE - Auto Updates
+// Checks the stable or beta channel for signed releases, downloads them and restarts into the new version
+
+use std::sync::Mutex;
+
+use tauri::{App, AppHandle, Manager, State, Url};
+use tauri_plugin_store::StoreExt;
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::notifications::{self, NoticeAction, NoticeKind};
+use crate::SETTINGS_STORE;
+
+pub(crate) const UPDATE_CHANNEL_STORE_KEY: &str = "update_channel";
+const ROLLOUT_BUCKET_STORE_KEY: &str = "update_rollout_bucket";
+
+/// Release manifest per channel; the updater fills in target, arch and current version
+const UPDATE_ENDPOINT: &str =
+    "https://queenmama.app/api/updates/{channel}/{{target}}/{{arch}}/{{current_version}}";
+
+/// Public key for release signatures, provided by the release build
+const UPDATER_PUBKEY: Option<&str> = option_env!("QUEEN_MAMA_UPDATER_PUBKEY");
+
+#[derive(Default)]
+pub struct UpdateState {
+    /// Release found by the last check
+    available: Mutex<Option<Update>>,
+    /// Installer bytes, once downloaded
+    downloaded: Mutex<Option<Vec<u8>>>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    fn name(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    version: String,
+    current_version: String,
+    channel: UpdateChannel,
+    /// Release notes
+    notes: Option<String>,
+    /// RFC 3339
+    date: Option<String>,
+}
+
+/// Emitted as `update_progress` while downloading
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateProgress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+pub fn setup_updates(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(UpdateState::default());
+    Ok(())
+}
+
+/// Get the release channel updates come from
+#[tauri::command]
+pub fn get_update_channel(app: AppHandle) -> UpdateChannel {
+    channel(&app)
+}
+
+/// Switch between stable and beta releases
+#[tauri::command]
+pub fn set_update_channel(app: AppHandle, channel: UpdateChannel) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        UPDATE_CHANNEL_STORE_KEY,
+        serde_json::to_value(channel).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Look for a newer release on the selected channel
+#[tauri::command]
+pub async fn check_for_updates(
+    app: AppHandle,
+    state: State<'_, UpdateState>,
+) -> Result<Option<UpdateInfo>, String> {
+    check(&app, &state).await
+}
+
+/// Download the release found by `check_for_updates`, emitting `update_progress`
+#[tauri::command]
+pub async fn download_update(app: AppHandle, state: State<'_, UpdateState>) -> Result<(), String> {
+    let update = state
+        .available
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| t!("error.update_nothing_to_download"))?;
+
+    let mut downloaded = 0u64;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = app.emit("update_progress", UpdateProgress { downloaded, total });
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    *state.downloaded.lock().map_err(|e| e.to_string())? = Some(bytes);
+    tracing::info!("Downloaded {}", update.version);
+    let _ = app.emit("update_downloaded", &update.version);
+    Ok(())
+}
+
+/// Install the downloaded release and relaunch
+#[tauri::command]
+pub fn install_and_restart(app: AppHandle, state: State<'_, UpdateState>) -> Result<(), String> {
+    let update = state
+        .available
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| t!("error.update_nothing_to_install"))?;
+    let bytes = state
+        .downloaded
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or_else(|| t!("error.update_not_downloaded"))?;
+
+    if crate::session::active_session_id(&app).is_some() {
+        *state.downloaded.lock().map_err(|e| e.to_string())? = Some(bytes);
+        return Err(t!("error.update_in_session"));
+    }
+
+    update.install(bytes).map_err(|e| e.to_string())?;
+    tracing::info!("Installed {}, restarting", update.version);
+    app.restart()
+}
+
+/// Check from the tray and report the outcome as a notification
+pub fn check_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<UpdateState>();
+        let (title, body) = match check(&app, &state).await {
+            Ok(Some(update)) => (
+                t!("notice.update_available.title"),
+                t!("notice.update_available.body", version = update.version),
+            ),
+            Ok(None) => (
+                t!("notice.up_to_date.title"),
+                t!("notice.up_to_date.body", version = app.package_info().version),
+            ),
+            Err(e) => (t!("notice.update_failed.title"), e),
+        };
+        notifications::notify(
+            &app,
+            NoticeKind::Update,
+            &title,
+            &body,
+            vec![(t!("action.open_settings"), NoticeAction::OpenSettings)],
+        );
+    });
+}
+
+async fn check(app: &AppHandle, state: &UpdateState) -> Result<Option<UpdateInfo>, String> {
+    let pubkey = UPDATER_PUBKEY.ok_or_else(|| t!("error.updates_disabled"))?;
+    let channel = channel(app);
+    let endpoint = Url::parse(&UPDATE_ENDPOINT.replace("{channel}", channel.name()))
+        .map_err(|e| e.to_string())?;
+
+    // The server uses the bucket to roll a release out to a growing share of installs
+    let update = app
+        .updater_builder()
+        .pubkey(pubkey)
+        .endpoints(vec![endpoint])
+        .and_then(|builder| builder.header("X-Rollout-Bucket", rollout_bucket(app).to_string()))
+        .and_then(|builder| builder.build())
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let info = update.as_ref().map(|update| UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        channel,
+        notes: update.body.clone(),
+        date: update.raw_json.get("pub_date").and_then(|date| date.as_str()).map(str::to_string),
+    });
+
+    *state.downloaded.lock().map_err(|e| e.to_string())? = None;
+    *state.available.lock().map_err(|e| e.to_string())? = update;
+
+    if let Some(info) = &info {
+        tracing::info!("{} is available on {}", info.version, channel.name());
+        let _ = app.emit("update_available", info);
+    }
+    Ok(info)
+}
+
+fn channel(app: &AppHandle) -> UpdateChannel {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(UPDATE_CHANNEL_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// A stable number from 0 to 99 for this install, picked on first use
+fn rollout_bucket(app: &AppHandle) -> u64 {
+    let Ok(store) = app.store(SETTINGS_STORE) else {
+        return 0;
+    };
+    if let Some(bucket) = store.get(ROLLOUT_BUCKET_STORE_KEY).and_then(|value| value.as_u64()) {
+        return bucket;
+    }
+
+    let bucket = (uuid::Uuid::new_v4().as_u128() % 100) as u64;
+    store.set(ROLLOUT_BUCKET_STORE_KEY, bucket);
+    let _ = store.save();
+    bucket
+}
@@ -0,0 +1,116 @@
+// Queen Mama LITE - Voice Activity Detection
+// Finds speech in captured audio with WebRTC's detector, so long silences never reach whisper
+
+use std::collections::VecDeque;
+
+use webrtc_vad::{SampleRate, Vad, VadMode};
+
+/// Audio must be 16 kHz mono, like whisper's
+const SAMPLE_RATE: i64 = 16_000;
+
+/// The detector takes 10, 20 or 30 ms frames
+const FRAME_MS: i64 = 30;
+const FRAME_LEN: usize = (SAMPLE_RATE * FRAME_MS / 1000) as usize;
+
+/// Silence kept after speech, so pauses between words and sentences stay in the audio
+const HANGOVER_MS: i64 = 600;
+
+/// Silence kept before speech, so the first syllable isn't clipped
+const PRE_ROLL_MS: i64 = 210;
+
+/// What the detector made of the audio it was given, in order
+pub enum Voice {
+    /// Someone started talking, at this position in the recording, ms
+    Started(i64),
+    /// Audio worth transcribing, starting at this position in the recording, ms
+    Audio { at_ms: i64, samples: Vec<f32> },
+    /// They stopped talking, at this position in the recording, ms
+    Stopped(i64),
+}
+
+/// Splits one source's audio into speech and silence, from the start of a recording
+pub struct VoiceDetector {
+    vad: Vad,
+    /// Samples short of a full frame, waiting for the next ones
+    pending: Vec<f32>,
+    /// Samples taken so far, i.e. the position of `pending[0]` in the recording
+    received: usize,
+    /// The last frames of silence, kept in case speech starts right after them
+    pre_roll: VecDeque<(i64, Vec<f32>)>,
+    speaking: bool,
+    /// Silence since the last frame with speech, while speaking
+    silence_ms: i64,
+}
+
+impl VoiceDetector {
+    pub fn new() -> Self {
+        Self {
+            vad: Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, VadMode::Aggressive),
+            pending: Vec::new(),
+            received: 0,
+            pre_roll: VecDeque::new(),
+            speaking: false,
+            silence_ms: 0,
+        }
+    }
+
+    /// Take the next 16 kHz samples; speech and the silences around it come back as `Audio`,
+    /// longer silences are dropped
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Voice> {
+        self.pending.extend_from_slice(samples);
+
+        let mut voices = Vec::new();
+        let mut start = 0;
+        while self.pending.len() - start >= FRAME_LEN {
+            let frame = self.pending[start..start + FRAME_LEN].to_vec();
+            let at_ms = (self.received + start) as i64 * 1000 / SAMPLE_RATE;
+            self.frame(at_ms, frame, &mut voices);
+            start += FRAME_LEN;
+        }
+        self.pending.drain(..start);
+        self.received += start;
+        voices
+    }
+
+    fn frame(&mut self, at_ms: i64, frame: Vec<f32>, voices: &mut Vec<Voice>) {
+        let pcm: Vec<i16> = frame
+            .iter()
+            .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect();
+        // It only fails on frames of the wrong length; better to transcribe than to lose speech
+        let voiced = self.vad.is_voice_segment(&pcm).unwrap_or(true);
+
+        if voiced {
+            self.silence_ms = 0;
+            if !self.speaking {
+                self.speaking = true;
+                let started = self.pre_roll.front().map_or(at_ms, |(at_ms, _)| *at_ms);
+                voices.push(Voice::Started(started));
+                voices.extend(
+                    self.pre_roll
+                        .drain(..)
+                        .map(|(at_ms, samples)| Voice::Audio { at_ms, samples }),
+                );
+            }
+            voices.push(Voice::Audio {
+                at_ms,
+                samples: frame,
+            });
+        } else if self.speaking {
+            voices.push(Voice::Audio {
+                at_ms,
+                samples: frame,
+            });
+            self.silence_ms += FRAME_MS;
+            if self.silence_ms >= HANGOVER_MS {
+                self.speaking = false;
+                voices.push(Voice::Stopped(at_ms + FRAME_MS));
+            }
+        } else {
+            self.pre_roll.push_back((at_ms, frame));
+            if self.pre_roll.len() as i64 > PRE_ROLL_MS / FRAME_MS {
+                self.pre_roll.pop_front();
+            }
+        }
+    }
+}
@@ -0,0 +1,755 @@
+// Queen Mama LITE - Encrypted Files
+// Seals the recordings, waveforms, attachments, imports and exports of a profile protected with a
+// passphrase, with a key derived from that passphrase. Files are sealed in chunks, so recordings
+// can be written as they're captured and read back from any point.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use tauri::{App, AppHandle, Manager};
+
+use crate::i18n::t;
+use crate::profiles;
+
+/// Starts every sealed file; files without it are read as they are
+const MAGIC: &[u8; 8] = b"QMVAULT1";
+const SALT_LEN: usize = 16;
+const FILE_ID_LEN: usize = 8;
+/// Magic, then the salt the key was derived with, so an export can be opened with the passphrase
+/// alone, then a random id binding the chunks to this file
+const HEADER_LEN: u64 = (MAGIC.len() + SALT_LEN + FILE_ID_LEN) as u64;
+
+/// Plaintext per chunk. Each is sealed with a fresh nonce, also when it's rewritten, and the last
+/// one is sealed as such, so a file cut short at a chunk boundary doesn't open as a shorter whole.
+/// An empty file still has one, empty, chunk.
+const CHUNK_LEN: usize = 64 * 1024;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const SEALED_CHUNK_LEN: u64 = (NONCE_LEN + CHUNK_LEN + TAG_LEN) as u64;
+
+/// Salt of a profile's file key, kept next to its database
+const SALT_FILE: &str = "vault.salt";
+
+/// Folders of a profile's data dir whose files are sealed
+const SEALED_DIRS: &[&str] = &["recordings", "attachments", "imports"];
+
+/// Suffix of a copy being sealed, renamed over the original once it's whole
+const SEALING_SUFFIX: &str = ".sealing";
+
+#[derive(Default)]
+pub struct VaultState {
+    /// The active profile's file key, while its history is unlocked
+    key: Mutex<Option<Arc<FileKey>>>,
+}
+
+struct FileKey {
+    cipher: Aes256Gcm,
+    salt: [u8; SALT_LEN],
+}
+
+impl FileKey {
+    fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self, String> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            cipher: Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())?,
+            salt,
+        })
+    }
+}
+
+struct Header {
+    salt: [u8; SALT_LEN],
+    file_id: [u8; FILE_ID_LEN],
+}
+
+impl Header {
+    /// The header of a sealed file, or None for a file that isn't sealed
+    fn read(file: &mut File) -> io::Result<Option<Self>> {
+        let mut bytes = [0u8; HEADER_LEN as usize];
+        match file.read_exact(&mut bytes) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let (magic, rest) = bytes.split_at(MAGIC.len());
+        if magic != MAGIC {
+            return Ok(None);
+        }
+        let mut header = Self {
+            salt: [0; SALT_LEN],
+            file_id: [0; FILE_ID_LEN],
+        };
+        header.salt.copy_from_slice(&rest[..SALT_LEN]);
+        header.file_id.copy_from_slice(&rest[SALT_LEN..]);
+        Ok(Some(header))
+    }
+
+    fn write(&self, file: &mut File) -> io::Result<()> {
+        file.write_all(MAGIC)?;
+        file.write_all(&self.salt)?;
+        file.write_all(&self.file_id)
+    }
+}
+
+/// A sealed file's chunks, each bound to the file and its place in it so none can be swapped
+struct SealedFile {
+    file: File,
+    key: Arc<FileKey>,
+    file_id: [u8; FILE_ID_LEN],
+}
+
+impl SealedFile {
+    fn read_chunk(&mut self, index: u64, len: usize, last: bool) -> io::Result<Vec<u8>> {
+        let mut sealed = vec![0u8; NONCE_LEN + len + TAG_LEN];
+        self.file.seek(SeekFrom::Start(chunk_offset(index)))?;
+        self.file.read_exact(&mut sealed)?;
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &self.aad(index, last),
+        };
+        self.key
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| damaged())
+    }
+
+    fn write_chunk(&mut self, index: u64, plaintext: &[u8], last: bool) -> io::Result<()> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: plaintext,
+            aad: &self.aad(index, last),
+        };
+        let ciphertext = self
+            .key
+            .cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| damaged())?;
+        self.file.seek(SeekFrom::Start(chunk_offset(index)))?;
+        self.file.write_all(&nonce)?;
+        self.file.write_all(&ciphertext)
+    }
+
+    /// Length of the plaintext, worked out from the file's
+    fn plaintext_len(&self) -> io::Result<u64> {
+        let stored = self.file.metadata()?.len().saturating_sub(HEADER_LEN);
+        let (full, rest) = (stored / SEALED_CHUNK_LEN, stored % SEALED_CHUNK_LEN);
+        let overhead = (NONCE_LEN + TAG_LEN) as u64;
+        // Only an empty file ends in an empty chunk
+        let empty = full == 0 && rest == overhead;
+        if stored == 0 || (rest != 0 && rest <= overhead && !empty) {
+            return Err(damaged());
+        }
+        Ok(full * CHUNK_LEN as u64 + rest.saturating_sub(overhead))
+    }
+
+    fn aad(&self, index: u64, last: bool) -> [u8; FILE_ID_LEN + 9] {
+        let mut aad = [0u8; FILE_ID_LEN + 9];
+        aad[..FILE_ID_LEN].copy_from_slice(&self.file_id);
+        aad[FILE_ID_LEN..FILE_ID_LEN + 8].copy_from_slice(&index.to_be_bytes());
+        aad[FILE_ID_LEN + 8] = last as u8;
+        aad
+    }
+}
+
+/// Writes a sealed file. Only the chunk being written is held in plaintext; moving to another one,
+/// e.g. when a WAV header is filled in at the end, seals it first.
+pub(crate) struct SealedWriter {
+    sealed: SealedFile,
+    /// Plaintext of the chunk at `index`
+    chunk: Vec<u8>,
+    index: u64,
+    /// Position within `chunk`
+    offset: usize,
+    /// `chunk` has changed since it was last sealed
+    dirty: bool,
+    len: u64,
+    /// Chunk sealed as the last one, if any is yet
+    sealed_last: Option<u64>,
+}
+
+impl SealedWriter {
+    fn create(mut file: File, key: Arc<FileKey>) -> io::Result<Self> {
+        let mut file_id = [0u8; FILE_ID_LEN];
+        OsRng.fill_bytes(&mut file_id);
+        Header {
+            salt: key.salt,
+            file_id,
+        }
+        .write(&mut file)?;
+        Ok(Self {
+            sealed: SealedFile { file, key, file_id },
+            chunk: Vec::with_capacity(CHUNK_LEN),
+            index: 0,
+            offset: 0,
+            dirty: false,
+            len: 0,
+            sealed_last: None,
+        })
+    }
+
+    /// Seal the chunk being written if it changed or is now sealed as the wrong kind
+    fn save_chunk(&mut self, last: bool) -> io::Result<()> {
+        let was_last = self.sealed_last == Some(self.index);
+        if !self.dirty && was_last == last {
+            return Ok(());
+        }
+        if last {
+            self.unmark_last(self.index)?;
+        }
+        self.sealed.write_chunk(self.index, &self.chunk, last)?;
+        self.dirty = false;
+        if last {
+            self.sealed_last = Some(self.index);
+        } else if was_last {
+            self.sealed_last = None;
+        }
+        Ok(())
+    }
+
+    /// Reseal the chunk sealed as the last one as an ordinary one, unless it's `index`
+    fn unmark_last(&mut self, index: u64) -> io::Result<()> {
+        if let Some(stale) = self.sealed_last.filter(|&stale| stale != index) {
+            let plaintext = self
+                .sealed
+                .read_chunk(stale, chunk_len(self.len, stale), true)?;
+            self.sealed.write_chunk(stale, &plaintext, false)?;
+            self.sealed_last = None;
+        }
+        Ok(())
+    }
+
+    /// Seal what's left, with the chunk at the end sealed as the last one and no other
+    fn finish(&mut self) -> io::Result<()> {
+        let last = last_index(self.len);
+        if self.index == last {
+            return self.save_chunk(true);
+        }
+        self.save_chunk(false)?;
+        if self.sealed_last != Some(last) {
+            self.unmark_last(last)?;
+            let plaintext = self
+                .sealed
+                .read_chunk(last, chunk_len(self.len, last), false)?;
+            self.sealed.write_chunk(last, &plaintext, true)?;
+            self.sealed_last = Some(last);
+        }
+        Ok(())
+    }
+
+    /// Seal the chunk being written, as the last one if `last`, and load chunk `index`
+    fn move_to(&mut self, index: u64, last: bool) -> io::Result<()> {
+        self.save_chunk(last)?;
+        self.chunk = if index * (CHUNK_LEN as u64) < self.len {
+            let last = self.sealed_last == Some(index);
+            self.sealed
+                .read_chunk(index, chunk_len(self.len, index), last)?
+        } else {
+            Vec::with_capacity(CHUNK_LEN)
+        };
+        self.index = index;
+        self.offset = 0;
+        Ok(())
+    }
+}
+
+impl Write for SealedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.offset == CHUNK_LEN {
+            // More follows, so the chunk being left isn't the last one
+            self.move_to(self.index + 1, false)?;
+        }
+        let written = buf.len().min(CHUNK_LEN - self.offset);
+        let end = self.offset + written;
+        if self.chunk.len() < end {
+            self.chunk.resize(end, 0);
+        }
+        self.chunk[self.offset..end].copy_from_slice(&buf[..written]);
+        self.offset = end;
+        self.dirty = true;
+        self.len = self.len.max(self.index * CHUNK_LEN as u64 + end as u64);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish()?;
+        self.sealed.file.flush()
+    }
+}
+
+impl Seek for SealedWriter {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let current = self.index * CHUNK_LEN as u64 + self.offset as u64;
+        let target = resolve(position, current, self.len)?;
+        // Nothing writes past the end, and a gap would have to be sealed as zeros
+        if target > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek past the end of a sealed file",
+            ));
+        }
+        let index = target / CHUNK_LEN as u64;
+        if index != self.index {
+            self.move_to(index, self.index == last_index(self.len))?;
+        }
+        self.offset = (target % CHUNK_LEN as u64) as usize;
+        Ok(target)
+    }
+}
+
+impl Drop for SealedWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Reads a sealed file a chunk at a time
+pub(crate) struct SealedReader {
+    sealed: SealedFile,
+    len: u64,
+    position: u64,
+    /// Plaintext of the chunk last read, and its index
+    chunk: Vec<u8>,
+    chunk_index: Option<u64>,
+}
+
+impl SealedReader {
+    fn open(file: File, header: Header, key: Arc<FileKey>) -> io::Result<Self> {
+        if header.salt != key.salt {
+            return Err(io::Error::other(t!("error.encrypted_file_other_key")));
+        }
+        let mut sealed = SealedFile {
+            file,
+            key,
+            file_id: header.file_id,
+        };
+        // Unsealing the last chunk up front catches a file that was cut short
+        let len = sealed.plaintext_len()?;
+        let last = last_index(len);
+        let chunk = sealed.read_chunk(last, chunk_len(len, last), true)?;
+        Ok(Self {
+            sealed,
+            len,
+            position: 0,
+            chunk,
+            chunk_index: Some(last),
+        })
+    }
+}
+
+impl Read for SealedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let index = self.position / CHUNK_LEN as u64;
+        if self.chunk_index != Some(index) {
+            let last = index == last_index(self.len);
+            self.chunk = self
+                .sealed
+                .read_chunk(index, chunk_len(self.len, index), last)?;
+            self.chunk_index = Some(index);
+        }
+        let offset = (self.position % CHUNK_LEN as u64) as usize;
+        let read = buf.len().min(self.chunk.len() - offset);
+        buf[..read].copy_from_slice(&self.chunk[offset..offset + read]);
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for SealedReader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.position = resolve(position, self.position, self.len)?;
+        Ok(self.position)
+    }
+}
+
+/// A file being written, sealed when the active profile is protected with a passphrase
+pub(crate) enum FileWriter {
+    Plain(BufWriter<File>),
+    Sealed(SealedWriter),
+}
+
+impl Write for FileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            FileWriter::Plain(writer) => writer.write(buf),
+            FileWriter::Sealed(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            FileWriter::Plain(writer) => writer.flush(),
+            FileWriter::Sealed(writer) => writer.flush(),
+        }
+    }
+}
+
+impl Seek for FileWriter {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        match self {
+            FileWriter::Plain(writer) => writer.seek(position),
+            FileWriter::Sealed(writer) => writer.seek(position),
+        }
+    }
+}
+
+/// A file being read, whether it's sealed or not
+pub(crate) enum FileReader {
+    Plain(BufReader<File>),
+    Sealed(SealedReader),
+}
+
+impl Read for FileReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            FileReader::Plain(reader) => reader.read(buf),
+            FileReader::Sealed(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for FileReader {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        match self {
+            FileReader::Plain(reader) => reader.seek(position),
+            FileReader::Sealed(reader) => reader.seek(position),
+        }
+    }
+}
+
+pub fn setup_vault(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(VaultState::default());
+    Ok(())
+}
+
+/// Derive the file key of the profile whose data is in `dir` from its passphrase, making its salt
+/// the first time, and use it until `lock`
+pub(crate) fn unlock(app: &AppHandle, dir: &Path, passphrase: &str) -> Result<(), String> {
+    let salt_path = dir.join(SALT_FILE);
+    let salt = match fs::read(&salt_path) {
+        Ok(salt) => salt
+            .try_into()
+            .map_err(|_| t!("error.encrypted_file_damaged"))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            fs::write(&salt_path, salt).map_err(|e| e.to_string())?;
+            salt
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    let key = FileKey::derive(passphrase, salt)?;
+    *app.state::<VaultState>()
+        .key
+        .lock()
+        .map_err(|e| e.to_string())? = Some(Arc::new(key));
+    Ok(())
+}
+
+/// Forget the file key, e.g. when the history locks or another profile is opened
+pub(crate) fn lock(app: &AppHandle) {
+    match app.state::<VaultState>().key.lock() {
+        Ok(mut key) => *key = None,
+        Err(e) => tracing::error!("Failed to forget the file key: {}", e),
+    }
+}
+
+/// Create or replace a file, sealed when the active profile is protected with a passphrase
+pub(crate) fn create(app: &AppHandle, path: &Path) -> Result<FileWriter, String> {
+    match key(app)? {
+        Some(key) => OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .and_then(|file| SealedWriter::create(file, key))
+            .map(FileWriter::Sealed),
+        None => File::create(path).map(|file| FileWriter::Plain(BufWriter::new(file))),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Open a file, unsealing it with the active profile's key if it's sealed
+pub(crate) fn open(app: &AppHandle, path: &Path) -> Result<FileReader, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    match Header::read(&mut file).map_err(|e| e.to_string())? {
+        Some(header) => {
+            let key = key(app)?.ok_or_else(|| t!("error.encrypted_file_other_key"))?;
+            SealedReader::open(file, header, key)
+                .map(FileReader::Sealed)
+                .map_err(|e| e.to_string())
+        }
+        None => {
+            file.rewind().map_err(|e| e.to_string())?;
+            Ok(FileReader::Plain(BufReader::new(file)))
+        }
+    }
+}
+
+/// Write a whole file, sealed as `create` does
+pub(crate) fn write(app: &AppHandle, path: &Path, data: impl AsRef<[u8]>) -> Result<(), String> {
+    let mut writer = create(app, path)?;
+    writer
+        .write_all(data.as_ref())
+        .and_then(|()| writer.flush())
+        .map_err(|e| e.to_string())
+}
+
+/// Read a whole file, unsealing it as `open` does
+pub(crate) fn read(app: &AppHandle, path: &Path) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    open(app, path)?
+        .read_to_end(&mut data)
+        .map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
+/// Copy a file in or out of the profile, e.g. an attachment, sealing the copy as `create` does.
+/// Returns the length of its contents.
+pub(crate) fn copy(app: &AppHandle, source: &Path, target: &Path) -> Result<u64, String> {
+    let mut reader = open(app, source)?;
+    let mut writer = create(app, target)?;
+    let copied = io::copy(&mut reader, &mut writer).map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(copied)
+}
+
+/// Seal the active profile's recordings, attachments and imports that aren't yet, e.g. ones kept
+/// from before the passphrase was set or left over by a failure. Returns how many were sealed.
+pub(crate) fn seal_existing(app: &AppHandle) -> Result<usize, String> {
+    let Some(key) = key(app)? else {
+        return Ok(0);
+    };
+    let dir = profiles::active_data_dir(app)?;
+    let mut sealed = 0;
+    for path in SEALED_DIRS.iter().flat_map(|name| files(&dir.join(name))) {
+        if path.to_string_lossy().ends_with(SEALING_SUFFIX) {
+            let _ = fs::remove_file(&path);
+            continue;
+        }
+        match seal_file(&path, &key) {
+            Ok(true) => sealed += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(path = ?path, "Failed to encrypt a file: {}", e),
+        }
+    }
+    Ok(sealed)
+}
+
+/// Decrypt a sealed file, e.g. an export, with the passphrase of the profile it came from
+pub(crate) fn decrypt(source: &Path, target: &Path, passphrase: &str) -> Result<(), String> {
+    let mut file = File::open(source).map_err(|e| e.to_string())?;
+    let header = Header::read(&mut file)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| t!("error.file_not_encrypted"))?;
+    let key = Arc::new(FileKey::derive(passphrase, header.salt)?);
+    let mut reader = SealedReader::open(file, header, key).map_err(unseal_error)?;
+
+    let mut writer = File::create(target)
+        .map(BufWriter::new)
+        .map_err(|e| e.to_string())?;
+    let copied = io::copy(&mut reader, &mut writer).and_then(|_| writer.flush());
+    if let Err(e) = copied {
+        drop(writer);
+        let _ = fs::remove_file(target);
+        return Err(unseal_error(e));
+    }
+    Ok(())
+}
+
+/// A wrong passphrase only shows as a chunk failing to unseal
+fn unseal_error(e: io::Error) -> String {
+    match e.kind() {
+        io::ErrorKind::InvalidData => t!("error.wrong_passphrase"),
+        _ => e.to_string(),
+    }
+}
+
+/// The active profile's file key; None when it has no passphrase, so files are left as they are
+fn key(app: &AppHandle) -> Result<Option<Arc<FileKey>>, String> {
+    if !profiles::has_passphrase(app, &profiles::load(app).active) {
+        return Ok(None);
+    }
+    app.state::<VaultState>()
+        .key
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .map(Some)
+        .ok_or_else(|| t!("error.database_locked"))
+}
+
+/// Seal a file in place, through a copy renamed over it. False when it already was.
+fn seal_file(path: &Path, key: &Arc<FileKey>) -> io::Result<bool> {
+    let mut source = File::open(path)?;
+    if Header::read(&mut source)?.is_some() {
+        return Ok(false);
+    }
+    source.rewind()?;
+
+    let mut partial = OsString::from(path.as_os_str());
+    partial.push(SEALING_SUFFIX);
+    let partial = PathBuf::from(partial);
+    let sealed = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&partial)
+        .and_then(|file| {
+            let mut writer = SealedWriter::create(file, key.clone())?;
+            io::copy(&mut BufReader::new(source), &mut writer)?;
+            writer.flush()
+        })
+        .and_then(|()| fs::rename(&partial, path));
+    if let Err(e) = sealed {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    Ok(true)
+}
+
+fn files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .flat_map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                files(&path)
+            } else {
+                vec![path]
+            }
+        })
+        .collect()
+}
+
+fn chunk_offset(index: u64) -> u64 {
+    HEADER_LEN + index * SEALED_CHUNK_LEN
+}
+
+/// Index of the last chunk of a file `len` long
+fn last_index(len: u64) -> u64 {
+    len.saturating_sub(1) / CHUNK_LEN as u64
+}
+
+/// Plaintext length of chunk `index` of a file `len` long
+fn chunk_len(len: u64, index: u64) -> usize {
+    (len - index * CHUNK_LEN as u64).min(CHUNK_LEN as u64) as usize
+}
+
+fn resolve(position: SeekFrom, current: u64, len: u64) -> io::Result<u64> {
+    match position {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::Current(delta) => current.checked_add_signed(delta),
+        SeekFrom::End(delta) => len.checked_add_signed(delta),
+    }
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start"))
+}
+
+fn damaged() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        t!("error.encrypted_file_damaged"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    use super::{chunk_offset, FileKey, Header, SealedReader, SealedWriter, CHUNK_LEN, SALT_LEN};
+
+    fn key() -> Arc<FileKey> {
+        Arc::new(FileKey::derive("correct horse", [7; SALT_LEN]).unwrap())
+    }
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("queen-mama-vault-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn seal(path: &Path, data: &[u8]) -> SealedWriter {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .unwrap();
+        let mut writer = SealedWriter::create(file, key()).unwrap();
+        writer.write_all(data).unwrap();
+        writer
+    }
+
+    fn unseal(path: &Path) -> std::io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        let header = Header::read(&mut file)?.unwrap();
+        let mut data = Vec::new();
+        SealedReader::open(file, header, key())?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    #[test]
+    fn round_trips_a_file_rewritten_at_its_start() {
+        let path = temp_path();
+        let mut data: Vec<u8> = (0..CHUNK_LEN * 5 / 2).map(|i| i as u8).collect();
+        let mut writer = seal(&path, &data);
+        // Like a WAV header filled in once the length is known
+        writer.seek(SeekFrom::Start(4)).unwrap();
+        writer.write_all(b"size").unwrap();
+        writer.seek(SeekFrom::End(0)).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        data[4..8].copy_from_slice(b"size");
+
+        assert_eq!(unseal(&path).unwrap(), data);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn round_trips_empty_and_whole_chunk_files() {
+        for len in [0, CHUNK_LEN, CHUNK_LEN * 2] {
+            let path = temp_path();
+            let data = vec![1u8; len];
+            seal(&path, &data).flush().unwrap();
+            assert_eq!(unseal(&path).unwrap(), data);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn file_cut_at_a_chunk_boundary_fails_to_open() {
+        for (len, kept) in [(CHUNK_LEN * 5 / 2, 2), (CHUNK_LEN * 2, 1), (CHUNK_LEN, 0)] {
+            let path = temp_path();
+            seal(&path, &vec![1u8; len]).flush().unwrap();
+            let file = OpenOptions::new().write(true).open(&path).unwrap();
+            file.set_len(chunk_offset(kept)).unwrap();
+            drop(file);
+
+            assert!(unseal(&path).is_err());
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
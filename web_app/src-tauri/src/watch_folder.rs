@@ -0,0 +1,184 @@
+// Queen Mama LITE - Watch Folder
+// Auto-imports audio and transcript files dropped into a user-chosen directory
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{App, AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::mpsc;
+
+use crate::events::VersionedEmitter;
+use crate::i18n::t;
+use crate::import;
+use crate::SETTINGS_STORE;
+
+pub(crate) const WATCH_FOLDER_STORE_KEY: &str = "watch_folder";
+
+/// Files are imported once their size stops changing for this long
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+pub struct WatchFolderState {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchFolderFileEvent {
+    path: PathBuf,
+    status: &'static str,
+    error: Option<String>,
+}
+
+pub fn setup_watch_folder(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(WatchFolderState::default());
+
+    if let Some(folder) = load_folder(app.app_handle()) {
+        let state = app.state::<WatchFolderState>();
+        match start_watching(app.app_handle(), &folder) {
+            Ok(watcher) => {
+                *state.watcher.lock().map_err(|e| e.to_string())? = Some(watcher);
+                tracing::info!(path = ?folder, "Watching a folder");
+            }
+            Err(e) => tracing::error!(
+                code = "watch_folder.unavailable",
+                path = ?folder,
+                "Failed to watch a folder: {}",
+                e
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the watched directory, if any
+#[tauri::command]
+pub fn get_watch_folder(app: AppHandle) -> Option<PathBuf> {
+    load_folder(&app)
+}
+
+/// Set (or clear with `null`) the watched directory
+#[tauri::command]
+pub fn set_watch_folder(
+    app: AppHandle,
+    state: State<'_, WatchFolderState>,
+    path: Option<PathBuf>,
+) -> Result<(), String> {
+    let mut watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+    *watcher = None;
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    match path {
+        Some(path) => {
+            *watcher = Some(start_watching(&app, &path)?);
+            store.set(WATCH_FOLDER_STORE_KEY, path.to_string_lossy().into_owned());
+        }
+        None => {
+            store.delete(WATCH_FOLDER_STORE_KEY);
+        }
+    }
+    store.save().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Restart the watcher on the saved directory
+pub fn reload(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<WatchFolderState>();
+    let mut watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+    *watcher = None;
+
+    if let Some(folder) = load_folder(app) {
+        *watcher = Some(start_watching(app, &folder)?);
+    }
+    Ok(())
+}
+
+fn load_folder(app: &AppHandle) -> Option<PathBuf> {
+    let store = app.store(SETTINGS_STORE).ok()?;
+    store
+        .get(WATCH_FOLDER_STORE_KEY)?
+        .as_str()
+        .map(PathBuf::from)
+}
+
+fn start_watching(app: &AppHandle, folder: &Path) -> Result<RecommendedWatcher, String> {
+    if !folder.is_dir() {
+        return Err(t!("error.not_a_directory", path = folder.display()));
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(import_queue(app.clone(), receiver));
+
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        // New files, including ones moved into the folder
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    watcher
+        .watch(folder, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    Ok(watcher)
+}
+
+/// Import files one at a time; the queue ends when the watcher is dropped
+async fn import_queue(app: AppHandle, mut receiver: mpsc::UnboundedReceiver<PathBuf>) {
+    let mut seen = HashSet::new();
+
+    while let Some(path) = receiver.recv().await {
+        if !path.is_file() || !import::is_supported(&path) || !seen.insert(path.clone()) {
+            continue;
+        }
+
+        emit_status(&app, &path, "queued", None);
+        wait_until_settled(&path).await;
+
+        match import::import(&app, &path) {
+            Ok(_) => emit_status(&app, &path, "imported", None),
+            Err(e) => {
+                tracing::warn!("{}", e);
+                emit_status(&app, &path, "failed", Some(e));
+            }
+        }
+    }
+}
+
+/// Wait for writers (sync clients, recorders) to finish copying the file
+async fn wait_until_settled(path: &Path) {
+    let mut last_len = None;
+    loop {
+        let Ok(len) = std::fs::metadata(path).map(|meta| meta.len()) else {
+            return;
+        };
+        if last_len == Some(len) {
+            return;
+        }
+        last_len = Some(len);
+        tokio::time::sleep(SETTLE_DELAY).await;
+    }
+}
+
+fn emit_status(app: &AppHandle, path: &Path, status: &'static str, error: Option<String>) {
+    let _ = app.emit(
+        "watch_folder_file",
+        WatchFolderFileEvent {
+            path: path.to_path_buf(),
+            status,
+            error,
+        },
+    );
+}
@@ -1,72 +1,411 @@
 // Queen Mama LITE - Window Management
 // Handles multi-window setup and overlay behavior
 
-use tauri::{App, Emitter, Manager, LogicalPosition, LogicalSize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
-/// Overlay dimensions
+use tauri::{
+    App, AppHandle, LogicalPosition, LogicalSize, Manager, Monitor, PhysicalPosition,
+    WebviewWindow, WindowEvent,
+};
+use tauri_plugin_store::StoreExt;
+
+use crate::SETTINGS_STORE;
+#[cfg(desktop)]
+use crate::accessibility;
+use crate::events::VersionedEmitter;
+#[cfg(desktop)]
+use crate::focus;
+use crate::i18n::t;
+
+pub(crate) const OVERLAY_GEOMETRY_STORE_KEY: &str = "overlay_geometry";
+pub(crate) const OVERLAY_ALL_WORKSPACES_STORE_KEY: &str = "overlay_all_workspaces";
+pub(crate) const OVERLAY_CONTENT_PROTECTION_STORE_KEY: &str = "overlay_content_protection";
+pub(crate) const OVERLAY_FOLLOW_MONITOR_STORE_KEY: &str = "overlay_follow_monitor";
+pub(crate) const OVERLAY_PLACEMENT_STORE_KEY: &str = "overlay_placement";
+pub(crate) const OVERLAY_AUTOHIDE_STORE_KEY: &str = "overlay_autohide";
+
+/// Default overlay dimensions
 const OVERLAY_COLLAPSED_WIDTH: u32 = 420;
 const OVERLAY_COLLAPSED_HEIGHT: u32 = 100;
 const OVERLAY_EXPANDED_WIDTH: u32 = 420;
 const OVERLAY_EXPANDED_HEIGHT: u32 = 400;
 
+/// Smallest overlay that still fits the response controls
+const OVERLAY_MIN_WIDTH: f64 = 280.0;
+const OVERLAY_MIN_HEIGHT: f64 = 60.0;
+
+/// Space kept free around the overlay when clamping to the monitor
+const OVERLAY_SCREEN_MARGIN: f64 = 20.0;
+
+/// Wait for the user to finish dragging before saving a moved or resized overlay
+const RESIZE_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Length of expand/collapse and move animations
+const ANIMATION_DURATION: Duration = Duration::from_millis(150);
+const ANIMATION_FRAME: Duration = Duration::from_millis(16);
+
+/// A drag counts as finished once the overlay stops moving for this long
+const SNAP_DELAY: Duration = Duration::from_millis(150);
+
+/// Drops this close to a screen edge snap to the nearest preset position
+const SNAP_DISTANCE: f64 = 40.0;
+
+/// How often the mouse is checked when the overlay follows it between monitors
+#[cfg(desktop)]
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the frontmost app is checked for auto-hide
+#[cfg(desktop)]
+const AUTOHIDE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Live caption strip along the bottom of the screen
+const CAPTION_WINDOW: &str = "caption";
+const CAPTION_WIDTH: f64 = 640.0;
+const CAPTION_HEIGHT: f64 = 56.0;
+const CAPTION_BOTTOM_MARGIN: f64 = 80.0;
+
+#[derive(Default)]
+pub struct OverlayState {
+    expanded: AtomicBool,
+    /// Bumped on every user resize so only the last one gets saved
+    resize_generation: AtomicU64,
+    /// Bumped on every move so only the last position gets saved
+    move_generation: AtomicU64,
+    /// The user is dragging the overlay via `begin_overlay_drag`
+    dragging: AtomicBool,
+    /// Bumped on every move during a drag so the snap waits for the last one
+    drag_generation: AtomicU64,
+    /// Bumped when an animation starts so a newer one takes over from it
+    animation_generation: AtomicU64,
+    /// Clicks pass through the overlay to the app beneath
+    pub(crate) click_through: AtomicBool,
+    /// The overlay was hidden by auto-hide and should come back when it ends
+    auto_hidden: AtomicBool,
+}
+
+/// Overlay sizes in logical pixels
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayGeometry {
+    collapsed: OverlaySize,
+    expanded: OverlaySize,
+}
+
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlaySize {
+    width: f64,
+    height: f64,
+}
+
+impl Default for OverlayGeometry {
+    fn default() -> Self {
+        Self {
+            collapsed: OverlaySize {
+                width: OVERLAY_COLLAPSED_WIDTH as f64,
+                height: OVERLAY_COLLAPSED_HEIGHT as f64,
+            },
+            expanded: OverlaySize {
+                width: OVERLAY_EXPANDED_WIDTH as f64,
+                height: OVERLAY_EXPANDED_HEIGHT as f64,
+            },
+        }
+    }
+}
+
+impl OverlayGeometry {
+    /// Both sizes are at least the overlay's minimum
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let fits = |size: &OverlaySize| {
+            size.width.is_finite()
+                && size.height.is_finite()
+                && size.width >= OVERLAY_MIN_WIDTH
+                && size.height >= OVERLAY_MIN_HEIGHT
+        };
+        if fits(&self.collapsed) && fits(&self.expanded) {
+            Ok(())
+        } else {
+            Err(t!(
+                "error.settings_invalid_size",
+                width = OVERLAY_MIN_WIDTH,
+                height = OVERLAY_MIN_HEIGHT
+            ))
+        }
+    }
+}
+
+/// A window's state as reported by `get_window_states`, bounds in logical pixels
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowStateInfo {
+    label: String,
+    visible: bool,
+    focused: bool,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// When to hide the overlay automatically, e.g. over games or DRM video
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoHideRules {
+    /// Hide while the frontmost window covers its whole monitor
+    fullscreen: bool,
+    /// App names (case-insensitive, partial match) to hide over
+    blocklist: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AutoHideEvent {
+    hidden: bool,
+    /// "fullscreen", "blocklist" or "focus" when hiding
+    reason: Option<&'static str>,
+    app_name: Option<String>,
+}
+
+/// Where the overlay was last left, restored at startup
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OverlayPlacement {
+    /// Outer position in physical pixels
+    x: i32,
+    y: i32,
+    monitor: Option<String>,
+    expanded: bool,
+}
+
+/// A display, with its bounds in logical pixels
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    index: usize,
+    name: Option<String>,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    scale_factor: f64,
+    is_primary: bool,
+    /// The overlay is on this monitor
+    is_current: bool,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayGeometryInfo {
+    #[serde(flatten)]
+    geometry: OverlayGeometry,
+    is_expanded: bool,
+}
+
 pub fn setup_windows(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    app.manage(OverlayState::default());
+
     // Get overlay window
     if let Some(overlay) = app.get_webview_window("overlay") {
-        // Set initial size using logical pixels (HiDPI aware)
-        let _ = overlay.set_size(LogicalSize::new(OVERLAY_COLLAPSED_WIDTH as f64, OVERLAY_COLLAPSED_HEIGHT as f64));
+        configure_overlay(app.app_handle(), &overlay);
+    }
 
-        // Position in top-right corner with some padding
-        if let Ok(monitor) = overlay.current_monitor() {
-            if let Some(monitor) = monitor {
-                let screen_size = monitor.size();
-                let scale_factor = monitor.scale_factor();
-                let logical_width = screen_size.width as f64 / scale_factor;
-                let x = logical_width - OVERLAY_COLLAPSED_WIDTH as f64 - 20.0;
-                let y = 100.0; // Top padding
-                let _ = overlay.set_position(LogicalPosition::new(x, y));
+    #[cfg(desktop)]
+    {
+        let app_handle = app.app_handle().clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(FOLLOW_INTERVAL);
+            if let Err(e) = follow_cursor_monitor(&app_handle) {
+                tracing::error!("Failed to follow the mouse: {}", e);
             }
-        }
+        });
 
-        // Keep always on top
-        let _ = overlay.set_always_on_top(true);
+        let app_handle = app.app_handle().clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(AUTOHIDE_INTERVAL);
+            if let Err(e) = check_autohide(&app_handle) {
+                tracing::error!("Auto-hide check failed: {}", e);
+            }
+        });
     }
 
-    println!("[Window] Windows setup complete");
+    tracing::info!("Windows setup complete");
     Ok(())
 }
 
+/// The overlay window, rebuilt from its definition if it's gone (crashed, or missing from the config)
+pub fn overlay_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        return Ok(overlay);
+    }
+
+    let overlay = build_overlay(app)?;
+    configure_overlay(app, &overlay);
+    tracing::info!("Recreated missing overlay window");
+    Ok(overlay)
+}
+
+/// Same definition as the overlay in tauri.conf.json
+#[cfg(desktop)]
+fn build_overlay(app: &AppHandle) -> Result<WebviewWindow, String> {
+    let builder = tauri::WebviewWindowBuilder::new(
+        app,
+        "overlay",
+        tauri::WebviewUrl::App("overlay.html".into()),
+    )
+    .title("Queen Mama Overlay")
+    .inner_size(OVERLAY_COLLAPSED_WIDTH as f64, OVERLAY_COLLAPSED_HEIGHT as f64)
+    .min_inner_size(380.0, 52.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .visible(false);
+
+    // Transparent windows need the private API on macOS
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+
+    builder.build().map_err(|e| e.to_string())
+}
+
+#[cfg(not(desktop))]
+fn build_overlay(_app: &AppHandle) -> Result<WebviewWindow, String> {
+    Err(t!("error.overlay_missing"))
+}
+
+/// Size, place and layer the overlay from the saved settings
+fn configure_overlay(app: &AppHandle, overlay: &WebviewWindow) {
+    // Come back where the user left the overlay, if that display is still connected
+    let placement = load_placement(app).filter(|placement| placement_fits(app, placement));
+    if let Some(placement) = &placement {
+        app.state::<OverlayState>()
+            .expanded
+            .store(placement.expanded, Ordering::SeqCst);
+    }
+
+    // Set initial size using logical pixels (HiDPI aware)
+    if let Err(e) = apply_size(app, overlay) {
+        tracing::error!("Failed to size overlay: {}", e);
+    }
+
+    if let Some(placement) = placement {
+        let _ = overlay.set_position(PhysicalPosition::new(placement.x, placement.y));
+    } else if let Ok(Some(monitor)) = overlay.current_monitor() {
+        // Position in top-right corner with some padding
+        let collapsed = clamped_geometry(app, overlay).collapsed;
+        let screen_size = monitor.size();
+        let scale_factor = monitor.scale_factor();
+        let logical_width = screen_size.width as f64 / scale_factor;
+        let x = logical_width - collapsed.width - 20.0;
+        let y = 100.0; // Top padding
+        let _ = overlay.set_position(LogicalPosition::new(x, y));
+    }
+
+    // Keep always on top
+    #[cfg(desktop)]
+    let _ = overlay.set_always_on_top(true);
+
+    #[cfg(desktop)]
+    if let Err(e) = apply_all_workspaces(app) {
+        tracing::error!("Failed to set workspace visibility: {}", e);
+    }
+
+    if let Err(e) = apply_content_protection(app) {
+        tracing::error!("Failed to set content protection: {}", e);
+    }
+
+    #[cfg(desktop)]
+    apply_vibrancy(app);
+}
+
+/// Blur the overlay's background, unless the OS asks for reduced transparency
+#[cfg(desktop)]
+pub fn apply_vibrancy(app: &AppHandle) {
+    let Some(overlay) = app.get_webview_window("overlay") else {
+        return;
+    };
+
+    let effects = if accessibility::prefs(app).reduced_transparency {
+        None
+    } else {
+        #[cfg(target_os = "macos")]
+        let effect = tauri::window::Effect::HudWindow;
+        #[cfg(not(target_os = "macos"))]
+        let effect = tauri::window::Effect::Acrylic;
+        Some(tauri::window::EffectsBuilder::new().effect(effect).build())
+    };
+    if let Err(e) = overlay.set_effects(effects) {
+        tracing::error!("Failed to set overlay vibrancy: {}", e);
+    }
+}
+
 /// Toggle overlay visibility
 #[tauri::command]
 pub async fn toggle_overlay(app: tauri::AppHandle) -> Result<bool, String> {
-    if let Some(overlay) = app.get_webview_window("overlay") {
-        let is_visible = overlay.is_visible().map_err(|e| e.to_string())?;
-
-        if is_visible {
-            overlay.hide().map_err(|e| e.to_string())?;
-        } else {
-            overlay.show().map_err(|e| e.to_string())?;
-            overlay.set_focus().map_err(|e| e.to_string())?;
-        }
+    let overlay = overlay_window(&app)?;
+    let is_visible = overlay.is_visible().map_err(|e| e.to_string())?;
 
-        Ok(!is_visible)
+    if is_visible {
+        overlay.hide().map_err(|e| e.to_string())?;
     } else {
-        Err("Overlay window not found".to_string())
+        #[cfg(desktop)]
+        follow_cursor_monitor(&app)?;
+        overlay.show().map_err(|e| e.to_string())?;
+        overlay.set_focus().map_err(|e| e.to_string())?;
     }
+
+    Ok(!is_visible)
+}
+
+/// Start moving the overlay with the mouse; it snaps to the nearest preset when dropped near an edge
+#[tauri::command]
+pub fn begin_overlay_drag(app: AppHandle) -> Result<(), String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+
+    app.state::<OverlayState>()
+        .dragging
+        .store(true, Ordering::SeqCst);
+    overlay.start_dragging().map_err(|e| e.to_string())
 }
 
-/// Set overlay expanded state
+/// Set overlay expanded state, animating to the saved size for that mode.
+/// A `size` replaces the saved size for the mode before animating to it.
 #[tauri::command]
-pub async fn set_overlay_expanded(app: tauri::AppHandle, expanded: bool) -> Result<(), String> {
+pub async fn set_overlay_expanded(
+    app: tauri::AppHandle,
+    expanded: bool,
+    size: Option<OverlaySize>,
+) -> Result<(), String> {
     if let Some(overlay) = app.get_webview_window("overlay") {
-        let (width, height) = if expanded {
-            (OVERLAY_EXPANDED_WIDTH as f64, OVERLAY_EXPANDED_HEIGHT as f64)
-        } else {
-            (OVERLAY_COLLAPSED_WIDTH as f64, OVERLAY_COLLAPSED_HEIGHT as f64)
-        };
+        if let Some(size) = size {
+            let mut geometry = load_geometry(&app);
+            if expanded {
+                geometry.expanded = size;
+            } else {
+                geometry.collapsed = size;
+            }
+            save_geometry(&app, &geometry)?;
+        }
 
-        overlay
-            .set_size(LogicalSize::new(width, height))
-            .map_err(|e| e.to_string())?;
+        // Flip the flag first so the resize below isn't mistaken for a user resize
+        app.state::<OverlayState>()
+            .expanded
+            .store(expanded, Ordering::SeqCst);
+
+        // Only the expanded overlay can be resized by dragging its edges
+        #[cfg(desktop)]
+        overlay.set_resizable(expanded).map_err(|e| e.to_string())?;
+
+        let size = target_size(&app, &overlay);
+        let size = LogicalSize::new(size.width, size.height);
+        let position = on_screen_position(&overlay, size)?;
+        animate(&app, &overlay, Some(size), position).await?;
+        if let Err(e) = save_placement(&app) {
+            tracing::error!("Failed to save overlay state: {}", e);
+        }
 
         // Emit event to frontend
         app.emit("overlay_expanded_changed", expanded)
@@ -74,78 +413,952 @@ pub async fn set_overlay_expanded(app: tauri::AppHandle, expanded: bool) -> Resu
 
         Ok(())
     } else {
-        Err("Overlay window not found".to_string())
+        Err(t!("error.overlay_missing"))
     }
 }
 
-/// Move overlay to a specific position
+/// Get the overlay sizes, clamped to the current monitor
 #[tauri::command]
-pub async fn move_overlay(app: tauri::AppHandle, position: OverlayPosition) -> Result<(), String> {
-    if let Some(overlay) = app.get_webview_window("overlay") {
-        let monitor = overlay.current_monitor()
-            .map_err(|e| e.to_string())?
-            .ok_or("No monitor found")?;
+pub fn get_overlay_geometry(app: AppHandle) -> Result<OverlayGeometryInfo, String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
 
-        let screen_size = monitor.size();
-        let scale_factor = monitor.scale_factor();
-        let logical_screen_width = screen_size.width as f64 / scale_factor;
-        let logical_screen_height = screen_size.height as f64 / scale_factor;
+    Ok(OverlayGeometryInfo {
+        geometry: clamped_geometry(&app, &overlay),
+        is_expanded: app.state::<OverlayState>().expanded.load(Ordering::SeqCst),
+    })
+}
+
+/// Set the collapsed and/or expanded overlay size and persist it
+#[tauri::command]
+pub fn set_overlay_geometry(
+    app: AppHandle,
+    collapsed: Option<OverlaySize>,
+    expanded: Option<OverlaySize>,
+) -> Result<OverlayGeometryInfo, String> {
+    let mut geometry = load_geometry(&app);
+    if let Some(collapsed) = collapsed {
+        geometry.collapsed = collapsed;
+    }
+    if let Some(expanded) = expanded {
+        geometry.expanded = expanded;
+    }
+    save_geometry(&app, &geometry)?;
 
-        let window_size = overlay.outer_size().map_err(|e| e.to_string())?;
-        let logical_window_width = window_size.width as f64 / scale_factor;
-        let logical_window_height = window_size.height as f64 / scale_factor;
+    reload(&app)?;
+    get_overlay_geometry(app)
+}
 
-        let padding = 20.0;
-        let menu_bar_height = 60.0; // Account for macOS menu bar
+/// Show the overlay on every Space / virtual desktop, so it follows the user between desktops
+#[cfg(desktop)]
+#[tauri::command]
+pub fn set_visible_on_all_workspaces(app: AppHandle, visible: bool) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(OVERLAY_ALL_WORKSPACES_STORE_KEY, visible);
+    store.save().map_err(|e| e.to_string())?;
 
-        let (x, y) = match position {
-            OverlayPosition::TopLeft => (padding, padding + menu_bar_height),
-            OverlayPosition::TopCenter => {
-                ((logical_screen_width - logical_window_width) / 2.0, padding + menu_bar_height)
-            }
-            OverlayPosition::TopRight => {
-                (logical_screen_width - logical_window_width - padding, padding + menu_bar_height)
-            }
-            OverlayPosition::BottomLeft => {
-                (padding, logical_screen_height - logical_window_height - padding)
+    apply_all_workspaces(&app)
+}
+
+/// Apply the saved workspace visibility to the overlay
+#[cfg(desktop)]
+pub fn apply_all_workspaces(app: &AppHandle) -> Result<(), String> {
+    let visible = app
+        .store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(OVERLAY_ALL_WORKSPACES_STORE_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    overlay
+        .set_visible_on_all_workspaces(visible)
+        .map_err(|e| e.to_string())
+}
+
+/// List connected displays, in the order `move_overlay_to_monitor` indexes them
+#[tauri::command]
+pub fn list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let primary = app.primary_monitor().map_err(|e| e.to_string())?;
+    let current = match app.get_webview_window("overlay") {
+        Some(overlay) => overlay.current_monitor().map_err(|e| e.to_string())?,
+        None => None,
+    };
+    let same = |a: &Monitor, b: &Option<Monitor>| {
+        b.as_ref().is_some_and(|b| a.position() == b.position() && a.size() == b.size())
+    };
+
+    Ok(app
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .enumerate()
+        .map(|(index, monitor)| {
+            let scale_factor = monitor.scale_factor();
+            let position = monitor.position().to_logical::<f64>(scale_factor);
+            let size = monitor.size().to_logical::<f64>(scale_factor);
+            MonitorInfo {
+                index,
+                name: monitor.name().cloned(),
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                scale_factor,
+                is_primary: same(&monitor, &primary),
+                is_current: same(&monitor, &current),
             }
-            OverlayPosition::BottomCenter => {
-                (
-                    (logical_screen_width - logical_window_width) / 2.0,
-                    logical_screen_height - logical_window_height - padding,
-                )
+        })
+        .collect())
+}
+
+/// Move the overlay to another monitor, at `position` or in the same spot it had on its current one
+#[tauri::command]
+pub fn move_overlay_to_monitor(
+    app: AppHandle,
+    monitor_index: usize,
+    position: Option<OverlayPosition>,
+) -> Result<(), String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    let monitor = app
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .nth(monitor_index)
+        .ok_or_else(|| t!("error.monitor_not_found", id = monitor_index))?;
+
+    let target = match position {
+        Some(position) => position_on_monitor(&overlay, &monitor, &position)?,
+        None => carried_position(&overlay, &monitor)?,
+    };
+    overlay.set_position(target).map_err(|e| e.to_string())
+}
+
+/// Keep the overlay on whichever monitor the mouse is on
+#[cfg(desktop)]
+#[tauri::command]
+pub fn set_overlay_follow_monitor(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(OVERLAY_FOLLOW_MONITOR_STORE_KEY, enabled);
+    store.save().map_err(|e| e.to_string())?;
+
+    follow_cursor_monitor(&app)
+}
+
+/// Bring the overlay to the monitor under the mouse, if following is on
+#[cfg(desktop)]
+pub fn follow_cursor_monitor(app: &AppHandle) -> Result<(), String> {
+    let enabled = app
+        .store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(OVERLAY_FOLLOW_MONITOR_STORE_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    // A docked overlay stays with its window
+    if !enabled || crate::attach::is_attached(app) {
+        return Ok(());
+    }
+
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    let cursor = app.cursor_position().map_err(|e| e.to_string())?;
+    let Some(target) = app
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            cursor.x >= position.x as f64
+                && cursor.x < position.x as f64 + size.width as f64
+                && cursor.y >= position.y as f64
+                && cursor.y < position.y as f64 + size.height as f64
+        })
+    else {
+        return Ok(());
+    };
+
+    let current = overlay.current_monitor().map_err(|e| e.to_string())?;
+    if current.is_some_and(|current| current.position() == target.position()) {
+        return Ok(());
+    }
+
+    let position = carried_position(&overlay, &target)?;
+    overlay.set_position(position).map_err(|e| e.to_string())
+}
+
+/// Get the rules for hiding the overlay over fullscreen or blocklisted apps
+#[cfg(desktop)]
+#[tauri::command]
+pub fn get_autohide_rules(app: AppHandle) -> AutoHideRules {
+    load_autohide_rules(&app)
+}
+
+/// Set the rules for hiding the overlay over fullscreen or blocklisted apps
+#[cfg(desktop)]
+#[tauri::command]
+pub fn set_autohide_rules(app: AppHandle, rules: AutoHideRules) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        OVERLAY_AUTOHIDE_STORE_KEY,
+        serde_json::to_value(&rules).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+#[cfg(desktop)]
+fn load_autohide_rules(app: &AppHandle) -> AutoHideRules {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(OVERLAY_AUTOHIDE_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Hide the overlay while the frontmost app matches the rules or a focus mode asks for it,
+/// and bring it back afterwards
+#[cfg(desktop)]
+fn check_autohide(app: &AppHandle) -> Result<(), String> {
+    let rules = load_autohide_rules(app);
+    let state = app.state::<OverlayState>();
+    let auto_hidden = state.auto_hidden.load(Ordering::SeqCst);
+    let focus = focus::hides_overlay(app);
+    if !rules.fullscreen && rules.blocklist.is_empty() && !focus && !auto_hidden {
+        return Ok(());
+    }
+
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+
+    let frontmost = xcap::Window::all()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|window| window.is_focused().unwrap_or(false));
+    // Our own windows taking focus don't change anything
+    if frontmost
+        .as_ref()
+        .is_some_and(|window| window.pid().ok() == Some(std::process::id()))
+    {
+        return Ok(());
+    }
+
+    let app_name = frontmost.as_ref().and_then(|window| window.app_name().ok());
+    let reason = match &frontmost {
+        _ if focus => Some("focus"),
+        Some(window) if rules.fullscreen && is_fullscreen(window) => Some("fullscreen"),
+        _ => app_name
+            .as_ref()
+            .map(|name| name.to_lowercase())
+            .filter(|name| {
+                rules
+                    .blocklist
+                    .iter()
+                    .any(|blocked| !blocked.is_empty() && name.contains(&blocked.to_lowercase()))
+            })
+            .map(|_| "blocklist"),
+    };
+
+    match (reason, auto_hidden) {
+        (Some(reason), false) => {
+            if !overlay.is_visible().map_err(|e| e.to_string())? {
+                return Ok(());
             }
-            OverlayPosition::BottomRight => {
-                (
-                    logical_screen_width - logical_window_width - padding,
-                    logical_screen_height - logical_window_height - padding,
-                )
+            overlay.hide().map_err(|e| e.to_string())?;
+            state.auto_hidden.store(true, Ordering::SeqCst);
+            tracing::info!("Auto-hid overlay ({})", reason);
+            app.emit(
+                "overlay_autohide",
+                AutoHideEvent {
+                    hidden: true,
+                    reason: Some(reason),
+                    app_name,
+                },
+            )
+            .map_err(|e| e.to_string())
+        }
+        (None, true) => {
+            state.auto_hidden.store(false, Ordering::SeqCst);
+            overlay.show().map_err(|e| e.to_string())?;
+            app.emit(
+                "overlay_autohide",
+                AutoHideEvent {
+                    hidden: false,
+                    reason: None,
+                    app_name,
+                },
+            )
+            .map_err(|e| e.to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether a window covers its whole monitor
+#[cfg(desktop)]
+fn is_fullscreen(window: &xcap::Window) -> bool {
+    let (Ok(monitor), Ok(x), Ok(y), Ok(width), Ok(height)) = (
+        window.current_monitor(),
+        window.x(),
+        window.y(),
+        window.width(),
+        window.height(),
+    ) else {
+        return false;
+    };
+    let (Ok(monitor_x), Ok(monitor_y), Ok(monitor_width), Ok(monitor_height)) =
+        (monitor.x(), monitor.y(), monitor.width(), monitor.height())
+    else {
+        return false;
+    };
+
+    x <= monitor_x
+        && y <= monitor_y
+        && x + width as i32 >= monitor_x + monitor_width as i32
+        && y + height as i32 >= monitor_y + monitor_height as i32
+}
+
+/// Hide the overlay from screen sharing and recordings (on by default)
+#[tauri::command]
+pub fn set_content_protection(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(OVERLAY_CONTENT_PROTECTION_STORE_KEY, enabled);
+    store.save().map_err(|e| e.to_string())?;
+
+    apply_content_protection(&app)
+}
+
+/// Whether the overlay is hidden from screen sharing
+#[tauri::command]
+pub fn get_content_protection(app: AppHandle) -> bool {
+    content_protection(&app)
+}
+
+/// Apply the saved content protection to the overlay
+pub fn apply_content_protection(app: &AppHandle) -> Result<(), String> {
+    let enabled = content_protection(app);
+
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    // NSWindow sharingType on macOS, SetWindowDisplayAffinity on Windows
+    overlay
+        .set_content_protected(enabled)
+        .map_err(|e| e.to_string())?;
+
+    app.emit("overlay_content_protection_changed", enabled)
+        .map_err(|e| e.to_string())
+}
+
+fn content_protection(app: &AppHandle) -> bool {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(OVERLAY_CONTENT_PROTECTION_STORE_KEY))
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true)
+}
+
+/// Let clicks pass through the overlay, e.g. to keep it visible while presenting
+#[cfg(desktop)]
+#[tauri::command]
+pub fn set_overlay_click_through(app: AppHandle, enabled: bool) -> Result<(), String> {
+    set_click_through(&app, enabled)
+}
+
+#[cfg(desktop)]
+pub fn set_click_through(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    overlay
+        .set_ignore_cursor_events(enabled)
+        .map_err(|e| e.to_string())?;
+
+    app.state::<OverlayState>()
+        .click_through
+        .store(enabled, Ordering::SeqCst);
+    app.emit("overlay_click_through_changed", enabled)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(desktop)]
+pub fn toggle_click_through(app: &AppHandle) -> Result<(), String> {
+    let enabled = app.state::<OverlayState>().click_through.load(Ordering::SeqCst);
+    set_click_through(app, !enabled)
+}
+
+/// Open or close the caption window showing the last transcript line; returns whether it's open.
+/// Async because building a webview from a sync command deadlocks on Windows.
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn toggle_caption_window(app: AppHandle) -> Result<bool, String> {
+    if let Some(caption) = app.get_webview_window(CAPTION_WINDOW) {
+        caption.close().map_err(|e| e.to_string())?;
+        return Ok(false);
+    }
+
+    let caption = tauri::WebviewWindowBuilder::new(
+        &app,
+        CAPTION_WINDOW,
+        tauri::WebviewUrl::App("caption.html".into()),
+    )
+    .title("Queen Mama Captions")
+    .inner_size(CAPTION_WIDTH, CAPTION_HEIGHT)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .focused(false)
+    .visible(false)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    // Centre along the bottom of the screen the overlay is on
+    let monitor = match app.get_webview_window("overlay") {
+        Some(overlay) => overlay.current_monitor().map_err(|e| e.to_string())?,
+        None => None,
+    };
+    let monitor = match monitor {
+        Some(monitor) => Some(monitor),
+        None => app.primary_monitor().map_err(|e| e.to_string())?,
+    };
+    if let Some(monitor) = monitor {
+        let scale_factor = monitor.scale_factor();
+        let origin = monitor.position().to_logical::<f64>(scale_factor);
+        let size = monitor.size().to_logical::<f64>(scale_factor);
+        caption
+            .set_position(LogicalPosition::new(
+                origin.x + (size.width - CAPTION_WIDTH) / 2.0,
+                origin.y + size.height - CAPTION_HEIGHT - CAPTION_BOTTOM_MARGIN,
+            ))
+            .map_err(|e| e.to_string())?;
+    }
+
+    caption.show().map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Resize the overlay to the saved size for its current mode
+pub fn reload(app: &AppHandle) -> Result<(), String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    apply_size(app, &overlay)
+}
+
+/// Remember the overlay's position and expanded size as the user moves and resizes it
+pub fn handle_window_event(window: &tauri::Window, event: &WindowEvent) {
+    if window.label() != "overlay" {
+        return;
+    }
+    match event {
+        WindowEvent::Resized(size) => remember_size(window, *size),
+        WindowEvent::Moved(_) => {
+            snap_after_drag(window);
+            remember_placement(window);
+        }
+        _ => {}
+    }
+}
+
+/// Once a drag settles, snap the overlay to the closest preset if it was dropped near an edge
+fn snap_after_drag(window: &tauri::Window) {
+    let app = window.app_handle().clone();
+    let state = app.state::<OverlayState>();
+    if !state.dragging.load(Ordering::SeqCst) {
+        return;
+    }
+    let generation = state.drag_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SNAP_DELAY).await;
+        let state = app.state::<OverlayState>();
+        if state.drag_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        state.dragging.store(false, Ordering::SeqCst);
+
+        let position = match snap_position(&app) {
+            Ok(Some(position)) => position,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!("Failed to snap overlay: {}", e);
+                return;
             }
         };
+        if let Err(e) = move_overlay(app.clone(), position).await {
+            tracing::error!("Failed to snap overlay: {}", e);
+            return;
+        }
+        let _ = app.emit("overlay_snapped", position);
+    });
+}
 
-        overlay
-            .set_position(LogicalPosition::new(x, y))
-            .map_err(|e| e.to_string())?;
+/// The preset the overlay should snap to, if it's within `SNAP_DISTANCE` of a screen edge
+fn snap_position(app: &AppHandle) -> Result<Option<OverlayPosition>, String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    let Some(monitor) = overlay.current_monitor().map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+
+    let scale_factor = monitor.scale_factor();
+    let screen_origin = monitor.position().to_logical::<f64>(scale_factor);
+    let screen = monitor.size().to_logical::<f64>(scale_factor);
+    let position = overlay
+        .outer_position()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale_factor);
+    let size = overlay
+        .outer_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale_factor);
+
+    let left = position.x - screen_origin.x;
+    let top = position.y - screen_origin.y;
+    let right = screen.width - left - size.width;
+    let bottom = screen.height - top - size.height;
+    if left.min(top).min(right).min(bottom) > SNAP_DISTANCE {
+        return Ok(None);
+    }
+
+    let center_x = left + size.width / 2.0;
+    let top_half = top + size.height / 2.0 < screen.height / 2.0;
+    Ok(Some(match (center_x < screen.width / 3.0, center_x > screen.width * 2.0 / 3.0, top_half) {
+        (true, _, true) => OverlayPosition::TopLeft,
+        (_, true, true) => OverlayPosition::TopRight,
+        (_, _, true) => OverlayPosition::TopCenter,
+        (true, _, false) => OverlayPosition::BottomLeft,
+        (_, true, false) => OverlayPosition::BottomRight,
+        (_, _, false) => OverlayPosition::BottomCenter,
+    }))
+}
+
+fn remember_placement(window: &tauri::Window) {
+    let app = window.app_handle().clone();
+    let generation = app
+        .state::<OverlayState>()
+        .move_generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RESIZE_SAVE_DELAY).await;
+        if app.state::<OverlayState>().move_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Err(e) = save_placement(&app) {
+            tracing::error!("Failed to save overlay position: {}", e);
+        }
+    });
+}
+
+fn remember_size(window: &tauri::Window, size: tauri::PhysicalSize<u32>) {
+    let app = window.app_handle().clone();
+    let state = app.state::<OverlayState>();
+    if !state.expanded.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let scale_factor = window.scale_factor().unwrap_or(1.0);
+    let size = size.to_logical::<f64>(scale_factor);
+    let generation = state.resize_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RESIZE_SAVE_DELAY).await;
+        let state = app.state::<OverlayState>();
+        if state.resize_generation.load(Ordering::SeqCst) != generation
+            || !state.expanded.load(Ordering::SeqCst)
+        {
+            return;
+        }
+
+        let mut geometry = load_geometry(&app);
+        geometry.expanded = OverlaySize {
+            width: size.width,
+            height: size.height,
+        };
+        if let Err(e) = save_geometry(&app, &geometry) {
+            tracing::error!("Failed to save overlay size: {}", e);
+        }
+    });
+}
+
+fn apply_size(app: &AppHandle, overlay: &WebviewWindow) -> Result<(), String> {
+    #[cfg(desktop)]
+    overlay
+        .set_resizable(app.state::<OverlayState>().expanded.load(Ordering::SeqCst))
+        .map_err(|e| e.to_string())?;
+
+    let size = target_size(app, overlay);
+    overlay
+        .set_size(LogicalSize::new(size.width, size.height))
+        .map_err(|e| e.to_string())
+}
+
+/// Saved size for the overlay's current mode
+fn target_size(app: &AppHandle, overlay: &WebviewWindow) -> OverlaySize {
+    let geometry = clamped_geometry(app, overlay);
+    if app.state::<OverlayState>().expanded.load(Ordering::SeqCst) {
+        geometry.expanded
+    } else {
+        geometry.collapsed
+    }
+}
+
+/// Where the overlay has to move so it stays on screen at a new size, if it has to move at all
+fn on_screen_position(
+    overlay: &WebviewWindow,
+    size: LogicalSize<f64>,
+) -> Result<Option<LogicalPosition<f64>>, String> {
+    let Some(monitor) = overlay.current_monitor().map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+
+    let scale_factor = overlay.scale_factor().map_err(|e| e.to_string())?;
+    let origin = monitor.position().to_logical::<f64>(scale_factor);
+    let screen = monitor.size().to_logical::<f64>(scale_factor);
+    let position = overlay
+        .outer_position()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(scale_factor);
+
+    // Grow up/left from the screen's bottom/right edge rather than off it
+    let x = position.x.min(origin.x + screen.width - size.width - OVERLAY_SCREEN_MARGIN);
+    let y = position.y.min(origin.y + screen.height - size.height - OVERLAY_SCREEN_MARGIN);
+    if x == position.x && y == position.y {
+        return Ok(None);
+    }
+    Ok(Some(LogicalPosition::new(x.max(origin.x), y.max(origin.y))))
+}
+
+/// Ease the overlay to a new size and/or position instead of jumping there
+async fn animate(
+    app: &AppHandle,
+    overlay: &WebviewWindow,
+    size: Option<LogicalSize<f64>>,
+    position: Option<LogicalPosition<f64>>,
+) -> Result<(), String> {
+    let state = app.state::<OverlayState>();
+    let generation = state.animation_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let scale_factor = overlay.scale_factor().map_err(|e| e.to_string())?;
+    let from_size: LogicalSize<f64> = overlay
+        .inner_size()
+        .map_err(|e| e.to_string())?
+        .to_logical(scale_factor);
+    let from_position: LogicalPosition<f64> = overlay
+        .outer_position()
+        .map_err(|e| e.to_string())?
+        .to_logical(scale_factor);
+
+    let started = Instant::now();
+    loop {
+        let t = (started.elapsed().as_secs_f64() / ANIMATION_DURATION.as_secs_f64()).min(1.0);
+        // Ease-out cubic: fast start, gentle landing
+        let eased = 1.0 - (1.0 - t).powi(3);
+        let lerp = |from: f64, to: f64| from + (to - from) * eased;
+
+        if let Some(size) = size {
+            overlay
+                .set_size(LogicalSize::new(
+                    lerp(from_size.width, size.width),
+                    lerp(from_size.height, size.height),
+                ))
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(position) = position {
+            overlay
+                .set_position(LogicalPosition::new(
+                    lerp(from_position.x, position.x),
+                    lerp(from_position.y, position.y),
+                ))
+                .map_err(|e| e.to_string())?;
+        }
+
+        if t >= 1.0 {
+            return Ok(());
+        }
+        tokio::time::sleep(ANIMATION_FRAME).await;
+        if state.animation_generation.load(Ordering::SeqCst) != generation {
+            return Ok(());
+        }
+    }
+}
+
+fn load_placement(app: &AppHandle) -> Option<OverlayPlacement> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(OVERLAY_PLACEMENT_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+fn save_placement(app: &AppHandle) -> Result<(), String> {
+    let overlay = app
+        .get_webview_window("overlay")
+        .ok_or_else(|| t!("error.overlay_missing"))?;
+    let position = overlay.outer_position().map_err(|e| e.to_string())?;
+    let monitor = overlay
+        .current_monitor()
+        .map_err(|e| e.to_string())?
+        .and_then(|monitor| monitor.name().cloned());
+
+    let placement = OverlayPlacement {
+        x: position.x,
+        y: position.y,
+        monitor,
+        expanded: app.state::<OverlayState>().expanded.load(Ordering::SeqCst),
+    };
+
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        OVERLAY_PLACEMENT_STORE_KEY,
+        serde_json::to_value(&placement).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Whether a saved position is still on screen, on the same monitor it was saved on
+fn placement_fits(app: &AppHandle, placement: &OverlayPlacement) -> bool {
+    let Ok(monitors) = app.available_monitors() else {
+        return false;
+    };
+
+    monitors.iter().any(|monitor| {
+        let position = monitor.position();
+        let size = monitor.size();
+        let inside = placement.x >= position.x
+            && placement.x < position.x + size.width as i32
+            && placement.y >= position.y
+            && placement.y < position.y + size.height as i32;
+        inside && (placement.monitor.is_none() || placement.monitor.as_ref() == monitor.name())
+    })
+}
+
+fn load_geometry(app: &AppHandle) -> OverlayGeometry {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(OVERLAY_GEOMETRY_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save_geometry(app: &AppHandle, geometry: &OverlayGeometry) -> Result<(), String> {
+    let store = app.store(SETTINGS_STORE).map_err(|e| e.to_string())?;
+    store.set(
+        OVERLAY_GEOMETRY_STORE_KEY,
+        serde_json::to_value(geometry).map_err(|e| e.to_string())?,
+    );
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Saved geometry limited to sizes that fit on the overlay's monitor
+fn clamped_geometry(app: &AppHandle, overlay: &WebviewWindow) -> OverlayGeometry {
+    let (max_width, max_height) = match overlay.current_monitor() {
+        Ok(Some(monitor)) => {
+            let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+            (
+                size.width - OVERLAY_SCREEN_MARGIN * 2.0,
+                size.height - OVERLAY_SCREEN_MARGIN * 2.0,
+            )
+        }
+        _ => (f64::MAX, f64::MAX),
+    };
+
+    let clamp = |size: OverlaySize| OverlaySize {
+        width: size.width.min(max_width).max(OVERLAY_MIN_WIDTH),
+        height: size.height.min(max_height).max(OVERLAY_MIN_HEIGHT),
+    };
+
+    let geometry = load_geometry(app);
+    OverlayGeometry {
+        collapsed: clamp(geometry.collapsed),
+        expanded: clamp(geometry.expanded),
+    }
+}
+
+/// Move overlay to a specific position
+#[tauri::command]
+pub async fn move_overlay(app: tauri::AppHandle, position: OverlayPosition) -> Result<(), String> {
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        let monitor = overlay.current_monitor()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| t!("error.no_monitor"))?;
+
+        let scale_factor = overlay.scale_factor().map_err(|e| e.to_string())?;
+        let target = position_on_monitor(&overlay, &monitor, &position)?.to_logical(scale_factor);
+        animate(&app, &overlay, None, Some(target)).await?;
 
         Ok(())
     } else {
-        Err("Overlay window not found".to_string())
+        Err(t!("error.overlay_missing"))
     }
 }
 
+/// Where a preset position lands on a monitor, in physical pixels
+fn position_on_monitor(
+    overlay: &WebviewWindow,
+    monitor: &Monitor,
+    position: &OverlayPosition,
+) -> Result<PhysicalPosition<f64>, String> {
+    let scale_factor = monitor.scale_factor();
+    let logical_screen_width = monitor.size().width as f64 / scale_factor;
+    let logical_screen_height = monitor.size().height as f64 / scale_factor;
+
+    let window_size = overlay
+        .outer_size()
+        .map_err(|e| e.to_string())?
+        .to_logical::<f64>(overlay.scale_factor().map_err(|e| e.to_string())?);
+    let logical_window_width = window_size.width;
+    let logical_window_height = window_size.height;
+
+    let padding = 20.0;
+    let menu_bar_height = 60.0; // Account for macOS menu bar
+
+    let (x, y) = match position {
+        OverlayPosition::TopLeft => (padding, padding + menu_bar_height),
+        OverlayPosition::TopCenter => {
+            ((logical_screen_width - logical_window_width) / 2.0, padding + menu_bar_height)
+        }
+        OverlayPosition::TopRight => {
+            (logical_screen_width - logical_window_width - padding, padding + menu_bar_height)
+        }
+        OverlayPosition::BottomLeft => {
+            (padding, logical_screen_height - logical_window_height - padding)
+        }
+        OverlayPosition::BottomCenter => {
+            (
+                (logical_screen_width - logical_window_width) / 2.0,
+                logical_screen_height - logical_window_height - padding,
+            )
+        }
+        OverlayPosition::BottomRight => {
+            (
+                logical_screen_width - logical_window_width - padding,
+                logical_screen_height - logical_window_height - padding,
+            )
+        }
+    };
+
+    // Offset from the monitor's own origin so secondary displays work
+    let origin = monitor.position();
+    Ok(PhysicalPosition::new(
+        origin.x as f64 + x * scale_factor,
+        origin.y as f64 + y * scale_factor,
+    ))
+}
+
+/// The overlay's position on another monitor, in the same relative spot as on its current one
+fn carried_position(overlay: &WebviewWindow, target: &Monitor) -> Result<PhysicalPosition<f64>, String> {
+    let Some(current) = overlay.current_monitor().map_err(|e| e.to_string())? else {
+        return position_on_monitor(overlay, target, &OverlayPosition::TopRight);
+    };
+    let size = overlay.outer_size().map_err(|e| e.to_string())?;
+    let position = overlay.outer_position().map_err(|e| e.to_string())?;
+
+    // Share of the free space to the left of / above the overlay
+    let fraction = |offset: i32, screen: u32, window: u32| {
+        (offset as f64 / screen.saturating_sub(window).max(1) as f64).clamp(0.0, 1.0)
+    };
+    let x = fraction(position.x - current.position().x, current.size().width, size.width);
+    let y = fraction(position.y - current.position().y, current.size().height, size.height);
+
+    // The overlay's physical size changes with the target's scale factor
+    let ratio = target.scale_factor() / current.scale_factor();
+    let free_width = (target.size().width as f64 - size.width as f64 * ratio).max(0.0);
+    let free_height = (target.size().height as f64 - size.height as f64 * ratio).max(0.0);
+
+    Ok(PhysicalPosition::new(
+        target.position().x as f64 + x * free_width,
+        target.position().y as f64 + y * free_height,
+    ))
+}
+
 /// Show main dashboard window
 #[tauri::command]
 pub async fn show_main_window(app: tauri::AppHandle) -> Result<(), String> {
-    if let Some(main) = app.get_webview_window("main") {
-        main.show().map_err(|e| e.to_string())?;
-        main.set_focus().map_err(|e| e.to_string())?;
-        Ok(())
-    } else {
-        Err("Main window not found".to_string())
+    show_window(app, "main".to_string()).await
+}
+
+/// Show and focus any window by label
+#[tauri::command]
+pub async fn show_window(app: AppHandle, label: String) -> Result<(), String> {
+    let window = window_by_label(&app, &label)?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Show and focus the dashboard
+pub fn show_main(app: &AppHandle) -> Result<(), String> {
+    let main = window_by_label(app, "main")?;
+    main.show().map_err(|e| e.to_string())?;
+    main.set_focus().map_err(|e| e.to_string())
+}
+
+/// Bring up the dashboard and ask it to show a session
+pub fn open_session(app: &AppHandle, session_id: &str) -> Result<(), String> {
+    show_main(app)?;
+    app.emit("open_session", session_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Hide any window by label
+#[tauri::command]
+pub fn hide_window(app: AppHandle, label: String) -> Result<(), String> {
+    window_by_label(&app, &label)?
+        .hide()
+        .map_err(|e| e.to_string())
+}
+
+/// Visibility, focus and bounds of every open window
+#[tauri::command]
+pub fn get_window_states(app: AppHandle) -> Result<Vec<WindowStateInfo>, String> {
+    let mut windows: Vec<_> = app.webview_windows().into_values().collect();
+    windows.sort_by(|a, b| a.label().cmp(b.label()));
+
+    windows
+        .iter()
+        .map(|window| {
+            let scale_factor = window.scale_factor().map_err(|e| e.to_string())?;
+            let position = window
+                .outer_position()
+                .map_err(|e| e.to_string())?
+                .to_logical::<f64>(scale_factor);
+            let size = window
+                .outer_size()
+                .map_err(|e| e.to_string())?
+                .to_logical::<f64>(scale_factor);
+
+            Ok(WindowStateInfo {
+                label: window.label().to_string(),
+                visible: window.is_visible().map_err(|e| e.to_string())?,
+                focused: window.is_focused().map_err(|e| e.to_string())?,
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            })
+        })
+        .collect()
+}
+
+/// Look up a window, recreating the overlay if it went missing
+fn window_by_label(app: &AppHandle, label: &str) -> Result<WebviewWindow, String> {
+    if label == "overlay" {
+        return overlay_window(app);
     }
+    app.get_webview_window(label)
+        .ok_or_else(|| t!("error.window_not_found", id = label))
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum OverlayPosition {
     TopLeft,
@@ -42,7 +42,7 @@ export function PillHeader({
   // Open dashboard
   const handleOpenDashboard = async () => {
     try {
-      await invoke('show_main_window');
+      await invoke('show_window', { label: 'main' });
     } catch (error) {
       console.error('[PillHeader] Failed to open dashboard:', error);
     }
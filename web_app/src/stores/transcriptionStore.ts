@@ -2,6 +2,7 @@
 // Manages Deepgram WebSocket connection and transcription state
 
 import { create } from 'zustand';
+import { emit } from '@tauri-apps/api/event';
 import { useSessionStore } from './sessionStore';
 import { useAuthStore } from './authStore';
 
@@ -104,6 +105,9 @@ export const useTranscriptionStore = create<TranscriptionStore>((set, get) => ({
             const isFinal = data.is_final;
 
             if (transcript) {
+              // Mirror to the caption window, if open
+              emit('caption_line', { text: transcript, isFinal }).catch(() => {});
+
               if (isFinal) {
                 // Final result - add to session
                 useSessionStore.getState().addTranscript(transcript, true);
@@ -0,0 +1,49 @@
+// Queen Mama LITE - Caption Window Entry Point
+// Shows only the latest transcript line, like live captions
+
+import React, { useEffect, useState } from 'react';
+import ReactDOM from 'react-dom/client';
+import { listen } from '@tauri-apps/api/event';
+import '../styles/globals.css';
+
+interface CaptionLine {
+  text: string;
+  isFinal: boolean;
+}
+
+function CaptionApp() {
+  const [line, setLine] = useState('');
+
+  useEffect(() => {
+    const onLine = (caption: CaptionLine) => {
+      if (caption.text.trim()) {
+        setLine(caption.text.trim());
+      }
+    };
+
+    // Local Whisper segments come from Rust, cloud transcription from the other windows
+    const unlisteners = Promise.all([
+      listen<CaptionLine>('transcript_segment', (event) => onLine(event.payload)),
+      listen<CaptionLine>('caption_line', (event) => onLine(event.payload)),
+    ]);
+
+    return () => {
+      unlisteners.then((fns) => fns.forEach((unlisten) => unlisten()));
+    };
+  }, []);
+
+  return (
+    <div
+      data-tauri-drag-region
+      className="glass flex items-center justify-center h-screen px-4 rounded-xl"
+    >
+      <p className="text-white text-base truncate">{line || '…'}</p>
+    </div>
+  );
+}
+
+ReactDOM.createRoot(document.getElementById('root')!).render(
+  <React.StrictMode>
+    <CaptionApp />
+  </React.StrictMode>
+);